@@ -26,6 +26,7 @@ typedef struct ParseOptions {
   bool css_modules;
   const char *css_modules_pattern;
   bool css_modules_dashed_idents;
+  bool css_modules_paint_worklets;
   bool error_recovery;
 } ParseOptions;
 
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{CStr, CString};
 use std::mem::ManuallyDrop;
 use std::os::raw::c_char;
@@ -78,6 +78,7 @@ pub struct ParseOptions {
   css_modules: bool,
   css_modules_pattern: *const c_char,
   css_modules_dashed_idents: bool,
+  css_modules_paint_worklets: bool,
   error_recovery: bool,
 }
 
@@ -185,6 +186,40 @@ impl Into<MinifyOptions> for TransformOptions {
         None
       },
       unused_symbols,
+      purge: None,
+      remove_unsupported_has: false,
+      normalize_attribute_selectors: false,
+      expand_is_selectors: false,
+      preserve_empty_rules: false,
+      strict_namespaces: false,
+      remove_shadowed_declarations: false,
+      merge_media_rules: false,
+      merge_non_adjacent_rules: false,
+      unsafe_merge_non_adjacent_rules: false,
+      merge_same_selector_rules: false,
+      flatten_anonymous_layers: false,
+      mangle_idents: false,
+      sort_declarations: None,
+      remove_obsolete_prefixes: false,
+      remove_legacy_hacks: false,
+      remove_unneeded_important: false,
+      optimization_level: Default::default(),
+      media_environment: None,
+      environment_variables: HashMap::new(),
+      replace_typed_attr: false,
+      legacy_high_contrast: false,
+      image_set_fallback: false,
+      remove_unused_font_faces: false,
+      remove_unused_keyframes: false,
+      merge_duplicate_keyframes: false,
+      remove_unused_transitions: false,
+      preserved_keyframes: HashSet::new(),
+      remove_unused_custom_properties: false,
+      preserved_custom_properties: HashSet::new(),
+      inline_custom_properties: false,
+      dynamic_custom_properties: HashSet::new(),
+      color_fallback_strategy: Default::default(),
+      logical_property_strategy: Default::default(),
     }
   }
 }
@@ -278,6 +313,7 @@ pub extern "C" fn lightningcss_stylesheet_parse(
       Some(lightningcss::css_modules::Config {
         pattern,
         dashed_idents: options.css_modules_dashed_idents,
+        paint_worklets: options.css_modules_paint_worklets,
       })
     } else {
       None
@@ -285,6 +321,10 @@ pub extern "C" fn lightningcss_stylesheet_parse(
     error_recovery: options.error_recovery,
     source_index: 0,
     warnings: Some(warnings.clone()),
+    local_global: Default::default(),
+    preserve_comments: false,
+    lossless: false,
+    custom_at_rules: Default::default(),
   };
 
   let stylesheet = unwrap!(StyleSheet::parse(code, opts), error, std::ptr::null_mut());
@@ -345,6 +385,13 @@ pub extern "C" fn lightningcss_stylesheet_to_css(
     } else {
       None
     },
+    has_polyfill: None,
+    color_format: Default::default(),
+    light_dark_mode: Default::default(),
+    gamut_mapping: Default::default(),
+    nesting_mode: Default::default(),
+    size_report: false,
+    shape_precision: None,
   };
 
   let res = unwrap!(wrapper.stylesheet.to_css(opts), error, ToCssResult::default());
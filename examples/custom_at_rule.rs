@@ -206,7 +206,11 @@ impl<'a, 'i> Visitor<'i, AtRule> for ApplyVisitor<'a, 'i> {
           source_index: 0,
           line: apply.loc.line,
           column: apply.loc.column,
+          #[cfg(feature = "spans")]
+          span: apply.loc.span,
         },
+        #[cfg(feature = "spans")]
+        selector_spans: vec![],
       })
     }
 
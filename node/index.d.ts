@@ -37,12 +37,45 @@ export interface TransformOptions<C extends CustomAtRules> {
    * This is useful for polyfills, for example.
    */
   pseudoClasses?: PseudoClasses,
+  /**
+   * The preferred serialization for colors. Defaults to `"shortest"`.
+   * The `"modern"` format uses `oklch()` when supported by all browser targets.
+   */
+  colorFormat?: 'shortest' | 'hex' | 'rgb' | 'preserve' | 'modern',
+  /**
+   * How `light-dark()` colors are compiled for browser targets that do not support them.
+   * Defaults to `"custom-properties"`, which relies on variables defined alongside `color-scheme`.
+   * The `"media-query"` mode repeats declarations in a `prefers-color-scheme: dark` media query instead.
+   */
+  lightDarkMode?: 'custom-properties' | 'media-query',
+  /**
+   * How colors outside the sRGB gamut, such as sRGB fallbacks for saturated `lab()` or `oklch()` colors,
+   * are mapped into the gamut. Defaults to `"chroma-reduction"`, the CSS Color 4 gamut mapping algorithm.
+   * The `"clip"` algorithm clamps each component instead, which may shift the hue of saturated colors.
+   */
+  gamutMapping?: 'chroma-reduction' | 'clip',
+  /**
+   * How nested style rules are compiled for browser targets that do not support nesting.
+   * Defaults to `"is"`, which replaces `&` with an `:is()` selector containing the parent selectors.
+   * The `"expand"` mode writes a separate selector for each combination of parent selectors instead.
+   */
+  nestingMode?: 'is' | 'expand',
+  /**
+   * The maximum number of decimal places to keep for numbers inside basic shape
+   * functions such as `path()`, `polygon()`, and `inset()`. By default, full precision is kept.
+   */
+  shapePrecision?: number,
   /**
    * A list of class names, ids, and custom identifiers (e.g. @keyframes) that are known
    * to be unused. These will be removed during minification. Note that these are not
    * selectors but individual names (without any . or # prefixes).
    */
   unusedSymbols?: string[],
+  /**
+   * Whether to keep style rules with no declarations during minification, e.g. rules
+   * that only exist so that a class name is exported by CSS modules.
+   */
+  preserveEmptyRules?: boolean,
   /**
    * Whether to ignore invalid rules and declarations rather than erroring.
    * When enabled, warnings are returned, and the invalid rule or declaration is
@@ -286,7 +319,9 @@ export interface CSSModulesConfig {
   /** The pattern to use when renaming class names and other identifiers. Default is `[hash]_[local]`. */
   pattern?: string,
   /** Whether to rename dashed identifiers, e.g. custom properties. */
-  dashedIdents?: boolean
+  dashedIdents?: boolean,
+  /** Whether to rename the names of paint worklets referenced by `paint()` images. */
+  paintWorklets?: boolean
 }
 
 export type CSSModuleExports = {
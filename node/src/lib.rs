@@ -7,6 +7,7 @@ use lightningcss::bundler::{BundleErrorKind, Bundler, FileProvider, SourceProvid
 use lightningcss::css_modules::{CssModuleExports, CssModuleReferences, PatternParseError};
 use lightningcss::dependencies::{Dependency, DependencyOptions};
 use lightningcss::error::{Error, ErrorLocation, MinifyErrorKind, ParserError, PrinterErrorKind};
+use lightningcss::printer::{ColorFormat, GamutMapping, LightDarkMode, NestingMode};
 use lightningcss::stylesheet::{
   MinifyOptions, ParserOptions, PrinterOptions, PseudoClasses, StyleAttribute, StyleSheet,
 };
@@ -499,7 +500,13 @@ struct Config {
   pub css_modules: Option<CssModulesOption>,
   pub analyze_dependencies: Option<AnalyzeDependenciesOption>,
   pub pseudo_classes: Option<OwnedPseudoClasses>,
+  pub color_format: Option<ColorFormat>,
+  pub light_dark_mode: Option<LightDarkMode>,
+  pub gamut_mapping: Option<GamutMapping>,
+  pub nesting_mode: Option<NestingMode>,
+  pub shape_precision: Option<u8>,
   pub unused_symbols: Option<HashSet<String>>,
+  pub preserve_empty_rules: Option<bool>,
   pub error_recovery: Option<bool>,
   pub custom_at_rules: Option<HashMap<String, CustomAtRuleConfig>>,
 }
@@ -529,6 +536,7 @@ enum CssModulesOption {
 struct CssModulesConfig {
   pattern: Option<String>,
   dashed_idents: Option<bool>,
+  paint_worklets: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -543,7 +551,13 @@ struct BundleConfig {
   pub css_modules: Option<CssModulesOption>,
   pub analyze_dependencies: Option<AnalyzeDependenciesOption>,
   pub pseudo_classes: Option<OwnedPseudoClasses>,
+  pub color_format: Option<ColorFormat>,
+  pub light_dark_mode: Option<LightDarkMode>,
+  pub gamut_mapping: Option<GamutMapping>,
+  pub nesting_mode: Option<NestingMode>,
+  pub shape_precision: Option<u8>,
   pub unused_symbols: Option<HashSet<String>>,
+  pub preserve_empty_rules: Option<bool>,
   pub error_recovery: Option<bool>,
   pub custom_at_rules: Option<HashMap<String, CustomAtRuleConfig>>,
 }
@@ -619,6 +633,7 @@ fn compile<'i>(
                 Default::default()
               },
               dashed_idents: c.dashed_idents.unwrap_or_default(),
+              paint_worklets: c.paint_worklets.unwrap_or_default(),
             }),
           }
         } else {
@@ -627,6 +642,10 @@ fn compile<'i>(
         source_index: 0,
         error_recovery: config.error_recovery.unwrap_or_default(),
         warnings: warnings.clone(),
+        local_global: Default::default(),
+        preserve_comments: false,
+        lossless: false,
+        custom_at_rules: Default::default(),
       },
       &mut CustomAtRuleParser {
         configs: config.custom_at_rules.clone().unwrap_or_default(),
@@ -640,6 +659,40 @@ fn compile<'i>(
     stylesheet.minify(MinifyOptions {
       targets: config.targets,
       unused_symbols: config.unused_symbols.clone().unwrap_or_default(),
+      purge: None,
+      remove_unsupported_has: false,
+      normalize_attribute_selectors: false,
+      expand_is_selectors: false,
+      preserve_empty_rules: config.preserve_empty_rules.unwrap_or_default(),
+      strict_namespaces: false,
+      remove_shadowed_declarations: false,
+      merge_media_rules: false,
+      merge_non_adjacent_rules: false,
+      unsafe_merge_non_adjacent_rules: false,
+      merge_same_selector_rules: false,
+      flatten_anonymous_layers: false,
+      mangle_idents: false,
+      sort_declarations: None,
+      remove_obsolete_prefixes: false,
+      remove_legacy_hacks: false,
+      remove_unneeded_important: false,
+      optimization_level: Default::default(),
+      media_environment: None,
+      environment_variables: HashMap::new(),
+      replace_typed_attr: false,
+      legacy_high_contrast: false,
+      image_set_fallback: false,
+      remove_unused_font_faces: false,
+      remove_unused_keyframes: false,
+      merge_duplicate_keyframes: false,
+      remove_unused_transitions: false,
+      preserved_keyframes: HashSet::new(),
+      remove_unused_custom_properties: false,
+      preserved_custom_properties: HashSet::new(),
+      inline_custom_properties: false,
+      dynamic_custom_properties: HashSet::new(),
+      color_fallback_strategy: Default::default(),
+      logical_property_strategy: Default::default(),
     })?;
 
     stylesheet.to_css(PrinterOptions {
@@ -659,6 +712,13 @@ fn compile<'i>(
         None
       },
       pseudo_classes: config.pseudo_classes.as_ref().map(|p| p.into()),
+      has_polyfill: None,
+      color_format: config.color_format.unwrap_or_default(),
+      light_dark_mode: config.light_dark_mode.unwrap_or_default(),
+      gamut_mapping: config.gamut_mapping.unwrap_or_default(),
+      nesting_mode: config.nesting_mode.unwrap_or_default(),
+      size_report: false,
+      shape_precision: config.shape_precision,
     })?
   };
 
@@ -728,6 +788,7 @@ fn compile_bundle<
               Default::default()
             },
             dashed_idents: c.dashed_idents.unwrap_or_default(),
+            paint_worklets: c.paint_worklets.unwrap_or_default(),
           }),
         }
       } else {
@@ -737,6 +798,10 @@ fn compile_bundle<
       warnings: warnings.clone(),
       filename: String::new(),
       source_index: 0,
+      local_global: Default::default(),
+      preserve_comments: false,
+      lossless: false,
+      custom_at_rules: Default::default(),
     };
 
     let mut at_rule_parser = CustomAtRuleParser {
@@ -754,6 +819,40 @@ fn compile_bundle<
     stylesheet.minify(MinifyOptions {
       targets: config.targets,
       unused_symbols: config.unused_symbols.clone().unwrap_or_default(),
+      purge: None,
+      remove_unsupported_has: false,
+      normalize_attribute_selectors: false,
+      expand_is_selectors: false,
+      preserve_empty_rules: config.preserve_empty_rules.unwrap_or_default(),
+      strict_namespaces: false,
+      remove_shadowed_declarations: false,
+      merge_media_rules: false,
+      merge_non_adjacent_rules: false,
+      unsafe_merge_non_adjacent_rules: false,
+      merge_same_selector_rules: false,
+      flatten_anonymous_layers: false,
+      mangle_idents: false,
+      sort_declarations: None,
+      remove_obsolete_prefixes: false,
+      remove_legacy_hacks: false,
+      remove_unneeded_important: false,
+      optimization_level: Default::default(),
+      media_environment: None,
+      environment_variables: HashMap::new(),
+      replace_typed_attr: false,
+      legacy_high_contrast: false,
+      image_set_fallback: false,
+      remove_unused_font_faces: false,
+      remove_unused_keyframes: false,
+      merge_duplicate_keyframes: false,
+      remove_unused_transitions: false,
+      preserved_keyframes: HashSet::new(),
+      remove_unused_custom_properties: false,
+      preserved_custom_properties: HashSet::new(),
+      inline_custom_properties: false,
+      dynamic_custom_properties: HashSet::new(),
+      color_fallback_strategy: Default::default(),
+      logical_property_strategy: Default::default(),
     })?;
 
     stylesheet.to_css(PrinterOptions {
@@ -773,6 +872,13 @@ fn compile_bundle<
         None
       },
       pseudo_classes: config.pseudo_classes.as_ref().map(|p| p.into()),
+      has_polyfill: None,
+      color_format: config.color_format.unwrap_or_default(),
+      light_dark_mode: config.light_dark_mode.unwrap_or_default(),
+      gamut_mapping: config.gamut_mapping.unwrap_or_default(),
+      nesting_mode: config.nesting_mode.unwrap_or_default(),
+      size_report: false,
+      shape_precision: config.shape_precision,
     })?
   };
 
@@ -878,6 +984,13 @@ fn compile_attr<'i>(
         None
       },
       pseudo_classes: None,
+      has_polyfill: None,
+      color_format: ColorFormat::default(),
+      light_dark_mode: LightDarkMode::default(),
+      gamut_mapping: GamutMapping::default(),
+      nesting_mode: NestingMode::default(),
+      size_report: false,
+      shape_precision: None,
     })?
   };
   Ok(AttrResult {
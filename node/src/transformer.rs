@@ -283,6 +283,11 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
             CssRule::LayerStatement(..) => "layer-statement",
             CssRule::Property(..) => "property",
             CssRule::Container(..) => "container",
+            CssRule::Scope(..) => "scope",
+            CssRule::StartingStyle(..) => "starting-style",
+            CssRule::PositionTry(..) => "position-try",
+            CssRule::NestedDeclarations(..) => "nested-declarations",
+            CssRule::Comment(..) => "comment",
             CssRule::MozDocument(..) => "moz-document",
             CssRule::Nesting(..) => "nesting",
             CssRule::Viewport(..) => "viewport",
@@ -296,6 +301,16 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
                 "unknown"
               }
             }
+            CssRule::CustomAtRule(v) => {
+              let name = v.name.as_ref();
+              if let Some(visit) = rule_map.custom(stage, "custom", name) {
+                let js_value = env.to_js_value(v)?;
+                let res = visit.call(None, &[js_value])?;
+                return env.from_js_value(res).map(serde_detach::detach);
+              } else {
+                "custom-at-rule"
+              }
+            }
             CssRule::Custom(c) => {
               let name = c.name.as_ref();
               if let Some(visit) = rule_map.custom(stage, "custom", name) {
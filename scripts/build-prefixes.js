@@ -262,7 +262,40 @@ let mdnFeatures = {
       })
   ),
   imageSet: mdn.css.types.image['image-set'].__compat.support,
-  xResolutionUnit: mdn.css.types.resolution.x.__compat.support
+  xResolutionUnit: mdn.css.types.resolution.x.__compat.support,
+  statePseudoClass: mdn.css.selectors.state.__compat.support,
+  popoverOpenPseudoClass: mdn.css.selectors['popover-open'].__compat.support,
+  modalPseudoClass: mdn.css.selectors.modal.__compat.support,
+  viewTransition: mdn.css.selectors['view-transition'].__compat.support,
+  viewTransitionClass: mdn.css.properties['view-transition-class'].__compat.support,
+  detailsContent: mdn.css.selectors['details-content'].__compat.support,
+  partPseudo: mdn.css.selectors.part.__compat.support,
+  mediaForcedColors: mdn.css['at-rules'].media['forced-colors'].__compat.support,
+  mediaPrefersContrast: mdn.css['at-rules'].media['prefers-contrast'].__compat.support,
+  mediaPrefersReducedData: mdn.css['at-rules'].media['prefers-reduced-data'].__compat.support,
+  mediaInvertedColors: mdn.css['at-rules'].media['inverted-colors'].__compat.support,
+  mediaScripting: mdn.css['at-rules'].media.scripting.__compat.support,
+  mediaUpdate: mdn.css['at-rules'].media.update.__compat.support,
+  mediaOverflowBlock: mdn.css['at-rules'].media['overflow-block'].__compat.support,
+  mediaOverflowInline: mdn.css['at-rules'].media['overflow-inline'].__compat.support,
+  supportsSelector: mdn.css['at-rules'].supports.selector.__compat.support,
+  supportsFontTech: mdn.css['at-rules'].supports['font-tech'].__compat.support,
+  supportsFontFormat: mdn.css['at-rules'].supports['font-format'].__compat.support,
+  cssGrid: mdn.css.properties.display.grid.__compat.support,
+  contentVisibility: mdn.css.properties['content-visibility'].__compat.support,
+  containIntrinsicSizeAuto: mdn.css.properties['contain-intrinsic-size'].auto.__compat.support,
+  fieldSizing: mdn.css.properties['field-sizing'].__compat.support,
+  masonry: mdn.css.properties['grid-template-rows'].masonry.__compat.support,
+  offsetPath: mdn.css.properties['offset-path'].__compat.support,
+  cssSticky: mdn.css.properties.position.sticky.__compat.support,
+  cssVariables: mdn.css.properties['custom-property'].__compat.support,
+  atProperty: mdn.css['at-rules'].property.__compat.support,
+  atScope: mdn.css['at-rules'].scope.__compat.support,
+  lightDark: mdn.css.types.color['light-dark'].__compat.support,
+  atStartingStyle: mdn.css['at-rules']['starting-style'].__compat.support,
+  transitionBehavior: mdn.css.properties['transition-behavior'].__compat.support,
+  anchorPositioning: mdn.css.properties['anchor-name'].__compat.support,
+  typedAttr: mdn.css.types.attr['type-or-unit'].__compat.support
 };
 
 for (let feature in mdnFeatures) {
@@ -308,6 +341,14 @@ addValue(compat, {
   ios_saf: parseVersion('10.3')
 }, 'langList');
 
+addValue(compat, {
+  // The motion-* properties were renamed to offset-* in Chrome 55.
+  chrome: parseVersion('46'),
+  opera: parseVersion('33'),
+  samsung: parseVersion('5'),
+  android: parseVersion('46')
+}, 'motionPath');
+
 let prefixMapping = {
   webkit: 'WebKit',
   moz: 'Moz',
@@ -377,6 +418,13 @@ impl Feature {
     }
     prefixes
   }
+
+  pub fn from_property(name: &str) -> Option<Feature> {
+    match name {
+      ${[...p.keys()].flat().filter(name => /^[a-z]/.test(name)).sort().map(name => `"${name}" => Some(Feature::${enumify(name)})`).join(',\n      ')},
+      _ => None,
+    }
+  }
 }
 
 pub fn is_flex_2009(browsers: Browsers) -> bool {
@@ -409,7 +457,7 @@ let c = `// This file is autogenerated by build-prefixes.js. DO NOT EDIT!
 
 use crate::targets::Browsers;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Feature {
   ${[...compat.keys()].flat().map(enumify).sort().join(',\n  ')}
 }
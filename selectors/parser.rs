@@ -40,6 +40,12 @@ pub trait PseudoElement<'i>: Sized + ToCss {
   fn is_webkit_scrollbar(&self) -> bool {
     false
   }
+
+  /// Whether this pseudo-element is one of the view transition pseudo-elements,
+  /// which may be followed by `:only-child`.
+  fn is_view_transition(&self) -> bool {
+    false
+  }
 }
 
 /// A trait that represents a pseudo-class.
@@ -126,6 +132,9 @@ bitflags! {
         const AFTER_NESTING = 1 << 7;
 
         const AFTER_WEBKIT_SCROLLBAR = 1 << 8;
+
+        /// Whether we've parsed a view transition pseudo-element already.
+        const AFTER_VIEW_TRANSITION = 1 << 9;
     }
 }
 
@@ -152,7 +161,10 @@ impl SelectorParsingState {
   // state, and so on.
   #[inline]
   fn allows_custom_functional_pseudo_classes(self) -> bool {
-    !self.intersects(Self::AFTER_PSEUDO)
+    // ::part() may be followed by any pseudo-class that doesn't depend on tree
+    // structure, e.g. :state().
+    // https://drafts.csswg.org/css-shadow-parts/#part
+    !self.intersects(Self::AFTER_SLOTTED | Self::AFTER_PSEUDO_ELEMENT)
   }
 
   #[inline]
@@ -948,14 +960,23 @@ impl<'i, Impl: SelectorImpl<'i>> From<Component<'i, Impl>> for Selector<'i, Impl
 impl<'i, Impl: SelectorImpl<'i>> From<Vec<Component<'i, Impl>>> for Selector<'i, Impl> {
   fn from(vec: Vec<Component<'i, Impl>>) -> Self {
     let mut builder = SelectorBuilder::default();
+    let mut parsed_pseudo = false;
+    let mut parsed_slotted = false;
+    let mut parsed_part = false;
     for component in vec.into_iter() {
       if let Some(combinator) = component.as_combinator() {
         builder.push_combinator(combinator);
       } else {
+        match component {
+          Component::PseudoElement(..) => parsed_pseudo = true,
+          Component::Slotted(..) => parsed_slotted = true,
+          Component::Part(..) => parsed_part = true,
+          _ => {}
+        }
         builder.push_simple_selector(component);
       }
     }
-    let (spec, components) = builder.build(false, false, false);
+    let (spec, components) = builder.build(parsed_pseudo, parsed_slotted, parsed_part);
     Selector(spec, components)
   }
 }
@@ -1827,18 +1848,6 @@ where
     input.reset(&state);
   }
 
-  // In the implicit nesting mode, selectors may not start with an ident or function token.
-  if nesting_requirement == NestingRequirement::Implicit {
-    let state = input.state();
-    match input.next()? {
-      Token::Ident(..) | Token::Function(..) => {
-        return Err(input.new_custom_error(SelectorParseErrorKind::MissingNestingPrefix));
-      }
-      _ => {}
-    }
-    input.reset(&state);
-  }
-
   let mut builder = SelectorBuilder::default();
 
   let mut has_pseudo_element = false;
@@ -2463,6 +2472,9 @@ where
         if p.is_webkit_scrollbar() {
           state.insert(SelectorParsingState::AFTER_WEBKIT_SCROLLBAR);
         }
+        if p.is_view_transition() {
+          state.insert(SelectorParsingState::AFTER_VIEW_TRANSITION);
+        }
         builder.push_combinator(Combinator::PseudoElement);
         builder.push_simple_selector(Component::PseudoElement(p));
       }
@@ -2735,6 +2747,11 @@ where
     return Err(location.new_custom_error(SelectorParseErrorKind::InvalidState));
   }
 
+  // https://drafts.csswg.org/css-view-transitions-1/#pseudo-root
+  if state.intersects(SelectorParsingState::AFTER_VIEW_TRANSITION) && name.eq_ignore_ascii_case("only-child") {
+    return Ok(Component::OnlyChild);
+  }
+
   if state.allows_tree_structural_pseudo_classes() {
     match_ignore_ascii_case! { &name,
         "first-child" => return Ok(Component::FirstChild),
@@ -0,0 +1,326 @@
+//! Asynchronous visitors for transforming values in a StyleSheet.
+//!
+//! An [AsyncVisitor](AsyncVisitor) is similar to a [Visitor](crate::visitor::Visitor), but its methods return
+//! futures. This allows transforms that need to perform IO, such as resolving asset URLs or looking up design
+//! tokens from a service, to run as part of the traversal without blocking.
+//!
+//! Only the value types in [SUPPORTED_TYPES](SUPPORTED_TYPES) can be visited: urls, colors, lengths, custom
+//! identifiers, dashed identifiers, variables, and tokens. Rules, selectors, properties, and other values must be
+//! transformed with a synchronous [Visitor](crate::visitor::Visitor) instead. Calling
+//! [visit_async](crate::stylesheet::StyleSheet::visit_async) with a visitor whose [TYPES](AsyncVisitor::TYPES)
+//! include any other flag fails to compile:
+//!
+//! ```compile_fail
+//! use lightningcss::{
+//!   async_visitor::AsyncVisitor,
+//!   stylesheet::{ParserOptions, StyleSheet},
+//!   visit_types,
+//!   visitor::VisitTypes,
+//! };
+//!
+//! struct RuleVisitor;
+//!
+//! impl<'i> AsyncVisitor<'i> for RuleVisitor {
+//!   type Error = ();
+//!
+//!   // Rules are not supported.
+//!   const TYPES: VisitTypes = visit_types!(URLS | RULES);
+//! }
+//!
+//! let mut stylesheet = StyleSheet::parse(".foo { color: red }", ParserOptions::default()).unwrap();
+//! let _ = stylesheet.visit_async(&mut RuleVisitor);
+//! ```
+//!
+//! Flags returned by an overridden [visit_types](AsyncVisitor::visit_types) method are only known at runtime,
+//! so unsupported flags there are ignored.
+//!
+//! Values are visited in two passes. First, the values of the requested [VisitTypes](VisitTypes) are collected
+//! from the style sheet in traversal order. Each value is then passed to the visitor, and its future is awaited
+//! before moving on to the next one. Finally, the updated values are written back into the style sheet. The
+//! children of a visited value are not visited, e.g. the urls and variables within a visited token, or the
+//! fallback of a visited variable, so a visitor that requests both must handle the nested values itself.
+//!
+//! The futures returned by visitor methods must be `Send`, so that a traversal can be spawned onto a
+//! multi-threaded tokio runtime, but no particular runtime is required.
+//!
+//! # Example
+//!
+//! This example rewrites urls using a (simulated) asset service.
+//!
+//! ```
+//! use lightningcss::{
+//!   async_visitor::{AsyncVisitor, VisitFuture},
+//!   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+//!   values::url::Url,
+//!   visit_types,
+//!   visitor::VisitTypes,
+//! };
+//!
+//! struct AssetResolver;
+//!
+//! impl<'i> AsyncVisitor<'i> for AssetResolver {
+//!   type Error = std::convert::Infallible;
+//!
+//!   const TYPES: VisitTypes = visit_types!(URLS);
+//!
+//!   fn visit_url<'a>(&'a mut self, url: &'a mut Url<'i>) -> VisitFuture<'a, Self::Error> {
+//!     Box::pin(async move {
+//!       // e.g. request the hashed file name from an asset service.
+//!       url.url = format!("https://cdn.example.com/{}", url.url).into();
+//!       Ok(())
+//!     })
+//!   }
+//! }
+//!
+//! async fn transform() {
+//!   let mut stylesheet = StyleSheet::parse(".foo { background: url(bg.png) }", ParserOptions::default()).unwrap();
+//!   stylesheet.visit_async(&mut AssetResolver).await.unwrap();
+//!
+//!   let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//!   assert_eq!(res.code, ".foo{background:url(https://cdn.example.com/bg.png)}");
+//! }
+//! ```
+
+use crate::properties::custom::{TokenOrValue, Variable};
+use crate::rules::CssRuleList;
+use crate::values::color::CssColor;
+use crate::values::ident::{CustomIdent, DashedIdent};
+use crate::values::length::LengthValue;
+use crate::values::url::Url;
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+/// The future returned by the methods of an [AsyncVisitor](AsyncVisitor).
+pub type VisitFuture<'a, E> = Pin<Box<dyn Future<Output = Result<(), E>> + Send + 'a>>;
+
+/// A trait for asynchronously visiting or transforming values in a StyleSheet.
+///
+/// Only the value types with a corresponding method are supported: urls, colors, lengths, custom
+/// identifiers, dashed identifiers, variables, and tokens. [TYPES](AsyncVisitor::TYPES) must be a subset
+/// of [SUPPORTED_TYPES](SUPPORTED_TYPES), which is checked at compile time.
+pub trait AsyncVisitor<'i> {
+  /// The `Err` value for `Result`s returned by `visit_*` methods.
+  type Error;
+
+  /// The types of values that this visitor should visit. May be constructed using
+  /// the [visit_types](crate::visit_types) macro. Must only contain [SUPPORTED_TYPES](SUPPORTED_TYPES).
+  const TYPES: VisitTypes;
+
+  /// Returns the types of values that this visitor should visit. By default, it returns
+  /// `Self::TYPES`, but this can be overridden to change the value at runtime.
+  #[inline]
+  fn visit_types(&self) -> VisitTypes {
+    Self::TYPES
+  }
+
+  /// Visits a url.
+  #[allow(unused_variables)]
+  fn visit_url<'a>(&'a mut self, url: &'a mut Url<'i>) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a color.
+  #[allow(unused_variables)]
+  fn visit_color<'a>(&'a mut self, color: &'a mut CssColor) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a length.
+  #[allow(unused_variables)]
+  fn visit_length<'a>(&'a mut self, length: &'a mut LengthValue) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a custom identifier.
+  #[allow(unused_variables)]
+  fn visit_custom_ident<'a>(&'a mut self, ident: &'a mut CustomIdent<'i>) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a dashed identifier.
+  #[allow(unused_variables)]
+  fn visit_dashed_ident<'a>(&'a mut self, ident: &'a mut DashedIdent<'i>) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a variable reference.
+  #[allow(unused_variables)]
+  fn visit_variable<'a>(&'a mut self, var: &'a mut Variable<'i>) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+
+  /// Visits a token or value in an unparsed property.
+  #[allow(unused_variables)]
+  fn visit_token<'a>(&'a mut self, token: &'a mut TokenOrValue<'i>) -> VisitFuture<'a, Self::Error> {
+    Box::pin(async { Ok(()) })
+  }
+}
+
+/// A value collected from the style sheet, to be passed to the async visitor.
+enum Value<'i> {
+  Url(Url<'i>),
+  Color(CssColor),
+  Length(LengthValue),
+  CustomIdent(CustomIdent<'i>),
+  DashedIdent(DashedIdent<'i>),
+  Variable(Variable<'i>),
+  Token(TokenOrValue<'i>),
+}
+
+/// The value types that an [AsyncVisitor](AsyncVisitor) can visit.
+pub const SUPPORTED_TYPES: VisitTypes =
+  crate::visit_types!(URLS | COLORS | LENGTHS | CUSTOM_IDENTS | DASHED_IDENTS | VARIABLES | TOKENS);
+
+/// Fails to compile when the `TYPES` of a visitor include a value type that is not supported.
+struct AssertSupportedTypes<'i, V>(PhantomData<(&'i (), V)>);
+
+impl<'i, V: AsyncVisitor<'i>> AssertSupportedTypes<'i, V> {
+  const OK: () = assert!(
+    SUPPORTED_TYPES.contains(V::TYPES),
+    "AsyncVisitor::TYPES may only contain URLS, COLORS, LENGTHS, CUSTOM_IDENTS, DASHED_IDENTS, VARIABLES, or TOKENS"
+  );
+}
+
+/// Allows an `impl Trait` return type to capture a lifetime that does not otherwise appear in its bounds.
+#[doc(hidden)]
+pub trait Captures<'a> {}
+
+impl<'a, T: ?Sized> Captures<'a> for T {}
+
+/// Collects copies of the values to visit, in traversal order.
+struct Collector<'i> {
+  types: VisitTypes,
+  values: Vec<Value<'i>>,
+}
+
+/// Writes the visited values back, in the same order they were collected.
+struct Applier<'i> {
+  types: VisitTypes,
+  values: std::vec::IntoIter<Value<'i>>,
+}
+
+// The identifier methods of `Visitor` are not tied to the `'i` lifetime, so identifiers are copied.
+macro_rules! impl_passes {
+  ($($method: ident($ty: ty) => $variant: ident),*) => {
+    impl<'i> Visitor<'i> for Collector<'i> {
+      type Error = std::convert::Infallible;
+
+      const TYPES: VisitTypes = VisitTypes::empty();
+
+      fn visit_types(&self) -> VisitTypes {
+        self.types
+      }
+
+      $(
+        fn $method(&mut self, value: &mut $ty) -> Result<(), Self::Error> {
+          self.values.push(Value::$variant(value.clone()));
+          Ok(())
+        }
+      )*
+
+      fn visit_custom_ident(&mut self, ident: &mut CustomIdent) -> Result<(), Self::Error> {
+        self.values.push(Value::CustomIdent(CustomIdent(ident.0.to_string().into())));
+        Ok(())
+      }
+
+      fn visit_dashed_ident(&mut self, ident: &mut DashedIdent) -> Result<(), Self::Error> {
+        self.values.push(Value::DashedIdent(DashedIdent(ident.0.to_string().into())));
+        Ok(())
+      }
+    }
+
+    impl<'i> Visitor<'i> for Applier<'i> {
+      type Error = std::convert::Infallible;
+
+      const TYPES: VisitTypes = VisitTypes::empty();
+
+      fn visit_types(&self) -> VisitTypes {
+        self.types
+      }
+
+      $(
+        fn $method(&mut self, value: &mut $ty) -> Result<(), Self::Error> {
+          if let Some(Value::$variant(visited)) = self.values.next() {
+            *value = visited;
+          }
+          Ok(())
+        }
+      )*
+
+      fn visit_custom_ident(&mut self, ident: &mut CustomIdent) -> Result<(), Self::Error> {
+        if let Some(Value::CustomIdent(visited)) = self.values.next() {
+          ident.0 = visited.0.to_string().into();
+        }
+        Ok(())
+      }
+
+      fn visit_dashed_ident(&mut self, ident: &mut DashedIdent) -> Result<(), Self::Error> {
+        if let Some(Value::DashedIdent(visited)) = self.values.next() {
+          ident.0 = visited.0.to_string().into();
+        }
+        Ok(())
+      }
+    }
+  };
+}
+
+impl_passes! {
+  visit_url(Url<'i>) => Url,
+  visit_color(CssColor) => Color,
+  visit_length(LengthValue) => Length,
+  visit_variable(Variable<'i>) => Variable,
+  visit_token(TokenOrValue<'i>) => Token
+}
+
+/// Visits the values in a rule list with an async visitor.
+pub(crate) fn visit_rules<'a, 'i, V: AsyncVisitor<'i>>(
+  rules: &'a mut CssRuleList<'i>,
+  visitor: &'a mut V,
+) -> impl Future<Output = Result<(), V::Error>> + Captures<'i> + 'a {
+  // Checked when the traversal is created rather than when it is first polled.
+  let () = AssertSupportedTypes::<'i, V>::OK;
+  visit_values(rules, visitor)
+}
+
+async fn visit_values<'i, V: AsyncVisitor<'i>>(
+  rules: &mut CssRuleList<'i>,
+  visitor: &mut V,
+) -> Result<(), V::Error> {
+  let types = visitor.visit_types() & SUPPORTED_TYPES;
+  if types.is_empty() {
+    return Ok(());
+  }
+
+  let mut collector = Collector {
+    types,
+    values: Vec::new(),
+  };
+  if let Err(e) = rules.visit(&mut collector) {
+    match e {}
+  }
+
+  let mut values = collector.values;
+  for value in &mut values {
+    match value {
+      Value::Url(url) => visitor.visit_url(url).await?,
+      Value::Color(color) => visitor.visit_color(color).await?,
+      Value::Length(length) => visitor.visit_length(length).await?,
+      Value::CustomIdent(ident) => visitor.visit_custom_ident(ident).await?,
+      Value::DashedIdent(ident) => visitor.visit_dashed_ident(ident).await?,
+      Value::Variable(var) => visitor.visit_variable(var).await?,
+      Value::Token(token) => visitor.visit_token(token).await?,
+    }
+  }
+
+  let mut applier = Applier {
+    types,
+    values: values.into_iter(),
+  };
+  if let Err(e) = rules.visit(&mut applier) {
+    match e {}
+  }
+
+  Ok(())
+}
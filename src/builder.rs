@@ -0,0 +1,291 @@
+//! Builders for constructing rules programmatically.
+//!
+//! Constructing rules by hand requires filling in every field of the AST, including source locations and
+//! empty rule lists. The builders in this module fill in these defaults, so that only the selectors,
+//! declarations, and conditions need to be specified. Declarations and media features are passed as
+//! typed values, e.g. a [Property](Property) rather than a string, so they are checked at compile time.
+//! For convenience, selectors may also be parsed from a string.
+//!
+//! # Example
+//!
+//! ```
+//! use cssparser::RGBA;
+//! use lightningcss::{
+//!   builder::{media, style, MediaQueryBuilder},
+//!   printer::PrinterOptions,
+//!   properties::{size::Size, Property},
+//!   rules::{CssRule, CssRuleList},
+//!   stylesheet::{ParserOptions, StyleSheet},
+//!   values::{color::CssColor, length::{Length, LengthPercentage}},
+//! };
+//!
+//! let rule: CssRule = media(MediaQueryBuilder::screen().min_width(Length::px(600.0)))
+//!   .nest(
+//!     style(".foo")
+//!       .unwrap()
+//!       .declare(Property::Color(CssColor::RGBA(RGBA::from_floats(0.0, 0.0, 1.0, 1.0))))
+//!       .declare(Property::Width(Size::LengthPercentage(LengthPercentage::px(100.0)))),
+//!   )
+//!   .into();
+//!
+//! let stylesheet = StyleSheet::new(vec![], CssRuleList(vec![rule]), ParserOptions::default());
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, "@media screen and (min-width:600px){.foo{color:#00f;width:100px}}");
+//! ```
+
+use crate::declaration::DeclarationBlock;
+use crate::error::ParserError;
+use crate::media_query::{
+  MediaCondition, MediaFeature, MediaFeatureValue, MediaList, MediaQuery, MediaType, Operator, Qualifier,
+};
+use crate::parser::DefaultAtRule;
+use crate::properties::Property;
+use crate::rules::media::MediaRule;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::SelectorList;
+use crate::stylesheet::ParserOptions;
+use crate::traits::ParseWithOptions;
+use crate::values::ident::Ident;
+use crate::values::length::Length;
+use crate::values::string::CowArcStr;
+use crate::vendor_prefix::VendorPrefix;
+use cssparser::ParseError;
+
+/// Creates a [StyleRuleBuilder](StyleRuleBuilder), parsing the selectors from a string.
+pub fn style<'i>(selectors: &'i str) -> Result<StyleRuleBuilder<'i>, ParseError<'i, ParserError<'i>>> {
+  let selectors = SelectorList::parse_string_with_options(selectors, ParserOptions::default())?;
+  Ok(StyleRuleBuilder::new(selectors))
+}
+
+/// Creates a [MediaRuleBuilder](MediaRuleBuilder) with the given media query.
+pub fn media<'i>(query: impl Into<MediaQuery<'i>>) -> MediaRuleBuilder<'i> {
+  MediaRuleBuilder::new(query)
+}
+
+/// The location of rules that were not parsed from a source file.
+fn builder_loc() -> Location {
+  Location {
+    source_index: 0,
+    line: 0,
+    column: 1,
+    #[cfg(feature = "spans")]
+    span: Default::default(),
+  }
+}
+
+/// A builder for a [StyleRule](StyleRule).
+#[derive(Debug, Clone)]
+pub struct StyleRuleBuilder<'i, R = DefaultAtRule> {
+  rule: StyleRule<'i, R>,
+}
+
+impl<'i, R> StyleRuleBuilder<'i, R> {
+  /// Creates a builder for a style rule with the given selectors.
+  pub fn new(selectors: SelectorList<'i>) -> Self {
+    StyleRuleBuilder {
+      rule: StyleRule {
+        selectors,
+        vendor_prefix: VendorPrefix::None,
+        declarations: DeclarationBlock::new(),
+        rules: CssRuleList(vec![]),
+        loc: builder_loc(),
+        #[cfg(feature = "spans")]
+        selector_spans: vec![],
+      },
+    }
+  }
+
+  /// Adds a declaration.
+  pub fn declare(mut self, property: Property<'i>) -> Self {
+    self.rule.declarations.declarations.push(property);
+    self
+  }
+
+  /// Adds an `!important` declaration.
+  pub fn declare_important(mut self, property: Property<'i>) -> Self {
+    self.rule.declarations.important_declarations.push(property);
+    self
+  }
+
+  /// Adds a nested rule, after the declarations.
+  pub fn nest(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+    self.rule.rules.0.push(rule.into());
+    self
+  }
+
+  /// Returns the style rule.
+  pub fn build(self) -> StyleRule<'i, R> {
+    self.rule
+  }
+}
+
+impl<'i, R> From<StyleRuleBuilder<'i, R>> for CssRule<'i, R> {
+  fn from(builder: StyleRuleBuilder<'i, R>) -> Self {
+    CssRule::Style(builder.build())
+  }
+}
+
+/// A builder for a [MediaRule](MediaRule).
+#[derive(Debug, Clone)]
+pub struct MediaRuleBuilder<'i, R = DefaultAtRule> {
+  rule: MediaRule<'i, R>,
+}
+
+impl<'i, R> MediaRuleBuilder<'i, R> {
+  /// Creates a builder for a `@media` rule with the given media query.
+  pub fn new(query: impl Into<MediaQuery<'i>>) -> Self {
+    MediaRuleBuilder {
+      rule: MediaRule {
+        query: MediaList {
+          media_queries: vec![query.into()],
+        },
+        rules: CssRuleList(vec![]),
+        loc: builder_loc(),
+      },
+    }
+  }
+
+  /// Adds an alternative media query, i.e. the rule applies if any of the queries match.
+  pub fn or(mut self, query: impl Into<MediaQuery<'i>>) -> Self {
+    self.rule.query.media_queries.push(query.into());
+    self
+  }
+
+  /// Adds a nested rule.
+  pub fn nest(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+    self.rule.rules.0.push(rule.into());
+    self
+  }
+
+  /// Returns the `@media` rule.
+  pub fn build(self) -> MediaRule<'i, R> {
+    self.rule
+  }
+}
+
+impl<'i, R> From<MediaRuleBuilder<'i, R>> for CssRule<'i, R> {
+  fn from(builder: MediaRuleBuilder<'i, R>) -> Self {
+    CssRule::Media(builder.build())
+  }
+}
+
+/// A builder for a [MediaQuery](MediaQuery). Each feature that is added must match, i.e. the
+/// features are combined with `and`.
+#[derive(Debug, Clone)]
+pub struct MediaQueryBuilder<'i> {
+  query: MediaQuery<'i>,
+}
+
+impl<'i> MediaQueryBuilder<'i> {
+  /// Creates a builder for a media query with the given media type.
+  pub fn new(media_type: MediaType<'i>) -> Self {
+    MediaQueryBuilder {
+      query: MediaQuery {
+        qualifier: None,
+        media_type,
+        condition: None,
+      },
+    }
+  }
+
+  /// Creates a builder for a media query that matches all devices.
+  pub fn all() -> Self {
+    Self::new(MediaType::All)
+  }
+
+  /// Creates a builder for a media query that matches screens.
+  pub fn screen() -> Self {
+    Self::new(MediaType::Screen)
+  }
+
+  /// Creates a builder for a media query that matches printers.
+  pub fn print() -> Self {
+    Self::new(MediaType::Print)
+  }
+
+  /// Negates the media query with the `not` qualifier.
+  pub fn negate(mut self) -> Self {
+    self.query.qualifier = Some(Qualifier::Not);
+    self
+  }
+
+  /// Adds the `only` qualifier, which hides the media query from older browsers.
+  pub fn only(mut self) -> Self {
+    self.query.qualifier = Some(Qualifier::Only);
+    self
+  }
+
+  /// Adds a condition that must match.
+  pub fn condition(mut self, condition: MediaCondition<'i>) -> Self {
+    self.query.condition = Some(match self.query.condition.take() {
+      None => condition,
+      Some(MediaCondition::Operation {
+        operator: Operator::And,
+        mut conditions,
+      }) => {
+        conditions.push(condition);
+        MediaCondition::Operation {
+          operator: Operator::And,
+          conditions,
+        }
+      }
+      Some(existing) => MediaCondition::Operation {
+        operator: Operator::And,
+        conditions: vec![existing, condition],
+      },
+    });
+    self
+  }
+
+  /// Adds a media feature that must match.
+  pub fn feature(self, feature: MediaFeature<'i>) -> Self {
+    self.condition(MediaCondition::Feature(feature))
+  }
+
+  /// Adds a media feature with a value, e.g. `(orientation: landscape)`.
+  pub fn plain(self, name: impl Into<CowArcStr<'i>>, value: MediaFeatureValue<'i>) -> Self {
+    self.feature(MediaFeature::Plain {
+      name: Ident(name.into()),
+      value,
+    })
+  }
+
+  /// Adds a boolean media feature, e.g. `(hover)`.
+  pub fn boolean(self, name: impl Into<CowArcStr<'i>>) -> Self {
+    self.feature(MediaFeature::Boolean {
+      name: Ident(name.into()),
+    })
+  }
+
+  /// Adds a `min-width` media feature.
+  pub fn min_width(self, width: Length) -> Self {
+    self.plain("min-width", MediaFeatureValue::Length(width))
+  }
+
+  /// Adds a `max-width` media feature.
+  pub fn max_width(self, width: Length) -> Self {
+    self.plain("max-width", MediaFeatureValue::Length(width))
+  }
+
+  /// Adds a `min-height` media feature.
+  pub fn min_height(self, height: Length) -> Self {
+    self.plain("min-height", MediaFeatureValue::Length(height))
+  }
+
+  /// Adds a `max-height` media feature.
+  pub fn max_height(self, height: Length) -> Self {
+    self.plain("max-height", MediaFeatureValue::Length(height))
+  }
+
+  /// Returns the media query.
+  pub fn build(self) -> MediaQuery<'i> {
+    self.query
+  }
+}
+
+impl<'i> From<MediaQueryBuilder<'i>> for MediaQuery<'i> {
+  fn from(builder: MediaQueryBuilder<'i>) -> Self {
+    builder.build()
+  }
+}
@@ -256,6 +256,8 @@ where
           source_index: 0,
           line: 0,
           column: 1,
+          #[cfg(feature = "spans")]
+          span: crate::span::Span::default(),
         },
       },
     )?;
@@ -563,6 +565,8 @@ where
                 source_index: style_loc.source_index,
                 line: loc.line,
                 column: loc.column,
+                #[cfg(feature = "spans")]
+                span: crate::span::Span::default(),
               },
             },
           );
@@ -677,7 +681,7 @@ where
             let layer = std::mem::replace(rule, CssRule::Ignored);
             dest.push(layer);
           }
-          CssRule::Ignored => {}
+          CssRule::Ignored | CssRule::Comment(..) => {}
           _ => break,
         }
       }
@@ -1,13 +1,21 @@
 use std::collections::HashSet;
 
 use crate::compat::Feature;
-use crate::declaration::DeclarationBlock;
+use crate::declaration::{DeclarationBlock, DeclarationHandler};
+use crate::media_query::{MediaCondition, MediaFeature, MediaFeatureValue, MediaList, MediaQuery, MediaType};
 use crate::properties::custom::UnparsedProperty;
+use crate::properties::custom::{TokenList, TokenOrValue, Variable};
 use crate::properties::Property;
+use crate::properties::PropertyId;
+use crate::rules::media::MediaRule;
 use crate::rules::supports::{SupportsCondition, SupportsRule};
-use crate::rules::{style::StyleRule, CssRule, CssRuleList};
+use crate::rules::{declarations_overlap, style::StyleRule, CssRule, CssRuleList};
 use crate::selector::{Direction, PseudoClass};
+use crate::stylesheet::{ColorFallbackStrategy, LogicalPropertyStrategy, PrinterOptions};
 use crate::targets::Browsers;
+use crate::traits::FallbackValues;
+use crate::values::color::ColorFallbackKind;
+use crate::values::ident::{DashedIdent, DashedIdentReference, Ident};
 use crate::vendor_prefix::VendorPrefix;
 use parcel_selectors::parser::Component;
 
@@ -24,6 +32,8 @@ pub(crate) enum DeclarationContext {
   StyleRule,
   Keyframes,
   StyleAttribute,
+  PositionTry,
+  Page,
 }
 
 #[derive(Debug)]
@@ -35,6 +45,12 @@ pub(crate) struct PropertyHandlerContext<'i, 'o> {
   rtl: Vec<Property<'i>>,
   pub context: DeclarationContext,
   pub unused_symbols: &'o HashSet<String>,
+  pub color_fallback_strategy: ColorFallbackStrategy,
+  pub logical_property_strategy: LogicalPropertyStrategy,
+  pub image_set_fallback: bool,
+  has_wide_gamut_colors: bool,
+  wide_gamut_pass: bool,
+  pub wide_gamut: Option<DeclarationBlock<'i>>,
 }
 
 impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
@@ -47,13 +63,23 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
       rtl: Vec::new(),
       context: DeclarationContext::None,
       unused_symbols,
+      color_fallback_strategy: ColorFallbackStrategy::Inline,
+      logical_property_strategy: LogicalPropertyStrategy::Dir,
+      image_set_fallback: false,
+      has_wide_gamut_colors: false,
+      wide_gamut_pass: false,
+      wide_gamut: None,
     }
   }
 
   pub fn is_supported(&self, feature: Feature) -> bool {
-    // Don't convert logical properties in style attributes because
-    // our fallbacks rely on extra rules to define --ltr and --rtl.
-    if self.context == DeclarationContext::StyleAttribute {
+    // Don't convert logical properties in style attributes or @position-try rules unless the
+    // strategy adds the physical properties inline, because the :dir() fallbacks rely on extra rules.
+    if matches!(
+      self.context,
+      DeclarationContext::StyleAttribute | DeclarationContext::PositionTry | DeclarationContext::Page
+    ) && self.logical_property_strategy == LogicalPropertyStrategy::Dir
+    {
       return true;
     }
 
@@ -69,6 +95,75 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
     self.rtl.push(rtl);
   }
 
+  /// Adds the physical properties collected by `add_logical_rule` to the given declarations, when
+  /// the logical property strategy does not use extra rules.
+  pub fn add_logical_declarations(&mut self, dest: &mut Vec<Property<'i>>) {
+    match self.logical_property_strategy {
+      LogicalPropertyStrategy::Dir => {}
+      LogicalPropertyStrategy::Ltr => {
+        dest.append(&mut self.ltr);
+        self.rtl.clear();
+      }
+      LogicalPropertyStrategy::Rtl => {
+        dest.append(&mut self.rtl);
+        self.ltr.clear();
+      }
+      LogicalPropertyStrategy::CustomProperties => {
+        // Group the values for each physical property, so that e.g. margin-left can
+        // select between the inline-start value in ltr and the inline-end value in rtl.
+        let mut values: Vec<(PropertyId<'i>, Option<TokenList<'i>>, Option<TokenList<'i>>)> = Vec::new();
+        for (property, is_rtl) in std::mem::take(&mut self.ltr)
+          .into_iter()
+          .map(|p| (p, false))
+          .chain(std::mem::take(&mut self.rtl).into_iter().map(|p| (p, true)))
+        {
+          let id = property.property_id();
+          let value = match property {
+            Property::Unparsed(unparsed) => unparsed.value,
+            property => match property.value_to_css_string(PrinterOptions::default()) {
+              Ok(css) => TokenList::tokenize_owned(&css),
+              Err(_) => continue,
+            },
+          };
+
+          let index = match values.iter().position(|(v, ..)| *v == id) {
+            Some(index) => index,
+            None => {
+              values.push((id, None, None));
+              values.len() - 1
+            }
+          };
+
+          if is_rtl {
+            values[index].2 = Some(value);
+          } else {
+            values[index].1 = Some(value);
+          }
+        }
+
+        for (property_id, ltr, rtl) in values {
+          let mut tokens = Vec::new();
+          for (name, fallback) in [("--ltr", ltr), ("--rtl", rtl)] {
+            if let Some(fallback) = fallback {
+              tokens.push(TokenOrValue::Var(Variable {
+                name: DashedIdentReference {
+                  ident: DashedIdent(name.into()),
+                  from: None,
+                },
+                fallback: Some(fallback),
+              }));
+            }
+          }
+
+          dest.push(Property::Unparsed(UnparsedProperty {
+            property_id,
+            value: TokenList(tokens),
+          }));
+        }
+      }
+    }
+  }
+
   pub fn get_logical_rules<T>(&mut self, style_rule: &StyleRule<'i, T>) -> Vec<CssRule<'i, T>> {
     // TODO: :dir/:lang raises the specificity of the selector. Use :where to lower it?
     let mut dest = Vec::new();
@@ -88,9 +183,15 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
           declarations: DeclarationBlock {
             declarations: std::mem::take(&mut self.$decls),
             important_declarations: vec![],
+            #[cfg(feature = "comments")]
+            comments: vec![],
+            #[cfg(feature = "spans")]
+            spans: vec![],
           },
           rules: CssRuleList(vec![]),
           loc: style_rule.loc.clone(),
+          #[cfg(feature = "spans")]
+          selector_spans: vec![],
         };
 
         dest.push(CssRule::Style(rule));
@@ -154,6 +255,121 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
     }
   }
 
+  /// Returns the color fallbacks to add before a value. If the wide-gamut colors are moved into a separate
+  /// rule by the color fallback strategy, the value is replaced with its lowest fallback instead.
+  /// A `url()` fallback for any `image-set()` is added first if enabled and needed by the targets.
+  pub fn get_color_fallbacks<T: FallbackValues>(&mut self, value: &mut T, targets: Browsers) -> Vec<T> {
+    let image_set_fallback = if self.image_set_fallback && !Feature::ImageSet.is_compatible(targets) {
+      value.get_image_set_fallback()
+    } else {
+      None
+    };
+
+    let mut fallbacks = value.get_fallbacks(targets);
+    if self.color_fallback_strategy != ColorFallbackStrategy::Inline
+      && self.context == DeclarationContext::StyleRule
+      && !self.wide_gamut_pass
+      && !fallbacks.is_empty()
+    {
+      *value = fallbacks.remove(0);
+      self.has_wide_gamut_colors = true;
+      return image_set_fallback.into_iter().collect();
+    }
+
+    if let Some(fallback) = image_set_fallback {
+      fallbacks.insert(0, fallback);
+    }
+
+    fallbacks
+  }
+
+  /// Minifies the original declarations of a style rule again with inline color fallbacks, if any wide-gamut
+  /// colors were replaced while minifying them into `base`. The declarations that differ from the base, along
+  /// with any later declarations they would otherwise override, are added to the rule returned by
+  /// `get_color_fallback_rules`.
+  pub fn add_wide_gamut_declarations(
+    &mut self,
+    mut declarations: DeclarationBlock<'i>,
+    base: &DeclarationBlock<'i>,
+    handler: &mut DeclarationHandler<'i>,
+    important_handler: &mut DeclarationHandler<'i>,
+  ) {
+    if !std::mem::take(&mut self.has_wide_gamut_colors) {
+      return;
+    }
+
+    // Rules for unparsed and logical properties were already added while minifying the base declarations.
+    let supports = std::mem::take(&mut self.supports);
+    let ltr = std::mem::take(&mut self.ltr);
+    let rtl = std::mem::take(&mut self.rtl);
+    self.wide_gamut_pass = true;
+    declarations.minify(handler, important_handler, self);
+    self.wide_gamut_pass = false;
+    self.supports = supports;
+    self.ltr = ltr;
+    self.rtl = rtl;
+
+    fn changed<'i>(declarations: Vec<Property<'i>>, base: &[Property<'i>]) -> Vec<Property<'i>> {
+      let mut res: Vec<Property<'i>> = Vec::new();
+      for property in declarations {
+        if !base.contains(&property)
+          || res
+            .iter()
+            .any(|changed| declarations_overlap(&changed.property_id(), &property.property_id()))
+        {
+          res.push(property);
+        }
+      }
+      res
+    }
+
+    let declarations = DeclarationBlock {
+      declarations: changed(declarations.declarations, &base.declarations),
+      important_declarations: changed(declarations.important_declarations, &base.important_declarations),
+      #[cfg(feature = "comments")]
+      comments: vec![],
+      #[cfg(feature = "spans")]
+      spans: vec![],
+    };
+
+    if !declarations.is_empty() {
+      self.wide_gamut = Some(declarations);
+    }
+  }
+
+  pub fn get_color_fallback_rules<T>(&mut self, style_rule: &StyleRule<'i, T>) -> Vec<CssRule<'i, T>> {
+    let declarations = match self.wide_gamut.take() {
+      Some(declarations) => declarations,
+      None => return Vec::new(),
+    };
+
+    let rules = CssRuleList(vec![CssRule::Style(StyleRule {
+      selectors: style_rule.selectors.clone(),
+      vendor_prefix: VendorPrefix::None,
+      declarations,
+      rules: CssRuleList(vec![]),
+      loc: style_rule.loc,
+      #[cfg(feature = "spans")]
+      selector_spans: style_rule.selector_spans.clone(),
+    })]);
+
+    let rule = match self.color_fallback_strategy {
+      ColorFallbackStrategy::Inline => unreachable!(),
+      ColorFallbackStrategy::Supports => CssRule::Supports(SupportsRule {
+        condition: ColorFallbackKind::LAB.supports_condition(),
+        rules,
+        loc: style_rule.loc,
+      }),
+      ColorFallbackStrategy::MediaQuery => CssRule::Media(MediaRule {
+        query: color_gamut_query(),
+        rules,
+        loc: style_rule.loc,
+      }),
+    };
+
+    vec![rule]
+  }
+
   pub fn get_supports_rules<T>(&mut self, style_rule: &StyleRule<'i, T>) -> Vec<CssRule<'i, T>> {
     if self.supports.is_empty() {
       return Vec::new();
@@ -170,9 +386,15 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
           declarations: DeclarationBlock {
             declarations: entry.declarations,
             important_declarations: entry.important_declarations,
+            #[cfg(feature = "comments")]
+            comments: vec![],
+            #[cfg(feature = "spans")]
+            spans: vec![],
           },
           rules: CssRuleList(vec![]),
           loc: style_rule.loc.clone(),
+          #[cfg(feature = "spans")]
+          selector_spans: vec![],
         })]),
         loc: style_rule.loc.clone(),
       }));
@@ -181,3 +403,17 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
     dest
   }
 }
+
+/// Returns the `(color-gamut: p3)` media query.
+fn color_gamut_query<'i>() -> MediaList<'i> {
+  MediaList {
+    media_queries: vec![MediaQuery {
+      qualifier: None,
+      media_type: MediaType::All,
+      condition: Some(MediaCondition::Feature(MediaFeature::Plain {
+        name: Ident("color-gamut".into()),
+        value: MediaFeatureValue::Ident(Ident("p3".into())),
+      })),
+    }],
+  }
+}
@@ -0,0 +1,223 @@
+//! Removal of unused rules based on Chrome DevTools CSS coverage.
+//!
+//! The Coverage panel in Chrome DevTools can export the byte ranges of each style sheet that were used
+//! while recording. A [Coverage](Coverage) holds the entries of one or more such exports, and
+//! [StyleSheet::unused_rules](crate::stylesheet::StyleSheet::unused_rules) reports the style rules that
+//! start outside of all used ranges, while
+//! [StyleSheet::remove_unused_rules](crate::stylesheet::StyleSheet::remove_unused_rules) also removes them.
+//!
+//! Coverage entries are matched to the source files of the style sheet by url, and rules are mapped to
+//! ranges using their original locations. This means that coverage recorded while serving the original
+//! files, e.g. from a development server, can be applied to the output of the bundler.
+//! Rules from source files with no matching coverage entry are never considered unused.
+
+use crate::printer::PrinterOptions;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::SelectorList;
+use crate::traits::ToCss;
+
+/// The coverage of a set of style sheets, recorded during one or more sessions.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(transparent)
+)]
+pub struct Coverage {
+  /// The coverage entries, e.g. as exported from Chrome DevTools.
+  pub entries: Vec<CoverageEntry>,
+}
+
+/// The coverage of a single style sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Deserialize))]
+pub struct CoverageEntry {
+  /// The url of the style sheet.
+  pub url: String,
+  /// The ranges of the style sheet that were used.
+  pub ranges: Vec<CoverageRange>,
+  /// The text of the style sheet, which is needed to map the ranges to line and column numbers.
+  /// Entries without text are ignored.
+  #[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(default))]
+  pub text: Option<String>,
+}
+
+/// A range of a style sheet that was used, in UTF-16 code units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Deserialize))]
+pub struct CoverageRange {
+  /// The offset of the start of the range.
+  pub start: usize,
+  /// The offset of the end of the range, exclusive.
+  pub end: usize,
+}
+
+/// A style rule that was never used according to the coverage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct UnusedRule {
+  /// The selectors of the style rule.
+  pub selectors: String,
+  /// The location of the style rule.
+  pub loc: Location,
+}
+
+impl Coverage {
+  /// Creates a coverage from the given entries.
+  pub fn new(entries: Vec<CoverageEntry>) -> Coverage {
+    Coverage { entries }
+  }
+
+  /// Adds the entries of another coverage, e.g. from a different recording session.
+  pub fn extend(&mut self, other: Coverage) {
+    self.entries.extend(other.entries)
+  }
+
+  pub(crate) fn resolve(&self, sources: &[String]) -> ResolvedCoverage {
+    let sources = sources
+      .iter()
+      .map(|source| {
+        let mut resolved: Option<SourceCoverage> = None;
+        for entry in &self.entries {
+          let text = match &entry.text {
+            Some(text) if url_matches(&entry.url, source) => text,
+            _ => continue,
+          };
+          let resolved = resolved.get_or_insert_with(|| SourceCoverage {
+            line_starts: line_starts(text),
+            ranges: Vec::new(),
+          });
+          resolved.ranges.extend(entry.ranges.iter().copied());
+        }
+        resolved
+      })
+      .collect();
+    ResolvedCoverage { sources }
+  }
+}
+
+struct SourceCoverage {
+  /// The offsets of the start of each line, in UTF-16 code units.
+  line_starts: Vec<usize>,
+  ranges: Vec<CoverageRange>,
+}
+
+pub(crate) struct ResolvedCoverage {
+  sources: Vec<Option<SourceCoverage>>,
+}
+
+impl ResolvedCoverage {
+  /// Returns whether a rule starting at the given location is known to be unused.
+  fn is_unused(&self, loc: Location) -> bool {
+    let source = match self.sources.get(loc.source_index as usize) {
+      Some(Some(source)) => source,
+      _ => return false,
+    };
+    let line_start = match source.line_starts.get(loc.line as usize) {
+      Some(line_start) => *line_start,
+      None => return false,
+    };
+    let offset = line_start + (loc.column as usize).saturating_sub(1);
+    !source.ranges.iter().any(|range| range.start <= offset && offset < range.end)
+  }
+
+  /// Adds the style rules that are unused to the list.
+  pub(crate) fn unused_rules<T>(&self, rules: &CssRuleList<T>, unused: &mut Vec<UnusedRule>) {
+    rules.for_each_rule(&mut |rule| match rule {
+      CssRule::Style(style) if self.is_unused(style.loc) => unused.push(unused_rule(&style.selectors, style.loc)),
+      CssRule::Nesting(nesting) if self.is_unused(nesting.style.loc) => {
+        unused.push(unused_rule(&nesting.style.selectors, nesting.style.loc))
+      }
+      _ => {}
+    });
+  }
+
+  /// Removes the style rules that are unused, adding them to the list. Unused style rules with used nested
+  /// rules are kept without their declarations, and at-rules left empty are removed.
+  pub(crate) fn remove_unused_rules<T>(&self, rules: &mut CssRuleList<T>, unused: &mut Vec<UnusedRule>) {
+    rules.0.retain_mut(|rule| match rule {
+      CssRule::Style(style) => self.keep_style_rule(style, unused),
+      CssRule::Nesting(nesting) => self.keep_style_rule(&mut nesting.style, unused),
+      CssRule::Media(media) => !self.remove_from_block(&mut media.rules, unused),
+      CssRule::Supports(supports) => !self.remove_from_block(&mut supports.rules, unused),
+      CssRule::Container(container) => !self.remove_from_block(&mut container.rules, unused),
+      CssRule::LayerBlock(layer) => !self.remove_from_block(&mut layer.rules, unused),
+      CssRule::Scope(scope) => !self.remove_from_block(&mut scope.rules, unused),
+      CssRule::StartingStyle(rule) => !self.remove_from_block(&mut rule.rules, unused),
+      CssRule::MozDocument(document) => !self.remove_from_block(&mut document.rules, unused),
+      _ => true,
+    });
+  }
+
+  /// Removes the unused rules nested within a style rule, and returns whether the style rule should be kept.
+  fn keep_style_rule<T>(&self, style: &mut StyleRule<T>, unused: &mut Vec<UnusedRule>) -> bool {
+    let is_unused = self.is_unused(style.loc);
+    if is_unused {
+      unused.push(unused_rule(&style.selectors, style.loc));
+    }
+
+    self.remove_unused_rules(&mut style.rules, unused);
+    if !is_unused {
+      return true;
+    }
+
+    style.declarations.declarations.clear();
+    style.declarations.important_declarations.clear();
+    !style.rules.0.is_empty()
+  }
+
+  /// Removes the unused rules within a block, and returns whether this left the block empty.
+  fn remove_from_block<T>(&self, rules: &mut CssRuleList<T>, unused: &mut Vec<UnusedRule>) -> bool {
+    let was_empty = rules.0.is_empty();
+    self.remove_unused_rules(rules, unused);
+    !was_empty && rules.0.is_empty()
+  }
+}
+
+fn unused_rule(selectors: &SelectorList, loc: Location) -> UnusedRule {
+  UnusedRule {
+    selectors: selectors.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+    loc,
+  }
+}
+
+/// Returns whether a coverage url refers to the given source file, by comparing the path of the url
+/// with the end of the file path, e.g. `http://localhost:8080/css/main.css` matches `src/css/main.css`.
+fn url_matches(url: &str, source: &str) -> bool {
+  let url = url.split(|c| c == '?' || c == '#').next().unwrap_or(url);
+  let path = match url.find("://") {
+    Some(index) => {
+      let rest = &url[index + 3..];
+      rest.find('/').map_or("", |index| &rest[index..])
+    }
+    None => url,
+  };
+  let path = path.trim_start_matches('/');
+  let source = source.replace('\\', "/");
+  let source = source.trim_start_matches("./");
+  !path.is_empty()
+    && (source == path || source.ends_with(&format!("/{}", path)) || path.ends_with(&format!("/{}", source)))
+}
+
+/// Returns the offset of the start of each line in UTF-16 code units, using the same newlines as the parser.
+fn line_starts(text: &str) -> Vec<usize> {
+  let mut line_starts = vec![0];
+  let mut offset = 0;
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    offset += c.len_utf16();
+    match c {
+      '\r' => {
+        if chars.peek() == Some(&'\n') {
+          chars.next();
+          offset += 1;
+        }
+        line_starts.push(offset);
+      }
+      '\n' | '\x0C' => line_starts.push(offset),
+      _ => {}
+    }
+  }
+  line_starts
+}
@@ -32,6 +32,30 @@ pub struct Config<'i> {
   pub pattern: Pattern<'i>,
   /// Whether to rename dashed identifiers, e.g. custom properties.
   pub dashed_idents: bool,
+  /// Whether to rename the names of paint worklets referenced by `paint()` images.
+  pub paint_worklets: bool,
+}
+
+/// How to handle the CSS modules `:local()` and `:global()` pseudo classes when
+/// CSS modules are not enabled.
+///
+/// This allows component CSS to be shared between builds with and without CSS modules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalGlobalMode {
+  /// Emit a warning and preserve the pseudo class as an unknown custom function. This is the default.
+  Warn,
+  /// Emit a parse error.
+  Error,
+  /// Remove the `:local()` or `:global()` wrapper, keeping the selector inside it.
+  Strip,
+  /// Preserve the pseudo class verbatim without a warning.
+  Preserve,
+}
+
+impl Default for LocalGlobalMode {
+  fn default() -> Self {
+    LocalGlobalMode::Warn
+  }
 }
 
 /// A CSS modules class name pattern.
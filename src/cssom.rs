@@ -0,0 +1,309 @@
+//! Imperative rule manipulation, modeled after the browser [CSSOM](https://drafts.csswg.org/cssom/).
+//!
+//! Rule lists, style sheets, and grouping rules such as `@media`, `@supports`, and `@layer` blocks support
+//! inserting, deleting, and replacing rules by index, similar to `insertRule()` and `deleteRule()` on
+//! `CSSStyleSheet` and `CSSGroupingRule` in the browser. Like the browser, rules that are not allowed at the
+//! given position, such as an `@import` rule after a style rule, are rejected.
+//!
+//! Nested rules are addressed by a path of indices, starting with the index of a top-level rule, followed by
+//! the index within its child rules, and so on. As with CSSOM indices, the path of a rule remains valid until
+//! a rule is inserted or deleted before it, or before one of its ancestors.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   cssom::GroupingRule,
+//!   rules::CssRule,
+//!   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+//! };
+//!
+//! let mut stylesheet = StyleSheet::parse(
+//!   ".foo { color: red } @media print { .bar { color: green } }",
+//!   ParserOptions::default(),
+//! )
+//! .unwrap();
+//!
+//! let rule = CssRule::parse_string(".baz { color: blue }", ParserOptions::default()).unwrap();
+//! if let Some(CssRule::Media(media)) = stylesheet.rule_mut(&[1]) {
+//!   media.insert_rule(0, rule).unwrap();
+//! }
+//! stylesheet.delete_rule(0).unwrap();
+//!
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, "@media print{.baz{color:#00f}.bar{color:green}}");
+//! ```
+
+use crate::parser::DefaultAtRule;
+use crate::rules::container::ContainerRule;
+use crate::rules::custom_at_rule::CustomAtRuleBody;
+use crate::rules::document::MozDocumentRule;
+use crate::rules::layer::LayerBlockRule;
+use crate::rules::media::MediaRule;
+use crate::rules::scope::ScopeRule;
+use crate::rules::starting_style::StartingStyleRule;
+use crate::rules::style::StyleRule;
+use crate::rules::supports::SupportsRule;
+use crate::rules::{CssRule, CssRuleList};
+
+/// An error that occurred while modifying a rule list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleListError {
+  /// The index is past the end of the rule list, like an `IndexSizeError` in the CSSOM.
+  IndexOutOfBounds {
+    /// The requested index.
+    index: usize,
+    /// The number of rules in the list.
+    len: usize,
+  },
+  /// The rule is not allowed at the requested position, like a `HierarchyRequestError` in the CSSOM,
+  /// e.g. an `@import` rule after a style rule, or within a grouping rule.
+  InvalidPosition,
+  /// The path does not refer to a rule, or to a rule of the expected type.
+  InvalidPath,
+}
+
+impl std::fmt::Display for RuleListError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    use RuleListError::*;
+    match self {
+      IndexOutOfBounds { index, len } => {
+        write!(f, "Index {} is out of bounds for a rule list of length {}", index, len)
+      }
+      InvalidPosition => write!(f, "The rule cannot be inserted at the requested position"),
+      InvalidPath => write!(f, "The path does not refer to a valid rule"),
+    }
+  }
+}
+
+impl std::error::Error for RuleListError {}
+
+impl<'i, T> CssRuleList<'i, T> {
+  /// Returns the number of rules in the list.
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  /// Returns whether the list contains no rules.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Returns the rule at the given index, if any.
+  pub fn get(&self, index: usize) -> Option<&CssRule<'i, T>> {
+    self.0.get(index)
+  }
+
+  /// Returns a mutable reference to the rule at the given index, if any.
+  pub fn get_mut(&mut self, index: usize) -> Option<&mut CssRule<'i, T>> {
+    self.0.get_mut(index)
+  }
+
+  /// Inserts a rule at the given index, shifting the following rules back, and returns the index.
+  /// An index equal to the length of the list appends the rule.
+  pub fn insert_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<usize, RuleListError> {
+    if index > self.0.len() {
+      return Err(RuleListError::IndexOutOfBounds {
+        index,
+        len: self.0.len(),
+      });
+    }
+
+    if !is_valid_position(&self.0[..index], &rule, &self.0[index..]) {
+      return Err(RuleListError::InvalidPosition);
+    }
+
+    self.0.insert(index, rule);
+    Ok(index)
+  }
+
+  /// Removes the rule at the given index, shifting the following rules forward, and returns it.
+  pub fn delete_rule(&mut self, index: usize) -> Result<CssRule<'i, T>, RuleListError> {
+    if index >= self.0.len() {
+      return Err(RuleListError::IndexOutOfBounds {
+        index,
+        len: self.0.len(),
+      });
+    }
+
+    Ok(self.0.remove(index))
+  }
+
+  /// Replaces the rule at the given index, and returns the previous rule.
+  pub fn replace_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<CssRule<'i, T>, RuleListError> {
+    if index >= self.0.len() {
+      return Err(RuleListError::IndexOutOfBounds {
+        index,
+        len: self.0.len(),
+      });
+    }
+
+    if !is_valid_position(&self.0[..index], &rule, &self.0[index + 1..]) {
+      return Err(RuleListError::InvalidPosition);
+    }
+
+    Ok(std::mem::replace(&mut self.0[index], rule))
+  }
+
+  /// Returns the rule at the given path of indices, if any. See the [cssom](crate::cssom) module.
+  pub fn rule_at(&self, path: &[usize]) -> Option<&CssRule<'i, T>> {
+    let (index, rest) = path.split_first()?;
+    let rule = self.0.get(*index)?;
+    if rest.is_empty() {
+      Some(rule)
+    } else {
+      rule.child_rules()?.rule_at(rest)
+    }
+  }
+
+  /// Returns a mutable reference to the rule at the given path of indices, if any.
+  pub fn rule_at_mut(&mut self, path: &[usize]) -> Option<&mut CssRule<'i, T>> {
+    let (index, rest) = path.split_first()?;
+    let rule = self.0.get_mut(*index)?;
+    if rest.is_empty() {
+      Some(rule)
+    } else {
+      rule.child_rules_mut()?.rule_at_mut(rest)
+    }
+  }
+}
+
+/// Returns whether a rule may appear between the given rules. `@import` rules must precede all other rules
+/// except `@layer` statements, and `@namespace` rules must precede all rules other than those.
+fn is_valid_position<T>(before: &[CssRule<'_, T>], rule: &CssRule<'_, T>, after: &[CssRule<'_, T>]) -> bool {
+  match rule {
+    CssRule::Import(..) => before.iter().all(|rule| {
+      matches!(
+        rule,
+        CssRule::Import(..) | CssRule::LayerStatement(..) | CssRule::Comment(..) | CssRule::Ignored
+      )
+    }),
+    CssRule::Namespace(..) => {
+      before.iter().all(|rule| {
+        matches!(
+          rule,
+          CssRule::Import(..)
+            | CssRule::Namespace(..)
+            | CssRule::LayerStatement(..)
+            | CssRule::Comment(..)
+            | CssRule::Ignored
+        )
+      }) && !after.iter().any(|rule| matches!(rule, CssRule::Import(..)))
+    }
+    CssRule::LayerStatement(..) | CssRule::Comment(..) | CssRule::Ignored => true,
+    _ => !after
+      .iter()
+      .any(|rule| matches!(rule, CssRule::Import(..) | CssRule::Namespace(..))),
+  }
+}
+
+impl<'i, T> CssRule<'i, T> {
+  /// Returns the child rules of a grouping rule or style rule, if any.
+  pub fn child_rules(&self) -> Option<&CssRuleList<'i, T>> {
+    match self {
+      CssRule::Media(rule) => Some(&rule.rules),
+      CssRule::Style(rule) => Some(&rule.rules),
+      CssRule::Supports(rule) => Some(&rule.rules),
+      CssRule::MozDocument(rule) => Some(&rule.rules),
+      CssRule::Nesting(rule) => Some(&rule.style.rules),
+      CssRule::LayerBlock(rule) => Some(&rule.rules),
+      CssRule::Container(rule) => Some(&rule.rules),
+      CssRule::Scope(rule) => Some(&rule.rules),
+      CssRule::StartingStyle(rule) => Some(&rule.rules),
+      CssRule::CustomAtRule(rule) => match &rule.body {
+        Some(CustomAtRuleBody::RuleList(rules)) => Some(rules),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Returns a mutable reference to the child rules of a grouping rule or style rule, if any.
+  pub fn child_rules_mut(&mut self) -> Option<&mut CssRuleList<'i, T>> {
+    match self {
+      CssRule::Media(rule) => Some(&mut rule.rules),
+      CssRule::Style(rule) => Some(&mut rule.rules),
+      CssRule::Supports(rule) => Some(&mut rule.rules),
+      CssRule::MozDocument(rule) => Some(&mut rule.rules),
+      CssRule::Nesting(rule) => Some(&mut rule.style.rules),
+      CssRule::LayerBlock(rule) => Some(&mut rule.rules),
+      CssRule::Container(rule) => Some(&mut rule.rules),
+      CssRule::Scope(rule) => Some(&mut rule.rules),
+      CssRule::StartingStyle(rule) => Some(&mut rule.rules),
+      CssRule::CustomAtRule(rule) => match &mut rule.body {
+        Some(CustomAtRuleBody::RuleList(rules)) => Some(rules),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+}
+
+/// A rule that contains a list of child rules, like `CSSGroupingRule` in the CSSOM.
+///
+/// `@import` and `@namespace` rules cannot be inserted into grouping rules.
+pub trait GroupingRule<'i, T = DefaultAtRule> {
+  /// Returns the child rules.
+  fn rules(&self) -> &CssRuleList<'i, T>;
+
+  /// Returns a mutable reference to the child rules.
+  fn rules_mut(&mut self) -> &mut CssRuleList<'i, T>;
+
+  /// Returns the child rule at the given index, if any.
+  fn rule(&self, index: usize) -> Option<&CssRule<'i, T>> {
+    self.rules().get(index)
+  }
+
+  /// Returns a mutable reference to the child rule at the given index, if any.
+  fn rule_mut(&mut self, index: usize) -> Option<&mut CssRule<'i, T>> {
+    self.rules_mut().get_mut(index)
+  }
+
+  /// Inserts a child rule at the given index, and returns the index.
+  fn insert_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<usize, RuleListError> {
+    if matches!(rule, CssRule::Import(..) | CssRule::Namespace(..)) {
+      return Err(RuleListError::InvalidPosition);
+    }
+    self.rules_mut().insert_rule(index, rule)
+  }
+
+  /// Removes the child rule at the given index, and returns it.
+  fn delete_rule(&mut self, index: usize) -> Result<CssRule<'i, T>, RuleListError> {
+    self.rules_mut().delete_rule(index)
+  }
+
+  /// Replaces the child rule at the given index, and returns the previous rule.
+  fn replace_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<CssRule<'i, T>, RuleListError> {
+    if matches!(rule, CssRule::Import(..) | CssRule::Namespace(..)) {
+      return Err(RuleListError::InvalidPosition);
+    }
+    self.rules_mut().replace_rule(index, rule)
+  }
+}
+
+macro_rules! impl_grouping_rule {
+  ($($t: ident),*) => {
+    $(
+      impl<'i, T> GroupingRule<'i, T> for $t<'i, T> {
+        fn rules(&self) -> &CssRuleList<'i, T> {
+          &self.rules
+        }
+
+        fn rules_mut(&mut self) -> &mut CssRuleList<'i, T> {
+          &mut self.rules
+        }
+      }
+    )*
+  };
+}
+
+impl_grouping_rule!(
+  MediaRule,
+  SupportsRule,
+  LayerBlockRule,
+  ContainerRule,
+  ScopeRule,
+  StartingStyleRule,
+  MozDocumentRule,
+  StyleRule
+);
@@ -1,20 +1,23 @@
 //! CSS declarations.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::ops::Range;
 
 use crate::context::PropertyHandlerContext;
 use crate::error::{ParserError, PrinterError};
-use crate::parser::ParserOptions;
-use crate::printer::Printer;
+use crate::parser::{parse_comments, ParserOptions};
+use crate::prefixes::Feature;
+use crate::printer::{Printer, PrinterOptions};
 use crate::properties::box_shadow::BoxShadowHandler;
+use crate::properties::custom::CustomPropertyName;
 use crate::properties::masking::MaskHandler;
 use crate::properties::{
   align::AlignHandler,
   animation::AnimationHandler,
   background::BackgroundHandler,
   border::BorderHandler,
-  contain::ContainerHandler,
+  contain::{ContainIntrinsicSizeHandler, ContainerHandler, ContentVisibilityHandler},
   display::DisplayHandler,
   flex::FlexHandler,
   font::FontHandler,
@@ -26,18 +29,290 @@ use crate::properties::{
   position::PositionHandler,
   prefix_handler::{FallbackHandler, PrefixHandler},
   size::SizeHandler,
-  text::TextDecorationHandler,
-  transform::TransformHandler,
+  text::{TextDecorationHandler, WhiteSpaceHandler},
+  transform::{OffsetHandler, TransformHandler},
   transition::TransitionHandler,
 };
-use crate::properties::{Property, PropertyId};
+use crate::properties::{Property, PropertyId, TypedProperty};
+#[cfg(feature = "comments")]
+use crate::rules::comment::is_comment_written;
+use crate::rules::comment::{write_comment, CommentRule};
+use crate::rules::{declarations_overlap, CssRule};
+#[cfg(feature = "spans")]
+use crate::span::{declaration_span, DeclarationSpan};
 use crate::targets::Browsers;
 use crate::traits::{PropertyHandler, ToCss};
 use crate::values::string::CowArcStr;
+use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
 
+/// An order in which to sort the declarations within a style rule.
+///
+/// See [MinifyOptions](crate::stylesheet::MinifyOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationOrder {
+  /// Sort declarations alphabetically by property name, ignoring vendor prefixes.
+  Alphabetical,
+  /// Sort declarations from the outside of the box model in, i.e. positioning and layout
+  /// first, followed by the margin, border, padding, and dimensions, and finally the
+  /// text and other content properties. Properties not in this order are sorted alphabetically
+  /// after the others, and custom properties come first.
+  Concentric,
+}
+
+/// Property names in concentric order, from https://github.com/brandon-rhodes/Concentric-CSS.
+const CONCENTRIC_ORDER: &[&str] = &[
+  "all",
+  "display",
+  "position",
+  "inset",
+  "inset-block",
+  "inset-block-start",
+  "inset-block-end",
+  "inset-inline",
+  "inset-inline-start",
+  "inset-inline-end",
+  "top",
+  "right",
+  "bottom",
+  "left",
+  "z-index",
+  "float",
+  "clear",
+  "box-sizing",
+  "flex",
+  "flex-basis",
+  "flex-grow",
+  "flex-shrink",
+  "flex-flow",
+  "flex-direction",
+  "flex-wrap",
+  "grid",
+  "grid-area",
+  "grid-template",
+  "grid-template-areas",
+  "grid-template-rows",
+  "grid-template-columns",
+  "grid-row",
+  "grid-row-start",
+  "grid-row-end",
+  "grid-column",
+  "grid-column-start",
+  "grid-column-end",
+  "grid-auto-rows",
+  "grid-auto-columns",
+  "grid-auto-flow",
+  "gap",
+  "row-gap",
+  "column-gap",
+  "place-content",
+  "align-content",
+  "justify-content",
+  "place-items",
+  "align-items",
+  "justify-items",
+  "place-self",
+  "align-self",
+  "justify-self",
+  "order",
+  "columns",
+  "column-width",
+  "column-count",
+  "transform",
+  "transform-origin",
+  "transform-style",
+  "perspective",
+  "perspective-origin",
+  "backface-visibility",
+  "transition",
+  "transition-property",
+  "transition-duration",
+  "transition-timing-function",
+  "transition-delay",
+  "transition-behavior",
+  "animation",
+  "animation-name",
+  "animation-duration",
+  "animation-timing-function",
+  "animation-delay",
+  "animation-iteration-count",
+  "animation-direction",
+  "animation-fill-mode",
+  "animation-play-state",
+  "visibility",
+  "opacity",
+  "margin",
+  "margin-top",
+  "margin-right",
+  "margin-bottom",
+  "margin-left",
+  "margin-block",
+  "margin-block-start",
+  "margin-block-end",
+  "margin-inline",
+  "margin-inline-start",
+  "margin-inline-end",
+  "outline",
+  "outline-width",
+  "outline-style",
+  "outline-color",
+  "outline-offset",
+  "border",
+  "border-width",
+  "border-style",
+  "border-color",
+  "border-top",
+  "border-top-width",
+  "border-top-style",
+  "border-top-color",
+  "border-right",
+  "border-right-width",
+  "border-right-style",
+  "border-right-color",
+  "border-bottom",
+  "border-bottom-width",
+  "border-bottom-style",
+  "border-bottom-color",
+  "border-left",
+  "border-left-width",
+  "border-left-style",
+  "border-left-color",
+  "border-block",
+  "border-inline",
+  "border-radius",
+  "border-top-left-radius",
+  "border-top-right-radius",
+  "border-bottom-right-radius",
+  "border-bottom-left-radius",
+  "border-image",
+  "box-shadow",
+  "background",
+  "background-color",
+  "background-image",
+  "background-repeat",
+  "background-attachment",
+  "background-position",
+  "background-position-x",
+  "background-position-y",
+  "background-size",
+  "background-origin",
+  "background-clip",
+  "cursor",
+  "padding",
+  "padding-top",
+  "padding-right",
+  "padding-bottom",
+  "padding-left",
+  "padding-block",
+  "padding-block-start",
+  "padding-block-end",
+  "padding-inline",
+  "padding-inline-start",
+  "padding-inline-end",
+  "width",
+  "min-width",
+  "max-width",
+  "height",
+  "min-height",
+  "max-height",
+  "inline-size",
+  "min-inline-size",
+  "max-inline-size",
+  "block-size",
+  "min-block-size",
+  "max-block-size",
+  "aspect-ratio",
+  "overflow",
+  "overflow-x",
+  "overflow-y",
+  "resize",
+  "list-style",
+  "list-style-type",
+  "list-style-position",
+  "list-style-image",
+  "caption-side",
+  "table-layout",
+  "border-collapse",
+  "border-spacing",
+  "empty-cells",
+  "vertical-align",
+  "text-align",
+  "text-align-last",
+  "text-indent",
+  "text-transform",
+  "text-decoration",
+  "text-decoration-line",
+  "text-decoration-style",
+  "text-decoration-color",
+  "text-decoration-thickness",
+  "text-shadow",
+  "text-overflow",
+  "white-space",
+  "white-space-collapse",
+  "text-wrap",
+  "text-wrap-mode",
+  "text-wrap-style",
+  "word-wrap",
+  "overflow-wrap",
+  "word-break",
+  "hyphens",
+  "letter-spacing",
+  "word-spacing",
+  "line-height",
+  "color",
+  "font",
+  "font-family",
+  "font-size",
+  "font-style",
+  "font-variant",
+  "font-weight",
+  "font-stretch",
+  "content",
+  "quotes",
+];
+
+impl DeclarationOrder {
+  fn rank(&self, property_id: &PropertyId) -> usize {
+    match self {
+      DeclarationOrder::Alphabetical => 0,
+      DeclarationOrder::Concentric => match property_id {
+        PropertyId::Custom(CustomPropertyName::Custom(..)) => 0,
+        _ => {
+          let name = property_id.name();
+          CONCENTRIC_ORDER
+            .iter()
+            .position(|n| *n == name)
+            .map_or(CONCENTRIC_ORDER.len() + 1, |i| i + 1)
+        }
+      },
+    }
+  }
+}
+
+/// Stable insertion sort that only moves a declaration past declarations it does not overlap with.
+fn sort_properties<'i>(properties: &mut Vec<Property<'i>>, order: DeclarationOrder) {
+  let ids: Vec<PropertyId> = properties.iter().map(|property| property.property_id()).collect();
+  let keys: Vec<(usize, &str)> = ids.iter().map(|id| (order.rank(id), id.name())).collect();
+
+  let mut sorted: Vec<usize> = Vec::with_capacity(ids.len());
+  for i in 0..ids.len() {
+    let mut j = sorted.len();
+    while j > 0 && keys[sorted[j - 1]] > keys[i] && !declarations_overlap(&ids[sorted[j - 1]], &ids[i]) {
+      j -= 1;
+    }
+    sorted.insert(j, i);
+  }
+
+  if sorted.iter().enumerate().all(|(i, j)| i == *j) {
+    return;
+  }
+
+  let mut taken: Vec<Option<Property<'i>>> = std::mem::take(properties).into_iter().map(Some).collect();
+  *properties = sorted.into_iter().map(|i| taken[i].take().unwrap()).collect();
+}
+
 /// A CSS declaration block.
 ///
 /// Properties are separated into a list of `!important` declararations,
@@ -59,6 +334,36 @@ pub struct DeclarationBlock<'i> {
   /// A list of normal declarations in the block.
   #[cfg_attr(feature = "serde", serde(default))]
   pub declarations: Vec<Property<'i>>,
+  /// Comments within the block, if the `preserve_comments` parser option is enabled.
+  #[cfg(feature = "comments")]
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub comments: Vec<DeclarationComment<'i>>,
+  /// The source spans of the declarations in the block.
+  #[cfg(feature = "spans")]
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub spans: Vec<DeclarationSpan>,
+}
+
+/// A comment within a declaration block, which is attached to the declaration that follows it.
+///
+/// Comments are identified by their position within the normal or `!important` declarations,
+/// so they may move to a different declaration when the block is minified.
+/// They are only stored in declaration blocks when the `comments` feature is enabled.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct DeclarationComment<'i> {
+  /// The text of the comment, excluding the `/*` and `*/` delimiters.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub text: CowArcStr<'i>,
+  /// Whether the comment is within the `!important` declarations.
+  pub important: bool,
+  /// The index of the declaration that follows the comment. Comments with an index at or past
+  /// the end of the declarations are written after the last one.
+  pub index: usize,
 }
 
 impl<'i> DeclarationBlock<'i> {
@@ -69,6 +374,10 @@ impl<'i> DeclarationBlock<'i> {
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut important_declarations = DeclarationList::new();
     let mut declarations = DeclarationList::new();
+    let mut comments = Vec::new();
+    #[cfg(feature = "spans")]
+    let mut spans = Vec::new();
+    let mut pending_comments = PendingComments::default();
     let mut parser = DeclarationListParser::new(
       input,
       PropertyDeclarationParser {
@@ -77,7 +386,22 @@ impl<'i> DeclarationBlock<'i> {
         options,
       },
     );
-    while let Some(res) = parser.next() {
+    loop {
+      pending_comments.parse(parser.input, options);
+      let lens = (
+        parser.parser.declarations.len(),
+        parser.parser.important_declarations.len(),
+      );
+      #[cfg(feature = "spans")]
+      let start = {
+        parser.input.skip_whitespace();
+        parser.input.state()
+      };
+      let res = match parser.next() {
+        Some(res) => res,
+        None => break,
+      };
+
       if let Err((err, _)) = res {
         if options.error_recovery {
           options.warn(err);
@@ -85,12 +409,32 @@ impl<'i> DeclarationBlock<'i> {
         }
         return Err(err);
       }
+
+      #[cfg(feature = "spans")]
+      if let Some((important, index)) =
+        added_declaration(&parser.parser.declarations, &parser.parser.important_declarations, lens)
+      {
+        spans.push(declaration_span(parser.input, &start, important, index));
+      }
+
+      pending_comments.attach(
+        &mut comments,
+        &parser.parser.declarations,
+        &parser.parser.important_declarations,
+        lens,
+      );
     }
 
-    Ok(DeclarationBlock {
+    let mut block = DeclarationBlock {
       important_declarations,
       declarations,
-    })
+      #[cfg(feature = "comments")]
+      comments,
+      #[cfg(feature = "spans")]
+      spans,
+    };
+    pending_comments.finish(&mut block);
+    Ok(block)
   }
 
   /// Parses a declaration block from a string.
@@ -110,6 +454,10 @@ impl<'i> DeclarationBlock<'i> {
     Self {
       declarations: vec![],
       important_declarations: vec![],
+      #[cfg(feature = "comments")]
+      comments: vec![],
+      #[cfg(feature = "spans")]
+      spans: vec![],
     }
   }
 
@@ -117,6 +465,170 @@ impl<'i> DeclarationBlock<'i> {
   pub fn len(&self) -> usize {
     self.declarations.len() + self.important_declarations.len()
   }
+
+  /// Returns the source span of the declaration at the given index within the normal or
+  /// `!important` declarations, if it was recorded by the parser.
+  #[cfg(feature = "spans")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "spans")))]
+  pub fn span_of(&self, important: bool, index: usize) -> Option<&DeclarationSpan> {
+    self
+      .spans
+      .iter()
+      .find(|span| span.important == important && span.index == index)
+  }
+
+  /// Moves the declarations, comments, and spans of another block to the end of this block.
+  pub(crate) fn append(&mut self, other: &mut DeclarationBlock<'i>) {
+    #[cfg(feature = "comments")]
+    for mut comment in other.comments.drain(..) {
+      comment.index += if comment.important {
+        self.important_declarations.len()
+      } else {
+        self.declarations.len()
+      };
+      self.comments.push(comment);
+    }
+    #[cfg(feature = "spans")]
+    for mut span in other.spans.drain(..) {
+      span.index += if span.important {
+        self.important_declarations.len()
+      } else {
+        self.declarations.len()
+      };
+      self.spans.push(span);
+    }
+    self.declarations.append(&mut other.declarations);
+    self.important_declarations.append(&mut other.important_declarations);
+  }
+
+  /// Returns the comments written before the declaration at the given index within the normal or
+  /// `!important` declarations, or after the last declaration if the index is the length of the list.
+  #[cfg(feature = "comments")]
+  pub(crate) fn comments_at<'a>(
+    &'a self,
+    important: bool,
+    index: usize,
+    minify: bool,
+  ) -> impl Iterator<Item = &'a CowArcStr<'i>> + 'a {
+    let len = if important {
+      self.important_declarations.len()
+    } else {
+      self.declarations.len()
+    };
+    self
+      .comments
+      .iter()
+      .filter(move |comment| {
+        comment.important == important
+          && (comment.index == index || (index == len && comment.index > len))
+          && is_comment_written(&comment.text, minify)
+      })
+      .map(|comment| &comment.text)
+  }
+
+  #[cfg(not(feature = "comments"))]
+  pub(crate) fn comments_at<'a>(
+    &'a self,
+    _important: bool,
+    _index: usize,
+    _minify: bool,
+  ) -> impl Iterator<Item = &'a CowArcStr<'i>> + 'a {
+    std::iter::empty()
+  }
+
+  /// Writes the comments before the declaration at the given index on their own lines.
+  pub(crate) fn write_comments<W>(
+    &self,
+    important: bool,
+    index: usize,
+    dest: &mut Printer<W>,
+  ) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    for comment in self.comments_at(important, index, dest.minify) {
+      dest.newline()?;
+      write_comment(comment, dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// Returns whether the declaration that was just parsed was added to the `!important` declarations,
+/// and its index, given the lengths of the normal and `!important` declarations before parsing it.
+pub(crate) fn added_declaration(
+  declarations: &[Property],
+  important_declarations: &[Property],
+  (len, important_len): (usize, usize),
+) -> Option<(bool, usize)> {
+  if important_declarations.len() > important_len {
+    Some((true, important_len))
+  } else if declarations.len() > len {
+    Some((false, len))
+  } else {
+    None
+  }
+}
+
+/// Comments within a declaration block that have not been attached to a declaration yet.
+#[derive(Default)]
+pub(crate) struct PendingComments<'i>(Vec<CommentRule<'i>>);
+
+impl<'i> PendingComments<'i> {
+  pub(crate) fn push(&mut self, comment: CommentRule<'i>) {
+    self.0.push(comment)
+  }
+
+  /// Consumes the comments before the next declaration, if the `preserve_comments` option is enabled.
+  pub(crate) fn parse(&mut self, input: &mut Parser<'i, '_>, options: &ParserOptions<'_, 'i>) {
+    self.0.extend(parse_comments(input, options))
+  }
+
+  /// Attaches the comments to the declaration that was added to one of the lists since they had the
+  /// given lengths. If no declaration was added, e.g. due to an invalid declaration, they remain pending.
+  pub(crate) fn attach(
+    &mut self,
+    comments: &mut Vec<DeclarationComment<'i>>,
+    declarations: &[Property<'i>],
+    important_declarations: &[Property<'i>],
+    lens: (usize, usize),
+  ) {
+    let (important, index) = match added_declaration(declarations, important_declarations, lens) {
+      Some(added) => added,
+      None => return,
+    };
+
+    comments.extend(self.0.drain(..).map(|comment| DeclarationComment {
+      text: comment.text,
+      important,
+      index,
+    }));
+  }
+
+  /// Attaches the remaining comments after the last declaration in the block.
+  #[cfg(feature = "comments")]
+  pub(crate) fn finish(mut self, block: &mut DeclarationBlock<'i>) {
+    let important = !block.important_declarations.is_empty();
+    let index = if important {
+      block.important_declarations.len()
+    } else {
+      block.declarations.len()
+    };
+    block.comments.extend(self.0.drain(..).map(|comment| DeclarationComment {
+      text: comment.text,
+      important,
+      index,
+    }));
+  }
+
+  /// Without the `comments` feature, comments are not stored in declaration blocks.
+  #[cfg(not(feature = "comments"))]
+  pub(crate) fn finish(self, _block: &mut DeclarationBlock<'i>) {}
+
+  /// Returns the remaining comments as rules, when they are followed by a nested rule rather than a declaration.
+  pub(crate) fn take_rules<T>(&mut self) -> impl Iterator<Item = CssRule<'i, T>> {
+    std::mem::take(&mut self.0).into_iter().map(CssRule::Comment)
+  }
 }
 
 impl<'i> ToCss for DeclarationBlock<'i> {
@@ -129,7 +641,11 @@ impl<'i> ToCss for DeclarationBlock<'i> {
 
     macro_rules! write {
       ($decls: expr, $important: literal) => {
-        for decl in &$decls {
+        for (index, decl) in $decls.iter().enumerate() {
+          for comment in self.comments_at($important, index, dest.minify) {
+            write_comment(comment, dest)?;
+            dest.whitespace()?;
+          }
           decl.to_css(dest, $important)?;
           if i != len - 1 {
             dest.write_char(';')?;
@@ -137,6 +653,17 @@ impl<'i> ToCss for DeclarationBlock<'i> {
           }
           i += 1;
         }
+
+        // Comments after the last declaration are separated from it by a space, since there is no semicolon.
+        for comment in self.comments_at($important, $decls.len(), dest.minify) {
+          if i == len && len > 0 {
+            dest.whitespace()?;
+          }
+          write_comment(comment, dest)?;
+          if i != len {
+            dest.whitespace()?;
+          }
+        }
       };
     }
 
@@ -161,7 +688,8 @@ impl<'i> DeclarationBlock<'i> {
 
     macro_rules! write {
       ($decls: expr, $important: literal) => {
-        for decl in &$decls {
+        for (index, decl) in $decls.iter().enumerate() {
+          self.write_comments($important, index, dest)?;
           dest.newline()?;
           decl.to_css(dest, $important)?;
           if i != len - 1 || !dest.minify {
@@ -169,6 +697,7 @@ impl<'i> DeclarationBlock<'i> {
           }
           i += 1;
         }
+        self.write_comments($important, $decls.len(), dest)?;
       };
     }
 
@@ -208,6 +737,27 @@ impl<'i> DeclarationBlock<'i> {
     important_handler.finalize(context);
     self.important_declarations = std::mem::take(&mut important_handler.decls);
     self.declarations = std::mem::take(&mut handler.decls);
+    #[cfg(feature = "spans")]
+    self.spans.clear();
+  }
+
+  /// Sorts the declarations in the block in the given order. Declarations are never moved
+  /// past a declaration that may set the same value (e.g. a shorthand and one of its longhands,
+  /// or a prefixed and unprefixed property), so the result is equivalent in the cascade.
+  pub(crate) fn sort(&mut self, order: DeclarationOrder) {
+    sort_properties(&mut self.important_declarations, order);
+    sort_properties(&mut self.declarations, order);
+    #[cfg(feature = "spans")]
+    self.spans.clear();
+  }
+
+  /// Removes vendor prefixed declarations that are followed by the same unprefixed property,
+  /// when none of the targets need the prefix anymore.
+  pub(crate) fn remove_obsolete_prefixes(&mut self, targets: Browsers) {
+    remove_obsolete_prefixes(&mut self.important_declarations, targets);
+    remove_obsolete_prefixes(&mut self.declarations, targets);
+    #[cfg(feature = "spans")]
+    self.spans.clear();
   }
 
   /// Returns whether the declaration block is empty.
@@ -403,6 +953,91 @@ impl<'i> DeclarationBlock<'i> {
   }
 }
 
+impl<'i> DeclarationBlock<'i> {
+  /// Returns the typed value of a property, along with whether it is `!important`, e.g.
+  /// `block.get_value::<typed::Color>()` returns a [CssColor](crate::values::color::CssColor).
+  ///
+  /// Like [get](DeclarationBlock::get), shorthand values are combined from their longhands, and
+  /// longhand values are extracted from shorthands.
+  pub fn get_value<P: TypedProperty<'i>>(&self) -> Option<(P::Value, bool)> {
+    let (property, important) = self.get(&P::property_id())?;
+    P::from_property(property.into_owned()).map(|value| (value, important))
+  }
+
+  /// Sets the typed value and importance of a property. See [set](DeclarationBlock::set).
+  pub fn set_value<P: TypedProperty<'i>>(&mut self, value: P::Value, important: bool) {
+    self.set(P::into_property(value), important)
+  }
+
+  /// Removes all declarations of a property. See [remove](DeclarationBlock::remove).
+  pub fn remove_value<P: TypedProperty<'i>>(&mut self) {
+    self.remove(&P::property_id())
+  }
+
+  /// Returns the declarations as `(name, value, important)` tuples, with the names and values
+  /// serialized as strings, in the same order as [iter](DeclarationBlock::iter).
+  pub fn entries(&self) -> Result<Vec<(String, String, bool)>, PrinterError> {
+    self
+      .iter()
+      .map(|(property, important)| {
+        Ok((
+          property.property_id().to_css_string(PrinterOptions::default())?,
+          property.value_to_css_string(PrinterOptions::default())?,
+          important,
+        ))
+      })
+      .collect()
+  }
+
+  /// Returns a map from property names to serialized values. The values of `!important` declarations
+  /// end with `!important`. If a property is declared more than once, the declaration that takes
+  /// precedence is used.
+  pub fn to_map(&self) -> Result<HashMap<String, String>, PrinterError> {
+    let mut map = HashMap::new();
+    for (name, value, important) in self.entries()? {
+      // Important declarations are returned last, so they override normal declarations.
+      if important {
+        map.insert(name, format!("{} !important", value));
+      } else {
+        map.insert(name, value);
+      }
+    }
+    Ok(map)
+  }
+
+  /// Parses a declaration block from a map of property names to values, such as the one returned by
+  /// [to_map](DeclarationBlock::to_map). The declarations are ordered by name.
+  pub fn from_map(
+    map: &'i HashMap<String, String>,
+    options: &ParserOptions<'_, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut names: Vec<&'i String> = map.keys().collect();
+    names.sort();
+
+    let mut block = DeclarationBlock::new();
+    for name in names {
+      let mut input = ParserInput::new(&map[name]);
+      let mut parser = Parser::new(&mut input);
+      let property = parser.parse_until_before(Delimiter::Bang, |input| {
+        Property::parse(PropertyId::from(name.as_str()), input, options)
+      })?;
+      let important = parser
+        .try_parse(|input| {
+          input.expect_delim('!')?;
+          input.expect_ident_matching("important")
+        })
+        .is_ok();
+      parser.expect_exhausted()?;
+      if important {
+        block.important_declarations.push(property);
+      } else {
+        block.declarations.push(property);
+      }
+    }
+    Ok(block)
+  }
+}
+
 struct PropertyDeclarationParser<'a, 'o, 'i> {
   important_declarations: &'a mut Vec<Property<'i>>,
   declarations: &'a mut Vec<Property<'i>>,
@@ -476,6 +1111,7 @@ pub(crate) struct DeclarationHandler<'i> {
   scroll_padding: ScrollPaddingHandler<'i>,
   font: FontHandler<'i>,
   text: TextDecorationHandler<'i>,
+  white_space: WhiteSpaceHandler,
   list: ListStyleHandler<'i>,
   transition: TransitionHandler<'i>,
   animation: AnimationHandler<'i>,
@@ -484,9 +1120,12 @@ pub(crate) struct DeclarationHandler<'i> {
   inset: InsetHandler<'i>,
   overflow: OverflowHandler,
   transform: TransformHandler,
+  offset: OffsetHandler<'i>,
   box_shadow: BoxShadowHandler,
   mask: MaskHandler<'i>,
   container: ContainerHandler<'i>,
+  contain_intrinsic_size: ContainIntrinsicSizeHandler,
+  content_visibility: ContentVisibilityHandler,
   fallback: FallbackHandler,
   prefix: PrefixHandler,
   decls: DeclarationList<'i>,
@@ -508,6 +1147,7 @@ impl<'i> DeclarationHandler<'i> {
       scroll_padding: ScrollPaddingHandler::default(),
       font: FontHandler::default(),
       text: TextDecorationHandler::new(targets),
+      white_space: WhiteSpaceHandler::default(),
       list: ListStyleHandler::new(targets),
       transition: TransitionHandler::new(targets),
       animation: AnimationHandler::new(targets),
@@ -516,9 +1156,12 @@ impl<'i> DeclarationHandler<'i> {
       inset: InsetHandler::default(),
       overflow: OverflowHandler::new(targets),
       transform: TransformHandler::new(targets),
+      offset: OffsetHandler::new(targets),
       box_shadow: BoxShadowHandler::new(targets),
       mask: MaskHandler::default(),
       container: ContainerHandler::default(),
+      contain_intrinsic_size: ContainIntrinsicSizeHandler::new(targets),
+      content_visibility: ContentVisibilityHandler::new(targets),
       fallback: FallbackHandler::new(targets),
       prefix: PrefixHandler::new(targets),
       decls: DeclarationList::new(),
@@ -549,6 +1192,7 @@ impl<'i> DeclarationHandler<'i> {
       || self.scroll_padding.handle_property(property, &mut self.decls, context)
       || self.font.handle_property(property, &mut self.decls, context)
       || self.text.handle_property(property, &mut self.decls, context)
+      || self.white_space.handle_property(property, &mut self.decls, context)
       || self.list.handle_property(property, &mut self.decls, context)
       || self.transition.handle_property(property, &mut self.decls, context)
       || self.animation.handle_property(property, &mut self.decls, context)
@@ -557,9 +1201,12 @@ impl<'i> DeclarationHandler<'i> {
       || self.inset.handle_property(property, &mut self.decls, context)
       || self.overflow.handle_property(property, &mut self.decls, context)
       || self.transform.handle_property(property, &mut self.decls, context)
+      || self.offset.handle_property(property, &mut self.decls, context)
       || self.box_shadow.handle_property(property, &mut self.decls, context)
       || self.mask.handle_property(property, &mut self.decls, context)
       || self.container.handle_property(property, &mut self.decls, context)
+      || self.contain_intrinsic_size.handle_property(property, &mut self.decls, context)
+      || self.content_visibility.handle_property(property, &mut self.decls, context)
       || self.fallback.handle_property(property, &mut self.decls, context)
       || self.prefix.handle_property(property, &mut self.decls, context)
   }
@@ -578,6 +1225,7 @@ impl<'i> DeclarationHandler<'i> {
     self.scroll_padding.finalize(&mut self.decls, context);
     self.font.finalize(&mut self.decls, context);
     self.text.finalize(&mut self.decls, context);
+    self.white_space.finalize(&mut self.decls, context);
     self.list.finalize(&mut self.decls, context);
     self.transition.finalize(&mut self.decls, context);
     self.animation.finalize(&mut self.decls, context);
@@ -586,10 +1234,66 @@ impl<'i> DeclarationHandler<'i> {
     self.inset.finalize(&mut self.decls, context);
     self.overflow.finalize(&mut self.decls, context);
     self.transform.finalize(&mut self.decls, context);
+    self.offset.finalize(&mut self.decls, context);
     self.box_shadow.finalize(&mut self.decls, context);
     self.mask.finalize(&mut self.decls, context);
     self.container.finalize(&mut self.decls, context);
+    self.contain_intrinsic_size.finalize(&mut self.decls, context);
+    self.content_visibility.finalize(&mut self.decls, context);
     self.fallback.finalize(&mut self.decls, context);
     self.prefix.finalize(&mut self.decls, context);
+    context.add_logical_declarations(&mut self.decls);
   }
 }
+
+/// Splits a property id into its vendor prefix and unprefixed name. Unknown properties are
+/// split by name, so that e.g. `-webkit-background-size` is recognized as prefixed.
+fn split_prefix<'a>(id: &'a PropertyId) -> (VendorPrefix, &'a str) {
+  if let PropertyId::Custom(name) = id {
+    let name = name.as_ref();
+    for (prefix, vendor_prefix) in [
+      ("-webkit-", VendorPrefix::WebKit),
+      ("-moz-", VendorPrefix::Moz),
+      ("-ms-", VendorPrefix::Ms),
+      ("-o-", VendorPrefix::O),
+    ] {
+      if let Some(unprefixed) = name.strip_prefix(prefix) {
+        return (vendor_prefix, unprefixed);
+      }
+    }
+    return (VendorPrefix::None, name);
+  }
+
+  let prefix = id.prefix();
+  (if prefix.is_empty() { VendorPrefix::None } else { prefix }, id.name())
+}
+
+/// Returns whether the property has a single vendor prefix that none of the targets need.
+pub(crate) fn is_obsolete_prefix(id: &PropertyId, targets: Browsers) -> bool {
+  let (prefix, name) = split_prefix(id);
+  if prefix.contains(VendorPrefix::None) || prefix.bits().count_ones() != 1 {
+    return false;
+  }
+
+  match Feature::from_property(name) {
+    Some(feature) => !feature.prefixes_for(targets).contains(prefix),
+    None => false,
+  }
+}
+
+fn remove_obsolete_prefixes(properties: &mut Vec<Property>, targets: Browsers) {
+  let ids: Vec<PropertyId> = properties.iter().map(|property| property.property_id()).collect();
+  let mut obsolete = ids.iter().enumerate().map(|(i, id)| {
+    if !is_obsolete_prefix(id, targets) {
+      return false;
+    }
+
+    let (_, name) = split_prefix(id);
+    ids[i + 1..].iter().any(|other| {
+      let (other_prefix, other_name) = split_prefix(other);
+      other_prefix.contains(VendorPrefix::None) && other_name == name
+    })
+  });
+
+  properties.retain(|_| !obsolete.next().unwrap());
+}
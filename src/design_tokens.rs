@@ -0,0 +1,430 @@
+//! Extraction of design tokens from style sheets.
+//!
+//! [StyleSheet::design_tokens](crate::stylesheet::StyleSheet::design_tokens) collects the colors, font stacks,
+//! spacing values, radii, and shadows used by a style sheet into [DesignTokens](DesignTokens). Values defined
+//! by custom properties are named after them, e.g. `--brand` becomes the `brand` token, and other values are
+//! given generated names. Each token records how many declarations use its value directly, so literal values
+//! that should have referenced a token can be found.
+//!
+//! With the `serde` feature, design tokens serialize as a [Design Tokens Format Module](https://tr.designtokens.org/format/)
+//! (DTCG) JSON document, with the usage counts in `$extensions`.
+
+use crate::printer::PrinterOptions;
+use crate::properties::custom::{CustomProperty, CustomPropertyName};
+use crate::properties::font::FontFamily;
+use crate::properties::{Property, PropertyId};
+use crate::rules::CssRuleList;
+use crate::stylesheet::ParserOptions;
+use crate::traits::{Parse, ToCss};
+use crate::values::color::CssColor;
+use crate::values::length::{Length, LengthPercentage};
+
+/// Options for [StyleSheet::design_tokens](crate::stylesheet::StyleSheet::design_tokens).
+#[derive(Debug, Clone, Default)]
+pub struct DesignTokenOptions {
+  /// Whether to only extract values defined by custom properties. Literal values in other
+  /// declarations are still counted as usages of the custom property with the same value.
+  pub custom_properties_only: bool,
+}
+
+/// The design tokens used by a style sheet, grouped by kind.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DesignTokens {
+  /// Colors, e.g. from `color` and `background-color`.
+  pub color: Vec<DesignToken>,
+  /// Font stacks from `font-family` and `font`.
+  pub font_family: Vec<DesignToken>,
+  /// Spacing values from margins, padding, and gaps.
+  pub spacing: Vec<DesignToken>,
+  /// Border radii.
+  pub radius: Vec<DesignToken>,
+  /// Box and text shadows.
+  pub shadow: Vec<DesignToken>,
+}
+
+/// A design token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignToken {
+  /// The name of the token, i.e. the custom property name without the leading `--`,
+  /// or a generated name such as `color-1`.
+  pub name: String,
+  /// The value of the token.
+  pub value: DesignTokenValue,
+  /// Whether the token is defined by a custom property.
+  pub custom_property: bool,
+  /// The number of declarations, other than custom property definitions, that use the value directly.
+  pub usages: usize,
+}
+
+/// The value of a [DesignToken](DesignToken).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DesignTokenValue {
+  /// A color, as a hex string when it is in the sRGB color space.
+  Color(String),
+  /// A list of font family names.
+  FontFamily(Vec<String>),
+  /// A length or percentage.
+  Dimension(String),
+  /// A list of shadows.
+  Shadow(Vec<ShadowToken>),
+}
+
+/// A single shadow within a shadow [DesignToken](DesignToken).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct ShadowToken {
+  /// The color of the shadow.
+  pub color: String,
+  /// The horizontal offset.
+  pub offset_x: String,
+  /// The vertical offset.
+  pub offset_y: String,
+  /// The blur radius.
+  pub blur: String,
+  /// The spread distance.
+  pub spread: String,
+  /// Whether the shadow is inset.
+  #[cfg_attr(
+    any(feature = "serde", feature = "nodejs"),
+    serde(skip_serializing_if = "std::ops::Not::not")
+  )]
+  pub inset: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Group {
+  Color,
+  FontFamily,
+  Spacing,
+  Radius,
+  Shadow,
+}
+
+impl DesignTokens {
+  pub(crate) fn extract<T>(rules: &CssRuleList<T>, options: &DesignTokenOptions) -> DesignTokens {
+    let mut tokens = DesignTokens::default();
+
+    // Custom properties are added first, so that literal values count as usages of them
+    // regardless of where they are defined.
+    rules.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        if let Property::Custom(CustomProperty {
+          name: CustomPropertyName::Custom(name),
+          ..
+        }) = property
+        {
+          if let Ok(value) = property.value_to_css_string(PrinterOptions::default()) {
+            tokens.add_custom_property(&name.0[2..], value.trim());
+          }
+        }
+      }
+    });
+
+    rules.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        tokens.add_property(property, options);
+      }
+    });
+
+    tokens
+  }
+
+  fn group_mut(&mut self, group: Group) -> &mut Vec<DesignToken> {
+    match group {
+      Group::Color => &mut self.color,
+      Group::FontFamily => &mut self.font_family,
+      Group::Spacing => &mut self.spacing,
+      Group::Radius => &mut self.radius,
+      Group::Shadow => &mut self.shadow,
+    }
+  }
+
+  /// Adds a token defined by a custom property, unless a custom property with the same name
+  /// was already added.
+  fn add_custom_property(&mut self, name: &str, value: &str) {
+    let (group, value) = match parse_custom_property(name, value) {
+      Some(token) => token,
+      None => return,
+    };
+
+    let tokens = self.group_mut(group);
+    if tokens.iter().all(|token| token.name != name) {
+      tokens.push(DesignToken {
+        name: name.to_owned(),
+        value,
+        custom_property: true,
+        usages: 0,
+      });
+    }
+  }
+
+  /// Counts the values used by a declaration, and adds tokens for new values unless only
+  /// custom properties are extracted.
+  fn add_property(&mut self, property: &Property, options: &DesignTokenOptions) {
+    if let Some(longhands) = property.property_id().longhands() {
+      for id in longhands {
+        if let Some(longhand) = property.longhand(&id) {
+          self.add_property(&longhand, options);
+        }
+      }
+      return;
+    }
+
+    let (group, value) = match property_value(property) {
+      Some(token) => token,
+      None => return,
+    };
+
+    let tokens = self.group_mut(group);
+    if let Some(token) = tokens.iter_mut().find(|token| token.value == value) {
+      token.usages += 1;
+    } else if !options.custom_properties_only {
+      let prefix = match group {
+        Group::Color => "color",
+        Group::FontFamily => "font-family",
+        Group::Spacing => "spacing",
+        Group::Radius => "radius",
+        Group::Shadow => "shadow",
+      };
+      let index = tokens.iter().filter(|token| !token.custom_property).count() + 1;
+      tokens.push(DesignToken {
+        name: format!("{}-{}", prefix, index),
+        value,
+        custom_property: false,
+        usages: 1,
+      });
+    }
+  }
+}
+
+fn property_value(property: &Property) -> Option<(Group, DesignTokenValue)> {
+  Some(match property {
+    Property::Color(color)
+    | Property::BackgroundColor(color)
+    | Property::BorderTopColor(color)
+    | Property::BorderBottomColor(color)
+    | Property::BorderLeftColor(color)
+    | Property::BorderRightColor(color)
+    | Property::BorderBlockStartColor(color)
+    | Property::BorderBlockEndColor(color)
+    | Property::BorderInlineStartColor(color)
+    | Property::BorderInlineEndColor(color)
+    | Property::OutlineColor(color)
+    | Property::TextDecorationColor(color, _)
+    | Property::TextEmphasisColor(color, _) => (Group::Color, DesignTokenValue::Color(color_value(color)?)),
+    Property::FontFamily(families) => (Group::FontFamily, font_family_value(families)),
+    Property::BoxShadow(shadows, _) => (
+      Group::Shadow,
+      DesignTokenValue::Shadow(
+        shadows
+          .iter()
+          .map(|shadow| {
+            shadow_value(
+              &shadow.color,
+              &shadow.x_offset,
+              &shadow.y_offset,
+              &shadow.blur,
+              &shadow.spread,
+              shadow.inset,
+            )
+          })
+          .collect(),
+      ),
+    ),
+    Property::TextShadow(shadows) => (
+      Group::Shadow,
+      DesignTokenValue::Shadow(
+        shadows
+          .iter()
+          .map(|shadow| {
+            shadow_value(
+              &shadow.color,
+              &shadow.x_offset,
+              &shadow.y_offset,
+              &shadow.blur,
+              &shadow.spread,
+              false,
+            )
+          })
+          .collect(),
+      ),
+    ),
+    Property::Unparsed(..) | Property::Custom(..) => return None,
+    property => {
+      let group = match property.property_id().name() {
+        "row-gap" | "column-gap" => Group::Spacing,
+        name if name.starts_with("margin-") || name.starts_with("padding-") => Group::Spacing,
+        name if name.ends_with("-radius") => Group::Radius,
+        _ => return None,
+      };
+      let value = property.value_to_css_string(PrinterOptions::default()).ok()?;
+      if value == "auto" || value == "normal" {
+        return None;
+      }
+      (group, DesignTokenValue::Dimension(value))
+    }
+  })
+}
+
+fn parse_custom_property(name: &str, value: &str) -> Option<(Group, DesignTokenValue)> {
+  if let Ok(Property::Color(color)) = Property::parse_string(PropertyId::Color, value, ParserOptions::default()) {
+    return Some((Group::Color, DesignTokenValue::Color(color_value(&color)?)));
+  }
+
+  if let Ok(property @ Property::BoxShadow(..)) =
+    Property::parse_string("box-shadow".into(), value, ParserOptions::default())
+  {
+    return property_value(&property);
+  }
+
+  if LengthPercentage::parse_string(value).is_ok() {
+    let group = if name.contains("radius") || name.contains("radii") || name.contains("rounded") {
+      Group::Radius
+    } else {
+      Group::Spacing
+    };
+    return Some((group, DesignTokenValue::Dimension(value.to_owned())));
+  }
+
+  if name.contains("font") || name.contains("family") {
+    if let Ok(Property::FontFamily(families)) =
+      Property::parse_string(PropertyId::FontFamily, value, ParserOptions::default())
+    {
+      return Some((Group::FontFamily, font_family_value(&families)));
+    }
+  }
+
+  None
+}
+
+fn color_value(color: &CssColor) -> Option<String> {
+  match color {
+    CssColor::CurrentColor | CssColor::LightDark { .. } => None,
+    CssColor::RGBA(rgba) if rgba.alpha == 255 => {
+      Some(format!("#{:02x}{:02x}{:02x}", rgba.red, rgba.green, rgba.blue))
+    }
+    CssColor::RGBA(rgba) => Some(format!(
+      "#{:02x}{:02x}{:02x}{:02x}",
+      rgba.red, rgba.green, rgba.blue, rgba.alpha
+    )),
+    color => color.to_css_string(PrinterOptions::default()).ok(),
+  }
+}
+
+fn font_family_value(families: &[FontFamily]) -> DesignTokenValue {
+  DesignTokenValue::FontFamily(
+    families
+      .iter()
+      .map(|family| match family {
+        FontFamily::FamilyName(name) => name.to_string(),
+        family => family.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+      })
+      .collect(),
+  )
+}
+
+fn shadow_value(
+  color: &CssColor,
+  offset_x: &Length,
+  offset_y: &Length,
+  blur: &Length,
+  spread: &Length,
+  inset: bool,
+) -> ShadowToken {
+  let length = |length: &Length| length.to_css_string(PrinterOptions::default()).unwrap_or_default();
+  ShadowToken {
+    color: color_value(color).unwrap_or_else(|| "currentColor".into()),
+    offset_x: length(offset_x),
+    offset_y: length(offset_y),
+    blur: length(blur),
+    spread: length(spread),
+    inset,
+  }
+}
+
+#[cfg(any(feature = "serde", feature = "nodejs"))]
+mod serialize {
+  use super::*;
+  use serde::ser::{Serialize, SerializeMap, Serializer};
+
+  impl Serialize for DesignTokens {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      let groups = [
+        ("color", &self.color),
+        ("fontFamily", &self.font_family),
+        ("spacing", &self.spacing),
+        ("radius", &self.radius),
+        ("shadow", &self.shadow),
+      ];
+      let mut map = serializer.serialize_map(None)?;
+      for (name, tokens) in groups {
+        if !tokens.is_empty() {
+          map.serialize_entry(name, &TokenGroup(tokens))?;
+        }
+      }
+      map.end()
+    }
+  }
+
+  struct TokenGroup<'a>(&'a [DesignToken]);
+
+  impl<'a> Serialize for TokenGroup<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      let mut map = serializer.serialize_map(Some(self.0.len()))?;
+      for token in self.0 {
+        map.serialize_entry(&token.name, token)?;
+      }
+      map.end()
+    }
+  }
+
+  #[derive(serde::Serialize)]
+  #[serde(rename_all = "camelCase")]
+  struct Extension {
+    usages: usize,
+    custom_property: bool,
+  }
+
+  impl Serialize for DesignToken {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      let mut map = serializer.serialize_map(Some(3))?;
+      match &self.value {
+        DesignTokenValue::Color(value) => {
+          map.serialize_entry("$type", "color")?;
+          map.serialize_entry("$value", value)?;
+        }
+        DesignTokenValue::FontFamily(value) => {
+          map.serialize_entry("$type", "fontFamily")?;
+          map.serialize_entry("$value", value)?;
+        }
+        DesignTokenValue::Dimension(value) => {
+          map.serialize_entry("$type", "dimension")?;
+          map.serialize_entry("$value", value)?;
+        }
+        DesignTokenValue::Shadow(value) => {
+          map.serialize_entry("$type", "shadow")?;
+          if value.len() == 1 {
+            map.serialize_entry("$value", &value[0])?;
+          } else {
+            map.serialize_entry("$value", value)?;
+          }
+        }
+      }
+
+      let mut extensions = std::collections::BTreeMap::new();
+      extensions.insert(
+        "dev.lightningcss",
+        Extension {
+          usages: self.usages,
+          custom_property: self.custom_property,
+        },
+      );
+      map.serialize_entry("$extensions", &extensions)?;
+      map.end()
+    }
+  }
+}
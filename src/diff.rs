@@ -0,0 +1,372 @@
+//! Structural differences between style sheets.
+//!
+//! [StyleSheet::diff](crate::stylesheet::StyleSheet::diff) compares two versions of a style sheet, and returns a
+//! [StyleSheetDiff](StyleSheetDiff) listing the rules that were added or removed, and the declarations that
+//! changed within style rules. This allows hot-reload servers to send only the changes to a client, and
+//! visual regression tools to explain what changed between two builds.
+//!
+//! Rules are matched by their selectors, or by their prelude for at-rules with child rules such as `@media`,
+//! so that changes within a rule are reported as changes to its declarations or child rules rather than as
+//! a replaced rule. Other rules, such as `@font-face`, are matched only if they are identical. Rules and
+//! declarations are compared by their serialization, ignoring source locations.
+//!
+//! The paths of the changes are lists of indices, as in the [cssom](crate::cssom) module, and refer to the
+//! style sheet as it is after the previous changes in the list are applied.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   diff::RuleChange,
+//!   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+//! };
+//!
+//! let mut old = StyleSheet::parse(".foo { color: red } .bar { color: red }", ParserOptions::default()).unwrap();
+//! let new = StyleSheet::parse(".foo { color: green } .baz { color: red }", ParserOptions::default()).unwrap();
+//!
+//! let diff = old.diff(&new);
+//! assert_eq!(diff.changes.len(), 3);
+//! assert!(matches!(&diff.changes[0], RuleChange::Removed { path, .. } if path == &[1]));
+//!
+//! diff.apply(&mut old).unwrap();
+//! let res = old.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{color:green}.baz{color:red}");
+//! ```
+
+use crate::cssom::RuleListError;
+use crate::declaration::DeclarationBlock;
+use crate::parser::DefaultAtRule;
+use crate::printer::PrinterOptions;
+use crate::properties::{Property, PropertyId};
+use crate::query::serialize;
+use crate::rules::{CssRule, CssRuleList};
+use crate::stylesheet::StyleSheet;
+use crate::traits::ToCss;
+
+/// The changes between two style sheets, returned by [StyleSheet::diff](crate::stylesheet::StyleSheet::diff).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StyleSheetDiff<'i, T = DefaultAtRule> {
+  /// The changes, in the order they must be applied.
+  pub changes: Vec<RuleChange<'i, T>>,
+}
+
+/// A change to a rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+pub enum RuleChange<'i, T = DefaultAtRule> {
+  /// A rule was added.
+  Added {
+    /// The path of the new rule.
+    path: Vec<usize>,
+    /// The new rule.
+    rule: CssRule<'i, T>,
+  },
+  /// A rule was removed.
+  Removed {
+    /// The path of the removed rule.
+    path: Vec<usize>,
+    /// The removed rule.
+    rule: CssRule<'i, T>,
+  },
+  /// The declarations of a style rule changed.
+  Declarations {
+    /// The path of the style rule.
+    path: Vec<usize>,
+    /// The changed declarations.
+    changes: Vec<DeclarationChange<'i>>,
+  },
+}
+
+/// A change to the declarations of a property within a style rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "camelCase"))]
+pub struct DeclarationChange<'i> {
+  /// The property that changed.
+  pub property_id: PropertyId<'i>,
+  /// Whether the changed declarations are `!important`.
+  pub important: bool,
+  /// The previous declarations of the property, which is empty if the property was added. There may be more
+  /// than one, e.g. when fallbacks are declared for older browsers.
+  pub old: Vec<Property<'i>>,
+  /// The new declarations of the property, which is empty if the property was removed.
+  pub new: Vec<Property<'i>>,
+}
+
+impl<'i, T> StyleSheetDiff<'i, T> {
+  /// Returns whether the style sheets are equivalent.
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+
+  /// Applies the changes to a style sheet, which should be equivalent to the one the diff was created from.
+  pub fn apply(&self, stylesheet: &mut StyleSheet<'i, '_, T>) -> Result<(), RuleListError>
+  where
+    T: Clone,
+  {
+    for change in &self.changes {
+      change.apply(&mut stylesheet.rules)?;
+    }
+    Ok(())
+  }
+}
+
+impl<'i, T: Clone> RuleChange<'i, T> {
+  fn apply(&self, rules: &mut CssRuleList<'i, T>) -> Result<(), RuleListError> {
+    match self {
+      RuleChange::Added { path, rule } => {
+        let (index, parent) = path.split_last().ok_or(RuleListError::InvalidPath)?;
+        parent_rules(rules, parent)?.insert_rule(*index, rule.clone())?;
+      }
+      RuleChange::Removed { path, .. } => {
+        let (index, parent) = path.split_last().ok_or(RuleListError::InvalidPath)?;
+        parent_rules(rules, parent)?.delete_rule(*index)?;
+      }
+      RuleChange::Declarations { path, changes } => {
+        let declarations = match rules.rule_at_mut(path) {
+          Some(CssRule::Style(style)) => &mut style.declarations,
+          Some(CssRule::Nesting(nesting)) => &mut nesting.style.declarations,
+          _ => return Err(RuleListError::InvalidPath),
+        };
+
+        for change in changes {
+          change.apply(declarations);
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<'i> DeclarationChange<'i> {
+  fn apply(&self, block: &mut DeclarationBlock<'i>) {
+    let declarations = if self.important {
+      &mut block.important_declarations
+    } else {
+      &mut block.declarations
+    };
+
+    // The new declarations replace the first of the previous ones.
+    let index = declarations
+      .iter()
+      .position(|property| property.property_id() == self.property_id)
+      .unwrap_or(declarations.len());
+    declarations.retain(|property| property.property_id() != self.property_id);
+    declarations.splice(index..index, self.new.iter().cloned());
+  }
+}
+
+fn parent_rules<'a, 'i, T>(
+  rules: &'a mut CssRuleList<'i, T>,
+  path: &[usize],
+) -> Result<&'a mut CssRuleList<'i, T>, RuleListError> {
+  if path.is_empty() {
+    return Ok(rules);
+  }
+
+  rules
+    .rule_at_mut(path)
+    .and_then(|rule| rule.child_rules_mut())
+    .ok_or(RuleListError::InvalidPath)
+}
+
+/// Adds the changes between two rule lists, with the given path prefix.
+pub(crate) fn diff_rules<'i, T: ToCss + Clone>(
+  old: &CssRuleList<'i, T>,
+  new: &CssRuleList<'i, T>,
+  path: &mut Vec<usize>,
+  changes: &mut Vec<RuleChange<'i, T>>,
+) {
+  let old_keys: Vec<String> = old.0.iter().map(rule_key).collect();
+  let new_keys: Vec<String> = new.0.iter().map(rule_key).collect();
+  let pairs = matching_pairs(&old_keys, &new_keys);
+
+  // Rules are removed from the end, so that the indices of earlier rules are unaffected, and then added
+  // in order, at which point the list contains the matched rules in their new positions.
+  let mut matched = pairs.iter().rev().peekable();
+  for index in (0..old.0.len()).rev() {
+    if matched.peek().map_or(false, |(i, _)| *i == index) {
+      matched.next();
+      continue;
+    }
+
+    path.push(index);
+    changes.push(RuleChange::Removed {
+      path: path.clone(),
+      rule: old.0[index].clone(),
+    });
+    path.pop();
+  }
+
+  let mut matched = pairs.iter().peekable();
+  for index in 0..new.0.len() {
+    if matched.peek().map_or(false, |(_, j)| *j == index) {
+      matched.next();
+      continue;
+    }
+
+    path.push(index);
+    changes.push(RuleChange::Added {
+      path: path.clone(),
+      rule: new.0[index].clone(),
+    });
+    path.pop();
+  }
+
+  for (i, j) in pairs {
+    path.push(j);
+    diff_rule(&old.0[i], &new.0[j], path, changes);
+    path.pop();
+  }
+}
+
+/// Adds the changes between two rules with the same key.
+fn diff_rule<'i, T: ToCss + Clone>(
+  old: &CssRule<'i, T>,
+  new: &CssRule<'i, T>,
+  path: &mut Vec<usize>,
+  changes: &mut Vec<RuleChange<'i, T>>,
+) {
+  let declarations = match (old, new) {
+    (CssRule::Style(old), CssRule::Style(new)) => Some((&old.declarations, &new.declarations)),
+    (CssRule::Nesting(old), CssRule::Nesting(new)) => Some((&old.style.declarations, &new.style.declarations)),
+    _ => None,
+  };
+
+  if let Some((old, new)) = declarations {
+    let declaration_changes = diff_declarations(old, new);
+    if !declaration_changes.is_empty() {
+      changes.push(RuleChange::Declarations {
+        path: path.clone(),
+        changes: declaration_changes,
+      });
+    }
+  }
+
+  if let (Some(old), Some(new)) = (old.child_rules(), new.child_rules()) {
+    diff_rules(old, new, path, changes);
+  }
+}
+
+/// Returns the key used to match rules. Style rules are matched by their selectors, rules with child rules
+/// by their serialization without the child rules, and other rules by their serialization.
+fn rule_key<T: ToCss + Clone>(rule: &CssRule<'_, T>) -> String {
+  match rule {
+    CssRule::Style(style) => format!("style {}", serialize(&style.selectors)),
+    CssRule::Nesting(nesting) => format!("nest {}", serialize(&nesting.style.selectors)),
+    _ if rule.child_rules().is_some() => {
+      let mut header = rule.clone();
+      if let Some(rules) = header.child_rules_mut() {
+        rules.0.clear();
+      }
+      serialize(&header)
+    }
+    _ => serialize(rule),
+  }
+}
+
+/// Returns the pairs of indices of matching keys, using the longest common subsequence.
+fn matching_pairs(old: &[String], new: &[String]) -> Vec<(usize, usize)> {
+  // Skip the common prefix and suffix, which are usually most of the style sheet.
+  let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+  let suffix = old[prefix..]
+    .iter()
+    .rev()
+    .zip(new[prefix..].iter().rev())
+    .take_while(|(a, b)| a == b)
+    .count();
+  let old_middle = &old[prefix..old.len() - suffix];
+  let new_middle = &new[prefix..new.len() - suffix];
+
+  // lengths[i][j] is the length of the longest common subsequence of old_middle[i..] and new_middle[j..].
+  let width = new_middle.len() + 1;
+  let mut lengths = vec![0u32; (old_middle.len() + 1) * width];
+  for i in (0..old_middle.len()).rev() {
+    for j in (0..new_middle.len()).rev() {
+      lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+        lengths[(i + 1) * width + j + 1] + 1
+      } else {
+        lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+      };
+    }
+  }
+
+  let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+  let (mut i, mut j) = (0, 0);
+  while i < old_middle.len() && j < new_middle.len() {
+    if old_middle[i] == new_middle[j] {
+      pairs.push((prefix + i, prefix + j));
+      i += 1;
+      j += 1;
+    } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+      i += 1;
+    } else {
+      j += 1;
+    }
+  }
+
+  pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+  pairs
+}
+
+/// Returns the changes between two declaration blocks, grouping the declarations of each property.
+fn diff_declarations<'i>(old: &DeclarationBlock<'i>, new: &DeclarationBlock<'i>) -> Vec<DeclarationChange<'i>> {
+  let mut changes = Vec::new();
+  for important in [false, true] {
+    let (old, new) = if important {
+      (&old.important_declarations, &new.important_declarations)
+    } else {
+      (&old.declarations, &new.declarations)
+    };
+
+    let old_groups = group_declarations(old);
+    let new_groups = group_declarations(new);
+    for (property_id, new) in &new_groups {
+      let old = old_groups
+        .iter()
+        .find(|(id, _)| id == property_id)
+        .map_or(&[][..], |(_, old)| &old[..]);
+      if !declarations_equal(old, new) {
+        changes.push(DeclarationChange {
+          property_id: property_id.clone(),
+          important,
+          old: old.iter().map(|property| (*property).clone()).collect(),
+          new: new.iter().map(|property| (*property).clone()).collect(),
+        });
+      }
+    }
+
+    for (property_id, old) in &old_groups {
+      if !new_groups.iter().any(|(id, _)| id == property_id) {
+        changes.push(DeclarationChange {
+          property_id: property_id.clone(),
+          important,
+          old: old.iter().map(|property| (*property).clone()).collect(),
+          new: vec![],
+        });
+      }
+    }
+  }
+  changes
+}
+
+fn group_declarations<'a, 'i>(declarations: &'a [Property<'i>]) -> Vec<(PropertyId<'i>, Vec<&'a Property<'i>>)> {
+  let mut groups: Vec<(PropertyId<'i>, Vec<&'a Property<'i>>)> = Vec::new();
+  for property in declarations {
+    let property_id = property.property_id();
+    match groups.iter_mut().find(|(id, _)| *id == property_id) {
+      Some((_, group)) => group.push(property),
+      None => groups.push((property_id, vec![property])),
+    }
+  }
+  groups
+}
+
+fn declarations_equal(a: &[&Property], b: &[&Property]) -> bool {
+  let print = |property: &&Property| property.to_css_string(false, PrinterOptions::default()).ok();
+  a.len() == b.len() && a.iter().map(print).eq(b.iter().map(print))
+}
@@ -0,0 +1,273 @@
+//! Reports of duplicated rules, selectors, and declarations.
+//!
+//! [StyleSheet::duplicate_report](crate::stylesheet::StyleSheet::duplicate_report) finds style rules with
+//! identical declaration blocks, selectors that are repeated across the style sheet, and declarations that are
+//! repeated within the same `@media`, `@supports`, `@container`, or `@layer` scope. Each group of duplicates
+//! includes its minified size in bytes, along with the number of bytes that the repetitions add to the output,
+//! to guide refactoring even when merging the rules automatically would be unsafe, e.g. because doing so would
+//! change the cascade order.
+
+use crate::declaration::DeclarationBlock;
+use crate::printer::PrinterOptions;
+use crate::properties::Property;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::SelectorList;
+use crate::traits::ToCss;
+use std::collections::HashMap;
+
+/// Duplicated content within a style sheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct DuplicateReport {
+  /// Style rules with identical declaration blocks, regardless of their selectors or scope.
+  pub declaration_blocks: Vec<DuplicateGroup>,
+  /// Selectors that appear in more than one style rule, regardless of scope. Selectors of nested style rules
+  /// are relative to their parent, so they are not included.
+  pub selectors: Vec<DuplicateGroup>,
+  /// Declarations that appear in more than one style rule within the same scope.
+  pub declarations: Vec<DuplicateGroup>,
+}
+
+/// A group of duplicates of the same content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct DuplicateGroup {
+  /// The minified content, e.g. `color:red;width:10px` for a declaration block.
+  pub content: String,
+  /// The size of the minified content in bytes.
+  pub bytes: usize,
+  /// The number of bytes added by the repetitions, i.e. the size of all occurrences except the first.
+  pub redundant_bytes: usize,
+  /// The style rules containing the content, in source order.
+  pub occurrences: Vec<DuplicateOccurrence>,
+}
+
+/// A style rule containing duplicated content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct DuplicateOccurrence {
+  /// The selectors of the style rule.
+  pub selectors: String,
+  /// The preludes of the at-rules enclosing the style rule, outermost first, e.g. `@media (min-width: 500px)`.
+  pub scope: Vec<String>,
+  /// The location of the style rule.
+  pub loc: Location,
+}
+
+impl DuplicateReport {
+  pub(crate) fn build<T>(rules: &CssRuleList<T>) -> DuplicateReport {
+    let mut collector = Collector::default();
+    collector.add_rules(rules, &mut Vec::new(), None);
+    DuplicateReport {
+      declaration_blocks: collector.declaration_blocks.finish(),
+      selectors: collector.selectors.finish(),
+      declarations: collector.declarations.finish(),
+    }
+  }
+
+  /// Returns the total number of bytes added by all duplicated declaration blocks, selectors, and declarations.
+  /// Since declarations within duplicated declaration blocks are also counted individually, this overestimates
+  /// the savings that refactoring could achieve.
+  pub fn redundant_bytes(&self) -> usize {
+    self
+      .declaration_blocks
+      .iter()
+      .chain(&self.selectors)
+      .chain(&self.declarations)
+      .map(|group| group.redundant_bytes)
+      .sum()
+  }
+}
+
+#[derive(Default)]
+struct Groups {
+  groups: Vec<DuplicateGroup>,
+  indices: HashMap<(Vec<String>, String), usize>,
+}
+
+impl Groups {
+  fn add(&mut self, key_scope: Vec<String>, content: String, occurrence: DuplicateOccurrence) {
+    let groups = &mut self.groups;
+    let index = *self.indices.entry((key_scope, content.clone())).or_insert_with(|| {
+      groups.push(DuplicateGroup {
+        bytes: content.len(),
+        content,
+        redundant_bytes: 0,
+        occurrences: Vec::new(),
+      });
+      groups.len() - 1
+    });
+    self.groups[index].occurrences.push(occurrence);
+  }
+
+  /// Returns the groups with more than one occurrence, those adding the most bytes first.
+  fn finish(self) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<_> = self
+      .groups
+      .into_iter()
+      .filter(|group| group.occurrences.len() > 1)
+      .map(|mut group| {
+        group.redundant_bytes = group.bytes * (group.occurrences.len() - 1);
+        group
+      })
+      .collect();
+    groups.sort_by(|a, b| b.redundant_bytes.cmp(&a.redundant_bytes));
+    groups
+  }
+}
+
+#[derive(Default)]
+struct Collector {
+  declaration_blocks: Groups,
+  selectors: Groups,
+  declarations: Groups,
+}
+
+impl Collector {
+  fn add_rules<T>(&mut self, rules: &CssRuleList<T>, scope: &mut Vec<String>, parent: Option<&SelectorList>) {
+    for rule in &rules.0 {
+      match rule {
+        CssRule::Style(style) => self.add_style_rule(style, scope, parent.is_some()),
+        CssRule::Nesting(nesting) => self.add_style_rule(&nesting.style, scope, true),
+        CssRule::NestedDeclarations(nested) => {
+          if let Some(parent) = parent {
+            self.add_declarations(&nested.declarations, parent, scope, nested.loc);
+          }
+        }
+        CssRule::Media(media) => {
+          let prelude = format!("@media {}", to_string(&media.query, false));
+          self.add_scoped_rules(prelude, &media.rules, scope, parent);
+        }
+        CssRule::Supports(supports) => {
+          let prelude = format!("@supports {}", to_string(&supports.condition, false));
+          self.add_scoped_rules(prelude, &supports.rules, scope, parent);
+        }
+        CssRule::Container(container) => {
+          let prelude = match &container.name {
+            Some(name) => format!(
+              "@container {} {}",
+              to_string(name, false),
+              to_string(&container.condition, false)
+            ),
+            None => format!("@container {}", to_string(&container.condition, false)),
+          };
+          self.add_scoped_rules(prelude, &container.rules, scope, parent);
+        }
+        CssRule::LayerBlock(layer) => {
+          let prelude = match &layer.name {
+            Some(name) => format!("@layer {}", to_string(name, false)),
+            None => "@layer".into(),
+          };
+          self.add_scoped_rules(prelude, &layer.rules, scope, parent);
+        }
+        CssRule::Scope(rule) => {
+          let mut prelude = String::from("@scope");
+          if let Some(scope_start) = &rule.scope_start {
+            prelude.push_str(&format!(" ({})", to_string(scope_start, false)));
+          }
+          if let Some(scope_end) = &rule.scope_end {
+            prelude.push_str(&format!(" to ({})", to_string(scope_end, false)));
+          }
+          self.add_scoped_rules(prelude, &rule.rules, scope, parent);
+        }
+        CssRule::StartingStyle(rule) => {
+          self.add_scoped_rules("@starting-style".into(), &rule.rules, scope, parent);
+        }
+        _ => {}
+      }
+    }
+  }
+
+  fn add_scoped_rules<T>(
+    &mut self,
+    prelude: String,
+    rules: &CssRuleList<T>,
+    scope: &mut Vec<String>,
+    parent: Option<&SelectorList>,
+  ) {
+    scope.push(prelude);
+    self.add_rules(rules, scope, parent);
+    scope.pop();
+  }
+
+  fn add_style_rule<T>(&mut self, style: &StyleRule<T>, scope: &mut Vec<String>, is_nested: bool) {
+    if !style.declarations.is_empty() {
+      let content = style
+        .declarations
+        .iter()
+        .map(|(property, important)| to_minified_declaration(property, important))
+        .collect::<Vec<_>>()
+        .join(";");
+      self
+        .declaration_blocks
+        .add(Vec::new(), content, occurrence(&style.selectors, scope, style.loc));
+    }
+
+    if !is_nested {
+      for selector in &style.selectors.0 {
+        self.selectors.add(
+          Vec::new(),
+          to_string(selector, true),
+          occurrence(&style.selectors, scope, style.loc),
+        );
+      }
+    }
+
+    self.add_declarations(&style.declarations, &style.selectors, scope, style.loc);
+    self.add_rules(&style.rules, scope, Some(&style.selectors));
+  }
+
+  fn add_declarations(
+    &mut self,
+    declarations: &DeclarationBlock,
+    selectors: &SelectorList,
+    scope: &[String],
+    loc: Location,
+  ) {
+    for (property, important) in declarations.iter() {
+      self.declarations.add(
+        scope.to_vec(),
+        to_minified_declaration(property, important),
+        occurrence(selectors, scope, loc),
+      );
+    }
+  }
+}
+
+fn occurrence(selectors: &SelectorList, scope: &[String], loc: Location) -> DuplicateOccurrence {
+  DuplicateOccurrence {
+    selectors: to_string(selectors, false),
+    scope: scope.to_vec(),
+    loc,
+  }
+}
+
+fn to_string<V: ToCss>(value: &V, minify: bool) -> String {
+  value
+    .to_css_string(PrinterOptions {
+      minify,
+      ..PrinterOptions::default()
+    })
+    .unwrap_or_default()
+}
+
+fn to_minified_declaration(property: &Property, important: bool) -> String {
+  property
+    .to_css_string(
+      important,
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    )
+    .unwrap_or_default()
+}
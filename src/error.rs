@@ -33,6 +33,67 @@ impl<T: fmt::Display> fmt::Display for Error<T> {
 
 impl<T: fmt::Display + fmt::Debug> std::error::Error for Error<T> {}
 
+/// A stable, machine-readable identifier and suggestion for each kind of error.
+///
+/// Codes are kebab-case strings such as `invalid-declaration`, and do not change between releases, so
+/// tools can match on them rather than on the error message, which may be reworded.
+pub trait ErrorCode: fmt::Display {
+  /// Returns the code of the error.
+  fn code(&self) -> &'static str;
+
+  /// Returns a suggestion for fixing the error, if any.
+  fn help(&self) -> Option<&'static str>;
+}
+
+impl<T: ErrorCode> Error<T> {
+  /// Returns the code of the error. See [ErrorCode](ErrorCode).
+  pub fn code(&self) -> &'static str {
+    self.kind.code()
+  }
+
+  /// Returns a suggestion for fixing the error, if any.
+  pub fn help(&self) -> Option<&'static str> {
+    self.kind.help()
+  }
+
+  /// Returns a serializable report of the error, including its code, message, and suggestion.
+  pub fn report(&self) -> ErrorReport {
+    ErrorReport {
+      code: self.kind.code(),
+      message: self.kind.to_string(),
+      help: self.kind.help(),
+      loc: self.loc.clone(),
+    }
+  }
+}
+
+/// A report of an error, returned by [Error::report](Error::report), for rendering by downstream tools.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+pub struct ErrorReport {
+  /// The code of the error. See [ErrorCode](ErrorCode).
+  pub code: &'static str,
+  /// The error message.
+  pub message: String,
+  /// A suggestion for fixing the error, if any.
+  pub help: Option<&'static str>,
+  /// The location where the error occurred.
+  pub loc: Option<ErrorLocation>,
+}
+
+impl fmt::Display for ErrorReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}[{}]", self.message, self.code)?;
+    if let Some(loc) = &self.loc {
+      write!(f, " at {}", loc)?;
+    }
+    if let Some(help) = self.help {
+      write!(f, "\nhelp: {}", help)?;
+    }
+    Ok(())
+  }
+}
+
 /// A line and column location within a source file.
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
@@ -101,6 +162,22 @@ pub enum ParserError<'i> {
   UnexpectedToken(#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(skip))] Token<'i>),
   /// Maximum nesting depth was reached.
   MaximumNestingDepth,
+  /// A named grid area in `grid-template-areas` does not form a rectangle.
+  NonRectangularGridArea(CowArcStr<'i>),
+  /// The scoping limit of an `@scope` rule was dropped while downleveling it for unsupported targets.
+  UnsupportedScopeLimit,
+  /// An `@starting-style` rule is not supported by the configured targets.
+  UnsupportedStartingStyle,
+  /// `transition-behavior: allow-discrete` is not supported by the configured targets.
+  UnsupportedAllowDiscrete,
+  /// Anchor positioning is not supported by the configured targets.
+  UnsupportedAnchorPositioning,
+  /// The `view-transition-name` or `view-transition-class` property is not supported by the configured targets.
+  UnsupportedViewTransition,
+  /// Masonry layout is not supported by the configured targets.
+  UnsupportedMasonry,
+  /// `field-sizing: content` is not supported by the configured targets.
+  UnsupportedFieldSizing,
 }
 
 impl<'i> fmt::Display for ParserError<'i> {
@@ -129,6 +206,103 @@ impl<'i> fmt::Display for ParserError<'i> {
       ),
       UnexpectedToken(token) => write!(f, "Unexpected token {:?}", token),
       MaximumNestingDepth => write!(f, "Overflowed the maximum nesting depth"),
+      NonRectangularGridArea(name) => write!(f, "Grid area \"{}\" is not rectangular", name),
+      UnsupportedScopeLimit => write!(
+        f,
+        "The scoping limit of an @scope rule cannot be downleveled for the configured targets and was ignored"
+      ),
+      UnsupportedStartingStyle => write!(
+        f,
+        "@starting-style is not supported by the configured targets, so elements will not transition from these styles"
+      ),
+      UnsupportedAllowDiscrete => write!(
+        f,
+        "transition-behavior: allow-discrete is not supported by the configured targets, so discrete properties will not transition"
+      ),
+      UnsupportedAnchorPositioning => write!(
+        f,
+        "Anchor positioning is not supported by the configured targets, so anchored elements will not be positioned"
+      ),
+      UnsupportedViewTransition => write!(
+        f,
+        "View transitions are not supported by the configured targets, so elements will not be animated independently"
+      ),
+      UnsupportedMasonry => write!(
+        f,
+        "Masonry layout is not supported by the configured targets, so masonry containers will be laid out as regular grids"
+      ),
+      UnsupportedFieldSizing => write!(
+        f,
+        "field-sizing: content is not supported by the configured targets, so form controls will keep their default size"
+      ),
+    }
+  }
+}
+
+impl<'i> ErrorCode for ParserError<'i> {
+  fn code(&self) -> &'static str {
+    use ParserError::*;
+    match self {
+      AtRuleBodyInvalid => "at-rule-body-invalid",
+      AtRulePreludeInvalid => "at-rule-prelude-invalid",
+      AtRuleInvalid(..) => "at-rule-invalid",
+      EndOfInput => "end-of-input",
+      InvalidDeclaration => "invalid-declaration",
+      InvalidMediaQuery => "invalid-media-query",
+      InvalidNesting => "invalid-nesting",
+      DeprecatedNestRule => "deprecated-nest-rule",
+      InvalidPageSelector => "invalid-page-selector",
+      InvalidValue => "invalid-value",
+      QualifiedRuleInvalid => "qualified-rule-invalid",
+      SelectorError(s) => s.code(),
+      UnexpectedImportRule => "unexpected-import-rule",
+      UnexpectedNamespaceRule => "unexpected-namespace-rule",
+      UnexpectedToken(..) => "unexpected-token",
+      MaximumNestingDepth => "maximum-nesting-depth",
+      NonRectangularGridArea(..) => "non-rectangular-grid-area",
+      UnsupportedScopeLimit => "unsupported-scope-limit",
+      UnsupportedStartingStyle => "unsupported-starting-style",
+      UnsupportedAllowDiscrete => "unsupported-allow-discrete",
+      UnsupportedAnchorPositioning => "unsupported-anchor-positioning",
+      UnsupportedViewTransition => "unsupported-view-transition",
+      UnsupportedMasonry => "unsupported-masonry",
+      UnsupportedFieldSizing => "unsupported-field-sizing",
+    }
+  }
+
+  fn help(&self) -> Option<&'static str> {
+    use ParserError::*;
+    match self {
+      AtRuleBodyInvalid | AtRulePreludeInvalid => {
+        Some("Check the syntax of the at rule against its specification")
+      }
+      AtRuleInvalid(..) => Some("Check the spelling of the at rule, or handle it with a custom at rule parser"),
+      EndOfInput => Some("Check for unclosed blocks, strings, or functions"),
+      InvalidDeclaration | InvalidValue => Some("Check the spelling of the property and the syntax of its value"),
+      InvalidMediaQuery => {
+        Some("Check that media features are wrapped in parentheses and combined with `and`, `or`, or `not`")
+      }
+      InvalidNesting => Some("Nest style rules only within style rules or conditional group rules such as @media"),
+      DeprecatedNestRule => Some("Remove `@nest` and start the selector with `&`"),
+      InvalidPageSelector => Some("Use a page name and `:first`, `:left`, `:right`, or `:blank` pseudo classes"),
+      QualifiedRuleInvalid => Some("Check for a missing `{` or an invalid selector"),
+      SelectorError(s) => s.help(),
+      UnexpectedImportRule => {
+        Some("Move the @import rule before all other rules except @charset and @layer statements")
+      }
+      UnexpectedNamespaceRule => {
+        Some("Move the @namespace rule before all other rules except @charset, @import, and @layer statements")
+      }
+      UnexpectedToken(..) => Some("Check for a missing semicolon, or a stray character before this token"),
+      MaximumNestingDepth => Some("Reduce the depth of nested rules or blocks"),
+      NonRectangularGridArea(..) => Some("Make each named grid area a single filled-in rectangle"),
+      UnsupportedScopeLimit
+      | UnsupportedStartingStyle
+      | UnsupportedAllowDiscrete
+      | UnsupportedAnchorPositioning
+      | UnsupportedViewTransition
+      | UnsupportedMasonry
+      | UnsupportedFieldSizing => Some("Update the browser targets, or provide a fallback for older browsers"),
     }
   }
 }
@@ -219,6 +393,8 @@ pub enum SelectorError<'i> {
   ),
   /// An unsupported pseudo class or pseudo element was encountered.
   UnsupportedPseudoClassOrElement(CowArcStr<'i>),
+  /// A `:has()` selector is not supported by the configured targets.
+  UnsupportedHasSelector,
 }
 
 impl<'i> fmt::Display for SelectorError<'i> {
@@ -244,6 +420,70 @@ impl<'i> fmt::Display for SelectorError<'i> {
       UnexpectedIdent(name) => write!(f, "Unexpected identifier: {}", name),
       UnexpectedTokenInAttributeSelector(token) => write!(f, "Unexpected token in attribute selector: {:?}", token),
       UnsupportedPseudoClassOrElement(name) => write!(f, "Unsupported pseudo class or element: {}", name),
+      UnsupportedHasSelector => write!(f, "The :has() selector is not supported by the configured targets"),
+    }
+  }
+}
+
+impl<'i> ErrorCode for SelectorError<'i> {
+  fn code(&self) -> &'static str {
+    use SelectorError::*;
+    match self {
+      BadValueInAttr(..) => "selector-bad-value-in-attr",
+      ClassNeedsIdent(..) => "selector-class-needs-ident",
+      DanglingCombinator => "selector-dangling-combinator",
+      EmptySelector => "selector-empty",
+      ExpectedBarInAttr(..) => "selector-expected-bar-in-attr",
+      ExpectedNamespace(..) => "selector-expected-namespace",
+      ExplicitNamespaceUnexpectedToken(..) => "selector-explicit-namespace-unexpected-token",
+      InvalidPseudoClassAfterPseudoElement => "selector-invalid-pseudo-class-after-pseudo-element",
+      InvalidPseudoClassAfterWebKitScrollbar => "selector-invalid-pseudo-class-after-webkit-scrollbar",
+      InvalidPseudoClassBeforeWebKitScrollbar => "selector-invalid-pseudo-class-before-webkit-scrollbar",
+      InvalidQualNameInAttr(..) => "selector-invalid-qual-name-in-attr",
+      InvalidState => "selector-invalid-state",
+      MissingNestingPrefix => "selector-missing-nesting-prefix",
+      MissingNestingSelector => "selector-missing-nesting-selector",
+      NoQualifiedNameInAttributeSelector(..) => "selector-no-qualified-name-in-attribute-selector",
+      PseudoElementExpectedIdent(..) => "selector-pseudo-element-expected-ident",
+      UnexpectedIdent(..) => "selector-unexpected-ident",
+      UnexpectedTokenInAttributeSelector(..) => "selector-unexpected-token-in-attribute-selector",
+      UnsupportedPseudoClassOrElement(..) => "selector-unsupported-pseudo-class-or-element",
+      UnsupportedHasSelector => "selector-unsupported-has",
+    }
+  }
+
+  fn help(&self) -> Option<&'static str> {
+    use SelectorError::*;
+    match self {
+      BadValueInAttr(..) | UnexpectedTokenInAttributeSelector(..) => {
+        Some("Quote the attribute value, e.g. `[data-foo=\"bar baz\"]`")
+      }
+      ClassNeedsIdent(..) => Some("Escape class names that start with a digit or contain special characters"),
+      DanglingCombinator => Some("Remove the trailing combinator, or add a selector after it"),
+      EmptySelector => Some("Remove the extra comma, or add a selector"),
+      ExpectedBarInAttr(..) | ExpectedNamespace(..) | ExplicitNamespaceUnexpectedToken(..) => {
+        Some("Declare the namespace prefix with an @namespace rule, and use the `prefix|name` syntax")
+      }
+      InvalidPseudoClassAfterPseudoElement => {
+        Some("Move the pseudo class before the pseudo element, e.g. `a:hover::before`")
+      }
+      InvalidPseudoClassAfterWebKitScrollbar | InvalidPseudoClassBeforeWebKitScrollbar => {
+        Some("Use scrollbar pseudo classes only after a `::-webkit-scrollbar` pseudo element")
+      }
+      InvalidQualNameInAttr(..) | NoQualifiedNameInAttributeSelector(..) => {
+        Some("Start the attribute selector with an attribute name, e.g. `[href]`")
+      }
+      InvalidState => None,
+      MissingNestingPrefix => Some("Start each nested selector with `&`"),
+      MissingNestingSelector => Some("Include `&` in each selector"),
+      PseudoElementExpectedIdent(..) => Some("Check the name of the pseudo element"),
+      UnexpectedIdent(..) => Some("Check for a missing combinator or delimiter between the selectors"),
+      UnsupportedPseudoClassOrElement(..) => {
+        Some("Check the spelling of the pseudo class or element, or add a vendor prefix")
+      }
+      UnsupportedHasSelector => {
+        Some("Update the browser targets, or use a selector that does not require `:has()`")
+      }
     }
   }
 }
@@ -340,6 +580,28 @@ impl fmt::Display for MinifyErrorKind {
   }
 }
 
+impl ErrorCode for MinifyErrorKind {
+  fn code(&self) -> &'static str {
+    use MinifyErrorKind::*;
+    match self {
+      CircularCustomMedia { .. } => "circular-custom-media",
+      CustomMediaNotDefined { .. } => "custom-media-not-defined",
+      UnsupportedCustomMediaBooleanLogic { .. } => "unsupported-custom-media-boolean-logic",
+    }
+  }
+
+  fn help(&self) -> Option<&'static str> {
+    use MinifyErrorKind::*;
+    match self {
+      CircularCustomMedia { .. } => Some("Remove the reference from the @custom-media rule to itself"),
+      CustomMediaNotDefined { .. } => Some("Define the query with a @custom-media rule, or check its spelling"),
+      UnsupportedCustomMediaBooleanLogic { .. } => {
+        Some("Move the media type out of the @custom-media rule and into the @media rule that references it")
+      }
+    }
+  }
+}
+
 impl MinifyErrorKind {
   #[deprecated(note = "use `MinifyErrorKind::to_string()` or `fmt::Display` instead")]
   #[allow(missing_docs)]
@@ -393,6 +655,30 @@ impl fmt::Display for PrinterErrorKind {
   }
 }
 
+impl ErrorCode for PrinterErrorKind {
+  fn code(&self) -> &'static str {
+    use PrinterErrorKind::*;
+    match self {
+      AmbiguousUrlInCustomProperty { .. } => "ambiguous-url-in-custom-property",
+      FmtError => "fmt-error",
+      InvalidComposesNesting => "invalid-composes-nesting",
+      InvalidComposesSelector => "invalid-composes-selector",
+      InvalidCssModulesPatternInGrid => "invalid-css-modules-pattern-in-grid",
+    }
+  }
+
+  fn help(&self) -> Option<&'static str> {
+    use PrinterErrorKind::*;
+    match self {
+      AmbiguousUrlInCustomProperty { .. } => Some("Use an absolute URL in the custom property"),
+      FmtError => None,
+      InvalidComposesNesting => Some("Move the `composes` declaration to a top-level rule"),
+      InvalidComposesSelector => Some("Use `composes` only in rules with a single class selector, e.g. `.foo`"),
+      InvalidCssModulesPatternInGrid => Some("Change the CSS modules pattern to end with `[local]`"),
+    }
+  }
+}
+
 impl PrinterErrorKind {
   #[deprecated(note = "use `PrinterErrorKind::to_string()` or `fmt::Display` instead")]
   #[allow(missing_docs)]
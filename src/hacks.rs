@@ -0,0 +1,203 @@
+//! Detection and removal of legacy browser hacks.
+//!
+//! Older style sheets often contain hacks that target old versions of Internet Explorer or Firefox, such as
+//! `_zoom: 1`, `color: red\9`, `filter: progid:DXImageTransform.Microsoft.Alpha(opacity=50)`, and
+//! `@-moz-document url-prefix()` rules, along with vendor prefixes that browsers no longer need.
+//! [StyleSheet::legacy_hacks](crate::stylesheet::StyleSheet::legacy_hacks) reports these with their locations,
+//! and the `remove_legacy_hacks` option of [MinifyOptions](crate::stylesheet::MinifyOptions) removes
+//! them when none of the targets need them anymore.
+//!
+//! Hacks that are not valid CSS syntax, such as `*zoom: 1`, are dropped by the parser, or reported as
+//! warnings with the `error_recovery` option, so they never appear in a style sheet.
+
+use crate::declaration::{is_obsolete_prefix, DeclarationBlock};
+use crate::prefixes::Feature;
+use crate::printer::PrinterOptions;
+use crate::properties::custom::{CustomProperty, CustomPropertyName};
+use crate::properties::Property;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::targets::Browsers;
+use crate::traits::ToCss;
+use crate::vendor_prefix::VendorPrefix;
+
+/// A kind of legacy hack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum LegacyHackKind {
+  /// A property name prefixed with an underscore, e.g. `_zoom`, which only Internet Explorer 6 and
+  /// earlier accept.
+  UnderscoreProperty,
+  /// A value followed by `\9`, which only Internet Explorer 10 and earlier accept.
+  BackslashNine,
+  /// A proprietary `progid:` filter, supported by Internet Explorer 9 and earlier.
+  Progid,
+  /// A `@-moz-document` rule, which is only supported by Firefox.
+  MozDocument,
+  /// A vendor prefix that none of the targets need.
+  ObsoletePrefix,
+}
+
+impl LegacyHackKind {
+  /// Returns whether any of the targets may still need the hack.
+  pub fn is_needed(&self, targets: Browsers) -> bool {
+    let ie_up_to = |version: u32| targets.ie.map_or(false, |ie| ie < (version + 1) << 16);
+    match self {
+      LegacyHackKind::UnderscoreProperty => ie_up_to(6),
+      LegacyHackKind::BackslashNine => ie_up_to(10),
+      LegacyHackKind::Progid => ie_up_to(9),
+      LegacyHackKind::MozDocument => targets.firefox.is_some(),
+      LegacyHackKind::ObsoletePrefix => false,
+    }
+  }
+}
+
+/// A legacy hack within a style sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct LegacyHack {
+  /// The kind of hack.
+  pub kind: LegacyHackKind,
+  /// The declaration or at-rule name containing the hack, e.g. `_zoom: 1` or `@-moz-document`.
+  pub text: String,
+  /// The location of the rule containing the hack.
+  pub loc: Location,
+}
+
+/// Adds the legacy hacks within the rules to the list. Obsolete prefixes are only reported if targets are given.
+pub(crate) fn find_legacy_hacks<T>(
+  rules: &CssRuleList<T>,
+  targets: Option<Browsers>,
+  hacks: &mut Vec<LegacyHack>,
+) {
+  rules.for_each_rule(&mut |rule| match rule {
+    CssRule::Style(style) => find_declaration_hacks(&style.declarations, style.loc, targets, hacks),
+    CssRule::Nesting(nesting) => {
+      find_declaration_hacks(&nesting.style.declarations, nesting.style.loc, targets, hacks)
+    }
+    CssRule::NestedDeclarations(nested) => {
+      find_declaration_hacks(&nested.declarations, nested.loc, targets, hacks)
+    }
+    CssRule::Keyframes(keyframes) => {
+      let prefix = keyframes.vendor_prefix;
+      if let Some(targets) = targets {
+        if prefix != VendorPrefix::None
+          && prefix.bits().count_ones() == 1
+          && !Feature::AtKeyframes.prefixes_for(targets).contains(prefix)
+        {
+          hacks.push(LegacyHack {
+            kind: LegacyHackKind::ObsoletePrefix,
+            text: format!("@{}keyframes", prefix_string(prefix)),
+            loc: keyframes.loc,
+          });
+        }
+      }
+
+      for keyframe in &keyframes.keyframes {
+        find_declaration_hacks(&keyframe.declarations, keyframes.loc, targets, hacks);
+      }
+    }
+    CssRule::MozDocument(document) => hacks.push(LegacyHack {
+      kind: LegacyHackKind::MozDocument,
+      text: "@-moz-document".into(),
+      loc: document.loc,
+    }),
+    _ => {}
+  });
+}
+
+fn find_declaration_hacks(
+  declarations: &DeclarationBlock,
+  loc: Location,
+  targets: Option<Browsers>,
+  hacks: &mut Vec<LegacyHack>,
+) {
+  for (property, important) in declarations.iter() {
+    let kind = match property_hack(property) {
+      Some(kind) => kind,
+      None if targets.map_or(false, |targets| is_obsolete_prefix(&property.property_id(), targets)) => {
+        LegacyHackKind::ObsoletePrefix
+      }
+      None => continue,
+    };
+
+    hacks.push(LegacyHack {
+      kind,
+      text: property.to_css_string(important, PrinterOptions::default()).unwrap_or_default(),
+      loc,
+    });
+  }
+}
+
+/// Returns the kind of IE hack used by the property, if any. These are only possible in properties that
+/// failed to parse, which are stored as unknown or unparsed properties.
+fn property_hack(property: &Property) -> Option<LegacyHackKind> {
+  match property {
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Unknown(name),
+      ..
+    }) if name.0.starts_with('_') => return Some(LegacyHackKind::UnderscoreProperty),
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Unknown(..),
+      ..
+    })
+    | Property::Unparsed(..) => {}
+    _ => return None,
+  }
+
+  // The `\9` escape is the tab character, which is always escaped when serialized.
+  let value = property.value_to_css_string(PrinterOptions::default()).ok()?;
+  if value.contains("\\9") {
+    Some(LegacyHackKind::BackslashNine)
+  } else if value.to_ascii_lowercase().contains("progid:") {
+    Some(LegacyHackKind::Progid)
+  } else {
+    None
+  }
+}
+
+fn prefix_string(prefix: VendorPrefix) -> String {
+  prefix.to_css_string(PrinterOptions::default()).unwrap_or_default()
+}
+
+/// Removes the declarations and rules using legacy hacks that none of the targets need.
+/// Obsolete prefixes are removed separately, since they are only safe to remove when followed by
+/// an unprefixed equivalent.
+pub(crate) fn remove_legacy_hacks<T>(rules: &mut CssRuleList<T>, targets: Browsers) {
+  rules.0.retain_mut(|rule| {
+    match rule {
+      CssRule::Style(style) => {
+        remove_declaration_hacks(&mut style.declarations, targets);
+        remove_legacy_hacks(&mut style.rules, targets);
+      }
+      CssRule::Nesting(nesting) => {
+        remove_declaration_hacks(&mut nesting.style.declarations, targets);
+        remove_legacy_hacks(&mut nesting.style.rules, targets);
+      }
+      CssRule::NestedDeclarations(nested) => remove_declaration_hacks(&mut nested.declarations, targets),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &mut keyframes.keyframes {
+          remove_declaration_hacks(&mut keyframe.declarations, targets);
+        }
+      }
+      CssRule::MozDocument(..) => return LegacyHackKind::MozDocument.is_needed(targets),
+      CssRule::Media(media) => remove_legacy_hacks(&mut media.rules, targets),
+      CssRule::Supports(supports) => remove_legacy_hacks(&mut supports.rules, targets),
+      CssRule::Container(container) => remove_legacy_hacks(&mut container.rules, targets),
+      CssRule::Scope(scope) => remove_legacy_hacks(&mut scope.rules, targets),
+      CssRule::StartingStyle(rule) => remove_legacy_hacks(&mut rule.rules, targets),
+      CssRule::LayerBlock(layer) => remove_legacy_hacks(&mut layer.rules, targets),
+      _ => {}
+    }
+    true
+  });
+}
+
+fn remove_declaration_hacks(declarations: &mut DeclarationBlock, targets: Browsers) {
+  let is_needed = |property: &Property| property_hack(property).map_or(true, |kind| kind.is_needed(targets));
+  declarations.declarations.retain(|property| is_needed(property));
+  declarations.important_declarations.retain(|property| is_needed(property));
+}
@@ -0,0 +1,118 @@
+//! Auditing of `!important` declarations.
+//!
+//! [StyleSheet::important_audit](crate::stylesheet::StyleSheet::important_audit) reports every `!important`
+//! declaration in the style rules of a style sheet, which can be grouped by property or by selector. Each
+//! declaration is marked as removable when no other declaration in the style sheet sets an overlapping
+//! property, including shorthands, longhands, and logical equivalents, so that removing `!important` cannot
+//! change which value applies. The `remove_unneeded_important` option of
+//! [MinifyOptions](crate::stylesheet::MinifyOptions) removes `!important` from these declarations.
+//!
+//! The analysis only considers the style sheet itself. Declarations in other style sheets or inline styles
+//! that set the same properties on the same elements may still depend on `!important`, so removing it is
+//! only safe when the style sheet is the only source of these properties.
+
+use crate::declaration::DeclarationBlock;
+use crate::printer::PrinterOptions;
+use crate::properties::PropertyId;
+use crate::rules::{is_uncontested, CssRule, CssRuleList, Location};
+use crate::traits::ToCss;
+use std::collections::BTreeMap;
+
+/// An `!important` declaration within a style rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ImportantDeclaration {
+  /// The name of the property, including any vendor prefix.
+  pub property: String,
+  /// The selectors of the style rule containing the declaration.
+  pub selectors: String,
+  /// The location of the style rule containing the declaration.
+  pub loc: Location,
+  /// Whether `!important` can be removed without changing the cascade within the style sheet.
+  pub removable: bool,
+}
+
+/// The `!important` declarations within a style sheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ImportantAudit {
+  /// The `!important` declarations, in source order.
+  pub declarations: Vec<ImportantDeclaration>,
+}
+
+impl ImportantAudit {
+  pub(crate) fn build<T>(rules: &CssRuleList<T>) -> ImportantAudit {
+    let mut audit = ImportantAudit::default();
+    let properties = rules.declared_properties();
+    audit.add_rules(rules, "", properties.as_deref());
+    audit
+  }
+
+  fn add_rules<T>(&mut self, rules: &CssRuleList<T>, parent_selectors: &str, properties: Option<&[PropertyId]>) {
+    for rule in &rules.0 {
+      match rule {
+        CssRule::Style(style) => {
+          let selectors = style.selectors.to_css_string(PrinterOptions::default()).unwrap_or_default();
+          self.add_declarations(&style.declarations, &selectors, style.loc, properties);
+          self.add_rules(&style.rules, &selectors, properties);
+        }
+        CssRule::Nesting(nesting) => {
+          let selectors = nesting
+            .style
+            .selectors
+            .to_css_string(PrinterOptions::default())
+            .unwrap_or_default();
+          self.add_declarations(&nesting.style.declarations, &selectors, nesting.style.loc, properties);
+          self.add_rules(&nesting.style.rules, &selectors, properties);
+        }
+        CssRule::NestedDeclarations(nested) => {
+          self.add_declarations(&nested.declarations, parent_selectors, nested.loc, properties)
+        }
+        CssRule::Media(media) => self.add_rules(&media.rules, parent_selectors, properties),
+        CssRule::Supports(supports) => self.add_rules(&supports.rules, parent_selectors, properties),
+        CssRule::Container(container) => self.add_rules(&container.rules, parent_selectors, properties),
+        CssRule::Scope(scope) => self.add_rules(&scope.rules, parent_selectors, properties),
+        CssRule::StartingStyle(rule) => self.add_rules(&rule.rules, parent_selectors, properties),
+        CssRule::LayerBlock(layer) => self.add_rules(&layer.rules, parent_selectors, properties),
+        CssRule::MozDocument(document) => self.add_rules(&document.rules, parent_selectors, properties),
+        _ => {}
+      }
+    }
+  }
+
+  fn add_declarations(
+    &mut self,
+    declarations: &DeclarationBlock,
+    selectors: &str,
+    loc: Location,
+    properties: Option<&[PropertyId]>,
+  ) {
+    for property in &declarations.important_declarations {
+      let id = property.property_id();
+      self.declarations.push(ImportantDeclaration {
+        property: id.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+        selectors: selectors.to_owned(),
+        loc,
+        removable: properties.map_or(false, |properties| is_uncontested(&id, properties)),
+      });
+    }
+  }
+
+  /// Returns the `!important` declarations grouped by property name.
+  pub fn by_property(&self) -> BTreeMap<&str, Vec<&ImportantDeclaration>> {
+    let mut groups: BTreeMap<&str, Vec<&ImportantDeclaration>> = BTreeMap::new();
+    for declaration in &self.declarations {
+      groups.entry(&declaration.property).or_default().push(declaration);
+    }
+    groups
+  }
+
+  /// Returns the `!important` declarations grouped by the selectors of their style rule.
+  pub fn by_selector(&self) -> BTreeMap<&str, Vec<&ImportantDeclaration>> {
+    let mut groups: BTreeMap<&str, Vec<&ImportantDeclaration>> = BTreeMap::new();
+    for declaration in &self.declarations {
+      groups.entry(&declaration.selectors).or_default().push(declaration);
+    }
+    groups
+  }
+}
@@ -0,0 +1,237 @@
+//! JSON patches for the serialized AST.
+//!
+//! A [JsonPatch](JsonPatch) is a list of [JSON Patch](https://datatracker.ietf.org/doc/html/rfc6902) operations,
+//! which edit the JSON serialization of a style sheet described by the [schema](crate::schema) module. This allows
+//! tools that are not written in Rust to make small programmatic edits, such as replacing a declaration or removing
+//! a rule, without parsing and serializing the whole AST themselves.
+//!
+//! [StyleSheet::to_patched_json](crate::stylesheet::StyleSheet::to_patched_json) applies a patch to a style sheet,
+//! and returns the patched JSON, which can be deserialized into a new style sheet before printing.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   json_patch::JsonPatch,
+//!   stylesheet::{StyleSheet, ParserOptions, PrinterOptions},
+//! };
+//! use serde::Deserialize;
+//!
+//! let stylesheet = StyleSheet::parse(".foo { color: red } .bar { color: green }", ParserOptions::default()).unwrap();
+//! let patch: JsonPatch = serde_json::from_str(r#"[
+//!   { "op": "remove", "path": "/rules/1" },
+//!   { "op": "replace", "path": "/rules/0/value/declarations/declarations/0", "value": { "property": "color", "raw": "blue" } }
+//! ]"#).unwrap();
+//!
+//! let json = stylesheet.to_patched_json(&patch).unwrap();
+//! let patched: StyleSheet = StyleSheet::deserialize(&json).unwrap();
+//! let res = patched.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{color:#00f}");
+//! ```
+
+use serde_json::Value;
+
+/// A list of JSON Patch operations, which are applied in order.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(pub Vec<PatchOperation>);
+
+/// A JSON Patch operation. Paths are [JSON Pointers](https://datatracker.ietf.org/doc/html/rfc6901),
+/// e.g. `/rules/0/value/selectors`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+  /// Inserts a value into an array, or sets a member of an object. The path `-` appends to an array.
+  Add {
+    /// The location to add the value at.
+    path: String,
+    /// The value to add.
+    value: Value,
+  },
+  /// Removes a value.
+  Remove {
+    /// The location of the value to remove.
+    path: String,
+  },
+  /// Replaces an existing value.
+  Replace {
+    /// The location of the value to replace.
+    path: String,
+    /// The new value.
+    value: Value,
+  },
+  /// Removes a value and adds it at another location.
+  Move {
+    /// The location of the value to move.
+    from: String,
+    /// The location to move the value to.
+    path: String,
+  },
+  /// Adds a copy of a value at another location.
+  Copy {
+    /// The location of the value to copy.
+    from: String,
+    /// The location to add the copy at.
+    path: String,
+  },
+  /// Checks that a value is equal to the given value, failing the patch otherwise.
+  Test {
+    /// The location of the value to check.
+    path: String,
+    /// The expected value.
+    value: Value,
+  },
+}
+
+/// An error that occurred while applying a JSON patch.
+#[derive(Debug)]
+pub enum JsonPatchError {
+  /// A path is not a valid JSON pointer.
+  InvalidPointer(String),
+  /// A path does not refer to an existing value, or to a location where a value can be added.
+  PathNotFound(String),
+  /// A `test` operation found a different value at the given path.
+  TestFailed(String),
+  /// A `move` operation attempted to move a value into one of its own children.
+  MoveIntoChild(String),
+  /// The style sheet could not be serialized.
+  Serialize(serde_json::Error),
+}
+
+impl std::fmt::Display for JsonPatchError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    use JsonPatchError::*;
+    match self {
+      InvalidPointer(path) => write!(f, "Error applying JSON patch: invalid path \"{}\"", path),
+      PathNotFound(path) => write!(f, "Error applying JSON patch: path \"{}\" does not exist", path),
+      TestFailed(path) => write!(f, "Error applying JSON patch: test failed at path \"{}\"", path),
+      MoveIntoChild(path) => write!(
+        f,
+        "Error applying JSON patch: cannot move \"{}\" into one of its children",
+        path
+      ),
+      Serialize(err) => write!(f, "Error applying JSON patch: {}", err),
+    }
+  }
+}
+
+impl std::error::Error for JsonPatchError {}
+
+impl JsonPatch {
+  /// Applies the operations to a JSON value. If any operation fails, the value is left unchanged.
+  pub fn apply(&self, value: &mut Value) -> Result<(), JsonPatchError> {
+    let mut patched = value.clone();
+    for operation in &self.0 {
+      operation.apply(&mut patched)?;
+    }
+    *value = patched;
+    Ok(())
+  }
+}
+
+impl PatchOperation {
+  fn apply(&self, value: &mut Value) -> Result<(), JsonPatchError> {
+    match self {
+      PatchOperation::Add { path, value: new_value } => add(value, path, new_value.clone()),
+      PatchOperation::Remove { path } => remove(value, path).map(|_| ()),
+      PatchOperation::Replace { path, value: new_value } => {
+        *lookup(value, path)? = new_value.clone();
+        Ok(())
+      }
+      PatchOperation::Move { from, path } => {
+        if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+          return Err(JsonPatchError::MoveIntoChild(from.clone()));
+        }
+        let moved = remove(value, from)?;
+        add(value, path, moved)
+      }
+      PatchOperation::Copy { from, path } => {
+        let copied = lookup(value, from)?.clone();
+        add(value, path, copied)
+      }
+      PatchOperation::Test { path, value: expected } => {
+        if *lookup(value, path)? == *expected {
+          Ok(())
+        } else {
+          Err(JsonPatchError::TestFailed(path.clone()))
+        }
+      }
+    }
+  }
+}
+
+/// Splits a JSON pointer into its parent pointer and unescaped last token.
+fn split_pointer(path: &str) -> Result<(&str, String), JsonPatchError> {
+  match path.rfind('/') {
+    Some(index) => Ok((&path[..index], unescape(&path[index + 1..]))),
+    None => Err(JsonPatchError::InvalidPointer(path.to_owned())),
+  }
+}
+
+fn unescape(token: &str) -> String {
+  token.replace("~1", "/").replace("~0", "~")
+}
+
+fn lookup<'a>(value: &'a mut Value, path: &str) -> Result<&'a mut Value, JsonPatchError> {
+  if !path.is_empty() && !path.starts_with('/') {
+    return Err(JsonPatchError::InvalidPointer(path.to_owned()));
+  }
+
+  let mut current = value;
+  for token in path.split('/').skip(1) {
+    let token = unescape(token);
+    current = match current {
+      Value::Object(map) => map.get_mut(&token),
+      Value::Array(array) => array_index(&token, array.len()).and_then(move |index| array.get_mut(index)),
+      _ => None,
+    }
+    .ok_or_else(|| JsonPatchError::PathNotFound(path.to_owned()))?;
+  }
+  Ok(current)
+}
+
+/// Parses an array index, which must not have leading zeros.
+fn array_index(token: &str, len: usize) -> Option<usize> {
+  let is_valid =
+    !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+  if !is_valid {
+    return None;
+  }
+  token.parse().ok().filter(|index| *index < len)
+}
+
+fn add(value: &mut Value, path: &str, new_value: Value) -> Result<(), JsonPatchError> {
+  if path.is_empty() {
+    *value = new_value;
+    return Ok(());
+  }
+
+  let (parent, token) = split_pointer(path)?;
+  match lookup(value, parent)? {
+    Value::Object(map) => {
+      map.insert(token, new_value);
+      Ok(())
+    }
+    Value::Array(array) => {
+      let index = if token == "-" {
+        array.len()
+      } else {
+        // Inserting at the end of the array is allowed.
+        array_index(&token, array.len() + 1).ok_or_else(|| JsonPatchError::PathNotFound(path.to_owned()))?
+      };
+      array.insert(index, new_value);
+      Ok(())
+    }
+    _ => Err(JsonPatchError::PathNotFound(path.to_owned())),
+  }
+}
+
+fn remove(value: &mut Value, path: &str) -> Result<Value, JsonPatchError> {
+  let (parent, token) = split_pointer(path)?;
+  let removed = match lookup(value, parent)? {
+    Value::Object(map) => map.remove(&token),
+    Value::Array(array) => array_index(&token, array.len()).map(|index| array.remove(index)),
+    _ => None,
+  };
+  removed.ok_or_else(|| JsonPatchError::PathNotFound(path.to_owned()))
+}
@@ -17,28 +17,55 @@
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "async_visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async_visitor")))]
+pub mod async_visitor;
+pub mod builder;
 #[cfg(feature = "bundler")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bundler")))]
 pub mod bundler;
 mod compat;
 mod context;
+pub mod coverage;
 pub mod css_modules;
+pub mod cssom;
 pub mod declaration;
 pub mod dependencies;
+pub mod design_tokens;
+pub mod diff;
+pub mod duplicates;
 pub mod error;
+pub mod hacks;
+pub mod important;
+#[cfg(feature = "json_patch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json_patch")))]
+pub mod json_patch;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod lint;
 mod logical;
+mod lossless;
 mod macros;
+mod mangle;
 pub mod media_query;
 mod parser;
 mod prefixes;
 pub mod printer;
 pub mod properties;
+pub mod purge;
+pub mod query;
 pub mod rules;
+pub mod schema;
 pub mod selector;
+#[cfg(feature = "spans")]
+#[cfg_attr(docsrs, doc(cfg(feature = "spans")))]
+pub mod span;
+pub mod stats;
 pub mod stylesheet;
 pub mod targets;
 pub mod traits;
 pub mod values;
+pub mod var_graph;
 pub mod vendor_prefix;
 #[cfg(feature = "visitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
@@ -56,14 +83,15 @@ mod tests {
   use crate::properties::Property;
   use crate::rules::CssRule;
   use crate::rules::Location;
+  use crate::selector::{SelectorList, SpecificityAdjustment};
   use crate::stylesheet::*;
   use crate::targets::Browsers;
-  use crate::traits::{Parse, ToCss};
+  use crate::traits::{Parse, ParseWithOptions, ToCss};
   use crate::values::color::CssColor;
   use crate::vendor_prefix::VendorPrefix;
   use cssparser::SourceLocation;
   use indoc::indoc;
-  use std::collections::HashMap;
+  use std::collections::{BTreeMap, HashMap};
 
   fn test(source: &str, expected: &str) {
     test_with_options(source, expected, ParserOptions::default())
@@ -186,6 +214,40 @@ mod tests {
     assert_eq!(res.references.unwrap(), expected_references);
   }
 
+  fn scope_test(source: &str, scope: &str, expected: &str) {
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let scope = SelectorList::parse_string_with_options(scope, ParserOptions::default()).unwrap();
+    stylesheet.scope(&scope);
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+  }
+
+  fn specificity_adjustment_test(source: &str, adjustment: SpecificityAdjustment, expected: &str) {
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet.adjust_specificity(adjustment);
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+  }
+
   fn custom_media_test(source: &str, expected: &str) {
     let mut stylesheet = StyleSheet::parse(
       &source,
@@ -3191,6 +3253,70 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_logical_property_strategy() {
+    fn test(source: &str, expected: &str, logical_property_strategy: LogicalPropertyStrategy) {
+      let targets = Some(Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      });
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          logical_property_strategy,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let source =
+      ".foo { margin-inline-start: 10px; margin-inline-end: 2em; padding-inline-start: calc(10px + 5%) }";
+    test(
+      source,
+      ".foo{margin-left:10px;margin-right:2em;padding-left:calc(10px + 5%)}",
+      LogicalPropertyStrategy::Ltr,
+    );
+    test(
+      source,
+      ".foo{margin-right:10px;margin-left:2em;padding-right:calc(10px + 5%)}",
+      LogicalPropertyStrategy::Rtl,
+    );
+    test(source, ".foo{margin-left:var(--ltr,10px)var(--rtl,2em);margin-right:var(--ltr,2em)var(--rtl,10px);padding-left:var(--ltr,calc(10px + 5%));padding-right:var(--rtl,calc(10px + 5%))}", LogicalPropertyStrategy::CustomProperties);
+    test(".foo { border-start-start-radius: 5px; inset-inline-start: var(--x) !important }", ".foo{border-top-left-radius:var(--ltr,5px);border-top-right-radius:var(--rtl,5px);left:var(--ltr,var(--x))!important;right:var(--rtl,var(--x))!important}", LogicalPropertyStrategy::CustomProperties);
+
+    let mut attr = StyleAttribute::parse(
+      "margin-inline-start: 10px; padding-inline-end: 5px",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let targets = Some(Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
+    });
+    attr.minify(MinifyOptions {
+      targets,
+      logical_property_strategy: LogicalPropertyStrategy::Rtl,
+      ..MinifyOptions::default()
+    });
+    let res = attr
+      .to_css(PrinterOptions {
+        targets,
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, "margin-right:10px;padding-left:5px");
+  }
+
   #[test]
   fn test_size() {
     prefix_test(
@@ -5222,6 +5348,57 @@ mod tests {
       "@font-face { font-family: 'revert-layer'; }",
       "@font-face{font-family:\"revert-layer\"}",
     );
+    minify_test(
+      ".foo { font-family: 'Times  New Roman', 'Arial', Arial, serif; }",
+      ".foo{font-family:\"Times  New Roman\",Arial,serif}",
+    );
+    minify_test(".foo { font-family: ' Foo'; }", ".foo{font-family:\" Foo\"}");
+
+    minify_test(
+      ".foo { font-feature-settings: normal; }",
+      ".foo{font-feature-settings:normal}",
+    );
+    minify_test(
+      ".foo { font-feature-settings: 'liga' 1, 'smcp' on, 'swsh' 2, 'kern' off; }",
+      ".foo{font-feature-settings:\"liga\",\"smcp\",\"swsh\"2,\"kern\"0}",
+    );
+    minify_test(
+      ".foo { font-feature-settings: 'liga' 0, 'dlig', 'liga'; }",
+      ".foo{font-feature-settings:\"dlig\",\"liga\"}",
+    );
+    minify_test(
+      ".foo { font-feature-settings: 'toolong'; }",
+      ".foo{font-feature-settings:\"toolong\"}",
+    );
+    minify_test(
+      ".foo { font-variation-settings: 'wght' 400, 'wdth' 100.0, 'wght' 700; }",
+      ".foo{font-variation-settings:\"wdth\"100,\"wght\"700}",
+    );
+    minify_test(
+      ".foo { font-variation-settings: normal; }",
+      ".foo{font-variation-settings:normal}",
+    );
+    minify_test(
+      ".foo { font-variation-settings: 'wght'; }",
+      ".foo{font-variation-settings:\"wght\"}",
+    );
+    test(
+      ".foo { font-variation-settings: 'wght' 400, 'slnt' -10; font-feature-settings: 'ss01' off; }",
+      indoc! {r#"
+        .foo {
+          font-variation-settings: "wght" 400, "slnt" -10;
+          font-feature-settings: "ss01" 0;
+        }
+      "#},
+    );
+    minify_test(
+      ".foo { font: 12px Arial; font-feature-settings: 'liga' 0; }",
+      ".foo{font:12px Arial;font-feature-settings:\"liga\"0}",
+    );
+    minify_test(
+      ".foo { font-feature-settings: 'liga' 0; font: 12px Arial; }",
+      ".foo{font-feature-settings:\"liga\"0;font:12px Arial}",
+    );
 
     prefix_test(
       r#"
@@ -5329,6 +5506,54 @@ mod tests {
       "custom-element::part(foo) {color:red}",
       "custom-element::part(foo){color:red}",
     );
+    minify_test(
+      "custom-element::part(foo   bar) {color:red}",
+      "custom-element::part(foo bar){color:red}",
+    );
+    minify_test(
+      "custom-element::part(foo):hover {color:red}",
+      "custom-element::part(foo):hover{color:red}",
+    );
+    minify_test(
+      "custom-element::part(foo):state(checked) {color:red}",
+      "custom-element::part(foo):state(checked){color:red}",
+    );
+    minify_test(
+      "custom-element::part(foo)::before {color:red}",
+      "custom-element::part(foo):before{color:red}",
+    );
+    error_test(
+      "custom-element::part(foo):nth-child(2) {color:red}",
+      ParserError::SelectorError(SelectorError::InvalidState),
+    );
+    prefix_test(
+      "custom-element::part(foo) {color: red} .foo {color: red}",
+      indoc! {r#"
+      custom-element::part(foo) {
+        color: red;
+      }
+
+      .foo {
+        color: red;
+      }
+      "#},
+      Browsers {
+        safari: Some(13 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      "custom-element::part(foo) {color: red} .foo {color: red}",
+      indoc! {r#"
+      custom-element::part(foo), .foo {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(73 << 16),
+        ..Browsers::default()
+      },
+    );
     minify_test(".sm\\:text-5xl { font-size: 3rem }", ".sm\\:text-5xl{font-size:3rem}");
     minify_test("a:has(> img) {color:red}", "a:has(>img){color:red}");
     minify_test("dt:has(+ dt) {color:red}", "dt:has(+dt){color:red}");
@@ -5736,6 +5961,132 @@ mod tests {
     minify_test("a:is(:is(.foo)) { color: yellow }", "a.foo{color:#ff0}");
     minify_test(":host(:hover) {color: red}", ":host(:hover){color:red}");
     minify_test("::slotted(:hover) {color: red}", "::slotted(:hover){color:red}");
+
+    minify_test(
+      "my-input:state(checked) {color: red}",
+      "my-input:state(checked){color:red}",
+    );
+    minify_test("my-input:--checked {color: red}", "my-input:--checked{color:red}");
+    minify_test(
+      "my-input:state(checked):hover {color: red}",
+      "my-input:state(checked):hover{color:red}",
+    );
+    prefix_test(
+      "my-input:state(checked) {color: red}",
+      indoc! {r#"
+      my-input:--checked {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(110 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      "my-input:--checked {color: red}",
+      indoc! {r#"
+      my-input:state(checked) {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(125 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    minify_test(
+      "[popover]:popover-open {color: red}",
+      "[popover]:popover-open{color:red}",
+    );
+    minify_test("dialog:modal {color: red}", "dialog:modal{color:red}");
+    prefix_test(
+      "dialog:modal {color: red} .foo {color: red}",
+      indoc! {r#"
+      dialog:modal {
+        color: red;
+      }
+
+      .foo {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(100 << 16),
+        ..Browsers::default()
+      },
+    );
+    minify_test("::view-transition {color: red}", "::view-transition{color:red}");
+    minify_test(
+      "::view-transition-group(hero) {color: red}",
+      "::view-transition-group(hero){color:red}",
+    );
+    minify_test(
+      "::view-transition-image-pair( * ) {color: red}",
+      "::view-transition-image-pair(*){color:red}",
+    );
+    minify_test(
+      "::view-transition-old(hero.card) {color: red}",
+      "::view-transition-old(hero.card){color:red}",
+    );
+    minify_test(
+      "::view-transition-new(.card.active) {color: red}",
+      "::view-transition-new(.card.active){color:red}",
+    );
+    minify_test(
+      "::view-transition-new(hero):only-child {color: red}",
+      "::view-transition-new(hero):only-child{color:red}",
+    );
+    error_test("::view-transition-group() {color: red}", ParserError::InvalidValue);
+    error_test(
+      "::view-transition-group(hero .card) {color: red}",
+      ParserError::UnexpectedToken(Token::Delim('.')),
+    );
+    minify_test(
+      "details::details-content {color: red}",
+      "details::details-content{color:red}",
+    );
+    prefix_test(
+      "details::details-content {color: red} .foo {color: red}",
+      indoc! {r#"
+      details::details-content {
+        color: red;
+      }
+
+      .foo {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      "details::details-content {color: red} .foo {color: red}",
+      indoc! {r#"
+      details::details-content, .foo {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(131 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      "dialog:modal {color: red} [popover]:popover-open {color: red}",
+      indoc! {r#"
+      dialog:modal, [popover]:popover-open {
+        color: red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(114 << 16),
+        ..Browsers::default()
+      },
+    );
   }
 
   #[test]
@@ -6326,6 +6677,26 @@ mod tests {
       ".foo{border-width:clamp(1px,1px + 2em,4px)}",
     );
     minify_test(".foo { border-width: clamp(1px, 2pt, 1in) }", ".foo{border-width:2pt}");
+    minify_test(
+      ".foo { border-width: clamp(10px, 1em + 2px, 5px) }",
+      ".foo{border-width:10px}",
+    );
+    minify_test(
+      ".foo { border-width: min(1em, min(2px, 1vh), 3px) }",
+      ".foo{border-width:min(1em,2px,1vh)}",
+    );
+    minify_test(
+      ".foo { border-width: max(1em, max(2px, 1vh), 3px) }",
+      ".foo{border-width:max(1em,3px,1vh)}",
+    );
+    minify_test(
+      ".foo { border-width: min(1em, max(2px, 1vh)) }",
+      ".foo{border-width:min(1em,max(2px,1vh))}",
+    );
+    minify_test(".foo { opacity: min(0.5, max(0.2, 0.3), 1) }", ".foo{opacity:.3}");
+    minify_test(".foo { opacity: clamp(0, 2, 1) }", ".foo{opacity:1}");
+    minify_test(".foo { width: calc(1px * round(up, 7.5, 2)) }", ".foo{width:8px}");
+    minify_test(".foo { width: abs(max(-10px, -20px)) }", ".foo{width:10px}");
 
     minify_test(
       ".foo { top: calc(-1 * clamp(1.75rem, 8vw, 4rem)) }",
@@ -6442,12 +6813,34 @@ mod tests {
     minify_test(".foo { margin: round(to-zero, -23px, 5px) }", ".foo{margin:-20px}");
     minify_test(".foo { margin: round(nearest, -23px, 5px) }", ".foo{margin:-25px}");
     minify_test(".foo { margin: calc(10px * round(22, 5)) }", ".foo{margin:200px}");
+    // Ties round toward positive infinity.
+    minify_test(".foo { margin: round(2.5px, 1px) }", ".foo{margin:3px}");
+    minify_test(".foo { margin: round(-2.5px, 1px) }", ".foo{margin:-2px}");
+    minify_test(".foo { margin: round(down, 23px, -5px) }", ".foo{margin:20px}");
+    minify_test(".foo { margin: round(up, -23px, -5px) }", ".foo{margin:-20px}");
+    minify_test(".foo { margin: round(5px, 0px) }", ".foo{margin:0}");
+    minify_test(
+      ".foo { margin: calc(1px * round(up, 5, infinity)) }",
+      ".foo{margin:3.40282e38px}",
+    );
+    minify_test(
+      ".foo { margin: calc(1px * round(down, 5, infinity)) }",
+      ".foo{margin:0}",
+    );
+    minify_test(".foo { margin: calc(1px * round(5, infinity)) }", ".foo{margin:0}");
+    minify_test(
+      ".foo { margin: calc(1px * round(infinity, 5)) }",
+      ".foo{margin:3.40282e38px}",
+    );
     minify_test(".foo { width: rem(18px, 5px) }", ".foo{width:3px}");
     minify_test(".foo { width: rem(-18px, 5px) }", ".foo{width:-3px}");
     minify_test(".foo { width: rem(18px, 5vw) }", ".foo{width:rem(18px,5vw)}");
     minify_test(".foo { rotate: rem(-140deg, -90deg) }", ".foo{rotate:-50deg}");
     minify_test(".foo { rotate: rem(140deg, -90deg) }", ".foo{rotate:50deg}");
     minify_test(".foo { width: calc(10px * rem(18, 5)) }", ".foo{width:30px}");
+    minify_test(".foo { width: rem(18px, 0px) }", ".foo{width:0}");
+    minify_test(".foo { width: calc(1px * rem(-5, infinity)) }", ".foo{width:-5px}");
+    minify_test(".foo { width: calc(1px * rem(infinity, 5)) }", ".foo{width:0}");
     minify_test(".foo { width: mod(18px, 5px) }", ".foo{width:3px}");
     minify_test(".foo { width: mod(-18px, 5px) }", ".foo{width:2px}");
     minify_test(".foo { rotate: mod(-140deg, -90deg) }", ".foo{rotate:-50deg}");
@@ -6458,6 +6851,11 @@ mod tests {
       ".foo{transform:rotateX(-40deg)rotateY(50deg)}",
     );
     minify_test(".foo { width: calc(10px * mod(18, 5)) }", ".foo{width:30px}");
+    minify_test(".foo { width: mod(18px, 0px) }", ".foo{width:0}");
+    minify_test(".foo { width: calc(1px * mod(5, infinity)) }", ".foo{width:5px}");
+    minify_test(".foo { width: calc(1px * mod(-5, infinity)) }", ".foo{width:0}");
+    minify_test(".foo { width: calc(1px * mod(-5, -infinity)) }", ".foo{width:-5px}");
+    minify_test(".foo { width: calc(0px * infinity) }", ".foo{width:0}");
   }
 
   #[test]
@@ -6486,6 +6884,12 @@ mod tests {
 
     minify_test(".foo { width: calc(2px * cos(45deg))", ".foo{width:1.41421px}");
     minify_test(".foo { width: calc(2px * tan(45deg))", ".foo{width:2px}");
+    minify_test(".foo { opacity: tan(90deg)", ".foo{opacity:3.40282e38}");
+    minify_test(".foo { opacity: tan(-90deg)", ".foo{opacity:-3.40282e38}");
+    minify_test(".foo { opacity: tan(450deg)", ".foo{opacity:3.40282e38}");
+    minify_test(".foo { opacity: tan(100grad)", ".foo{opacity:3.40282e38}");
+    minify_test(".foo { opacity: tan(.75turn)", ".foo{opacity:-3.40282e38}");
+    minify_test(".foo { width: calc(1px / tan(90deg))", ".foo{width:0}");
 
     minify_test(".foo { rotate: asin(sin(45deg))", ".foo{rotate:45deg}");
     minify_test(".foo { rotate: asin(1)", ".foo{rotate:90deg}");
@@ -7114,36 +7518,116 @@ mod tests {
         ..Browsers::default()
       },
     );
-  }
-
-  #[test]
-  fn test_merge_layers() {
-    test(
-      r#"
-      @layer foo {
-        .foo {
-          color: red;
-        }
-      }
-      @layer foo {
-        .foo {
-          background: #fff;
-        }
-
-        .baz {
-          color: #fff;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @layer foo {
-        .foo {
-          color: red;
-          background: #fff;
-        }
-
-        .baz {
-          color: #fff;
+    minify_test(
+      "@media not (not (hover)) { .foo { color: red }}",
+      "@media (hover){.foo{color:red}}",
+    );
+    minify_test(
+      "@media not (width < 600px) { .foo { color: red }}",
+      "@media (width>=600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media not (not (width < 600px)) { .foo { color: red }}",
+      "@media (width<600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (min-width: 600px) and (min-width: 400px) { .foo { color: red }}",
+      "@media (min-width:600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (max-width: 600px) and (width < 600px) { .foo { color: red }}",
+      "@media (width<600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (min-width: 600px) or (width > 400px) { .foo { color: red }}",
+      "@media (width>400px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media ((min-width: 400px) and (hover)) and (min-width: 600px) { .foo { color: red }}",
+      "@media (min-width:600px) and (hover){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (min-width: 400px) and (max-width: 600px) { .foo { color: red }}",
+      "@media (min-width:400px) and (max-width:600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (min-width: 40em) and (min-width: 600px) { .foo { color: red }}",
+      "@media (min-width:40em) and (min-width:600px){.foo{color:red}}",
+    );
+    minify_test(
+      "@media (hover) and (hover) { .foo { color: red }}",
+      "@media (hover){.foo{color:red}}",
+    );
+    minify_test("@media (min-width: 0) { .foo { color: red }}", ".foo{color:red}");
+    minify_test(
+      "@media screen and (min-width: 0px) { .foo { color: red }}",
+      "@media screen{.foo{color:red}}",
+    );
+    minify_test(
+      "@media (min-width: 0) or (hover) { .foo { color: red }}",
+      ".foo{color:red}",
+    );
+    minify_test(
+      "@media (min-width: 600px) and (max-width: 400px) { .foo { color: red }}",
+      "",
+    );
+    minify_test("@media (width > 600px) and (width <= 600px) { .foo { color: red }}", "");
+    minify_test(
+      "@media (width < 600px) or (width >= 600px) { .foo { color: red }}",
+      ".foo{color:red}",
+    );
+    minify_test(
+      "@media print, (min-width: 600px) and (max-width: 400px) { .foo { color: red }}",
+      "@media print{.foo{color:red}}",
+    );
+    minify_test(
+      "@media not print and (min-width: 0) { .foo { color: red }}",
+      "@media not print{.foo{color:red}}",
+    );
+    prefix_test(
+      "@media (width >= 600px) and (width >= 400px) and (width < 900px) { .foo { color: red }}",
+      indoc! { r#"
+        @media (min-width: 600px) and (max-width: 899.999px) {
+          .foo {
+            color: red;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_merge_layers() {
+    test(
+      r#"
+      @layer foo {
+        .foo {
+          color: red;
+        }
+      }
+      @layer foo {
+        .foo {
+          background: #fff;
+        }
+
+        .baz {
+          color: #fff;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @layer foo {
+        .foo {
+          color: red;
+          background: #fff;
+        }
+
+        .baz {
+          color: #fff;
         }
       }
     "#},
@@ -7829,6 +8313,30 @@ mod tests {
   #[test]
   fn test_transitions() {
     minify_test(".foo { transition-duration: 500ms }", ".foo{transition-duration:.5s}");
+    minify_test(".foo { transition: all 1s }", ".foo{transition:1s}");
+    minify_test(".foo { transition: all }", ".foo{transition:all}");
+    minify_test(".foo { transition: all ease-in }", ".foo{transition:ease-in}");
+    minify_test(".foo { transition: all 0s 1s }", ".foo{transition:0s 1s}");
+    minify_test(
+      ".foo { transition: opacity 1s, color 2s, opacity 3s }",
+      ".foo{transition:color 2s,opacity 3s}",
+    );
+    minify_test(
+      ".foo { transition: opacity 1s, all 2s, color 3s }",
+      ".foo{transition:2s,color 3s}",
+    );
+    minify_test(
+      ".foo { transition: border-top-width 1s, border-width 2s, border-color 3s }",
+      ".foo{transition:border-width 2s,border-color 3s}",
+    );
+    minify_test(
+      ".foo { transition: border-width 1s, border-top-width 2s }",
+      ".foo{transition:border-width 1s,border-top-width 2s}",
+    );
+    minify_test(
+      ".foo { transition-property: opacity, opacity; transition-duration: 1s, 2s; transition-delay: 0s; transition-timing-function: ease }",
+      ".foo{transition:opacity 2s}",
+    );
     minify_test(".foo { transition-duration: .5s }", ".foo{transition-duration:.5s}");
     minify_test(".foo { transition-duration: 99ms }", ".foo{transition-duration:99ms}");
     minify_test(".foo { transition-duration: .099s }", ".foo{transition-duration:99ms}");
@@ -8495,6 +9003,79 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_transition_behavior() {
+    minify_test(
+      ".foo { transition-behavior: allow-discrete }",
+      ".foo{transition-behavior:allow-discrete}",
+    );
+    minify_test(
+      ".foo { transition-behavior: normal, allow-discrete }",
+      ".foo{transition-behavior:normal,allow-discrete}",
+    );
+    minify_test(
+      ".foo { transition: display 1s allow-discrete }",
+      ".foo{transition:display 1s allow-discrete}",
+    );
+    minify_test(
+      ".foo { transition: allow-discrete opacity 1s, display 1s }",
+      ".foo{transition:opacity 1s allow-discrete,display 1s}",
+    );
+    minify_test(".foo { transition: all normal }", ".foo{transition:all}");
+    minify_test(
+      ".foo { transition: display 1s; transition-behavior: allow-discrete }",
+      ".foo{transition:display 1s allow-discrete}",
+    );
+    minify_test(
+      ".foo { transition-property: display, overlay; transition-duration: 1s; transition-delay: 0s; transition-timing-function: ease; transition-behavior: allow-discrete }",
+      ".foo{transition:display 1s allow-discrete,overlay 1s allow-discrete}",
+    );
+    minify_test(
+      ".foo { transition-duration: 1s; transition-behavior: allow-discrete }",
+      ".foo{transition-duration:1s;transition-behavior:allow-discrete}",
+    );
+
+    prefix_test(
+      ".foo { transition: display 1s allow-discrete }",
+      indoc! {r#"
+      .foo {
+        transition: display 1s;
+        transition-behavior: allow-discrete;
+      }
+    "#},
+      Browsers {
+        chrome: Some(110 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      ".foo { transition: display 1s allow-discrete }",
+      indoc! {r#"
+      .foo {
+        transition: display 1s allow-discrete;
+      }
+    "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      ".foo { transition: opacity 1s allow-discrete }",
+      indoc! {r#"
+      .foo {
+        -webkit-transition: opacity 1s;
+        transition: opacity 1s;
+        transition-behavior: allow-discrete;
+      }
+    "#},
+      Browsers {
+        safari: Some(6 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
   #[test]
   fn test_animation() {
     minify_test(".foo { animation-name: test }", ".foo{animation-name:test}");
@@ -8608,6 +9189,23 @@ mod tests {
     minify_test(".foo { animation: none, none }", ".foo{animation:none,none}");
     minify_test(".foo { animation: \"none\" none }", ".foo{animation:\"none\"}");
     minify_test(".foo { animation: none none }", ".foo{animation:none}");
+    minify_test(
+      ".foo { animation: spin 1s, fade 2s, spin 1s }",
+      ".foo{animation:2s fade,1s spin}",
+    );
+    minify_test(
+      ".foo { animation: none, spin 1s, none, spin 1s }",
+      ".foo{animation:none,none,1s spin}",
+    );
+    // Unknown longhands such as animation-timeline are matched with the animations by index.
+    minify_test(
+      ".foo { animation: spin 1s, fade 2s, spin 1s; animation-timeline: --a, --b, --c }",
+      ".foo{animation-timeline:--a,--b,--c;animation:1s spin,2s fade,1s spin}",
+    );
+    minify_test(
+      ".foo { animation: spin 1s, spin 2s }",
+      ".foo{animation:1s spin,2s spin}",
+    );
 
     // Test animation-name + animation-fill-mode
     minify_test(
@@ -10207,6 +10805,44 @@ mod tests {
         ..Browsers::default()
       },
     );
+    prefix_test(
+      r#"@font-palette-values --Cooler {
+      font-family: Handover Sans;
+      override-colors: 0 oklch(60% 0.25 330);
+    }"#,
+      indoc! {r#"@font-palette-values --Cooler {
+      font-family: Handover Sans;
+      override-colors: 0 #cb27c5;
+      override-colors: 0 lab(49.6672% 72.9406 -44.8196);
+    }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
+    );
+    minify_test(
+      r#"@font-palette-values --Cooler {
+      font-family: Bixa;
+      base-palette: 1;
+      override-colors: 0 red;
+      base-palette: 2;
+      override-colors: 1 blue;
+    }"#,
+      "@font-palette-values --Cooler{font-family:Bixa;base-palette:2;override-colors:1 #00f}",
+    );
+    minify_test(
+      r#"@font-palette-values --Cooler {
+      override-colors: 0 red;
+      override-colors: 0 var(--foo);
+    }"#,
+      "@font-palette-values --Cooler{override-colors:0 var(--foo)}",
+    );
+    minify_test(
+      "@font-palette-values --Cooler { base-palette: 70000; override-colors: 70000 red }",
+      "@font-palette-values --Cooler{base-palette:70000;override-colors:70000 red}",
+    );
     minify_test(".foo { font-palette: --Custom; }", ".foo{font-palette:--Custom}");
   }
 
@@ -10313,6 +10949,34 @@ mod tests {
       "#,
       ParserError::AtRuleInvalid("bottom-left".into()),
     );
+
+    minify_test("@page {size: auto}", "@page{size:auto}");
+    minify_test("@page {size: 10cm 10cm}", "@page{size:10cm}");
+    minify_test("@page {size: 8.5in 11in}", "@page{size:8.5in 11in}");
+    minify_test("@page {size: landscape A4}", "@page{size:A4 landscape}");
+    minify_test("@page {size: jis-b5 portrait}", "@page{size:JIS-B5 portrait}");
+    minify_test("@page {size: letter}", "@page{size:letter}");
+    minify_test("@page {size: -1cm}", "@page{size:-1cm}");
+    minify_test("@page {size: A4 A5}", "@page{size:A4 A5}");
+    minify_test(".foo {page: auto}", ".foo{page:auto}");
+    minify_test(".foo {page: chapter}", ".foo{page:chapter}");
+    minify_test(
+      r#"
+    @page :first {
+      size: A4;
+      margin-top: 1in;
+      margin-bottom: 1in;
+      margin-left: 2cm;
+      margin-right: 2cm;
+
+      @top-center { content: "Title" counter(page); color: rgb(255, 0, 0) }
+      @top-left-corner {}
+      @bottom-right { padding-top: 1pt; padding-bottom: 1pt; padding-left: 1pt; padding-right: 1pt }
+    }
+    "#,
+      "@page:first{size:A4;margin:1in 2cm;@top-center{content:\"Title\" counter(page);color:red}@bottom-right{padding:1pt}}",
+    );
+    minify_test("@page { @top-left {} }", "");
   }
 
   #[test]
@@ -10550,85 +11214,376 @@ mod tests {
     "#,
       "@supports (color:hsl(0deg, 0%, 0%)){.test{color:#000}}",
     );
-  }
 
-  #[test]
-  fn test_counter_style() {
-    test(
+    let modern = Browsers {
+      chrome: Some(100 << 16),
+      firefox: Some(100 << 16),
+      safari: Some(15 << 16),
+      ..Browsers::default()
+    };
+    let legacy = Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    };
+    prefix_test(
       r#"
-      @counter-style circled-alpha {
-        system: fixed;
-        symbols: Ⓐ Ⓑ Ⓒ;
-        suffix: " ";
+      .a { float: left }
+      @supports (display: grid) {
+        .a { display: grid }
+      }
+      @supports not (display: grid) {
+        .a { display: block }
       }
     "#,
       indoc! { r#"
-      @counter-style circled-alpha {
-        system: fixed;
-        symbols: Ⓐ Ⓑ Ⓒ;
-        suffix: " ";
+      .a {
+        float: left;
       }
-    "#},
-    );
-  }
 
-  #[test]
-  fn test_namespace() {
-    minify_test(
-      "@namespace url(http://toto.example.org);",
-      "@namespace \"http://toto.example.org\";",
-    );
-    minify_test(
-      "@namespace \"http://toto.example.org\";",
-      "@namespace \"http://toto.example.org\";",
-    );
-    minify_test(
-      "@namespace toto \"http://toto.example.org\";",
-      "@namespace toto \"http://toto.example.org\";",
+      .a {
+        display: grid;
+      }
+    "#},
+      modern,
     );
-    minify_test(
-      "@namespace toto url(http://toto.example.org);",
-      "@namespace toto \"http://toto.example.org\";",
+    prefix_test(
+      r#"
+      @supports (display: grid) {
+        .a { display: grid }
+      }
+      @supports not (display: grid) {
+        .a { display: block }
+      }
+    "#,
+      indoc! { r#"
+      .a {
+        display: block;
+      }
+    "#},
+      legacy,
     );
-
-    test(
+    // Some of the targets support the feature, so the condition must be evaluated at runtime.
+    prefix_test(
       r#"
-      @namespace "http://example.com/foo";
-
-      x {
-        color: red;
+      @supports (position: sticky) {
+        .a { position: sticky }
       }
     "#,
-      indoc! {r#"
-      @namespace "http://example.com/foo";
-
-      x {
-        color: red;
+      indoc! { r#"
+      @supports (position: sticky) {
+        .a {
+          position: sticky;
+        }
       }
     "#},
+      Browsers {
+        chrome: Some(100 << 16),
+        ie: Some(11 << 16),
+        ..Browsers::default()
+      },
     );
-
-    test(
+    prefix_test(
       r#"
-      @namespace toto "http://toto.example.org";
-
-      toto|x {
-        color: red;
+      @supports (--foo: red) and (margin-inline-start: 0) {
+        .a { color: red }
       }
-
-      [toto|att=val] {
-        color: blue
+      @supports (display: grid) or (foo: bar) {
+        .b { color: red }
+      }
+      @supports (display: grid) and (foo: bar) {
+        .c { color: red }
+      }
+      @supports selector(:focus-within) {
+        .d:focus-within { color: red }
       }
     "#,
-      indoc! {r#"
-      @namespace toto "http://toto.example.org";
-
-      toto|x {
+      indoc! { r#"
+      .a {
         color: red;
       }
 
-      [toto|att="val"] {
-        color: #00f;
+      .b {
+        color: red;
+      }
+
+      @supports (display: grid) and (foo: bar) {
+        .c {
+          color: red;
+        }
+      }
+
+      .d:focus-within {
+        color: red;
+      }
+    "#},
+      modern,
+    );
+    prefix_test(
+      r#"
+      @supports (color: lab(50% 20 30)) or ((display: grid) and (foo: bar)) {
+        .a { color: red }
+      }
+      @supports (color: oklch(50% .1 30)) {
+        .b { color: red }
+      }
+    "#,
+      indoc! { r#"
+      @supports (color: lab(50% 20 30)) or ((display: grid) and (foo: bar)) {
+        .a {
+          color: red;
+        }
+      }
+
+      @supports (color: oklch(50% .1 30)) {
+        .b {
+          color: red;
+        }
+      }
+    "#},
+      modern,
+    );
+    prefix_test(
+      r#"
+      @supports selector(::view-transition) {
+        .a { color: red }
+      }
+      @supports selector(:has(> img)) {
+        .b { color: red }
+      }
+      @supports font-tech(variations) {
+        .c { color: red }
+      }
+      @supports font-format(woff2) {
+        .d { color: red }
+      }
+      @supports font-tech(incremental) or font-format(embedded-opentype) {
+        .e { color: red }
+      }
+      @supports font-tech(color-colrv1) {
+        .f { color: red }
+      }
+    "#,
+      indoc! { r#"
+      @supports selector(:has(> img)) {
+        .b {
+          color: red;
+        }
+      }
+
+      @supports font-tech(variations) {
+        .c {
+          color: red;
+        }
+      }
+
+      @supports font-format(woff2) {
+        .d {
+          color: red;
+        }
+      }
+
+      @supports font-tech(color-colrv1) {
+        .f {
+          color: red;
+        }
+      }
+    "#},
+      Browsers {
+        chrome: Some(110 << 16),
+        firefox: Some(100 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      @supports font-tech(variations) and font-format(woff2) {
+        .a { color: red }
+      }
+      @supports not font-tech(incremental) {
+        .b { color: red }
+      }
+      @supports font-tech(color-colrv1) {
+        .c { color: red }
+      }
+    "#,
+      indoc! { r#"
+      .a {
+        color: red;
+      }
+
+      .b {
+        color: red;
+      }
+
+      @supports font-tech(color-colrv1) {
+        .c {
+          color: red;
+        }
+      }
+    "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        firefox: Some(120 << 16),
+        safari: Some(17 << 16),
+        ..Browsers::default()
+      },
+    );
+    minify_test(
+      "@supports font-tech(COLOR-COLRv1) { .a { color: red } }",
+      "@supports font-tech(color-colrv1){.a{color:red}}",
+    );
+    minify_test(
+      "@supports font-format(WOFF2) { .a { color: red } }",
+      "@supports font-format(woff2){.a{color:red}}",
+    );
+    minify_test(
+      "@supports font-format(foo) { .a { color: red } }",
+      "@supports font-format(foo){.a{color:red}}",
+    );
+    minify_test(
+      "@supports font-tech(variations foo) { .a { color: red } }",
+      "@supports font-tech(variations foo){.a{color:red}}",
+    );
+  }
+
+  #[test]
+  fn test_counter_style() {
+    test(
+      r#"
+      @counter-style circled-alpha {
+        system: fixed;
+        symbols: Ⓐ Ⓑ Ⓒ;
+        suffix: " ";
+      }
+    "#,
+      indoc! { r#"
+      @counter-style circled-alpha {
+        system: fixed;
+        symbols: Ⓐ Ⓑ Ⓒ;
+        suffix: " ";
+      }
+    "#},
+    );
+
+    minify_test(
+      "@counter-style foo { system: fixed 1; symbols: a 'b' url(c.png) }",
+      "@counter-style foo{system:fixed;symbols:a \"b\" url(c.png)}",
+    );
+    minify_test(
+      "@counter-style foo { system: fixed -5 }",
+      "@counter-style foo{system:fixed -5}",
+    );
+    minify_test(
+      "@counter-style foo { system: extends decimal; pad: \"0\" 3 }",
+      "@counter-style foo{system:extends decimal;pad:3 \"0\"}",
+    );
+    minify_test(
+      "@counter-style foo { system: extends symbols(cyclic '*') }",
+      "@counter-style foo{}",
+    );
+    minify_test(
+      "@counter-style foo { system: additive; additive-symbols: 10 X, 5 'V', 1 I }",
+      "@counter-style foo{system:additive;additive-symbols:10 X,5 \"V\",1 I}",
+    );
+    minify_test(
+      "@counter-style foo { additive-symbols: 1 I, 5 V }",
+      "@counter-style foo{}",
+    );
+    minify_test(
+      "@counter-style foo { additive-symbols: 5 V, 5 I }",
+      "@counter-style foo{}",
+    );
+    minify_test(
+      "@counter-style foo { additive-symbols: 5 V, -1 I }",
+      "@counter-style foo{}",
+    );
+    minify_test(
+      "@counter-style foo { negative: '(' ')'; prefix: '['; suffix: ']' }",
+      "@counter-style foo{negative:\"(\" \")\";prefix:\"[\";suffix:\"]\"}",
+    );
+    minify_test(
+      "@counter-style foo { range: infinite -1, 5 infinite, 1 10 }",
+      "@counter-style foo{range:infinite -1,5 infinite,1 10}",
+    );
+    minify_test("@counter-style foo { range: AUTO }", "@counter-style foo{range:auto}");
+    minify_test("@counter-style foo { range: 10 1 }", "@counter-style foo{}");
+    minify_test("@counter-style foo { pad: -1 '0' }", "@counter-style foo{}");
+    minify_test(
+      "@counter-style foo { fallback: lower-alpha; speak-as: spell-out }",
+      "@counter-style foo{fallback:lower-alpha;speak-as:spell-out}",
+    );
+    minify_test(
+      "@counter-style foo { speak-as: bar }",
+      "@counter-style foo{speak-as:bar}",
+    );
+    minify_test("@counter-style foo { fallback: symbols('*') }", "@counter-style foo{}");
+    minify_test(
+      "@counter-style foo { system: cyclic; system: numeric; symbols: '0' '1' }",
+      "@counter-style foo{system:numeric;symbols:\"0\" \"1\"}",
+    );
+    minify_test(
+      "@counter-style foo { system: cyclic; system: bar; unknown: 1 2 }",
+      "@counter-style foo{system:cyclic;unknown:1 2}",
+    );
+  }
+
+  #[test]
+  fn test_namespace() {
+    minify_test(
+      "@namespace url(http://toto.example.org);",
+      "@namespace \"http://toto.example.org\";",
+    );
+    minify_test(
+      "@namespace \"http://toto.example.org\";",
+      "@namespace \"http://toto.example.org\";",
+    );
+    minify_test(
+      "@namespace toto \"http://toto.example.org\";",
+      "@namespace toto \"http://toto.example.org\";",
+    );
+    minify_test(
+      "@namespace toto url(http://toto.example.org);",
+      "@namespace toto \"http://toto.example.org\";",
+    );
+
+    test(
+      r#"
+      @namespace "http://example.com/foo";
+
+      x {
+        color: red;
+      }
+    "#,
+      indoc! {r#"
+      @namespace "http://example.com/foo";
+
+      x {
+        color: red;
+      }
+    "#},
+    );
+
+    test(
+      r#"
+      @namespace toto "http://toto.example.org";
+
+      toto|x {
+        color: red;
+      }
+
+      [toto|att=val] {
+        color: blue
+      }
+    "#,
+      indoc! {r#"
+      @namespace toto "http://toto.example.org";
+
+      toto|x {
+        color: red;
+      }
+
+      [toto|att="val"] {
+        color: #00f;
       }
     "#},
     );
@@ -11009,6 +11964,59 @@ mod tests {
     minify_test(".foo { white-space: break-spaces }", ".foo{white-space:break-spaces}");
     minify_test(".foo { white-space: pre-line }", ".foo{white-space:pre-line}");
     minify_test(".foo { white-space: NoWrAp }", ".foo{white-space:nowrap}");
+    minify_test(".foo { white-space: preserve nowrap }", ".foo{white-space:pre}");
+    minify_test(".foo { white-space: nowrap collapse }", ".foo{white-space:nowrap}");
+    minify_test(
+      ".foo { white-space: preserve-spaces }",
+      ".foo{white-space:preserve-spaces}",
+    );
+    minify_test(
+      ".foo { white-space: discard nowrap }",
+      ".foo{white-space:discard nowrap}",
+    );
+    minify_test(".foo { white-space: pre nowrap }", ".foo{white-space:pre nowrap}");
+    minify_test(
+      ".foo { white-space-collapse: preserve-breaks }",
+      ".foo{white-space-collapse:preserve-breaks}",
+    );
+    minify_test(".foo { text-wrap-mode: nowrap }", ".foo{text-wrap-mode:nowrap}");
+    minify_test(".foo { text-wrap-style: pretty }", ".foo{text-wrap-style:pretty}");
+    minify_test(".foo { text-wrap: balance }", ".foo{text-wrap:balance}");
+    minify_test(".foo { text-wrap: wrap auto }", ".foo{text-wrap:wrap}");
+    minify_test(".foo { text-wrap: stable nowrap }", ".foo{text-wrap:nowrap stable}");
+    minify_test(
+      ".foo { white-space-collapse: preserve; text-wrap-mode: wrap }",
+      ".foo{white-space:pre-wrap}",
+    );
+    minify_test(
+      ".foo { text-wrap-mode: nowrap; text-wrap-style: balance }",
+      ".foo{text-wrap:nowrap balance}",
+    );
+    minify_test(
+      ".foo { text-wrap: balance; white-space: pre }",
+      ".foo{white-space:pre;text-wrap-style:balance}",
+    );
+    minify_test(
+      ".foo { white-space: pre; white-space-collapse: preserve-breaks }",
+      ".foo{white-space:preserve-breaks nowrap}",
+    );
+    minify_test(
+      ".foo { white-space: pre; text-wrap-mode: var(--mode) }",
+      ".foo{white-space:pre;text-wrap-mode:var(--mode)}",
+    );
+
+    prefix_test(
+      ".foo { white-space-collapse: preserve-breaks; text-wrap-mode: wrap }",
+      indoc! {r#"
+      .foo {
+        white-space: pre-line;
+      }
+      "#},
+      Browsers {
+        chrome: Some(100 << 16),
+        ..Browsers::default()
+      },
+    );
   }
 
   #[test]
@@ -12585,203 +13593,591 @@ mod tests {
   }
 
   #[test]
-  fn test_overflow() {
-    minify_test(".foo { overflow: hidden }", ".foo{overflow:hidden}");
-    minify_test(".foo { overflow: hidden hidden }", ".foo{overflow:hidden}");
-    minify_test(".foo { overflow: hidden auto }", ".foo{overflow:hidden auto}");
-
-    test(
-      r#"
-      .foo {
-        overflow-x: hidden;
-        overflow-y: auto;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        overflow: hidden auto;
-      }
-    "#},
+  fn test_anchor_positioning() {
+    minify_test(".foo { anchor-name: --foo }", ".foo{anchor-name:--foo}");
+    minify_test(".foo { anchor-name: --foo, --bar }", ".foo{anchor-name:--foo,--bar}");
+    minify_test(".foo { anchor-name: none }", ".foo{anchor-name:none}");
+    minify_test(".foo { position-anchor: --foo }", ".foo{position-anchor:--foo}");
+    minify_test(".foo { position-anchor: auto }", ".foo{position-anchor:auto}");
+    minify_test(".foo { position-area: none }", ".foo{position-area:none}");
+    minify_test(".foo { position-area: top }", ".foo{position-area:top}");
+    minify_test(".foo { position-area: top span-all }", ".foo{position-area:top}");
+    minify_test(".foo { position-area: span-all left }", ".foo{position-area:left}");
+    minify_test(".foo { position-area: top left }", ".foo{position-area:top left}");
+    minify_test(".foo { position-area: center center }", ".foo{position-area:center}");
+    minify_test(
+      ".foo { position-area: center span-all }",
+      ".foo{position-area:center span-all}",
+    );
+    minify_test(".foo { position-area: start start }", ".foo{position-area:start}");
+    minify_test(".foo { position-area: start end }", ".foo{position-area:start end}");
+    minify_test(
+      ".foo { position-area: block-start span-inline-end }",
+      ".foo{position-area:block-start span-inline-end}",
+    );
+    minify_test(".foo { position-area: left right }", ".foo{position-area:left right}");
+    minify_test(".foo { top: anchor(--foo bottom) }", ".foo{top:anchor(--foo bottom)}");
+    minify_test(".foo { top: anchor(bottom --foo) }", ".foo{top:anchor(--foo bottom)}");
+    minify_test(".foo { top: anchor(bottom) }", ".foo{top:anchor(bottom)}");
+    minify_test(".foo { top: anchor(center) }", ".foo{top:anchor(50%)}");
+    minify_test(".foo { top: anchor(--foo 25.0%) }", ".foo{top:anchor(--foo 25%)}");
+    minify_test(
+      ".foo { top: anchor(--foo bottom, 0px) }",
+      ".foo{top:anchor(--foo bottom,0)}",
+    );
+    minify_test(
+      ".foo { top: anchor(--foo bottom, calc(10px + 5px)) }",
+      ".foo{top:anchor(--foo bottom,15px)}",
+    );
+    minify_test(
+      ".foo { left: calc(anchor(--foo right) + 5px) }",
+      ".foo{left:calc(anchor(--foo right) + 5px)}",
+    );
+    minify_test(".foo { --bar: anchor(--foo top) }", ".foo{--bar:anchor(--foo top)}");
+    minify_test(".foo { top: anchor(foo) }", ".foo{top:anchor(foo)}");
+    minify_test(".foo { width: anchor-size() }", ".foo{width:anchor-size()}");
+    minify_test(".foo { width: anchor-size(width) }", ".foo{width:anchor-size(width)}");
+    minify_test(
+      ".foo { width: anchor-size(self-inline --foo) }",
+      ".foo{width:anchor-size(--foo self-inline)}",
+    );
+    minify_test(
+      ".foo { width: anchor-size(--foo, 10.0px) }",
+      ".foo{width:anchor-size(--foo,10px)}",
     );
-
     test(
       r#"
       .foo {
-        overflow: hidden;
-        overflow-y: auto;
+        left: calc(anchor(--foo right) + 5px);
+        width: calc(anchor-size(--foo width) * 2);
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden auto;
+        left: calc(anchor(--foo right) + 5px);
+        width: calc(anchor-size(--foo width) * 2);
       }
     "#},
     );
-    test(
-      r#"
-      .foo {
-        overflow: hidden;
-        overflow-y: var(--y);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        overflow: hidden;
-        overflow-y: var(--y);
-      }
-    "#},
+
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      .anchor { anchor-name: --foo }
+      .foo { top: anchor(--foo bottom) }
+      .bar { color: red }
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          firefox: Some(120 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![
+        Error {
+          kind: ParserError::UnsupportedAnchorPositioning,
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 1,
+            column: 7,
+          }),
+        },
+        Error {
+          kind: ParserError::UnsupportedAnchorPositioning,
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 2,
+            column: 7,
+          }),
+        }
+      ]
     );
-    prefix_test(
+  }
+
+  #[test]
+  fn test_view_transition() {
+    minify_test(".foo { view-transition-name: none }", ".foo{view-transition-name:none}");
+    minify_test(".foo { view-transition-name: auto }", ".foo{view-transition-name:auto}");
+    minify_test(".foo { view-transition-name: card }", ".foo{view-transition-name:card}");
+    // Invalid values are preserved as is.
+    minify_test(
+      ".foo { view-transition-name: card hero }",
+      ".foo{view-transition-name:card hero}",
+    );
+    minify_test(
+      ".foo { view-transition-name: inherit }",
+      ".foo{view-transition-name:inherit}",
+    );
+    minify_test(
+      ".foo { view-transition-class: none }",
+      ".foo{view-transition-class:none}",
+    );
+    minify_test(
+      ".foo { view-transition-class: card  hero }",
+      ".foo{view-transition-class:card hero}",
+    );
+    minify_test(
+      ".foo { view-transition-class: card none }",
+      ".foo{view-transition-class:card none}",
+    );
+
+    css_modules_test(
       r#"
       .foo {
-        overflow: hidden auto;
+        view-transition-name: card;
+        view-transition-class: slide;
+      }
+
+      ::view-transition-group(card.slide) {
+        animation-duration: 1s;
       }
     "#,
       indoc! {r#"
-      .foo {
-        overflow-x: hidden;
-        overflow-y: auto;
+      .EgL3uq_foo {
+        view-transition-name: EgL3uq_card;
+        view-transition-class: EgL3uq_slide;
+      }
+
+      ::view-transition-group(EgL3uq_card.EgL3uq_slide) {
+        animation-duration: 1s;
       }
     "#},
-      Browsers {
-        chrome: Some(67 << 16),
-        ..Browsers::default()
+      map! {
+        "foo" => "EgL3uq_foo",
+        "card" => "EgL3uq_card",
+        "slide" => "EgL3uq_slide"
       },
+      HashMap::new(),
+      Default::default(),
+    );
+
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      .foo { view-transition-name: card }
+      .bar { view-transition-name: none; view-transition-class: slide }
+      .baz { view-transition-name: none }
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          chrome: Some(115 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![Error {
+        kind: ParserError::UnsupportedViewTransition,
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 2,
+          column: 7,
+        }),
+      }]
+    );
+  }
+
+  #[test]
+  fn test_contain() {
+    minify_test(".foo { contain: none }", ".foo{contain:none}");
+    minify_test(".foo { contain: size layout style paint }", ".foo{contain:strict}");
+    minify_test(".foo { contain: paint style layout }", ".foo{contain:content}");
+    minify_test(".foo { contain: paint inline-size }", ".foo{contain:inline-size paint}");
+    minify_test(".foo { contain: size inline-size }", ".foo{contain:size inline-size}");
+    minify_test(
+      ".foo { contain: content-visibility }",
+      ".foo{contain:content-visibility}",
+    );
+    minify_test(".foo { content-visibility: auto }", ".foo{content-visibility:auto}");
+    minify_test(".foo { field-sizing: content }", ".foo{field-sizing:content}");
+
+    minify_test(
+      ".foo { contain-intrinsic-size: 10px }",
+      ".foo{contain-intrinsic-size:10px}",
+    );
+    minify_test(
+      ".foo { contain-intrinsic-size: 10px 10px }",
+      ".foo{contain-intrinsic-size:10px}",
+    );
+    minify_test(
+      ".foo { contain-intrinsic-size: auto 10px auto none }",
+      ".foo{contain-intrinsic-size:auto 10px auto none}",
+    );
+    minify_test(
+      ".foo { contain-intrinsic-width: auto 10px; contain-intrinsic-height: auto 10px }",
+      ".foo{contain-intrinsic-size:auto 10px}",
+    );
+    minify_test(
+      ".foo { contain-intrinsic-size: 10px; contain-intrinsic-height: none }",
+      ".foo{contain-intrinsic-size:10px none}",
+    );
+    minify_test(
+      ".foo { contain-intrinsic-size: auto }",
+      ".foo{contain-intrinsic-size:auto}",
     );
+
     prefix_test(
       r#"
       .foo {
-        overflow: hidden hidden;
+        content-visibility: auto;
+      }
+      .bar {
+        content-visibility: hidden;
+      }
+      .baz {
+        contain: paint;
+        content-visibility: auto;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden;
+        contain: content;
+        content-visibility: auto;
+      }
+
+      .bar {
+        contain: strict;
+        content-visibility: hidden;
+      }
+
+      .baz {
+        contain: paint;
+        content-visibility: auto;
       }
     "#},
       Browsers {
-        chrome: Some(67 << 16),
+        safari: Some(15 << 16),
         ..Browsers::default()
       },
     );
     prefix_test(
       r#"
       .foo {
-        overflow: hidden auto;
+        content-visibility: auto;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden auto;
+        content-visibility: auto;
       }
     "#},
       Browsers {
-        chrome: Some(68 << 16),
+        safari: Some(18 << 16),
         ..Browsers::default()
       },
     );
-
-    minify_test(".foo { text-overflow: ellipsis }", ".foo{text-overflow:ellipsis}");
     prefix_test(
       r#"
       .foo {
-        text-overflow: ellipsis;
+        contain-intrinsic-size: auto 100px auto 200px;
+      }
+      .bar {
+        contain-intrinsic-height: auto 100px;
       }
     "#,
       indoc! {r#"
       .foo {
-        -o-text-overflow: ellipsis;
-        text-overflow: ellipsis;
+        contain-intrinsic-size: 100px 200px;
+        contain-intrinsic-size: auto 100px auto 200px;
+      }
+
+      .bar {
+        contain-intrinsic-height: 100px;
+        contain-intrinsic-height: auto 100px;
       }
     "#},
       Browsers {
-        safari: Some(4 << 16),
-        opera: Some(10 << 16),
+        chrome: Some(90 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        -o-text-overflow: ellipsis;
-        text-overflow: ellipsis;
+        contain-intrinsic-size: auto 100px;
       }
     "#,
       indoc! {r#"
       .foo {
-        text-overflow: ellipsis;
+        contain-intrinsic-size: auto 100px;
       }
     "#},
       Browsers {
-        safari: Some(4 << 16),
-        opera: Some(14 << 16),
+        chrome: Some(100 << 16),
         ..Browsers::default()
       },
     );
+
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      .foo { field-sizing: content }
+      .bar { field-sizing: fixed }
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          safari: Some(18 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![Error {
+        kind: ParserError::UnsupportedFieldSizing,
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 1,
+          column: 7,
+        }),
+      }]
+    );
   }
 
   #[test]
-  fn test_ui() {
-    minify_test(".foo { resize: both }", ".foo{resize:both}");
-    minify_test(".foo { resize: Horizontal }", ".foo{resize:horizontal}");
-    minify_test(".foo { cursor: ew-resize }", ".foo{cursor:ew-resize}");
-    minify_test(
-      ".foo { cursor: url(\"test.cur\"), ew-resize }",
-      ".foo{cursor:url(test.cur),ew-resize}",
-    );
-    minify_test(
-      ".foo { cursor: url(\"test.cur\"), url(\"foo.cur\"), ew-resize }",
-      ".foo{cursor:url(test.cur),url(foo.cur),ew-resize}",
-    );
-    minify_test(".foo { caret-color: auto }", ".foo{caret-color:auto}");
-    minify_test(".foo { caret-color: yellow }", ".foo{caret-color:#ff0}");
-    minify_test(".foo { caret-shape: block }", ".foo{caret-shape:block}");
-    minify_test(".foo { caret: yellow block }", ".foo{caret:#ff0 block}");
-    minify_test(".foo { caret: block yellow }", ".foo{caret:#ff0 block}");
-    minify_test(".foo { caret: block }", ".foo{caret:block}");
-    minify_test(".foo { caret: yellow }", ".foo{caret:#ff0}");
-    minify_test(".foo { caret: auto auto }", ".foo{caret:auto}");
-    minify_test(".foo { caret: auto }", ".foo{caret:auto}");
-    minify_test(".foo { caret: yellow auto }", ".foo{caret:#ff0}");
-    minify_test(".foo { caret: auto block }", ".foo{caret:block}");
-    minify_test(".foo { user-select: none }", ".foo{user-select:none}");
-    minify_test(".foo { -webkit-user-select: none }", ".foo{-webkit-user-select:none}");
-    minify_test(".foo { accent-color: auto }", ".foo{accent-color:auto}");
-    minify_test(".foo { accent-color: yellow }", ".foo{accent-color:#ff0}");
-    minify_test(".foo { appearance: None }", ".foo{appearance:none}");
-    minify_test(
-      ".foo { -webkit-appearance: textfield }",
-      ".foo{-webkit-appearance:textfield}",
-    );
+  fn test_overflow() {
+    minify_test(".foo { overflow: hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden auto }", ".foo{overflow:hidden auto}");
 
-    prefix_test(
+    test(
       r#"
       .foo {
-        user-select: none;
+        overflow-x: hidden;
+        overflow-y: auto;
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-user-select: none;
-        -moz-user-select: none;
-        -ms-user-select: none;
-        user-select: none;
+        overflow: hidden auto;
       }
     "#},
-      Browsers {
-        safari: Some(8 << 16),
-        opera: Some(5 << 16),
-        firefox: Some(10 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
+    test(
       r#"
       .foo {
-        -webkit-user-select: none;
+        overflow: hidden;
+        overflow-y: auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#},
+    );
+    test(
+      r#"
+      .foo {
+        overflow: hidden;
+        overflow-y: var(--y);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden;
+        overflow-y: var(--y);
+      }
+    "#},
+    );
+    prefix_test(
+      r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow-x: hidden;
+        overflow-y: auto;
+      }
+    "#},
+      Browsers {
+        chrome: Some(67 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        overflow: hidden hidden;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden;
+      }
+    "#},
+      Browsers {
+        chrome: Some(67 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#},
+      Browsers {
+        chrome: Some(68 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    minify_test(".foo { text-overflow: ellipsis }", ".foo{text-overflow:ellipsis}");
+    prefix_test(
+      r#"
+      .foo {
+        text-overflow: ellipsis;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -o-text-overflow: ellipsis;
+        text-overflow: ellipsis;
+      }
+    "#},
+      Browsers {
+        safari: Some(4 << 16),
+        opera: Some(10 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        -o-text-overflow: ellipsis;
+        text-overflow: ellipsis;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        text-overflow: ellipsis;
+      }
+    "#},
+      Browsers {
+        safari: Some(4 << 16),
+        opera: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_ui() {
+    minify_test(".foo { resize: both }", ".foo{resize:both}");
+    minify_test(".foo { resize: Horizontal }", ".foo{resize:horizontal}");
+    minify_test(".foo { cursor: ew-resize }", ".foo{cursor:ew-resize}");
+    minify_test(
+      ".foo { cursor: url(\"test.cur\"), ew-resize }",
+      ".foo{cursor:url(test.cur),ew-resize}",
+    );
+    minify_test(
+      ".foo { cursor: url(\"test.cur\"), url(\"foo.cur\"), ew-resize }",
+      ".foo{cursor:url(test.cur),url(foo.cur),ew-resize}",
+    );
+    minify_test(".foo { caret-color: auto }", ".foo{caret-color:auto}");
+    minify_test(".foo { caret-color: yellow }", ".foo{caret-color:#ff0}");
+    minify_test(".foo { caret-shape: block }", ".foo{caret-shape:block}");
+    minify_test(".foo { caret: yellow block }", ".foo{caret:#ff0 block}");
+    minify_test(".foo { caret: block yellow }", ".foo{caret:#ff0 block}");
+    minify_test(".foo { caret: block }", ".foo{caret:block}");
+    minify_test(".foo { caret: yellow }", ".foo{caret:#ff0}");
+    minify_test(".foo { caret: auto auto }", ".foo{caret:auto}");
+    minify_test(".foo { caret: auto }", ".foo{caret:auto}");
+    minify_test(".foo { caret: yellow auto }", ".foo{caret:#ff0}");
+    minify_test(".foo { caret: auto block }", ".foo{caret:block}");
+    minify_test(".foo { user-select: none }", ".foo{user-select:none}");
+    minify_test(".foo { -webkit-user-select: none }", ".foo{-webkit-user-select:none}");
+    minify_test(".foo { accent-color: auto }", ".foo{accent-color:auto}");
+    minify_test(".foo { accent-color: yellow }", ".foo{accent-color:#ff0}");
+    minify_test(".foo { appearance: None }", ".foo{appearance:none}");
+    minify_test(
+      ".foo { -webkit-appearance: textfield }",
+      ".foo{-webkit-appearance:textfield}",
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        user-select: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -webkit-user-select: none;
+        -moz-user-select: none;
+        -ms-user-select: none;
+        user-select: none;
+      }
+    "#},
+      Browsers {
+        safari: Some(8 << 16),
+        opera: Some(5 << 16),
+        firefox: Some(10 << 16),
+        ie: Some(10 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-user-select: none;
         -moz-user-select: none;
         -ms-user-select: none;
         user-select: none;
@@ -13252,63 +14648,242 @@ mod tests {
         },
       );
     }
-  }
 
-  #[test]
-  fn test_color() {
-    minify_test(".foo { color: yellow }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgb(255, 255, 0) }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgba(255, 255, 0, 1) }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgba(255, 255, 0, 0.8) }", ".foo{color:#ff0c}");
-    minify_test(".foo { color: rgb(128, 128, 128) }", ".foo{color:gray}");
-    minify_test(".foo { color: rgb(123, 255, 255) }", ".foo{color:#7bffff}");
-    minify_test(".foo { color: rgba(123, 255, 255, 0.5) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(123 255 255) }", ".foo{color:#7bffff}");
-    minify_test(".foo { color: rgb(123 255 255 / .5) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(123 255 255 / 50%) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(48% 100% 100% / 50%) }", ".foo{color:#7affff80}");
-    minify_test(".foo { color: hsl(100deg, 100%, 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100, 100%, 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100 100% 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsl(100 100% 50% / .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsla(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsla(100 100% 50% / .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: transparent }", ".foo{color:#0000}");
-    minify_test(".foo { color: currentColor }", ".foo{color:currentColor}");
-    minify_test(".foo { color: hwb(194 0% 0%) }", ".foo{color:#00c4ff}");
-    minify_test(".foo { color: hwb(194 0% 0% / 50%) }", ".foo{color:#00c4ff80}");
-    minify_test(".foo { color: hwb(194 0% 50%) }", ".foo{color:#006280}");
-    minify_test(".foo { color: hwb(194 50% 0%) }", ".foo{color:#80e1ff}");
-    minify_test(".foo { color: hwb(194 50% 50%) }", ".foo{color:gray}");
-    // minify_test(".foo { color: ActiveText }", ".foo{color:ActiveTet}");
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
-    );
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664 / 100%); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
-    );
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664 / 50%); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664/.5)}",
-    );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 27); }",
-      ".foo{color:lch(29.2345% 44.2 27)}",
-    );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 45deg); }",
-      ".foo{color:lch(29.2345% 44.2 45)}",
+    prefix_test(
+      r#"
+      .foo {
+        background-image: image-set("foo.avif" type("image/avif"), "foo.jpg");
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        background-image: -webkit-image-set(url("foo.jpg") 1x);
+        background-image: image-set("foo.avif" 1x type("image/avif"), "foo.jpg" 1x);
+      }
+    "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 .5turn); }",
-      ".foo{color:lch(29.2345% 44.2 180)}",
+
+    prefix_test(
+      r#"
+      .foo {
+        background-image: image-set("foo.avif" type("image/avif"), "foo.webp" type("image/webp"));
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        background-image: image-set("foo.avif" 1x type("image/avif"), "foo.webp" 1x type("image/webp"));
+      }
+    "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 27 / 100%); }",
-      ".foo{color:lch(29.2345% 44.2 27)}",
+
+    fn image_set_fallback_test(source: &str, expected: &str, targets: Browsers) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets: Some(targets),
+          image_set_fallback: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          targets: Some(targets),
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    image_set_fallback_test(
+      r#"
+      .foo {
+        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        background: url("bar.png");
+        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+        background: image-set("foo.png" 2x, "bar.png" 1x);
+      }
+    "#},
+      Browsers {
+        ie: Some(11 << 16),
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    image_set_fallback_test(
+      r#"
+      .foo {
+        background-image: image-set("foo.avif" type("image/avif"), "foo@2x.jpg" 2x, "foo.jpg" 96dpi), linear-gradient(red, blue);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        background-image: url("foo.jpg"), linear-gradient(red, #00f);
+        background-image: image-set("foo.avif" 1x type("image/avif"), "foo@2x.jpg" 2x, "foo.jpg" 96dpi), linear-gradient(red, #00f);
+      }
+    "#},
+      Browsers {
+        firefox: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    image_set_fallback_test(
+      r#"
+      .foo {
+        list-style-image: image-set("foo.png" 1x, "foo@2x.png" 2x);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        list-style-image: url("foo.png");
+        list-style-image: image-set("foo.png" 1x, "foo@2x.png" 2x);
+      }
+    "#},
+      Browsers {
+        ie: Some(11 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    image_set_fallback_test(
+      r#"
+      .foo {
+        background-image: image-set("foo.png" 1x, "foo@2x.png" 2x);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        background-image: image-set("foo.png" 1x, "foo@2x.png" 2x);
+      }
+    "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_image_functions() {
+    minify_test(
+      ".foo { background-image: paint(checkerboard) }",
+      ".foo{background-image:paint(checkerboard)}",
+    );
+    minify_test(
+      ".foo { background-image: paint( checkerboard , 10px ,  rgb(255, 0, 0) ) }",
+      ".foo{background-image:paint(checkerboard,10px,red)}",
+    );
+    minify_test(
+      ".foo { mask-image: paint(ripple, var(--x)) }",
+      ".foo{mask-image:paint(ripple,var(--x))}",
+    );
+    minify_test(
+      ".foo { background-image: cross-fade(url(a.png) 25%, url(b.png) 75%) }",
+      ".foo{background-image:cross-fade(url(a.png) 25%,url(b.png) 75%)}",
+    );
+    minify_test(
+      ".foo { background-image: cross-fade(50% url(a.png), rgb(255, 0, 0)) }",
+      ".foo{background-image:cross-fade(url(a.png) 50%,red)}",
+    );
+    minify_test(
+      ".foo { background-image: cross-fade(linear-gradient(yellow, blue), url(b.png)) }",
+      ".foo{background-image:cross-fade(linear-gradient(#ff0,#00f),url(b.png))}",
+    );
+    minify_test(
+      ".foo { background-image: element(#target) }",
+      ".foo{background-image:element(#target)}",
+    );
+    minify_test(
+      ".foo { background: element(#target) no-repeat, paint(foo) }",
+      ".foo{background:element(#target) no-repeat,paint(foo)}",
+    );
+    minify_test(
+      ".foo { list-style-image: cross-fade(url(a.png), url(b.png) 30%) }",
+      ".foo{list-style-image:cross-fade(url(a.png),url(b.png) 30%)}",
+    );
+    prefix_test(
+      ".foo { background-image: cross-fade(url(a.png) 25%, lab(40% 56.6 39)) }",
+      indoc! {r#"
+      .foo {
+        background-image: cross-fade(url("a.png") 25%, #b32323);
+        background-image: cross-fade(url("a.png") 25%, lab(40% 56.6 39));
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_color() {
+    minify_test(".foo { color: yellow }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgb(255, 255, 0) }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgba(255, 255, 0, 1) }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgba(255, 255, 0, 0.8) }", ".foo{color:#ff0c}");
+    minify_test(".foo { color: rgb(128, 128, 128) }", ".foo{color:gray}");
+    minify_test(".foo { color: rgb(123, 255, 255) }", ".foo{color:#7bffff}");
+    minify_test(".foo { color: rgba(123, 255, 255, 0.5) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(123 255 255) }", ".foo{color:#7bffff}");
+    minify_test(".foo { color: rgb(123 255 255 / .5) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(123 255 255 / 50%) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(48% 100% 100% / 50%) }", ".foo{color:#7affff80}");
+    minify_test(".foo { color: hsl(100deg, 100%, 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100, 100%, 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100 100% 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsl(100 100% 50% / .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsla(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsla(100 100% 50% / .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: transparent }", ".foo{color:#0000}");
+    minify_test(".foo { color: currentColor }", ".foo{color:currentColor}");
+    minify_test(".foo { color: hwb(194 0% 0%) }", ".foo{color:#00c4ff}");
+    minify_test(".foo { color: hwb(194 0% 0% / 50%) }", ".foo{color:#00c4ff80}");
+    minify_test(".foo { color: hwb(194 0% 50%) }", ".foo{color:#006280}");
+    minify_test(".foo { color: hwb(194 50% 0%) }", ".foo{color:#80e1ff}");
+    minify_test(".foo { color: hwb(194 50% 50%) }", ".foo{color:gray}");
+    // minify_test(".foo { color: ActiveText }", ".foo{color:ActiveTet}");
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+    );
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664 / 100%); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+    );
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664 / 50%); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664/.5)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 27); }",
+      ".foo{color:lch(29.2345% 44.2 27)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 45deg); }",
+      ".foo{color:lch(29.2345% 44.2 45)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 .5turn); }",
+      ".foo{color:lch(29.2345% 44.2 180)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 27 / 100%); }",
+      ".foo{color:lch(29.2345% 44.2 27)}",
     );
     minify_test(
       ".foo { color: lch(29.2345% 44.2 27 / 50%); }",
@@ -14015,6 +15590,149 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_color_format() {
+    use crate::printer::ColorFormat;
+
+    fn test(source: &str, expected: &str, color_format: ColorFormat, targets: Option<Browsers>) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          color_format,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let source = ".foo { color: #ff0000; background: rgb(0 0 0 / .5); border-color: #aabbcc }";
+    test(
+      source,
+      ".foo{color:red;background:#00000080;border-color:#abc}",
+      ColorFormat::Shortest,
+      None,
+    );
+    test(
+      source,
+      ".foo{color:#ff0000;background:#00000080;border-color:#aabbcc}",
+      ColorFormat::Hex,
+      None,
+    );
+    test(
+      ".foo { color: red; border-color: transparent }",
+      ".foo{color:#ff0000;border-color:#00000000}",
+      ColorFormat::Hex,
+      None,
+    );
+    test(
+      source,
+      ".foo{color:rgb(255,0,0);background:rgba(0,0,0,.5);border-color:rgb(170,187,204)}",
+      ColorFormat::Rgb,
+      None,
+    );
+    test(
+      ".foo { color: hsl(120 none 50%); background: lab(50% 20 30) }",
+      ".foo{color:hsl(120 none 50%);background:lab(50% 20 30)}",
+      ColorFormat::Preserve,
+      None,
+    );
+    test(
+      ".foo { color: hsl(120 none 50%) }",
+      ".foo{color:gray}",
+      ColorFormat::Shortest,
+      None,
+    );
+    test(
+      ".foo { color: #ff0000 }",
+      ".foo{color:oklch(62.7955% .257683 29.2339)}",
+      ColorFormat::Modern,
+      None,
+    );
+    test(
+      ".foo { color: #ff0000 }",
+      ".foo{color:red}",
+      ColorFormat::Modern,
+      Some(Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      }),
+    );
+  }
+
+  #[test]
+  fn test_gamut_mapping() {
+    use crate::printer::GamutMapping;
+
+    fn test(source: &str, expected: &str, gamut_mapping: GamutMapping, targets: Browsers) {
+      let targets = Some(targets);
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          gamut_mapping,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let chrome = Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    };
+    let chrome_and_safari = Browsers {
+      chrome: Some(90 << 16),
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    };
+
+    let source =
+      ".foo { color: oklch(70% 0.3 150); background: linear-gradient(lab(50% 80 -20), lab(60% 10 10)) }";
+    test(
+      source,
+      concat!(
+        ".foo{color:#00c051;color:lab(68.7583% -94.0446 67.4796);",
+        "background:linear-gradient(#e0009b,#a78a7f);background:linear-gradient(lab(50% 80 -20),lab(60% 10 10))}"
+      ),
+      GamutMapping::ChromaReduction,
+      chrome,
+    );
+    test(
+      source,
+      concat!(
+        ".foo{color:#00cb00;color:lab(68.7583% -94.0446 67.4796);",
+        "background:linear-gradient(#e1009c,#a78a7f);background:linear-gradient(lab(50% 80 -20),lab(60% 10 10))}"
+      ),
+      GamutMapping::Clip,
+      chrome,
+    );
+
+    // Only the sRGB fallback is mapped. color() allows components outside the P3 gamut.
+    let source = ".foo { color: oklch(70% 0.4 150) }";
+    test(
+      source,
+      ".foo{color:#00bf54;color:color(display-p3 -.419032 .820075 -.210266);color:lab(69.7635% -130.77 123.403)}",
+      GamutMapping::ChromaReduction,
+      chrome_and_safari,
+    );
+    test(
+      source,
+      ".foo{color:#00d600;color:color(display-p3 -.419032 .820075 -.210266);color:lab(69.7635% -130.77 123.403)}",
+      GamutMapping::Clip,
+      chrome_and_safari,
+    );
+  }
+
   #[test]
   fn test_relative_color() {
     fn test(input: &str, output: &str) {
@@ -15810,28 +17528,221 @@ mod tests {
   }
 
   #[test]
-  fn test_color_mix() {
+  fn test_light_dark() {
+    use crate::printer::LightDarkMode;
+
+    fn test(source: &str, expected: &str, light_dark_mode: LightDarkMode, targets: Option<Browsers>) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          light_dark_mode,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let safari = Some(Browsers {
+      safari: Some(15 << 16),
+      ..Browsers::default()
+    });
+
     minify_test(
-      ".foo { color: color-mix(in lab, purple 50%, plum 50%); }",
-      ".foo{color:lab(51.5117% 43.3777 -29.0443)}",
+      ".foo { color: light-dark(#ff0000, rgb(0, 0, 255)); }",
+      ".foo{color:light-dark(red,#00f)}",
     );
+    minify_test(".foo { color-scheme: normal; }", ".foo{color-scheme:normal}");
+    minify_test(".foo { color-scheme: dark light; }", ".foo{color-scheme:light dark}");
+    minify_test(".foo { color-scheme: only dark; }", ".foo{color-scheme:dark only}");
+    minify_test(".foo { color-scheme: light custom; }", ".foo{color-scheme:light}");
+    minify_test(".foo { color-scheme: only only; }", ".foo{color-scheme:only only}");
     minify_test(
-      ".foo { color: color-mix(in lch, peru 40%, palegoldenrod); }",
-      ".foo{color:lch(79.7255% 40.4542 84.7634)}",
+      ".foo { color: color-mix(in srgb, light-dark(red, blue), white); }",
+      ".foo{color:color-mix(in srgb,light-dark(red,#00f),white)}",
     );
     minify_test(
-      ".foo { color: color-mix(in lch, teal 65%, olive); }",
-      ".foo{color:lch(49.4431% 40.4806 162.546)}",
+      ".foo { color: rgb(from light-dark(red, blue) r g b); }",
+      ".foo{color:rgb(from light-dark(red,#00f) r g b)}",
     );
-    minify_test(
-      ".foo { color: color-mix(in lch, white, black); }",
-      ".foo{color:lch(50% 0 none)}",
+
+    let source = indoc! {r#"
+      :root { color-scheme: light dark; }
+      .foo { color: light-dark(red, blue); background: light-dark(#fff, #000) !important; }
+      .bar { color-scheme: dark only; }
+    "#};
+
+    test(
+      source,
+      ":root{color-scheme:light dark}.foo{color:light-dark(red,#00f);background:light-dark(#fff,#000)!important}.bar{color-scheme:dark only}",
+      LightDarkMode::CustomProperties,
+      Some(Browsers {
+        chrome: Some(123 << 16),
+        ..Browsers::default()
+      }),
     );
-    minify_test(
-      ".foo { color: color-mix(in xyz, rgb(82.02% 30.21% 35.02%) 75.23%, rgb(5.64% 55.94% 85.31%)); }",
-      ".foo{color:color(xyz .287458 .208776 .260566)}",
+    test(
+      source,
+      concat!(
+        ":root{--lightningcss-light:initial;--lightningcss-dark: ;color-scheme:light dark}",
+        "@media (prefers-color-scheme:dark){:root{--lightningcss-light: ;--lightningcss-dark:initial}}",
+        ".foo{color:var(--lightningcss-light,red) var(--lightningcss-dark,#00f);",
+        "background:var(--lightningcss-light,#fff) var(--lightningcss-dark,#000)!important}",
+        ".bar{--lightningcss-light: ;--lightningcss-dark:initial;color-scheme:dark only}"
+      ),
+      LightDarkMode::CustomProperties,
+      safari,
     );
-    minify_test(
+    test(
+      source,
+      concat!(
+        ":root{color-scheme:light dark}",
+        ".foo{color:red;background:#fff!important}",
+        "@media (prefers-color-scheme:dark){.foo{color:#00f;background:#000!important}}",
+        ".bar{color-scheme:dark only}"
+      ),
+      LightDarkMode::MediaQuery,
+      safari,
+    );
+    test(
+      ".foo { --custom: light-dark(red, blue); }",
+      ".foo{--custom:var(--lightningcss-light,red) var(--lightningcss-dark,#00f)}",
+      LightDarkMode::CustomProperties,
+      safari,
+    );
+
+    // Each side of light-dark() is converted when fallbacks are needed.
+    let chrome = Some(Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+    test(
+      ".a { box-shadow: 0 0 2px light-dark(oklch(70% 0.3 150), black); }",
+      concat!(
+        ".a{box-shadow:0 0 2px var(--lightningcss-light,#00c051) var(--lightningcss-dark,#000);",
+        "box-shadow:0 0 2px var(--lightningcss-light,lab(68.7583% -94.0446 67.4796)) var(--lightningcss-dark,lab(0% 0 0))}"
+      ),
+      LightDarkMode::CustomProperties,
+      chrome,
+    );
+    test(
+      ".a { text-shadow: 0 0 2px light-dark(lab(50% 80 -20), black); }",
+      concat!(
+        ".a{text-shadow:0 0 2px var(--lightningcss-light,#e0009b) var(--lightningcss-dark,#000);",
+        "text-shadow:0 0 2px var(--lightningcss-light,lab(50% 80 -20)) var(--lightningcss-dark,#000)}"
+      ),
+      LightDarkMode::CustomProperties,
+      chrome,
+    );
+    test(
+      ".a { color: light-dark(currentColor, oklch(70% 0.3 150)); }",
+      concat!(
+        ".a{color:var(--lightningcss-light,currentColor) var(--lightningcss-dark,#00c051);",
+        "color:var(--lightningcss-light,currentColor) var(--lightningcss-dark,lab(68.7583% -94.0446 67.4796))}"
+      ),
+      LightDarkMode::CustomProperties,
+      chrome,
+    );
+  }
+
+  #[test]
+  fn test_color_fallback_strategy() {
+    fn test(source: &str, expected: &str, color_fallback_strategy: ColorFallbackStrategy) {
+      let targets = Some(Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      });
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          color_fallback_strategy,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let source = ".foo { color: lab(50% 80 -20); border-color: color(display-p3 1 .5 0); border-top-color: red; width: 10px } .bar { color: red }";
+    test(
+      source,
+      concat!(
+        ".foo{color:#e0009b;color:color(display-p3 .807948 .154646 .596674);color:lab(50% 80 -20);width:10px;",
+        "border-color:red #ff8004 #ff8004;border-color:red color(display-p3 1 .5) color(display-p3 1 .5)}",
+        ".bar{color:red}"
+      ),
+      ColorFallbackStrategy::Inline,
+    );
+    test(
+      source,
+      concat!(
+        ".foo{color:#e0009b;width:10px;border-color:red #ff8004 #ff8004}",
+        "@media (color-gamut:p3){.foo{color:color(display-p3 .807948 .154646 .596674);color:lab(50% 80 -20);",
+        "border-color:red color(display-p3 1 .5) color(display-p3 1 .5)}}",
+        ".bar{color:red}"
+      ),
+      ColorFallbackStrategy::MediaQuery,
+    );
+    test(
+      source,
+      concat!(
+        ".foo{color:#e0009b;width:10px;border-color:red #ff8004 #ff8004}",
+        "@supports (color:lab(0% 0 0)){.foo{color:color(display-p3 .807948 .154646 .596674);color:lab(50% 80 -20);",
+        "border-color:red color(display-p3 1 .5) color(display-p3 1 .5)}}",
+        ".bar{color:red}"
+      ),
+      ColorFallbackStrategy::Supports,
+    );
+    test(
+      ".foo { background: lab(50% 80 -20) !important; outline-color: var(--x); outline: 2px solid lab(50% 80 -20); outline-width: var(--w) }",
+      concat!(
+        ".foo{outline-color:var(--x);outline:2px solid #e0009b;outline-width:var(--w);background:#e0009b!important}",
+        "@media (color-gamut:p3){.foo{outline:2px solid color(display-p3 .807948 .154646 .596674);",
+        "outline:2px solid lab(50% 80 -20);outline-width:var(--w);",
+        "background:color(display-p3 .807948 .154646 .596674)!important;background:lab(50% 80 -20)!important}}"
+      ),
+      ColorFallbackStrategy::MediaQuery,
+    );
+  }
+
+  #[test]
+  fn test_color_mix() {
+    minify_test(
+      ".foo { color: color-mix(in lab, purple 50%, plum 50%); }",
+      ".foo{color:lab(51.5117% 43.3777 -29.0443)}",
+    );
+    minify_test(
+      ".foo { color: color-mix(in lch, peru 40%, palegoldenrod); }",
+      ".foo{color:lch(79.7255% 40.4542 84.7634)}",
+    );
+    minify_test(
+      ".foo { color: color-mix(in lch, teal 65%, olive); }",
+      ".foo{color:lch(49.4431% 40.4806 162.546)}",
+    );
+    minify_test(
+      ".foo { color: color-mix(in lch, white, black); }",
+      ".foo{color:lch(50% 0 none)}",
+    );
+    minify_test(
+      ".foo { color: color-mix(in xyz, rgb(82.02% 30.21% 35.02%) 75.23%, rgb(5.64% 55.94% 85.31%)); }",
+      ".foo{color:color(xyz .287458 .208776 .260566)}",
+    );
+    minify_test(
       ".foo { color: color-mix(in lch, white, blue); }",
       ".foo{color:lch(64.7842% 65.6007 301.364)}",
     );
@@ -18036,6 +19947,137 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_grid_template_areas_validation() {
+    use std::sync::{Arc, RwLock};
+
+    let source = r#"
+      .a {
+        grid-template-areas: "a a b" "a c c";
+      }
+
+      .b {
+        grid-template-areas: "a a ." "a a .";
+        grid-template: "x y" 1fr "y y" 1fr / 1fr 1fr;
+      }
+
+      .c {
+        grid-template-areas: "a b a";
+      }
+    "#;
+
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".a{grid-template-areas:\"a a b\"\"a c c\"}.b{grid-template:\"x y\"1fr\"y y\"1fr/1fr 1fr}.c{grid-template-areas:\"a b a\"}"
+    );
+
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    let warning = |name: &str, line| Error {
+      kind: ParserError::NonRectangularGridArea(name.to_owned().into()),
+      loc: Some(ErrorLocation {
+        filename: "test.css".into(),
+        line,
+        column: 7,
+      }),
+    };
+    assert_eq!(*warnings, vec![warning("a", 1), warning("y", 5), warning("a", 10)]);
+  }
+
+  #[test]
+  fn test_masonry() {
+    minify_test(
+      ".foo { grid-template-rows: masonry }",
+      ".foo{grid-template-rows:masonry}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: MASONRY }",
+      ".foo{grid-template-columns:masonry}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(3, 1fr); grid-template-rows: masonry; grid-template-areas: none }",
+      ".foo{grid-template:masonry/repeat(3,1fr)}",
+    );
+    minify_test(
+      ".foo { grid-template-rows: masonry; grid-template-columns: 1fr 1fr; grid-template-areas: \"a b\" }",
+      ".foo{grid-template-rows:masonry;grid-template-columns:1fr 1fr;grid-template-areas:\"a b\"}",
+    );
+    minify_test(".foo { masonry-auto-flow: pack }", ".foo{masonry-auto-flow:pack}");
+    minify_test(".foo { masonry-auto-flow: next }", ".foo{masonry-auto-flow:next}");
+    minify_test(
+      ".foo { masonry-auto-flow: definite-first }",
+      ".foo{masonry-auto-flow:pack}",
+    );
+    minify_test(
+      ".foo { masonry-auto-flow: pack ordered }",
+      ".foo{masonry-auto-flow:ordered}",
+    );
+    minify_test(
+      ".foo { masonry-auto-flow: ordered next }",
+      ".foo{masonry-auto-flow:next ordered}",
+    );
+    minify_test(
+      ".foo { masonry-auto-flow: next next }",
+      ".foo{masonry-auto-flow:next next}",
+    );
+
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      .foo { display: grid; grid-template-rows: masonry }
+      .bar { display: grid; grid-template-rows: 1fr 1fr }
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          firefox: Some(130 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![Error {
+        kind: ParserError::UnsupportedMasonry,
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 1,
+          column: 7,
+        }),
+      }]
+    );
+  }
+
   #[test]
   fn test_moz_document() {
     minify_test(
@@ -18058,13 +20100,47 @@ mod tests {
     "#,
       "@-moz-document url-prefix(){h1{color:#ff0}}",
     );
+    minify_test(
+      "@-moz-document url-prefix(https://example.com/foo) { h1 { color: yellow } }",
+      "@-moz-document url-prefix(\"https://example.com/foo\"){h1{color:#ff0}}",
+    );
+    minify_test(
+      "@-moz-document url-prefix(\"https://example.com/\") { h1 { color: yellow } }",
+      "@-moz-document url-prefix(\"https://example.com/\"){h1{color:#ff0}}",
+    );
+    minify_test(
+      "@-moz-document url(https://example.com/), url(\"https://example.org/a b\"), domain(example.com), regexp(\"https:.*\") { h1 { color: yellow } }",
+      "@-moz-document url(https://example.com/),url(https://example.org/a\\20 b),domain(\"example.com\"),regexp(\"https:.*\"){h1{color:#ff0}}",
+    );
+    minify_test(
+      "@-moz-document domain(\"example.com\") { h1 { color: yellow } }",
+      "@-moz-document domain(\"example.com\"){h1{color:#ff0}}",
+    );
+    minify_test(
+      "@document url-prefix() { h1 { color: yellow } }",
+      "@document url-prefix(){h1{color:#ff0}}",
+    );
+    minify_test(
+      "@-MOZ-DOCUMENT URL-PREFIX() { h1 { color: yellow } }",
+      "@-moz-document url-prefix(){h1{color:#ff0}}",
+    );
+    test(
+      "@-moz-document url-prefix(), domain(example.com) { h1 { color: yellow } }",
+      indoc! {r#"
+        @-moz-document url-prefix(), domain("example.com") {
+          h1 {
+            color: #ff0;
+          }
+        }
+      "#},
+    );
     error_test(
-      "@-moz-document url-prefix(foo) {}",
+      "@-moz-document regexp(foo) {}",
       ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("foo".into())),
     );
     error_test(
-      "@-moz-document url-prefix(\"foo\") {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::String("foo".into())),
+      "@-moz-document foo(bar) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("foo".into())),
     );
   }
 
@@ -19551,7 +21627,7 @@ mod tests {
       "#},
     );
 
-    nesting_error_test(
+    nesting_test(
       r#"
     .foo {
       color: blue;
@@ -19560,7 +21636,92 @@ mod tests {
       }
     }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::CurlyBracketBlock),
+      indoc! {r#"
+      .foo {
+        color: #00f;
+      }
+
+      .foo div {
+        color: red;
+      }
+      "#},
+    );
+
+    nesting_error_test(
+      r#"
+    .foo {
+      color: blue;
+      div >
+    }
+    "#,
+      ParserError::EndOfInput,
+    );
+
+    nesting_test(
+      r#"
+    .foo {
+      a:hover {
+        color: red;
+      }
+      color: blue;
+    }
+    "#,
+      indoc! {r#"
+      .foo a:hover {
+        color: red;
+      }
+
+      .foo {
+        color: #00f;
+      }
+      "#},
+    );
+
+    nesting_test(
+      r#"
+    .foo {
+      color: red;
+      div { color: green; }
+      background: blue;
+      @media (min-width: 100px) {
+        color: yellow;
+        .bar { color: pink }
+        width: 10px;
+      }
+      margin: 0;
+    }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      .foo div {
+        color: green;
+      }
+
+      .foo {
+        background: #00f;
+      }
+
+      @media (min-width: 100px) {
+        .foo {
+          color: #ff0;
+        }
+
+        .foo .bar {
+          color: pink;
+        }
+
+        .foo {
+          width: 10px;
+        }
+      }
+
+      .foo {
+        margin: 0;
+      }
+      "#},
     );
 
     nesting_test(
@@ -19807,13 +21968,13 @@ mod tests {
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: red;
-        }
-
         .foo .bar {
           color: #00f;
         }
+
+        .foo {
+          color: red;
+        }
       "#},
     );
 
@@ -19828,12 +21989,15 @@ mod tests {
       indoc! {r#"
         article {
           color: green;
-          color: red;
         }
 
         article {
           color: #00f;
         }
+
+        article {
+          color: red;
+        }
       "#},
     );
 
@@ -19892,13 +22056,52 @@ mod tests {
     nesting_test_no_targets(
       r#"
         .foo {
-          color: blue;
-          @nest .bar & {
-            color: red;
-            &.baz {
-              color: green;
-            }
-          }
+          color: red;
+          div { color: green; }
+          background: blue;
+          @media (min-width: 100px) {
+            color: yellow;
+            .bar { color: pink }
+            width: 10px;
+          }
+          margin: 0;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          color: red;
+
+          & div {
+            color: green;
+          }
+
+          background: #00f;
+
+          @media (min-width: 100px) {
+            color: #ff0;
+
+            & .bar {
+              color: pink;
+            }
+
+            width: 10px;
+          }
+
+          margin: 0;
+        }
+      "#},
+    );
+
+    nesting_test_no_targets(
+      r#"
+        .foo {
+          color: blue;
+          @nest .bar & {
+            color: red;
+            &.baz {
+              color: green;
+            }
+          }
         }
       "#,
       indoc! {r#"
@@ -19960,6 +22163,63 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_nesting_expand() {
+    use crate::printer::NestingMode;
+
+    fn test(source: &str, expected: &str) {
+      let targets = Some(Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      });
+      let mut stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          nesting: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          nesting_mode: NestingMode::Expand,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    test(
+      ".a, .b { color: red; & .c { color: blue } }",
+      ".a,.b{color:red}.a .c,.b .c{color:#00f}",
+    );
+    test(
+      ".a, .b { & .c, & .d { &:hover { color: red } } }",
+      ".a .c:hover,.b .c:hover,.a .d:hover,.b .d:hover{color:red}",
+    );
+    test(".a, .b { & + & { color: red } }", ".a+.a,.a+.b,.b+.a,.b+.b{color:red}");
+    test(".a .b, div { .x& { color: red } }", ".a .x.b,div.x{color:red}");
+    test("div { span& { color: red } }", "span:is(div){color:red}");
+    test(
+      ".a, .b { .c { color: red } color: blue }",
+      ".a .c,.b .c{color:red}.a,.b{color:#00f}",
+    );
+    test(".a, .b { :not(&) { color: red } }", ":not(:is(.a,.b)){color:red}");
+    test(
+      ".a, .b { @media (width > 500px) { color: red; .c { color: blue } } }",
+      "@media (min-width:500.001px){.a,.b{color:red}.a .c,.b .c{color:#00f}}",
+    );
+    test(".a, .b { &::before { color: red } }", ".a:before,.b:before{color:red}");
+  }
+
   #[test]
   fn test_css_modules() {
     css_modules_test(
@@ -20098,6 +22358,33 @@ mod tests {
       Default::default(),
     );
 
+    css_modules_test(
+      r#"
+      ::view-transition-old(hero) {
+        animation-duration: 1s;
+      }
+
+      ::view-transition-group(*.card) {
+        animation-duration: 2s;
+      }
+    "#,
+      indoc! {r#"
+      ::view-transition-old(EgL3uq_hero) {
+        animation-duration: 1s;
+      }
+
+      ::view-transition-group(*.EgL3uq_card) {
+        animation-duration: 2s;
+      }
+    "#},
+      map! {
+        "hero" => "EgL3uq_hero",
+        "card" => "EgL3uq_card"
+      },
+      HashMap::new(),
+      Default::default(),
+    );
+
     #[cfg(feature = "grid")]
     css_modules_test(
       r#"
@@ -20541,6 +22828,28 @@ mod tests {
       },
     );
 
+    css_modules_test(
+      r#"
+      .foo {
+        background: paint(checkerboard, 10px, red);
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo {
+        background: paint(EgL3uq_checkerboard, 10px, red);
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "checkerboard" => "EgL3uq_checkerboard"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        paint_worklets: true,
+        ..Default::default()
+      },
+    );
+
     // Stable hashes between project roots.
     fn test_project_root(project_root: &str, filename: &str, hash: &str) {
       let stylesheet = StyleSheet::parse(
@@ -20659,3008 +22968,7905 @@ mod tests {
   }
 
   #[test]
-  fn test_unused_symbols() {
+  fn test_has_fallbacks() {
+    use crate::selector::HasPolyfill;
+    use std::sync::{Arc, RwLock};
+
     let source = r#"
-      .foo {
+      .card:has(> img) {
         color: red;
       }
 
-      .bar {
+      .foo, .bar:has(.baz) {
         color: green;
       }
 
-      .bar:hover {
-        color: purple;
-      }
-
-      .bar .baz {
-        background: red;
-      }
-
-      .baz:is(.bar) {
-        background: green;
-      }
-
-      #id {
-        animation: 2s test;
-      }
-
-      #other_id {
-        color: red;
-      }
-
-      @keyframes test {
-        from { color: red }
-        to { color: yellow }
-      }
-
-      @counter-style circles {
-        symbols: Ⓐ Ⓑ Ⓒ;
-      }
-
-      @keyframes fade {
-        from { opacity: 0 }
-        to { opacity: 1 }
-      }
-    "#;
-
-    let expected = indoc! {r#"
-      .foo {
-        color: red;
-      }
-
-      #id {
-        animation: 2s test;
-      }
-
-      @keyframes test {
-        from {
-          color: red;
-        }
-
-        to {
-          color: #ff0;
-        }
-      }
-    "#};
-
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
-        unused_symbols: vec!["bar", "other_id", "fade", "circles"]
-          .iter()
-          .map(|s| String::from(*s))
-          .collect(),
-        ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
-
-    let source = r#"
-      .foo {
-        color: red;
-
-        &.bar {
-          color: green;
+      @media (hover) {
+        :is(.a, .b:has(.c)) {
+          color: blue;
         }
       }
     "#;
 
-    let expected = indoc! {r#"
-      .foo {
-        color: red;
-      }
-    "#};
+    let targets = Some(Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    });
 
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
     let mut stylesheet = StyleSheet::parse(
-      &source,
+      source,
       ParserOptions {
-        nesting: true,
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
         ..ParserOptions::default()
       },
     )
     .unwrap();
     stylesheet
       .minify(MinifyOptions {
-        unused_symbols: vec!["bar"].iter().map(|s| String::from(*s)).collect(),
+        targets,
+        remove_unsupported_has: true,
         ..MinifyOptions::default()
       })
       .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
-
-    let source = r#"
-      .foo {
-        color: red;
-
-        &.bar {
-          color: purple;
-        }
-
-        @nest &.bar {
-          color: orange;
-        }
-
-        @nest :not(&) {
-          color: green;
-        }
-
-        @media (orientation: portrait) {
-          color: brown;
-        }
-      }
-
-      .x {
-        color: purple;
-
-        &.y {
-          color: green;
-        }
-      }
-    "#;
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{color:green}");
 
-    let expected = indoc! {r#"
-      :not(.foo) {
-        color: green;
-      }
-    "#};
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    let has_warning = |line, column| Error {
+      kind: ParserError::SelectorError(SelectorError::UnsupportedHasSelector),
+      loc: Some(ErrorLocation {
+        filename: "test.css".into(),
+        line,
+        column,
+      }),
+    };
+    assert_eq!(
+      *warnings,
+      vec![has_warning(1, 7), has_warning(5, 7), has_warning(10, 9)]
+    );
 
+    // Supported targets don't warn.
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
     let mut stylesheet = StyleSheet::parse(
-      &source,
+      source,
       ParserOptions {
-        nesting: true,
+        warnings: warnings.clone(),
         ..ParserOptions::default()
       },
     )
     .unwrap();
     stylesheet
       .minify(MinifyOptions {
-        unused_symbols: vec!["foo", "x"].iter().map(|s| String::from(*s)).collect(),
+        targets: Some(Browsers {
+          chrome: Some(105 << 16),
+          ..Browsers::default()
+        }),
+        remove_unsupported_has: true,
         ..MinifyOptions::default()
       })
       .unwrap();
+    assert!(warnings.unwrap().read().unwrap().is_empty());
+
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets,
+        has_polyfill: Some("data-has"),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      r#".card[data-has~="0"]{color:red}.foo,.bar[data-has~="1"]{color:green}@media (hover){:is(.a,.b[data-has~="2"]){color:#00f}}"#
+    );
+    assert_eq!(
+      res.has_polyfills,
+      Some(vec![
+        HasPolyfill {
+          id: "0".into(),
+          selector: ">img".into()
+        },
+        HasPolyfill {
+          id: "1".into(),
+          selector: ".baz".into()
+        },
+        HasPolyfill {
+          id: "2".into(),
+          selector: ".c".into()
+        },
+      ])
+    );
+
     let res = stylesheet
       .to_css(PrinterOptions {
+        minify: true,
         targets: Some(Browsers {
-          chrome: Some(95 << 16),
+          safari: Some(15 << 16 | 4 << 8),
           ..Browsers::default()
         }),
+        has_polyfill: Some("data-has"),
         ..PrinterOptions::default()
       })
       .unwrap();
-    assert_eq!(res.code, expected);
+    assert!(res.code.starts_with(".card:has(>img){color:red}"));
+    assert_eq!(res.has_polyfills, None);
+  }
 
-    let source = r#"
-      @property --EgL3uq_foo {
-        syntax: "<color>";
-        inherits: false;
-        initial-value: #ff0;
+  #[test]
+  fn test_local_global_without_css_modules() {
+    use crate::css_modules::LocalGlobalMode;
+    use std::sync::{Arc, RwLock};
+
+    let source = ":global(.foo) .bar, :local(.baz) {color: red}";
+    let local_global_test = |mode, expected: &str| {
+      let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+      let stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          local_global: mode,
+          warnings: warnings.clone(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      let count = warnings.unwrap().read().unwrap().len();
+      count
+    };
+
+    assert_eq!(
+      local_global_test(LocalGlobalMode::Warn, ":global(.foo) .bar,:local(.baz){color:red}"),
+      2
+    );
+    assert_eq!(
+      local_global_test(LocalGlobalMode::Strip, ".foo .bar,.baz{color:red}"),
+      0
+    );
+    assert_eq!(
+      local_global_test(LocalGlobalMode::Preserve, ":global(.foo) .bar,:local(.baz){color:red}"),
+      0
+    );
+
+    let res = StyleSheet::parse(
+      source,
+      ParserOptions {
+        local_global: LocalGlobalMode::Error,
+        ..ParserOptions::default()
+      },
+    );
+    assert_eq!(
+      res.unwrap_err().kind,
+      ParserError::SelectorError(SelectorError::UnsupportedPseudoClassOrElement("global".into()))
+    );
+  }
+
+  #[test]
+  fn test_custom_element_usage() {
+    let stylesheet = StyleSheet::parse(
+      r#"
+      my-button::part(label icon):hover, x-tab:state(selected) {
+        color: red;
       }
 
-      @font-palette-values --EgL3uq_Cooler {
-        font-family: Bixa;
-        base-palette: 1;
-        override-colors: 1 #7EB7E4;
+      @media (min-width: 500px) {
+        :is(My-Card, div):not(x-tab) > font-face::part(header) {
+          color: red;
+        }
       }
 
-      .EgL3uq_foo {
-        --EgL3uq_foo: red;
+      ui-dialog {
+        &:state(open) {
+          color: red;
+        }
       }
 
-      .EgL3uq_bar {
-        color: green;
+      @keyframes my-animation {
+        from { color: red }
       }
-    "#;
+    "#,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
 
-    let expected = indoc! {r#"
-      .EgL3uq_bar {
+    let set = |names: &[&str]| -> std::collections::BTreeSet<String> {
+      names.iter().map(|name| name.to_string()).collect()
+    };
+    let usage = stylesheet.custom_element_usage();
+    assert_eq!(usage.tag_names, set(&["my-button", "my-card", "ui-dialog", "x-tab"]));
+    assert_eq!(usage.parts, set(&["header", "icon", "label"]));
+    assert_eq!(usage.states, set(&["open", "selected"]));
+  }
+
+  #[test]
+  fn test_attribute_selector_normalization() {
+    use std::sync::{Arc, RwLock};
+
+    fn normalize_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets: Some(Browsers {
+            chrome: Some(100 << 16),
+            ..Browsers::default()
+          }),
+          normalize_attribute_selectors: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    normalize_test(r#"[type="Text" i] {color: red}"#, "[type=text i]{color:red}");
+    normalize_test(r#"[type="Text"] {color: red}"#, "[type=Text]{color:red}");
+    normalize_test(r#"[type="Text" s] {color: red}"#, "[type=Text s]{color:red}");
+    normalize_test(
+      r#"[type="Text" i], [type="TEXT" i], [type=text i] {color: red}"#,
+      "[type=text i]{color:red}",
+    );
+    normalize_test(r#"input[x="y" i][x="Y" i] {color: red}"#, "input[x=y i]{color:red}");
+    normalize_test(r#"[x][x], [x] [x] {color: red}"#, "[x],[x] [x]{color:red}");
+    normalize_test(
+      r#":is([lang="EN" i], [lang="en" i]) {color: red}"#,
+      ":is([lang=en i],[lang=en i]){color:red}",
+    );
+    normalize_test(
+      r#"[type="A" i] {color: red} [type="a" i] {background: green}"#,
+      "[type=a i]{color:red;background:green}",
+    );
+
+    let source = r#"
+      @namespace svg url(http://www.w3.org/2000/svg);
+      svg|a {
+        color: red;
+      }
+
+      foo|a, [xlink|href] {
         color: green;
       }
-    "#};
 
+      [|title], *|b {
+        color: blue;
+      }
+    "#;
+
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
     let mut stylesheet = StyleSheet::parse(
-      &source,
+      source,
       ParserOptions {
-        nesting: true,
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
         ..ParserOptions::default()
       },
     )
     .unwrap();
     stylesheet
       .minify(MinifyOptions {
-        unused_symbols: vec!["--EgL3uq_foo", "--EgL3uq_Cooler"]
-          .iter()
-          .map(|s| String::from(*s))
-          .collect(),
+        strict_namespaces: true,
         ..MinifyOptions::default()
       })
       .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
+
+    assert_eq!(
+      *warnings.unwrap().read().unwrap(),
+      vec![Error {
+        kind: ParserError::SelectorError(SelectorError::ExpectedNamespace("foo".into())),
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 6,
+          column: 7,
+        }),
+      }]
+    );
   }
 
   #[test]
-  fn test_svg() {
-    minify_test(".foo { fill: yellow; }", ".foo{fill:#ff0}");
-    minify_test(".foo { fill: url(#foo); }", ".foo{fill:url(#foo)}");
-    minify_test(".foo { fill: url(#foo) none; }", ".foo{fill:url(#foo) none}");
-    minify_test(".foo { fill: url(#foo) yellow; }", ".foo{fill:url(#foo) #ff0}");
-    minify_test(".foo { fill: none; }", ".foo{fill:none}");
-    minify_test(".foo { fill: context-fill; }", ".foo{fill:context-fill}");
-    minify_test(".foo { fill: context-stroke; }", ".foo{fill:context-stroke}");
-
-    minify_test(".foo { stroke: yellow; }", ".foo{stroke:#ff0}");
-    minify_test(".foo { stroke: url(#foo); }", ".foo{stroke:url(#foo)}");
-    minify_test(".foo { stroke: url(#foo) none; }", ".foo{stroke:url(#foo) none}");
-    minify_test(".foo { stroke: url(#foo) yellow; }", ".foo{stroke:url(#foo) #ff0}");
-    minify_test(".foo { stroke: none; }", ".foo{stroke:none}");
-    minify_test(".foo { stroke: context-fill; }", ".foo{stroke:context-fill}");
-    minify_test(".foo { stroke: context-stroke; }", ".foo{stroke:context-stroke}");
-
-    minify_test(".foo { marker-start: url(#foo); }", ".foo{marker-start:url(#foo)}");
+  fn test_expand_is_selectors() {
+    fn expand_test(source: &str, expected: &str, targets: Browsers, expand_is_selectors: bool) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets: Some(targets),
+          expand_is_selectors,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets: Some(targets),
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
 
-    minify_test(".foo { stroke-dasharray: 4 1 2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(".foo { stroke-dasharray: 4,1,2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(".foo { stroke-dasharray: 4, 1, 2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(
-      ".foo { stroke-dasharray: 4px, 1px, 2px; }",
-      ".foo{stroke-dasharray:4 1 2}",
-    );
+    let safari = Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
+    };
+    let chrome = Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
 
-    minify_test(".foo { mask: url('foo.svg'); }", ".foo{mask:url(foo.svg)}");
-    minify_test(
-      ".foo { mask: url(masks.svg#star) luminance }",
-      ".foo{mask:url(masks.svg#star) luminance}",
+    expand_test(
+      ":is(.a .b, .c) .d {color: red}",
+      ".a .b .d,.c .d{color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) 40px 20px }",
-      ".foo{mask:url(masks.svg#star) 40px 20px}",
+    expand_test(
+      ":is(.a .b, .c) .d {color: red}",
+      ":is(.a .b,.c) .d{color:red}",
+      safari,
+      false,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) 0 0 / 50px 50px }",
-      ".foo{mask:url(masks.svg#star) 0 0/50px 50px}",
+    expand_test(
+      ":is(.a .b, .c) .d {color: red}",
+      ":is(.a .b,.c) .d{color:red}",
+      chrome,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) repeat-x }",
-      ".foo{mask:url(masks.svg#star) repeat-x}",
+    expand_test(
+      ":is(.a, .b) .c {color: red}",
+      ":-webkit-any(.a,.b) .c{color:red}:is(.a,.b) .c{color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) stroke-box }",
-      ".foo{mask:url(masks.svg#star) stroke-box}",
+    expand_test(
+      "div:is(.a > .b, .c) {color: red}",
+      ".a>div.b,div.c{color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) stroke-box stroke-box }",
-      ".foo{mask:url(masks.svg#star) stroke-box}",
+    expand_test(
+      ":is(.a .b, .c) :is(.d .e, .f) {color: red}",
+      ":is(.a .b,.c) :is(.d .e,.f){color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) border-box }",
-      ".foo{mask:url(masks.svg#star)}",
+    expand_test(".x :is(.a > .b) {color: red}", ".x :is(.a>.b){color:red}", safari, true);
+    expand_test(
+      ".x > :is(.a .b, .c) {color: red}",
+      ".x>:is(.a .b,.c){color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) left / 16px repeat-y, url(masks.svg#circle) right / 16px repeat-y }",
-      ".foo{mask:url(masks.svg#star) 0/16px repeat-y,url(masks.svg#circle) 100%/16px repeat-y}",
+    expand_test(
+      ".x + :is(.a ~ .b).y {color: red}",
+      ".x+:is(.a~.b).y{color:red}",
+      safari,
+      true,
     );
-
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25; }",
-      ".foo{mask-border:url(border-mask.png) 25}",
+    expand_test(
+      ":is(.a .b, .c) .d, .x :is(.e > .f) {color: red}",
+      ":is(.a .b,.c) .d,.x :is(.e>.f){color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space alpha; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space}",
+    expand_test(
+      ":is(.a > .b) > .c .d {color: red}",
+      ".a>.b>.c .d{color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space luminance; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    expand_test(
+      ":is(.a .b, .c) :not(:is(.d .e)) {color: red}",
+      ".a .b :not(:is(.d .e)),.c :not(:is(.d .e)){color:red}",
+      safari,
+      true,
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') luminance 25 / 35px / 12px space; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    expand_test(
+      ":is(.a .b, .c)::before {color: red}",
+      ".a .b:before,.c:before{color:red}",
+      safari,
+      true,
     );
+  }
 
-    minify_test(
-      ".foo { clip-path: url('clip.svg#star'); }",
-      ".foo{clip-path:url(clip.svg#star)}",
+  #[test]
+  fn test_remove_shadowed_declarations() {
+    fn shadowed_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          remove_shadowed_declarations: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    shadowed_test(
+      ".a {color: red; width: 10px} .b {color: blue} .a {color: green}",
+      ".a{width:10px}.b{color:#00f}.a{color:green}",
     );
-    minify_test(".foo { clip-path: margin-box; }", ".foo{clip-path:margin-box}");
-    minify_test(
-      ".foo { clip-path: inset(100px 50px); }",
-      ".foo{clip-path:inset(100px 50px)}",
+    shadowed_test(
+      ".a {color: red} .b {color: blue} .a {color: green !important}",
+      ".b{color:#00f}.a{color:green!important}",
     );
-    minify_test(
-      ".foo { clip-path: inset(100px 50px round 5px); }",
-      ".foo{clip-path:inset(100px 50px round 5px)}",
+    shadowed_test(
+      ".a {color: red !important} .b {color: blue} .a {color: green}",
+      ".a{color:red!important}.b{color:#00f}.a{color:green}",
     );
-    minify_test(
-      ".foo { clip-path: inset(100px 50px round 5px 5px 5px 5px); }",
-      ".foo{clip-path:inset(100px 50px round 5px)}",
+    shadowed_test(
+      ".a, .b {color: red} .c {color: blue} .a {color: green}",
+      ".a,.b{color:red}.c{color:#00f}.a{color:green}",
     );
-    minify_test(".foo { clip-path: circle(50px); }", ".foo{clip-path:circle(50px)}");
-    minify_test(
-      ".foo { clip-path: circle(50px at center center); }",
-      ".foo{clip-path:circle(50px)}",
+    shadowed_test(
+      ".a {color: red} @media (min-width: 500px) {.a {color: green}} .b {color: blue}",
+      ".a{color:red}@media (min-width:500px){.a{color:green}}.b{color:#00f}",
     );
-    minify_test(
-      ".foo { clip-path: circle(50px at 50% 50%); }",
-      ".foo{clip-path:circle(50px)}",
+    shadowed_test(
+      "@media (min-width: 500px) {.a {color: red} .b {color: blue} .a {color: green}}",
+      "@media (min-width:500px){.b{color:#00f}.a{color:green}}",
     );
-    minify_test(
-      ".foo { clip-path: circle(50px at 0 100px); }",
-      ".foo{clip-path:circle(50px at 0 100px)}",
+    shadowed_test(
+      ".a {-webkit-transition: opacity 1s} .b {color: blue} .a {transition: opacity 1s}",
+      ".a{-webkit-transition:opacity 1s}.b{color:#00f}.a{transition:opacity 1s}",
     );
-    minify_test(
-      ".foo { clip-path: circle(closest-side at 0 100px); }",
-      ".foo{clip-path:circle(at 0 100px)}",
+    // Without targets, selectors using pseudo classes could be dropped by some browsers.
+    shadowed_test(
+      ".a:focus-visible {color: red} .b {color: blue} .a:focus-visible {color: green}",
+      ".a:focus-visible{color:red}.b{color:#00f}.a:focus-visible{color:green}",
     );
-    minify_test(
-      ".foo { clip-path: circle(farthest-side at 0 100px); }",
-      ".foo{clip-path:circle(farthest-side at 0 100px)}",
+
+    // With targets, the earlier declaration may be a fallback for browsers that do not support the later value.
+    fn targets_test(source: &str, expected: &str, targets: Browsers) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          remove_shadowed_declarations: true,
+          targets: Some(targets),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets: Some(targets),
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let ie = Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    };
+    let chrome = Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
+    targets_test(
+      ".a{width:100px}.b{color:blue}.a{width:fit-content}",
+      ".a{width:100px}.b{color:#00f}.a{width:fit-content}",
+      ie,
+    );
+    targets_test(
+      ".a{width:100px}.b{color:blue}.a{width:50px}",
+      ".b{color:#00f}.a{width:50px}",
+      ie,
+    );
+    targets_test(
+      ".a{display:block}.b{color:blue}.a{display:grid}",
+      ".a{display:block}.b{color:#00f}.a{display:grid}",
+      ie,
+    );
+    targets_test(
+      ".a{display:block}.b{color:blue}.a{display:grid}",
+      ".b{color:#00f}.a{display:grid}",
+      chrome,
+    );
+    // The later rule contains its own fallback.
+    targets_test(
+      ".a{color:red}.b{color:blue}.a{color:lab(50% 80 -20)}",
+      ".b{color:#00f}.a{color:#e0009b;color:lab(50% 80 -20)}",
+      chrome,
+    );
+    targets_test(
+      ".a{width:100px}.b{color:blue}.a{width:100px;width:fit-content}",
+      ".b{color:#00f}.a{width:100px;width:fit-content}",
+      ie,
+    );
+    targets_test(
+      ".a{color:red}.b{color:blue}.a{color:var(--foo)}",
+      ".a{color:red}.b{color:#00f}.a{color:var(--foo)}",
+      chrome,
     );
-    minify_test(
-      ".foo { clip-path: circle(closest-side at 50% 50%); }",
-      ".foo{clip-path:circle()}",
+  }
+
+  #[test]
+  fn test_merge_non_adjacent_media_rules() {
+    fn merge_media_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          merge_media_rules: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      .b { width: 10px }
+      @media (min-width: 500px) { .c { background: green } }
+      .d { height: 10px }
+      @media (min-width: 500px) { .e { color: red } }
+      "#,
+      "@media (min-width:500px){.a{color:red}.c{background:green}.e{color:red}}.b{width:10px}.d{height:10px}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(50px 60px at 0 10% 20%); }",
-      ".foo{clip-path:ellipse(50px 60px at 0 10% 20%)}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      .b { color: blue }
+      @media (min-width: 500px) { .c { color: green } }
+      "#,
+      "@media (min-width:500px){.a{color:red}}.b{color:#00f}@media (min-width:500px){.c{color:green}}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(50px 60px at center center); }",
-      ".foo{clip-path:ellipse(50px 60px)}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { margin-top: 0 } }
+      .b { margin: 10px }
+      @media (min-width: 500px) { .c { margin-left: 0 } }
+      "#,
+      "@media (min-width:500px){.a{margin-top:0}}.b{margin:10px}@media (min-width:500px){.c{margin-left:0}}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(closest-side closest-side at 50% 50%); }",
-      ".foo{clip-path:ellipse()}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      .b { margin-inline-start: 10px }
+      @media (min-width: 500px) { .c { margin-left: 0 } }
+      "#,
+      "@media (min-width:500px){.a{color:red}}.b{margin-inline-start:10px}@media (min-width:500px){.c{margin-left:0}}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(closest-side closest-side at 10% 20%); }",
-      ".foo{clip-path:ellipse(at 10% 20%)}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      @media (min-width: 500px) and (max-width: 700px) { .b { width: 10px } }
+      @supports (display: grid) { .b { display: grid } }
+      @media (min-width: 500px) { .c { height: 10px } }
+      "#,
+      "@media (min-width:500px){.a{color:red}.c{height:10px}}@media (min-width:500px) and (max-width:700px){.b{width:10px}}@supports (display:grid){.b{display:grid}}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(farthest-side closest-side at 10% 20%); }",
-      ".foo{clip-path:ellipse(farthest-side closest-side at 10% 20%)}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      .b { all: unset }
+      @media (min-width: 500px) { .c { height: 10px } }
+      "#,
+      "@media (min-width:500px){.a{color:red}}.b{all:unset}@media (min-width:500px){.c{height:10px}}",
     );
-    minify_test(
-      ".foo { clip-path: polygon(50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    merge_media_test(
+      r#"
+      @media (min-width: 500px) { .a { color: red } }
+      @keyframes fade { from { opacity: 0 } }
+      @media (min-width: 500px) { @keyframes fade { from { opacity: 1 } } }
+      "#,
+      "@media (min-width:500px){.a{color:red}}@keyframes fade{0%{opacity:0}}@media (min-width:500px){@keyframes fade{0%{opacity:1}}}",
     );
-    minify_test(
-      ".foo { clip-path: polygon(nonzero, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+  }
+
+  #[test]
+  fn test_merge_non_adjacent_rules() {
+    fn merge_rules_test(source: &str, unsafe_merge: bool, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          merge_non_adjacent_rules: true,
+          unsafe_merge_non_adjacent_rules: unsafe_merge,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    merge_rules_test(
+      ".a {color: red} .b {width: 0} .c {color: red}",
+      false,
+      ".a,.c{color:red}.b{width:0}",
+    );
+    merge_rules_test(
+      ".a {color: red; width: 0} .b {height: 0} .c {color: red; width: 0} .d {height: 0}",
+      false,
+      ".a,.c{color:red;width:0}.b,.d{height:0}",
+    );
+    merge_rules_test(
+      ".a {color: red} .b {color: blue} .c {color: red}",
+      false,
+      ".a{color:red}.b{color:#00f}.c{color:red}",
+    );
+    merge_rules_test(
+      ".a {color: red} .b {color: blue} .c {color: red}",
+      true,
+      ".a,.c{color:red}.b{color:#00f}",
+    );
+    merge_rules_test(
+      ".a {background-color: red} @media (min-width: 500px) {.b {background: blue}} .c {background-color: red}",
+      false,
+      ".a{background-color:red}@media (min-width:500px){.b{background:#00f}}.c{background-color:red}",
+    );
+    merge_rules_test(
+      ".a {color: red} .b {width: 0} .a {color: red}",
+      false,
+      ".a{color:red}.b{width:0}",
+    );
+    merge_rules_test(
+      ".a:focus-visible {color: red} .b {width: 0} .c {color: red}",
+      false,
+      ".a:focus-visible{color:red}.b{width:0}.c{color:red}",
+    );
+  }
+
+  #[test]
+  fn test_merge_same_selector_rules() {
+    fn merge_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          merge_same_selector_rules: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    merge_test(
+      ".a { margin-top: 0; margin-left: 0 } .b { color: red } .a { margin-bottom: 0; margin-right: 0 }",
+      ".a{margin:0}.b{color:red}",
+    );
+    merge_test(
+      r#"
+      @media print {
+        .a { padding-top: 1px }
+        .b { color: red }
+        .a { padding-right: 1px; padding-bottom: 1px; padding-left: 1px }
+      }
+    "#,
+      "@media print{.a{padding:1px}.b{color:red}}",
+    );
+    // The later declarations cannot move up, so the earlier ones move down.
+    merge_test(
+      ".a { color: red } .b { margin-top: 2px } .a { margin-top: 1px; margin-right: 1px; margin-bottom: 1px; margin-left: 1px }",
+      ".b{margin-top:2px}.a{color:red;margin:1px}",
     );
+    // Neither can move without changing the cascade.
+    merge_test(
+      ".a { margin-top: 0 } .b { margin: 1px } .a { margin-bottom: 0 }",
+      ".a{margin-top:0}.b{margin:1px}.a{margin-bottom:0}",
+    );
+    merge_test(
+      ".a { color: red } @media print { .a { color: blue } } .a { width: 0 }",
+      ".a{color:red;width:0}@media print{.a{color:#00f}}",
+    );
+  }
+
+  #[test]
+  fn test_sort_declarations() {
+    use crate::declaration::DeclarationOrder;
+
+    fn sort_test(source: &str, expected: &str, order: DeclarationOrder) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          sort_declarations: Some(order),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    sort_test(
+      ".a { width: 0; color: red; --z: 1; display: block; --a: 2 }",
+      ".a{--a:2;--z:1;color:red;display:block;width:0}",
+      DeclarationOrder::Alphabetical,
+    );
+    sort_test(
+      ".a { color: red; width: 0; padding: 0; margin: 0; position: absolute; --x: 1 }",
+      ".a{--x:1;position:absolute;margin:0;padding:0;width:0;color:red}",
+      DeclarationOrder::Concentric,
+    );
+    sort_test(
+      ".a { width: 0 !important; color: red !important; z-index: 1; opacity: 1 }",
+      ".a{opacity:1;z-index:1;color:red!important;width:0!important}",
+      DeclarationOrder::Alphabetical,
+    );
+    // Overlapping declarations keep their relative order.
+    sort_test(
+      ".a { margin: 0; color: red; margin-inline-start: 1px; background: red; background-color: var(--x) }",
+      ".a{background:red;background-color:var(--x);color:red;margin:0;margin-inline-start:1px}",
+      DeclarationOrder::Alphabetical,
+    );
+    sort_test(
+      ".a { z-index: 1; opacity: 1; all: unset; color: red }",
+      ".a{opacity:1;z-index:1;all:unset;color:red}",
+      DeclarationOrder::Alphabetical,
+    );
+    sort_test(
+      ".a { x-foo: 1; color: red; b-foo: 2; --v: 1 }",
+      ".a{--v:1;x-foo:1;color:red;b-foo:2}",
+      DeclarationOrder::Alphabetical,
+    );
+    sort_test(
+      ".a { width: 0; color: red } @media print { .c { width: 0; color: red } } @supports (display: grid) { .d { z-index: 1; align-items: center } }",
+      ".a{color:red;width:0}@media print{.c{color:red;width:0}}@supports (display:grid){.d{align-items:center;z-index:1}}",
+      DeclarationOrder::Alphabetical,
+    );
+  }
+
+  #[test]
+  fn test_media_environment() {
+    use crate::media_query::{MediaEnvironment, MediaType};
+
+    fn environment_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          media_environment: Some(MediaEnvironment {
+            media_type: Some(MediaType::Screen),
+            min_width: Some(1024.0),
+            features: HashMap::from([("hover".into(), "hover".into())]),
+            ..MediaEnvironment::default()
+          }),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    environment_test(
+      ".a { color: red } @media print { .a { color: black } }",
+      ".a{color:red}",
+    );
+    environment_test(
+      ".a { color: red } @media screen and (min-width: 768px) { .a { color: green } }",
+      ".a{color:red}.a{color:green}",
+    );
+    environment_test(
+      "@media (max-width: 600px) { .a { color: red } } @media (hover) { .b { color: red } }",
+      ".b{color:red}",
+    );
+    environment_test(
+      "@media (prefers-color-scheme: dark) { .a { color: red } }",
+      "@media (prefers-color-scheme:dark){.a{color:red}}",
+    );
+    environment_test(
+      "@media (min-width: 1200px) { .a { color: red } }",
+      "@media (min-width:1200px){.a{color:red}}",
+    );
+    environment_test(
+      "@supports (display: grid) { @media screen { .a { display: grid } } }",
+      "@supports (display:grid){.a{display:grid}}",
+    );
+    minify_test("@media print { .a { color: black } }", "@media print{.a{color:#000}}");
+  }
+
+  #[test]
+  fn test_preference_media_features() {
+    fn high_contrast_test(source: &str, expected: &str, targets: Browsers) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets: Some(targets),
+          legacy_high_contrast: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets: Some(targets),
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
     minify_test(
-      ".foo { clip-path: polygon(evenodd, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(evenodd,50% 0%,100% 50%,50% 100%,0% 50%)}",
+      "@media (forced-colors: active) { .a { color: red } }",
+      "@media (forced-colors:active){.a{color:red}}",
     );
     minify_test(
-      ".foo { clip-path: padding-box circle(50px at 0 100px); }",
-      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+      "@media (prefers-contrast: more) and (scripting: enabled) { .a { color: red } }",
+      "@media (prefers-contrast:more) and (scripting:enabled){.a{color:red}}",
     );
     minify_test(
-      ".foo { clip-path: circle(50px at 0 100px) padding-box; }",
-      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+      "@media (overflow-block: optional-paged), (update: slow) { .a { color: red } }",
+      "@media (overflow-block:optional-paged),(update:slow){.a{color:red}}",
     );
     minify_test(
-      ".foo { clip-path: circle(50px at 0 100px) border-box; }",
-      ".foo{clip-path:circle(50px at 0 100px)}",
+      "@media (prefers-reduced-data: reduce) { .a { color: red } }",
+      "@media (prefers-reduced-data:reduce){.a{color:red}}",
     );
 
+    let targets = Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    };
     prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          -webkit-clip-path: circle(50px);
-          clip-path: circle(50px);
+      "@media (inverted-colors: inverted) { .a { color: red } } .b { color: green }",
+      indoc! {r#"
+      .b {
+        color: green;
+      }
+    "#},
+      targets,
+    );
+    prefix_test(
+      "@media not (inverted-colors: inverted) { .a { color: red } }",
+      "\n",
+      targets,
+    );
+    prefix_test("@media (scripting: foo) { .a { color: red } }", "\n", targets);
+    prefix_test(
+      "@media (inverted-colors: inverted) { .a { color: red } }",
+      indoc! {r#"
+      @media (inverted-colors: inverted) {
+        .a {
+          color: red;
         }
-      "#},
+      }
+    "#},
       Browsers {
-        chrome: Some(30 << 16),
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          clip-path: circle(50px);
+      "@media (forced-colors: active), (min-width: 100px) { .a { color: red } }",
+      indoc! {r#"
+      @media (forced-colors: active), (min-width: 100px) {
+        .a {
+          color: red;
         }
-      "#},
+      }
+    "#},
+      targets,
+    );
+
+    high_contrast_test(
+      "@media (forced-colors: active) { .a { color: red } }",
+      "@media (-ms-high-contrast:active){.a{color:red}}",
       Browsers {
-        chrome: Some(80 << 16),
+        edge: Some(18 << 16),
         ..Browsers::default()
       },
     );
-
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          -webkit-clip-path: circle(50px);
-          clip-path: circle(50px);
-        }
-      "#},
+    high_contrast_test(
+      "@media (forced-colors: none) { .a { color: red } }",
+      "@media (-ms-high-contrast:none){.a{color:red}}",
       Browsers {
-        safari: Some(8 << 16),
+        ie: Some(11 << 16),
         ..Browsers::default()
       },
     );
-
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          clip-path: circle(50px);
-        }
-      "#},
+    high_contrast_test(
+      "@media (forced-colors: active) { .a { color: red } }",
+      "@media (forced-colors:active){.a{color:red}}",
       Browsers {
-        safari: Some(14 << 16),
+        chrome: Some(90 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      ".foo { fill: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          fill: #ee00be;
-          fill: color(display-p3 .972962 -.362078 .804206);
-          fill: lch(50.998% 135.363 338);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
+  #[test]
+  fn test_legacy_hacks() {
+    use crate::hacks::LegacyHackKind;
+
+    let source = r#".a { _zoom: 1; color: red; color: blue\9; -webkit-filter: blur(1px); filter: blur(1px) }
+.b { filter: progid:DXImageTransform.Microsoft.Alpha(opacity=50); opacity: 0.5 }
+@-moz-document url-prefix() { .c { color: red } }"#;
+    let targets = Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
 
-    prefix_test(
-      ".foo { stroke: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          stroke: #ee00be;
-          stroke: color(display-p3 .972962 -.362078 .804206);
-          stroke: lch(50.998% 135.363 338);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let hacks = stylesheet.legacy_hacks(Some(targets));
+    let kinds: Vec<_> = hacks.iter().map(|hack| (hack.kind, hack.loc.line)).collect();
+    assert_eq!(
+      kinds,
+      vec![
+        (LegacyHackKind::UnderscoreProperty, 0),
+        (LegacyHackKind::BackslashNine, 0),
+        (LegacyHackKind::ObsoletePrefix, 0),
+        (LegacyHackKind::Progid, 1),
+        (LegacyHackKind::MozDocument, 2),
+      ]
     );
+    assert_eq!(hacks[0].text, "_zoom: 1");
+    assert_eq!(stylesheet.legacy_hacks(None).len(), 4);
 
-    prefix_test(
-      ".foo { fill: url(#foo) lch(50.998% 135.363 338) }",
-      indoc! { r##"
-        .foo {
-          fill: url("#foo") #ee00be;
-          fill: url("#foo") color(display-p3 .972962 -.362078 .804206);
-          fill: url("#foo") lch(50.998% 135.363 338);
-        }
-      "##},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
+    let ie = |version: u32| Browsers {
+      ie: Some(version << 16),
+      ..Browsers::default()
+    };
+    assert!(LegacyHackKind::UnderscoreProperty.is_needed(ie(6)));
+    assert!(!LegacyHackKind::UnderscoreProperty.is_needed(ie(7)));
+    assert!(LegacyHackKind::BackslashNine.is_needed(ie(10)));
+    assert!(!LegacyHackKind::BackslashNine.is_needed(ie(11)));
+    assert!(LegacyHackKind::Progid.is_needed(ie(9)));
+    assert!(!LegacyHackKind::Progid.is_needed(targets));
+    assert!(!LegacyHackKind::MozDocument.is_needed(targets));
 
-    prefix_test(
-      ".foo { fill: var(--url) lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          fill: var(--url) #ee00be;
-        }
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(targets),
+        remove_legacy_hacks: true,
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets: Some(targets),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".a{color:red;filter:blur(1px)}.b{opacity:.5}");
+  }
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            fill: var(--url) lab(50.998% 125.506 -50.7078);
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
-    );
+  #[test]
+  fn test_remove_obsolete_prefixes() {
+    fn prefix_test(source: &str, expected: &str, targets: Option<Browsers>) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets,
+          remove_obsolete_prefixes: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          targets,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let targets = Some(Browsers {
+      chrome: Some(100 << 16),
+      firefox: Some(100 << 16),
+      safari: Some(15 << 16),
+      ..Browsers::default()
+    });
 
     prefix_test(
-      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
-          -webkit-mask-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
-          mask-image: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
+      ".a { -webkit-background-size: cover; background-size: cover }",
+      ".a{background-size:cover}",
+      targets,
     );
-
     prefix_test(
-      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
-          mask-image: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
+      ".a { -webkit-filter: blur(1px); filter: blur(1px) }",
+      ".a{filter:blur(1px)}",
+      targets,
     );
-
     prefix_test(
-      ".foo { mask-image: linear-gradient(red, green) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: linear-gradient(red, green);
-          mask-image: linear-gradient(red, green);
-        }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
+      ".a { -webkit-column-count: 2; -moz-column-count: 2; column-count: 2 }",
+      ".a{column-count:2}",
+      targets,
     );
-
+    // Still needed by Safari 15.
     prefix_test(
-      ".foo { -webkit-mask-image: url(x.svg); mask-image: url(x.svg); }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: url("x.svg");
-          mask-image: url("x.svg");
-        }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
+      ".a { -webkit-hyphens: auto; hyphens: auto }",
+      ".a{-webkit-hyphens:auto;hyphens:auto}",
+      targets,
     );
-
+    // The prefixed declaration wins in browsers that support it.
     prefix_test(
-      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
-          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
-          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
-        }
-      "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
+      ".a { filter: blur(2px); -webkit-filter: blur(1px) }",
+      ".a{filter:blur(2px);-webkit-filter:blur(1px)}",
+      targets,
     );
-
+    prefix_test(".a { -webkit-column-count: 2 }", ".a{-webkit-column-count:2}", targets);
     prefix_test(
-      ".foo { mask: -webkit-linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
-          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-        }
-      "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
+      ".a { -webkit-filter: blur(1px); filter: blur(1px) }",
+      ".a{-webkit-filter:blur(1px);filter:blur(1px)}",
+      None,
     );
-
     prefix_test(
-      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px var(--foo) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
-          mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
-        }
-
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            -webkit-mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
-            mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "@-webkit-keyframes x { from { -webkit-transform: none } } @keyframes x { from { transform: none } }",
+      "@keyframes x{0%{transform:none}}",
+      targets,
     );
-
     prefix_test(
-      ".foo { mask: url(masks.svg#star) luminance }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: url("masks.svg#star");
-          -webkit-mask-source-type: luminance;
-          mask: url("masks.svg#star") luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      ".a::-webkit-input-placeholder { color: red } .a::placeholder { color: blue }",
+      ".a::placeholder{color:#00f}",
+      targets,
     );
-
     prefix_test(
-      ".foo { mask-image: url(masks.svg#star) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: url("masks.svg#star");
-          mask-image: url("masks.svg#star");
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      ".a::-webkit-input-placeholder { color: red; opacity: 1 } .a::placeholder { color: blue }",
+      ".a::-webkit-input-placeholder{color:red;opacity:1}.a::placeholder{color:#00f}",
+      targets,
     );
+  }
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-image: url(masks.svg#star);
-          mask-position: 25% 75%;
-          mask-size: cover;
-          mask-repeat: no-repeat;
-          mask-clip: padding-box;
-          mask-origin: content-box;
-          mask-composite: subtract;
-          mask-mode: luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_preserve_empty_rules() {
+    minify_test(
+      ".a {} .b { color: red } @media print { .c {} } @supports (display: grid) { .d {} }",
+      ".b{color:red}",
+    );
+    minify_test(
+      "@font-face {} @page {} @page :first { margin: 0 }",
+      "@page:first{margin:0}",
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-position: 25% 75%;
-          mask-size: cover;
-          mask-repeat: no-repeat;
-          mask-clip: padding-box;
-          mask-origin: content-box;
-          mask-composite: subtract;
-          mask-mode: luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+    let source = ".a {} .b { color: red } @media print { .c {} } @page {}";
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        preserve_empty_rules: true,
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".a{}.b{color:red}@media print{.c{}}");
+
+    let mut stylesheet = StyleSheet::parse(
+      ".anchor {} .b { color: red }",
+      ParserOptions {
+        filename: "test.css".into(),
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
       },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        preserve_empty_rules: true,
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert!(res.exports.unwrap().contains_key("anchor"));
+  }
+
+  #[test]
+  fn test_important_audit() {
+    let mut stylesheet = StyleSheet::parse(
+      ".a { color: red !important; width: 0 }\n.b { margin: 0 !important }\n@media print { .c { margin-top: 1px; top: 0 !important } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let audit = stylesheet.important_audit();
+    let declarations: Vec<_> = audit
+      .declarations
+      .iter()
+      .map(|d| (d.property.as_str(), d.selectors.as_str(), d.loc.line, d.removable))
+      .collect();
+    assert_eq!(
+      declarations,
+      &[
+        ("color", ".a", 0, true),
+        ("margin", ".b", 1, false),
+        ("top", ".c", 2, true)
+      ]
+    );
+    assert_eq!(
+      audit.by_property().keys().copied().collect::<Vec<_>>(),
+      &["color", "margin", "top"]
     );
+    assert_eq!(audit.by_selector()[".b"][0].property, "margin");
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-composite: subtract;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-composite: source-out;
-          mask-composite: subtract;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    stylesheet
+      .minify(MinifyOptions {
+        remove_unneeded_important: true,
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".a{width:0;color:red}.b{margin:0!important}@media print{.c{margin-top:1px;top:0}}"
     );
+  }
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-mode: luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-source-type: luminance;
-          mask-mode: luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_optimization_level() {
+    fn o2_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          optimization_level: OptimizationLevel::O2,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    minify_test(
+      ".a { color: red !important } .b { width: 0 } .c { color: red !important }",
+      ".a{color:red!important}.b{width:0}.c{color:red!important}",
+    );
+    o2_test(
+      ".a { color: red !important } .b { width: 0 } .c { color: red !important }",
+      ".a,.c{color:red}.b{width:0}",
+    );
+    o2_test(".a { color: red !important; width: 0 }", ".a{width:0;color:red}");
+    o2_test(
+      ".a { margin: 0 !important } .b { margin-top: 1px }",
+      ".a{margin:0!important}.b{margin-top:1px}",
+    );
+    o2_test(
+      ".a { opacity: 1 !important } @keyframes x { from { opacity: 0 } }",
+      ".a{opacity:1!important}@keyframes x{0%{opacity:0}}",
+    );
+    o2_test(
+      ".a { x-foo: 1 !important; color: red !important } .b { x-bar: 1 }",
+      ".a{x-foo:1!important;color:red!important}.b{x-bar:1}",
+    );
+    o2_test(
+      ".a { color: red; width: 0 } @media print { .a { color: red; width: 1px } }",
+      ".a{color:red;width:0}@media print{.a{width:1px}}",
+    );
+    o2_test(
+      ".a { color: red } @media print { .a { color: red } .b { width: 0 } }",
+      ".a{color:red}@media print{.b{width:0}}",
     );
+    o2_test(
+      ".a { color: red } .b { width: 0 } @media print { .a { color: red } }",
+      ".a{color:red}.b{width:0}",
+    );
+    // A rule in between sets the same property.
+    o2_test(
+      ".a { color: red } .b { color: blue } @media print { .a { color: red } }",
+      ".a{color:red}.b{color:#00f}@media print{.a{color:red}}",
+    );
+    o2_test(
+      ".a { color: red } @media print { .b { color: blue } .a { color: red } }",
+      ".a{color:red}@media print{.b{color:#00f}.a{color:red}}",
+    );
+    o2_test(
+      ".a { color: red; color: var(--x) } @media print { .a { color: red } }",
+      ".a{color:red;color:var(--x)}@media print{.a{color:red}}",
+    );
+    o2_test(
+      ".a { color: red } @media print { .a { color: red !important } }",
+      ".a{color:red}@media print{.a{color:red!important}}",
+    );
+  }
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border: url('border-mask.png') 25 / 35px / 12px space luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("border-mask.png") 25 / 35px / 12px space;
-          mask-border: url("border-mask.png") 25 / 35px / 12px space luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_shape_precision() {
+    let print = |source: &str, minify: bool| {
+      let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .to_css(PrinterOptions {
+          minify,
+          shape_precision: Some(2),
+          ..PrinterOptions::default()
+        })
+        .unwrap()
+        .code
+    };
+
+    assert_eq!(
+      print(
+        ".foo { clip-path: polygon(10.1234% 0.006%, 100% 50.5555%, 0.4567px 100%); }",
+        true
+      ),
+      ".foo{clip-path:polygon(10.12% .01%,100% 50.56%,.46px 100%)}"
+    );
+    assert_eq!(
+      print(".foo { shape-outside: inset(1.23456px 2.5px round 0.001px); }", true),
+      ".foo{shape-outside:inset(1.23px 2.5px round 0)}"
     );
+    assert_eq!(
+      print(
+        ".foo { clip-path: path('M 0.1234 0.5678 l 0.333 0.333 l 0.333 0.333 l 0.333 0.333'); }",
+        true
+      ),
+      ".foo{clip-path:path(\"M.12.57.46.9l.33.33.33.34\")}"
+    );
+    assert_eq!(
+      print(".foo { offset-path: path('M 1.005 2.5 L 3.14159 4'); }", false),
+      ".foo {\n  offset-path: path(\"M 1 2.5 L 3.14 4\");\n}\n"
+    );
+    // Numbers outside of shapes are unaffected.
+    assert_eq!(
+      print(".foo { width: 1.23456px; clip-path: circle(1.23456px); }", true),
+      ".foo{width:1.23456px;clip-path:circle(1.23px)}"
+    );
+  }
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space;
-          mask-border: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space luminance;
-          -webkit-mask-box-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space;
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_size_report() {
+    let source = r#"
+      @import "a.css";
+      .foo { color: red }
+      @media print {
+        .bar { width: 0 }
+        .baz { height: 0 }
+      }
+      @keyframes x { from { opacity: 0 } }
+    "#;
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let size_report = |minify| {
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify,
+          size_report: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      let report = res.size_report.unwrap();
+      assert!(report.iter().map(|rule| rule.bytes).sum::<usize>() <= res.code.len());
+      report.into_iter().map(|rule| (rule.prelude, rule.bytes)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      size_report(true),
+      vec![
+        ("@import \"a.css\"".into(), 16),
+        (".foo".into(), 15),
+        ("@media print".into(), 41),
+        ("@keyframes x".into(), 27),
+      ]
+    );
+    assert_eq!(
+      size_report(false),
+      vec![
+        ("@import \"a.css\"".into(), 16),
+        (".foo".into(), 22),
+        ("@media print".into(), 72),
+        ("@keyframes x".into(), 45),
+      ]
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-source: linear-gradient(#ff0f0e, #7773ff);
-          mask-border-source: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-box-image-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.size_report, None);
+    assert_eq!(res.size_breakdown, None);
+  }
+
+  #[test]
+  fn test_size_breakdown() {
+    use crate::stylesheet::{SizeBreakdown, SizeGroup};
+
+    fn size_breakdown(source: &str) -> SizeBreakdown {
+      let stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          filename: "test.css".into(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          size_report: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      res.size_breakdown.unwrap()
+    }
+
+    fn groups(groups: &[(&str, usize)]) -> Vec<SizeGroup> {
+      groups
+        .iter()
+        .map(|(name, bytes)| SizeGroup {
+          name: (*name).into(),
+          bytes: *bytes,
+        })
+        .collect()
+    }
+
+    assert_eq!(
+      size_breakdown(
+        r#"
+        @import "a.css";
+        .foo { color: red }
+        @media print {
+          .bar { width: 0 }
+          .baz { height: 0 }
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+        @keyframes x { from { opacity: 0 } }
+      "#
+      ),
+      SizeBreakdown {
+        media_queries: groups(&[("print", 41)]),
+        layers: vec![],
+        sources: groups(&[("test.css", 99)]),
+        properties: groups(&[("color", 9), ("opacity", 9), ("height", 8), ("width", 7)]),
+      }
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-source: url(foo.png);
-          mask-border-slice: 10 40 10 40;
-          mask-border-width: 10px;
-          mask-border-outset: 0;
-          mask-border-repeat: round round;
-          mask-border-mode: luminance;
+    let breakdown = size_breakdown(
+      "@layer a { .x { color: red } @layer b { .y { color: red } } } @layer { .z { color: red } } @media print { @media (min-width: 100px) { .w { color: red } } }",
+    );
+    assert_eq!(breakdown.layers, groups(&[("a", 23), ("a.b", 23), ("<anonymous>", 21)]));
+    assert_eq!(
+      breakdown.media_queries,
+      groups(&[("print and (min-width: 100px)", 39), ("print", 14)])
+    );
+    assert_eq!(breakdown.properties, groups(&[("color", 36)]));
+  }
+
+  #[test]
+  fn test_flatten_anonymous_layers() {
+    fn flatten_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          flatten_anonymous_layers: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    flatten_test(
+      "@layer { .foo { color: red } } .bar { width: 0 }",
+      ".foo{color:red}.bar{width:0}",
+    );
+    flatten_test(
+      "@layer { .foo { color: red } } @layer a { .bar { width: 0 } } @layer { .baz { height: 0 } }",
+      ".foo{color:red}@layer a{.bar{width:0}}.baz{height:0}",
+    );
+    // Other rules set the same property, so unlayering would change which one wins.
+    flatten_test(
+      "@layer { .foo { color: red } } .bar { color: blue }",
+      "@layer{.foo{color:red}}.bar{color:#00f}",
+    );
+    flatten_test(
+      "@layer { .foo { margin-top: 0 } } @layer a { .bar { margin: 1px } }",
+      "@layer{.foo{margin-top:0}}@layer a{.bar{margin:1px}}",
+    );
+    // Nested layers are order dependent.
+    flatten_test(
+      "@layer { @layer a { .foo { color: red } } }",
+      "@layer{@layer a{.foo{color:red}}}",
+    );
+  }
+
+  #[test]
+  fn test_mangle_idents() {
+    fn mangle_test(source: &str, expected: &str) -> MangledIdents {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      let result = stylesheet
+        .minify(MinifyOptions {
+          mangle_idents: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      result.mangled_idents
+    }
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+      entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    let res = mangle_test(
+      r#"
+      @keyframes fade-in { from { opacity: 0 } }
+      @keyframes "slide out" { to { opacity: 0 } }
+      .foo { animation: fade-in 1s, "slide out" 2s }
+      .bar { animation-name: missing }
+    "#,
+      "@keyframes a{0%{opacity:0}}@keyframes b{to{opacity:0}}.foo{animation:1s a,2s b}.bar{animation-name:missing}",
+    );
+    assert_eq!(res.keyframes, map(&[("fade-in", "a"), ("slide out", "b")]));
+
+    let res = mangle_test(
+      r#"
+      @counter-style thumbs { system: cyclic; symbols: "👍"; suffix: " " }
+      ul { list-style: thumbs inside }
+      ol { list-style-type: decimal }
+    "#,
+      "@counter-style a{system:cyclic;symbols:\"👍\";suffix:\" \"}ul{list-style:a inside}ol{list-style-type:decimal}",
+    );
+    assert_eq!(res.counter_styles, map(&[("thumbs", "a")]));
+
+    let res = mangle_test(
+      r#"
+      @counter-style thumbs { system: cyclic; symbols: "👍" }
+      @counter-style big-thumbs { system: extends thumbs; fallback: thumbs; speak-as: thumbs }
+      ul { list-style: big-thumbs }
+    "#,
+      "@counter-style a{system:cyclic;symbols:\"👍\"}@counter-style b{system:extends a;fallback:a;speak-as:a}ul{list-style:b}",
+    );
+    assert_eq!(res.counter_styles, map(&[("thumbs", "a"), ("big-thumbs", "b")]));
+
+    let res = mangle_test(
+      r#"
+      .sidebar { container: sidebar / inline-size }
+      @container sidebar (min-width: 400px) { .foo { color: red } }
+    "#,
+      ".sidebar{container:a/inline-size}@container a (min-width:400px){.foo{color:red}}",
+    );
+    assert_eq!(res.container_names, map(&[("sidebar", "a")]));
+
+    let res = mangle_test(
+      r#"
+      .grid {
+        grid-template-columns: [full-start] 1fr [content-start] 2fr [content-end] 1fr [full-end];
+        grid-template-areas: "header header" "nav main";
+      }
+      .header { grid-area: header }
+      .main { grid-column: content-start / full-end; grid-row: nav }
+    "#,
+      ".grid{grid-template-columns:[a-start]1fr[b-start]2fr[b-end]1fr[a-end];grid-template-areas:\"c c\"\"d e\"}.header{grid-area:c}.main{grid-area:d/b-start/d/a-end}",
+    );
+    assert_eq!(
+      res.grid_names,
+      map(&[
+        ("full", "a"),
+        ("content", "b"),
+        ("header", "c"),
+        ("nav", "d"),
+        ("main", "e")
+      ])
+    );
+
+    // Names referenced from unparsed values are not renamed.
+    let res = mangle_test(
+      r#"
+      @keyframes a { from { opacity: 0 } }
+      @keyframes spin { from { opacity: 0 } }
+      @keyframes pulse { from { opacity: 0 } }
+      .foo { --anim: spin; animation: var(--anim) 1s }
+      .bar { animation: pulse 1s, a 1s }
+    "#,
+      "@keyframes b{0%{opacity:0}}@keyframes spin{0%{opacity:0}}@keyframes c{0%{opacity:0}}.foo{--anim:spin;animation:var(--anim)1s}.bar{animation:1s c,1s b}",
+    );
+    assert_eq!(res.keyframes, map(&[("a", "b"), ("pulse", "c")]));
+  }
+
+  #[test]
+  fn test_remove_unused_font_faces() {
+    fn font_face_test(source: &str, expected: &str, removed: &[&str]) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      let result = stylesheet
+        .minify(MinifyOptions {
+          remove_unused_font_faces: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      assert_eq!(result.removed_font_faces, removed);
+    }
+
+    font_face_test(
+      r#"
+      @font-face { font-family: Used; src: url(used.woff2) }
+      @font-face { font-family: "Unused Font"; src: url(unused.woff2) }
+      .foo { font-family: used, sans-serif }
+    "#,
+      "@font-face{font-family:Used;src:url(used.woff2)}.foo{font-family:used,sans-serif}",
+      &["Unused Font"],
+    );
+    font_face_test(
+      r#"
+      @font-face { font-family: A; src: url(a.woff2) }
+      @font-face { font-family: B; src: url(b.woff2) }
+      @font-face { font-family: C; src: url(c.woff2) }
+      .foo { font: 12px B }
+      .bar { --font: "C", serif }
+    "#,
+      "@font-face{font-family:B;src:url(b.woff2)}@font-face{font-family:C;src:url(c.woff2)}.foo{font:12px B}.bar{--font:\"C\",serif}",
+      &["A"],
+    );
+    font_face_test(
+      r#"
+      @media print {
+        @font-face { font-family: A; src: url(a.woff2) }
+        @font-face { font-family: A; src: url(a-bold.woff2); font-weight: bold }
+      }
+      .foo { font-family: B }
+    "#,
+      ".foo{font-family:B}",
+      &["A"],
+    );
+    font_face_test(
+      r#"
+      @font-face { font-family: A; src: url(a.woff2) }
+      @unknown { font-family: A }
+    "#,
+      "@font-face{font-family:A;src:url(a.woff2)}@unknown{font-family: A}",
+      &[],
+    );
+  }
+
+  #[test]
+  fn test_remove_unused_keyframes() {
+    fn keyframes_test(source: &str, preserved: &[&str], expected: &str, removed: &[&str]) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      let result = stylesheet
+        .minify(MinifyOptions {
+          remove_unused_keyframes: true,
+          preserved_keyframes: preserved.iter().map(|s| s.to_string()).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      assert_eq!(result.removed_keyframes, removed);
+    }
+
+    keyframes_test(
+      r#"
+      @keyframes used { from { opacity: 0 } }
+      @keyframes unused { from { opacity: 0 } }
+      @keyframes "quoted" { from { opacity: 0 } }
+      .foo { animation: used 1s }
+      .bar { animation-name: "quoted" }
+    "#,
+      &[],
+      "@keyframes used{0%{opacity:0}}@keyframes quoted{0%{opacity:0}}.foo{animation:1s used}.bar{animation-name:quoted}",
+      &["unused"],
+    );
+    keyframes_test(
+      r#"
+      @keyframes a { from { opacity: 0 } }
+      @keyframes b { from { opacity: 0 } }
+      @keyframes c { from { opacity: 0 } }
+      .foo { --anim: a; animation: var(--anim) 1s }
+    "#,
+      &["b"],
+      "@keyframes a{0%{opacity:0}}@keyframes b{0%{opacity:0}}.foo{--anim:a;animation:var(--anim)1s}",
+      &["c"],
+    );
+    keyframes_test(
+      r#"
+      @media (prefers-reduced-motion: no-preference) {
+        @keyframes a { from { opacity: 0 } }
+        @-webkit-keyframes a { from { opacity: 0 } }
+      }
+      .foo { animation: none }
+    "#,
+      &[],
+      ".foo{animation:none}",
+      &["a"],
+    );
+  }
+
+  #[test]
+  fn test_remove_unused_transitions() {
+    fn transitions_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          remove_unused_transitions: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    transitions_test(
+      ".a { transition: opacity 1s, color 1s, transform 2s } .a:hover { opacity: .5 }",
+      ".a{transition:opacity 1s}.a:hover{opacity:.5}",
+    );
+    // Shorthands and longhands overlap.
+    transitions_test(
+      ".a { transition: border-color 1s, margin-top 1s } .b { border: 1px solid red; margin: 0 }",
+      ".a{transition:border-color 1s,margin-top 1s}.b{border:1px solid red;margin:0}",
+    );
+    // Properties set in keyframes or with var() count as used.
+    transitions_test(
+      ".a { transition: width 1s, height 1s, color 1s } @keyframes x { to { height: 0 } } .b { width: var(--w) }",
+      ".a{transition:width 1s,height 1s}@keyframes x{to{height:0}}.b{width:var(--w)}",
+    );
+    // All and unknown properties are kept.
+    transitions_test(
+      ".a { transition: all 1s, color 1s } .b { foo: bar }",
+      ".a{transition:1s,color 1s}.b{foo:bar}",
+    );
+    // Declarations with no used properties are left as is.
+    transitions_test(".a { transition: color 1s }", ".a{transition:color 1s}");
+    transitions_test(
+      ".a { transition: color 1s, opacity 1s } @unknown { .b { opacity: 0 } }",
+      ".a{transition:color 1s,opacity 1s}@unknown{.b { opacity: 0 }}",
+    );
+  }
+
+  #[test]
+  fn test_merge_duplicate_keyframes() {
+    fn merge_test(source: &str, preserved: &[&str], expected: &str, merged: &[(&str, &str)]) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      let result = stylesheet
+        .minify(MinifyOptions {
+          merge_duplicate_keyframes: true,
+          preserved_keyframes: preserved.iter().map(|s| s.to_string()).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      assert_eq!(
+        result.merged_keyframes,
+        merged
+          .iter()
+          .map(|(a, b)| (a.to_string(), b.to_string()))
+          .collect::<BTreeMap<_, _>>()
+      );
+    }
+
+    merge_test(
+      r#"
+      @keyframes fade { from { opacity: 0 } to { opacity: 1 } }
+      @keyframes button-fade { 0% { opacity: 0 } 100% { opacity: 1 } }
+      @keyframes spin { to { transform: rotate(360deg) } }
+      .a { animation: fade 1s }
+      .b { animation: button-fade 2s, spin 1s }
+      .c { animation-name: button-fade }
+    "#,
+      &[],
+      "@keyframes fade{0%{opacity:0}to{opacity:1}}@keyframes spin{to{transform:rotate(360deg)}}.a{animation:1s fade}.b{animation:2s fade,1s spin}.c{animation-name:fade}",
+      &[("button-fade", "fade")],
+    );
+    merge_test(
+      r#"
+      @keyframes a { from { opacity: 0 } }
+      @keyframes b { from { opacity: 0 } }
+      @keyframes c { from { opacity: 0 } }
+      .foo { --anim: b; animation: a 1s }
+    "#,
+      &["c"],
+      "@keyframes a{0%{opacity:0}}@keyframes b{0%{opacity:0}}@keyframes c{0%{opacity:0}}.foo{--anim:b;animation:1s a}",
+      &[],
+    );
+    // Names defined more than once, or within conditional rules, are not merged.
+    merge_test(
+      r#"
+      @keyframes a { from { opacity: 0 } }
+      @keyframes b { from { opacity: 0 } }
+      @media (prefers-reduced-motion: reduce) {
+        @keyframes b { from { opacity: 1 } }
+      }
+      @keyframes c { from { opacity: 0 } }
+      .foo { animation-name: b, c }
+    "#,
+      &[],
+      "@keyframes a{0%{opacity:0}}@keyframes b{0%{opacity:0}}@media (prefers-reduced-motion:reduce){@keyframes b{0%{opacity:1}}}.foo{animation-name:b,a}",
+      &[("c", "a")],
+    );
+  }
+
+  #[test]
+  fn test_inline_custom_properties() {
+    fn inline_test(source: &str, dynamic: &[&str], expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          inline_custom_properties: true,
+          dynamic_custom_properties: dynamic.iter().map(|s| s.to_string()).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    inline_test(
+      ":root { --gap: 8px; --brand: #ff0000 } .foo { margin: var(--gap) calc(var(--gap) * 2); color: var(--brand, blue) }",
+      &[],
+      ".foo{margin:8px calc(8px*2);color:red}",
+    );
+    inline_test(
+      ":root { --a: 1px; --b: 2px; color: red } .foo { width: var(--a); height: var(--b) }",
+      &["--b"],
+      ":root{--b:2px;color:red}.foo{width:1px;height:var(--b)}",
+    );
+    // Shadowed by another definition.
+    inline_test(
+      ":root { --a: 1px } .foo { --a: 2px; width: var(--a) }",
+      &[],
+      ":root{--a:1px}.foo{--a:2px;width:var(--a)}",
+    );
+    // Not a static value.
+    inline_test(
+      ":root { --a: var(--b); --c: env(safe-area-inset-top) } .foo { width: var(--a); height: var(--c) }",
+      &[],
+      ":root{--a:var(--b);--c:env(safe-area-inset-top)}.foo{width:var(--a);height:var(--c)}",
+    );
+    // Only top-level :root rules.
+    inline_test(
+      "@media print { :root { --a: 1px } } html { --b: 1px } .foo { width: var(--a); height: var(--b) }",
+      &[],
+      "@media print{:root{--a:1px}}html{--b:1px}.foo{width:var(--a);height:var(--b)}",
+    );
+    inline_test(
+      r#"
+      @property --angle { syntax: "<angle>"; inherits: false; initial-value: 0deg }
+      :root { --angle: 10deg }
+      .foo { transform: rotate(var(--angle)) }
+    "#,
+      &[],
+      "@property --angle{syntax:\"<angle>\";inherits:false;initial-value:0deg}:root{--angle:10deg}.foo{transform:rotate(var(--angle))}",
+    );
+    inline_test(
+      ":root { --a: 1px } .foo { --b: var(--a); width: var(--missing, var(--a)) }",
+      &[],
+      ".foo{--b:1px;width:var(--missing,1px)}",
+    );
+    // Empty or invalid substitutions are left as references.
+    inline_test(
+      ":root { --x: ; --y: foo } .a { width: var(--x); height: var(--y) }",
+      &[],
+      ":root{--x: ;--y:foo}.a{width:var(--x);height:var(--y)}",
+    );
+    inline_test(
+      ":root { --x: foo } .a { width: var(--x); --b: var(--x) }",
+      &[],
+      ":root{--x:foo}.a{width:var(--x);--b:foo}",
+    );
+  }
+
+  #[test]
+  fn test_remove_unused_custom_properties() {
+    fn custom_properties_test(source: &str, preserved: &[&str], expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          remove_unused_custom_properties: true,
+          preserved_custom_properties: preserved.iter().map(|s| s.to_string()).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    custom_properties_test(
+      ".foo { --used: red; --unused: blue; color: var(--used) }",
+      &[],
+      ".foo{--used:red;color:var(--used)}",
+    );
+    custom_properties_test(
+      ":root { --a: 1px; --b: var(--a); --c: var(--b); --d: var(--c) } .foo { width: var(--c) }",
+      &[],
+      ":root{--a:1px;--b:var(--a);--c:var(--b)}.foo{width:var(--c)}",
+    );
+    custom_properties_test(
+      ":root { --a: red; --b: blue } .foo { --c: green }",
+      &["--b"],
+      ":root{--b:blue}",
+    );
+    custom_properties_test(
+      r#"
+      @property --angle { syntax: "<angle>"; inherits: false; initial-value: 0deg }
+      @property --unused { syntax: "<length>"; inherits: false; initial-value: 0px }
+      .foo { transition: --angle 1s }
+      .bar { color: var(--missing, var(--fallback)) } .baz { --fallback: red; --missing: blue }
+    "#,
+      &[],
+      "@property --angle{syntax:\"<angle>\";inherits:false;initial-value:0deg}.foo{transition:--angle 1s}.bar{color:var(--missing,var(--fallback))}.baz{--fallback:red;--missing:blue}",
+    );
+  }
+
+  #[test]
+  fn test_unused_symbols() {
+    let source = r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        color: green;
+      }
+
+      .bar:hover {
+        color: purple;
+      }
+
+      .bar .baz {
+        background: red;
+      }
+
+      .baz:is(.bar) {
+        background: green;
+      }
+
+      #id {
+        animation: 2s test;
+      }
+
+      #other_id {
+        color: red;
+      }
+
+      @keyframes test {
+        from { color: red }
+        to { color: yellow }
+      }
+
+      @counter-style circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
+      }
+
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
+    "#;
+
+    let expected = indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      #id {
+        animation: 2s test;
+      }
+
+      @keyframes test {
+        from {
+          color: red;
+        }
+
+        to {
+          color: #ff0;
+        }
+      }
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["bar", "other_id", "fade", "circles"]
+          .iter()
+          .map(|s| String::from(*s))
+          .collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
+
+    let source = r#"
+      .foo {
+        color: red;
+
+        &.bar {
+          color: green;
+        }
+      }
+    "#;
+
+    let expected = indoc! {r#"
+      .foo {
+        color: red;
+      }
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(
+      &source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["bar"].iter().map(|s| String::from(*s)).collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
+
+    let source = r#"
+      .foo {
+        color: red;
+
+        &.bar {
+          color: purple;
+        }
+
+        @nest &.bar {
+          color: orange;
+        }
+
+        @nest :not(&) {
+          color: green;
+        }
+
+        @media (orientation: portrait) {
+          color: brown;
+        }
+      }
+
+      .x {
+        color: purple;
+
+        &.y {
+          color: green;
+        }
+      }
+    "#;
+
+    let expected = indoc! {r#"
+      :not(.foo) {
+        color: green;
+      }
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(
+      &source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["foo", "x"].iter().map(|s| String::from(*s)).collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        targets: Some(Browsers {
+          chrome: Some(95 << 16),
+          ..Browsers::default()
+        }),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+
+    let source = r#"
+      @property --EgL3uq_foo {
+        syntax: "<color>";
+        inherits: false;
+        initial-value: #ff0;
+      }
+
+      @font-palette-values --EgL3uq_Cooler {
+        font-family: Bixa;
+        base-palette: 1;
+        override-colors: 1 #7EB7E4;
+      }
+
+      .EgL3uq_foo {
+        --EgL3uq_foo: red;
+      }
+
+      .EgL3uq_bar {
+        color: green;
+      }
+    "#;
+
+    let expected = indoc! {r#"
+      .EgL3uq_bar {
+        color: green;
+      }
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(
+      &source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["--EgL3uq_foo", "--EgL3uq_Cooler"]
+          .iter()
+          .map(|s| String::from(*s))
+          .collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
+  }
+
+  #[test]
+  fn test_purge() {
+    use crate::purge::{DefaultExtractor, Purge};
+    use std::collections::HashSet;
+
+    fn purge_test(source: &str, purge: &Purge, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          nesting: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          purge: Some(purge.clone()),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    let mut purge = Purge::new();
+    purge.add_content(r#"<div class="foo bar-baz" id="main"></div>"#, &DefaultExtractor::new());
+    purge_test(
+      ".foo, .qux { color: red } .bar-baz span { color: green } #main > div { width: 0 }",
+      &purge,
+      ".foo{color:red}#main>div{width:0}",
+    );
+    purge_test(
+      ":is(.qux, .foo) div { color: blue } :where(.qux) { color: red } :has(.qux) { color: red }",
+      &purge,
+      ":is(.qux,.foo) div{color:#00f}",
+    );
+    purge_test(
+      ":root { --x: 1 } * { margin: 0 } [hidden] { display: none } .foo::before { content: \"\" }",
+      &purge,
+      ":root{--x:1}*{margin:0}[hidden]{display:none}.foo:before{content:\"\"}",
+    );
+    purge_test(
+      ".qux { color: red; & .foo { color: green } .foo & { color: blue } } .foo { & .qux { color: red } }",
+      &purge,
+      "",
+    );
+
+    purge.safelist.insert("qux".into());
+    purge_test(".qux, .other { color: red }", &purge, ".qux{color:red}");
+
+    let mut purge = Purge::new();
+    purge.add_content("md:flex w-1/2", &DefaultExtractor::with_chars(":/"));
+    purge_test(
+      ".md\\:flex { display: flex } .w-1\\/2 { width: 50% } .flex { display: flex }",
+      &purge,
+      ".md\\:flex{display:flex}.w-1\\/2{width:50%}",
+    );
+
+    let mut purge = Purge::new();
+    purge.add_content("foo,bar", &|content: &str, candidates: &mut HashSet<String>| {
+      candidates.extend(content.split(',').map(|s| s.to_owned()))
+    });
+    purge_test(".foo, .bar, .baz { color: red }", &purge, ".foo,.bar{color:red}");
+  }
+
+  #[test]
+  fn test_svg() {
+    minify_test(".foo { fill: yellow; }", ".foo{fill:#ff0}");
+    minify_test(".foo { fill: url(#foo); }", ".foo{fill:url(#foo)}");
+    minify_test(".foo { fill: url(#foo) none; }", ".foo{fill:url(#foo) none}");
+    minify_test(".foo { fill: url(#foo) yellow; }", ".foo{fill:url(#foo) #ff0}");
+    minify_test(".foo { fill: none; }", ".foo{fill:none}");
+    minify_test(".foo { fill: context-fill; }", ".foo{fill:context-fill}");
+    minify_test(".foo { fill: context-stroke; }", ".foo{fill:context-stroke}");
+
+    minify_test(".foo { stroke: yellow; }", ".foo{stroke:#ff0}");
+    minify_test(".foo { stroke: url(#foo); }", ".foo{stroke:url(#foo)}");
+    minify_test(".foo { stroke: url(#foo) none; }", ".foo{stroke:url(#foo) none}");
+    minify_test(".foo { stroke: url(#foo) yellow; }", ".foo{stroke:url(#foo) #ff0}");
+    minify_test(".foo { stroke: none; }", ".foo{stroke:none}");
+    minify_test(".foo { stroke: context-fill; }", ".foo{stroke:context-fill}");
+    minify_test(".foo { stroke: context-stroke; }", ".foo{stroke:context-stroke}");
+
+    minify_test(".foo { marker-start: url(#foo); }", ".foo{marker-start:url(#foo)}");
+
+    minify_test(".foo { stroke-dasharray: 4 1 2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(".foo { stroke-dasharray: 4,1,2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(".foo { stroke-dasharray: 4, 1, 2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(
+      ".foo { stroke-dasharray: 4px, 1px, 2px; }",
+      ".foo{stroke-dasharray:4 1 2}",
+    );
+
+    minify_test(".foo { mask: url('foo.svg'); }", ".foo{mask:url(foo.svg)}");
+    minify_test(
+      ".foo { mask: url(masks.svg#star) luminance }",
+      ".foo{mask:url(masks.svg#star) luminance}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) 40px 20px }",
+      ".foo{mask:url(masks.svg#star) 40px 20px}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) 0 0 / 50px 50px }",
+      ".foo{mask:url(masks.svg#star) 0 0/50px 50px}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) repeat-x }",
+      ".foo{mask:url(masks.svg#star) repeat-x}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) stroke-box }",
+      ".foo{mask:url(masks.svg#star) stroke-box}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) stroke-box stroke-box }",
+      ".foo{mask:url(masks.svg#star) stroke-box}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) border-box }",
+      ".foo{mask:url(masks.svg#star)}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) left / 16px repeat-y, url(masks.svg#circle) right / 16px repeat-y }",
+      ".foo{mask:url(masks.svg#star) 0/16px repeat-y,url(masks.svg#circle) 100%/16px repeat-y}",
+    );
+
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25; }",
+      ".foo{mask-border:url(border-mask.png) 25}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space alpha; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space luminance; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') luminance 25 / 35px / 12px space; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    );
+
+    minify_test(
+      ".foo { clip-path: url('clip.svg#star'); }",
+      ".foo{clip-path:url(clip.svg#star)}",
+    );
+    minify_test(".foo { clip-path: margin-box; }", ".foo{clip-path:margin-box}");
+    minify_test(
+      ".foo { clip-path: inset(100px 50px); }",
+      ".foo{clip-path:inset(100px 50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: inset(100px 50px round 5px); }",
+      ".foo{clip-path:inset(100px 50px round 5px)}",
+    );
+    minify_test(
+      ".foo { clip-path: inset(100px 50px round 5px 5px 5px 5px); }",
+      ".foo{clip-path:inset(100px 50px round 5px)}",
+    );
+    minify_test(".foo { clip-path: circle(50px); }", ".foo{clip-path:circle(50px)}");
+    minify_test(
+      ".foo { clip-path: circle(50px at center center); }",
+      ".foo{clip-path:circle(50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 50% 50%); }",
+      ".foo{clip-path:circle(50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px); }",
+      ".foo{clip-path:circle(50px at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(closest-side at 0 100px); }",
+      ".foo{clip-path:circle(at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(farthest-side at 0 100px); }",
+      ".foo{clip-path:circle(farthest-side at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(closest-side at 50% 50%); }",
+      ".foo{clip-path:circle()}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(50px 60px at 0 10% 20%); }",
+      ".foo{clip-path:ellipse(50px 60px at 0 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(50px 60px at center center); }",
+      ".foo{clip-path:ellipse(50px 60px)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(closest-side closest-side at 50% 50%); }",
+      ".foo{clip-path:ellipse()}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(closest-side closest-side at 10% 20%); }",
+      ".foo{clip-path:ellipse(at 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(farthest-side closest-side at 10% 20%); }",
+      ".foo{clip-path:ellipse(farthest-side closest-side at 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(nonzero, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(evenodd, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(evenodd,50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: padding-box circle(50px at 0 100px); }",
+      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px) padding-box; }",
+      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px) border-box; }",
+      ".foo{clip-path:circle(50px at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M 10 10 L 20 20 L 30 10 Z'); }",
+      ".foo{clip-path:path(\"M10 10 20 20 30 10Z\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path(nonzero, 'm10,10 l10,10 l10,-10 z'); }",
+      ".foo{clip-path:path(\"M10 10 20 20 30 10Z\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path(evenodd, 'M 0.5 0.25 H 10 V 0.5 L -0.5 -0.25'); }",
+      ".foo{clip-path:path(evenodd,\"M.5.25H10V.5L-.5-.25\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M 1000 1000 L 1001 1001 L 1002 1000 z'); }",
+      ".foo{clip-path:path(\"M1000 1000l1 1 1-1Z\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M100 100 A 25 25 0 1 0 150 100 Q 200 150 250 100 T 350 100 S 400 50 450 100'); }",
+      ".foo{clip-path:path(\"M100 100a25 25 0 1 0 50 0q50 50 100 0t100 0s50-50 100 0\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M 0 0 L foo'); }",
+      ".foo{clip-path:path(\"M 0 0 L foo\")}",
+    );
+    minify_test(".foo { clip-path: path('L 0 0'); }", ".foo{clip-path:path(\"L 0 0\")}");
+    test(
+      ".foo { clip-path: path(evenodd, 'm10,10 l10,10 20 20 h 5'); }",
+      indoc! { r#"
+        .foo {
+          clip-path: path(evenodd, "m 10 10 l 10 10 l 20 20 h 5");
+        }
+      "#},
+    );
+    minify_test(
+      ".foo { offset-path: path('M 0 0 C 10 20, 30 40, 50 60'); }",
+      ".foo{offset-path:path(\"M0 0C10 20 30 40 50 60\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M 1234.56789 16777217 L 1234.56789 0'); }",
+      ".foo{clip-path:path(\"M1234.56789 16777217l0-16777217\")}",
+    );
+    test(
+      ".foo { clip-path: path('M 0.123456789 0 l 0.1 0.2 l 0.1 0.2'); }",
+      indoc! { r#"
+        .foo {
+          clip-path: path("M .123456789 0 l .1 .2 l .1 .2");
+        }
+      "#},
+    );
+    minify_test(".foo { offset-path: none; }", ".foo{offset-path:none}");
+    minify_test(".foo { offset-path: url('#path'); }", ".foo{offset-path:url(#path)}");
+    minify_test(
+      ".foo { shape-outside: polygon(nonzero, 0% 0%, 100% 50%, 0% 100%); }",
+      ".foo{shape-outside:polygon(0% 0%,100% 50%,0% 100%)}",
+    );
+    minify_test(
+      ".foo { shape-outside: inset(10px 10px round 5px 5px); }",
+      ".foo{shape-outside:inset(10px round 5px)}",
+    );
+    minify_test(
+      ".foo { shape-outside: url('shape.png'); }",
+      ".foo{shape-outside:url(shape.png)}",
+    );
+    minify_test(".foo { shape-outside: none; }", ".foo{shape-outside:none}");
+    minify_test(
+      ".foo { shape-outside: circle(50%) margin-box; }",
+      ".foo{shape-outside:circle(50%)}",
+    );
+    minify_test(
+      ".foo { shape-outside: content-box ellipse(10px 20px); }",
+      ".foo{shape-outside:ellipse(10px 20px) content-box}",
+    );
+    minify_test(
+      ".foo { shape-outside: padding-box; }",
+      ".foo{shape-outside:padding-box}",
+    );
+
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          -webkit-clip-path: circle(50px);
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(30 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          -webkit-clip-path: circle(50px);
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { fill: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          fill: #ee00be;
+          fill: color(display-p3 .972962 -.362078 .804206);
+          fill: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { stroke: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          stroke: #ee00be;
+          stroke: color(display-p3 .972962 -.362078 .804206);
+          stroke: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { fill: url(#foo) lch(50.998% 135.363 338) }",
+      indoc! { r##"
+        .foo {
+          fill: url("#foo") #ee00be;
+          fill: url("#foo") color(display-p3 .972962 -.362078 .804206);
+          fill: url("#foo") lch(50.998% 135.363 338);
+        }
+      "##},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { fill: var(--url) lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          fill: var(--url) #ee00be;
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            fill: var(--url) lab(50.998% 125.506 -50.7078);
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
+          -webkit-mask-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
+          mask-image: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
+          mask-image: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask-image: linear-gradient(red, green) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: linear-gradient(red, green);
+          mask-image: linear-gradient(red, green);
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { -webkit-mask-image: url(x.svg); mask-image: url(x.svg); }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: url("x.svg");
+          mask-image: url("x.svg");
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
+          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
+          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask: -webkit-linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
+          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px var(--foo) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
+          mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            -webkit-mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
+            mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask: url(masks.svg#star) luminance }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: url("masks.svg#star");
+          -webkit-mask-source-type: luminance;
+          mask: url("masks.svg#star") luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { mask-image: url(masks.svg#star) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: url("masks.svg#star");
+          mask-image: url("masks.svg#star");
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-image: url(masks.svg#star);
+          mask-position: 25% 75%;
+          mask-size: cover;
+          mask-repeat: no-repeat;
+          mask-clip: padding-box;
+          mask-origin: content-box;
+          mask-composite: subtract;
+          mask-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box subtract luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-position: 25% 75%;
+          mask-size: cover;
+          mask-repeat: no-repeat;
+          mask-clip: padding-box;
+          mask-origin: content-box;
+          mask-composite: subtract;
+          mask-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
+          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-composite: subtract;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-composite: source-out;
+          mask-composite: subtract;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-source-type: luminance;
+          mask-mode: luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: url('border-mask.png') 25 / 35px / 12px space luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("border-mask.png") 25 / 35px / 12px space;
+          mask-border: url("border-mask.png") 25 / 35px / 12px space luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space;
+          mask-border: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space luminance;
+          -webkit-mask-box-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space;
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-source: linear-gradient(#ff0f0e, #7773ff);
+          mask-border-source: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-box-image-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-source: url(foo.png);
+          mask-border-slice: 10 40 10 40;
+          mask-border-width: 10px;
+          mask-border-outset: 0;
+          mask-border-repeat: round round;
+          mask-border-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
+          mask-border: url("foo.png") 10 40 / 10px round luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          -webkit-mask-box-image-source: url(foo.png);
+          -webkit-mask-box-image-slice: 10 40 10 40;
+          -webkit-mask-box-image-width: 10px;
+          -webkit-mask-box-image-outset: 0;
+          -webkit-mask-box-image-repeat: round round;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-slice: 10 40 10 40;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-slice: 10 40;
+          mask-border-slice: 10 40;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-slice: var(--foo);
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-slice: var(--foo);
+          mask-border-slice: var(--foo);
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) var(--foo);
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) var(--foo);
+          mask-border: linear-gradient(#ff0f0e, #7773ff) var(--foo);
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            -webkit-mask-box-image: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
+            mask-border: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
+          }
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          transition: mask 200ms;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition: -webkit-mask .2s, mask .2s;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          transition: mask-border 200ms;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition: -webkit-mask-box-image .2s, mask-border .2s;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask, mask;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask-border;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask-box-image, mask-border;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask-composite, mask-mode;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask-composite, mask-composite, -webkit-mask-source-type, mask-mode;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_motion_path() {
+    minify_test(".foo { offset-path: ray(45deg); }", ".foo{offset-path:ray(45deg)}");
+    minify_test(
+      ".foo { offset-path: ray(closest-side 45deg); }",
+      ".foo{offset-path:ray(45deg)}",
+    );
+    minify_test(
+      ".foo { offset-path: ray(contain farthest-corner 0.25turn at 10px 20px); }",
+      ".foo{offset-path:ray(.25turn farthest-corner contain at 10px 20px)}",
+    );
+    minify_test(
+      ".foo { offset-path: circle(50px at center); }",
+      ".foo{offset-path:circle(50px)}",
+    );
+    minify_test(".foo { offset-distance: 50%; }", ".foo{offset-distance:50%}");
+    minify_test(".foo { offset-rotate: auto 180deg; }", ".foo{offset-rotate:reverse}");
+    minify_test(
+      ".foo { offset-rotate: reverse 45deg; }",
+      ".foo{offset-rotate:auto 225deg}",
+    );
+    minify_test(".foo { offset-rotate: 30deg; }", ".foo{offset-rotate:30deg}");
+    minify_test(".foo { offset-rotate: auto; }", ".foo{offset-rotate:auto}");
+    minify_test(".foo { offset-anchor: right top; }", ".foo{offset-anchor:100% 0}");
+    minify_test(".foo { offset-position: normal; }", ".foo{offset-position:normal}");
+    minify_test(
+      ".foo { offset-position: left 10px top 20px; }",
+      ".foo{offset-position:10px 20px}",
+    );
+    minify_test(
+      ".foo { offset: path('M 0 0 L 100 100') 50% auto; }",
+      ".foo{offset:path(\"M0 0 100 100\") 50%}",
+    );
+    minify_test(
+      ".foo { offset: 10px 20px ray(45deg) 30% reverse / center; }",
+      ".foo{offset:10px 20px ray(45deg) 30% reverse/50%}",
+    );
+    minify_test(".foo { offset: auto; }", ".foo{offset:auto}");
+    minify_test(".foo { offset: none / left top; }", ".foo{offset:none/0 0}");
+    minify_test(".foo { offset-path: ray(45deg); offset-distance: 10px; offset-rotate: auto; offset-anchor: auto; offset-position: normal; }", ".foo{offset:ray(45deg) 10px}");
+    minify_test(
+      ".foo { offset: ray(45deg) 20px; offset-distance: 50%; }",
+      ".foo{offset:ray(45deg) 50%}",
+    );
+    minify_test(
+      ".foo { offset: none; offset-distance: 50%; }",
+      ".foo{offset-position:normal;offset-path:none;offset-distance:50%;offset-rotate:auto;offset-anchor:auto}",
+    );
+    prefix_test(
+      ".foo { offset-path: path('M 0 0 L 100 100'); offset-distance: 50%; offset-rotate: reverse; }",
+      indoc! { r#"
+        .foo {
+          motion-path: path("M 0 0 L 100 100");
+          motion-offset: 50%;
+          motion-rotation: reverse;
+          offset-path: path("M 0 0 L 100 100");
+          offset-distance: 50%;
+          offset-rotate: reverse;
+        }
+      "#},
+      Browsers {
+        chrome: Some(50 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      ".foo { offset-path: ray(45deg); offset-rotate: 30deg; }",
+      indoc! { r#"
+        .foo {
+          motion-rotation: 30deg;
+          offset-path: ray(45deg);
+          offset-rotate: 30deg;
+        }
+      "#},
+      Browsers {
+        chrome: Some(50 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      ".foo { offset-path: path('M 0 0 L 100 100'); offset-distance: 50%; }",
+      indoc! { r#"
+        .foo {
+          offset-path: path("M 0 0 L 100 100");
+          offset-distance: 50%;
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      ".foo { offset-path: path('M 0 0 L 100 100'); }",
+      indoc! { r#"
+        .foo {
+          offset-path: path("M 0 0 L 100 100");
+        }
+      "#},
+      Browsers {
+        firefox: Some(60 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_filter() {
+    minify_test(
+      ".foo { filter: url('filters.svg#filter-id'); }",
+      ".foo{filter:url(filters.svg#filter-id)}",
+    );
+    minify_test(".foo { filter: blur(5px); }", ".foo{filter:blur(5px)}");
+    minify_test(".foo { filter: blur(0px); }", ".foo{filter:blur()}");
+    minify_test(".foo { filter: brightness(10%); }", ".foo{filter:brightness(10%)}");
+    minify_test(".foo { filter: brightness(100%); }", ".foo{filter:brightness()}");
+    minify_test(
+      ".foo { filter: drop-shadow(16px 16px 20px yellow); }",
+      ".foo{filter:drop-shadow(16px 16px 20px #ff0)}",
+    );
+    minify_test(
+      ".foo { filter: contrast(175%) brightness(3%); }",
+      ".foo{filter:contrast(175%)brightness(3%)}",
+    );
+    minify_test(".foo { filter: hue-rotate(0) }", ".foo{filter:hue-rotate()}");
+
+    prefix_test(
+      ".foo { filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: blur(5px);
+          filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { backdrop-filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          backdrop-filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { backdrop-filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          -webkit-backdrop-filter: blur(5px);
+          backdrop-filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: var(--foo) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: var(--foo);
+          filter: var(--foo);
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: drop-shadow(16px 16px 20px #b32323);
+          filter: drop-shadow(16px 16px 20px #b32323);
+          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39));
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          filter: contrast(175%) drop-shadow(16px 16px 20px #b32323);
+          filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px yellow) }",
+      indoc! { r#"
+        .foo {
+          filter: drop-shadow(16px 16px 20px #b32323) drop-shadow(16px 16px 20px #ff0);
+          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px #ff0);
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          filter: var(--foo) drop-shadow(16px 16px 20px #b32323);
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_viewport() {
+    minify_test(
+      r#"
+    @viewport {
+      width: 100vw;
+    }"#,
+      "@viewport{width:100vw}",
+    );
+    minify_test(
+      r#"
+    @-ms-viewport {
+      width: device-width;
+    }"#,
+      "@-ms-viewport{width:device-width}",
+    );
+  }
+
+  #[test]
+  fn test_custom_media() {
+    custom_media_test(
+      r#"
+      @custom-media --modern (color), (hover);
+
+      @media (--modern) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media ((color) or (hover)) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+
+      @media (--color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --a (color);
+      @custom-media --b (--a);
+
+      @media (--b) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --not-color not (color);
+
+      @media not (--not-color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media (--color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media print and (--color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --not-color-print not print and (color);
+
+      @media not print and (--not-color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media not (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --print not print;
+
+      @media not (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media ((--print)) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --print print;
+
+      @media (--print) and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --not-print not print;
+
+      @media (--not-print) and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --screen screen;
+      @custom-media --print print;
+
+      @media (--print) and (--color), (--screen) and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color), screen and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color print and (color), print and (script);
+
+      @media (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and ((color) or (script)) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --not-color not all and (--color);
+
+      @media (--not-color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+        @media not all and (color) {
+          .a {
+            color: green;
+          }
+        }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+
+      @media not all and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+        @media not all and (color) {
+          .a {
+            color: green;
+          }
+        }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @media (--print) {
+        .a {
+          color: green;
+        }
+      }
+
+      @custom-media --print print;
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --not-width not (min-width: 300px);
+      @media screen and ((prefers-color-scheme: dark) or (--not-width)) {
+        .foo {
+          order: 6;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media screen and ((prefers-color-scheme: dark) or (not (min-width: 300px))) {
+        .foo {
+          order: 6;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --true true;
+      @custom-media --false false;
+      @media (--true) and (color) {
+        .a {
+          color: green;
+        }
+      }
+      @media (--false) or (hover) {
+        .b {
+          color: green;
+        }
+      }
+      @media (--false) and (hover) {
+        .c {
+          color: green;
+        }
+      }
+      @media not (--false) {
+        .d {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) {
+        .a {
+          color: green;
+        }
+      }
+
+      @media (hover) {
+        .b {
+          color: green;
+        }
+      }
+
+      @media all {
+        .d {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --narrow (max-width: 30em);
+      @custom-media --touch (hover: none) and (pointer: coarse);
+      @custom-media --small-touch (--narrow) and (--touch);
+      @custom-media --mobile (--small-touch) or (--false);
+      @custom-media --false false;
+      @media (--mobile) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (max-width: 30em) and (hover: none) and (pointer: coarse) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    let mut stylesheet = StyleSheet::parse(
+      r#"
+      @custom-media --narrow (max-width: 30em);
+      @custom-media --touch (hover: none);
+      @custom-media --small-touch (--narrow) and (--touch);
+      @custom-media --never false;
+      @custom-media --print print;
+      "#,
+      ParserOptions {
+        custom_media: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet.minify(MinifyOptions::default()).unwrap();
+    assert_eq!(
+      res.custom_media,
+      BTreeMap::from([
+        ("--narrow".into(), "(max-width: 30em)".into()),
+        ("--never".into(), "not all".into()),
+        ("--print".into(), "print".into()),
+        ("--small-touch".into(), "(max-width: 30em) and (hover: none)".into()),
+        ("--touch".into(), "(hover: none)".into()),
+      ])
+    );
+
+    fn custom_media_error_test(source: &str, err: Error<MinifyErrorKind>) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          filename: "test.css".into(),
+          custom_media: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet.minify(MinifyOptions {
+        targets: Some(Browsers {
+          chrome: Some(95 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      });
+      assert_eq!(res, Err(err))
+    }
+
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media screen and (--color-print) {
+        .a {
+          color: green;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
-          mask-border: url("foo.png") 10 40 / 10px round luminance;
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 7, end: 53 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media not print and (--color-print) {
+        .a {
+          color: green;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 7, end: 53 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+      @custom-media --color-screen screen and (color);
+
+      @media (--color-print) or (--color-screen) {}
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 60, end: 108 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+      @custom-media --color-screen screen and (color);
+
+      @media (--color-print) and (--color-screen) {}
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 60, end: 108 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --screen screen;
+      @custom-media --print print;
+
+      @media (--print) and (--screen) {}
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 7, end: 37 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --not-print not print and (color);
+      @custom-media --not-screen not screen and (color);
+
+      @media ((script) or ((--not-print) and (--not-screen))) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 62, end: 112 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --color screen and (color), print and (color);
+
+      @media (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span { start: 7, end: 67 },
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @media (--not-defined) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::CustomMediaNotDefined {
+          name: "--not-defined".into(),
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 1,
+          column: 7,
+        }),
+      },
+    );
+
+    custom_media_error_test(
+      r#"
+      @custom-media --circular-mq-a (--circular-mq-b);
+      @custom-media --circular-mq-b (--circular-mq-a);
+
+      @media (--circular-mq-a) {
+        body {
+          order: 3;
+        }
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::CircularCustomMedia {
+          name: "--circular-mq-a".into(),
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
+    );
+  }
+
+  #[test]
+  fn test_dependencies() {
+    fn dep_test(source: &str, expected: &str, deps: Vec<(&str, &str)>) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          filename: "test.css".into(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          analyze_dependencies: Some(Default::default()),
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      let dependencies = res.dependencies.unwrap();
+      assert_eq!(dependencies.len(), deps.len());
+      for (i, (url, placeholder)) in deps.into_iter().enumerate() {
+        match &dependencies[i] {
+          Dependency::Url(dep) => {
+            assert_eq!(dep.url, url);
+            assert_eq!(dep.placeholder, placeholder);
+          }
+          Dependency::Import(dep) => {
+            assert_eq!(dep.url, url);
+            assert_eq!(dep.placeholder, placeholder);
+          }
+        }
+      }
+    }
+
+    fn dep_error_test(source: &str, error: PrinterErrorKind) {
+      let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      let res = stylesheet.to_css(PrinterOptions {
+        analyze_dependencies: Some(Default::default()),
+        ..PrinterOptions::default()
+      });
+      match res {
+        Err(e) => assert_eq!(e.kind, error),
+        _ => unreachable!(),
+      }
+    }
+
+    dep_test(
+      ".foo { background: image-set('./img12x.png', './img21x.png' 2x)}",
+      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
+      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+    );
+
+    dep_test(
+      ".foo { background: image-set(url(./img12x.png), url('./img21x.png') 2x)}",
+      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
+      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+    );
+
+    dep_test(
+      ".foo { --test: url(/foo.png) }",
+      ".foo{--test:url(\"lDnnrG\")}",
+      vec![("/foo.png", "lDnnrG")],
+    );
+
+    dep_test(
+      ".foo { --test: url(\"/foo.png\") }",
+      ".foo{--test:url(\"lDnnrG\")}",
+      vec![("/foo.png", "lDnnrG")],
+    );
+
+    dep_test(
+      ".foo { --test: url(\"http://example.com/foo.png\") }",
+      ".foo{--test:url(\"3X1zSW\")}",
+      vec![("http://example.com/foo.png", "3X1zSW")],
+    );
+
+    dep_test(
+      ".foo { --test: url(\"data:image/svg+xml;utf8,<svg></svg>\") }",
+      ".foo{--test:url(\"-vl-rG\")}",
+      vec![("data:image/svg+xml;utf8,<svg></svg>", "-vl-rG")],
+    );
+
+    dep_test(
+      ".foo { background: url(\"foo.png\") var(--test) }",
+      ".foo{background:url(\"Vwkwkq\") var(--test)}",
+      vec![("foo.png", "Vwkwkq")],
+    );
+
+    dep_error_test(
+      ".foo { --test: url(\"foo.png\") }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    );
+
+    dep_error_test(
+      ".foo { --test: url(foo.png) }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    );
+
+    dep_error_test(
+      ".foo { --test: url(./foo.png) }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty {
+        url: "./foo.png".into(),
       },
     );
 
-    prefix_test(
+    dep_test(
+      ".foo { behavior: url(#foo) }",
+      ".foo{behavior:url(\"Zn9-2q\")}",
+      vec![("#foo", "Zn9-2q")],
+    );
+
+    dep_test(
+      ".foo { --foo: url(#foo) }",
+      ".foo{--foo:url(\"Zn9-2q\")}",
+      vec![("#foo", "Zn9-2q")],
+    );
+
+    dep_test(
+      "@import \"test.css\"; .foo { color: red }",
+      "@import \"hHsogW\";.foo{color:red}",
+      vec![("test.css", "hHsogW")],
+    );
+  }
+
+  #[test]
+  fn test_api() {
+    let stylesheet = StyleSheet::parse(".foo:hover { color: red }", ParserOptions::default()).unwrap();
+    match &stylesheet.rules.0[0] {
+      CssRule::Style(s) => {
+        assert_eq!(&s.selectors.to_string(), ".foo:hover");
+      }
+      _ => unreachable!(),
+    }
+
+    let color = CssColor::parse_string("#f0f").unwrap();
+    assert_eq!(color.to_css_string(PrinterOptions::default()).unwrap(), "#f0f");
+
+    let rule = CssRule::parse_string(".foo { color: red }", ParserOptions::default()).unwrap();
+    assert_eq!(
+      rule.to_css_string(PrinterOptions::default()).unwrap(),
+      indoc! {r#"
+    .foo {
+      color: red;
+    }"#}
+    );
+
+    let property = Property::parse_string("color".into(), "#f0f", ParserOptions::default()).unwrap();
+    assert_eq!(
+      property.to_css_string(false, PrinterOptions::default()).unwrap(),
+      "color: #f0f"
+    );
+    assert_eq!(
+      property.to_css_string(true, PrinterOptions::default()).unwrap(),
+      "color: #f0f !important"
+    );
+
+    let code = indoc! { r#"
+      .foo {
+        color: green;
+      }
+
+      .bar {
+        color: red;
+        background: pink;
+      }
+
+      @media print {
+        .baz {
+          color: green;
+        }
+      }
+    "#};
+    let stylesheet = StyleSheet::parse(code, ParserOptions::default()).unwrap();
+    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
+      let (key, val) = style.property_location(code, 0).unwrap();
+      assert_eq!(
+        key,
+        SourceLocation { line: 5, column: 3 }..SourceLocation { line: 5, column: 8 }
+      );
+      assert_eq!(
+        val,
+        SourceLocation { line: 5, column: 10 }..SourceLocation { line: 5, column: 13 }
+      );
+    }
+
+    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
+      let (key, val) = style.property_location(code, 1).unwrap();
+      assert_eq!(
+        key,
+        SourceLocation { line: 6, column: 3 }..SourceLocation { line: 6, column: 13 }
+      );
+      assert_eq!(
+        val,
+        SourceLocation { line: 6, column: 15 }..SourceLocation { line: 6, column: 19 }
+      );
+    }
+    if let CssRule::Media(media) = &stylesheet.rules.0[2] {
+      if let CssRule::Style(style) = &media.rules.0[0] {
+        let (key, val) = style.property_location(code, 0).unwrap();
+        assert_eq!(
+          key,
+          SourceLocation { line: 11, column: 5 }..SourceLocation { line: 11, column: 10 }
+        );
+        assert_eq!(
+          val,
+          SourceLocation { line: 11, column: 12 }..SourceLocation { line: 11, column: 17 }
+        );
+      }
+    }
+
+    let mut property = Property::Transform(Default::default(), VendorPrefix::WebKit);
+    property.set_prefix(VendorPrefix::None);
+    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::None));
+    property.set_prefix(VendorPrefix::Moz);
+    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::Moz));
+    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz);
+    assert_eq!(
+      property,
+      Property::Transform(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
+    );
+
+    let mut property = Property::TextDecorationLine(Default::default(), VendorPrefix::None);
+    property.set_prefix(VendorPrefix::Ms);
+    assert_eq!(
+      property,
+      Property::TextDecorationLine(Default::default(), VendorPrefix::None)
+    );
+    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz | VendorPrefix::Ms);
+    assert_eq!(
+      property,
+      Property::TextDecorationLine(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
+    );
+
+    let mut property = Property::AccentColor(Default::default());
+    property.set_prefix(VendorPrefix::WebKit);
+    assert_eq!(property, Property::AccentColor(Default::default()));
+  }
+
+  #[cfg(feature = "substitute_variables")]
+  #[test]
+  fn test_substitute_vars() {
+    use crate::properties::custom::TokenList;
+    use crate::traits::ParseWithOptions;
+
+    fn test(property: Property, vars: HashMap<&str, &str>, expected: &str) {
+      if let Property::Unparsed(unparsed) = property {
+        let vars = vars
+          .into_iter()
+          .map(|(k, v)| {
+            (
+              k,
+              TokenList::parse_string_with_options(v, ParserOptions::default()).unwrap(),
+            )
+          })
+          .collect();
+        let substituted = unparsed.substitute_variables(&vars).unwrap();
+        assert_eq!(
+          substituted.to_css_string(false, PrinterOptions::default()).unwrap(),
+          expected
+        );
+      } else {
+        panic!("Not an unparsed property");
+      }
+    }
+
+    let property = Property::parse_string("color".into(), "var(--test)", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--test", "yellow")]), "color: #ff0");
+
+    let property =
+      Property::parse_string("color".into(), "var(--test, var(--foo))", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--foo", "yellow")]), "color: #ff0");
+    let property = Property::parse_string(
+      "color".into(),
+      "var(--test, var(--foo, yellow))",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    test(property, HashMap::new(), "color: #ff0");
+
+    let property =
+      Property::parse_string("width".into(), "calc(var(--a) + var(--b))", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--a", "2px"), ("--b", "4px")]), "width: 6px");
+
+    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
+    test(
+      property,
+      HashMap::from([("--a", "var(--b)"), ("--b", "yellow")]),
+      "color: #ff0",
+    );
+
+    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
+    test(
+      property,
+      HashMap::from([("--a", "var(--b)"), ("--b", "var(--c)"), ("--c", "var(--a)")]),
+      "color: var(--a)",
+    );
+  }
+
+  #[test]
+  fn test_layer() {
+    minify_test("@layer foo;", "@layer foo;");
+    minify_test("@layer foo, bar;", "@layer foo,bar;");
+    minify_test("@layer foo.bar;", "@layer foo.bar;");
+    minify_test("@layer foo.bar, baz;", "@layer foo.bar,baz;");
+
+    minify_test(
       r#"
-        .foo {
-          -webkit-mask-box-image-source: url(foo.png);
-          -webkit-mask-box-image-slice: 10 40 10 40;
-          -webkit-mask-box-image-width: 10px;
-          -webkit-mask-box-image-outset: 0;
-          -webkit-mask-box-image-repeat: round round;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
+      @layer foo {
+        .bar {
+          color: red;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      }
+    "#,
+      "@layer foo{.bar{color:red}}",
     );
-
-    prefix_test(
+    minify_test(
       r#"
-        .foo {
-          mask-border-slice: 10 40 10 40;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-slice: 10 40;
-          mask-border-slice: 10 40;
+      @layer foo.bar {
+        .bar {
+          color: red;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      }
+    "#,
+      "@layer foo.bar{.bar{color:red}}",
     );
+    minify_test(r#"
+      @layer base {
+        p { max-width: 70ch; }
+      }
 
-    prefix_test(
+      @layer framework {
+        @layer base {
+          p { margin-block: 0.75em; }
+        }
+
+        @layer theme {
+          p { color: #222; }
+        }
+      }
+    "#, "@layer base{p{max-width:70ch}}@layer framework{@layer base{p{margin-block:.75em}}@layer theme{p{color:#222}}}");
+    minify_test(
       r#"
-        .foo {
-          mask-border-slice: var(--foo);
+      @layer {
+        .bar {
+          color: red;
         }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-slice: var(--foo);
-          mask-border-slice: var(--foo);
+      }
+    "#,
+      "@layer{.bar{color:red}}",
+    );
+    minify_test(
+      r#"
+      @layer foo\20 bar, baz;
+    "#,
+      "@layer foo\\ bar,baz;",
+    );
+    minify_test(
+      r#"
+      @layer one.two\20 three\#four\.five {
+        .bar {
+          color: red;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      }
+    "#,
+      "@layer one.two\\ three\\#four\\.five{.bar{color:red}}",
     );
 
-    prefix_test(
+    error_test("@layer;", ParserError::UnexpectedToken(Token::Semicolon));
+    error_test("@layer foo, bar {};", ParserError::AtRuleBodyInvalid);
+    minify_test("@import 'test.css' layer;", "@import \"test.css\" layer;");
+    minify_test("@import 'test.css' layer(foo);", "@import \"test.css\" layer(foo);");
+    minify_test(
+      "@import 'test.css' layer(foo.bar);",
+      "@import \"test.css\" layer(foo.bar);",
+    );
+    minify_test(
+      "@import 'test.css' layer(foo\\20 bar);",
+      "@import \"test.css\" layer(foo\\ bar);",
+    );
+    error_test(
+      "@import 'test.css' layer(foo, bar) {};",
+      ParserError::UnexpectedToken(Token::Comma),
+    );
+    minify_test(
       r#"
-        .foo {
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) var(--foo);
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) var(--foo);
-          mask-border: linear-gradient(#ff0f0e, #7773ff) var(--foo);
+      @layer one {
+        body {
+          background: red;
         }
+      }
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            -webkit-mask-box-image: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
-            mask-border: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
-          }
+      body {
+        background: red;
+      }
+      
+      @layer two {
+        body {
+          background: green;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
-    );
-
-    prefix_test(
-      r#"
-        .foo {
-          transition: mask 200ms;
+      }
+      
+      @layer one {
+        body {
+          background: yellow;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          transition: -webkit-mask .2s, mask .2s;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "@layer one{body{background:#ff0}}body{background:red}@layer two{body{background:green}}",
+    );
+    minify_test("@layer a, b; @layer b, c;", "@layer a,b,c;");
+    minify_test("@layer a; @layer a;", "@layer a;");
+    minify_test("@layer a.b; @layer a, c;", "@layer a.b,c;");
+    minify_test("@layer a, a, b;", "@layer a,b;");
+    minify_test(
+      "@layer a { .foo { color: red } } @layer a, b;",
+      "@layer a{.foo{color:red}}@layer b;",
+    );
+    minify_test(
+      "@layer a; @import url(foo.css); @layer b;",
+      "@layer a;@import \"foo.css\";@layer b;",
+    );
+    minify_test("@media print { @layer a; @layer a, b; }", "@media print{@layer a,b;}");
+    minify_test(
+      "@layer a {} @layer b { .foo { color: red } }",
+      "@layer a;@layer b{.foo{color:red}}",
+    );
+    minify_test("@layer a {} @layer b {}", "@layer a,b;");
+    minify_test("@layer a; @layer b; @layer a {}", "@layer a,b;");
+    minify_test("@layer {} @layer a.b {}", "@layer a.b;");
+    minify_test(
+      "@layer a {} .foo { color: red } @layer a { .bar { color: red } }",
+      "@layer a;.foo{color:red}@layer a{.bar{color:red}}",
     );
+  }
 
-    prefix_test(
+  #[test]
+  fn test_property() {
+    minify_test(
       r#"
-        .foo {
-          transition: mask-border 200ms;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition: -webkit-mask-box-image .2s, mask-border .2s;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: yellow;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>\";inherits:false;initial-value:#ff0}",
     );
 
-    prefix_test(
+    minify_test(
       r#"
-        .foo {
-          transition-property: mask;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask, mask;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      @property --property-name {
+        syntax: '<length>';
+        inherits: true;
+        initial-value: 25px;
+      }
+    "#,
+      "@property --property-name{syntax:\"<length>\";inherits:true;initial-value:25px}",
     );
 
-    prefix_test(
+    error_test(
       r#"
-        .foo {
-          transition-property: mask-border;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask-box-image, mask-border;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: 25px;
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Dimension {
+        has_sign: false,
+        value: 25.0,
+        int_value: Some(25),
+        unit: "px".into(),
+      }),
     );
 
-    prefix_test(
+    error_test(
       r#"
-        .foo {
-          transition-property: mask-composite, mask-mode;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask-composite, mask-composite, -webkit-mask-source-type, mask-mode;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      @property --property-name {
+        syntax: '<length>';
+        inherits: false;
+        initial-value: var(--some-value);
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("var".into())),
     );
-  }
 
-  #[test]
-  fn test_filter() {
-    minify_test(
-      ".foo { filter: url('filters.svg#filter-id'); }",
-      ".foo{filter:url(filters.svg#filter-id)}",
-    );
-    minify_test(".foo { filter: blur(5px); }", ".foo{filter:blur(5px)}");
-    minify_test(".foo { filter: blur(0px); }", ".foo{filter:blur()}");
-    minify_test(".foo { filter: brightness(10%); }", ".foo{filter:brightness(10%)}");
-    minify_test(".foo { filter: brightness(100%); }", ".foo{filter:brightness()}");
-    minify_test(
-      ".foo { filter: drop-shadow(16px 16px 20px yellow); }",
-      ".foo{filter:drop-shadow(16px 16px 20px #ff0)}",
+    error_test(
+      r#"
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
+      }
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
+
     minify_test(
-      ".foo { filter: contrast(175%) brightness(3%); }",
-      ".foo{filter:contrast(175%)brightness(3%)}",
+      r#"
+      @property --property-name {
+        syntax: '*';
+        inherits: false;
+      }
+    "#,
+      "@property --property-name{syntax:\"*\";inherits:false}",
     );
-    minify_test(".foo { filter: hue-rotate(0) }", ".foo{filter:hue-rotate()}");
 
-    prefix_test(
-      ".foo { filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: blur(5px);
-          filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
+    error_test(
+      r#"
+      @property --property-name {
+        syntax: '*';
+      }
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
 
-    prefix_test(
-      ".foo { filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
-      },
+    error_test(
+      r#"
+      @property --property-name {
+        inherits: false;
+      }
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
 
-    prefix_test(
-      ".foo { backdrop-filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          backdrop-filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
-      },
+    error_test(
+      r#"
+      @property property-name {
+        syntax: '*';
+        inherits: false;
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("property-name".into())),
     );
 
-    prefix_test(
-      ".foo { backdrop-filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          -webkit-backdrop-filter: blur(5px);
-          backdrop-filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: 'custom | <color>';
+        inherits: false;
+        initial-value: yellow;
+      }
+    "#,
+      "@property --property-name{syntax:\"custom|<color>\";inherits:false;initial-value:#ff0}",
     );
 
-    prefix_test(
-      ".foo { filter: var(--foo) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: var(--foo);
-          filter: var(--foo);
-        }
-      "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
-    );
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<transform-list>';
+        inherits: false;
+        initial-value: translate(200px,300px) translate(100px,200px) scale(2);
+      }
+    "#, "@property --property-name{syntax:\"<transform-list>\";inherits:false;initial-value:matrix(2,0,0,2,300,500)}");
 
-    prefix_test(
-      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: drop-shadow(16px 16px 20px #b32323);
-          filter: drop-shadow(16px 16px 20px #b32323);
-          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39));
-        }
-      "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<time>';
+        inherits: false;
+        initial-value: 1000ms;
+      }
+    "#,
+      "@property --property-name{syntax:\"<time>\";inherits:false;initial-value:1s}",
     );
 
-    prefix_test(
-      ".foo { filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          filter: contrast(175%) drop-shadow(16px 16px 20px #b32323);
-          filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39));
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<url>';
+        inherits: false;
+        initial-value: url("foo.png");
+      }
+    "#,
+      "@property --property-name{syntax:\"<url>\";inherits:false;initial-value:url(foo.png)}",
     );
 
-    prefix_test(
-      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px yellow) }",
-      indoc! { r#"
-        .foo {
-          filter: drop-shadow(16px 16px 20px #b32323) drop-shadow(16px 16px 20px #ff0);
-          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px #ff0);
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<image>';
+        inherits: false;
+        initial-value: linear-gradient(yellow, blue);
+      }
+    "#,
+      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
     );
 
-    prefix_test(
-      ".foo { filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          filter: var(--foo) drop-shadow(16px 16px 20px #b32323);
-        }
+    minify_test(
+      r#"
+      @property --property-name {
+        initial-value: linear-gradient(yellow, blue);
+        inherits: false;
+        syntax: '<image>';
+      }
+    "#,
+      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
+    );
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39));
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    test(
+      r#"
+      @property --property-name {
+        syntax: '<length>|none';
+        inherits: false;
+        initial-value: none;
+      }
+    "#,
+      indoc! {r#"
+      @property --property-name {
+        syntax: "<length> | none";
+        inherits: false;
+        initial-value: none;
+      }
+    "#},
     );
-  }
 
-  #[test]
-  fn test_viewport() {
     minify_test(
       r#"
-    @viewport {
-      width: 100vw;
-    }"#,
-      "@viewport{width:100vw}",
+      @property --property-name {
+        syntax: '<color>#';
+        inherits: false;
+        initial-value: yellow, blue;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>#\";inherits:false;initial-value:#ff0,#00f}",
     );
     minify_test(
       r#"
-    @-ms-viewport {
-      width: device-width;
-    }"#,
-      "@-ms-viewport{width:device-width}",
+      @property --property-name {
+        syntax: '<color>+';
+        inherits: false;
+        initial-value: yellow blue;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>+\";inherits:false;initial-value:#ff0 #00f}",
     );
   }
 
   #[test]
-  fn test_custom_media() {
-    custom_media_test(
-      r#"
-      @custom-media --modern (color), (hover);
+  fn test_extract_property_rules() {
+    use crate::rules::property::PropertyRegistration;
 
-      @media (--modern) and (width > 1024px) {
-        .a {
-          color: green;
-        }
+    let source = r#"
+      @property --angle {
+        syntax: "<angle>";
+        inherits: false;
+        initial-value: 45deg;
       }
-      "#,
+      .foo {
+        rotate: var(--angle);
+      }
+      @property --any {
+        syntax: "*";
+        inherits: true;
+      }
+      .bar {
+        rotate: var(--angle);
+      }
+    "#;
+
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let registrations = stylesheet.extract_property_rules(None, true).unwrap();
+    assert_eq!(
+      registrations,
+      vec![
+        PropertyRegistration {
+          name: "--angle".into(),
+          syntax: "<angle>".into(),
+          inherits: false,
+          initial_value: Some("45deg".into()),
+        },
+        PropertyRegistration {
+          name: "--any".into(),
+          syntax: "*".into(),
+          inherits: true,
+          initial_value: None,
+        },
+      ]
+    );
+    assert_eq!(
+      registrations[0].to_js(),
+      r#"CSS.registerProperty({name: "--angle", syntax: "<angle>", inherits: false, initialValue: "45deg"});"#
+    );
+    assert_eq!(
+      registrations[1].to_js(),
+      r#"CSS.registerProperty({name: "--any", syntax: "*", inherits: true});"#
+    );
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      res.code,
       indoc! {r#"
-      @media ((color) or (hover)) and (width > 1024px) {
-        .a {
-          color: green;
-        }
+      .foo {
+        rotate: var(--angle);
       }
-      "#},
+
+      .bar {
+        rotate: var(--angle);
+      }
+      "#}
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
+    // Rules are kept when not removed.
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    assert_eq!(stylesheet.extract_property_rules(None, false).unwrap().len(), 2);
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, "@property --angle{syntax:\"<angle>\";inherits:false;initial-value:45deg}.foo{rotate:var(--angle)}@property --any{syntax:\"*\";inherits:true}.bar{rotate:var(--angle)}");
 
-      @media (--color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media (color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    // Nothing is extracted when all targets support @property.
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let targets = Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
+    assert_eq!(stylesheet.extract_property_rules(Some(targets), true).unwrap(), vec![]);
+    let targets = Browsers {
+      firefox: Some(115 << 16),
+      ..Browsers::default()
+    };
+    assert_eq!(stylesheet.extract_property_rules(Some(targets), true).unwrap().len(), 2);
+  }
+
+  #[test]
+  fn test_compat_report() {
+    let source = ".foo:has(.bar) { color: red }\n.baz { display: grid; field-sizing: content }\n@media print { @starting-style { .qux { opacity: 0 } } }";
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let targets = Browsers {
+      chrome: Some(100 << 16),
+      safari: Some(15 << 16),
+      ..Browsers::default()
+    };
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(targets),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let report: Vec<String> = stylesheet
+      .compat_report(targets)
+      .iter()
+      .map(|issue| issue.to_string())
+      .collect();
+    assert_eq!(
+      report,
+      vec![
+        "test.css:1:1: css-has is not supported by chrome 100, safari 15: .foo:has(.bar)",
+        "test.css:2:1: field-sizing is not supported by chrome 100, safari 15: field-sizing: content",
+        "test.css:3:16: at-starting-style is not supported by chrome 100, safari 15: @starting-style",
+      ]
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --a (color);
-      @custom-media --b (--a);
+    let targets = Browsers {
+      chrome: Some(130 << 16),
+      ..Browsers::default()
+    };
+    assert_eq!(stylesheet.compat_report(targets), vec![]);
+  }
 
-      @media (--b) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media (color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+  #[test]
+  fn test_design_tokens() {
+    use crate::design_tokens::{DesignToken, DesignTokenOptions, DesignTokenValue, ShadowToken};
+
+    fn summary(tokens: &[DesignToken]) -> Vec<(&str, usize)> {
+      tokens.iter().map(|token| (token.name.as_str(), token.usages)).collect()
+    }
+
+    let source = r#"
+      :root {
+        --brand: #ff0000;
+        --space-sm: 4px;
+        --radius-md: 8px;
+        --font-body: Inter, sans-serif;
+        --shadow: 0 1px 2px rgba(0, 0, 0, .5);
+        --display: block;
+      }
+      .a { color: red; margin: 4px 8px; border-radius: 8px; font-family: Inter, sans-serif }
+      .b { background-color: #00f; box-shadow: 0 1px 2px rgba(0, 0, 0, .5); padding: 0; margin-top: auto }
+    "#;
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let tokens = stylesheet.design_tokens(DesignTokenOptions::default());
+    assert_eq!(summary(&tokens.color), vec![("brand", 1), ("color-1", 1)]);
+    assert_eq!(summary(&tokens.font_family), vec![("font-body", 1)]);
+    assert_eq!(
+      summary(&tokens.spacing),
+      vec![("space-sm", 2), ("spacing-1", 2), ("spacing-2", 4)]
+    );
+    assert_eq!(summary(&tokens.radius), vec![("radius-md", 4)]);
+    assert_eq!(summary(&tokens.shadow), vec![("shadow", 1)]);
+    assert_eq!(tokens.color[0].value, DesignTokenValue::Color("#ff0000".into()));
+    assert!(tokens.color[0].custom_property);
+    assert_eq!(tokens.color[1].value, DesignTokenValue::Color("#0000ff".into()));
+    assert!(!tokens.color[1].custom_property);
+    assert_eq!(
+      tokens.font_family[0].value,
+      DesignTokenValue::FontFamily(vec!["Inter".into(), "sans-serif".into()])
     );
+    assert_eq!(tokens.spacing[2].value, DesignTokenValue::Dimension("0".into()));
+    assert_eq!(
+      tokens.shadow[0].value,
+      DesignTokenValue::Shadow(vec![ShadowToken {
+        color: "#00000080".into(),
+        offset_x: "0".into(),
+        offset_y: "1px".into(),
+        blur: "2px".into(),
+        spread: "0".into(),
+        inset: false,
+      }])
+    );
+
+    let tokens = stylesheet.design_tokens(DesignTokenOptions {
+      custom_properties_only: true,
+    });
+    assert_eq!(summary(&tokens.color), vec![("brand", 1)]);
+    assert_eq!(summary(&tokens.spacing), vec![("space-sm", 2)]);
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --not-color not (color);
+  #[test]
+  fn test_var_graph() {
+    use crate::var_graph::VarReference;
+    use std::collections::BTreeSet;
+
+    let source = ":root { --a: var(--b); --b: var(--a); --c: 1px; --d: var(--c) var(--missing, 2px); --self: var(--self) }\n.foo { width: var(--d); color: var(--undefined) }";
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let graph = stylesheet.var_graph();
+    assert_eq!(graph.dependencies("--d"), BTreeSet::from(["--c", "--missing"]));
+    assert_eq!(graph.dependencies("--c"), BTreeSet::new());
+    assert_eq!(graph.dependents("--c"), BTreeSet::from(["--d"]));
+    assert_eq!(graph.dependents("--a"), BTreeSet::from(["--b"]));
+    assert!(graph.is_defined("--a"));
+    assert!(!graph.is_defined("--missing"));
+    assert_eq!(graph.undefined(), vec!["--missing", "--undefined"]);
+    assert_eq!(graph.cycles(), vec![vec!["--a", "--b"], vec!["--self"]]);
+    let undefined: Vec<_> = graph
+      .undefined_references::<&str>(&[])
+      .into_iter()
+      .map(|(name, reference)| (name, reference.property.as_str()))
+      .collect();
+    assert_eq!(undefined, vec![("--undefined", "color")]);
+    assert!(graph.undefined_references(&["--undef*"]).is_empty());
+    assert_eq!(
+      graph.variables["--d"].references,
+      vec![VarReference {
+        property: "width".into(),
+        loc: Location {
+          source_index: 0,
+          line: 1,
+          column: 1,
+          #[cfg(feature = "spans")]
+          span: crate::span::Span { start: 105, end: 154 },
+        },
+        has_fallback: false,
+      }]
+    );
+    assert_eq!(graph.variables["--missing"].references[0].property, "--d");
+    assert!(graph.variables["--missing"].references[0].has_fallback);
+    assert_eq!(
+      graph.variables["--d"].definitions[0].dependencies,
+      vec!["--c", "--missing"]
+    );
+  }
 
-      @media not (--not-color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media (color) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+  #[test]
+  fn test_specificity_report() {
+    use crate::selector::Specificity;
+
+    let source =
+      ".a { color: red }\n#b .c, div { color: red }\n@media print { .a.d > span { & .e { color: red } } }";
+    let stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let report = stylesheet.specificity_report();
+    let selectors: Vec<(&str, String)> = report
+      .selectors
+      .iter()
+      .map(|s| (s.selector.as_str(), s.specificity.to_string()))
+      .collect();
+    assert_eq!(
+      selectors,
+      vec![
+        (".a", "0,1,0".into()),
+        ("#b .c", "1,1,0".into()),
+        ("div", "0,0,1".into()),
+        (".a.d > span", "0,2,1".into()),
+        ("& .e", "0,1,0".into()),
+      ]
+    );
+    assert_eq!(
+      report.histogram().into_iter().collect::<Vec<_>>(),
+      vec![
+        (Specificity::new(0, 0, 1), 1),
+        (Specificity::new(0, 1, 0), 2),
+        (Specificity::new(0, 2, 1), 1),
+        (Specificity::new(1, 1, 0), 1),
+      ]
     );
+    let most_specific: Vec<_> = report.most_specific(2).iter().map(|s| s.selector.as_str()).collect();
+    assert_eq!(most_specific, vec!["#b .c", ".a.d > span"]);
+    assert_eq!(
+      report.most_specific(1)[0].loc,
+      Location {
+        source_index: 0,
+        line: 1,
+        column: 1,
+        #[cfg(feature = "spans")]
+        span: crate::span::Span { start: 18, end: 43 },
+      }
+    );
+    let exceeding: Vec<_> = report
+      .exceeding(Specificity::new(0, 1, 0))
+      .iter()
+      .map(|s| s.selector.as_str())
+      .collect();
+    assert_eq!(exceeding, vec!["#b .c", ".a.d > span"]);
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --color-print print and (color);
+  #[test]
+  fn test_duplicate_report() {
+    let source = ".a { color: red; width: 10px }\n.b { color: red; width: 10px }\n.a { height: 5px }\n@media print { .c { color: red } .d { color: red } }\n.e { color: red }";
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let report = stylesheet.duplicate_report();
+
+    let summary = |groups: &[crate::duplicates::DuplicateGroup]| -> Vec<(String, usize, usize, Vec<String>)> {
+      groups
+        .iter()
+        .map(|group| {
+          (
+            group.content.clone(),
+            group.bytes,
+            group.redundant_bytes,
+            group.occurrences.iter().map(|o| o.selectors.clone()).collect(),
+          )
+        })
+        .collect()
+    };
 
-      @media (--color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
-        }
+    assert_eq!(
+      summary(&report.declaration_blocks),
+      vec![
+        ("color:red;width:10px".into(), 20, 20, vec![".a".into(), ".b".into()]),
+        ("color:red".into(), 9, 18, vec![".c".into(), ".d".into(), ".e".into()]),
+      ]
+    );
+    assert_eq!(
+      summary(&report.selectors),
+      vec![(".a".into(), 2, 2, vec![".a".into(), ".a".into()])]
+    );
+    assert_eq!(
+      summary(&report.declarations),
+      vec![
+        ("color:red".into(), 9, 18, vec![".a".into(), ".b".into(), ".e".into()]),
+        ("width:10px".into(), 10, 10, vec![".a".into(), ".b".into()]),
+        ("color:red".into(), 9, 9, vec![".c".into(), ".d".into()]),
+      ]
+    );
+    assert_eq!(report.declarations[2].occurrences[0].scope, vec!["@media print"]);
+    assert_eq!(
+      report.declarations[2].occurrences[0].loc,
+      Location {
+        source_index: 0,
+        line: 3,
+        column: 16,
+        #[cfg(feature = "spans")]
+        span: crate::span::Span { start: 96, end: 113 },
       }
-      "#},
     );
+    assert_eq!(report.redundant_bytes(), 77);
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --color-print print and (color);
+  #[test]
+  fn test_selector_complexity() {
+    use crate::selector::SelectorComplexity;
 
-      @media print and (--color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    let source = ".a > .b .c { color: red }\n.a * { color: red }\nul li:not([hidden]):hover::before, :is(.a, [b]) + :has(> img) { color: red }";
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let report = stylesheet.selector_complexity();
+    let complexity: Vec<_> = report.selectors.iter().map(|entry| entry.complexity).collect();
+    assert_eq!(
+      complexity,
+      vec![
+        SelectorComplexity {
+          compounds: 3,
+          combinator_depth: 2,
+          descendant_combinators: 1,
+          universal_key: false,
+          ..SelectorComplexity::default()
+        },
+        SelectorComplexity {
+          compounds: 2,
+          combinator_depth: 1,
+          descendant_combinators: 1,
+          universal: 1,
+          universal_key: true,
+          ..SelectorComplexity::default()
+        },
+        SelectorComplexity {
+          compounds: 2,
+          combinator_depth: 1,
+          descendant_combinators: 1,
+          pseudo_classes: 2,
+          pseudo_elements: 1,
+          attributes: 1,
+          universal_key: false,
+          ..SelectorComplexity::default()
+        },
+        SelectorComplexity {
+          compounds: 2,
+          combinator_depth: 1,
+          pseudo_classes: 2,
+          attributes: 1,
+          universal_key: true,
+          ..SelectorComplexity::default()
+        },
+      ]
+    );
+    assert_eq!(report.selectors[2].selector, "ul li:not([hidden]):hover:before");
+    assert_eq!(report.selectors[2].loc.line, 2);
+
+    let universal: Vec<_> = report
+      .filter(|complexity| complexity.universal_key)
+      .iter()
+      .map(|entry| entry.selector.as_str())
+      .collect();
+    assert_eq!(universal, vec![".a *", ":is(.a, [b]) + :has( > img)"]);
+  }
+
+  #[test]
+  fn test_usage_stats() {
+    let source = r#"
+      .a, .b { color: var(--x); width: calc(100% - 10px) !important; -webkit-transition: opacity 1s }
+      @media print { .c { background: url(a.png) } }
+      @-webkit-keyframes fade { from { opacity: 0 } }
+    "#;
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let stats = stylesheet.usage_stats();
+    let counts = |map: &std::collections::BTreeMap<String, usize>| {
+      map.iter().map(|(name, count)| (name.clone(), *count)).collect::<Vec<_>>()
+    };
+    assert_eq!(stats.style_rules, 2);
+    assert_eq!(stats.selectors, 3);
+    assert_eq!(stats.declarations, 5);
+    assert_eq!(stats.important_declarations, 1);
+    assert_eq!(
+      counts(&stats.properties),
+      vec![
+        ("background".into(), 1),
+        ("color".into(), 1),
+        ("opacity".into(), 1),
+        ("transition".into(), 1),
+        ("width".into(), 1),
+      ]
+    );
+    assert_eq!(
+      counts(&stats.functions),
+      vec![("calc".into(), 1), ("url".into(), 1), ("var".into(), 1)]
+    );
+    assert_eq!(
+      counts(&stats.at_rules),
+      vec![("keyframes".into(), 1), ("media".into(), 1)]
     );
+    assert_eq!(counts(&stats.vendor_prefixes), vec![("-webkit-".into(), 2)]);
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --not-color-print not print and (color);
+  #[test]
+  fn test_rule_query() {
+    use crate::properties::PropertyId;
+    use crate::rules::layer::LayerName;
 
-      @media not print and (--not-color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media not print and (color) {
-        .a {
-          color: green;
-        }
+    let source = r#"
+      .a { color: red; top: 0 }
+      @media (min-width: 500px) {
+        .a, .b { color: blue; width: 10px }
+        @supports (display: grid) { .c > .d { display: grid } }
       }
-      "#},
+      @layer base { .a { color: green; left: 0 } }
+    "#;
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+
+    let matches = stylesheet.query_selector(".c>.d");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].context.media.len(), 1);
+    assert_eq!(matches[0].context.supports.len(), 1);
+    assert!(stylesheet.query_selector(".e").is_empty());
+    assert_eq!(stylesheet.query_property(&PropertyId::Width).len(), 1);
+    assert_eq!(stylesheet.query_at_rule("media", "(min-width: 500px)").len(), 1);
+    assert_eq!(stylesheet.query_at_rule("supports", "(display: grid)").len(), 1);
+    assert!(stylesheet.query_at_rule("media", "print").is_empty());
+    assert_eq!(
+      stylesheet.query_selectors(|selector| selector.specificity() >= 2 << 10).len(),
+      1
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
-
-      @media (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
+    let matches = stylesheet.query_selector(".a");
+    assert_eq!(matches.len(), 3);
+    assert_eq!(
+      matches[2].context.layers,
+      vec![Some(LayerName::parse_string("base").unwrap())]
+    );
+    for m in matches {
+      if let CssRule::Style(style) = m.rule {
+        style.declarations.remove(&PropertyId::Color);
       }
-      "#},
+    }
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".a{top:0}@media (min-width:500px){.a,.b{width:10px}@supports (display:grid){.c>.d{display:grid}}}@layer base{.a{left:0}}"
     );
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
+  #[test]
+  fn test_coverage() {
+    use crate::coverage::{Coverage, CoverageEntry, CoverageRange};
+
+    let source =
+      ".a { color: red }\n.b { color: blue }\n@media print {\n  .c { color: green }\n}\n.d { color: black }\n";
+    let coverage = Coverage::new(vec![
+      CoverageEntry {
+        url: "http://localhost:8080/styles/main.css?v=1".into(),
+        ranges: vec![
+          CoverageRange { start: 0, end: 17 },
+          CoverageRange { start: 76, end: 95 },
+        ],
+        text: Some(source.into()),
+      },
+      CoverageEntry {
+        url: "http://localhost:8080/other.css".into(),
+        ranges: vec![],
+        text: Some(source.into()),
+      },
+    ]);
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "src/styles/main.css".into(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
 
-      @media not (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media not print {
-        .a {
-          color: green;
-        }
-      }
-      "#},
-    );
+    let unused: Vec<_> = stylesheet
+      .unused_rules(&coverage)
+      .into_iter()
+      .map(|rule| (rule.selectors, rule.loc.line, rule.loc.column))
+      .collect();
+    assert_eq!(unused, vec![(".b".into(), 1, 1), (".c".into(), 3, 3)]);
 
-    custom_media_test(
-      r#"
-      @custom-media --print not print;
+    assert_eq!(stylesheet.remove_unused_rules(&coverage).len(), 2);
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".a{color:red}.d{color:#000}");
 
-      @media not (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
-      }
-      "#},
-    );
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    assert!(stylesheet.unused_rules(&coverage).is_empty());
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
+  #[test]
+  fn test_quoting_unquoting_urls() {
+    // Quotes remain double quotes when not minifying
+    test(
+      r#".foo {
+      background-image: url("0123abcd");
+    }"#,
+      r#".foo {
+  background-image: url("0123abcd");
+}
+"#,
+    );
 
-      @media ((--print)) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    // Quotes removed when minifying
+    minify_test(
+      r#".foo {
+      background-image: url("0123abcd");
+    }"#,
+      r#".foo{background-image:url(0123abcd)}"#,
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --print print;
+    // Doubles quotes added if not present when not minifying
+    test(
+      r#".foo {
+      background-image: url(0123abcd);
+    }"#,
+      r#".foo {
+  background-image: url("0123abcd");
+}
+"#,
+    );
 
-      @media (--print) and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    // No quotes changed if not present when not minifying
+    minify_test(
+      r#".foo {
+      background-image: url(0123abcd);
+    }"#,
+      r#".foo{background-image:url(0123abcd)}"#,
     );
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --not-print not print;
+  #[test]
+  fn test_zindex() {
+    minify_test(".foo { z-index: 2 }", ".foo{z-index:2}");
+    minify_test(".foo { z-index: -2 }", ".foo{z-index:-2}");
+    minify_test(".foo { z-index: 999999 }", ".foo{z-index:999999}");
+    minify_test(".foo { z-index: 9999999 }", ".foo{z-index:9999999}");
+    minify_test(".foo { z-index: -9999999 }", ".foo{z-index:-9999999}");
+  }
 
-      @media (--not-print) and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media not print and (color) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
-    );
+  #[test]
+  #[cfg(feature = "sourcemap")]
+  fn test_input_source_map() {
+    let source = r#".imported {
+      content: "yay, file support!";
+    }
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --screen screen;
-      @custom-media --print print;
+    .selector {
+      margin: 1em;
+      background-color: #f60;
+    }
 
-      @media (--print) and (--color), (--screen) and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color), screen and (color) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    .selector .nested {
+      margin: 0.5em;
+    }
+
+    /*# sourceMappingURL=data:application/json;base64,ewoJInZlcnNpb24iOiAzLAoJInNvdXJjZVJvb3QiOiAicm9vdCIsCgkiZmlsZSI6ICJzdGRvdXQiLAoJInNvdXJjZXMiOiBbCgkJInN0ZGluIiwKCQkic2Fzcy9fdmFyaWFibGVzLnNjc3MiLAoJCSJzYXNzL19kZW1vLnNjc3MiCgldLAoJInNvdXJjZXNDb250ZW50IjogWwoJCSJAaW1wb3J0IFwiX3ZhcmlhYmxlc1wiO1xuQGltcG9ydCBcIl9kZW1vXCI7XG5cbi5zZWxlY3RvciB7XG4gIG1hcmdpbjogJHNpemU7XG4gIGJhY2tncm91bmQtY29sb3I6ICRicmFuZENvbG9yO1xuXG4gIC5uZXN0ZWQge1xuICAgIG1hcmdpbjogJHNpemUgLyAyO1xuICB9XG59IiwKCQkiJGJyYW5kQ29sb3I6ICNmNjA7XG4kc2l6ZTogMWVtOyIsCgkJIi5pbXBvcnRlZCB7XG4gIGNvbnRlbnQ6IFwieWF5LCBmaWxlIHN1cHBvcnQhXCI7XG59IgoJXSwKCSJtYXBwaW5ncyI6ICJBRUFBLFNBQVMsQ0FBQztFQUNSLE9BQU8sRUFBRSxvQkFBcUI7Q0FDL0I7O0FGQ0QsU0FBUyxDQUFDO0VBQ1IsTUFBTSxFQ0hELEdBQUc7RURJUixnQkFBZ0IsRUNMTCxJQUFJO0NEVWhCOztBQVBELFNBQVMsQ0FJUCxPQUFPLENBQUM7RUFDTixNQUFNLEVDUEgsS0FBRztDRFFQIiwKCSJuYW1lcyI6IFtdCn0= */"#;
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let mut sm = parcel_sourcemap::SourceMap::new("/");
+    stylesheet
+      .to_css(PrinterOptions {
+        source_map: Some(&mut sm),
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    let map = sm.to_json(None).unwrap();
+    assert_eq!(
+      map,
+      r#"{"version":3,"sourceRoot":null,"mappings":"AAAA,uCCGA,2CAAA","sources":["sass/_demo.scss","stdin"],"sourcesContent":[".imported {\n  content: \"yay, file support!\";\n}","@import \"_variables\";\n@import \"_demo\";\n\n.selector {\n  margin: $size;\n  background-color: $brandColor;\n\n  .nested {\n    margin: $size / 2;\n  }\n}"],"names":[]}"#
     );
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --color print and (color), print and (script);
+  #[test]
+  #[cfg(feature = "comments")]
+  fn test_preserve_comments() {
+    fn comments_test(source: &str, expected: &str, minify: bool) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          nesting: true,
+          preserve_comments: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
 
-      @media (--color) {
-        .a {
-          color: green;
-        }
+    let source = r#"
+      /*! License */
+      /* Header */
+      .foo {
+        /* Text color */
+        color: red;
+        width: 0 !important; /* Trailing */
       }
-      "#,
+
+      /*! Keep */
+      .bar { /*! Keep */ color: blue }
+      /* Footer */
+    "#;
+    comments_test(
+      source,
       indoc! {r#"
-      @media print and ((color) or (script)) {
-        .a {
-          color: green;
+        /*! License */
+        /* Header */
+        .foo {
+          /* Text color */
+          color: red;
+          width: 0 !important;
+          /* Trailing */
         }
-      }
+
+        /*! Keep */
+        .bar {
+          /*! Keep */
+          color: #00f;
+        }
+
+        /* Footer */
       "#},
+      false,
+    );
+    comments_test(
+      source,
+      "/*! License */.foo{color:red;width:0!important}/*! Keep */.bar{/*! Keep */color:#00f}",
+      true,
     );
 
-    custom_media_test(
+    comments_test(
       r#"
-      @custom-media --color (color);
-      @custom-media --not-color not all and (--color);
-
-      @media (--not-color) {
-        .a {
-          color: green;
+        .foo {
+          /* a */
+          color: red;
+          /* b */
+          div { color: green; }
+          /* c */
+          background: blue;
         }
-      }
       "#,
       indoc! {r#"
-        @media not all and (color) {
-          .a {
+        .foo {
+          /* a */
+          color: red;
+
+          /* b */
+          & div {
             color: green;
           }
+
+          /* c */
+          background: #00f;
         }
       "#},
+      false,
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
+    let mut attr = StyleAttribute::parse(
+      "/* a */ color: red; /* b */ width: 0; /* c */",
+      ParserOptions {
+        preserve_comments: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    attr.minify(MinifyOptions::default());
+    let res = attr.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, "/* a */ color: red; /* b */ width: 0 /* c */");
 
-      @media not all and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+    let stylesheet = StyleSheet::parse("/* a */ .foo { /* b */ color: red }", ParserOptions::default()).unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, ".foo {\n  color: red;\n}\n");
+  }
+
+  #[test]
+  #[cfg(not(feature = "comments"))]
+  fn test_preserve_comments() {
+    // Without the `comments` feature, only comments between rules are preserved.
+    let mut stylesheet = StyleSheet::parse(
+      "/* a */ .foo { /* b */ color: red; /* c */ div { color: green } } /* d */",
+      ParserOptions {
+        nesting: true,
+        preserve_comments: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      res.code,
       indoc! {r#"
-        @media not all and (color) {
-          .a {
+        /* a */
+        .foo {
+          color: red;
+
+          /* c */
+          & div {
             color: green;
           }
         }
-      "#},
+
+        /* d */
+      "#}
     );
+  }
 
-    custom_media_test(
+  #[test]
+  #[cfg(feature = "spans")]
+  fn test_spans() {
+    use crate::span::Spanned;
+
+    let source = indoc! {r#"
+      @import "foo.css";
+      .foo, .bar > .baz:not(.qux) {
+        color: red;
+        border: 1px solid rgb(0 0 0);
+        width: 100px !important;
+        &:hover { color: blue }
+        height: 0
+      }
+      @media print {
+        .qux { display: none; }
+      }
+    "#};
+    // Declared before the style sheets, which must share its lifetime to be compared.
+    let shifted = format!("\n\n{}", source);
+    let stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let rules = &stylesheet.rules.0;
+    assert_eq!(rules[0].span().slice(source), "@import \"foo.css\";");
+    assert_eq!(
+      rules[2].span().slice(source),
+      "@media print {\n  .qux { display: none; }\n}"
+    );
+
+    let style = match &rules[1] {
+      CssRule::Style(style) => style,
+      _ => unreachable!(),
+    };
+    assert!(style.loc.span().slice(source).starts_with(".foo, .bar > .baz:not(.qux) {"));
+    let selectors: Vec<_> = style.selector_spans.iter().map(|span| span.slice(source)).collect();
+    assert_eq!(selectors, vec![".foo", ".bar > .baz:not(.qux)"]);
+
+    let color = style.declarations.span_of(false, 0).unwrap();
+    assert_eq!(color.span.slice(source), "color: red");
+    assert_eq!(color.name.slice(source), "color");
+    assert_eq!(color.value.slice(source), "red");
+    let border = style.declarations.span_of(false, 1).unwrap();
+    assert_eq!(border.value.slice(source), "1px solid rgb(0 0 0)");
+    let values: Vec<_> = border.values.iter().map(|span| span.slice(source)).collect();
+    assert_eq!(values, vec!["1px", "solid", "rgb(0 0 0)"]);
+    let width = style.declarations.span_of(true, 0).unwrap();
+    assert_eq!(width.span.slice(source), "width: 100px !important");
+    assert_eq!(width.value.slice(source), "100px");
+    assert_eq!(width.values.len(), 1);
+
+    assert_eq!(style.rules.0[0].span().slice(source), "&:hover { color: blue }");
+    let hover = match &style.rules.0[0] {
+      CssRule::Style(hover) => hover,
+      _ => unreachable!(),
+    };
+    assert_eq!(hover.selector_spans[0].slice(source), "&:hover");
+    assert_eq!(
+      hover.declarations.span_of(false, 0).unwrap().value.slice(source),
+      "blue"
+    );
+    let nested = match &style.rules.0[1] {
+      CssRule::NestedDeclarations(nested) => nested,
+      _ => unreachable!(),
+    };
+    assert_eq!(
+      nested.declarations.span_of(false, 0).unwrap().span.slice(source),
+      "height: 0"
+    );
+
+    let media = match &rules[2] {
+      CssRule::Media(media) => media,
+      _ => unreachable!(),
+    };
+    assert_eq!(media.rules.0[0].span().slice(source), ".qux { display: none; }");
+
+    // Spans affect equality, and are removed when minifying.
+    let options = ParserOptions {
+      nesting: true,
+      ..ParserOptions::default()
+    };
+    let mut other = StyleSheet::parse(&shifted, options).unwrap();
+    assert_eq!(other.rules.0[0].span().slice(&shifted), "@import \"foo.css\";");
+    assert_ne!(other.rules, stylesheet.rules);
+    other.minify(MinifyOptions::default()).unwrap();
+    let style = match &other.rules.0[1] {
+      CssRule::Style(style) => style,
+      _ => unreachable!(),
+    };
+    assert!(style.selector_spans.is_empty());
+    assert!(style.declarations.span_of(false, 0).is_none());
+  }
+
+  #[test]
+  fn test_lossless() {
+    fn lossless_test(source: &str, preserve_comments: bool) {
+      let stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          lossless: true,
+          preserve_comments,
+          error_recovery: true,
+          nesting: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+      assert_eq!(res.code, source);
+    }
+
+    let source = r#"@charset "utf-8";
+@import url("foo.css") screen;
+
+/* Header */
+.foo,.bar{color:#FF0000;margin : 0 0 0 0 !important}
+  @media (min-width:100px){ .baz { background: URL(a.png) } }
+
+..invalid { color: red }
+.qux {
+	transition: opacity .3s;
+  &:hover { color: rgb(0 0 0 / 50%) }
+}
+/* Footer */"#;
+    lossless_test(source, false);
+    lossless_test(source, true);
+
+    // Changed rules are printed normally, and the original text is kept around them.
+    let mut stylesheet = StyleSheet::parse(
+      ".a{color:red}\n\n.b {  color : #FF0000 }\n  .c{color:blue}\n",
+      ParserOptions {
+        lossless: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet.rules.0.remove(0);
+    if let CssRule::Style(style) = &mut stylesheet.rules.0[1] {
+      style.declarations.declarations.clear();
+    }
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, "\n\n.b {  color : #FF0000 }\n  .c {\n}\n");
+
+    // The original text is not used when minifying.
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".b{color:red}.c{}");
+  }
+
+  #[test]
+  fn test_custom_at_rules() {
+    use crate::rules::custom_at_rule::{CustomAtRuleBody, CustomAtRuleBodyType, CustomAtRuleConfig};
+    use crate::values::syntax::{ParsedComponent, SyntaxString};
+
+    fn options() -> ParserOptions<'static, 'static> {
+      let mut options = ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      };
+      let mut register = |name: &str, prelude: Option<&str>, body: Option<CustomAtRuleBodyType>| {
+        options.custom_at_rules.insert(
+          name.into(),
+          CustomAtRuleConfig {
+            prelude: prelude.map(|prelude| SyntaxString::parse_string(prelude).unwrap()),
+            body,
+          },
+        );
+      };
+      register("tailwind", Some("<custom-ident>"), None);
+      register("my-block", None, Some(CustomAtRuleBodyType::DeclarationList));
+      register("tenant", Some("<custom-ident>"), Some(CustomAtRuleBodyType::RuleList));
+      register("mixin", Some("<custom-ident>"), Some(CustomAtRuleBodyType::StyleBlock));
+      register("snippet", None, Some(CustomAtRuleBodyType::Raw));
+      options
+    }
+
+    fn custom_at_rule_test(source: &'static str, expected: &str, minified: &str) {
+      let mut stylesheet = StyleSheet::parse(source, options()).unwrap();
+      let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+      assert_eq!(res.code, expected);
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, minified);
+    }
+
+    custom_at_rule_test("@tailwind base;", "@tailwind base;\n", "@tailwind base;");
+    custom_at_rule_test(
+      "@my-block { color: red; width: 10px }",
+      "@my-block {\n  color: red;\n  width: 10px;\n}\n",
+      "@my-block{color:red;width:10px}",
+    );
+    custom_at_rule_test(
+      "@tenant acme { .foo { color: red } }",
+      "@tenant acme {\n  .foo {\n    color: red;\n  }\n}\n",
+      "@tenant acme{.foo{color:red}}",
+    );
+    custom_at_rule_test(
+      ".foo { @mixin card { color: red; &:hover { width: 10px } } }",
+      ".foo {\n  @mixin card {\n    color: red;\n\n    &:hover {\n      width: 10px;\n    }\n  }\n}\n",
+      ".foo{@mixin card{color:red;&:hover{width:10px}}}",
+    );
+    custom_at_rule_test(
+      "@snippet {foo(bar)}",
+      "@snippet {\n  foo(bar)\n}\n",
+      "@snippet{foo(bar)}",
+    );
+
+    let stylesheet = StyleSheet::parse("@tailwind base; @tenant acme { .foo { color: red } }", options()).unwrap();
+    let rule = match &stylesheet.rules.0[0] {
+      CssRule::CustomAtRule(rule) => rule,
+      _ => unreachable!(),
+    };
+    assert_eq!(rule.name.as_ref(), "tailwind");
+    assert!(matches!(&rule.prelude, Some(ParsedComponent::CustomIdent(ident)) if ident.0 == "base"));
+    assert!(rule.body.is_none());
+    let rule = match &stylesheet.rules.0[1] {
+      CssRule::CustomAtRule(rule) => rule,
+      _ => unreachable!(),
+    };
+    assert!(matches!(&rule.body, Some(CustomAtRuleBody::RuleList(rules)) if rules.0.len() == 1));
+
+    // Preludes and bodies that do not match the registered grammar are invalid.
+    assert!(StyleSheet::parse("@tailwind 12;", options()).is_err());
+    assert!(StyleSheet::parse("@tailwind base components;", options()).is_err());
+    assert!(StyleSheet::parse("@tailwind base { color: red }", options()).is_err());
+    assert!(StyleSheet::parse("@my-block;", options()).is_err());
+    assert!(StyleSheet::parse("@my-block foo { color: red }", options()).is_err());
+
+    // At-rules that are not registered are still parsed as unknown at-rules.
+    let stylesheet = StyleSheet::parse("@unregistered foo;", options()).unwrap();
+    assert!(matches!(stylesheet.rules.0[0], CssRule::Unknown(..)));
+  }
+
+  #[test]
+  fn test_error_recovery() {
+    use std::sync::{Arc, RwLock};
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    test_with_options(
       r#"
-      @media (--print) {
-        .a {
-          color: green;
+      h1(>h1) {
+        color: red;
+      }
+
+      .foo {
+        color: red;
+      }
+
+      .clearfix {
+        *zoom: 1;
+        background: red;
+      }
+
+      @media (hover) {
+        h1(>h1) {
+          color: red;
+        }
+
+        .bar {
+          color: red;
+        }
+      }
+    "#,
+      indoc! { r#"
+      .foo {
+        color: red;
+      }
+
+      .clearfix {
+        background: red;
+      }
+
+      @media (hover) {
+        .bar {
+          color: red;
         }
       }
+    "#},
+      ParserOptions {
+        filename: "test.css".into(),
+        error_recovery: true,
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    );
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![
+        Error {
+          kind: ParserError::SelectorError(SelectorError::EmptySelector),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 1,
+            column: 7
+          })
+        },
+        Error {
+          kind: ParserError::UnexpectedToken(Token::Delim('*')),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 10,
+            column: 9
+          })
+        },
+        Error {
+          kind: ParserError::SelectorError(SelectorError::EmptySelector),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 15,
+            column: 9
+          })
+        },
+      ]
+    )
+  }
 
-      @custom-media --print print;
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
+  #[test]
+  fn test_container_queries() {
+    // with name
+    minify_test(
+      r#"
+      @container my-layout (inline-size > 45em) {
+        .foo {
+          color: red;
         }
       }
-      "#},
+    "#,
+      "@container my-layout (inline-size>45em){.foo{color:red}}",
     );
 
-    custom_media_test(
+    minify_test(
       r#"
-      @custom-media --not-width not (min-width: 300px);
-      @media screen and ((prefers-color-scheme: dark) or (--not-width)) {
-        .foo {
-          order: 6;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media screen and ((prefers-color-scheme: dark) or (not (min-width: 300px))) {
+      @container my-layout ( not (width > 500px) ) {
         .foo {
-          order: 6;
+          color: red;
         }
       }
-      "#},
+    "#,
+      "@container my-layout not (width>500px){.foo{color:red}}",
     );
 
-    fn custom_media_error_test(source: &str, err: Error<MinifyErrorKind>) {
-      let mut stylesheet = StyleSheet::parse(
-        &source,
-        ParserOptions {
-          filename: "test.css".into(),
-          custom_media: true,
-          ..ParserOptions::default()
-        },
-      )
-      .unwrap();
-      let res = stylesheet.minify(MinifyOptions {
-        targets: Some(Browsers {
-          chrome: Some(95 << 16),
-          ..Browsers::default()
-        }),
-        ..MinifyOptions::default()
-      });
-      assert_eq!(res, Err(err))
-    }
-
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --color-print print and (color);
-
-      @media screen and (--color-print) {
-        .a {
-          color: green;
+      @container my-layout not (width > 500px) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container my-layout not (width>500px){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --color-print print and (color);
-
-      @media not print and (--color-print) {
-        .a {
-          color: green;
+      @container not (width > 500px) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
-    );
-
-    custom_media_error_test(
-      r#"
-      @custom-media --color-print print and (color);
-      @custom-media --color-screen screen and (color);
-
-      @media (--color-print) or (--color-screen) {}
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container not (width>500px){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --color-print print and (color);
-      @custom-media --color-screen screen and (color);
-
-      @media (--color-print) and (--color-screen) {}
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+      @container my-layout ((width: 100px) and (not (height: 100px))) {
+        .foo {
+          color: red;
+        }
+      }
+    "#,
+      "@container my-layout (width:100px) and (not (height:100px)){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --screen screen;
-      @custom-media --print print;
-
-      @media (--print) and (--screen) {}
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+      @container my-layout (width = max(10px, 10em)) {
+        .foo {
+          color: red;
+        }
+      }
+    "#,
+      "@container my-layout (width=max(10px,10em)){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    // without name
+    minify_test(
       r#"
-      @custom-media --not-print not print and (color);
-      @custom-media --not-screen not screen and (color);
-
-      @media ((script) or ((--not-print) and (--not-screen))) {
-        .a {
-          color: green;
+      @container (inline-size > 45em) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container (inline-size>45em){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --color screen and (color), print and (color);
-
-      @media (--color) {
-        .a {
-          color: green;
+      @container (inline-size > 45em) and (inline-size < 100em) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container (inline-size>45em) and (inline-size<100em){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    // calc()
+    minify_test(
       r#"
-      @media (--not-defined) {
-        .a {
-          color: green;
+      @container (width > calc(100vw - 50px)) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::CustomMediaNotDefined {
-          name: "--not-defined".into(),
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 1,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container (width>calc(100vw - 50px)){.foo{color:red}}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --circular-mq-a (--circular-mq-b);
-      @custom-media --circular-mq-b (--circular-mq-a);
-
-      @media (--circular-mq-a) {
-        body {
-          order: 3;
+      @container (calc(100vh - 50px) <= height ) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::CircularCustomMedia {
-          name: "--circular-mq-a".into(),
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    "#,
+      "@container (height>=calc(100vh - 50px)){.foo{color:red}}",
     );
-  }
 
-  #[test]
-  fn test_dependencies() {
-    fn dep_test(source: &str, expected: &str, deps: Vec<(&str, &str)>) {
-      let mut stylesheet = StyleSheet::parse(
-        &source,
-        ParserOptions {
-          filename: "test.css".into(),
-          ..ParserOptions::default()
-        },
-      )
-      .unwrap();
-      stylesheet.minify(MinifyOptions::default()).unwrap();
-      let res = stylesheet
-        .to_css(PrinterOptions {
-          analyze_dependencies: Some(Default::default()),
-          minify: true,
-          ..PrinterOptions::default()
-        })
-        .unwrap();
-      assert_eq!(res.code, expected);
-      let dependencies = res.dependencies.unwrap();
-      assert_eq!(dependencies.len(), deps.len());
-      for (i, (url, placeholder)) in deps.into_iter().enumerate() {
-        match &dependencies[i] {
-          Dependency::Url(dep) => {
-            assert_eq!(dep.url, url);
-            assert_eq!(dep.placeholder, placeholder);
-          }
-          Dependency::Import(dep) => {
-            assert_eq!(dep.url, url);
-            assert_eq!(dep.placeholder, placeholder);
-          }
+    // merge adjacent
+    minify_test(
+      r#"
+      @container my-layout (inline-size > 45em) {
+        .foo {
+          color: red;
         }
       }
-    }
 
-    fn dep_error_test(source: &str, error: PrinterErrorKind) {
-      let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-      let res = stylesheet.to_css(PrinterOptions {
-        analyze_dependencies: Some(Default::default()),
-        ..PrinterOptions::default()
-      });
-      match res {
-        Err(e) => assert_eq!(e.kind, error),
-        _ => unreachable!(),
-      }
-    }
+      @container my-layout (inline-size > 45em) {
+        .foo {
+          background: yellow;
+        }
 
-    dep_test(
-      ".foo { background: image-set('./img12x.png', './img21x.png' 2x)}",
-      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
-      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+        .bar {
+          color: white;
+        }
+      }
+    "#,
+      "@container my-layout (inline-size>45em){.foo{color:red;background:#ff0}.bar{color:#fff}}",
     );
 
-    dep_test(
-      ".foo { background: image-set(url(./img12x.png), url('./img21x.png') 2x)}",
-      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
-      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+    minify_test(
+      r#"
+    .foo {
+      container-name: foo bar;
+      container-type: size;
+    }
+    "#,
+      ".foo{container:foo bar/size}",
     );
-
-    dep_test(
-      ".foo { --test: url(/foo.png) }",
-      ".foo{--test:url(\"lDnnrG\")}",
-      vec![("/foo.png", "lDnnrG")],
+    minify_test(
+      r#"
+    .foo {
+      container-name: foo bar;
+      container-type: normal;
+    }
+    "#,
+      ".foo{container:foo bar}",
     );
-
-    dep_test(
-      ".foo { --test: url(\"/foo.png\") }",
-      ".foo{--test:url(\"lDnnrG\")}",
-      vec![("/foo.png", "lDnnrG")],
+    minify_test(
+      ".foo{ container-type: inline-size }",
+      ".foo{container-type:inline-size}",
+    );
+    minify_test(".foo{ container-name: none; }", ".foo{container-name:none}");
+    minify_test(".foo{ container-name: foo; }", ".foo{container-name:foo}");
+    minify_test(".foo{ container: foo / normal; }", ".foo{container:foo}");
+    minify_test(
+      ".foo{ container: foo / inline-size; }",
+      ".foo{container:foo/inline-size}",
     );
+    minify_test(".foo { width: calc(1cqw + 2cqw) }", ".foo{width:3cqw}");
+    minify_test(".foo { width: calc(1cqh + 2cqh) }", ".foo{width:3cqh}");
+    minify_test(".foo { width: calc(1cqi + 2cqi) }", ".foo{width:3cqi}");
+    minify_test(".foo { width: calc(1cqb + 2cqb) }", ".foo{width:3cqb}");
+    minify_test(".foo { width: calc(1cqmin + 2cqmin) }", ".foo{width:3cqmin}");
+    minify_test(".foo { width: calc(1cqmax + 2cqmax) }", ".foo{width:3cqmax}");
 
-    dep_test(
-      ".foo { --test: url(\"http://example.com/foo.png\") }",
-      ".foo{--test:url(\"3X1zSW\")}",
-      vec![("http://example.com/foo.png", "3X1zSW")],
+    // Unlike in @media, there is no need to convert the range syntax in @container,
+    // because browsers all support this syntax.
+    prefix_test(
+      r#"
+      @container (width > 100px) {
+        .foo { padding: 5px; }
+      }
+      "#,
+      indoc! { r#"
+        @container (width > 100px) {
+          .foo {
+            padding: 5px;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(105 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      @container (min-width: 100px) {
+        .foo { padding: 5px; }
+      }
+      "#,
+      indoc! { r#"
+        @container (min-width: 100px) {
+          .foo {
+            padding: 5px;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(105 << 16),
+        ..Browsers::default()
+      },
     );
 
-    dep_test(
-      ".foo { --test: url(\"data:image/svg+xml;utf8,<svg></svg>\") }",
-      ".foo{--test:url(\"-vl-rG\")}",
-      vec![("data:image/svg+xml;utf8,<svg></svg>", "-vl-rG")],
+    // Disallow 'none', 'not', 'and', 'or' as a `<container-name>`
+    // https://github.com/w3c/csswg-drafts/issues/7203#issuecomment-1144257312
+    // https://chromium-review.googlesource.com/c/chromium/src/+/3698402
+    error_test(
+      "@container none (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("none".into())),
     );
 
-    dep_test(
-      ".foo { background: url(\"foo.png\") var(--test) }",
-      ".foo{background:url(\"Vwkwkq\") var(--test)}",
-      vec![("foo.png", "Vwkwkq")],
+    error_test(
+      "@container and (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("and".into())),
     );
 
-    dep_error_test(
-      ".foo { --test: url(\"foo.png\") }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    error_test(
+      "@container or (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("or".into())),
     );
 
-    dep_error_test(
-      ".foo { --test: url(foo.png) }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    // Disallow CSS wide keywords as a `<container-name>`
+    error_test(
+      "@container revert-layer (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("revert-layer".into())),
     );
 
-    dep_error_test(
-      ".foo { --test: url(./foo.png) }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty {
-        url: "./foo.png".into(),
-      },
+    error_test(
+      "@container initial (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("initial".into())),
     );
 
-    dep_test(
-      ".foo { behavior: url(#foo) }",
-      ".foo{behavior:url(\"Zn9-2q\")}",
-      vec![("#foo", "Zn9-2q")],
+    // <ident> contains spaces
+    // https://github.com/web-platform-tests/wpt/blob/39f0da08fbbe33d0582a35749b6dbf8bd067a52d/css/css-contain/container-queries/at-container-parsing.html#L160-L178
+    error_test(
+      "@container foo bar (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("bar".into())),
     );
+  }
 
-    dep_test(
-      ".foo { --foo: url(#foo) }",
-      ".foo{--foo:url(\"Zn9-2q\")}",
-      vec![("#foo", "Zn9-2q")],
+  #[test]
+  fn test_container_query_polyfill() {
+    use crate::rules::container::{ContainerQueryManifest, PolyfillContainer, PolyfillContainerQuery};
+
+    let source = r#"
+      .sidebar { container: sidebar / inline-size }
+      .card { container-type: size; color: red; .media { container-name: media } }
+      @container sidebar (width > 400px) {
+        .title, .subtitle::before { font-size: 2em }
+        @media print {
+          .title { color: black }
+        }
+      }
+      @container (orientation: landscape) {
+        .a > .b { display: flex }
+        @container sidebar (width > 400px) {
+          .c { display: none }
+        }
+      }
+    "#;
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let manifest = stylesheet.polyfill_container_queries("data-cq").unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets: Some(Browsers {
+          safari: Some(15 << 16),
+          ..Browsers::default()
+        }),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      r#".sidebar{container:sidebar/inline-size}.card{container-type:size;color:red}.card .media{container-name:media}.title[data-cq~="0"],.subtitle[data-cq~="0"]:before{font-size:2em}@media print{.title[data-cq~="0"]{color:#000}}.a>.b[data-cq~="1"]{display:flex}.c[data-cq~="0"][data-cq~="1"]{display:none}"#
     );
-
-    dep_test(
-      "@import \"test.css\"; .foo { color: red }",
-      "@import \"hHsogW\";.foo{color:red}",
-      vec![("test.css", "hHsogW")],
+    assert_eq!(
+      manifest,
+      ContainerQueryManifest {
+        containers: vec![
+          PolyfillContainer {
+            selector: ".sidebar".into(),
+            name: Some("sidebar".into()),
+            container_type: Some("inline-size".into()),
+          },
+          PolyfillContainer {
+            selector: ".card".into(),
+            name: None,
+            container_type: Some("size".into()),
+          },
+          PolyfillContainer {
+            selector: ".card .media".into(),
+            name: Some("media".into()),
+            container_type: None,
+          },
+        ],
+        queries: vec![
+          PolyfillContainerQuery {
+            id: "0".into(),
+            name: Some("sidebar".into()),
+            condition: "(width > 400px)".into(),
+          },
+          PolyfillContainerQuery {
+            id: "1".into(),
+            name: None,
+            condition: "(orientation: landscape)".into(),
+          },
+        ],
+      }
     );
   }
 
   #[test]
-  fn test_api() {
-    let stylesheet = StyleSheet::parse(".foo:hover { color: red }", ParserOptions::default()).unwrap();
-    match &stylesheet.rules.0[0] {
-      CssRule::Style(s) => {
-        assert_eq!(&s.selectors.to_string(), ".foo:hover");
+  fn test_scope_rule() {
+    minify_test(
+      r#"
+      @scope (.card) to (.content) {
+        img {
+          border: 1px solid black;
+        }
       }
-      _ => unreachable!(),
-    }
-
-    let color = CssColor::parse_string("#f0f").unwrap();
-    assert_eq!(color.to_css_string(PrinterOptions::default()).unwrap(), "#f0f");
-
-    let rule = CssRule::parse_string(".foo { color: red }", ParserOptions::default()).unwrap();
-    assert_eq!(
-      rule.to_css_string(PrinterOptions::default()).unwrap(),
-      indoc! {r#"
-    .foo {
-      color: red;
-    }"#}
+    "#,
+      "@scope(.card) to (.content){img{border:1px solid #000}}",
     );
-
-    let property = Property::parse_string("color".into(), "#f0f", ParserOptions::default()).unwrap();
-    assert_eq!(
-      property.to_css_string(false, PrinterOptions::default()).unwrap(),
-      "color: #f0f"
+    minify_test(
+      "@scope (.a, .b) { :scope { color: red } .c { color: blue } }",
+      "@scope(.a,.b){:scope{color:red}.c{color:#00f}}",
     );
-    assert_eq!(
-      property.to_css_string(true, PrinterOptions::default()).unwrap(),
-      "color: #f0f !important"
+    minify_test("@scope { .c { color: blue } }", "@scope{.c{color:#00f}}");
+    minify_test(
+      "@scope to (.c) { .d { color: blue } }",
+      "@scope to (.c){.d{color:#00f}}",
+    );
+    minify_test("@scope (.a) {}", "");
+    test(
+      "@scope (.a) to (.b) { .c { color: blue } }",
+      indoc! {r#"
+        @scope (.a) to (.b) {
+          .c {
+            color: #00f;
+          }
+        }
+      "#},
     );
+    error_test("@scope (.a) to {}", ParserError::EndOfInput);
 
-    let code = indoc! { r#"
-      .foo {
-        color: green;
+    prefix_test(
+      r#"
+      @scope (.card) {
+        .title { color: red }
+        :scope { padding: 0 }
+        :scope > img { width: 100% }
+        @media (width > 500px) {
+          .title { color: blue }
+        }
       }
+    "#,
+      indoc! {r#"
+        @scope (.card) {
+          .title {
+            color: red;
+          }
 
-      .bar {
-        color: red;
-        background: pink;
-      }
+          :scope {
+            padding: 0;
+          }
 
-      @media print {
-        .baz {
-          color: green;
+          :scope > img {
+            width: 100%;
+          }
+
+          @media (width > 500px) {
+            .title {
+              color: #00f;
+            }
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      @scope (.card) {
+        .title { color: red }
+        :scope { padding: 0 }
+        :scope > img { width: 100% }
+        @media (min-width: 500px) {
+          .title { color: blue }
         }
       }
-    "#};
-    let stylesheet = StyleSheet::parse(code, ParserOptions::default()).unwrap();
-    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
-      let (key, val) = style.property_location(code, 0).unwrap();
-      assert_eq!(
-        key,
-        SourceLocation { line: 5, column: 3 }..SourceLocation { line: 5, column: 8 }
-      );
-      assert_eq!(
-        val,
-        SourceLocation { line: 5, column: 10 }..SourceLocation { line: 5, column: 13 }
-      );
-    }
+    "#,
+      indoc! {r#"
+        :where(.card) .title {
+          color: red;
+        }
 
-    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
-      let (key, val) = style.property_location(code, 1).unwrap();
-      assert_eq!(
-        key,
-        SourceLocation { line: 6, column: 3 }..SourceLocation { line: 6, column: 13 }
-      );
-      assert_eq!(
-        val,
-        SourceLocation { line: 6, column: 15 }..SourceLocation { line: 6, column: 19 }
-      );
-    }
-    if let CssRule::Media(media) = &stylesheet.rules.0[2] {
-      if let CssRule::Style(style) = &media.rules.0[0] {
-        let (key, val) = style.property_location(code, 0).unwrap();
-        assert_eq!(
-          key,
-          SourceLocation { line: 11, column: 5 }..SourceLocation { line: 11, column: 10 }
-        );
-        assert_eq!(
-          val,
-          SourceLocation { line: 11, column: 12 }..SourceLocation { line: 11, column: 17 }
-        );
-      }
-    }
+        :where(.card) {
+          padding: 0;
+        }
 
-    let mut property = Property::Transform(Default::default(), VendorPrefix::WebKit);
-    property.set_prefix(VendorPrefix::None);
-    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::None));
-    property.set_prefix(VendorPrefix::Moz);
-    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::Moz));
-    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz);
-    assert_eq!(
-      property,
-      Property::Transform(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
-    );
+        :where(.card) > img {
+          width: 100%;
+        }
 
-    let mut property = Property::TextDecorationLine(Default::default(), VendorPrefix::None);
-    property.set_prefix(VendorPrefix::Ms);
-    assert_eq!(
-      property,
-      Property::TextDecorationLine(Default::default(), VendorPrefix::None)
+        @media (min-width: 500px) {
+          :where(.card) .title {
+            color: #00f;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(100 << 16),
+        ..Browsers::default()
+      },
     );
-    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz | VendorPrefix::Ms);
-    assert_eq!(
-      property,
-      Property::TextDecorationLine(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
+    prefix_test(
+      r#"
+      @scope (.a, .b) {
+        @scope (.c) {
+          .d { color: red }
+        }
+      }
+    "#,
+      indoc! {r#"
+        :where(:where(.a, .b) .c) .d {
+          color: red;
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
+    );
+    // Without an explicit scoping root, the rule cannot be downleveled.
+    prefix_test(
+      "@scope { .d { color: red } }",
+      indoc! {r#"
+        @scope {
+          .d {
+            color: red;
+          }
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
 
-    let mut property = Property::AccentColor(Default::default());
-    property.set_prefix(VendorPrefix::WebKit);
-    assert_eq!(property, Property::AccentColor(Default::default()));
-  }
-
-  #[cfg(feature = "substitute_variables")]
-  #[test]
-  fn test_substitute_vars() {
-    use crate::properties::custom::TokenList;
-    use crate::traits::ParseWithOptions;
-
-    fn test(property: Property, vars: HashMap<&str, &str>, expected: &str) {
-      if let Property::Unparsed(unparsed) = property {
-        let vars = vars
-          .into_iter()
-          .map(|(k, v)| {
-            (
-              k,
-              TokenList::parse_string_with_options(v, ParserOptions::default()).unwrap(),
-            )
-          })
-          .collect();
-        let substituted = unparsed.substitute_variables(&vars).unwrap();
-        assert_eq!(
-          substituted.to_css_string(false, PrinterOptions::default()).unwrap(),
-          expected
-        );
-      } else {
-        panic!("Not an unparsed property");
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      @scope (.card) to (.content) {
+        img { width: 100% }
       }
-    }
-
-    let property = Property::parse_string("color".into(), "var(--test)", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--test", "yellow")]), "color: #ff0");
-
-    let property =
-      Property::parse_string("color".into(), "var(--test, var(--foo))", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--foo", "yellow")]), "color: #ff0");
-    let property = Property::parse_string(
-      "color".into(),
-      "var(--test, var(--foo, yellow))",
-      ParserOptions::default(),
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
     )
     .unwrap();
-    test(property, HashMap::new(), "color: #ff0");
-
-    let property =
-      Property::parse_string("width".into(), "calc(var(--a) + var(--b))", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--a", "2px"), ("--b", "4px")]), "width: 6px");
-
-    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
-    test(
-      property,
-      HashMap::from([("--a", "var(--b)"), ("--b", "yellow")]),
-      "color: #ff0",
-    );
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          firefox: Some(120 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ":where(.card) img{width:100%}");
 
-    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
-    test(
-      property,
-      HashMap::from([("--a", "var(--b)"), ("--b", "var(--c)"), ("--c", "var(--a)")]),
-      "color: var(--a)",
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![Error {
+        kind: ParserError::UnsupportedScopeLimit,
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 1,
+          column: 7,
+        }),
+      }]
     );
   }
 
   #[test]
-  fn test_layer() {
-    minify_test("@layer foo;", "@layer foo;");
-    minify_test("@layer foo, bar;", "@layer foo,bar;");
-    minify_test("@layer foo.bar;", "@layer foo.bar;");
-    minify_test("@layer foo.bar, baz;", "@layer foo.bar,baz;");
-
+  fn test_starting_style() {
     minify_test(
+      "@starting-style { .foo { opacity: 0 } }",
+      "@starting-style{.foo{opacity:0}}",
+    );
+    minify_test("@starting-style { }", "");
+    nesting_test_no_targets(
       r#"
-      @layer foo {
-        .bar {
-          color: red;
+      .foo {
+        opacity: 1;
+        transition: opacity 1s, display 1s allow-discrete;
+        @starting-style {
+          opacity: 0;
         }
       }
     "#,
-      "@layer foo{.bar{color:red}}",
-    );
-    minify_test(
-      r#"
-      @layer foo.bar {
-        .bar {
-          color: red;
+      indoc! {r#"
+      .foo {
+        opacity: 1;
+        transition: opacity 1s, display 1s allow-discrete;
+
+        @starting-style {
+          opacity: 0;
         }
       }
-    "#,
-      "@layer foo.bar{.bar{color:red}}",
+    "#},
     );
-    minify_test(r#"
-      @layer base {
-        p { max-width: 70ch; }
+
+    use std::sync::{Arc, RwLock};
+    let source = r#"
+      @starting-style {
+        dialog[open] { opacity: 0 }
       }
+      dialog { transition: display 1s allow-discrete }
+    "#;
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      source,
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Some(Browsers {
+          safari: Some(16 << 16),
+          ..Browsers::default()
+        }),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "@starting-style{dialog[open]{opacity:0}}dialog{transition:display 1s;transition-behavior:allow-discrete}"
+    );
 
-      @layer framework {
-        @layer base {
-          p { margin-block: 0.75em; }
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![
+        Error {
+          kind: ParserError::UnsupportedStartingStyle,
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 1,
+            column: 7,
+          }),
+        },
+        Error {
+          kind: ParserError::UnsupportedAllowDiscrete,
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 4,
+            column: 7,
+          }),
         }
+      ]
+    );
+  }
 
-        @layer theme {
-          p { color: #222; }
-        }
-      }
-    "#, "@layer base{p{max-width:70ch}}@layer framework{@layer base{p{margin-block:.75em}}@layer theme{p{color:#222}}}");
+  #[test]
+  fn test_position_try() {
+    minify_test(
+      "@position-try --foo { top: anchor(--bar bottom); left: anchor(--bar left) }",
+      "@position-try --foo{top:anchor(--bar bottom);left:anchor(--bar left)}",
+    );
+    minify_test(
+      "@position-try --foo { margin-top: 10px; margin-right: 10px; margin-bottom: 10px; margin-left: 10px }",
+      "@position-try --foo{margin:10px}",
+    );
+    minify_test(
+      "@position-try --foo { position-area: top span-all; width: anchor-size(width); align-self: center }",
+      "@position-try --foo{position-area:top;width:anchor-size(width);align-self:center}",
+    );
+    minify_test(
+      "@position-try --foo { top: 0; color: red; --bar: 1px; width: 10px !important; -webkit-align-self: center }",
+      "@position-try --foo{top:0}",
+    );
+    minify_test("@position-try --foo { }", "@position-try --foo{}");
     minify_test(
+      "@media (min-width: 100px) { @position-try --foo { top: 0 } }",
+      "@media (min-width:100px){@position-try --foo{top:0}}",
+    );
+    test(
       r#"
-      @layer {
-        .bar {
-          color: red;
-        }
+      @position-try --foo {
+        inset-inline-start: anchor(--bar end);
+        margin-block: 5px 5px;
       }
     "#,
-      "@layer{.bar{color:red}}",
+      indoc! {r#"
+      @position-try --foo {
+        margin-block: 5px;
+        inset-inline-start: anchor(--bar end);
+      }
+    "#},
     );
-    minify_test(
+    prefix_test(
       r#"
-      @layer foo\20 bar, baz;
+      @position-try --foo {
+        inset-inline-start: anchor(--bar end);
+      }
     "#,
-      "@layer foo\\ bar,baz;",
+      indoc! {r#"
+      @position-try --foo {
+        inset-inline-start: anchor(--bar end);
+      }
+    "#},
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
+
+    css_modules_test(
       r#"
-      @layer one.two\20 three\#four\.five {
-        .bar {
-          color: red;
-        }
+      @position-try --foo {
+        top: anchor(--bar bottom);
+      }
+
+      .foo {
+        position-try-fallbacks: --foo;
       }
     "#,
-      "@layer one.two\\ three\\#four\\.five{.bar{color:red}}",
+      indoc! {r#"
+      @position-try --EgL3uq_foo {
+        top: anchor(--EgL3uq_bar bottom);
+      }
+
+      .EgL3uq_foo {
+        position-try-fallbacks: --EgL3uq_foo;
+      }
+    "#},
+      map! {
+        "--foo" => "--EgL3uq_foo",
+        "--bar" => "--EgL3uq_bar",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        dashed_idents: true,
+        ..Default::default()
+      },
     );
 
-    error_test("@layer;", ParserError::UnexpectedToken(Token::Semicolon));
-    error_test("@layer foo, bar {};", ParserError::AtRuleBodyInvalid);
-    minify_test("@import 'test.css' layer;", "@import \"test.css\" layer;");
-    minify_test("@import 'test.css' layer(foo);", "@import \"test.css\" layer(foo);");
+    use std::sync::{Arc, RwLock};
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    StyleSheet::parse(
+      "@position-try --foo { color: red; top: 0 }",
+      ParserOptions {
+        filename: "test.css".into(),
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![Error {
+        kind: ParserError::InvalidDeclaration,
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 0,
+          column: 29,
+        }),
+      }]
+    );
+  }
+
+  #[test]
+  fn test_unknown_at_rules() {
+    minify_test("@foo;", "@foo;");
+    minify_test("@foo bar;", "@foo bar;");
+    minify_test("@foo (bar: baz);", "@foo (bar: baz);");
+    test(
+      r#"@foo test {
+      div {
+        color: red;
+      }
+    }"#,
+      indoc! {r#"
+      @foo test {
+        div { color: red; }
+      }
+      "#},
+    );
     minify_test(
-      "@import 'test.css' layer(foo.bar);",
-      "@import \"test.css\" layer(foo.bar);",
+      r#"@foo test {
+      div {
+        color: red;
+      }
+    }"#,
+      "@foo test{div { color: red; }}",
     );
     minify_test(
-      "@import 'test.css' layer(foo\\20 bar);",
-      "@import \"test.css\" layer(foo\\ bar);",
+      r#"@foo test {
+        foo: bar;
+      }"#,
+      "@foo test{foo: bar;}",
     );
-    error_test(
-      "@import 'test.css' layer(foo, bar) {};",
-      ParserError::UnexpectedToken(Token::Comma),
+    test(
+      r#"@foo {
+        foo: bar;
+      }"#,
+      indoc! {r#"
+      @foo {
+        foo: bar;
+      }
+      "#},
     );
     minify_test(
+      r#"@foo {
+        foo: bar;
+      }"#,
+      "@foo{foo: bar;}",
+    );
+  }
+
+  #[test]
+  fn test_resolution() {
+    prefix_test(
+      r#"
+      @media (resolution: 1dppx) {
+        body {
+          background: red;
+        }
+      }
+      "#,
+      indoc! { r#"
+      @media (resolution: 1dppx) {
+        body {
+          background: red;
+        }
+      }
+      "#},
+      Browsers {
+        chrome: Some(50 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
       r#"
-      @layer one {
+      @media (resolution: 1dppx) {
         body {
           background: red;
         }
       }
-
-      body {
-        background: red;
-      }
-      
-      @layer two {
-        body {
-          background: green;
-        }
-      }
-      
-      @layer one {
+      "#,
+      indoc! { r#"
+      @media (resolution: 1x) {
         body {
-          background: yellow;
+          background: red;
         }
       }
-      "#,
-      "@layer one{body{background:#ff0}}body{background:red}@layer two{body{background:green}}",
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
     );
   }
 
   #[test]
-  fn test_property() {
+  fn test_environment() {
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: yellow;
+      @media (max-width: env(--branding-small)) {
+        body {
+          padding: env(--branding-padding);
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<color>\";inherits:false;initial-value:#ff0}",
+      "@media (max-width:env(--branding-small)){body{padding:env(--branding-padding)}}",
     );
 
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<length>';
-        inherits: true;
-        initial-value: 25px;
+      @media (max-width: env(--branding-small 1)) {
+        body {
+          padding: env(--branding-padding 2);
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<length>\";inherits:true;initial-value:25px}",
+      "@media (max-width:env(--branding-small 1)){body{padding:env(--branding-padding 2)}}",
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: 25px;
+      @media (max-width: env(--branding-small 1, 20px)) {
+        body {
+          padding: env(--branding-padding 2, 20px);
+        }
       }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Dimension {
-        has_sign: false,
-        value: 25.0,
-        int_value: Some(25),
-        unit: "px".into(),
-      }),
+      "@media (max-width:env(--branding-small 1,20px)){body{padding:env(--branding-padding 2,20px)}}",
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<length>';
-        inherits: false;
-        initial-value: var(--some-value);
+      @media (max-width: env(safe-area-inset-top)) {
+        body {
+          padding: env(safe-area-inset-top);
+        }
       }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("var".into())),
+      "@media (max-width:env(safe-area-inset-top)){body{padding:env(safe-area-inset-top)}}",
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
+      @media (max-width: env(unknown)) {
+        body {
+          padding: env(unknown);
+        }
       }
     "#,
-      ParserError::AtRuleBodyInvalid,
+      "@media (max-width:env(unknown)){body{padding:env(unknown)}}",
     );
 
-    minify_test(
+    prefix_test(
       r#"
-      @property --property-name {
-        syntax: '*';
-        inherits: false;
+      .foo {
+        color: env(--brand-color, color(display-p3 0 1 0));
       }
     "#,
-      "@property --property-name{syntax:\"*\";inherits:false}",
-    );
+      indoc! {r#"
+      .foo {
+        color: env(--brand-color, #00f942);
+      }
 
-    error_test(
-      r#"
-      @property --property-name {
-        syntax: '*';
+      @supports (color: color(display-p3 0 0 0)) {
+        .foo {
+          color: env(--brand-color, color(display-p3 0 1 0));
+        }
       }
-    "#,
-      ParserError::AtRuleBodyInvalid,
+    "#},
+      Browsers {
+        safari: Some(15 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    error_test(
+    css_modules_test(
       r#"
-      @property --property-name {
-        inherits: false;
+      @media (max-width: env(--branding-small)) {
+        .foo {
+          color: env(--brand-color);
+        }
       }
     "#,
-      ParserError::AtRuleBodyInvalid,
+      indoc! {r#"
+      @media (max-width: env(--EgL3uq_branding-small)) {
+        .EgL3uq_foo {
+          color: env(--EgL3uq_brand-color);
+        }
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "--brand-color" => "--EgL3uq_brand-color" referenced: true,
+        "--branding-small" => "--EgL3uq_branding-small" referenced: true
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        dashed_idents: true,
+        ..Default::default()
+      },
     );
+  }
 
-    error_test(
-      r#"
-      @property property-name {
-        syntax: '*';
-        inherits: false;
-      }
-    "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("property-name".into())),
+  #[test]
+  fn test_substitute_environment_variables() {
+    fn env_test(source: &str, vars: &[(&str, &str)], expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          environment_variables: vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    env_test(
+      ".foo { padding-top: env(safe-area-inset-top); padding-bottom: env(safe-area-inset-bottom, 8px) }",
+      &[("safe-area-inset-top", "0px")],
+      ".foo{padding-top:0px;padding-bottom:env(safe-area-inset-bottom,8px)}",
     );
+    env_test(
+      ".foo { margin: env(--gutter) calc(env(--gutter) * 2); --header: env(titlebar-area-height, var(--h)) }",
+      &[("--gutter", "4px"), ("titlebar-area-height", "32px")],
+      ".foo{margin:4px calc(4px*2);--header:32px}",
+    );
+    // Fallbacks are searched, and indices are left untouched.
+    env_test(
+      ".foo { width: env(--unknown, env(--size)); height: env(viewport-segment-height 0 0) }",
+      &[("--size", "10px"), ("viewport-segment-height", "100px")],
+      ".foo{width:env(--unknown,10px);height:env(viewport-segment-height 0 0)}",
+    );
+    env_test(
+      "@media (min-width: 100px) { .foo { color: env(--brand) !important } }",
+      &[("--brand", "#ff0000")],
+      "@media (min-width:100px){.foo{color:#ff0000!important}}",
+    );
+  }
 
+  #[test]
+  fn test_attr() {
     minify_test(
-      r#"
-      @property --property-name {
-        syntax: 'custom | <color>';
-        inherits: false;
-        initial-value: yellow;
-      }
-    "#,
-      "@property --property-name{syntax:\"custom|<color>\";inherits:false;initial-value:#ff0}",
+      ".foo { width: attr(data-size px, 1em) }",
+      ".foo{width:attr(data-size px,1em)}",
     );
-
-    minify_test(r#"
-      @property --property-name {
-        syntax: '<transform-list>';
-        inherits: false;
-        initial-value: translate(200px,300px) translate(100px,200px) scale(2);
-      }
-    "#, "@property --property-name{syntax:\"<transform-list>\";inherits:false;initial-value:matrix(2,0,0,2,300,500)}");
-
     minify_test(
-      r#"
-      @property --property-name {
-        syntax: '<time>';
-        inherits: false;
-        initial-value: 1000ms;
-      }
-    "#,
-      "@property --property-name{syntax:\"<time>\";inherits:false;initial-value:1s}",
+      ".foo { width: attr(data-size  type(<length> | <percentage>) , 10px) }",
+      ".foo{width:attr(data-size type(<length>|<percentage>),10px)}",
     );
-
     minify_test(
-      r#"
-      @property --property-name {
-        syntax: '<url>';
-        inherits: false;
-        initial-value: url("foo.png");
-      }
-    "#,
-      "@property --property-name{syntax:\"<url>\";inherits:false;initial-value:url(foo.png)}",
+      ".foo { --x: attr(data-x raw-string) }",
+      ".foo{--x:attr(data-x raw-string)}",
     );
-
     minify_test(
-      r#"
-      @property --property-name {
-        syntax: '<image>';
-        inherits: false;
-        initial-value: linear-gradient(yellow, blue);
-      }
-    "#,
-      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
+      ".foo { opacity: attr(data-opacity number, calc(1/2)) }",
+      ".foo{opacity:attr(data-opacity number,calc(1/2))}",
     );
-
+    minify_test(".foo { width: attr(data-size %) }", ".foo{width:attr(data-size %)}");
     minify_test(
-      r#"
-      @property --property-name {
-        initial-value: linear-gradient(yellow, blue);
-        inherits: false;
-        syntax: '<image>';
-      }
-    "#,
-      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
+      ".foo { width: attr(data-size type(*)) }",
+      ".foo{width:attr(data-size type(*))}",
     );
-
-    test(
-      r#"
-      @property --property-name {
-        syntax: '<length>|none';
-        inherits: false;
-        initial-value: none;
-      }
-    "#,
-      indoc! {r#"
-      @property --property-name {
-        syntax: "<length> | none";
-        inherits: false;
-        initial-value: none;
-      }
-    "#},
+    minify_test(".foo { content: attr(title) }", ".foo{content:attr(title)}");
+    // Unknown units are kept as is.
+    minify_test(
+      ".foo { width: attr(data-size foo, 1em) }",
+      ".foo{width:attr(data-size foo,1em)}",
     );
 
-    minify_test(
-      r#"
-      @property --property-name {
-        syntax: '<color>#';
-        inherits: false;
-        initial-value: yellow, blue;
-      }
-    "#,
-      "@property --property-name{syntax:\"<color>#\";inherits:false;initial-value:#ff0,#00f}",
+    fn attr_fallback_test(source: &str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          targets: Some(Browsers {
+            safari: Some(17 << 16),
+            ..Browsers::default()
+          }),
+          replace_typed_attr: true,
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    attr_fallback_test(
+      ".foo { width: attr(data-size px, 1em); height: calc(attr(data-h type(<length>), 2px) + 1px); content: attr(title) }",
+      ".foo{width:1em;height:calc(2px + 1px);content:attr(title)}",
     );
-    minify_test(
-      r#"
-      @property --property-name {
-        syntax: '<color>+';
-        inherits: false;
-        initial-value: yellow blue;
-      }
-    "#,
-      "@property --property-name{syntax:\"<color>+\";inherits:false;initial-value:#ff0 #00f}",
+    attr_fallback_test(
+      ".foo { width: attr(data-size px); --y: var(--z, attr(data-y number, attr(data-z number, 3))) }",
+      ".foo{width:attr(data-size px);--y:var(--z,3)}",
     );
   }
 
   #[test]
-  fn test_quoting_unquoting_urls() {
-    // Quotes remain double quotes when not minifying
-    test(
-      r#".foo {
-      background-image: url("0123abcd");
-    }"#,
-      r#".foo {
-  background-image: url("0123abcd");
-}
-"#,
+  fn test_scope() {
+    scope_test(".foo {color: red}", ".widget", ".widget .foo{color:red}");
+    scope_test(
+      ".foo, .bar > .baz {color: red}",
+      ".widget",
+      ".widget .foo,.widget .bar>.baz{color:red}",
+    );
+    scope_test(":root {--accent: red}", ".widget", ".widget{--accent:red}");
+    scope_test("html, body {margin: 0}", ".widget", ".widget{margin:0}");
+    scope_test("html body .foo {color: red}", ".widget", ".widget .foo{color:red}");
+    scope_test("body.dark .foo {color: red}", ".widget", ".widget.dark .foo{color:red}");
+    scope_test(":root > .foo {color: red}", ".widget", ".widget>.foo{color:red}");
+    scope_test("html::before {color: red}", ".widget", ".widget:before{color:red}");
+    scope_test(".foo::before {color: red}", ".widget", ".widget .foo:before{color:red}");
+    scope_test(
+      "htmlfoo, .html {color: red}",
+      ".widget",
+      ".widget htmlfoo,.widget .html{color:red}",
+    );
+    scope_test(".foo {color: red}", "#app .widget", "#app .widget .foo{color:red}");
+    scope_test(".foo {color: red}", ".a, .b", ":is(.a,.b) .foo{color:red}");
+    scope_test(
+      "@media (min-width: 500px) { .foo {color: red} }",
+      ".widget",
+      "@media (min-width:500px){.widget .foo{color:red}}",
+    );
+    scope_test(
+      "@supports (display: grid) { @layer base { .foo {color: red} } }",
+      ".widget",
+      "@supports (display:grid){@layer base{.widget .foo{color:red}}}",
+    );
+    scope_test(
+      "@keyframes fade { from {opacity: 0} to {opacity: 1} }",
+      ".widget",
+      "@keyframes fade{0%{opacity:0}to{opacity:1}}",
+    );
+    scope_test(
+      "@font-face { font-family: Foo; src: url(foo.woff) }",
+      ".widget",
+      "@font-face{font-family:Foo;src:url(foo.woff)}",
+    );
+    scope_test(
+      ".foo {color: red} .bar {color: red}",
+      ".widget",
+      ".widget .foo,.widget .bar{color:red}",
     );
+  }
 
-    // Quotes removed when minifying
-    minify_test(
-      r#".foo {
-      background-image: url("0123abcd");
-    }"#,
-      r#".foo{background-image:url(0123abcd)}"#,
+  #[test]
+  fn test_specificity_adjustment() {
+    use SpecificityAdjustment::*;
+    specificity_adjustment_test(".foo {color: red}", Zero, ":where(.foo){color:red}");
+    specificity_adjustment_test(
+      ".foo .bar, #baz > a {color: red}",
+      Zero,
+      ":where(.foo .bar),:where(#baz>a){color:red}",
+    );
+    specificity_adjustment_test(".foo::before {color: red}", Zero, ":where(.foo):before{color:red}");
+    specificity_adjustment_test("::selection {color: red}", Zero, "::selection{color:red}");
+    specificity_adjustment_test("* {color: red}", Zero, "*{color:red}");
+    specificity_adjustment_test(
+      "@media (min-width: 500px) { .foo {color: red} }",
+      Zero,
+      "@media (min-width:500px){:where(.foo){color:red}}",
+    );
+    specificity_adjustment_test(
+      ".foo { & .bar { color: blue } &.baz > .qux { color: red } }",
+      Zero,
+      ":where(.foo){& :where(.bar){color:#00f}&:where(.baz)>:where(.qux){color:red}}",
+    );
+    specificity_adjustment_test(
+      ".foo {color: red}",
+      Raise { ids: 1, classes: 0 },
+      r".foo:not(#\#){color:red}",
+    );
+    specificity_adjustment_test(
+      ".foo .bar::before {color: red}",
+      Raise { ids: 0, classes: 2 },
+      r".foo .bar:not(.\#):not(.\#):before{color:red}",
+    );
+    specificity_adjustment_test(
+      ".foo { & .bar { color: blue } }",
+      Raise { ids: 1, classes: 0 },
+      r".foo:not(#\#){& .bar{color:#00f}}",
     );
+  }
 
-    // Doubles quotes added if not present when not minifying
-    test(
-      r#".foo {
-      background-image: url(0123abcd);
-    }"#,
-      r#".foo {
-  background-image: url("0123abcd");
-}
-"#,
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_lint() {
+    use crate::lint::{
+      LintContext, LintRule, Linter, MaxSpecificity, NamingConvention, NoUndefinedVariables, Severity,
+    };
+    use crate::selector::Specificity;
+    use crate::visit_types;
+    use crate::visitor::{Visit, VisitTypes};
+
+    fn lint_test<'i>(linter: &mut Linter<'i>, source: &'i str, expected: &[&str]) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet.visit(linter).unwrap();
+      let diagnostics: Vec<String> = linter.diagnostics().iter().map(|d| d.to_string()).collect();
+      assert_eq!(diagnostics, expected);
+    }
+
+    lint_test(
+      &mut Linter::recommended(),
+      ".foo {}\n.bar { color: red; color: red; colr: red }\n@foo bar;",
+      &[
+        "warning[no-empty-rules]: Unexpected empty style rule at 1:1",
+        "warning[no-duplicate-properties]: Duplicate declaration of `color` at 2:1",
+        "warning[no-unknown-properties]: Unknown property `colr` at 2:1",
+        "warning[no-unknown-at-rules]: Unknown at-rule `@foo` at 3:1",
+      ],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".foo { color: red; color: rgb(0 0 0 / 50%); --x: 1; --x: 2 }",
+      &[],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".foo { padding-left: 1px; padding: 2px; border-top-width: 1px; border: none; margin: 0; margin-top: 1px }",
+      &[
+        "warning[no-shorthand-property-overrides]: Shorthand property `padding` overrides `padding-left` declared before it at 1:1",
+        "warning[no-shorthand-property-overrides]: Shorthand property `border` overrides `border-top-width` declared before it at 1:1",
+      ],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".foo { color: 1px; width: var(--x); color: inherit; top: anchor(bottom) }",
+      &["warning[no-invalid-values]: Invalid value for `color` at 1:1"],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".a { color: red }\n.b { color: red }\n.a { color: blue }\n@media print { .a { color: green } }",
+      &["warning[no-duplicate-selectors]: Duplicate selector `.a` at 3:1"],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      "#a .b { color: red }\n.b, .c { color: blue }\n.c:hover { color: red }\n@media print { .b { color: green } }",
+      &["warning[no-descending-specificity]: Expected selector `.b` to come before `#a .b` at 2:1"],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      "@media print { .foo {} }",
+      &["warning[no-empty-rules]: Unexpected empty style rule at 1:16"],
+    );
+
+    lint_test(
+      &mut Linter::recommended(),
+      "button { outline: none }\n.a { outline: 0 }\n.b { outline: 2px solid }",
+      &[
+        "warning[no-outline-none]: The focus outline of `button` is removed without an alternative focus style at 1:1",
+        "warning[no-outline-none]: The focus outline of `.a` is removed without an alternative focus style at 2:1",
+      ],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      "button { outline: none }\nbutton:focus-visible { box-shadow: 0 0 0 2px blue }",
+      &[],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".a { font-size: 10px }\n.b { font: x-small sans-serif }\n.c { font-size: 0.5em; font-size: 12px }",
+      &[
+        "warning[min-font-size]: Font size `10px` is smaller than the minimum of 12px at 1:1",
+        "warning[min-font-size]: Font size `x-small` is smaller than the minimum of 12px at 2:1",
+      ],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".a { animation: spin 2s infinite }\n.b { animation: fade 1s }\n.c { animation: slide 3s 2 }",
+      &[
+        "warning[require-reduced-motion]: Animation `spin` is not guarded by a `prefers-reduced-motion` media query at 1:1",
+        "warning[require-reduced-motion]: Animation `slide` is not guarded by a `prefers-reduced-motion` media query at 3:1",
+      ],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".a { animation: spin 2s infinite }\n@media (prefers-reduced-motion: reduce) { .a { animation: none } }",
+      &[],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      "@media (prefers-reduced-motion: no-preference) { .a { animation: spin 2s infinite } }",
+      &[],
+    );
+    lint_test(
+      &mut Linter::recommended(),
+      ".a { color: #777; background: white }\n.b { color: black; background-color: white }\n.c { color: #777; background: url(a.png) white }\n.d { color: #777; background-color: rgb(255 255 255 / 50%) }",
+      &["warning[color-contrast]: Contrast ratio 4.48:1 between `#777` and `#fff` is below the minimum of 4.5:1 at 1:1"],
+    );
+
+    let mut linter = Linter::new();
+    linter.add_rule(MaxSpecificity(Specificity::new(0, 2, 0)));
+    lint_test(
+      &mut linter,
+      ".a.b.c { color: red }\n.a, #b { color: red }\n.a.b span { color: red }",
+      &[
+        "warning[max-specificity]: Selector `.a.b.c` has specificity 0,3,0, which exceeds the maximum of 0,2,0 at 1:1",
+        "warning[max-specificity]: Selector `#b` has specificity 1,0,0, which exceeds the maximum of 0,2,0 at 2:1",
+        "warning[max-specificity]: Selector `.a.b span` has specificity 0,2,1, which exceeds the maximum of 0,2,0 at 3:1",
+      ],
+    );
+
+    let mut linter = Linter::new();
+    linter.add_rule(NoUndefinedVariables::new(["--theme-*"]));
+    lint_test(
+      &mut linter,
+      ".a { color: var(--missing); width: var(--theme-gap); --b: var(--a) }\n.b { top: var(--c, 0); margin: var(--defined) }\n:root { --defined: 1px }",
+      &[
+        "warning[no-undefined-variables]: Custom property `--a` referenced by `--b` is not defined and has no fallback value at 1:1",
+        "warning[no-undefined-variables]: Custom property `--missing` referenced by `color` is not defined and has no fallback value at 1:1",
+      ],
+    );
+
+    let mut linter = Linter::new();
+    linter.add_rule(
+      NamingConvention::new()
+        .class(NamingConvention::BEM)
+        .unwrap()
+        .id(NamingConvention::KEBAB_CASE)
+        .unwrap()
+        .custom_property(NamingConvention::KEBAB_CASE)
+        .unwrap()
+        .keyframes(NamingConvention::KEBAB_CASE)
+        .unwrap(),
     );
+    lint_test(
+      &mut linter,
+      ".card__title--large { color: red }\n.Card .card_title { --brandColor: red; --brand-color: blue }\n#mainNav, :not(.is-Active) {}\n@keyframes fadeIn { from { opacity: 0 } }",
+      &[
+        "warning[naming-convention]: Class name `card_title` does not match the naming convention at 2:1",
+        "warning[naming-convention]: Class name `Card` does not match the naming convention at 2:1",
+        "warning[naming-convention]: Custom property `--brandColor` does not match the naming convention at 2:1",
+        "warning[naming-convention]: Id `mainNav` does not match the naming convention at 3:1",
+        "warning[naming-convention]: Class name `is-Active` does not match the naming convention at 3:1",
+        "warning[naming-convention]: Keyframes name `fadeIn` does not match the naming convention at 4:1",
+      ],
+    );
+    assert!(NamingConvention::new().class("(").is_err());
 
-    // No quotes changed if not present when not minifying
-    minify_test(
-      r#".foo {
-      background-image: url(0123abcd);
-    }"#,
-      r#".foo{background-image:url(0123abcd)}"#,
+    let mut linter = Linter::recommended();
+    assert!(linter.set_severity("no-empty-rules", Severity::Off));
+    assert!(linter.set_severity("no-unknown-properties", Severity::Error));
+    assert!(!linter.set_severity("no-such-rule", Severity::Error));
+    lint_test(
+      &mut linter,
+      ".foo {} .bar { colr: red }",
+      &["error[no-unknown-properties]: Unknown property `colr` at 1:9"],
+    );
+    assert!(linter.has_errors());
+
+    struct NoHttpUrls;
+    impl<'i> LintRule<'i> for NoHttpUrls {
+      fn name(&self) -> &'static str {
+        "no-http-urls"
+      }
+
+      fn visit_types(&self) -> VisitTypes {
+        visit_types!(URLS)
+      }
+
+      fn check_url(&mut self, url: &crate::values::url::Url<'i>, cx: &mut LintContext) {
+        if url.url.starts_with("http:") {
+          cx.report(format!("Insecure url `{}`", url.url));
+        }
+      }
+    }
+
+    let mut linter = Linter::new();
+    linter.add_rule(NoHttpUrls);
+    lint_test(
+      &mut linter,
+      ".foo { background: url(http://example.com/a.png) }\n.bar { background: url(https://example.com/b.png) }",
+      &["warning[no-http-urls]: Insecure url `http://example.com/a.png` at 1:1"],
     );
   }
 
+  #[cfg(feature = "visitor")]
   #[test]
-  fn test_zindex() {
-    minify_test(".foo { z-index: 2 }", ".foo{z-index:2}");
-    minify_test(".foo { z-index: -2 }", ".foo{z-index:-2}");
-    minify_test(".foo { z-index: 999999 }", ".foo{z-index:999999}");
-    minify_test(".foo { z-index: 9999999 }", ".foo{z-index:9999999}");
-    minify_test(".foo { z-index: -9999999 }", ".foo{z-index:-9999999}");
+  fn test_rename_map() {
+    use crate::visitor::{RenameMap, SelectorRenamer, Visit};
+
+    fn rename_test(source: &str, expected: &str) {
+      let mut map = RenameMap::default();
+      map.classes.insert("foo".into(), "a".into());
+      map.classes.insert("bar".into(), "b".into());
+      map.ids.insert("baz".into(), "c".into());
+
+      let mut stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          nesting: true,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.visit(&mut SelectorRenamer::new(&map)).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    rename_test(".foo #baz .qux {color: red}", ".a #c .qux{color:red}");
+    rename_test(
+      ".foo:is(.bar, #baz):not(.foo) {color: red}",
+      ".a:is(.b,#c):not(.a){color:red}",
+    );
+    rename_test(
+      ".foo:where(.bar):has(> #baz) {color: red}",
+      ".a:where(.b):has(>#c){color:red}",
+    );
+    rename_test("#foo.baz {color: red}", "#foo.baz{color:red}");
+    rename_test(".foo { & .bar { color: red } }", ".a{& .b{color:red}}");
+    rename_test(
+      "@media (min-width: 500px) { .foo {color: red} }",
+      "@media (min-width:500px){.a{color:red}}",
+    );
+    rename_test(
+      "@supports selector(.foo > .bar) and (not selector(#baz)) { .foo {color: red} }",
+      "@supports selector(.a > .b) and (not selector(#c)){.a{color:red}}",
+    );
   }
 
+  #[cfg(feature = "visitor")]
   #[test]
-  #[cfg(feature = "sourcemap")]
-  fn test_input_source_map() {
-    let source = r#".imported {
-      content: "yay, file support!";
-    }
+  fn test_visitor_hooks() {
+    use crate::properties::custom::{Token, TokenOrValue};
+    use crate::rules::comment::CommentRule;
+    use crate::rules::custom_at_rule::{CustomAtRule, CustomAtRuleBodyType, CustomAtRuleConfig};
+    use crate::values::ident::CustomIdent;
+    use crate::values::syntax::{ParsedComponent, SyntaxString};
+    use crate::visit_types;
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
 
-    .selector {
-      margin: 1em;
-      background-color: #f60;
-    }
+    struct Hooks;
 
-    .selector .nested {
-      margin: 0.5em;
+    impl<'i> Visitor<'i> for Hooks {
+      type Error = Infallible;
+
+      const TYPES: VisitTypes = visit_types!(COMMENTS | TOKENS | CUSTOM_AT_RULES);
+
+      fn visit_comment(&mut self, comment: &mut CommentRule<'i>) -> Result<(), Self::Error> {
+        comment.text = comment.text.to_uppercase().into();
+        Ok(())
+      }
+
+      fn visit_token(&mut self, token: &mut TokenOrValue<'i>) -> Result<(), Self::Error> {
+        if let TokenOrValue::Token(Token::Ident(name)) = token {
+          if name.as_ref() == "foo" {
+            *name = "bar".into();
+          }
+        }
+        token.visit_children(self)
+      }
+
+      fn visit_custom_at_rule(&mut self, rule: &mut CustomAtRule<'i>) -> Result<(), Self::Error> {
+        if rule.name.as_ref() == "tailwind" {
+          rule.prelude = Some(ParsedComponent::CustomIdent(CustomIdent("utilities".into())));
+        }
+        rule.visit_children(self)
+      }
     }
 
-    /*# sourceMappingURL=data:application/json;base64,ewoJInZlcnNpb24iOiAzLAoJInNvdXJjZVJvb3QiOiAicm9vdCIsCgkiZmlsZSI6ICJzdGRvdXQiLAoJInNvdXJjZXMiOiBbCgkJInN0ZGluIiwKCQkic2Fzcy9fdmFyaWFibGVzLnNjc3MiLAoJCSJzYXNzL19kZW1vLnNjc3MiCgldLAoJInNvdXJjZXNDb250ZW50IjogWwoJCSJAaW1wb3J0IFwiX3ZhcmlhYmxlc1wiO1xuQGltcG9ydCBcIl9kZW1vXCI7XG5cbi5zZWxlY3RvciB7XG4gIG1hcmdpbjogJHNpemU7XG4gIGJhY2tncm91bmQtY29sb3I6ICRicmFuZENvbG9yO1xuXG4gIC5uZXN0ZWQge1xuICAgIG1hcmdpbjogJHNpemUgLyAyO1xuICB9XG59IiwKCQkiJGJyYW5kQ29sb3I6ICNmNjA7XG4kc2l6ZTogMWVtOyIsCgkJIi5pbXBvcnRlZCB7XG4gIGNvbnRlbnQ6IFwieWF5LCBmaWxlIHN1cHBvcnQhXCI7XG59IgoJXSwKCSJtYXBwaW5ncyI6ICJBRUFBLFNBQVMsQ0FBQztFQUNSLE9BQU8sRUFBRSxvQkFBcUI7Q0FDL0I7O0FGQ0QsU0FBUyxDQUFDO0VBQ1IsTUFBTSxFQ0hELEdBQUc7RURJUixnQkFBZ0IsRUNMTCxJQUFJO0NEVWhCOztBQVBELFNBQVMsQ0FJUCxPQUFPLENBQUM7RUFDTixNQUFNLEVDUEgsS0FBRztDRFFQIiwKCSJuYW1lcyI6IFtdCn0= */"#;
+    let mut options = ParserOptions {
+      preserve_comments: true,
+      ..ParserOptions::default()
+    };
+    options.custom_at_rules.insert(
+      "tailwind".into(),
+      CustomAtRuleConfig {
+        prelude: Some(SyntaxString::parse_string("<custom-ident>").unwrap()),
+        body: None,
+      },
+    );
+    options.custom_at_rules.insert(
+      "snippet".into(),
+      CustomAtRuleConfig {
+        prelude: None,
+        body: Some(CustomAtRuleBodyType::Raw),
+      },
+    );
 
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet.minify(MinifyOptions::default()).unwrap();
-    let mut sm = parcel_sourcemap::SourceMap::new("/");
-    stylesheet
+    let mut stylesheet = StyleSheet::parse(
+      "/*! hello */ .a:unknown(foo)::part-x(foo), :is(.b:unknown(foo)) { color: red } @foo foo; @snippet {foo} @tailwind base;",
+      options,
+    )
+    .unwrap();
+    stylesheet.visit(&mut Hooks).unwrap();
+    let res = stylesheet
       .to_css(PrinterOptions {
-        source_map: Some(&mut sm),
         minify: true,
         ..PrinterOptions::default()
       })
       .unwrap();
-    let map = sm.to_json(None).unwrap();
     assert_eq!(
-      map,
-      r#"{"version":3,"sourceRoot":null,"mappings":"AAAA,uCCGA,2CAAA","sources":["sass/_demo.scss","stdin"],"sourcesContent":[".imported {\n  content: \"yay, file support!\";\n}","@import \"_variables\";\n@import \"_demo\";\n\n.selector {\n  margin: $size;\n  background-color: $brandColor;\n\n  .nested {\n    margin: $size / 2;\n  }\n}"],"names":[]}"#
+      res.code,
+      "/*! HELLO */.a:unknown(bar)::part-x(bar),.b:unknown(bar){color:red}@foo bar;@snippet{bar}@tailwind utilities;"
     );
   }
 
+  #[cfg(feature = "async_visitor")]
   #[test]
-  fn test_error_recovery() {
-    use std::sync::{Arc, RwLock};
-    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
-    test_with_options(
-      r#"
-      h1(>h1) {
-        color: red;
-      }
+  fn test_async_visitor() {
+    use crate::async_visitor::{AsyncVisitor, VisitFuture};
+    use crate::values::ident::DashedIdent;
+    use crate::values::url::Url;
+    use crate::visit_types;
+    use crate::visitor::VisitTypes;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+      fn wake(self: Arc<Self>) {}
+    }
 
-      .foo {
-        color: red;
+    fn block_on<F: Future>(future: F) -> F::Output {
+      let waker = Waker::from(Arc::new(NoopWaker));
+      let mut cx = Context::from_waker(&waker);
+      let mut future = Box::pin(future);
+      loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+          return output;
+        }
       }
+    }
 
-      .clearfix {
-        *zoom: 1;
-        background: red;
-      }
+    /// A future that is pending once before it completes, like a request to a service.
+    struct Lookup(bool);
 
-      @media (hover) {
-        h1(>h1) {
-          color: red;
-        }
+    impl Future for Lookup {
+      type Output = ();
 
-        .bar {
-          color: red;
+      fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+          Poll::Ready(())
+        } else {
+          self.0 = true;
+          cx.waker().wake_by_ref();
+          Poll::Pending
         }
       }
-    "#,
-      indoc! { r#"
-      .foo {
-        color: red;
-      }
+    }
 
-      .clearfix {
-        background: red;
-      }
+    struct Resolver {
+      requests: usize,
+    }
 
-      @media (hover) {
-        .bar {
-          color: red;
-        }
-      }
-    "#},
-      ParserOptions {
-        filename: "test.css".into(),
-        error_recovery: true,
-        warnings: warnings.clone(),
-        ..ParserOptions::default()
-      },
-    );
-    let w = warnings.unwrap();
-    let warnings = w.read().unwrap();
-    assert_eq!(
-      *warnings,
-      vec![
-        Error {
-          kind: ParserError::SelectorError(SelectorError::EmptySelector),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 1,
-            column: 7
-          })
-        },
-        Error {
-          kind: ParserError::UnexpectedToken(Token::Delim('*')),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 10,
-            column: 9
-          })
-        },
-        Error {
-          kind: ParserError::SelectorError(SelectorError::EmptySelector),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 15,
-            column: 9
-          })
-        },
-      ]
-    )
-  }
+    impl<'i> AsyncVisitor<'i> for Resolver {
+      type Error = String;
 
-  #[test]
-  fn test_container_queries() {
-    // with name
-    minify_test(
-      r#"
-      @container my-layout (inline-size > 45em) {
-        .foo {
-          color: red;
-        }
+      const TYPES: VisitTypes = visit_types!(URLS | DASHED_IDENTS);
+
+      fn visit_url<'a>(&'a mut self, url: &'a mut Url<'i>) -> VisitFuture<'a, Self::Error> {
+        Box::pin(async move {
+          Lookup(false).await;
+          self.requests += 1;
+          if url.url.as_ref() == "missing.png" {
+            return Err(format!("Unknown asset {}", url.url));
+          }
+          url.url = format!("/assets/{}", url.url).into();
+          Ok(())
+        })
       }
-    "#,
-      "@container my-layout (inline-size>45em){.foo{color:red}}",
-    );
 
-    minify_test(
-      r#"
-      @container my-layout ( not (width > 500px) ) {
-        .foo {
-          color: red;
-        }
+      fn visit_dashed_ident<'a>(&'a mut self, ident: &'a mut DashedIdent<'i>) -> VisitFuture<'a, Self::Error> {
+        Box::pin(async move {
+          Lookup(false).await;
+          self.requests += 1;
+          ident.0 = format!("--theme-{}", &ident.0[2..]).into();
+          Ok(())
+        })
       }
-    "#,
-      "@container my-layout not (width>500px){.foo{color:red}}",
+    }
+
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { background: url(a.png); color: var(--brand) } @media print { .bar { background: url(b.png) } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut resolver = Resolver { requests: 0 };
+    block_on(stylesheet.visit_async(&mut resolver)).unwrap();
+    assert_eq!(resolver.requests, 3);
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".foo{background:url(/assets/a.png);color:var(--theme-brand)}@media print{.bar{background:url(/assets/b.png)}}"
     );
 
-    minify_test(
-      r#"
-      @container my-layout not (width > 500px) {
-        .foo {
-          color: red;
-        }
-      }
-    "#,
-      "@container my-layout not (width>500px){.foo{color:red}}",
+    // Errors stop the traversal, and the style sheet is left unchanged.
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { background: url(missing.png) } .bar { background: url(b.png) }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut resolver = Resolver { requests: 0 };
+    assert_eq!(
+      block_on(stylesheet.visit_async(&mut resolver)),
+      Err("Unknown asset missing.png".into())
     );
+    assert_eq!(resolver.requests, 1);
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{background:url(missing.png)}.bar{background:url(b.png)}");
+  }
 
-    minify_test(
-      r#"
-      @container not (width > 500px) {
-        .foo {
-          color: red;
-        }
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visitor_chain() {
+    use crate::rules::CssRule;
+    use crate::values::length::LengthValue;
+    use crate::values::url::Url;
+    use crate::visit_types;
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct UrlCollector(Vec<String>);
+
+    impl<'i> Visitor<'i> for UrlCollector {
+      type Error = Infallible;
+
+      const TYPES: VisitTypes = visit_types!(URLS);
+
+      fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+        self.0.push(url.url.to_string());
+        url.url = format!("/assets/{}", url.url).into();
+        Ok(())
       }
-    "#,
-      "@container not (width>500px){.foo{color:red}}",
-    );
+    }
 
-    minify_test(
-      r#"
-      @container my-layout ((width: 100px) and (not (height: 100px))) {
-        .foo {
-          color: red;
+    struct PxToRem;
+
+    impl<'i> Visitor<'i> for PxToRem {
+      type Error = Infallible;
+
+      const TYPES: VisitTypes = visit_types!(LENGTHS);
+
+      fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
+        if let LengthValue::Px(px) = length {
+          *length = LengthValue::Rem(*px / 16.0);
         }
+        Ok(())
       }
-    "#,
-      "@container my-layout (width:100px) and (not (height:100px)){.foo{color:red}}",
-    );
+    }
 
-    minify_test(
-      r#"
-      @container my-layout (width = max(10px, 10em)) {
-        .foo {
-          color: red;
-        }
+    struct RuleCounter {
+      rules: usize,
+      urls: usize,
+    }
+
+    impl<'i> Visitor<'i> for RuleCounter {
+      type Error = Infallible;
+
+      const TYPES: VisitTypes = visit_types!(RULES | URLS);
+
+      fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+        self.rules += 1;
+        rule.visit_children(self)
       }
-    "#,
-      "@container my-layout (width=max(10px,10em)){.foo{color:red}}",
-    );
 
-    // without name
-    minify_test(
-      r#"
-      @container (inline-size > 45em) {
-        .foo {
-          color: red;
-        }
+      fn visit_url(&mut self, _: &mut Url<'i>) -> Result<(), Self::Error> {
+        self.urls += 1;
+        Ok(())
       }
-    "#,
-      "@container (inline-size>45em){.foo{color:red}}",
+    }
+
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { background: url(a.png); width: 32px } @media print { .bar { background: url(b.png) } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut chain = UrlCollector(Vec::new()).chain(PxToRem).chain(RuleCounter { rules: 0, urls: 0 });
+    assert_eq!(chain.visit_types(), visit_types!(URLS | LENGTHS | RULES));
+    stylesheet.visit(&mut chain).unwrap();
+
+    let (first, counter) = chain.into_inner();
+    let (urls, _) = first.into_inner();
+    assert_eq!(urls.0, vec!["a.png".to_string(), "b.png".to_string()]);
+    assert_eq!(counter.rules, 3);
+    assert_eq!(counter.urls, 2);
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".foo{background:url(/assets/a.png);width:2rem}@media print{.bar{background:url(/assets/b.png)}}"
     );
+  }
 
-    minify_test(
-      r#"
-      @container (inline-size > 45em) and (inline-size < 100em) {
-        .foo {
-          color: red;
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_visit_parallel() {
+    use crate::values::length::LengthValue;
+    use crate::values::url::Url;
+    use crate::visit_types;
+    use crate::visitor::{ParallelVisitor, VisitTypes, Visitor};
+
+    struct Transformer {
+      urls: Vec<String>,
+    }
+
+    impl<'i> Visitor<'i> for Transformer {
+      type Error = String;
+
+      const TYPES: VisitTypes = visit_types!(URLS | LENGTHS);
+
+      fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+        if url.url.as_ref() == "missing.png" {
+          return Err(format!("Unknown asset {}", url.url));
         }
+        self.urls.push(url.url.to_string());
+        url.url = format!("/assets/{}", url.url).into();
+        Ok(())
       }
-    "#,
-      "@container (inline-size>45em) and (inline-size<100em){.foo{color:red}}",
-    );
 
-    // calc()
-    minify_test(
-      r#"
-      @container (width > calc(100vw - 50px)) {
-        .foo {
-          color: red;
+      fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
+        if let LengthValue::Px(px) = length {
+          *length = LengthValue::Rem(*px / 16.0);
         }
+        Ok(())
       }
-    "#,
-      "@container (width>calc(100vw - 50px)){.foo{color:red}}",
-    );
+    }
 
-    minify_test(
-      r#"
-      @container (calc(100vh - 50px) <= height ) {
-        .foo {
-          color: red;
-        }
+    impl<'i> ParallelVisitor<'i> for Transformer {
+      fn fork(&self) -> Self {
+        Transformer { urls: Vec::new() }
       }
-    "#,
-      "@container (height>=calc(100vh - 50px)){.foo{color:red}}",
-    );
 
-    // merge adjacent
-    minify_test(
-      r#"
-      @container my-layout (inline-size > 45em) {
-        .foo {
-          color: red;
-        }
+      fn merge(&mut self, other: Self) {
+        self.urls.extend(other.urls);
       }
+    }
 
-      @container my-layout (inline-size > 45em) {
-        .foo {
-          background: yellow;
-        }
+    let mut source = String::new();
+    for i in 0..100 {
+      source.push_str(&format!(".a{} {{ background: url({}.png); width: 32px }}\n", i, i));
+    }
+    source.push_str("@media print { .b { background: url(print.png) } }");
 
-        .bar {
-          color: white;
-        }
-      }
-    "#,
-      "@container my-layout (inline-size>45em){.foo{color:red;background:#ff0}.bar{color:#fff}}",
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    let mut transformer = Transformer { urls: Vec::new() };
+    stylesheet.visit_parallel(&mut transformer).unwrap();
+
+    let mut expected_urls: Vec<String> = (0..100).map(|i| format!("{}.png", i)).collect();
+    expected_urls.push("print.png".into());
+    assert_eq!(transformer.urls, expected_urls);
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert!(res.code.starts_with(".a0{background:url(/assets/0.png);width:2rem}.a1{"));
+    assert!(res.code.ends_with("@media print{.b{background:url(/assets/print.png)}}"));
+
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { background: url(a.png) } .bar { background: url(missing.png) }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut transformer = Transformer { urls: Vec::new() };
+    assert_eq!(
+      stylesheet.visit_parallel(&mut transformer),
+      Err("Unknown asset missing.png".into())
     );
+  }
 
-    minify_test(
-      r#"
-    .foo {
-      container-name: foo bar;
-      container-type: size;
+  #[test]
+  fn test_cssom() {
+    use crate::cssom::{GroupingRule, RuleListError};
+    use crate::rules::CssRule;
+
+    fn rule(source: &str) -> CssRule<'_> {
+      CssRule::parse_string(source, ParserOptions::default()).unwrap()
     }
-    "#,
-      ".foo{container:foo bar/size}",
-    );
-    minify_test(
-      r#"
-    .foo {
-      container-name: foo bar;
-      container-type: normal;
+
+    fn print(stylesheet: &StyleSheet) -> String {
+      stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap()
+        .code
     }
-    "#,
-      ".foo{container:foo bar}",
+
+    let mut stylesheet = StyleSheet::parse(
+      "@import 'a.css'; .foo { color: red } @media print { .bar { color: red } @supports (display: grid) { .baz { color: red } } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(stylesheet.rules.len(), 3);
+    assert!(matches!(stylesheet.rule(&[0]), Some(CssRule::Import(..))));
+    assert!(matches!(stylesheet.rule(&[2, 1]), Some(CssRule::Supports(..))));
+    assert!(matches!(stylesheet.rule(&[2, 1, 0]), Some(CssRule::Style(..))));
+    assert!(stylesheet.rule(&[2, 2]).is_none());
+    assert!(stylesheet.rule(&[0, 0]).is_none());
+    assert!(stylesheet.rule(&[]).is_none());
+
+    assert_eq!(stylesheet.insert_rule(3, rule(".qux { color: red }")), Ok(3));
+    assert_eq!(stylesheet.insert_rule(0, rule("@layer base;")), Ok(0));
+    assert_eq!(
+      stylesheet.insert_rule(2, rule("@namespace svg url(http://www.w3.org/2000/svg);")),
+      Ok(2)
     );
-    minify_test(
-      ".foo{ container-type: inline-size }",
-      ".foo{container-type:inline-size}",
+    assert_eq!(
+      stylesheet.insert_rule(9, rule(".qux { color: red }")),
+      Err(RuleListError::IndexOutOfBounds { index: 9, len: 6 })
     );
-    minify_test(".foo{ container-name: none; }", ".foo{container-name:none}");
-    minify_test(".foo{ container-name: foo; }", ".foo{container-name:foo}");
-    minify_test(".foo{ container: foo / normal; }", ".foo{container:foo}");
-    minify_test(
-      ".foo{ container: foo / inline-size; }",
-      ".foo{container:foo/inline-size}",
+    assert_eq!(
+      stylesheet.insert_rule(3, rule("@import 'b.css';")),
+      Err(RuleListError::InvalidPosition)
+    );
+    assert_eq!(
+      stylesheet.insert_rule(1, rule(".qux { color: red }")),
+      Err(RuleListError::InvalidPosition)
+    );
+    assert_eq!(
+      stylesheet.insert_rule(1, rule("@namespace html url(http://www.w3.org/1999/xhtml);")),
+      Err(RuleListError::InvalidPosition)
     );
-    minify_test(".foo { width: calc(1cqw + 2cqw) }", ".foo{width:3cqw}");
-    minify_test(".foo { width: calc(1cqh + 2cqh) }", ".foo{width:3cqh}");
-    minify_test(".foo { width: calc(1cqi + 2cqi) }", ".foo{width:3cqi}");
-    minify_test(".foo { width: calc(1cqb + 2cqb) }", ".foo{width:3cqb}");
-    minify_test(".foo { width: calc(1cqmin + 2cqmin) }", ".foo{width:3cqmin}");
-    minify_test(".foo { width: calc(1cqmax + 2cqmax) }", ".foo{width:3cqmax}");
 
-    // Unlike in @media, there is no need to convert the range syntax in @container,
-    // because browsers all support this syntax.
-    prefix_test(
-      r#"
-      @container (width > 100px) {
-        .foo { padding: 5px; }
-      }
-      "#,
-      indoc! { r#"
-        @container (width > 100px) {
-          .foo {
-            padding: 5px;
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(105 << 16),
-        ..Browsers::default()
-      },
+    let deleted = stylesheet.delete_rule(5).unwrap();
+    assert!(matches!(deleted, CssRule::Style(..)));
+    assert_eq!(
+      stylesheet.delete_rule(5),
+      Err(RuleListError::IndexOutOfBounds { index: 5, len: 5 })
     );
-    prefix_test(
-      r#"
-      @container (min-width: 100px) {
-        .foo { padding: 5px; }
-      }
-      "#,
-      indoc! { r#"
-        @container (min-width: 100px) {
-          .foo {
-            padding: 5px;
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(105 << 16),
-        ..Browsers::default()
-      },
+    assert!(matches!(
+      stylesheet.replace_rule(3, rule(".foo { color: green }")),
+      Ok(CssRule::Style(..))
+    ));
+    assert_eq!(
+      stylesheet.replace_rule(3, rule("@import 'b.css';")),
+      Err(RuleListError::InvalidPosition)
     );
 
-    // Disallow 'none', 'not', 'and', 'or' as a `<container-name>`
-    // https://github.com/w3c/csswg-drafts/issues/7203#issuecomment-1144257312
-    // https://chromium-review.googlesource.com/c/chromium/src/+/3698402
-    error_test(
-      "@container none (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("none".into())),
+    if let Some(CssRule::Media(media)) = stylesheet.rule_mut(&[4]) {
+      assert_eq!(media.insert_rule(0, rule(".first { color: red }")), Ok(0));
+      assert_eq!(
+        media.insert_rule(0, rule("@import 'b.css';")),
+        Err(RuleListError::InvalidPosition)
+      );
+      assert!(matches!(media.rule(2), Some(CssRule::Supports(..))));
+    } else {
+      unreachable!()
+    }
+
+    if let Some(CssRule::Supports(supports)) = stylesheet.rule_mut(&[4, 2]) {
+      supports.replace_rule(0, rule(".replaced { color: red }")).unwrap();
+      assert!(supports.delete_rule(1).is_err());
+    } else {
+      unreachable!()
+    }
+
+    assert_eq!(
+      print(&stylesheet),
+      "@layer base;@import \"a.css\";@namespace svg \"http://www.w3.org/2000/svg\";.foo{color:green}@media print{.first{color:red}.bar{color:red}@supports (display:grid){.replaced{color:red}}}"
+    );
+  }
+
+  #[test]
+  fn test_builder() {
+    use crate::builder::{media, style, MediaQueryBuilder, StyleRuleBuilder};
+    use crate::media_query::MediaFeatureValue;
+    use crate::properties::size::Size;
+    use crate::properties::Property;
+    use crate::rules::{CssRule, CssRuleList};
+    use crate::values::color::CssColor;
+    use crate::values::ident::Ident;
+    use crate::values::length::{Length, LengthPercentage};
+    use cssparser::RGBA;
+
+    let blue = CssColor::RGBA(RGBA::from_floats(0.0, 0.0, 1.0, 1.0));
+    let rule = style(".a, .b")
+      .unwrap()
+      .declare(Property::Width(Size::LengthPercentage(LengthPercentage::px(10.0))))
+      .declare_important(Property::Color(blue.clone()))
+      .build();
+    assert_eq!(rule.selectors.0.len(), 2);
+    assert_eq!(rule.declarations.declarations.len(), 1);
+    assert_eq!(rule.declarations.important_declarations.len(), 1);
+    assert!(style("..a").is_err());
+
+    let query = MediaQueryBuilder::screen()
+      .min_width(Length::px(600.0))
+      .boolean("hover")
+      .plain("orientation", MediaFeatureValue::Ident(Ident("landscape".into())));
+    let rules = CssRuleList(vec![
+      media(query)
+        .or(MediaQueryBuilder::print().negate())
+        .nest(CssRule::Style(rule))
+        .into(),
+      media(MediaQueryBuilder::all().max_width(Length::px(300.0)))
+        .nest(StyleRuleBuilder::new(style(".c").unwrap().build().selectors).declare(Property::Color(blue)))
+        .into(),
+    ]);
+    assert!(matches!(rules.0[0], CssRule::Media(..)));
+
+    let stylesheet = StyleSheet::new(vec![], rules, ParserOptions::default());
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "@media screen and (min-width:600px) and (hover) and (orientation:landscape),not print{.a,.b{width:10px;color:#00f!important}}@media (max-width:300px){.c{color:#00f}}"
     );
+  }
 
-    error_test(
-      "@container and (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("and".into())),
-    );
+  #[test]
+  fn test_typed_declarations() {
+    use crate::declaration::DeclarationBlock;
+    use crate::properties::{size::Size, typed};
+    use crate::values::color::CssColor;
+    use crate::values::length::{LengthPercentage, LengthPercentageOrAuto};
+    use std::collections::HashMap;
+
+    let mut block = DeclarationBlock::parse_string(
+      "color: red; width: 10px; margin: 10px 20px; -webkit-transform: none; background: green !important",
+      ParserOptions::default(),
+    )
+    .unwrap();
 
-    error_test(
-      "@container or (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("or".into())),
+    let (color, important) = block.get_value::<typed::Color>().unwrap();
+    assert!(matches!(color, CssColor::RGBA(..)));
+    assert!(!important);
+    assert_eq!(
+      block.get_value::<typed::Width>(),
+      Some((Size::LengthPercentage(LengthPercentage::px(10.0)), false))
     );
+    assert_eq!(
+      block.get_value::<typed::MarginLeft>(),
+      Some((
+        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::px(20.0)),
+        false
+      ))
+    );
+    assert!(matches!(
+      block.get_value::<typed::BackgroundColor>(),
+      Some((CssColor::RGBA(..), true))
+    ));
+    assert!(block.get_value::<typed::Height>().is_none());
+
+    block.set_value::<typed::Width>(Size::Auto, false);
+    block.set_value::<typed::Height>(Size::LengthPercentage(LengthPercentage::px(5.0)), true);
+    block.remove_value::<typed::Color>();
+    block.remove_value::<typed::Margin>();
+    assert!(block.get_value::<typed::Color>().is_none());
 
-    // Disallow CSS wide keywords as a `<container-name>`
-    error_test(
-      "@container revert-layer (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("revert-layer".into())),
+    assert_eq!(
+      block.entries().unwrap(),
+      vec![
+        ("width".into(), "auto".into(), false),
+        ("-webkit-transform".into(), "none".into(), false),
+        ("background".into(), "green".into(), true),
+        ("height".into(), "5px".into(), true),
+      ]
     );
 
-    error_test(
-      "@container initial (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("initial".into())),
-    );
+    let map = block.to_map().unwrap();
+    let mut expected = HashMap::new();
+    expected.insert("width".to_string(), "auto".to_string());
+    expected.insert("-webkit-transform".to_string(), "none".to_string());
+    expected.insert("background".to_string(), "green !important".to_string());
+    expected.insert("height".to_string(), "5px !important".to_string());
+    assert_eq!(map, expected);
 
-    // <ident> contains spaces
-    // https://github.com/web-platform-tests/wpt/blob/39f0da08fbbe33d0582a35749b6dbf8bd067a52d/css/css-contain/container-queries/at-container-parsing.html#L160-L178
-    error_test(
-      "@container foo bar (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("bar".into())),
+    let block = DeclarationBlock::from_map(&map, &ParserOptions::default()).unwrap();
+    assert_eq!(
+      block.to_css_string(PrinterOptions::default()).unwrap(),
+      "-webkit-transform: none; width: auto; background: green !important; height: 5px !important"
     );
+
+    let mut invalid = HashMap::new();
+    invalid.insert("width".to_string(), "10px !foo".to_string());
+    assert!(DeclarationBlock::from_map(&invalid, &ParserOptions::default()).is_err());
   }
 
   #[test]
-  fn test_unknown_at_rules() {
-    minify_test("@foo;", "@foo;");
-    minify_test("@foo bar;", "@foo bar;");
-    minify_test("@foo (bar: baz);", "@foo (bar: baz);");
-    test(
-      r#"@foo test {
-      div {
-        color: red;
-      }
-    }"#,
-      indoc! {r#"
-      @foo test {
-        div { color: red; }
+  fn test_stylesheet_diff() {
+    use crate::diff::RuleChange;
+
+    fn options<'o, 'i>() -> ParserOptions<'o, 'i> {
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
       }
-      "#},
-    );
-    minify_test(
-      r#"@foo test {
-      div {
-        color: red;
+    }
+
+    fn check(old: &str, new: &str) -> usize {
+      let mut old = StyleSheet::parse(old, options()).unwrap();
+      let new = StyleSheet::parse(new, options()).unwrap();
+      let diff = old.diff(&new);
+      diff.apply(&mut old).unwrap();
+      assert_eq!(
+        old.to_css(PrinterOptions::default()).unwrap().code,
+        new.to_css(PrinterOptions::default()).unwrap().code
+      );
+      diff.changes.len()
+    }
+
+    let old = StyleSheet::parse(
+      ".a { color: red; width: 10px } .b { color: red } @media print { .c { color: red } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    assert!(old.diff(&old).is_empty());
+
+    let new = StyleSheet::parse(
+      ".a { color: green; height: 10px } @media print { .c { color: red } .d { color: red } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let diff = old.diff(&new);
+    assert_eq!(diff.changes.len(), 3);
+    assert!(matches!(&diff.changes[0], RuleChange::Removed { path, .. } if path == &[1]));
+    match &diff.changes[1] {
+      RuleChange::Declarations { path, changes } => {
+        assert_eq!(path, &[0]);
+        let summary: Vec<_> = changes
+          .iter()
+          .map(|change| (change.property_id.name(), change.old.len(), change.new.len()))
+          .collect();
+        assert_eq!(summary, vec![("color", 1, 1), ("height", 0, 1), ("width", 1, 0)]);
       }
-    }"#,
-      "@foo test{div { color: red; }}",
+      change => panic!("unexpected change {:?}", change),
+    }
+    assert!(matches!(&diff.changes[2], RuleChange::Added { path, .. } if path == &[1, 1]));
+
+    assert_eq!(
+      check(
+        ".a { color: red; width: 10px } .b { color: red } @media print { .c { color: red } }",
+        ".a { color: green; height: 10px } @media print { .c { color: red } .d { color: red } }",
+      ),
+      3
     );
-    minify_test(
-      r#"@foo test {
-        foo: bar;
-      }"#,
-      "@foo test{foo: bar;}",
+    assert_eq!(
+      check(
+        ".a { color: red } .b { color: red } .c { color: red }",
+        ".c { color: red } .a { color: red } .d { color: red } .b { color: blue !important }",
+      ),
+      4
     );
-    test(
-      r#"@foo {
-        foo: bar;
-      }"#,
-      indoc! {r#"
-      @foo {
-        foo: bar;
-      }
-      "#},
+    assert_eq!(
+      check(
+        ".a { color: red; & .b { color: red } } @font-face { font-family: A }",
+        ".a { color: red; & .b { color: blue } } @font-face { font-family: B }",
+      ),
+      3
     );
-    minify_test(
-      r#"@foo {
-        foo: bar;
-      }"#,
-      "@foo{foo: bar;}",
+    assert_eq!(
+      check(
+        ".a { color: red; color: lab(50% 20 30) }",
+        ".a { color: blue; width: 5px }"
+      ),
+      1
     );
   }
 
   #[test]
-  fn test_resolution() {
-    prefix_test(
+  fn test_flattened_iterators() {
+    use crate::query::serialize;
+
+    let stylesheet = StyleSheet::parse(
       r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
-        }
-      }
-      "#,
-      indoc! { r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
+      .a, .b { color: red; width: 1px !important }
+      @media print {
+        @supports (display: grid) {
+          .c { color: green; .d { top: 0 } left: 0 }
         }
       }
-      "#},
-      Browsers {
-        chrome: Some(50 << 16),
-        ..Browsers::default()
+      @font-face { font-family: Foo }
+    "#,
+      ParserOptions {
+        nesting: true,
+        ..ParserOptions::default()
       },
+    )
+    .unwrap();
+
+    assert_eq!(stylesheet.iter_rules().count(), 7);
+
+    let declarations: Vec<_> = stylesheet
+      .iter_declarations()
+      .map(|entry| {
+        (
+          entry.property.to_css_string(false, PrinterOptions::default()).unwrap(),
+          entry.important,
+          serialize(entry.selectors),
+          entry.context.media.iter().map(serialize).collect::<Vec<_>>(),
+          entry.context.supports.len(),
+          entry.context.parent_selectors.iter().map(serialize).collect::<Vec<_>>(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      declarations,
+      vec![
+        ("color: red".into(), false, ".a, .b".into(), vec![], 0, vec![]),
+        ("width: 1px".into(), true, ".a, .b".into(), vec![], 0, vec![]),
+        (
+          "color: green".into(),
+          false,
+          ".c".into(),
+          vec!["print".into()],
+          1,
+          vec![]
+        ),
+        (
+          "top: 0".into(),
+          false,
+          "& .d".into(),
+          vec!["print".into()],
+          1,
+          vec![".c".into()]
+        ),
+        (
+          "left: 0".into(),
+          false,
+          ".c".into(),
+          vec!["print".into()],
+          1,
+          vec![".c".into()]
+        ),
+      ]
     );
 
-    prefix_test(
-      r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
-        }
-      }
-      "#,
-      indoc! { r#"
-      @media (resolution: 1x) {
-        body {
-          background: red;
-        }
-      }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
+    let selectors: Vec<_> = stylesheet
+      .iter_selectors()
+      .map(|entry| (serialize(entry.selector), entry.context.parent_selectors.len()))
+      .collect();
+    assert_eq!(
+      selectors,
+      vec![(".a".into(), 0), (".b".into(), 0), (".c".into(), 0), ("& .d".into(), 1)]
     );
   }
 
   #[test]
-  fn test_environment() {
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small)) {
-        body {
-          padding: env(--branding-padding);
-        }
-      }
-    "#,
-      "@media (max-width:env(--branding-small)){body{padding:env(--branding-padding)}}",
-    );
-
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small 1)) {
-        body {
-          padding: env(--branding-padding 2);
-        }
-      }
-    "#,
-      "@media (max-width:env(--branding-small 1)){body{padding:env(--branding-padding 2)}}",
-    );
+  fn test_error_codes() {
+    use crate::error::ErrorCode;
 
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small 1, 20px)) {
-        body {
-          padding: env(--branding-padding 2, 20px);
-        }
-      }
-    "#,
-      "@media (max-width:env(--branding-small 1,20px)){body{padding:env(--branding-padding 2,20px)}}",
+    let err =
+      StyleSheet::parse(".foo { color: red } @import url(bar.css);", ParserOptions::default()).unwrap_err();
+    assert_eq!(err.code(), "unexpected-import-rule");
+    assert_eq!(
+      err.help(),
+      Some("Move the @import rule before all other rules except @charset and @layer statements")
     );
 
-    minify_test(
-      r#"
-      @media (max-width: env(safe-area-inset-top)) {
-        body {
-          padding: env(safe-area-inset-top);
-        }
-      }
-    "#,
-      "@media (max-width:env(safe-area-inset-top)){body{padding:env(safe-area-inset-top)}}",
-    );
+    let report = err.report();
+    assert_eq!(report.code, "unexpected-import-rule");
+    assert_eq!(report.message, err.kind.to_string());
+    assert_eq!(report.loc, err.loc);
 
-    minify_test(
-      r#"
-      @media (max-width: env(unknown)) {
-        body {
-          padding: env(unknown);
-        }
-      }
-    "#,
-      "@media (max-width:env(unknown)){body{padding:env(unknown)}}",
+    let selector_error = ParserError::SelectorError(SelectorError::DanglingCombinator);
+    assert_eq!(selector_error.code(), "selector-dangling-combinator");
+    assert_eq!(
+      selector_error.help(),
+      Some("Remove the trailing combinator, or add a selector after it")
     );
-
-    prefix_test(
-      r#"
-      .foo {
-        color: env(--brand-color, color(display-p3 0 1 0));
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        color: env(--brand-color, #00f942);
-      }
-
-      @supports (color: color(display-p3 0 0 0)) {
-        .foo {
-          color: env(--brand-color, color(display-p3 0 1 0));
-        }
-      }
-    "#},
-      Browsers {
-        safari: Some(15 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    assert_eq!(PrinterErrorKind::FmtError.code(), "fmt-error");
+    assert_eq!(PrinterErrorKind::FmtError.help(), None);
+    assert_eq!(
+      MinifyErrorKind::CustomMediaNotDefined { name: "--foo".into() }.code(),
+      "custom-media-not-defined"
     );
+  }
 
-    css_modules_test(
-      r#"
-      @media (max-width: env(--branding-small)) {
-        .foo {
-          color: env(--brand-color);
-        }
-      }
-    "#,
-      indoc! {r#"
-      @media (max-width: env(--EgL3uq_branding-small)) {
-        .EgL3uq_foo {
-          color: env(--EgL3uq_brand-color);
-        }
-      }
-    "#},
-      map! {
-        "foo" => "EgL3uq_foo",
-        "--brand-color" => "--EgL3uq_brand-color" referenced: true,
-        "--branding-small" => "--EgL3uq_branding-small" referenced: true
-      },
-      HashMap::new(),
-      crate::css_modules::Config {
-        dashed_idents: true,
-        ..Default::default()
-      },
-    );
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_error_report_serde() {
+    let err =
+      StyleSheet::parse(".foo { color: red } @import url(bar.css);", ParserOptions::default()).unwrap_err();
+    let json = serde_json::to_value(err.report()).unwrap();
+    assert_eq!(json["code"], "unexpected-import-rule");
+    assert_eq!(json["message"], err.kind.to_string());
+    assert!(json["help"].is_string());
+    assert!(json["loc"]["line"].is_number());
   }
 }
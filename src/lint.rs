@@ -0,0 +1,1103 @@
+//! Linting for style sheets.
+//!
+//! Lint rules implement the [LintRule](LintRule) trait, and are registered with a [Linter](Linter).
+//! The linter is a [Visitor](crate::visitor::Visitor), so all registered rules are checked in a single
+//! traversal of the style sheet, which can be run alongside other visitors and before minification.
+//! Each rule has a name and a default [Severity](Severity), which can be overridden per rule.
+//! Rules report problems as structured [Diagnostic](Diagnostic) values.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   lint::{Linter, Severity},
+//!   stylesheet::{StyleSheet, ParserOptions, MinifyOptions},
+//!   visitor::Visit,
+//! };
+//!
+//! let mut stylesheet = StyleSheet::parse(
+//!   r#"
+//!     .foo {}
+//!     .bar { color: red; color: red; }
+//!   "#,
+//!   ParserOptions::default()
+//! ).unwrap();
+//!
+//! let mut linter = Linter::recommended();
+//! linter.set_severity("no-duplicate-properties", Severity::Error);
+//! stylesheet.visit(&mut linter).unwrap();
+//! stylesheet.minify(MinifyOptions::default()).unwrap();
+//!
+//! let diagnostics = linter.into_diagnostics();
+//! assert_eq!(diagnostics.len(), 2);
+//! assert_eq!(diagnostics[0].rule, "no-empty-rules");
+//! assert_eq!(diagnostics[1].severity, Severity::Error);
+//! ```
+
+use crate::{
+  declaration::DeclarationBlock,
+  media_query::{MediaList, MediaQuery},
+  printer::PrinterOptions,
+  properties::{
+    animation::{AnimationIterationCount, AnimationName},
+    border::{BorderSideWidth, LineStyle},
+    custom::{CustomPropertyName, Token, TokenOrValue},
+    font::{AbsoluteFontSize, FontSize},
+    outline::OutlineStyle,
+    Property, PropertyId,
+  },
+  rules::{keyframes::KeyframesName, CssRule, CssRuleList, Location},
+  selector::{Component, PseudoClass, Selector, SelectorList, Specificity},
+  traits::{ToCss, Zero},
+  values::{color::CssColor, image::Image, length::LengthPercentage, url::Url},
+  var_graph::VarGraph,
+  visit_types,
+  visitor::{Visit, VisitTypes, Visitor},
+};
+use regex::Regex;
+use std::convert::Infallible;
+use std::fmt;
+
+/// The severity of a lint diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "lowercase"))]
+pub enum Severity {
+  /// The rule is disabled.
+  Off,
+  /// Problems are reported as warnings.
+  Warning,
+  /// Problems are reported as errors.
+  Error,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Severity::Off => write!(f, "off"),
+      Severity::Warning => write!(f, "warning"),
+      Severity::Error => write!(f, "error"),
+    }
+  }
+}
+
+/// A problem reported by a lint rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct Diagnostic {
+  /// The name of the rule that reported the problem.
+  pub rule: &'static str,
+  /// The severity of the problem.
+  pub severity: Severity,
+  /// A description of the problem.
+  pub message: String,
+  /// The location of the CSS rule containing the problem, if known.
+  pub loc: Option<Location>,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)?;
+    if let Some(loc) = &self.loc {
+      write!(f, " at {}:{}", loc.line + 1, loc.column)?;
+    }
+    Ok(())
+  }
+}
+
+/// The context passed to a [LintRule](LintRule), used to report problems.
+pub struct LintContext<'a, 'i> {
+  rule: &'static str,
+  severity: Severity,
+  loc: Option<Location>,
+  media_queries: &'a [MediaList<'i>],
+  diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a, 'i> LintContext<'a, 'i> {
+  /// Reports a problem at the location of the current CSS rule.
+  pub fn report<S: Into<String>>(&mut self, message: S) {
+    self.diagnostics.push(Diagnostic {
+      rule: self.rule,
+      severity: self.severity,
+      message: message.into(),
+      loc: self.loc,
+    })
+  }
+
+  /// Reports a problem at the given location.
+  pub fn report_at<S: Into<String>>(&mut self, loc: Location, message: S) {
+    self.diagnostics.push(Diagnostic {
+      rule: self.rule,
+      severity: self.severity,
+      message: message.into(),
+      loc: Some(loc),
+    })
+  }
+
+  /// Returns the location of the CSS rule currently being visited, if known.
+  pub fn loc(&self) -> Option<Location> {
+    self.loc
+  }
+
+  /// Returns the queries of the `@media` rules enclosing the CSS rule currently being visited, outermost first.
+  pub fn media_queries(&self) -> &[MediaList<'i>] {
+    self.media_queries
+  }
+}
+
+/// A lint rule, which checks values in a style sheet and reports problems.
+///
+/// Each `check_*` method is called for the corresponding values in the style sheet, if the matching
+/// flag is included in [visit_types](LintRule::visit_types).
+#[allow(unused_variables)]
+pub trait LintRule<'i> {
+  /// The name of the rule, used to configure its severity.
+  fn name(&self) -> &'static str;
+
+  /// The severity of the rule when it is not configured explicitly.
+  fn default_severity(&self) -> Severity {
+    Severity::Warning
+  }
+
+  /// The types of values that this rule checks.
+  fn visit_types(&self) -> VisitTypes;
+
+  /// Checks a list of CSS rules, e.g. the top-level rules of a style sheet, or the children of an `@media` rule.
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {}
+
+  /// Checks a CSS rule.
+  fn check_rule(&mut self, rule: &CssRule<'i>, cx: &mut LintContext) {}
+
+  /// Checks a declaration block.
+  fn check_declaration_block(&mut self, decls: &DeclarationBlock<'i>, cx: &mut LintContext) {}
+
+  /// Checks a property.
+  fn check_property(&mut self, property: &Property<'i>, cx: &mut LintContext) {}
+
+  /// Checks a selector.
+  fn check_selector(&mut self, selector: &Selector<'i>, cx: &mut LintContext) {}
+
+  /// Checks a media query.
+  fn check_media_query(&mut self, query: &MediaQuery<'i>, cx: &mut LintContext) {}
+
+  /// Checks a url.
+  fn check_url(&mut self, url: &Url<'i>, cx: &mut LintContext) {}
+
+  /// Checks a color.
+  fn check_color(&mut self, color: &CssColor, cx: &mut LintContext) {}
+}
+
+struct RegisteredRule<'i> {
+  rule: Box<dyn LintRule<'i>>,
+  severity: Severity,
+}
+
+/// A registry of lint rules, which checks them against a style sheet when used as a visitor.
+#[derive(Default)]
+pub struct Linter<'i> {
+  rules: Vec<RegisteredRule<'i>>,
+  diagnostics: Vec<Diagnostic>,
+  loc: Option<Location>,
+  media_queries: Vec<MediaList<'i>>,
+}
+
+impl<'i> Linter<'i> {
+  /// Creates a linter with no rules.
+  pub fn new() -> Self {
+    Linter::default()
+  }
+
+  /// Creates a linter with all of the built-in rules, at their default severities.
+  pub fn recommended() -> Self {
+    let mut linter = Linter::new();
+    linter.add_rule(NoEmptyRules);
+    linter.add_rule(NoDuplicateProperties);
+    linter.add_rule(NoShorthandPropertyOverrides);
+    linter.add_rule(NoUnknownProperties);
+    linter.add_rule(NoInvalidValues);
+    linter.add_rule(NoUnknownAtRules);
+    linter.add_rule(NoDuplicateSelectors);
+    linter.add_rule(NoDescendingSpecificity);
+    linter.add_rule(NoOutlineNone);
+    linter.add_rule(MinFontSize::default());
+    linter.add_rule(RequireReducedMotion::default());
+    linter.add_rule(ColorContrast::default());
+    linter
+  }
+
+  /// Registers a rule at its default severity. A rule with the same name is replaced.
+  pub fn add_rule<R: LintRule<'i> + 'static>(&mut self, rule: R) {
+    let severity = rule.default_severity();
+    self.rules.retain(|registered| registered.rule.name() != rule.name());
+    self.rules.push(RegisteredRule {
+      rule: Box::new(rule),
+      severity,
+    });
+  }
+
+  /// Sets the severity of the rule with the given name. Returns `false` if no such rule is registered.
+  pub fn set_severity(&mut self, name: &str, severity: Severity) -> bool {
+    match self.rules.iter_mut().find(|registered| registered.rule.name() == name) {
+      Some(registered) => {
+        registered.severity = severity;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the diagnostics reported so far.
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self.diagnostics
+  }
+
+  /// Consumes the linter, returning the reported diagnostics.
+  pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+    self.diagnostics
+  }
+
+  /// Returns whether any errors were reported.
+  pub fn has_errors(&self) -> bool {
+    self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+  }
+
+  fn check(&mut self, flag: VisitTypes, mut f: impl FnMut(&mut dyn LintRule<'i>, &mut LintContext<'_, 'i>)) {
+    for registered in &mut self.rules {
+      if registered.severity == Severity::Off || !registered.rule.visit_types().intersects(flag) {
+        continue;
+      }
+
+      let mut cx = LintContext {
+        rule: registered.rule.name(),
+        severity: registered.severity,
+        loc: self.loc,
+        media_queries: &self.media_queries,
+        diagnostics: &mut self.diagnostics,
+      };
+      f(registered.rule.as_mut(), &mut cx);
+    }
+  }
+}
+
+impl<'i> Visitor<'i> for Linter<'i> {
+  type Error = Infallible;
+
+  const TYPES: VisitTypes = VisitTypes::all();
+
+  fn visit_types(&self) -> VisitTypes {
+    let mut types = VisitTypes::RULES;
+    for registered in &self.rules {
+      if registered.severity != Severity::Off {
+        types |= registered.rule.visit_types();
+      }
+    }
+    types
+  }
+
+  fn visit_rule_list(&mut self, rules: &mut CssRuleList<'i>) -> Result<(), Self::Error> {
+    self.check(VisitTypes::RULES, |r, cx| r.check_rule_list(rules, cx));
+    rules.visit_children(self)
+  }
+
+  fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+    let parent_loc = self.loc;
+    if let Some(loc) = rule.loc() {
+      self.loc = Some(loc);
+    }
+
+    self.check(VisitTypes::RULES, |r, cx| r.check_rule(rule, cx));
+    let is_media = if let CssRule::Media(media) = rule {
+      self.media_queries.push(media.query.clone());
+      true
+    } else {
+      false
+    };
+    rule.visit_children(self)?;
+    if is_media {
+      self.media_queries.pop();
+    }
+    self.loc = parent_loc;
+    Ok(())
+  }
+
+  fn visit_declaration_block(&mut self, decls: &mut DeclarationBlock<'i>) -> Result<(), Self::Error> {
+    self.check(VisitTypes::PROPERTIES, |r, cx| r.check_declaration_block(decls, cx));
+    decls.visit_children(self)
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    self.check(VisitTypes::PROPERTIES, |r, cx| r.check_property(property, cx));
+    property.visit_children(self)
+  }
+
+  fn visit_selector_list(&mut self, selectors: &mut SelectorList<'i>) -> Result<(), Self::Error> {
+    for selector in selectors.0.iter() {
+      self.check(VisitTypes::SELECTORS, |r, cx| r.check_selector(selector, cx));
+    }
+    Ok(())
+  }
+
+  fn visit_media_query(&mut self, query: &mut MediaQuery<'i>) -> Result<(), Self::Error> {
+    self.check(VisitTypes::MEDIA_QUERIES, |r, cx| r.check_media_query(query, cx));
+    query.visit_children(self)
+  }
+
+  fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+    self.check(VisitTypes::URLS, |r, cx| r.check_url(url, cx));
+    Ok(())
+  }
+
+  fn visit_color(&mut self, color: &mut CssColor) -> Result<(), Self::Error> {
+    self.check(VisitTypes::COLORS, |r, cx| r.check_color(color, cx));
+    Ok(())
+  }
+}
+
+/// Reports style rules that contain no declarations or nested rules.
+pub struct NoEmptyRules;
+
+impl<'i> LintRule<'i> for NoEmptyRules {
+  fn name(&self) -> &'static str {
+    "no-empty-rules"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule(&mut self, rule: &CssRule<'i>, cx: &mut LintContext) {
+    if let CssRule::Style(style) = rule {
+      if style.declarations.is_empty() && style.rules.0.is_empty() {
+        cx.report("Unexpected empty style rule");
+      }
+    }
+  }
+}
+
+/// Reports declarations that are repeated with the same value in a declaration block.
+/// Repeated properties with different values are allowed, since they are commonly used as fallbacks.
+pub struct NoDuplicateProperties;
+
+impl<'i> LintRule<'i> for NoDuplicateProperties {
+  fn name(&self) -> &'static str {
+    "no-duplicate-properties"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_declaration_block(&mut self, decls: &DeclarationBlock<'i>, cx: &mut LintContext) {
+    for list in [&decls.declarations, &decls.important_declarations] {
+      for (i, property) in list.iter().enumerate() {
+        if list[..i].contains(property) {
+          cx.report(format!("Duplicate declaration of `{}`", property.property_id().name()));
+        }
+      }
+    }
+  }
+}
+
+/// Reports shorthand properties that override a longhand declared earlier in the same declaration block.
+pub struct NoShorthandPropertyOverrides;
+
+fn is_longhand_of(shorthand: &PropertyId, property_id: &PropertyId) -> bool {
+  match shorthand.longhands() {
+    Some(longhands) => longhands
+      .iter()
+      .any(|longhand| longhand == property_id || is_longhand_of(longhand, property_id)),
+    None => false,
+  }
+}
+
+impl<'i> LintRule<'i> for NoShorthandPropertyOverrides {
+  fn name(&self) -> &'static str {
+    "no-shorthand-property-overrides"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_declaration_block(&mut self, decls: &DeclarationBlock<'i>, cx: &mut LintContext) {
+    for list in [&decls.declarations, &decls.important_declarations] {
+      for (i, property) in list.iter().enumerate() {
+        let shorthand = property.property_id();
+        for previous in &list[..i] {
+          let longhand = previous.property_id();
+          if is_longhand_of(&shorthand, &longhand) {
+            cx.report(format!(
+              "Shorthand property `{}` overrides `{}` declared before it",
+              shorthand.name(),
+              longhand.name()
+            ));
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Reports properties that are not known to the parser, excluding custom properties.
+pub struct NoUnknownProperties;
+
+impl<'i> LintRule<'i> for NoUnknownProperties {
+  fn name(&self) -> &'static str {
+    "no-unknown-properties"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_property(&mut self, property: &Property<'i>, cx: &mut LintContext) {
+    if let Property::Custom(custom) = property {
+      if let CustomPropertyName::Unknown(name) = &custom.name {
+        cx.report(format!("Unknown property `{}`", name.0));
+      }
+    }
+  }
+}
+
+/// Reports at-rules that are not known to the parser.
+pub struct NoUnknownAtRules;
+
+impl<'i> LintRule<'i> for NoUnknownAtRules {
+  fn name(&self) -> &'static str {
+    "no-unknown-at-rules"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule(&mut self, rule: &CssRule<'i>, cx: &mut LintContext) {
+    if let CssRule::Unknown(rule) = rule {
+      cx.report(format!("Unknown at-rule `@{}`", rule.name));
+    }
+  }
+}
+
+/// Reports declarations of known properties whose values could not be parsed.
+///
+/// CSS-wide keywords are valid for every property. Values containing `var()`, `env()`, `attr()`
+/// or anchor functions are skipped, since they can only be validated once substituted.
+pub struct NoInvalidValues;
+
+impl<'i> LintRule<'i> for NoInvalidValues {
+  fn name(&self) -> &'static str {
+    "no-invalid-values"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_property(&mut self, property: &Property<'i>, cx: &mut LintContext) {
+    if let Property::Unparsed(unparsed) = property {
+      let is_wide_keyword = matches!(
+        &unparsed.value.0[..],
+        [TokenOrValue::Token(Token::Ident(ident))] if matches!(
+          ident.to_ascii_lowercase().as_str(),
+          "initial" | "inherit" | "unset" | "revert" | "revert-layer"
+        )
+      );
+      if !is_wide_keyword && !unparsed.value.has_references() && !unparsed.value.has_anchor_functions() {
+        cx.report(format!("Invalid value for `{}`", unparsed.property_id.name()));
+      }
+    }
+  }
+}
+
+fn selector_string(selector: &Selector) -> String {
+  selector.to_css_string(PrinterOptions::default()).unwrap_or_default()
+}
+
+/// Reports style rules whose selectors repeat those of an earlier style rule in the same rule list.
+pub struct NoDuplicateSelectors;
+
+impl<'i> LintRule<'i> for NoDuplicateSelectors {
+  fn name(&self) -> &'static str {
+    "no-duplicate-selectors"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {
+    let mut seen: Vec<&SelectorList<'i>> = Vec::new();
+    for rule in &rules.0 {
+      if let CssRule::Style(style) = rule {
+        if seen.contains(&&style.selectors) {
+          cx.report_at(
+            style.loc,
+            format!(
+              "Duplicate selector `{}`",
+              style.selectors.to_css_string(PrinterOptions::default()).unwrap_or_default()
+            ),
+          );
+        } else {
+          seen.push(&style.selectors);
+        }
+      }
+    }
+  }
+}
+
+/// Reports selectors that appear after a selector with a higher specificity targeting the same
+/// compound selector, within the same rule list.
+pub struct NoDescendingSpecificity;
+
+impl<'i> LintRule<'i> for NoDescendingSpecificity {
+  fn name(&self) -> &'static str {
+    "no-descending-specificity"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {
+    let mut seen: Vec<(Vec<&Component<'i>>, &Selector<'i>)> = Vec::new();
+    for rule in &rules.0 {
+      if let CssRule::Style(style) = rule {
+        for selector in &style.selectors.0 {
+          let compound: Vec<_> = selector.iter().collect();
+          if let Some((_, previous)) = seen
+            .iter()
+            .find(|(c, previous)| *c == compound && previous.specificity() > selector.specificity())
+          {
+            cx.report_at(
+              style.loc,
+              format!(
+                "Expected selector `{}` to come before `{}`",
+                selector_string(selector),
+                selector_string(previous)
+              ),
+            );
+          }
+          seen.push((compound, selector));
+        }
+      }
+    }
+  }
+}
+
+/// Reports selectors with a higher specificity than the configured maximum.
+///
+/// This rule is not included in [Linter::recommended](Linter::recommended), since the appropriate
+/// maximum depends on the project.
+pub struct MaxSpecificity(pub Specificity);
+
+impl<'i> LintRule<'i> for MaxSpecificity {
+  fn name(&self) -> &'static str {
+    "max-specificity"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(SELECTORS)
+  }
+
+  fn check_selector(&mut self, selector: &Selector<'i>, cx: &mut LintContext) {
+    let specificity = Specificity::from(selector.specificity());
+    if specificity > self.0 {
+      cx.report(format!(
+        "Selector `{}` has specificity {}, which exceeds the maximum of {}",
+        selector_string(selector),
+        specificity,
+        self.0
+      ));
+    }
+  }
+}
+
+/// Reports `var()` references to custom properties that are not defined anywhere in the style sheet and
+/// have no fallback value. Lint a bundled style sheet so that definitions in imported files are found.
+///
+/// Custom properties that are defined at runtime, e.g. by JavaScript or inline styles, can be added to the
+/// allowlist. Entries ending in `*` match all custom properties starting with the rest of the entry. This
+/// rule is not included in [Linter::recommended](Linter::recommended), since the allowlist depends on the project.
+#[derive(Default)]
+pub struct NoUndefinedVariables {
+  /// The names of custom properties to ignore, including the leading `--`, e.g. `--theme-*`.
+  pub allowlist: Vec<String>,
+}
+
+impl NoUndefinedVariables {
+  /// Creates the rule with the given allowlist.
+  pub fn new<S: Into<String>, I: IntoIterator<Item = S>>(allowlist: I) -> Self {
+    NoUndefinedVariables {
+      allowlist: allowlist.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+impl<'i> LintRule<'i> for NoUndefinedVariables {
+  fn name(&self) -> &'static str {
+    "no-undefined-variables"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {
+    // Definitions may appear anywhere in the style sheet, so check all references from the top-level rules.
+    if cx.loc().is_some() {
+      return;
+    }
+
+    let graph = VarGraph::build(rules);
+    for (name, reference) in graph.undefined_references(&self.allowlist) {
+      cx.report_at(
+        reference.loc,
+        format!(
+          "Custom property `{}` referenced by `{}` is not defined and has no fallback value",
+          name, reference.property
+        ),
+      );
+    }
+  }
+}
+
+/// Reports class names, ids, custom property names, and `@keyframes` names that do not match the
+/// configured regular expressions, e.g. to enforce BEM or kebab-case names in a design system.
+///
+/// Names are matched without their leading `.`, `#`, or `--`, and patterns must match the whole name.
+/// Names without a configured pattern are not checked. This rule is not included in
+/// [Linter::recommended](Linter::recommended), since naming conventions depend on the project.
+///
+/// ```
+/// use lightningcss::lint::NamingConvention;
+///
+/// let rule = NamingConvention::new()
+///   .class(NamingConvention::BEM)
+///   .unwrap()
+///   .custom_property(NamingConvention::KEBAB_CASE)
+///   .unwrap();
+/// ```
+#[derive(Default)]
+pub struct NamingConvention {
+  class: Option<Regex>,
+  id: Option<Regex>,
+  custom_property: Option<Regex>,
+  keyframes: Option<Regex>,
+}
+
+impl NamingConvention {
+  /// A pattern matching kebab-case names, e.g. `primary-button`.
+  pub const KEBAB_CASE: &str = r"[a-z][a-z0-9]*(-[a-z0-9]+)*";
+
+  /// A pattern matching BEM names in kebab-case, e.g. `card`, `card__title`, or `card__title--large`.
+  pub const BEM: &str = r"[a-z][a-z0-9]*(-[a-z0-9]+)*(__[a-z0-9]+(-[a-z0-9]+)*)?(--[a-z0-9]+(-[a-z0-9]+)*)?";
+
+  /// Creates the rule with no patterns.
+  pub fn new() -> Self {
+    NamingConvention::default()
+  }
+
+  /// Sets the pattern for class names.
+  pub fn class(mut self, pattern: &str) -> Result<Self, regex::Error> {
+    self.class = Some(anchored(pattern)?);
+    Ok(self)
+  }
+
+  /// Sets the pattern for ids.
+  pub fn id(mut self, pattern: &str) -> Result<Self, regex::Error> {
+    self.id = Some(anchored(pattern)?);
+    Ok(self)
+  }
+
+  /// Sets the pattern for the names of custom properties declared in the style sheet.
+  pub fn custom_property(mut self, pattern: &str) -> Result<Self, regex::Error> {
+    self.custom_property = Some(anchored(pattern)?);
+    Ok(self)
+  }
+
+  /// Sets the pattern for `@keyframes` names.
+  pub fn keyframes(mut self, pattern: &str) -> Result<Self, regex::Error> {
+    self.keyframes = Some(anchored(pattern)?);
+    Ok(self)
+  }
+
+  fn check_name(pattern: &Option<Regex>, kind: &str, name: &str, cx: &mut LintContext) {
+    if let Some(pattern) = pattern {
+      if !pattern.is_match(name.strip_prefix("--").unwrap_or(name)) {
+        cx.report(format!("{} `{}` does not match the naming convention", kind, name));
+      }
+    }
+  }
+
+  fn check_components(&self, selector: &Selector, cx: &mut LintContext) {
+    for component in selector.iter_raw_match_order() {
+      match component {
+        Component::Class(name) => NamingConvention::check_name(&self.class, "Class name", name.0.as_ref(), cx),
+        Component::ID(name) => NamingConvention::check_name(&self.id, "Id", name.0.as_ref(), cx),
+        Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Negation(selectors)
+        | Component::Has(selectors) => {
+          for selector in selectors.iter() {
+            self.check_components(selector, cx);
+          }
+        }
+        Component::Slotted(selector) | Component::Host(Some(selector)) => self.check_components(selector, cx),
+        _ => {}
+      }
+    }
+  }
+}
+
+/// Compiles a pattern that must match the whole string.
+fn anchored(pattern: &str) -> Result<Regex, regex::Error> {
+  Regex::new(&format!("^(?:{})$", pattern))
+}
+
+impl<'i> LintRule<'i> for NamingConvention {
+  fn name(&self) -> &'static str {
+    "naming-convention"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES | PROPERTIES | SELECTORS)
+  }
+
+  fn check_rule(&mut self, rule: &CssRule<'i>, cx: &mut LintContext) {
+    if let CssRule::Keyframes(keyframes) = rule {
+      let name = match &keyframes.name {
+        KeyframesName::Ident(ident) => ident.0.as_ref(),
+        KeyframesName::Custom(name) => name.as_ref(),
+      };
+      NamingConvention::check_name(&self.keyframes, "Keyframes name", name, cx);
+    }
+  }
+
+  fn check_property(&mut self, property: &Property<'i>, cx: &mut LintContext) {
+    if let Property::Custom(custom) = property {
+      if let CustomPropertyName::Custom(name) = &custom.name {
+        NamingConvention::check_name(&self.custom_property, "Custom property", name.0.as_ref(), cx);
+      }
+    }
+  }
+
+  fn check_selector(&mut self, selector: &Selector<'i>, cx: &mut LintContext) {
+    self.check_components(selector, cx);
+  }
+}
+
+/// Returns whether the property removes the focus outline, e.g. `outline: none` or `outline: 0`.
+fn removes_outline(property: &Property) -> bool {
+  let is_zero = |width: &BorderSideWidth| matches!(width, BorderSideWidth::Length(length) if length.is_zero());
+  match property {
+    Property::Outline(outline) => {
+      outline.style == OutlineStyle::LineStyle(LineStyle::None) || is_zero(&outline.width)
+    }
+    Property::OutlineStyle(style) => *style == OutlineStyle::LineStyle(LineStyle::None),
+    Property::OutlineWidth(width) => is_zero(width),
+    _ => false,
+  }
+}
+
+/// Reports style rules that remove the focus outline with `outline: none` or `outline: 0`, unless a
+/// style rule in the same rule list provides an alternative focus style, such as a `box-shadow` or
+/// `border`, for a `:focus`, `:focus-visible`, or `:focus-within` selector.
+pub struct NoOutlineNone;
+
+impl<'i> LintRule<'i> for NoOutlineNone {
+  fn name(&self) -> &'static str {
+    "no-outline-none"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {
+    let has_focus_style = rules.0.iter().any(|rule| match rule {
+      CssRule::Style(style) => {
+        let is_focus_selector = style.selectors.0.iter().any(|selector| {
+          selector.iter_raw_match_order().any(|component| {
+            matches!(
+              component,
+              Component::NonTSPseudoClass(
+                PseudoClass::Focus | PseudoClass::FocusVisible | PseudoClass::FocusWithin
+              )
+            )
+          })
+        });
+        is_focus_selector
+          && style.declarations.iter().any(|(property, _)| {
+            let id = property.property_id();
+            let name = id.name();
+            name == "box-shadow"
+              || name.starts_with("border")
+              || name.starts_with("background")
+              || name.starts_with("text-decoration")
+              || (name.starts_with("outline") && !removes_outline(property))
+          })
+      }
+      _ => false,
+    });
+    if has_focus_style {
+      return;
+    }
+
+    for rule in &rules.0 {
+      if let CssRule::Style(style) = rule {
+        if style.declarations.iter().any(|(property, _)| removes_outline(property)) {
+          cx.report_at(
+            style.loc,
+            format!(
+              "The focus outline of `{}` is removed without an alternative focus style",
+              style.selectors.to_css_string(PrinterOptions::default()).unwrap_or_default()
+            ),
+          );
+        }
+      }
+    }
+  }
+}
+
+/// Reports font sizes smaller than a minimum number of pixels, 12 by default. Only absolute lengths and
+/// the `xx-small` and `x-small` keywords are checked, since relative sizes depend on the parent element.
+pub struct MinFontSize(pub f32);
+
+impl Default for MinFontSize {
+  fn default() -> Self {
+    MinFontSize(12.0)
+  }
+}
+
+impl<'i> LintRule<'i> for MinFontSize {
+  fn name(&self) -> &'static str {
+    "min-font-size"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_property(&mut self, property: &Property<'i>, cx: &mut LintContext) {
+    let size = match property {
+      Property::FontSize(size) => size,
+      Property::Font(font) => &font.size,
+      _ => return,
+    };
+
+    let px = match size {
+      FontSize::Length(LengthPercentage::Dimension(length)) => length.to_px(),
+      // The sizes used by browsers when the default font size is 16px.
+      FontSize::Absolute(AbsoluteFontSize::XXSmall) => Some(9.0),
+      FontSize::Absolute(AbsoluteFontSize::XSmall) => Some(10.0),
+      _ => None,
+    };
+    if let Some(px) = px {
+      if px < self.0 {
+        cx.report(format!(
+          "Font size `{}` is smaller than the minimum of {}px",
+          size.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+          self.0
+        ));
+      }
+    }
+  }
+}
+
+/// Reports animations that run for at least a minimum duration, including all iterations, and are not
+/// guarded by a `prefers-reduced-motion` media query. The default minimum is 5 seconds, after which
+/// moving content must be possible to stop according to WCAG.
+///
+/// An animation is guarded if the rule declaring it is within a `@media` rule that queries
+/// `prefers-reduced-motion`, or if its rule list or the style rule itself contains one.
+pub struct RequireReducedMotion {
+  /// The minimum total duration of an animation to report, in milliseconds.
+  pub min_duration_ms: f32,
+}
+
+impl Default for RequireReducedMotion {
+  fn default() -> Self {
+    RequireReducedMotion {
+      min_duration_ms: 5000.0,
+    }
+  }
+}
+
+fn queries_reduced_motion(media: &MediaList) -> bool {
+  media
+    .to_css_string(PrinterOptions::default())
+    .map_or(false, |query| query.contains("prefers-reduced-motion"))
+}
+
+fn has_reduced_motion_rule(rules: &CssRuleList) -> bool {
+  rules
+    .0
+    .iter()
+    .any(|rule| matches!(rule, CssRule::Media(media) if queries_reduced_motion(&media.query)))
+}
+
+impl RequireReducedMotion {
+  fn check_declarations(&self, decls: &DeclarationBlock, loc: Location, cx: &mut LintContext) {
+    let mut names = Vec::new();
+    let mut durations = Vec::new();
+    // The number of iterations of each animation, or `None` if it is infinite.
+    let mut iterations = Vec::new();
+    let iteration_count = |count: &AnimationIterationCount| match count {
+      AnimationIterationCount::Number(n) => Some(*n),
+      AnimationIterationCount::Infinite => None,
+    };
+
+    for (property, _) in decls.iter() {
+      match property {
+        Property::Animation(animations, _) => {
+          names = animations.iter().map(|animation| animation.name.clone()).collect();
+          durations = animations.iter().map(|animation| animation.duration.to_ms()).collect();
+          iterations = animations
+            .iter()
+            .map(|animation| iteration_count(&animation.iteration_count))
+            .collect();
+        }
+        Property::AnimationName(list, _) => names = list.to_vec(),
+        Property::AnimationDuration(list, _) => durations = list.iter().map(|time| time.to_ms()).collect(),
+        Property::AnimationIterationCount(list, _) => iterations = list.iter().map(iteration_count).collect(),
+        _ => {}
+      }
+    }
+
+    for (i, name) in names.iter().enumerate() {
+      let name = match name {
+        AnimationName::Ident(ident) => ident.0.as_ref(),
+        AnimationName::String(s) => s.as_ref(),
+        _ => continue,
+      };
+      let duration = if durations.is_empty() {
+        0.0
+      } else {
+        durations[i % durations.len()]
+      };
+      let iterations = if iterations.is_empty() {
+        Some(1.0)
+      } else {
+        iterations[i % iterations.len()]
+      };
+      let is_long = duration > 0.0 && iterations.map_or(true, |n| duration * n >= self.min_duration_ms);
+      if is_long {
+        cx.report_at(
+          loc,
+          format!(
+            "Animation `{}` is not guarded by a `prefers-reduced-motion` media query",
+            name
+          ),
+        );
+      }
+    }
+  }
+}
+
+impl<'i> LintRule<'i> for RequireReducedMotion {
+  fn name(&self) -> &'static str {
+    "require-reduced-motion"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(RULES)
+  }
+
+  fn check_rule_list(&mut self, rules: &CssRuleList<'i>, cx: &mut LintContext) {
+    if cx.media_queries().iter().any(queries_reduced_motion) || has_reduced_motion_rule(rules) {
+      return;
+    }
+
+    for rule in &rules.0 {
+      match rule {
+        CssRule::Style(style) if !has_reduced_motion_rule(&style.rules) => {
+          self.check_declarations(&style.declarations, style.loc, cx)
+        }
+        CssRule::NestedDeclarations(nested) => self.check_declarations(&nested.declarations, nested.loc, cx),
+        _ => {}
+      }
+    }
+  }
+}
+
+/// Reports declaration blocks that set both `color` and a background color to literal opaque colors
+/// with a contrast ratio below a minimum, 4.5:1 by default, as required by WCAG level AA for normal text.
+pub struct ColorContrast(pub f32);
+
+impl Default for ColorContrast {
+  fn default() -> Self {
+    ColorContrast(4.5)
+  }
+}
+
+/// Returns the relative luminance of a literal opaque color, as defined by WCAG.
+fn relative_luminance(color: &CssColor) -> Option<f32> {
+  if !matches!(
+    color,
+    CssColor::RGBA(..) | CssColor::LAB(..) | CssColor::Predefined(..) | CssColor::Float(..)
+  ) {
+    return None;
+  }
+
+  match color.to_rgb() {
+    CssColor::RGBA(rgba) if rgba.alpha == 255 => {
+      let channel = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 {
+          c / 12.92
+        } else {
+          ((c + 0.055) / 1.055).powf(2.4)
+        }
+      };
+      Some(0.2126 * channel(rgba.red) + 0.7152 * channel(rgba.green) + 0.0722 * channel(rgba.blue))
+    }
+    _ => None,
+  }
+}
+
+impl<'i> LintRule<'i> for ColorContrast {
+  fn name(&self) -> &'static str {
+    "color-contrast"
+  }
+
+  fn visit_types(&self) -> VisitTypes {
+    visit_types!(PROPERTIES)
+  }
+
+  fn check_declaration_block(&mut self, decls: &DeclarationBlock<'i>, cx: &mut LintContext) {
+    let mut foreground = None;
+    let mut background = None;
+    for (property, _) in decls.iter() {
+      match property {
+        Property::Color(color) => foreground = Some(color),
+        Property::BackgroundColor(color) => background = Some(color),
+        Property::Background(backgrounds) => {
+          // Text may be drawn over a background image, whose colors are unknown.
+          background = match backgrounds.last() {
+            Some(last) if backgrounds.iter().all(|b| b.image == Image::None) => Some(&last.color),
+            _ => None,
+          }
+        }
+        _ => {}
+      }
+    }
+
+    let (foreground, background) = match (foreground, background) {
+      (Some(foreground), Some(background)) => (foreground, background),
+      _ => return,
+    };
+    if let (Some(a), Some(b)) = (relative_luminance(foreground), relative_luminance(background)) {
+      let ratio = (a.max(b) + 0.05) / (a.min(b) + 0.05);
+      if ratio < self.0 {
+        cx.report(format!(
+          "Contrast ratio {:.2}:1 between `{}` and `{}` is below the minimum of {}:1",
+          ratio,
+          foreground.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+          background.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+          self.0
+        ));
+      }
+    }
+  }
+}
@@ -0,0 +1,155 @@
+//! Original source text of style sheets parsed with the `lossless` option.
+
+use crate::error::PrinterError;
+use crate::parser::DefaultAtRule;
+use crate::printer::{Printer, PrinterOptions};
+use crate::rules::comment::CommentRule;
+use crate::rules::{CssRule, CssRuleList};
+use crate::traits::ToCss;
+use cssparser::SourcePosition;
+
+/// The original text of the top-level rules in a style sheet, along with the whitespace,
+/// comments, and invalid rules between them.
+///
+/// Each rule is printed from its original text if it serializes the same way as it did
+/// when it was parsed, i.e. it was not changed afterward. Otherwise, the rule is printed
+/// normally, and only the text around it is kept.
+#[derive(Debug, Default)]
+pub(crate) struct LosslessSource<'i> {
+  rules: Vec<RawRule<'i>>,
+  trailing: &'i str,
+  /// The byte offset of the end of the last rule.
+  end: usize,
+}
+
+#[derive(Debug)]
+struct RawRule<'i> {
+  /// The text between the previous rule and this one.
+  leading: &'i str,
+  /// The text of the rule itself.
+  text: &'i str,
+  /// The rule as it was serialized after parsing.
+  printed: Option<String>,
+}
+
+impl<'i> LosslessSource<'i> {
+  /// Records the comments that were parsed as [CommentRule](CommentRule) nodes, starting at the given position.
+  pub(crate) fn push_comments(&mut self, code: &'i str, start: SourcePosition, comments: &[CommentRule<'i>]) {
+    let mut cursor = start.byte_index();
+    for comment in comments {
+      // Only whitespace may appear between the comments, so the next `/*` starts the comment.
+      let start = match code[cursor..].find("/*") {
+        Some(offset) => cursor + offset,
+        None => return,
+      };
+      let end = (start + comment.text.len() + 4).min(code.len());
+      cursor = end;
+      self.push(
+        code,
+        start,
+        end,
+        print_rule(&CssRule::<DefaultAtRule>::Comment(comment.clone())),
+      );
+    }
+  }
+
+  /// Records a rule that was parsed between the given positions.
+  pub(crate) fn push_rule<T: ToCss>(
+    &mut self,
+    code: &'i str,
+    start: SourcePosition,
+    end: SourcePosition,
+    rule: &CssRule<'i, T>,
+  ) {
+    self.push(code, start.byte_index(), end.byte_index(), print_rule(rule))
+  }
+
+  fn push(&mut self, code: &'i str, start: usize, end: usize, printed: Option<String>) {
+    self.rules.push(RawRule {
+      leading: &code[self.end..start],
+      text: &code[start..end],
+      printed,
+    });
+    self.end = end;
+  }
+
+  /// Records the text after the last rule.
+  pub(crate) fn finish(&mut self, code: &'i str) {
+    self.trailing = &code[self.end..];
+  }
+
+  /// Returns whether the original text can be written with the printer's options. Options that
+  /// transform the output, such as minification, browser targets, or CSS modules, require
+  /// every rule to be printed normally.
+  pub(crate) fn can_write<W>(&self, dest: &Printer<W>) -> bool {
+    !dest.minify
+      && dest.targets.is_none()
+      && dest.css_module.is_none()
+      && dest.dependencies.is_none()
+      && dest.pseudo_classes.is_none()
+      && dest.rule_ranges.is_none()
+  }
+
+  /// Writes the rules, using the original text for the rules that are unchanged.
+  pub(crate) fn write<T: ToCss, W: std::fmt::Write>(
+    &self,
+    rules: &CssRuleList<'i, T>,
+    dest: &mut Printer<W>,
+  ) -> Result<(), PrinterError> {
+    let mut next = 0;
+    for rule in &rules.0 {
+      if let CssRule::Ignored = rule {
+        continue;
+      }
+
+      let printed = print_rule(rule);
+      let original = printed
+        .as_ref()
+        .and_then(|printed| (next..self.rules.len()).find(|i| self.rules[*i].printed.as_ref() == Some(printed)));
+
+      match original {
+        Some(i) => {
+          let raw = &self.rules[i];
+          write_raw(raw.leading, dest)?;
+          #[cfg(feature = "sourcemap")]
+          if let Some(loc) = rule.loc() {
+            dest.add_mapping(loc);
+          }
+          write_raw(raw.text, dest)?;
+          next = i + 1;
+        }
+        None => {
+          // A changed or inserted rule takes the place of the next original rule.
+          if let Some(raw) = self.rules.get(next) {
+            write_raw(raw.leading, dest)?;
+            next += 1;
+          } else if dest.bytes > 0 {
+            dest.newline()?;
+            dest.newline()?;
+          }
+          rule.to_css(dest)?;
+        }
+      }
+    }
+
+    write_raw(self.trailing, dest)
+  }
+}
+
+fn print_rule<T: ToCss>(rule: &CssRule<'_, T>) -> Option<String> {
+  let mut printed = String::new();
+  let mut printer = Printer::new(&mut printed, PrinterOptions::default());
+  rule.to_css(&mut printer).ok()?;
+  Some(printed)
+}
+
+/// Writes text that may span multiple lines.
+fn write_raw<W: std::fmt::Write>(text: &str, dest: &mut Printer<W>) -> Result<(), PrinterError> {
+  for (i, line) in text.split('\n').enumerate() {
+    if i > 0 {
+      dest.write_char('\n')?;
+    }
+    dest.write_str(line)?;
+  }
+  Ok(())
+}
@@ -288,7 +288,8 @@ macro_rules! shorthand_handler {
         true
       }
 
-      fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+      #[allow(unused_variables)]
+      fn finalize(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
         if !self.has_any {
           return
         }
@@ -310,7 +311,7 @@ macro_rules! shorthand_handler {
           $(
             if $shorthand_fallback {
               if let Some(targets) = self.targets {
-                let fallbacks = shorthand.get_fallbacks(targets);
+                let fallbacks = context.get_color_fallbacks(&mut shorthand, targets);
                 for fallback in fallbacks {
                   dest.push(Property::$shorthand(fallback));
                 }
@@ -326,7 +327,7 @@ macro_rules! shorthand_handler {
               $(
                 if $fallback {
                   if let Some(targets) = self.targets {
-                    let fallbacks = val.get_fallbacks(targets);
+                    let fallbacks = context.get_color_fallbacks(&mut val, targets);
                     for fallback in fallbacks {
                       dest.push(Property::$prop(fallback));
                     }
@@ -1,10 +1,20 @@
 use atty::Stream;
 use clap::{ArgGroup, Parser};
 use lightningcss::bundler::{Bundler, FileProvider};
-use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
+use lightningcss::coverage::Coverage;
+use lightningcss::design_tokens::DesignTokenOptions;
+use lightningcss::json_patch::JsonPatch;
+use lightningcss::purge::{DefaultExtractor, Purge};
+use lightningcss::selector::SelectorList;
+use lightningcss::stylesheet::{
+  MinifyOptions, OptimizationLevel, ParserOptions, PrinterOptions, RuleSize, SizeBreakdown, SizeGroup, StyleSheet,
+};
 use lightningcss::targets::Browsers;
+use lightningcss::traits::ParseWithOptions;
+use lightningcss::visitor::{RenameMap, SelectorRenamer, Visit};
 use parcel_sourcemap::SourceMap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::{ffi, fs, io, path, path::Path};
 
@@ -34,6 +44,10 @@ struct CliArgs {
   /// Enable parsing custom media queries
   #[clap(long, value_parser)]
   custom_media: bool,
+  /// Write a JSON file mapping each custom media query name to its resolved media query,
+  /// e.g. for use with matchMedia at runtime. Requires --custom-media
+  #[clap(long, value_parser)]
+  custom_media_json: Option<String>,
   /// Enable CSS modules in output.
   /// If no filename is provided, <output_file>.json will be used.
   /// If no --output-file is specified, code and exports will be printed to stdout as JSON.
@@ -43,6 +57,8 @@ struct CliArgs {
   css_modules_pattern: Option<String>,
   #[clap(long, requires = "css_modules", value_parser)]
   css_modules_dashed_idents: bool,
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_paint_worklets: bool,
   /// Enable sourcemap, at <output_file>.map
   #[clap(long, requires = "output_file", value_parser)]
   sourcemap: bool,
@@ -54,6 +70,98 @@ struct CliArgs {
   browserslist: bool,
   #[clap(long, value_parser)]
   error_recovery: bool,
+  /// Preserve comments in the output. When minifying, only `/*!` comments are kept.
+  #[clap(long, value_parser)]
+  preserve_comments: bool,
+  /// Scope all selectors under the given root selector, e.g. ".my-widget"
+  #[clap(long, value_parser)]
+  scope_under: Option<String>,
+  /// Rename classes and ids according to a JSON file, e.g. {"classes": {"foo": "a"}, "ids": {"bar": "b"}}
+  #[clap(long, value_parser)]
+  rename_map: Option<String>,
+  /// Rename @keyframes, @counter-style, container, and grid names to short identifiers,
+  /// and write a JSON file mapping the original names to the new ones
+  #[clap(long, value_parser)]
+  mangle_idents: Option<String>,
+  /// Substitute env() references to the given environment variable, e.g. safe-area-inset-top=0px.
+  /// May be repeated
+  #[clap(long = "env", value_parser)]
+  env: Vec<String>,
+  /// Remove vendor prefixed declarations and rules followed by their unprefixed equivalent,
+  /// when the targets no longer need the prefix
+  #[clap(long, requires = "targets-resolution", value_parser)]
+  remove_obsolete_prefixes: bool,
+  /// Remove legacy hacks for old versions of Internet Explorer, such as `_zoom: 1` and `color: red\9`,
+  /// and @-moz-document rules when the targets don't need them, along with obsolete prefixes
+  #[clap(long, requires = "targets-resolution", value_parser)]
+  remove_legacy_hacks: bool,
+  /// Remove !important from declarations when no other declaration in the stylesheet sets
+  /// an overlapping property
+  #[clap(long, value_parser)]
+  remove_unneeded_important: bool,
+  /// Write a JSON list of the !important declarations in the stylesheet, and whether each of them
+  /// is needed, to the given file
+  #[clap(long, value_parser)]
+  important_report: Option<String>,
+  /// Remove selectors with class names, ids, or tag names that don't appear in any of the given
+  /// HTML, JavaScript, or other content files. May be repeated
+  #[clap(long = "purge", value_parser)]
+  purge: Vec<String>,
+  /// A class name, id, or tag name to keep when using --purge. May be repeated
+  #[clap(long = "purge-safelist", requires = "purge", value_parser)]
+  purge_safelist: Vec<String>,
+  /// The optimization level. Level 2 enables transforms that assume no other CSS,
+  /// including inline styles, interacts with the output
+  #[clap(short = 'O', long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+  optimization_level: u8,
+  /// Print the number of bytes each top-level rule contributes to the output to stderr,
+  /// and optionally write the report to a JSON file
+  #[clap(long, value_parser)]
+  size_report: Option<Option<String>>,
+  /// Print the size of the output grouped by media query, layer, source file, and property to stderr,
+  /// and optionally write the breakdown to a JSON file
+  #[clap(long, value_parser)]
+  size_breakdown: Option<Option<String>>,
+  /// Print the features used by the stylesheet that the targets don't support and that can't be
+  /// transpiled to stderr, with their locations, and optionally write the report to a JSON file
+  #[clap(long, requires = "targets-resolution", value_parser)]
+  compat_report: Option<Option<String>>,
+  /// Replace @container rules with attribute selectors for a runtime container query polyfill,
+  /// and write a JSON manifest of the query containers and container queries to the given file
+  #[clap(long, value_parser)]
+  container_query_polyfill: Option<String>,
+  /// The attribute that the container query polyfill adds to elements while a query matches
+  #[clap(long, default_value = "data-cq", value_parser)]
+  container_query_attribute: String,
+  /// Write the @property rules as CSS.registerProperty() calls to the given file, or as a JSON list
+  /// of descriptors if it ends in .json. Nothing is written if all targets support @property
+  #[clap(long, value_parser)]
+  register_properties: Option<String>,
+  /// Remove the @property rules written by --register-properties from the output
+  #[clap(long, requires = "register-properties", value_parser)]
+  remove_property_rules: bool,
+  /// Write the colors, font stacks, spacing values, radii, and shadows used by the stylesheet to
+  /// the given file as a design tokens (DTCG) JSON document
+  #[clap(long, value_parser)]
+  design_tokens: Option<String>,
+  /// Only extract design tokens defined by custom properties
+  #[clap(long, requires = "design-tokens", value_parser)]
+  design_tokens_custom_properties_only: bool,
+  /// Write a JSON graph of the custom properties defined and referenced by the stylesheet to the
+  /// given file, including cycles and references to undefined custom properties
+  #[clap(long, value_parser)]
+  emit_var_graph: Option<String>,
+  /// Remove style rules that were never used according to the given Chrome DevTools CSS coverage
+  /// export. May be repeated to combine multiple recording sessions
+  #[clap(long, value_parser)]
+  coverage: Vec<String>,
+  /// Write a JSON list of the style rules removed by --coverage to the given file
+  #[clap(long, requires = "coverage", value_parser)]
+  coverage_report: Option<String>,
+  /// Apply the JSON Patch operations in the given file to the JSON AST of the stylesheet before
+  /// printing it
+  #[clap(long, conflicts_with = "css_modules", value_parser)]
+  ast_patch: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -114,6 +222,7 @@ pub fn main() -> Result<(), std::io::Error> {
     Some(lightningcss::css_modules::Config {
       pattern,
       dashed_idents: cli_args.css_modules_dashed_idents,
+      paint_worklets: cli_args.css_modules_paint_worklets,
       ..Default::default()
     })
   } else {
@@ -121,6 +230,80 @@ pub fn main() -> Result<(), std::io::Error> {
   };
 
   let fs = FileProvider::new();
+  let scope = if let Some(scope) = cli_args.scope_under.as_ref() {
+    match SelectorList::parse_string_with_options(scope, ParserOptions::default()) {
+      Ok(scope) => Some(scope),
+      Err(e) => {
+        eprintln!("Invalid --scope-under selector: {:?}", e.kind);
+        std::process::exit(1);
+      }
+    }
+  } else {
+    None
+  };
+
+  let mut environment_variables = HashMap::new();
+  for var in &cli_args.env {
+    match var.split_once('=') {
+      Some((name, value)) => {
+        environment_variables.insert(name.trim().to_owned(), value.trim().to_owned());
+      }
+      None => {
+        eprintln!("Invalid --env value, expected NAME=VALUE: {}", var);
+        std::process::exit(1);
+      }
+    }
+  }
+
+  let rename_map = if let Some(path) = cli_args.rename_map.as_ref() {
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str::<RenameMap>(&contents) {
+      Ok(map) => Some(map),
+      Err(e) => {
+        eprintln!("Invalid --rename-map file: {}", e);
+        std::process::exit(1);
+      }
+    }
+  } else {
+    None
+  };
+
+  let purge = if !cli_args.purge.is_empty() {
+    let mut purge = Purge::new();
+    for path in &cli_args.purge {
+      purge.add_content(&fs::read_to_string(path)?, &DefaultExtractor::new());
+    }
+    purge.safelist.extend(cli_args.purge_safelist.iter().cloned());
+    Some(purge)
+  } else {
+    None
+  };
+
+  let mut coverage = Coverage::default();
+  for path in &cli_args.coverage {
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str::<Coverage>(&contents) {
+      Ok(entries) => coverage.extend(entries),
+      Err(e) => {
+        eprintln!("Invalid --coverage file: {}", e);
+        std::process::exit(1);
+      }
+    }
+  }
+
+  let ast_patch = if let Some(path) = &cli_args.ast_patch {
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str::<JsonPatch>(&contents) {
+      Ok(patch) => Some(patch),
+      Err(e) => {
+        eprintln!("Invalid --ast-patch file: {}", e);
+        std::process::exit(1);
+      }
+    }
+  } else {
+    None
+  };
+
   let warnings = if cli_args.error_recovery {
     Some(Arc::new(RwLock::new(Vec::new())))
   } else {
@@ -140,6 +323,7 @@ pub fn main() -> Result<(), std::io::Error> {
       custom_media: cli_args.custom_media,
       error_recovery: cli_args.error_recovery,
       warnings: warnings.clone(),
+      preserve_comments: cli_args.preserve_comments,
       ..ParserOptions::default()
     };
 
@@ -155,6 +339,32 @@ pub fn main() -> Result<(), std::io::Error> {
       StyleSheet::parse(&source, options).unwrap()
     };
 
+    if !cli_args.coverage.is_empty() {
+      let unused = stylesheet.remove_unused_rules(&coverage);
+      if let Some(path) = &cli_args.coverage_report {
+        fs::write(path, serde_json::to_string(&unused)?)?;
+      }
+    }
+
+    if let Some(path) = &cli_args.important_report {
+      fs::write(path, serde_json::to_string(&stylesheet.important_audit())?)?;
+    }
+
+    if let Some(scope) = &scope {
+      stylesheet.scope(scope);
+    }
+
+    if let Some(map) = &rename_map {
+      stylesheet.visit(&mut SelectorRenamer::new(map)).unwrap();
+    }
+
+    if let Some(path) = &cli_args.container_query_polyfill {
+      let manifest = stylesheet
+        .polyfill_container_queries(&cli_args.container_query_attribute)
+        .unwrap();
+      fs::write(path, serde_json::to_string(&manifest)?)?;
+    }
+
     let targets = if !cli_args.targets.is_empty() {
       Browsers::from_browserslist(cli_args.targets).unwrap()
     } else if cli_args.browserslist {
@@ -163,22 +373,99 @@ pub fn main() -> Result<(), std::io::Error> {
       None
     };
 
-    stylesheet
+    if let Some(path) = &cli_args.register_properties {
+      let registrations = stylesheet
+        .extract_property_rules(targets, cli_args.remove_property_rules)
+        .unwrap();
+      if path.ends_with(".json") {
+        fs::write(path, serde_json::to_string(&registrations)?)?;
+      } else if !registrations.is_empty() {
+        let js: Vec<String> = registrations.iter().map(|registration| registration.to_js()).collect();
+        fs::write(path, js.join("\n") + "\n")?;
+      }
+    }
+
+    if let Some(path) = &cli_args.emit_var_graph {
+      let graph = stylesheet.var_graph();
+      let json = serde_json::json!({
+        "variables": &graph.variables,
+        "cycles": graph.cycles(),
+        "undefined": graph.undefined(),
+      });
+      fs::write(path, serde_json::to_string(&json)?)?;
+    }
+
+    if let Some(path) = &cli_args.design_tokens {
+      let tokens = stylesheet.design_tokens(DesignTokenOptions {
+        custom_properties_only: cli_args.design_tokens_custom_properties_only,
+      });
+      fs::write(path, serde_json::to_string(&tokens)?)?;
+    }
+
+    let minify_result = stylesheet
       .minify(MinifyOptions {
         targets,
+        mangle_idents: cli_args.mangle_idents.is_some(),
+        environment_variables,
+        remove_obsolete_prefixes: cli_args.remove_obsolete_prefixes,
+        remove_legacy_hacks: cli_args.remove_legacy_hacks,
+        remove_unneeded_important: cli_args.remove_unneeded_important,
+        purge,
+        optimization_level: if cli_args.optimization_level >= 2 {
+          OptimizationLevel::O2
+        } else {
+          OptimizationLevel::O1
+        },
         ..MinifyOptions::default()
       })
       .unwrap();
 
-    stylesheet
-      .to_css(PrinterOptions {
-        minify: cli_args.minify,
-        source_map: source_map.as_mut(),
-        project_root: Some(&project_root.to_string_lossy()),
-        targets,
-        ..PrinterOptions::default()
-      })
-      .unwrap()
+    if let Some(path) = &cli_args.mangle_idents {
+      fs::write(path, serde_json::to_string(&minify_result.mangled_idents)?)?;
+    }
+
+    if let Some(path) = &cli_args.custom_media_json {
+      fs::write(path, serde_json::to_string(&minify_result.custom_media)?)?;
+    }
+
+    if let Some(report) = &cli_args.compat_report {
+      let issues = stylesheet.compat_report(targets.unwrap_or_default());
+      for issue in &issues {
+        eprintln!("{}", issue);
+      }
+      if let Some(path) = report {
+        fs::write(path, serde_json::to_string(&issues)?)?;
+      }
+    }
+
+    let printer_options = PrinterOptions {
+      minify: cli_args.minify,
+      source_map: source_map.as_mut(),
+      project_root: Some(&project_root.to_string_lossy()),
+      targets,
+      size_report: cli_args.size_report.is_some() || cli_args.size_breakdown.is_some(),
+      ..PrinterOptions::default()
+    };
+
+    if let Some(patch) = &ast_patch {
+      let json = match stylesheet.to_patched_json(patch) {
+        Ok(json) => json,
+        Err(e) => {
+          eprintln!("{}", e);
+          std::process::exit(1);
+        }
+      };
+      let patched: Result<StyleSheet, _> = StyleSheet::deserialize(&json);
+      match patched {
+        Ok(patched) => patched.to_css(printer_options).unwrap(),
+        Err(e) => {
+          eprintln!("Invalid AST after applying --ast-patch: {}", e);
+          std::process::exit(1);
+        }
+      }
+    } else {
+      stylesheet.to_css(printer_options).unwrap()
+    }
   };
 
   let map = if let Some(ref mut source_map) = source_map {
@@ -207,6 +494,20 @@ pub fn main() -> Result<(), std::io::Error> {
     }
   }
 
+  if let (Some(size_report), Some(path)) = (&res.size_report, &cli_args.size_report) {
+    eprint!("{}", format_size_report(size_report));
+    if let Some(path) = path {
+      fs::write(path, serde_json::to_string(size_report)?)?;
+    }
+  }
+
+  if let (Some(size_breakdown), Some(path)) = (&res.size_breakdown, &cli_args.size_breakdown) {
+    eprint!("{}", format_size_breakdown(size_breakdown, res.code.len()));
+    if let Some(path) = path {
+      fs::write(path, serde_json::to_string(size_breakdown)?)?;
+    }
+  }
+
   if let Some(output_file) = &cli_args.output_file {
     let mut code = res.code;
     if cli_args.sourcemap {
@@ -251,6 +552,56 @@ pub fn main() -> Result<(), std::io::Error> {
   Ok(())
 }
 
+/// Formats a table of rule sizes, largest first.
+fn format_size_report(size_report: &[RuleSize]) -> String {
+  let total: usize = size_report.iter().map(|rule| rule.bytes).sum();
+  let mut rules: Vec<&RuleSize> = size_report.iter().collect();
+  rules.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+  let mut table = format!("{:>8}  {:>6}  {}\n", "Bytes", "%", "Rule");
+  for rule in rules {
+    let percent = if total > 0 {
+      rule.bytes as f64 * 100.0 / total as f64
+    } else {
+      0.0
+    };
+    let mut prelude: String = rule.prelude.chars().take(80).collect();
+    if prelude.len() < rule.prelude.len() {
+      prelude.push('…');
+    }
+    table += &format!("{:>8}  {:>5.1}%  {}\n", rule.bytes, percent, prelude);
+  }
+  table += &format!("{:>8}  {:>5.1}%  {}\n", total, 100.0, "Total");
+  table
+}
+
+/// Formats a table for each group of the size breakdown that is not empty, with percentages of the total output size.
+fn format_size_breakdown(size_breakdown: &SizeBreakdown, total: usize) -> String {
+  let mut tables = Vec::new();
+  for (title, groups) in [
+    ("Media query", &size_breakdown.media_queries),
+    ("Layer", &size_breakdown.layers),
+    ("Source", &size_breakdown.sources),
+    ("Property", &size_breakdown.properties),
+  ] {
+    if groups.is_empty() {
+      continue;
+    }
+
+    let mut table = format!("{:>8}  {:>6}  {}\n", "Bytes", "%", title);
+    for SizeGroup { name, bytes } in groups {
+      let percent = if total > 0 {
+        *bytes as f64 * 100.0 / total as f64
+      } else {
+        0.0
+      };
+      table += &format!("{:>8}  {:>5.1}%  {}\n", bytes, percent, name);
+    }
+    tables.push(table);
+  }
+  tables.join("\n")
+}
+
 fn infer_css_modules_filename(output_file: &str) -> Result<String, std::io::Error> {
   let path = path::Path::new(output_file);
   if path.extension() == Some(ffi::OsStr::new("json")) {
@@ -0,0 +1,374 @@
+//! Renaming of author-defined identifiers to shorter names.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::animation::AnimationName;
+use crate::properties::contain::ContainerNameList;
+#[cfg(feature = "grid")]
+use crate::properties::grid::{GridLine, GridTemplateAreas, TrackListItem, TrackSizing};
+use crate::properties::list::{CounterStyle, ListStyleType};
+use crate::properties::Property;
+use crate::rules::counter_style::{CounterStyleProperty, CounterSystem, SpeakAs};
+use crate::rules::keyframes::KeyframesName;
+use crate::rules::{collect_token_values, CssRule, CssRuleList};
+use crate::stylesheet::MangledIdents;
+use crate::values::ident::CustomIdent;
+use crate::values::string::CowArcStr;
+use std::collections::{BTreeMap, HashSet};
+
+/// Identifiers that cannot be used as a name in at least one of the mangled contexts.
+const RESERVED: &[&str] = &[
+  "none", "auto", "span", "and", "or", "not", "only", "default", "inherit", "initial", "unset", "revert",
+];
+
+#[derive(Default)]
+struct Names {
+  /// Names defined in the style sheet, in order of first definition.
+  defined: Vec<String>,
+  /// All names defined or referenced in the style sheet.
+  seen: HashSet<String>,
+  map: BTreeMap<String, String>,
+}
+
+impl Names {
+  fn add(&mut self, name: &str, define: bool) {
+    self.seen.insert(name.to_owned());
+    if define && !self.defined.iter().any(|n| n == name) {
+      self.defined.push(name.to_owned());
+    }
+  }
+
+  /// Assigns a short name to each defined name that is not referenced by an unparsed value.
+  fn assign(&mut self, tokens: &HashSet<String>, excluded: impl Fn(&str) -> bool) {
+    let mut index = 0;
+    for name in &self.defined {
+      if excluded(name) {
+        continue;
+      }
+
+      let short = loop {
+        let short = short_name(index);
+        index += 1;
+        if !self.seen.contains(&short)
+          && !tokens.contains(&short)
+          && !RESERVED.iter().any(|r| r.eq_ignore_ascii_case(&short))
+        {
+          break short;
+        }
+      };
+      self.map.insert(name.clone(), short);
+    }
+  }
+}
+
+/// Returns the identifier at the given index in the sequence `a`..`Z`, `aa`, `ba`, etc.
+fn short_name(mut index: usize) -> String {
+  const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+  const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+  let mut name = String::new();
+  name.push(FIRST[index % FIRST.len()] as char);
+  index /= FIRST.len();
+  while index > 0 {
+    index -= 1;
+    name.push(REST[index % REST.len()] as char);
+    index /= REST.len();
+  }
+  name
+}
+
+/// Grid line names ending in `-start` or `-end` are implicitly associated with the area of the
+/// same name, so they are renamed along with it.
+#[cfg(feature = "grid")]
+fn split_grid_name(name: &str) -> (&str, &str) {
+  for suffix in ["-start", "-end"] {
+    if let Some(base) = name.strip_suffix(suffix) {
+      if !base.is_empty() {
+        return (base, suffix);
+      }
+    }
+  }
+  (name, "")
+}
+
+#[derive(Default)]
+struct Mangler {
+  renaming: bool,
+  tokens: HashSet<String>,
+  keyframes: Names,
+  counter_styles: Names,
+  container_names: Names,
+  #[cfg(feature = "grid")]
+  grid_names: Names,
+}
+
+impl Mangler {
+  fn ident(&mut self, names: fn(&mut Self) -> &mut Names, name: &mut CowArcStr, define: bool) -> bool {
+    let renaming = self.renaming;
+    let names = names(self);
+    if !renaming {
+      names.add(name, define);
+      return false;
+    }
+
+    if let Some(short) = names.map.get(name.as_ref()) {
+      *name = short.clone().into();
+      return true;
+    }
+    false
+  }
+
+  #[cfg(feature = "grid")]
+  fn grid_ident(&mut self, name: &mut CowArcStr, define: bool) {
+    let (base, suffix) = split_grid_name(name);
+    if !self.renaming {
+      self.grid_names.add(base, define);
+    } else if let Some(short) = self.grid_names.map.get(base) {
+      *name = format!("{}{}", short, suffix).into();
+    }
+  }
+
+  fn keyframes_name(&mut self, name: &mut KeyframesName) {
+    match name {
+      KeyframesName::Ident(ident) => {
+        self.ident(|m| &mut m.keyframes, &mut ident.0, true);
+      }
+      KeyframesName::Custom(s) => {
+        if self.ident(|m| &mut m.keyframes, s, true) {
+          *name = KeyframesName::Ident(CustomIdent(s.clone()));
+        }
+      }
+    }
+  }
+
+  fn animation_name(&mut self, name: &mut AnimationName) {
+    match name {
+      AnimationName::Ident(ident) => {
+        self.ident(|m| &mut m.keyframes, &mut ident.0, false);
+      }
+      AnimationName::String(s) => {
+        if self.ident(|m| &mut m.keyframes, s, false) {
+          *name = AnimationName::Ident(CustomIdent(s.clone()));
+        }
+      }
+      AnimationName::None => {}
+    }
+  }
+
+  fn list_style_type(&mut self, list_style_type: &mut ListStyleType) {
+    if let ListStyleType::CounterStyle(counter_style) = list_style_type {
+      self.counter_style(counter_style);
+    }
+  }
+
+  fn counter_style(&mut self, counter_style: &mut CounterStyle) {
+    if let CounterStyle::Name(ident) = counter_style {
+      self.ident(|m| &mut m.counter_styles, &mut ident.0, false);
+    }
+  }
+
+  fn container_names(&mut self, names: &mut ContainerNameList) {
+    if let ContainerNameList::Names(names) = names {
+      for name in names {
+        self.ident(|m| &mut m.container_names, &mut (name.0).0, true);
+      }
+    }
+  }
+
+  #[cfg(feature = "grid")]
+  fn track_sizing(&mut self, sizing: &mut TrackSizing) {
+    if let TrackSizing::TrackList(list) = sizing {
+      for name in list.line_names.iter_mut().flatten() {
+        self.grid_ident(&mut name.0, true);
+      }
+      for item in &mut list.items {
+        if let TrackListItem::TrackRepeat(repeat) = item {
+          for name in repeat.line_names.iter_mut().flatten() {
+            self.grid_ident(&mut name.0, true);
+          }
+        }
+      }
+    }
+  }
+
+  #[cfg(feature = "grid")]
+  fn grid_areas(&mut self, areas: &mut GridTemplateAreas) {
+    if let GridTemplateAreas::Areas { areas, .. } = areas {
+      for area in areas.iter_mut().flatten() {
+        let mut name = CowArcStr::from(area.clone());
+        self.grid_ident(&mut name, true);
+        *area = name.to_string();
+      }
+    }
+  }
+
+  #[cfg(feature = "grid")]
+  fn grid_line(&mut self, line: &mut GridLine) {
+    match line {
+      GridLine::Area { name }
+      | GridLine::Line { name: Some(name), .. }
+      | GridLine::Span { name: Some(name), .. } => self.grid_ident(&mut name.0, false),
+      _ => {}
+    }
+  }
+
+  fn declarations(&mut self, declarations: &mut DeclarationBlock) {
+    for property in declarations
+      .declarations
+      .iter_mut()
+      .chain(declarations.important_declarations.iter_mut())
+    {
+      match property {
+        Property::AnimationName(names, _) => names.iter_mut().for_each(|name| self.animation_name(name)),
+        Property::Animation(animations, _) => animations
+          .iter_mut()
+          .for_each(|animation| self.animation_name(&mut animation.name)),
+        Property::ListStyleType(list_style_type) => self.list_style_type(list_style_type),
+        Property::ListStyle(list_style) => self.list_style_type(&mut list_style.list_style_type),
+        Property::ContainerName(names) => self.container_names(names),
+        Property::Container(container) => self.container_names(&mut container.name),
+        #[cfg(feature = "grid")]
+        Property::GridTemplateColumns(sizing) | Property::GridTemplateRows(sizing) => self.track_sizing(sizing),
+        #[cfg(feature = "grid")]
+        Property::GridTemplateAreas(areas) => self.grid_areas(areas),
+        #[cfg(feature = "grid")]
+        Property::GridTemplate(template) => {
+          self.track_sizing(&mut template.rows);
+          self.track_sizing(&mut template.columns);
+          self.grid_areas(&mut template.areas);
+        }
+        #[cfg(feature = "grid")]
+        Property::Grid(grid) => {
+          self.track_sizing(&mut grid.rows);
+          self.track_sizing(&mut grid.columns);
+          self.grid_areas(&mut grid.areas);
+        }
+        #[cfg(feature = "grid")]
+        Property::GridRowStart(line)
+        | Property::GridRowEnd(line)
+        | Property::GridColumnStart(line)
+        | Property::GridColumnEnd(line) => self.grid_line(line),
+        #[cfg(feature = "grid")]
+        Property::GridRow(row) => {
+          self.grid_line(&mut row.start);
+          self.grid_line(&mut row.end);
+        }
+        #[cfg(feature = "grid")]
+        Property::GridColumn(column) => {
+          self.grid_line(&mut column.start);
+          self.grid_line(&mut column.end);
+        }
+        #[cfg(feature = "grid")]
+        Property::GridArea(area) => {
+          self.grid_line(&mut area.row_start);
+          self.grid_line(&mut area.column_start);
+          self.grid_line(&mut area.row_end);
+          self.grid_line(&mut area.column_end);
+        }
+        Property::Unparsed(unparsed) if !self.renaming => collect_token_values(&unparsed.value, &mut self.tokens),
+        Property::Custom(custom) if !self.renaming => collect_token_values(&custom.value, &mut self.tokens),
+        _ => {}
+      }
+    }
+  }
+
+  fn rules<T>(&mut self, rules: &mut CssRuleList<T>) {
+    for rule in &mut rules.0 {
+      match rule {
+        CssRule::Style(style) => {
+          self.declarations(&mut style.declarations);
+          self.rules(&mut style.rules);
+        }
+        CssRule::Nesting(nesting) => {
+          self.declarations(&mut nesting.style.declarations);
+          self.rules(&mut nesting.style.rules);
+        }
+        CssRule::Media(media) => self.rules(&mut media.rules),
+        CssRule::Supports(supports) => self.rules(&mut supports.rules),
+        CssRule::LayerBlock(layer) => self.rules(&mut layer.rules),
+        CssRule::MozDocument(document) => self.rules(&mut document.rules),
+        CssRule::Container(container) => {
+          if let Some(name) = &mut container.name {
+            self.ident(|m| &mut m.container_names, &mut (name.0).0, false);
+          }
+          self.rules(&mut container.rules);
+        }
+        CssRule::Scope(scope) => self.rules(&mut scope.rules),
+        CssRule::StartingStyle(rule) => self.rules(&mut rule.rules),
+        CssRule::Keyframes(keyframes) => {
+          self.keyframes_name(&mut keyframes.name);
+          for keyframe in &mut keyframes.keyframes {
+            self.declarations(&mut keyframe.declarations);
+          }
+        }
+        CssRule::CounterStyle(counter_style) => {
+          self.ident(|m| &mut m.counter_styles, &mut counter_style.name.0, true);
+          for property in &mut counter_style.properties {
+            match property {
+              CounterStyleProperty::System(CounterSystem::Extends(name))
+              | CounterStyleProperty::Fallback(name)
+              | CounterStyleProperty::SpeakAs(SpeakAs::CounterStyle(name)) => self.counter_style(name),
+              _ => {}
+            }
+          }
+        }
+        CssRule::Page(page) => {
+          self.declarations(&mut page.declarations);
+          for margin_rule in &mut page.rules {
+            self.declarations(&mut margin_rule.declarations);
+          }
+        }
+        CssRule::Viewport(viewport) => self.declarations(&mut viewport.declarations),
+        CssRule::PositionTry(position_try) => self.declarations(&mut position_try.declarations),
+        CssRule::NestedDeclarations(nested) => self.declarations(&mut nested.declarations),
+        _ => {}
+      }
+    }
+  }
+}
+
+/// Renames `@keyframes`, `@counter-style`, container, and grid line and area names defined in the
+/// rule list to short identifiers, and updates all references to them. Names that appear in
+/// unparsed values (e.g. custom properties) cannot be updated reliably, so they are left as is.
+pub(crate) fn mangle_idents<T>(rules: &mut CssRuleList<T>) -> MangledIdents {
+  let mut result = MangledIdents::default();
+  let mut unknown = false;
+  rules.for_each_rule(&mut |rule| {
+    unknown |= matches!(
+      rule,
+      CssRule::Unknown(..) | CssRule::CustomAtRule(..) | CssRule::Custom(..)
+    )
+  });
+  if unknown {
+    return result;
+  }
+
+  let mut mangler = Mangler::default();
+  mangler.rules(rules);
+
+  let tokens: HashSet<String> = mangler
+    .tokens
+    .iter()
+    .flat_map(|token| std::iter::once(token.as_str()).chain(token.split_whitespace()))
+    .map(|token| token.to_owned())
+    .collect();
+  mangler.keyframes.assign(&tokens, |name| tokens.contains(name));
+  mangler.counter_styles.assign(&tokens, |name| tokens.contains(name));
+  mangler.container_names.assign(&tokens, |name| tokens.contains(name));
+  #[cfg(feature = "grid")]
+  mangler.grid_names.assign(&tokens, |name| {
+    tokens.contains(name)
+      || tokens.contains(&format!("{}-start", name))
+      || tokens.contains(&format!("{}-end", name))
+  });
+
+  mangler.renaming = true;
+  mangler.rules(rules);
+
+  result.keyframes = std::mem::take(&mut mangler.keyframes.map);
+  result.counter_styles = std::mem::take(&mut mangler.counter_styles.map);
+  result.container_names = std::mem::take(&mut mangler.container_names.map);
+  #[cfg(feature = "grid")]
+  {
+    result.grid_names = std::mem::take(&mut mangler.grid_names.map);
+  }
+  result
+}
@@ -68,6 +68,17 @@ impl<'i> MediaList<'i> {
     Ok(MediaList { media_queries })
   }
 
+  /// Creates a media query list that always (`all`) or never (`not all`) matches.
+  pub fn from_constant(value: bool) -> Self {
+    MediaList {
+      media_queries: vec![MediaQuery {
+        qualifier: if value { None } else { Some(Qualifier::Not) },
+        media_type: MediaType::All,
+        condition: None,
+      }],
+    }
+  }
+
   pub(crate) fn transform_custom_media(
     &mut self,
     loc: Location,
@@ -280,7 +291,7 @@ impl<'i> MediaQuery<'i> {
     custom_media: &HashMap<CowArcStr<'i>, CustomMediaRule<'i>>,
   ) -> Result<(), MinifyError> {
     if let Some(condition) = &mut self.condition {
-      let used = process_condition(
+      let res = process_condition(
         loc,
         custom_media,
         &mut self.media_type,
@@ -288,8 +299,15 @@ impl<'i> MediaQuery<'i> {
         condition,
         &mut HashSet::new(),
       )?;
-      if !used {
-        self.condition = None;
+      match res {
+        ProcessedCondition::Used => {}
+        ProcessedCondition::Unused | ProcessedCondition::Constant(true) => self.condition = None,
+        ProcessedCondition::Constant(false) => {
+          // The whole query can never match.
+          self.qualifier = Some(Qualifier::Not);
+          self.media_type = MediaType::All;
+          self.condition = None;
+        }
       }
     }
     Ok(())
@@ -608,6 +626,17 @@ impl ToCss for MediaFeatureComparison {
 }
 
 impl MediaFeatureComparison {
+  /// Returns the comparison that matches exactly when this one does not.
+  fn negate(&self) -> MediaFeatureComparison {
+    match self {
+      MediaFeatureComparison::GreaterThan => MediaFeatureComparison::LessThanEqual,
+      MediaFeatureComparison::GreaterThanEqual => MediaFeatureComparison::LessThan,
+      MediaFeatureComparison::LessThan => MediaFeatureComparison::GreaterThanEqual,
+      MediaFeatureComparison::LessThanEqual => MediaFeatureComparison::GreaterThan,
+      MediaFeatureComparison::Equal => MediaFeatureComparison::Equal,
+    }
+  }
+
   fn opposite(&self) -> MediaFeatureComparison {
     match self {
       MediaFeatureComparison::GreaterThan => MediaFeatureComparison::LessThan,
@@ -954,6 +983,17 @@ fn consume_operation_or_colon<'i, 't>(
   }))
 }
 
+/// The result of resolving custom media references within a media condition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProcessedCondition {
+  /// The condition remains and should be kept.
+  Used,
+  /// The condition was replaced by a media type and qualifier, and should be removed.
+  Unused,
+  /// The condition always (`true`) or never (`false`) matches.
+  Constant(bool),
+}
+
 fn process_condition<'i>(
   loc: Location,
   custom_media: &HashMap<CowArcStr<'i>, CustomMediaRule<'i>>,
@@ -961,19 +1001,22 @@ fn process_condition<'i>(
   qualifier: &mut Option<Qualifier>,
   condition: &mut MediaCondition<'i>,
   seen: &mut HashSet<Ident<'i>>,
-) -> Result<bool, MinifyError> {
+) -> Result<ProcessedCondition, MinifyError> {
   match condition {
     MediaCondition::Not(cond) => {
-      let used = process_condition(loc, custom_media, media_type, qualifier, &mut *cond, seen)?;
-      if !used {
-        // If unused, only a media type remains so apply a not qualifier.
-        // If it is already not, then it cancels out.
-        *qualifier = if *qualifier == Some(Qualifier::Not) {
-          None
-        } else {
-          Some(Qualifier::Not)
-        };
-        return Ok(false);
+      match process_condition(loc, custom_media, media_type, qualifier, &mut *cond, seen)? {
+        ProcessedCondition::Used => {}
+        ProcessedCondition::Unused => {
+          // If unused, only a media type remains so apply a not qualifier.
+          // If it is already not, then it cancels out.
+          *qualifier = if *qualifier == Some(Qualifier::Not) {
+            None
+          } else {
+            Some(Qualifier::Not)
+          };
+          return Ok(ProcessedCondition::Unused);
+        }
+        ProcessedCondition::Constant(value) => return Ok(ProcessedCondition::Constant(!value)),
       }
 
       // Unwrap nested nots
@@ -984,22 +1027,51 @@ fn process_condition<'i>(
         _ => {}
       }
     }
-    MediaCondition::Operation { conditions, .. } => {
-      let mut res = Ok(true);
+    MediaCondition::Operation { conditions, operator } => {
+      // A constant that matches the identity of the operator (true for and, false for or)
+      // can be removed, otherwise it determines the result of the whole operation.
+      let identity = *operator == Operator::And;
+      let mut res = Ok(ProcessedCondition::Used);
+      let mut has_unused = false;
+      let mut result = None;
       conditions.retain_mut(|condition| {
-        let r = process_condition(loc, custom_media, media_type, qualifier, condition, seen);
-        if let Ok(used) = r {
-          used
-        } else {
-          res = r;
-          false
+        if res.is_err() || result.is_some() {
+          return false;
+        }
+        match process_condition(loc, custom_media, media_type, qualifier, condition, seen) {
+          Ok(ProcessedCondition::Used) => true,
+          Ok(ProcessedCondition::Unused) => {
+            has_unused = true;
+            false
+          }
+          Ok(ProcessedCondition::Constant(value)) => {
+            if value != identity {
+              result = Some(value);
+            }
+            false
+          }
+          Err(err) => {
+            res = Err(err);
+            false
+          }
         }
       });
-      return res;
+      res?;
+      if let Some(value) = result {
+        return Ok(ProcessedCondition::Constant(value));
+      }
+      if conditions.is_empty() {
+        return Ok(if has_unused {
+          ProcessedCondition::Unused
+        } else {
+          ProcessedCondition::Constant(identity)
+        });
+      }
+      return Ok(ProcessedCondition::Used);
     }
     MediaCondition::Feature(MediaFeature::Boolean { name }) => {
       if !name.starts_with("--") {
-        return Ok(true);
+        return Ok(ProcessedCondition::Used);
       }
 
       if seen.contains(name) {
@@ -1014,19 +1086,31 @@ fn process_condition<'i>(
         loc,
       })?;
 
+      // A query that always matches makes the whole custom media match.
+      // Queries that never match do not contribute anything to the result.
+      if rule.query.media_queries.iter().any(|query| query.always_matches()) {
+        return Ok(ProcessedCondition::Constant(true));
+      }
+      if rule.query.media_queries.iter().all(|query| query.never_matches()) {
+        return Ok(ProcessedCondition::Constant(false));
+      }
+
       seen.insert(name.clone());
 
-      let mut res = Ok(true);
+      let mut res = Ok(());
+      let mut constant = None;
+      let mut unused = false;
       let mut conditions: Vec<MediaCondition> = rule
         .query
         .media_queries
         .iter()
+        .filter(|query| !query.never_matches())
         .filter_map(|query| {
           if query.media_type != MediaType::All || query.qualifier != None {
             if *media_type == MediaType::All {
               // `not all` will never match.
               if *qualifier == Some(Qualifier::Not) {
-                res = Ok(false);
+                unused = true;
                 return None;
               }
 
@@ -1047,29 +1131,44 @@ fn process_condition<'i>(
 
           if let Some(condition) = &query.condition {
             let mut condition = condition.clone();
-            let r = process_condition(loc, custom_media, media_type, qualifier, &mut condition, seen);
-            if r.is_err() {
-              res = r;
-            }
-            // Parentheses are required around the condition unless there is a single media feature.
-            match condition {
-              MediaCondition::Feature(..) => Some(condition),
-              _ => Some(condition),
+            match process_condition(loc, custom_media, media_type, qualifier, &mut condition, seen) {
+              Ok(ProcessedCondition::Used) => Some(condition),
+              Ok(ProcessedCondition::Unused) => {
+                unused = true;
+                None
+              }
+              Ok(ProcessedCondition::Constant(value)) => {
+                // Within a list of queries, a match makes the whole list match.
+                if value {
+                  constant = Some(true);
+                }
+                None
+              }
+              Err(err) => {
+                res = Err(err);
+                None
+              }
             }
           } else {
+            unused = true;
             None
           }
         })
         .collect();
 
       seen.remove(name);
+      res?;
 
-      if res.is_err() {
-        return res;
+      if let Some(value) = constant {
+        return Ok(ProcessedCondition::Constant(value));
       }
 
       if conditions.is_empty() {
-        return Ok(false);
+        return Ok(if unused {
+          ProcessedCondition::Unused
+        } else {
+          ProcessedCondition::Constant(false)
+        });
       }
 
       if conditions.len() == 1 {
@@ -1084,7 +1183,801 @@ fn process_condition<'i>(
     _ => {}
   }
 
-  Ok(true)
+  Ok(ProcessedCondition::Used)
+}
+
+/// A static environment to evaluate media queries against, e.g. to generate separate builds
+/// for different classes of devices. Anything that is not specified is treated as unknown, and
+/// conditions that depend on it are left to be evaluated by the browser.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaEnvironment {
+  /// The media type, e.g. `screen` or `print`.
+  pub media_type: Option<MediaType<'static>>,
+  /// The minimum viewport width, in pixels.
+  pub min_width: Option<f32>,
+  /// The maximum viewport width, in pixels.
+  pub max_width: Option<f32>,
+  /// The minimum viewport height, in pixels.
+  pub min_height: Option<f32>,
+  /// The maximum viewport height, in pixels.
+  pub max_height: Option<f32>,
+  /// Values of other discrete media features by name, e.g. `hover` as `none`,
+  /// or `prefers-color-scheme` as `dark`.
+  pub features: HashMap<String, String>,
+}
+
+/// Combines two optional results with `and`, where `None` means the result is unknown.
+fn and_matches(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+  match (a, b) {
+    (Some(false), _) | (_, Some(false)) => Some(false),
+    (Some(true), Some(true)) => Some(true),
+    _ => None,
+  }
+}
+
+/// Combines two optional results with `or`, where `None` means the result is unknown.
+fn or_matches(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+  match (a, b) {
+    (Some(true), _) | (_, Some(true)) => Some(true),
+    (Some(false), Some(false)) => Some(false),
+    _ => None,
+  }
+}
+
+impl<'i> MediaList<'i> {
+  /// Evaluates the media query list in the given environment. Returns `None` if the result
+  /// depends on something the environment does not specify.
+  pub(crate) fn evaluate(&self, environment: &MediaEnvironment) -> Option<bool> {
+    self
+      .media_queries
+      .iter()
+      .fold(Some(self.media_queries.is_empty()), |result, query| {
+        or_matches(result, query.evaluate(environment))
+      })
+  }
+}
+
+impl<'i> MediaQuery<'i> {
+  fn evaluate(&self, environment: &MediaEnvironment) -> Option<bool> {
+    let media_type = match &self.media_type {
+      MediaType::All => Some(true),
+      media_type => environment.media_type.as_ref().map(|t| media_type == t),
+    };
+    let condition = self.condition.as_ref().map_or(Some(true), |c| c.evaluate(environment));
+    let matches = and_matches(media_type, condition);
+    if self.qualifier == Some(Qualifier::Not) {
+      matches.map(|matches| !matches)
+    } else {
+      matches
+    }
+  }
+}
+
+impl<'i> MediaCondition<'i> {
+  fn evaluate(&self, environment: &MediaEnvironment) -> Option<bool> {
+    match self {
+      MediaCondition::Feature(feature) => feature.evaluate(environment),
+      MediaCondition::Not(condition) => condition.evaluate(environment).map(|matches| !matches),
+      MediaCondition::Operation { operator, conditions } => {
+        let iter = conditions.iter().map(|condition| condition.evaluate(environment));
+        match operator {
+          Operator::And => iter.fold(Some(true), and_matches),
+          Operator::Or => iter.fold(Some(false), or_matches),
+        }
+      }
+    }
+  }
+}
+
+impl<'i> MediaFeature<'i> {
+  fn evaluate(&self, environment: &MediaEnvironment) -> Option<bool> {
+    match self {
+      MediaFeature::Plain { name, value } => {
+        let name = name.0.as_ref();
+        let (dimension, operator) = if let Some(dimension) = name.strip_prefix("min-") {
+          (dimension, MediaFeatureComparison::GreaterThanEqual)
+        } else if let Some(dimension) = name.strip_prefix("max-") {
+          (dimension, MediaFeatureComparison::LessThanEqual)
+        } else {
+          (name, MediaFeatureComparison::Equal)
+        };
+        if let Some(range) = environment.range(dimension) {
+          return evaluate_range(range, operator, value);
+        }
+
+        let expected = environment.features.get(name)?;
+        match value {
+          MediaFeatureValue::Ident(ident) => Some(expected.eq_ignore_ascii_case(ident.0.as_ref())),
+          MediaFeatureValue::Number(number) => expected.parse::<f32>().ok().map(|n| n == *number),
+          _ => None,
+        }
+      }
+      MediaFeature::Boolean { name } => {
+        let value = environment.features.get(name.0.as_ref())?;
+        Some(!matches!(value.as_str(), "none" | "0" | "no-preference"))
+      }
+      MediaFeature::Range { name, operator, value } => {
+        evaluate_range(environment.range(name.0.as_ref())?, *operator, value)
+      }
+      MediaFeature::Interval {
+        name,
+        start,
+        start_operator,
+        end,
+        end_operator,
+      } => {
+        let range = environment.range(name.0.as_ref())?;
+        and_matches(
+          evaluate_range(range, start_operator.opposite(), start),
+          evaluate_range(range, *end_operator, end),
+        )
+      }
+    }
+  }
+}
+
+impl MediaEnvironment {
+  /// Returns the known range of the given viewport dimension, if any.
+  fn range(&self, name: &str) -> Option<(f32, f32)> {
+    let (min, max) = match name {
+      "width" => (self.min_width, self.max_width),
+      "height" => (self.min_height, self.max_height),
+      _ => return None,
+    };
+    if min.is_none() && max.is_none() {
+      return None;
+    }
+    Some((min.unwrap_or(0.0), max.unwrap_or(f32::INFINITY)))
+  }
+}
+
+/// Evaluates `dimension <operator> value` for every dimension within the given range.
+fn evaluate_range(
+  (min, max): (f32, f32),
+  operator: MediaFeatureComparison,
+  value: &MediaFeatureValue,
+) -> Option<bool> {
+  let value = match value {
+    MediaFeatureValue::Length(length) => length.to_px()?,
+    _ => return None,
+  };
+
+  let (always, never) = match operator {
+    MediaFeatureComparison::GreaterThanEqual => (min >= value, max < value),
+    MediaFeatureComparison::GreaterThan => (min > value, max <= value),
+    MediaFeatureComparison::LessThanEqual => (max <= value, min > value),
+    MediaFeatureComparison::LessThan => (max < value, min >= value),
+    MediaFeatureComparison::Equal => (min == value && max == value, value < min || value > max),
+  };
+
+  if always {
+    Some(true)
+  } else if never {
+    Some(false)
+  } else {
+    None
+  }
+}
+
+enum_property! {
+  /// A value for the [forced-colors](https://drafts.csswg.org/mediaqueries-5/#forced-colors) media feature.
+  pub enum ForcedColors {
+    /// Forced colors mode is not active.
+    "none": None,
+    /// Forced colors mode is active.
+    "active": Active,
+  }
+}
+
+enum_property! {
+  /// A value for the [prefers-contrast](https://drafts.csswg.org/mediaqueries-5/#prefers-contrast) media feature.
+  pub enum PrefersContrast {
+    /// The user has not expressed a preference.
+    "no-preference": NoPreference,
+    /// The user prefers more contrast.
+    "more": More,
+    /// The user prefers less contrast.
+    "less": Less,
+    /// The user prefers a specific set of colors that is neither more nor less contrasting.
+    "custom": Custom,
+  }
+}
+
+enum_property! {
+  /// A value for the [prefers-reduced-data](https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-data) media feature.
+  pub enum PrefersReducedData {
+    /// The user has not expressed a preference.
+    "no-preference": NoPreference,
+    /// The user prefers to use less data.
+    "reduce": Reduce,
+  }
+}
+
+enum_property! {
+  /// A value for the [inverted-colors](https://drafts.csswg.org/mediaqueries-5/#inverted) media feature.
+  pub enum InvertedColors {
+    /// Colors are displayed normally.
+    "none": None,
+    /// All pixels are displayed in their inverse color.
+    "inverted": Inverted,
+  }
+}
+
+enum_property! {
+  /// A value for the [scripting](https://drafts.csswg.org/mediaqueries-5/#scripting) media feature.
+  pub enum Scripting {
+    /// Scripting is not available.
+    "none": None,
+    /// Scripting is only available during the initial page load.
+    "initial-only": InitialOnly,
+    /// Scripting is available.
+    "enabled": Enabled,
+  }
+}
+
+enum_property! {
+  /// A value for the [update](https://drafts.csswg.org/mediaqueries-4/#update) media feature.
+  pub enum Update {
+    /// The layout cannot change once rendered, e.g. when printed.
+    "none": None,
+    /// The layout may change, but not quickly enough to be perceived as smooth animation.
+    "slow": Slow,
+    /// The layout may change quickly, e.g. on a computer screen.
+    "fast": Fast,
+  }
+}
+
+enum_property! {
+  /// A value for the [overflow-block](https://drafts.csswg.org/mediaqueries-4/#mf-overflow-block) media feature.
+  pub enum OverflowBlock {
+    /// Content that overflows the block axis is not displayed.
+    "none": None,
+    /// Content that overflows the block axis can be scrolled to.
+    "scroll": Scroll,
+    /// Content is broken up into pages.
+    "paged": Paged,
+  }
+}
+
+enum_property! {
+  /// A value for the [overflow-inline](https://drafts.csswg.org/mediaqueries-4/#mf-overflow-inline) media feature.
+  pub enum OverflowInline {
+    /// Content that overflows the inline axis is not displayed.
+    "none": None,
+    /// Content that overflows the inline axis can be scrolled to.
+    "scroll": Scroll,
+  }
+}
+
+/// A compatibility feature for a discrete media feature, along with a function that validates its values.
+type DiscreteFeature = (Feature, fn(&str) -> bool);
+
+/// Returns the compatibility feature and value validator for a discrete media feature.
+fn discrete_feature(name: &str) -> Option<DiscreteFeature> {
+  macro_rules! discrete {
+    ($feature: ident, $ty: ty) => {
+      ($feature, |value| <$ty>::parse_string(value).is_ok())
+    };
+  }
+
+  use Feature::*;
+  Some(match_ignore_ascii_case! { name,
+    "forced-colors" => discrete!(MediaForcedColors, ForcedColors),
+    "prefers-contrast" => discrete!(MediaPrefersContrast, PrefersContrast),
+    "prefers-reduced-data" => discrete!(MediaPrefersReducedData, PrefersReducedData),
+    "inverted-colors" => discrete!(MediaInvertedColors, InvertedColors),
+    "scripting" => discrete!(MediaScripting, Scripting),
+    "update" => discrete!(MediaUpdate, Update),
+    "overflow-block" => discrete!(MediaOverflowBlock, OverflowBlock),
+    "overflow-inline" => discrete!(MediaOverflowInline, OverflowInline),
+    _ => return None
+  })
+}
+
+/// The set of possible results of a media condition across browsers: true, false, or unknown,
+/// where a condition is unknown in browsers that don't support one of its features.
+#[derive(Clone, Copy, PartialEq)]
+struct PossibleResults(u8);
+
+impl PossibleResults {
+  const TRUE: u8 = 0b001;
+  const FALSE: u8 = 0b010;
+  const UNKNOWN: u8 = 0b100;
+  const VALUES: [u8; 3] = [Self::TRUE, Self::FALSE, Self::UNKNOWN];
+
+  fn can_match(self) -> bool {
+    self.0 & Self::TRUE != 0
+  }
+
+  fn not(self) -> PossibleResults {
+    let mut res = self.0 & Self::UNKNOWN;
+    if self.0 & Self::TRUE != 0 {
+      res |= Self::FALSE;
+    }
+    if self.0 & Self::FALSE != 0 {
+      res |= Self::TRUE;
+    }
+    PossibleResults(res)
+  }
+
+  fn combine(self, other: PossibleResults, operator: Operator) -> PossibleResults {
+    let mut res = 0;
+    for a in Self::VALUES.iter().filter(|a| self.0 & **a != 0) {
+      for b in Self::VALUES.iter().filter(|b| other.0 & **b != 0) {
+        // Three-valued logic, where unknown propagates unless the result is decided by the other operand.
+        res |= match operator {
+          Operator::And if *a == Self::FALSE || *b == Self::FALSE => Self::FALSE,
+          Operator::And if *a == Self::TRUE && *b == Self::TRUE => Self::TRUE,
+          Operator::Or if *a == Self::TRUE || *b == Self::TRUE => Self::TRUE,
+          Operator::Or if *a == Self::FALSE && *b == Self::FALSE => Self::FALSE,
+          _ => Self::UNKNOWN,
+        };
+      }
+    }
+    PossibleResults(res)
+  }
+}
+
+impl<'i> MediaList<'i> {
+  /// Replaces queries that cannot match in any of the targets with `not all`, e.g. because they
+  /// use a media feature that none of the targets support, or an invalid value for a known feature.
+  pub(crate) fn remove_unsupported_queries(&mut self, targets: Browsers) {
+    for query in self.media_queries.iter_mut() {
+      if let Some(condition) = &query.condition {
+        let mut results = condition.possible_results(targets);
+        if query.qualifier == Some(Qualifier::Not) {
+          results = results.not();
+        }
+        if !results.can_match() {
+          *query = MediaQuery {
+            qualifier: Some(Qualifier::Not),
+            media_type: MediaType::All,
+            condition: None,
+          };
+        }
+      }
+    }
+  }
+
+  /// Returns the compatibility features of the discrete media features used in the list that are
+  /// not supported by all of the targets.
+  pub(crate) fn unsupported_features(&self, targets: Browsers) -> Vec<Feature> {
+    let mut features = Vec::new();
+    for query in &self.media_queries {
+      if let Some(condition) = &query.condition {
+        condition.collect_features(&mut features);
+      }
+    }
+    features.retain(|feature| !feature.is_compatible(targets));
+    features
+  }
+
+  /// Adds an equivalent `-ms-high-contrast` query for each query that uses the `forced-colors`
+  /// media feature, for legacy Edge and Internet Explorer.
+  pub(crate) fn add_high_contrast_fallbacks(&mut self) {
+    let mut i = 0;
+    while i < self.media_queries.len() {
+      if let Some(condition) = &self.media_queries[i].condition {
+        if let Some(fallback) = condition.to_high_contrast() {
+          let query = MediaQuery {
+            condition: Some(fallback),
+            ..self.media_queries[i].clone()
+          };
+          if !self.media_queries.contains(&query) {
+            i += 1;
+            self.media_queries.insert(i, query);
+          }
+        }
+      }
+      i += 1;
+    }
+  }
+}
+
+impl<'i> MediaCondition<'i> {
+  fn collect_features(&self, features: &mut Vec<Feature>) {
+    match self {
+      MediaCondition::Feature(feature) => {
+        let name = match feature {
+          MediaFeature::Plain { name, .. }
+          | MediaFeature::Boolean { name }
+          | MediaFeature::Range { name, .. }
+          | MediaFeature::Interval { name, .. } => name,
+        };
+        if let Some((feature, _)) = discrete_feature(name.0.as_ref()) {
+          if !features.contains(&feature) {
+            features.push(feature);
+          }
+        }
+      }
+      MediaCondition::Not(condition) => condition.collect_features(features),
+      MediaCondition::Operation { conditions, .. } => {
+        for condition in conditions {
+          condition.collect_features(features);
+        }
+      }
+    }
+  }
+
+  fn possible_results(&self, targets: Browsers) -> PossibleResults {
+    match self {
+      MediaCondition::Feature(feature) => feature.possible_results(targets),
+      MediaCondition::Not(condition) => condition.possible_results(targets).not(),
+      MediaCondition::Operation { operator, conditions } => conditions
+        .iter()
+        .map(|condition| condition.possible_results(targets))
+        .reduce(|a, b| a.combine(b, *operator))
+        .unwrap_or(PossibleResults(PossibleResults::TRUE)),
+    }
+  }
+
+  /// Returns a copy of the condition with `forced-colors` features replaced by `-ms-high-contrast`,
+  /// or `None` if the condition doesn't use `forced-colors`.
+  fn to_high_contrast(&self) -> Option<MediaCondition<'i>> {
+    match self {
+      MediaCondition::Feature(feature) => {
+        let (name, value) = match feature {
+          MediaFeature::Boolean { name } => (name, None),
+          MediaFeature::Plain {
+            name,
+            value: MediaFeatureValue::Ident(value),
+          } => (name, Some(value)),
+          _ => return None,
+        };
+        if !name.0.eq_ignore_ascii_case("forced-colors") {
+          return None;
+        }
+
+        let value = match value.map(|value| ForcedColors::parse_string(value.0.as_ref())) {
+          None | Some(Ok(ForcedColors::Active)) => "active",
+          Some(Ok(ForcedColors::None)) => "none",
+          Some(Err(..)) => return None,
+        };
+        Some(MediaCondition::Feature(MediaFeature::Plain {
+          name: Ident("-ms-high-contrast".into()),
+          value: MediaFeatureValue::Ident(Ident(value.into())),
+        }))
+      }
+      MediaCondition::Not(condition) => Some(MediaCondition::Not(Box::new(condition.to_high_contrast()?))),
+      MediaCondition::Operation { operator, conditions } => {
+        let mut changed = false;
+        let conditions = conditions
+          .iter()
+          .map(|condition| match condition.to_high_contrast() {
+            Some(condition) => {
+              changed = true;
+              condition
+            }
+            None => condition.clone(),
+          })
+          .collect();
+        if !changed {
+          return None;
+        }
+        Some(MediaCondition::Operation {
+          operator: *operator,
+          conditions,
+        })
+      }
+    }
+  }
+}
+
+impl<'i> MediaFeature<'i> {
+  fn possible_results(&self, targets: Browsers) -> PossibleResults {
+    let (name, value) = match self {
+      MediaFeature::Plain { name, value } => (name, Some(value)),
+      MediaFeature::Boolean { name } => (name, None),
+      MediaFeature::Range { name, .. } | MediaFeature::Interval { name, .. } => (name, None),
+    };
+
+    let runtime = PossibleResults(PossibleResults::TRUE | PossibleResults::FALSE);
+    let (feature, is_valid) = match discrete_feature(name.0.as_ref()) {
+      Some(res) => res,
+      None => return runtime,
+    };
+
+    // Discrete features can only be used in boolean context or compared to one of their keywords.
+    let valid = match (self, value) {
+      (MediaFeature::Boolean { .. }, _) => true,
+      (_, Some(MediaFeatureValue::Ident(value))) => is_valid(value.0.as_ref()),
+      _ => false,
+    };
+
+    if !valid || !feature.is_partially_compatible(targets) {
+      PossibleResults(PossibleResults::UNKNOWN)
+    } else if feature.is_compatible(targets) {
+      runtime
+    } else {
+      PossibleResults(runtime.0 | PossibleResults::UNKNOWN)
+    }
+  }
+}
+
+impl<'i> MediaList<'i> {
+  /// Simplifies the media queries in the list. Queries that always match replace the whole list,
+  /// and queries that never match are removed unless no other queries remain.
+  pub(crate) fn simplify(&mut self) {
+    for query in self.media_queries.iter_mut() {
+      query.simplify();
+    }
+
+    if self.media_queries.iter().any(|query| query.always_matches()) {
+      self.media_queries = vec![MediaQuery {
+        qualifier: None,
+        media_type: MediaType::All,
+        condition: None,
+      }];
+      return;
+    }
+
+    if !self.never_matches() {
+      self.media_queries.retain(|query| !query.never_matches());
+    }
+
+    let mut i = 1;
+    while i < self.media_queries.len() {
+      if self.media_queries[..i].contains(&self.media_queries[i]) {
+        self.media_queries.remove(i);
+      } else {
+        i += 1;
+      }
+    }
+  }
+}
+
+impl<'i> MediaQuery<'i> {
+  fn simplify(&mut self) {
+    let matches = match &mut self.condition {
+      Some(condition) => condition.simplify(),
+      None => return,
+    };
+
+    match matches {
+      Some(true) => self.condition = None,
+      Some(false) => {
+        // The query never matches, or always matches if negated.
+        self.qualifier = if self.qualifier == Some(Qualifier::Not) {
+          None
+        } else {
+          Some(Qualifier::Not)
+        };
+        self.media_type = MediaType::All;
+        self.condition = None;
+      }
+      None => {}
+    }
+  }
+}
+
+impl<'i> MediaCondition<'i> {
+  /// Simplifies the condition in place. Returns `Some` if the condition always or never matches,
+  /// in which case it should be removed by the caller.
+  fn simplify(&mut self) -> Option<bool> {
+    match self {
+      MediaCondition::Feature(feature) => {
+        if feature.is_tautology() {
+          return Some(true);
+        }
+      }
+      MediaCondition::Not(condition) => {
+        if let Some(matches) = condition.simplify() {
+          return Some(!matches);
+        }
+
+        match &mut **condition {
+          // `not (not (a))` => `(a)`
+          MediaCondition::Not(inner) => {
+            *self = std::mem::replace(
+              &mut **inner,
+              MediaCondition::Operation {
+                operator: Operator::And,
+                conditions: vec![],
+              },
+            );
+          }
+          // `not (width < 600px)` => `(width >= 600px)`
+          MediaCondition::Feature(MediaFeature::Range { operator, .. })
+            if *operator != MediaFeatureComparison::Equal =>
+          {
+            *operator = operator.negate();
+            *self = std::mem::replace(
+              &mut **condition,
+              MediaCondition::Operation {
+                operator: Operator::And,
+                conditions: vec![],
+              },
+            );
+          }
+          _ => {}
+        }
+      }
+      MediaCondition::Operation { operator, conditions } => {
+        let operator = *operator;
+        let mut simplified = Vec::with_capacity(conditions.len());
+        for mut condition in conditions.drain(..) {
+          match (condition.simplify(), operator) {
+            (Some(true), Operator::Or) => return Some(true),
+            (Some(false), Operator::And) => return Some(false),
+            (Some(_), _) => continue,
+            (None, _) => {}
+          }
+
+          // `(a) and ((b) and (c))` => `(a) and (b) and (c)`
+          match condition {
+            MediaCondition::Operation {
+              operator: inner_operator,
+              conditions: inner,
+            } if inner_operator == operator => simplified.extend(inner),
+            condition => simplified.push(condition),
+          }
+        }
+
+        let mut i = 1;
+        while i < simplified.len() {
+          if simplified[..i].contains(&simplified[i]) {
+            simplified.remove(i);
+          } else {
+            i += 1;
+          }
+        }
+
+        if let Some(matches) = merge_range_bounds(&mut simplified, operator) {
+          return Some(matches);
+        }
+
+        match simplified.len() {
+          0 => return Some(operator == Operator::And),
+          1 => *self = simplified.pop().unwrap(),
+          _ => *conditions = simplified,
+        }
+      }
+    }
+
+    None
+  }
+}
+
+impl<'i> MediaFeature<'i> {
+  /// Returns whether the feature always matches, e.g. `(min-width: 0)`.
+  fn is_tautology(&self) -> bool {
+    match self.range_bound() {
+      Some(bound) => {
+        bound.is_lower
+          && bound.inclusive
+          && bound.value <= 0.0
+          && ["width", "height", "device-width", "device-height"]
+            .iter()
+            .any(|name| bound.name.eq_ignore_ascii_case(name))
+      }
+      None => false,
+    }
+  }
+
+  /// Returns the bound represented by a `min-` or `max-` prefixed feature, or a range, if any.
+  fn range_bound(&self) -> Option<RangeBound<'_>> {
+    let (name, is_lower, inclusive, value) = match self {
+      MediaFeature::Plain { name, value } => {
+        if let Some(name) = name.0.strip_prefix("min-") {
+          (name, true, true, value)
+        } else if let Some(name) = name.0.strip_prefix("max-") {
+          (name, false, true, value)
+        } else {
+          return None;
+        }
+      }
+      MediaFeature::Range { name, operator, value } => match operator {
+        MediaFeatureComparison::GreaterThan => (name.0.as_ref(), true, false, value),
+        MediaFeatureComparison::GreaterThanEqual => (name.0.as_ref(), true, true, value),
+        MediaFeatureComparison::LessThan => (name.0.as_ref(), false, false, value),
+        MediaFeatureComparison::LessThanEqual => (name.0.as_ref(), false, true, value),
+        MediaFeatureComparison::Equal => return None,
+      },
+      _ => return None,
+    };
+
+    let (kind, value) = match value {
+      MediaFeatureValue::Length(length) => (RangeKind::Length, length.to_px()?),
+      MediaFeatureValue::Number(number) => (RangeKind::Number, *number),
+      _ => return None,
+    };
+
+    Some(RangeBound {
+      name,
+      is_lower,
+      inclusive,
+      kind,
+      value,
+    })
+  }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum RangeKind {
+  Length,
+  Number,
+}
+
+/// A lower or upper bound on the value of a range media feature.
+struct RangeBound<'a> {
+  name: &'a str,
+  is_lower: bool,
+  inclusive: bool,
+  kind: RangeKind,
+  value: f32,
+}
+
+impl<'a> RangeBound<'a> {
+  fn is_comparable(&self, other: &RangeBound) -> bool {
+    self.name.eq_ignore_ascii_case(other.name) && self.kind == other.kind
+  }
+
+  /// Returns whether this bound is tighter than another bound on the same side.
+  fn is_tighter_than(&self, other: &RangeBound) -> bool {
+    if self.value == other.value {
+      return !self.inclusive && other.inclusive;
+    }
+
+    (self.value > other.value) == self.is_lower
+  }
+}
+
+/// Merges the bounds on the same feature within the conditions of an operation, keeping the tightest bound
+/// on each side for `and`, and the loosest for `or`. Returns `Some(false)` if the bounds of an `and` operation
+/// contradict each other, and `Some(true)` if the bounds of an `or` operation cover every value.
+fn merge_range_bounds(conditions: &mut Vec<MediaCondition>, operator: Operator) -> Option<bool> {
+  let mut i = 0;
+  while i < conditions.len() {
+    let mut j = i + 1;
+    while j < conditions.len() {
+      let (a, b) = match (&conditions[i], &conditions[j]) {
+        (MediaCondition::Feature(a), MediaCondition::Feature(b)) => match (a.range_bound(), b.range_bound()) {
+          (Some(a), Some(b)) if a.is_comparable(&b) => (a, b),
+          _ => {
+            j += 1;
+            continue;
+          }
+        },
+        _ => {
+          j += 1;
+          continue;
+        }
+      };
+
+      if a.is_lower != b.is_lower {
+        let (lower, upper) = if a.is_lower { (&a, &b) } else { (&b, &a) };
+        match operator {
+          // `(width >= 600px) and (width < 400px)` never matches.
+          Operator::And
+            if lower.value > upper.value
+              || (lower.value == upper.value && !(lower.inclusive && upper.inclusive)) =>
+          {
+            return Some(false)
+          }
+          // `(width >= 400px) or (width < 600px)` always matches.
+          Operator::Or
+            if lower.value < upper.value
+              || (lower.value == upper.value && (lower.inclusive || upper.inclusive)) =>
+          {
+            return Some(true)
+          }
+          _ => {}
+        }
+        j += 1;
+        continue;
+      }
+
+      // Keep the first condition in place, replaced by whichever bound wins.
+      let keep_b = b.is_tighter_than(&a) == (operator == Operator::And);
+      if keep_b {
+        conditions.swap(i, j);
+      }
+      conditions.remove(j);
+    }
+    i += 1;
+  }
+
+  None
 }
 
 #[cfg(test)]
@@ -1138,6 +2031,47 @@ mod tests {
     assert_eq!(and("print", "print"), "print");
   }
 
+  fn evaluate(s: &str, environment: &MediaEnvironment) -> Option<bool> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    MediaList::parse(&mut parser).unwrap().evaluate(environment)
+  }
+
+  #[test]
+  fn test_evaluate() {
+    let environment = MediaEnvironment {
+      media_type: Some(MediaType::Screen),
+      min_width: Some(768.0),
+      max_width: Some(1024.0),
+      features: HashMap::from([("hover".into(), "hover".into()), ("pointer".into(), "fine".into())]),
+      ..MediaEnvironment::default()
+    };
+    assert_eq!(evaluate("screen", &environment), Some(true));
+    assert_eq!(evaluate("print", &environment), Some(false));
+    assert_eq!(evaluate("not print", &environment), Some(true));
+    assert_eq!(evaluate("print, (min-width: 500px)", &environment), Some(true));
+    assert_eq!(evaluate("(min-width: 500px)", &environment), Some(true));
+    assert_eq!(evaluate("(min-width: 800px)", &environment), None);
+    assert_eq!(evaluate("(max-width: 600px)", &environment), Some(false));
+    assert_eq!(evaluate("(width >= 1100px)", &environment), Some(false));
+    assert_eq!(evaluate("(width < 1100px)", &environment), Some(true));
+    assert_eq!(evaluate("(500px <= width <= 1200px)", &environment), Some(true));
+    assert_eq!(evaluate("(min-width: 50em)", &environment), None);
+    assert_eq!(evaluate("(min-height: 500px)", &environment), None);
+    assert_eq!(evaluate("(hover)", &environment), Some(true));
+    assert_eq!(evaluate("(pointer: coarse)", &environment), Some(false));
+    assert_eq!(evaluate("(prefers-color-scheme: dark)", &environment), None);
+    assert_eq!(
+      evaluate("print and (prefers-color-scheme: dark)", &environment),
+      Some(false)
+    );
+    assert_eq!(
+      evaluate("(prefers-color-scheme: dark) or (hover)", &environment),
+      Some(true)
+    );
+    assert_eq!(evaluate("screen and (max-width: 600px)", &environment), Some(false));
+  }
+
   #[test]
   fn test_negated_interval_parens() {
     let media_query = parse("screen and not (200px <= width < 500px)");
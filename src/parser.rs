@@ -1,4 +1,6 @@
-use crate::declaration::{parse_declaration, DeclarationBlock, DeclarationList};
+#[cfg(feature = "spans")]
+use crate::declaration::added_declaration;
+use crate::declaration::{parse_declaration, DeclarationBlock, DeclarationList, PendingComments};
 use crate::error::{Error, ParserError, PrinterError};
 use crate::media_query::*;
 use crate::printer::Printer;
@@ -6,18 +8,24 @@ use crate::properties::custom::TokenList;
 use crate::rules::container::{ContainerName, ContainerRule};
 use crate::rules::font_palette_values::FontPaletteValuesRule;
 use crate::rules::layer::{LayerBlockRule, LayerStatementRule};
+use crate::rules::position_try::PositionTryRule;
 use crate::rules::property::PropertyRule;
+use crate::rules::scope::ScopeRule;
+use crate::rules::starting_style::StartingStyleRule;
 use crate::rules::viewport::ViewportRule;
 use crate::rules::{
+  comment::CommentRule,
   counter_style::CounterStyleRule,
+  custom_at_rule::{CustomAtRule, CustomAtRuleConfig},
   custom_media::CustomMediaRule,
-  document::MozDocumentRule,
+  document::{DocumentMatcher, MozDocumentRule},
   font_face::{FontFaceDeclarationParser, FontFaceRule},
   import::ImportRule,
   keyframes::{KeyframeListParser, KeyframesName, KeyframesRule},
   layer::LayerName,
   media::MediaRule,
   namespace::NamespaceRule,
+  nested_declarations::NestedDeclarationsRule,
   nesting::NestingRule,
   page::{PageRule, PageSelector},
   style::StyleRule,
@@ -25,15 +33,19 @@ use crate::rules::{
   unknown::UnknownAtRule,
   CssRule, CssRuleList, Location,
 };
-use crate::selector::{Component, SelectorList, SelectorParser};
+use crate::selector::{SelectorList, SelectorParser};
+#[cfg(feature = "spans")]
+use crate::span::{declaration_span, set_rule_span, Span};
 use crate::traits::Parse;
 use crate::values::ident::{CustomIdent, DashedIdent};
 use crate::values::string::CowArcStr;
+use crate::values::syntax::ParsedComponent;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
 use cssparser::*;
 use parcel_selectors::parser::NestingRequirement;
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 /// CSS parsing options.
@@ -47,6 +59,8 @@ pub struct ParserOptions<'o, 'i> {
   pub custom_media: bool,
   /// Whether the enable [CSS modules](https://github.com/css-modules/css-modules).
   pub css_modules: Option<crate::css_modules::Config<'o>>,
+  /// How to handle `:local()` and `:global()` selectors when `css_modules` is not enabled.
+  pub local_global: crate::css_modules::LocalGlobalMode,
   /// The source index to assign to all parsed rules. Impacts the source map when
   /// the style sheet is serialized.
   pub source_index: u32,
@@ -54,6 +68,21 @@ pub struct ParserOptions<'o, 'i> {
   pub error_recovery: bool,
   /// A list that will be appended to when a warning occurs.
   pub warnings: Option<Arc<RwLock<Vec<Error<ParserError<'i>>>>>>,
+  /// Whether to preserve comments as [CommentRule](crate::rules::comment::CommentRule) nodes within rule lists,
+  /// and, with the `comments` feature, as [DeclarationComment](crate::declaration::DeclarationComment) entries
+  /// within declaration blocks. Otherwise, comments between declarations are dropped.
+  pub preserve_comments: bool,
+  /// Whether to keep the original source text of top-level rules, along with the whitespace and comments
+  /// between them, so that printing an unchanged style sheet without minification reproduces the input
+  /// exactly. Rules that are changed after parsing are printed normally. The original text is not used
+  /// when printer options that transform the output, such as minification, browser targets, or CSS modules,
+  /// are enabled.
+  pub lossless: bool,
+  /// At-rules to parse according to a registered grammar, keyed by name (without the @). These are
+  /// parsed as [CustomAtRule](crate::rules::custom_at_rule::CustomAtRule) nodes rather than unknown
+  /// at-rules. Built-in at-rules and those handled by a custom [AtRuleParser](crate::traits::AtRuleParser)
+  /// take precedence.
+  pub custom_at_rules: HashMap<String, CustomAtRuleConfig>,
 }
 
 impl<'o, 'i> ParserOptions<'o, 'i> {
@@ -156,7 +185,7 @@ pub enum AtRulePrelude<'i, T> {
   /// A @page rule prelude.
   Page(Vec<PageSelector<'i>>),
   /// A @-moz-document rule.
-  MozDocument,
+  MozDocument(Vec<DocumentMatcher<'i>>, VendorPrefix),
   /// A @import rule prelude.
   Import(
     CowRcStr<'i>,
@@ -178,8 +207,16 @@ pub enum AtRulePrelude<'i, T> {
   Property(DashedIdent<'i>),
   /// A @container prelude.
   Container(Option<ContainerName<'i>>, MediaCondition<'i>),
+  /// A @scope rule prelude, with its scoping root and limit selectors.
+  Scope(Option<SelectorList<'i>>, Option<SelectorList<'i>>),
+  /// A @starting-style prelude.
+  StartingStyle,
+  /// A @position-try prelude, with its name.
+  PositionTry(DashedIdent<'i>),
   /// An unknown prelude.
   Unknown(CowArcStr<'i>, TokenList<'i>),
+  /// A prelude of an at-rule registered with the `custom_at_rules` option.
+  CustomAtRule(CowArcStr<'i>, Option<ParsedComponent<'i>>),
   /// A custom prelude.
   Custom(T),
 }
@@ -240,7 +277,22 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for TopLev
       },
       "custom-media" if self.options.custom_media => {
         let name = DashedIdent::parse(input)?;
-        let media = MediaList::parse(input)?;
+        // `true` and `false` are constant values that always or never match.
+        let media = if let Ok(value) = input.try_parse(|input| {
+          let location = input.current_source_location();
+          let ident = input.expect_ident()?;
+          let value = match_ignore_ascii_case! { ident,
+            "true" => true,
+            "false" => false,
+            _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+          };
+          input.expect_exhausted()?;
+          Ok::<_, ParseError<'i, ParserError<'i>>>(value)
+        }) {
+          MediaList::from_constant(value)
+        } else {
+          MediaList::parse(input)?
+        };
         return Ok(AtRulePrelude::CustomMedia(name, media))
       },
       "property" => {
@@ -276,6 +328,8 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for TopLev
       source_index: self.options.source_index,
       line: loc.line,
       column: loc.column,
+      #[cfg(feature = "spans")]
+      span: crate::span::Span::default(),
     };
 
     let rule = match prelude {
@@ -318,7 +372,7 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for TopLev
         block: None,
         loc,
       }),
-      AtRulePrelude::Custom(_) => {
+      AtRulePrelude::CustomAtRule(..) | AtRulePrelude::Custom(_) => {
         self.state = State::Body;
         AtRuleParser::rule_without_block(&mut self.nested(), prelude, start)?
       }
@@ -374,10 +428,30 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> NestedRuleParser<'a, 'o
 
     let mut iter = RuleListParser::new_for_nested_rule(input, nested_parser);
     let mut rules = Vec::new();
-    while let Some(result) = iter.next() {
+    loop {
+      rules.extend(
+        parse_comments(iter.input, iter.parser.options)
+          .into_iter()
+          .map(CssRule::Comment),
+      );
+      #[cfg(feature = "spans")]
+      let start = {
+        iter.input.skip_whitespace();
+        iter.input.state()
+      };
+      let result = match iter.next() {
+        Some(result) => result,
+        None => break,
+      };
+
       match result {
         Ok(CssRule::Ignored) => {}
-        Ok(rule) => rules.push(rule),
+        #[allow(unused_mut)]
+        Ok(mut rule) => {
+          #[cfg(feature = "spans")]
+          set_rule_span(&mut rule, iter.input, &start);
+          rules.push(rule)
+        }
         Err((e, _)) => {
           if iter.parser.options.error_recovery {
             iter.parser.options.warn(e);
@@ -397,6 +471,8 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> NestedRuleParser<'a, 'o
       source_index: self.options.source_index,
       line: loc.line,
       column: loc.column,
+      #[cfg(feature = "spans")]
+      span: crate::span::Span::default(),
     }
   }
 }
@@ -467,25 +543,18 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         let selectors = input.try_parse(|input| input.parse_comma_separated(PageSelector::parse)).unwrap_or_default();
         Ok(AtRulePrelude::Page(selectors))
       },
-      "-moz-document" => {
-        // Firefox only supports the url-prefix() function with no arguments as a legacy CSS hack.
+      "-moz-document" | "document" => {
+        // Firefox still supports an empty url-prefix() as a legacy CSS hack, and the other
+        // matching functions in user stylesheets.
         // See https://css-tricks.com/snippets/css/css-hacks-targeting-firefox/
-        input.expect_function_matching("url-prefix")?;
-        input.parse_nested_block(|input| {
-          // Firefox also allows an empty string as an argument...
-          // https://github.com/mozilla/gecko-dev/blob/0077f2248712a1b45bf02f0f866449f663538164/servo/components/style/stylesheets/document_rule.rs#L303
-          let _ = input.try_parse(|input| -> Result<(), ParseError<'i, Self::Error>> {
-            let s = input.expect_string()?;
-            if !s.is_empty() {
-              return Err(input.new_custom_error(ParserError::InvalidValue))
-            }
-            Ok(())
-          });
-          input.expect_exhausted()?;
-          Ok(())
-        })?;
-
-        Ok(AtRulePrelude::MozDocument)
+        // and https://github.com/mozilla/gecko-dev/blob/0077f2248712a1b45bf02f0f866449f663538164/servo/components/style/stylesheets/document_rule.rs
+        let vendor_prefix = if starts_with_ignore_ascii_case(&name, "-moz-") {
+          VendorPrefix::Moz
+        } else {
+          VendorPrefix::None
+        };
+        let matchers = input.parse_comma_separated(DocumentMatcher::parse)?;
+        Ok(AtRulePrelude::MozDocument(matchers, vendor_prefix))
       },
       "layer" => {
         let names = match Vec::<LayerName>::parse(input) {
@@ -500,6 +569,30 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         let condition = MediaCondition::parse(input, true)?;
         Ok(AtRulePrelude::Container(name, condition))
       },
+      "scope" => {
+        let selector_parser = SelectorParser {
+          is_nesting_allowed: self.options.nesting,
+          options: self.options,
+        };
+        let parse_selectors = |input: &mut Parser<'i, '_>| -> Result<_, ParseError<'i, Self::Error>> {
+          input.expect_parenthesis_block()?;
+          input.parse_nested_block(|input| SelectorList::parse(&selector_parser, input, NestingRequirement::None))
+        };
+        let scope_start = input.try_parse(parse_selectors).ok();
+        let scope_end = if input.try_parse(|input| input.expect_ident_matching("to")).is_ok() {
+          Some(parse_selectors(input)?)
+        } else {
+          None
+        };
+        Ok(AtRulePrelude::Scope(scope_start, scope_end))
+      },
+      "starting-style" => {
+        Ok(AtRulePrelude::StartingStyle)
+      },
+      "position-try" => {
+        let name = DashedIdent::parse(input)?;
+        Ok(AtRulePrelude::PositionTry(name))
+      },
       _ => parse_custom_at_rule_prelude(&name, input, self.options, self.at_rule_parser)
     }
   }
@@ -542,11 +635,10 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         let rule = FontPaletteValuesRule::parse(name, input, loc)?;
         Ok(CssRule::FontPaletteValues(rule))
       }
-      AtRulePrelude::CounterStyle(name) => Ok(CssRule::CounterStyle(CounterStyleRule {
-        name,
-        declarations: DeclarationBlock::parse(input, self.options)?,
-        loc,
-      })),
+      AtRulePrelude::CounterStyle(name) => {
+        let rule = CounterStyleRule::parse(name, input, loc, self.options)?;
+        Ok(CssRule::CounterStyle(rule))
+      }
       AtRulePrelude::Media(query) => Ok(CssRule::Media(MediaRule {
         query,
         rules: self.parse_nested_rules(input)?,
@@ -563,6 +655,16 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         rules: self.parse_nested_rules(input)?,
         loc,
       })),
+      AtRulePrelude::Scope(scope_start, scope_end) => Ok(CssRule::Scope(ScopeRule {
+        scope_start,
+        scope_end,
+        rules: self.parse_nested_rules(input)?,
+        loc,
+      })),
+      AtRulePrelude::StartingStyle => Ok(CssRule::StartingStyle(StartingStyleRule {
+        rules: self.parse_nested_rules(input)?,
+        loc,
+      })),
       AtRulePrelude::Viewport(vendor_prefix) => {
         Ok(CssRule::Viewport(ViewportRule {
           vendor_prefix,
@@ -585,8 +687,10 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         let rule = PageRule::parse(selectors, input, loc, self.options)?;
         Ok(CssRule::Page(rule))
       }
-      AtRulePrelude::MozDocument => Ok(CssRule::MozDocument(MozDocumentRule {
+      AtRulePrelude::MozDocument(matchers, vendor_prefix) => Ok(CssRule::MozDocument(MozDocumentRule {
+        matchers,
         rules: self.parse_nested_rules(input)?,
+        vendor_prefix,
         loc,
       })),
       AtRulePrelude::Layer(names) => {
@@ -606,6 +710,10 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
       }
       AtRulePrelude::LayerBlock(..) => unreachable!(), // only used in nested style rules.
       AtRulePrelude::Property(name) => Ok(CssRule::Property(PropertyRule::parse(name, input, loc)?)),
+      AtRulePrelude::PositionTry(name) => {
+        let rule = PositionTryRule::parse(name, input, loc, self.options)?;
+        Ok(CssRule::PositionTry(rule))
+      }
       AtRulePrelude::Import(..)
       | AtRulePrelude::Namespace(..)
       | AtRulePrelude::CustomMedia(..)
@@ -620,6 +728,14 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         block: Some(TokenList::parse(input, &self.options, 0)?),
         loc,
       })),
+      AtRulePrelude::CustomAtRule(name, prelude) => Ok(CssRule::CustomAtRule(CustomAtRule::parse_block(
+        name,
+        prelude,
+        input,
+        loc,
+        self.options,
+        self.at_rule_parser,
+      )?)),
       AtRulePrelude::Custom(prelude) => {
         parse_custom_at_rule_body(prelude, input, start, self.options, self.at_rule_parser)
       }
@@ -647,6 +763,12 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
         block: None,
         loc,
       })),
+      AtRulePrelude::CustomAtRule(name, prelude) => Ok(CssRule::CustomAtRule(CustomAtRule::without_block(
+        name,
+        prelude,
+        loc,
+        self.options,
+      )?)),
       AtRulePrelude::Custom(prelude) => {
         parse_custom_at_rule_without_block(prelude, start, self.options, self.at_rule_parser)
       }
@@ -691,6 +813,8 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> QualifiedRuleParser<'i>
       declarations,
       rules,
       loc,
+      #[cfg(feature = "spans")]
+      selector_spans: vec![],
     }))
   }
 }
@@ -706,7 +830,12 @@ fn parse_custom_at_rule_prelude<'i, 't, T: crate::traits::AtRuleParser<'i>>(
     Err(ParseError {
       kind: ParseErrorKind::Basic(BasicParseErrorKind::AtRuleInvalid(..)),
       ..
-    }) => {}
+    }) => {
+      if let Some(config) = options.custom_at_rules.get(name.as_ref()) {
+        let prelude = config.parse_prelude(input)?;
+        return Ok(AtRulePrelude::CustomAtRule(name.into(), prelude));
+      }
+    }
     Err(err) => {
       return Err(match &err.kind {
         ParseErrorKind::Basic(kind) => ParseError {
@@ -761,6 +890,10 @@ fn parse_declarations_and_nested_rules<'a, 'o, 'i, 't, T: crate::traits::AtRuleP
 ) -> Result<(DeclarationBlock<'i>, CssRuleList<'i, T::AtRule>), ParseError<'i, ParserError<'i>>> {
   let mut important_declarations = DeclarationList::new();
   let mut declarations = DeclarationList::new();
+  let mut comments = Vec::new();
+  #[cfg(feature = "spans")]
+  let mut spans = Vec::new();
+  let mut pending_comments = PendingComments::default();
   let mut rules = CssRuleList(vec![]);
   let mut parser = StyleRuleParser {
     options,
@@ -774,18 +907,101 @@ fn parse_declarations_and_nested_rules<'a, 'o, 'i, 't, T: crate::traits::AtRuleP
   // https://drafts.csswg.org/css-syntax/#consume-style-block
   loop {
     let start = input.state();
-    match input.next_including_whitespace_and_comments() {
-      Ok(&Token::WhiteSpace(_)) | Ok(&Token::Comment(_)) | Ok(&Token::Semicolon) => continue,
-      Ok(&Token::Ident(ref name)) => {
-        let name = name.clone();
-        let callback = |input: &mut Parser<'i, '_>| {
+    let token = input.next_including_whitespace_and_comments().cloned();
+    match token {
+      Ok(Token::Comment(text)) if options.preserve_comments => {
+        let loc = start.source_location();
+        pending_comments.push(CommentRule {
+          text: text.into(),
+          loc: Location {
+            source_index: options.source_index,
+            line: loc.line,
+            column: loc.column,
+            #[cfg(feature = "spans")]
+            span: Span::between(&start, &input.state()),
+          },
+        });
+      }
+      Ok(Token::WhiteSpace(_)) | Ok(Token::Comment(_)) | Ok(Token::Semicolon) => continue,
+      Ok(Token::Ident(name)) if name.starts_with("--") || !is_nested_rule(input) => {
+        if parser.rules.0.is_empty() {
+          let lens = (parser.declarations.len(), parser.important_declarations.len());
+          let callback = |input: &mut Parser<'i, '_>| {
+            input.expect_colon()?;
+            parser.parse_value(name, input)
+          };
+          input.parse_until_after(Delimiter::Semicolon, callback)?;
+          #[cfg(feature = "spans")]
+          if let Some((important, index)) =
+            added_declaration(&parser.declarations, &parser.important_declarations, lens)
+          {
+            spans.push(declaration_span(input, &start, important, index));
+          }
+          pending_comments.attach(
+            &mut comments,
+            &parser.declarations,
+            &parser.important_declarations,
+            lens,
+          );
+          continue;
+        }
+
+        // Declarations that follow a nested rule are kept in place, rather than being hoisted
+        // above the nested rules.
+        let mut block = DeclarationBlock::new();
+        input.parse_until_after(Delimiter::Semicolon, |input| {
           input.expect_colon()?;
-          parser.parse_value(name, input)
-        };
-        input.parse_until_after(Delimiter::Semicolon, callback)?;
+          parse_declaration(
+            name,
+            input,
+            &mut block.declarations,
+            &mut block.important_declarations,
+            options,
+          )
+        })?;
+        #[cfg(feature = "spans")]
+        if let Some((important, index)) =
+          added_declaration(&block.declarations, &block.important_declarations, (0, 0))
+        {
+          block.spans.push(declaration_span(input, &start, important, index));
+        }
+        let mut block_comments = Vec::new();
+        pending_comments.attach(
+          &mut block_comments,
+          &block.declarations,
+          &block.important_declarations,
+          (0, 0),
+        );
+        #[cfg(feature = "comments")]
+        {
+          block.comments = block_comments;
+        }
+
+        if let Some(CssRule::NestedDeclarations(nested)) = parser.rules.0.last_mut() {
+          nested.declarations.append(&mut block);
+          #[cfg(feature = "spans")]
+          {
+            nested.loc.span.end = input.position().byte_index() as u32;
+          }
+        } else {
+          let loc = start.source_location();
+          parser.rules.0.push(CssRule::NestedDeclarations(NestedDeclarationsRule {
+            declarations: block,
+            loc: Location {
+              source_index: options.source_index,
+              line: loc.line,
+              column: loc.column,
+              #[cfg(feature = "spans")]
+              span: Span::between(&start, &input.state()),
+            },
+          }));
+        }
       }
       Ok(_) => {
         input.reset(&start);
+        parser.rules.0.extend(pending_comments.take_rules());
+        #[cfg(feature = "spans")]
+        let len = parser.rules.0.len();
         let mut iter = RuleListParser::new_for_nested_rule(input, parser);
         if let Some(result) = iter.next() {
           if let Err((err, _)) = result {
@@ -798,18 +1014,92 @@ fn parse_declarations_and_nested_rules<'a, 'o, 'i, 't, T: crate::traits::AtRuleP
           }
         }
         parser = iter.parser;
+        #[cfg(feature = "spans")]
+        if parser.rules.0.len() > len {
+          if let Some(rule) = parser.rules.0.last_mut() {
+            set_rule_span(rule, input, &start);
+          }
+        }
       }
       Err(_) => break,
     }
   }
 
-  Ok((
-    DeclarationBlock {
-      declarations,
-      important_declarations,
-    },
-    rules,
-  ))
+  let mut block = DeclarationBlock {
+    declarations,
+    important_declarations,
+    #[cfg(feature = "comments")]
+    comments,
+    #[cfg(feature = "spans")]
+    spans,
+  };
+  if rules.0.is_empty() {
+    pending_comments.finish(&mut block);
+  } else {
+    rules.0.extend(pending_comments.take_rules());
+  }
+
+  Ok((block, rules))
+}
+
+/// Returns whether the tokens following an identifier within a style block form a nested rule
+/// rather than a declaration. This is the case for bare type selectors such as `div { ... }`, where
+/// the identifier is not followed by a colon, and for selectors such as `a:hover { ... }`, where the
+/// value would contain a `{}` block along with other tokens.
+/// https://drafts.csswg.org/css-syntax/#consume-block-contents
+fn is_nested_rule(input: &mut Parser<'_, '_>) -> bool {
+  let state = input.state();
+  let result = input.parse_until_before(Delimiter::Semicolon, |input| {
+    let has_colon = input.try_parse(|input| input.expect_colon()).is_ok();
+    let mut has_block = false;
+    let mut has_other = false;
+    while let Ok(token) = input.next() {
+      match token {
+        Token::CurlyBracketBlock => has_block = true,
+        _ => has_other = true,
+      }
+    }
+    Ok::<_, ParseError<'_, ()>>(!has_colon || (has_block && has_other))
+  });
+  input.reset(&state);
+  result.unwrap_or(false)
+}
+
+/// Consumes the whitespace and comments before the next rule or declaration, and returns the comments
+/// if the `preserve_comments` option is enabled.
+pub(crate) fn parse_comments<'i>(
+  input: &mut Parser<'i, '_>,
+  options: &ParserOptions<'_, 'i>,
+) -> Vec<CommentRule<'i>> {
+  let mut comments = Vec::new();
+  if !options.preserve_comments {
+    return comments;
+  }
+
+  loop {
+    let start = input.state();
+    match input.next_including_whitespace_and_comments() {
+      Ok(Token::WhiteSpace(_)) => {}
+      Ok(Token::Comment(text)) => {
+        let text: &'i str = *text;
+        let loc = start.source_location();
+        comments.push(CommentRule {
+          text: text.into(),
+          loc: Location {
+            source_index: options.source_index,
+            line: loc.line,
+            column: loc.column,
+            #[cfg(feature = "spans")]
+            span: Span::between(&start, &input.state()),
+          },
+        });
+      }
+      _ => {
+        input.reset(&start);
+        return comments;
+      }
+    }
+  }
 }
 
 pub struct StyleRuleParser<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> {
@@ -871,6 +1161,9 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
         let name = input.try_parse(LayerName::parse).ok();
         Ok(AtRulePrelude::LayerBlock(name))
       },
+      "starting-style" => {
+        Ok(AtRulePrelude::StartingStyle)
+      },
       "nest" => {
         self.options.warn(input.new_custom_error(ParserError::DeprecatedNestRule));
         let selector_parser = SelectorParser {
@@ -895,6 +1188,8 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
       source_index: self.options.source_index,
       line: loc.line,
       column: loc.column,
+      #[cfg(feature = "spans")]
+      span: crate::span::Span::default(),
     };
     match prelude {
       AtRulePrelude::Media(query) => {
@@ -930,6 +1225,13 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
         }));
         Ok(())
       }
+      AtRulePrelude::StartingStyle => {
+        self.rules.0.push(CssRule::StartingStyle(StartingStyleRule {
+          rules: parse_nested_at_rule(input, self.options, self.at_rule_parser)?,
+          loc,
+        }));
+        Ok(())
+      }
       AtRulePrelude::Nest(selectors) => {
         let (declarations, rules) = parse_declarations_and_nested_rules(input, self.options, self.at_rule_parser)?;
         self.rules.0.push(CssRule::Nesting(NestingRule {
@@ -939,6 +1241,8 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
             vendor_prefix: VendorPrefix::empty(),
             rules,
             loc,
+            #[cfg(feature = "spans")]
+            selector_spans: vec![],
           },
           loc,
         }));
@@ -953,6 +1257,17 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
         }));
         Ok(())
       }
+      AtRulePrelude::CustomAtRule(name, prelude) => {
+        self.rules.0.push(CssRule::CustomAtRule(CustomAtRule::parse_block(
+          name,
+          prelude,
+          input,
+          loc,
+          self.options,
+          self.at_rule_parser,
+        )?));
+        Ok(())
+      }
       AtRulePrelude::Custom(prelude) => {
         self.rules.0.push(parse_custom_at_rule_body(
           prelude,
@@ -979,10 +1294,29 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for StyleR
             source_index: self.options.source_index,
             line: loc.line,
             column: loc.column,
+            #[cfg(feature = "spans")]
+            span: crate::span::Span::default(),
           },
         }));
         Ok(())
       }
+      AtRulePrelude::CustomAtRule(name, prelude) => {
+        let loc = start.source_location();
+        let loc = Location {
+          source_index: self.options.source_index,
+          line: loc.line,
+          column: loc.column,
+          #[cfg(feature = "spans")]
+          span: crate::span::Span::default(),
+        };
+        self.rules.0.push(CssRule::CustomAtRule(CustomAtRule::without_block(
+          name,
+          prelude,
+          loc,
+          self.options,
+        )?));
+        Ok(())
+      }
       AtRulePrelude::Custom(prelude) => {
         self.rules.0.push(parse_custom_at_rule_without_block(
           prelude,
@@ -1007,22 +1341,18 @@ pub fn parse_nested_at_rule<'a, 'o, 'i, 't, T: crate::traits::AtRuleParser<'i>>(
     source_index: options.source_index,
     line: loc.line,
     column: loc.column,
+    #[cfg(feature = "spans")]
+    span: crate::span::Span::default(),
   };
 
   // Declarations can be immediately within @media and @supports blocks that are nested within a parent style rule.
-  // These act the same way as if they were nested within a `& { ... }` block.
+  // These apply to the elements matched by the parent style rule, like nested declarations that follow a nested rule.
   let (declarations, mut rules) = parse_declarations_and_nested_rules(input, options, at_rule_parser)?;
 
   if declarations.len() > 0 {
     rules.0.insert(
       0,
-      CssRule::Style(StyleRule {
-        selectors: Component::Nesting.into(),
-        declarations,
-        vendor_prefix: VendorPrefix::empty(),
-        rules: CssRuleList(vec![]),
-        loc,
-      }),
+      CssRule::NestedDeclarations(NestedDeclarationsRule { declarations, loc }),
     )
   }
 
@@ -1064,7 +1394,11 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> QualifiedRuleParser<'i>
         source_index: self.options.source_index,
         line: loc.line,
         column: loc.column,
+        #[cfg(feature = "spans")]
+        span: crate::span::Span::default(),
       },
+      #[cfg(feature = "spans")]
+      selector_spans: vec![],
     }));
     Ok(())
   }
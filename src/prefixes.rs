@@ -2267,6 +2267,183 @@ impl Feature {
     }
     prefixes
   }
+
+  pub fn from_property(name: &str) -> Option<Feature> {
+    match name {
+      "align-content" => Some(Feature::AlignContent),
+      "align-items" => Some(Feature::AlignItems),
+      "align-self" => Some(Feature::AlignSelf),
+      "animation" => Some(Feature::Animation),
+      "animation-delay" => Some(Feature::AnimationDelay),
+      "animation-direction" => Some(Feature::AnimationDirection),
+      "animation-duration" => Some(Feature::AnimationDuration),
+      "animation-fill-mode" => Some(Feature::AnimationFillMode),
+      "animation-iteration-count" => Some(Feature::AnimationIterationCount),
+      "animation-name" => Some(Feature::AnimationName),
+      "animation-play-state" => Some(Feature::AnimationPlayState),
+      "animation-timing-function" => Some(Feature::AnimationTimingFunction),
+      "any-pseudo" => Some(Feature::AnyPseudo),
+      "appearance" => Some(Feature::Appearance),
+      "backdrop-filter" => Some(Feature::BackdropFilter),
+      "backface-visibility" => Some(Feature::BackfaceVisibility),
+      "background-clip" => Some(Feature::BackgroundClip),
+      "background-origin" => Some(Feature::BackgroundOrigin),
+      "background-size" => Some(Feature::BackgroundSize),
+      "border-block-end" => Some(Feature::BorderBlockEnd),
+      "border-block-start" => Some(Feature::BorderBlockStart),
+      "border-bottom-left-radius" => Some(Feature::BorderBottomLeftRadius),
+      "border-bottom-right-radius" => Some(Feature::BorderBottomRightRadius),
+      "border-image" => Some(Feature::BorderImage),
+      "border-inline-end" => Some(Feature::BorderInlineEnd),
+      "border-inline-start" => Some(Feature::BorderInlineStart),
+      "border-radius" => Some(Feature::BorderRadius),
+      "border-top-left-radius" => Some(Feature::BorderTopLeftRadius),
+      "border-top-right-radius" => Some(Feature::BorderTopRightRadius),
+      "box-decoration-break" => Some(Feature::BoxDecorationBreak),
+      "box-shadow" => Some(Feature::BoxShadow),
+      "box-sizing" => Some(Feature::BoxSizing),
+      "break-after" => Some(Feature::BreakAfter),
+      "break-before" => Some(Feature::BreakBefore),
+      "break-inside" => Some(Feature::BreakInside),
+      "calc" => Some(Feature::Calc),
+      "clip-path" => Some(Feature::ClipPath),
+      "color-adjust" => Some(Feature::ColorAdjust),
+      "column-count" => Some(Feature::ColumnCount),
+      "column-fill" => Some(Feature::ColumnFill),
+      "column-gap" => Some(Feature::ColumnGap),
+      "column-rule" => Some(Feature::ColumnRule),
+      "column-rule-color" => Some(Feature::ColumnRuleColor),
+      "column-rule-style" => Some(Feature::ColumnRuleStyle),
+      "column-rule-width" => Some(Feature::ColumnRuleWidth),
+      "column-span" => Some(Feature::ColumnSpan),
+      "column-width" => Some(Feature::ColumnWidth),
+      "columns" => Some(Feature::Columns),
+      "cross-fade" => Some(Feature::CrossFade),
+      "display-flex" => Some(Feature::DisplayFlex),
+      "display-grid" => Some(Feature::DisplayGrid),
+      "element" => Some(Feature::Element),
+      "fill" => Some(Feature::Fill),
+      "fill-available" => Some(Feature::FillAvailable),
+      "filter" => Some(Feature::Filter),
+      "filter-function" => Some(Feature::FilterFunction),
+      "fit-content" => Some(Feature::FitContent),
+      "flex" => Some(Feature::Flex),
+      "flex-basis" => Some(Feature::FlexBasis),
+      "flex-direction" => Some(Feature::FlexDirection),
+      "flex-flow" => Some(Feature::FlexFlow),
+      "flex-grow" => Some(Feature::FlexGrow),
+      "flex-shrink" => Some(Feature::FlexShrink),
+      "flex-wrap" => Some(Feature::FlexWrap),
+      "flow-from" => Some(Feature::FlowFrom),
+      "flow-into" => Some(Feature::FlowInto),
+      "font-feature-settings" => Some(Feature::FontFeatureSettings),
+      "font-kerning" => Some(Feature::FontKerning),
+      "font-language-override" => Some(Feature::FontLanguageOverride),
+      "font-variant-ligatures" => Some(Feature::FontVariantLigatures),
+      "grab" => Some(Feature::Grab),
+      "grabbing" => Some(Feature::Grabbing),
+      "grid-area" => Some(Feature::GridArea),
+      "grid-column" => Some(Feature::GridColumn),
+      "grid-column-align" => Some(Feature::GridColumnAlign),
+      "grid-column-end" => Some(Feature::GridColumnEnd),
+      "grid-column-start" => Some(Feature::GridColumnStart),
+      "grid-row" => Some(Feature::GridRow),
+      "grid-row-align" => Some(Feature::GridRowAlign),
+      "grid-row-end" => Some(Feature::GridRowEnd),
+      "grid-row-start" => Some(Feature::GridRowStart),
+      "grid-template" => Some(Feature::GridTemplate),
+      "grid-template-areas" => Some(Feature::GridTemplateAreas),
+      "grid-template-columns" => Some(Feature::GridTemplateColumns),
+      "grid-template-rows" => Some(Feature::GridTemplateRows),
+      "hyphens" => Some(Feature::Hyphens),
+      "image-rendering" => Some(Feature::ImageRendering),
+      "image-set" => Some(Feature::ImageSet),
+      "inline-flex" => Some(Feature::InlineFlex),
+      "inline-grid" => Some(Feature::InlineGrid),
+      "isolate" => Some(Feature::Isolate),
+      "isolate-override" => Some(Feature::IsolateOverride),
+      "justify-content" => Some(Feature::JustifyContent),
+      "linear-gradient" => Some(Feature::LinearGradient),
+      "margin-block-end" => Some(Feature::MarginBlockEnd),
+      "margin-block-start" => Some(Feature::MarginBlockStart),
+      "margin-inline-end" => Some(Feature::MarginInlineEnd),
+      "margin-inline-start" => Some(Feature::MarginInlineStart),
+      "mask" => Some(Feature::Mask),
+      "mask-border" => Some(Feature::MaskBorder),
+      "mask-border-outset" => Some(Feature::MaskBorderOutset),
+      "mask-border-repeat" => Some(Feature::MaskBorderRepeat),
+      "mask-border-slice" => Some(Feature::MaskBorderSlice),
+      "mask-border-source" => Some(Feature::MaskBorderSource),
+      "mask-border-width" => Some(Feature::MaskBorderWidth),
+      "mask-clip" => Some(Feature::MaskClip),
+      "mask-composite" => Some(Feature::MaskComposite),
+      "mask-image" => Some(Feature::MaskImage),
+      "mask-origin" => Some(Feature::MaskOrigin),
+      "mask-position" => Some(Feature::MaskPosition),
+      "mask-repeat" => Some(Feature::MaskRepeat),
+      "mask-size" => Some(Feature::MaskSize),
+      "max-content" => Some(Feature::MaxContent),
+      "min-content" => Some(Feature::MinContent),
+      "object-fit" => Some(Feature::ObjectFit),
+      "object-position" => Some(Feature::ObjectPosition),
+      "order" => Some(Feature::Order),
+      "overscroll-behavior" => Some(Feature::OverscrollBehavior),
+      "padding-block-end" => Some(Feature::PaddingBlockEnd),
+      "padding-block-start" => Some(Feature::PaddingBlockStart),
+      "padding-inline-end" => Some(Feature::PaddingInlineEnd),
+      "padding-inline-start" => Some(Feature::PaddingInlineStart),
+      "perspective" => Some(Feature::Perspective),
+      "perspective-origin" => Some(Feature::PerspectiveOrigin),
+      "pixelated" => Some(Feature::Pixelated),
+      "place-self" => Some(Feature::PlaceSelf),
+      "plaintext" => Some(Feature::Plaintext),
+      "print-color-adjust" => Some(Feature::PrintColorAdjust),
+      "radial-gradient" => Some(Feature::RadialGradient),
+      "region-fragment" => Some(Feature::RegionFragment),
+      "repeating-linear-gradient" => Some(Feature::RepeatingLinearGradient),
+      "repeating-radial-gradient" => Some(Feature::RepeatingRadialGradient),
+      "scroll-snap-coordinate" => Some(Feature::ScrollSnapCoordinate),
+      "scroll-snap-destination" => Some(Feature::ScrollSnapDestination),
+      "scroll-snap-points-x" => Some(Feature::ScrollSnapPointsX),
+      "scroll-snap-points-y" => Some(Feature::ScrollSnapPointsY),
+      "scroll-snap-type" => Some(Feature::ScrollSnapType),
+      "shape-image-threshold" => Some(Feature::ShapeImageThreshold),
+      "shape-margin" => Some(Feature::ShapeMargin),
+      "shape-outside" => Some(Feature::ShapeOutside),
+      "sticky" => Some(Feature::Sticky),
+      "stretch" => Some(Feature::Stretch),
+      "tab-size" => Some(Feature::TabSize),
+      "text-align-last" => Some(Feature::TextAlignLast),
+      "text-decoration" => Some(Feature::TextDecoration),
+      "text-decoration-color" => Some(Feature::TextDecorationColor),
+      "text-decoration-line" => Some(Feature::TextDecorationLine),
+      "text-decoration-skip" => Some(Feature::TextDecorationSkip),
+      "text-decoration-skip-ink" => Some(Feature::TextDecorationSkipInk),
+      "text-decoration-style" => Some(Feature::TextDecorationStyle),
+      "text-emphasis" => Some(Feature::TextEmphasis),
+      "text-emphasis-color" => Some(Feature::TextEmphasisColor),
+      "text-emphasis-position" => Some(Feature::TextEmphasisPosition),
+      "text-emphasis-style" => Some(Feature::TextEmphasisStyle),
+      "text-orientation" => Some(Feature::TextOrientation),
+      "text-overflow" => Some(Feature::TextOverflow),
+      "text-size-adjust" => Some(Feature::TextSizeAdjust),
+      "text-spacing" => Some(Feature::TextSpacing),
+      "touch-action" => Some(Feature::TouchAction),
+      "transform" => Some(Feature::Transform),
+      "transform-origin" => Some(Feature::TransformOrigin),
+      "transform-style" => Some(Feature::TransformStyle),
+      "transition" => Some(Feature::Transition),
+      "transition-delay" => Some(Feature::TransitionDelay),
+      "transition-duration" => Some(Feature::TransitionDuration),
+      "transition-property" => Some(Feature::TransitionProperty),
+      "transition-timing-function" => Some(Feature::TransitionTimingFunction),
+      "user-select" => Some(Feature::UserSelect),
+      "writing-mode" => Some(Feature::WritingMode),
+      "zoom-in" => Some(Feature::ZoomIn),
+      "zoom-out" => Some(Feature::ZoomOut),
+      _ => None,
+    }
+  }
 }
 
 pub fn is_flex_2009(browsers: Browsers) -> bool {
@@ -3,13 +3,17 @@
 use crate::css_modules::CssModule;
 use crate::dependencies::{Dependency, DependencyOptions};
 use crate::error::{Error, ErrorLocation, PrinterError, PrinterErrorKind};
-use crate::rules::{Location, StyleContext};
-use crate::selector::SelectorList;
+use crate::rules::{CssRule, Location, StyleContext};
+use crate::selector::{HasPolyfill, SelectorList};
+use crate::stylesheet::{SizeBreakdown, SizeGroup};
 use crate::targets::Browsers;
+use crate::traits::ToCss;
+use crate::values::number::round_to_precision;
 use crate::vendor_prefix::VendorPrefix;
 use cssparser::{serialize_identifier, serialize_name};
 #[cfg(feature = "sourcemap")]
 use parcel_sourcemap::{OriginalLocation, SourceMap};
+use std::collections::HashMap;
 
 /// Options that control how CSS is serialized to a string.
 #[derive(Default)]
@@ -35,6 +39,117 @@ pub struct PrinterOptions<'a> {
   /// A mapping of pseudo classes to replace with class names that can be applied
   /// from JavaScript. Useful for polyfills, for example.
   pub pseudo_classes: Option<PseudoClasses<'a>>,
+  /// An attribute name to replace `:has()` selectors with when they are not supported
+  /// by the targets, e.g. `data-has-polyfill`. Each unique `:has()` argument is assigned an
+  /// id, which a runtime polyfill can add to the attribute of matching elements. The ids
+  /// are returned as part of the [ToCssResult](super::stylesheet::ToCssResult).
+  pub has_polyfill: Option<&'a str>,
+  /// The preferred serialization for colors.
+  pub color_format: ColorFormat,
+  /// How `light-dark()` colors are compiled when the targets do not support them.
+  pub light_dark_mode: LightDarkMode,
+  /// How colors outside the sRGB gamut, e.g. sRGB fallbacks for saturated `lab()` or `oklch()` colors,
+  /// are mapped into the gamut when they are written.
+  pub gamut_mapping: GamutMapping,
+  /// How nested style rules are compiled when the targets do not support nesting.
+  pub nesting_mode: NestingMode,
+  /// Whether to record the number of bytes each top-level rule contributes to the output, along with
+  /// the size of the output grouped by media query, layer, source file, and property.
+  /// If true, the sizes are returned as part of the [ToCssResult](super::stylesheet::ToCssResult).
+  pub size_report: bool,
+  /// The maximum number of decimal places to keep for numbers inside basic shape
+  /// functions such as `path()`, `polygon()`, and `inset()`. By default, full precision is kept.
+  pub shape_precision: Option<u8>,
+}
+
+/// How `light-dark()` colors are compiled for browser targets that do not support them.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum LightDarkMode {
+  /// Write both colors using `var()` with the `--lightningcss-light` and `--lightningcss-dark`
+  /// custom properties, which are defined alongside each `color-scheme` declaration. Elements
+  /// using `light-dark()` must have an ancestor (or themselves) with a `color-scheme` declaration.
+  #[default]
+  CustomProperties,
+  /// Write the light color, and repeat declarations using `light-dark()` with the dark color within
+  /// a `@media (prefers-color-scheme: dark)` rule. The `color-scheme` property is not taken into account.
+  MediaQuery,
+}
+
+/// The preferred serialization for colors when printing.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum ColorFormat {
+  /// The shortest representation, e.g. a named color or a compact hex color.
+  #[default]
+  Shortest,
+  /// Always use full length hex colors, e.g. `#ff0000` or `#ff000080`.
+  Hex,
+  /// Always use the `rgb()` or `rgba()` functions.
+  Rgb,
+  /// Keep colors in the notation they were parsed as where possible. Colors in the
+  /// `lab()`, `lch()`, `oklab()`, `oklch()`, and `color()` notations are unchanged, and
+  /// `hsl()` and `hwb()` colors with `none` components keep their function. Other sRGB
+  /// colors, including named colors, are printed as full length hex colors since the
+  /// original notation is not retained by the parser.
+  Preserve,
+  /// Use `oklch()` for sRGB colors when supported by all browser targets (or when no
+  /// targets are given), otherwise fall back to the shortest representation.
+  Modern,
+}
+
+/// An algorithm used to map colors outside the sRGB gamut into the gamut when printing.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum GamutMapping {
+  /// The [CSS Color 4 gamut mapping](https://www.w3.org/TR/css-color-4/#binsearch) algorithm, which reduces
+  /// the chroma of the color in the OKLCH color space, preserving its lightness and hue.
+  #[default]
+  ChromaReduction,
+  /// Clamp each sRGB component into the gamut. This is faster to compute, but can shift the hue
+  /// and lightness of saturated colors.
+  Clip,
+}
+
+/// How nested style rules are compiled for browser targets that do not support nesting.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum NestingMode {
+  /// Replace the nesting selector with an `:is()` selector containing the parent selectors,
+  /// e.g. `.a, .b { & .c {} }` becomes `:is(.a, .b) .c`. A parent selector list with a single
+  /// selector is written directly where possible.
+  #[default]
+  Is,
+  /// Expand each nested selector into one selector for every combination of parent selectors,
+  /// e.g. `.a, .b { & .c {} }` becomes `.a .c, .b .c`. This avoids `:is()`, and gives each
+  /// selector its own specificity rather than the highest specificity of the parent selectors.
+  /// The output grows with the number of parent selectors, and parent selectors are substituted
+  /// as written, so `.x & {}` within `.a .b {}` becomes `.x .a .b` like in CSS preprocessors.
+  Expand,
 }
 
 /// A mapping of user action pseudo classes to replace with class names.
@@ -82,10 +197,29 @@ pub struct Printer<'a, 'b, 'c, W> {
   /// the vendor prefix of whatever is being printed.
   pub(crate) vendor_prefix: VendorPrefix,
   pub(crate) in_calc: bool,
+  /// Whether the printer is currently inside a basic shape function.
+  pub(crate) in_shape: bool,
+  shape_precision: Option<u8>,
   pub(crate) css_module: Option<CssModule<'a, 'b, 'c>>,
   pub(crate) dependencies: Option<Vec<Dependency>>,
   pub(crate) remove_imports: bool,
   pub(crate) pseudo_classes: Option<PseudoClasses<'a>>,
+  pub(crate) has_polyfill: Option<&'a str>,
+  pub(crate) has_polyfills: Option<Vec<HasPolyfill>>,
+  pub(crate) color_format: ColorFormat,
+  pub(crate) light_dark_mode: LightDarkMode,
+  pub(crate) gamut_mapping: GamutMapping,
+  pub(crate) nesting_mode: NestingMode,
+  /// Whether a `light-dark()` color was written using the `MediaQuery` mode.
+  pub(crate) has_light_dark: bool,
+  /// Whether to write the dark color of `light-dark()` colors using the `MediaQuery` mode.
+  pub(crate) dark_color_scheme: bool,
+  /// The number of bytes written so far.
+  pub(crate) bytes: usize,
+  /// The output byte ranges of the top-level rules, if the `size_report` option is enabled.
+  pub(crate) rule_ranges: Option<Vec<std::ops::Range<usize>>>,
+  /// The output size grouped by media query, layer, source file, and property, if the `size_report` option is enabled.
+  pub(crate) size_breakdown: Option<SizeBreakdownBuilder>,
   context: Option<&'a StyleContext<'a, 'b>>,
 }
 
@@ -103,6 +237,8 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
         source_index: 0,
         line: 0,
         column: 1,
+        #[cfg(feature = "spans")]
+        span: crate::span::Span::default(),
       },
       indent: 0,
       line: 0,
@@ -111,6 +247,8 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       targets: options.targets,
       vendor_prefix: VendorPrefix::empty(),
       in_calc: false,
+      in_shape: false,
+      shape_precision: options.shape_precision,
       css_module: None,
       dependencies: if options.analyze_dependencies.is_some() {
         Some(Vec::new())
@@ -119,6 +257,21 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       },
       remove_imports: matches!(&options.analyze_dependencies, Some(d) if d.remove_imports),
       pseudo_classes: options.pseudo_classes,
+      has_polyfill: options.has_polyfill,
+      has_polyfills: None,
+      color_format: options.color_format,
+      light_dark_mode: options.light_dark_mode,
+      gamut_mapping: options.gamut_mapping,
+      nesting_mode: options.nesting_mode,
+      has_light_dark: false,
+      dark_color_scheme: false,
+      bytes: 0,
+      rule_ranges: if options.size_report { Some(Vec::new()) } else { None },
+      size_breakdown: if options.size_report {
+        Some(SizeBreakdownBuilder::default())
+      } else {
+        None
+      },
       context: None,
     }
   }
@@ -142,12 +295,14 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
   /// If such a string is written, it will break source maps.
   pub fn write_str(&mut self, s: &str) -> Result<(), PrinterError> {
     self.col += s.len() as u32;
+    self.bytes += s.len();
     self.dest.write_str(s)?;
     Ok(())
   }
 
   /// Write a single character to the underlying destination.
   pub fn write_char(&mut self, c: char) -> Result<(), PrinterError> {
+    self.bytes += c.len_utf8();
     if c == '\n' {
       self.line += 1;
       self.col = 0;
@@ -353,6 +508,23 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
     res
   }
 
+  /// Returns the number of decimal places to round numbers to, if currently printing a basic shape.
+  pub(crate) fn shape_precision(&self) -> Option<u8> {
+    if self.in_shape {
+      self.shape_precision
+    } else {
+      None
+    }
+  }
+
+  /// Rounds a number to the configured shape precision, if currently printing a basic shape.
+  pub(crate) fn round_shape_value(&self, value: f32) -> f32 {
+    match self.shape_precision() {
+      Some(precision) => round_to_precision(value, precision),
+      None => value,
+    }
+  }
+
   pub(crate) fn context(&self) -> Option<&'a StyleContext<'a, 'b>> {
     self.context.clone()
   }
@@ -361,6 +533,93 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
 impl<'a, 'b, 'c, W: std::fmt::Write + Sized> std::fmt::Write for Printer<'a, 'b, 'c, W> {
   fn write_str(&mut self, s: &str) -> std::fmt::Result {
     self.col += s.len() as u32;
+    self.bytes += s.len();
     self.dest.write_str(s)
   }
 }
+
+/// Accumulates a [SizeBreakdown](SizeBreakdown) while a style sheet is printed.
+#[derive(Default)]
+pub(crate) struct SizeBreakdownBuilder {
+  /// The queries of the `@media` rules enclosing the rule being printed.
+  media_queries: Vec<String>,
+  /// The names of the `@layer` blocks enclosing the rule being printed.
+  layers: Vec<String>,
+  /// The number of bytes written so far by the child rules of the rule being printed.
+  child_bytes: usize,
+  by_media_query: HashMap<String, usize>,
+  by_layer: HashMap<String, usize>,
+  by_source: HashMap<u32, usize>,
+  by_property: HashMap<String, usize>,
+}
+
+impl SizeBreakdownBuilder {
+  /// Called before a rule is printed. Returns the number of bytes written by the preceding sibling rules,
+  /// which must be passed to [exit_rule](SizeBreakdownBuilder::exit_rule).
+  pub(crate) fn enter_rule<T>(&mut self, rule: &CssRule<T>) -> usize {
+    match rule {
+      CssRule::Media(media) => self
+        .media_queries
+        .push(media.query.to_css_string(PrinterOptions::default()).unwrap_or_default()),
+      CssRule::LayerBlock(layer) => self.layers.push(match &layer.name {
+        Some(name) => name.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+        None => "<anonymous>".into(),
+      }),
+      _ => {}
+    }
+    std::mem::take(&mut self.child_bytes)
+  }
+
+  /// Called after a rule is printed, with the number of bytes it occupies in the output. Bytes written by
+  /// child rules are only counted for the child rules themselves.
+  pub(crate) fn exit_rule<T>(&mut self, rule: &CssRule<T>, bytes: usize, preceding_bytes: usize) {
+    let own_bytes = bytes.saturating_sub(self.child_bytes);
+    if !self.media_queries.is_empty() {
+      *self.by_media_query.entry(self.media_queries.join(" and ")).or_default() += own_bytes;
+    }
+    if !self.layers.is_empty() {
+      *self.by_layer.entry(self.layers.join(".")).or_default() += own_bytes;
+    }
+    if let Some(loc) = rule.loc() {
+      *self.by_source.entry(loc.source_index).or_default() += own_bytes;
+    }
+
+    match rule {
+      CssRule::Media(..) => {
+        self.media_queries.pop();
+      }
+      CssRule::LayerBlock(..) => {
+        self.layers.pop();
+      }
+      _ => {}
+    }
+    self.child_bytes = preceding_bytes + bytes;
+  }
+
+  pub(crate) fn add_property(&mut self, name: &str, bytes: usize) {
+    match self.by_property.get_mut(name) {
+      Some(size) => *size += bytes,
+      None => {
+        self.by_property.insert(name.to_owned(), bytes);
+      }
+    }
+  }
+
+  pub(crate) fn finish(self, sources: &[String]) -> SizeBreakdown {
+    fn groups(sizes: impl Iterator<Item = (String, usize)>) -> Vec<SizeGroup> {
+      let mut groups: Vec<_> = sizes.map(|(name, bytes)| SizeGroup { name, bytes }).collect();
+      groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+      groups
+    }
+
+    SizeBreakdown {
+      media_queries: groups(self.by_media_query.into_iter()),
+      layers: groups(self.by_layer.into_iter()),
+      sources: groups(self.by_source.into_iter().map(|(source_index, bytes)| {
+        let name = sources.get(source_index as usize).cloned().unwrap_or_default();
+        (name, bytes)
+      })),
+      properties: groups(self.by_property.into_iter()),
+    }
+  }
+}
@@ -0,0 +1,569 @@
+//! CSS properties and functions related to anchor positioning.
+
+use super::Property;
+use crate::declaration::DeclarationBlock;
+use crate::error::{ParserError, PrinterError};
+use crate::macros::enum_property;
+use crate::printer::Printer;
+use crate::traits::{Parse, ToCss};
+use crate::values::ident::DashedIdent;
+use crate::values::length::LengthPercentage;
+use crate::values::percentage::Percentage;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+use smallvec::SmallVec;
+
+/// A value for the [anchor-name](https://drafts.csswg.org/css-anchor-position-1/#name) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum AnchorName<'i> {
+  /// The element is not an anchor.
+  None,
+  /// A list of anchor names.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Names(SmallVec<[DashedIdent<'i>; 1]>),
+}
+
+impl<'i> Default for AnchorName<'i> {
+  fn default() -> Self {
+    AnchorName::None
+  }
+}
+
+impl<'i> Parse<'i> for AnchorName<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(AnchorName::None);
+    }
+
+    let names = input.parse_comma_separated(DashedIdent::parse)?;
+    Ok(AnchorName::Names(names.into()))
+  }
+}
+
+impl<'i> ToCss for AnchorName<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      AnchorName::None => dest.write_str("none"),
+      AnchorName::Names(names) => {
+        let mut first = true;
+        for name in names {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          name.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// A value for the [position-anchor](https://drafts.csswg.org/css-anchor-position-1/#position-anchor) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum PositionAnchor<'i> {
+  /// The element uses its implicit anchor element, if any.
+  Auto,
+  /// The name of the default anchor element.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Name(DashedIdent<'i>),
+}
+
+impl<'i> Default for PositionAnchor<'i> {
+  fn default() -> Self {
+    PositionAnchor::Auto
+  }
+}
+
+impl<'i> Parse<'i> for PositionAnchor<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(PositionAnchor::Auto);
+    }
+
+    Ok(PositionAnchor::Name(DashedIdent::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for PositionAnchor<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      PositionAnchor::Auto => dest.write_str("auto"),
+      PositionAnchor::Name(name) => name.to_css(dest),
+    }
+  }
+}
+
+enum_property! {
+  /// A keyword for the [position-area](https://drafts.csswg.org/css-anchor-position-1/#position-area) property.
+  pub enum PositionAreaKeyword {
+    /// The left row or column.
+    "left": Left,
+    /// The center row or column.
+    "center": Center,
+    /// The right column.
+    "right": Right,
+    /// The center and left columns.
+    "span-left": SpanLeft,
+    /// The center and right columns.
+    "span-right": SpanRight,
+    /// The start column in the containing block's writing mode.
+    "x-start": XStart,
+    /// The end column in the containing block's writing mode.
+    "x-end": XEnd,
+    /// The center and start columns in the containing block's writing mode.
+    "span-x-start": SpanXStart,
+    /// The center and end columns in the containing block's writing mode.
+    "span-x-end": SpanXEnd,
+    /// The start column in the element's writing mode.
+    "self-x-start": SelfXStart,
+    /// The end column in the element's writing mode.
+    "self-x-end": SelfXEnd,
+    /// The center and start columns in the element's writing mode.
+    "span-self-x-start": SpanSelfXStart,
+    /// The center and end columns in the element's writing mode.
+    "span-self-x-end": SpanSelfXEnd,
+    /// All three rows or columns.
+    "span-all": SpanAll,
+    /// The top row.
+    "top": Top,
+    /// The bottom row.
+    "bottom": Bottom,
+    /// The center and top rows.
+    "span-top": SpanTop,
+    /// The center and bottom rows.
+    "span-bottom": SpanBottom,
+    /// The start row in the containing block's writing mode.
+    "y-start": YStart,
+    /// The end row in the containing block's writing mode.
+    "y-end": YEnd,
+    /// The center and start rows in the containing block's writing mode.
+    "span-y-start": SpanYStart,
+    /// The center and end rows in the containing block's writing mode.
+    "span-y-end": SpanYEnd,
+    /// The start row in the element's writing mode.
+    "self-y-start": SelfYStart,
+    /// The end row in the element's writing mode.
+    "self-y-end": SelfYEnd,
+    /// The center and start rows in the element's writing mode.
+    "span-self-y-start": SpanSelfYStart,
+    /// The center and end rows in the element's writing mode.
+    "span-self-y-end": SpanSelfYEnd,
+    /// The start of the block axis in the containing block's writing mode.
+    "block-start": BlockStart,
+    /// The end of the block axis in the containing block's writing mode.
+    "block-end": BlockEnd,
+    /// The center and start of the block axis in the containing block's writing mode.
+    "span-block-start": SpanBlockStart,
+    /// The center and end of the block axis in the containing block's writing mode.
+    "span-block-end": SpanBlockEnd,
+    /// The start of the inline axis in the containing block's writing mode.
+    "inline-start": InlineStart,
+    /// The end of the inline axis in the containing block's writing mode.
+    "inline-end": InlineEnd,
+    /// The center and start of the inline axis in the containing block's writing mode.
+    "span-inline-start": SpanInlineStart,
+    /// The center and end of the inline axis in the containing block's writing mode.
+    "span-inline-end": SpanInlineEnd,
+    /// The start of the block axis in the element's writing mode.
+    "self-block-start": SelfBlockStart,
+    /// The end of the block axis in the element's writing mode.
+    "self-block-end": SelfBlockEnd,
+    /// The center and start of the block axis in the element's writing mode.
+    "span-self-block-start": SpanSelfBlockStart,
+    /// The center and end of the block axis in the element's writing mode.
+    "span-self-block-end": SpanSelfBlockEnd,
+    /// The start of the inline axis in the element's writing mode.
+    "self-inline-start": SelfInlineStart,
+    /// The end of the inline axis in the element's writing mode.
+    "self-inline-end": SelfInlineEnd,
+    /// The center and start of the inline axis in the element's writing mode.
+    "span-self-inline-start": SpanSelfInlineStart,
+    /// The center and end of the inline axis in the element's writing mode.
+    "span-self-inline-end": SpanSelfInlineEnd,
+    /// The start of both axes in the containing block's writing mode.
+    "start": Start,
+    /// The end of both axes in the containing block's writing mode.
+    "end": End,
+    /// The center and start of both axes in the containing block's writing mode.
+    "span-start": SpanStart,
+    /// The center and end of both axes in the containing block's writing mode.
+    "span-end": SpanEnd,
+    /// The start of both axes in the element's writing mode.
+    "self-start": SelfStart,
+    /// The end of both axes in the element's writing mode.
+    "self-end": SelfEnd,
+    /// The center and start of both axes in the element's writing mode.
+    "span-self-start": SpanSelfStart,
+    /// The center and end of both axes in the element's writing mode.
+    "span-self-end": SpanSelfEnd,
+  }
+}
+
+/// The axis that a position-area keyword refers to.
+#[derive(PartialEq)]
+enum PositionAreaAxis {
+  Any,
+  X,
+  Y,
+  Block,
+  Inline,
+  SelfBlock,
+  SelfInline,
+  Both,
+  SelfBoth,
+}
+
+impl PositionAreaKeyword {
+  fn axis(&self) -> PositionAreaAxis {
+    use PositionAreaKeyword::*;
+    match self {
+      Center | SpanAll => PositionAreaAxis::Any,
+      Left | Right | SpanLeft | SpanRight | XStart | XEnd | SpanXStart | SpanXEnd | SelfXStart | SelfXEnd
+      | SpanSelfXStart | SpanSelfXEnd => PositionAreaAxis::X,
+      Top | Bottom | SpanTop | SpanBottom | YStart | YEnd | SpanYStart | SpanYEnd | SelfYStart | SelfYEnd
+      | SpanSelfYStart | SpanSelfYEnd => PositionAreaAxis::Y,
+      BlockStart | BlockEnd | SpanBlockStart | SpanBlockEnd => PositionAreaAxis::Block,
+      InlineStart | InlineEnd | SpanInlineStart | SpanInlineEnd => PositionAreaAxis::Inline,
+      SelfBlockStart | SelfBlockEnd | SpanSelfBlockStart | SpanSelfBlockEnd => PositionAreaAxis::SelfBlock,
+      SelfInlineStart | SelfInlineEnd | SpanSelfInlineStart | SpanSelfInlineEnd => PositionAreaAxis::SelfInline,
+      Start | End | SpanStart | SpanEnd => PositionAreaAxis::Both,
+      SelfStart | SelfEnd | SpanSelfStart | SpanSelfEnd => PositionAreaAxis::SelfBoth,
+    }
+  }
+
+  /// Returns whether the keyword only applies to a single axis, in which case
+  /// omitting the other keyword is equivalent to `span-all`.
+  fn is_single_axis(&self) -> bool {
+    !matches!(
+      self.axis(),
+      PositionAreaAxis::Any | PositionAreaAxis::Both | PositionAreaAxis::SelfBoth
+    )
+  }
+}
+
+/// A value for the [position-area](https://drafts.csswg.org/css-anchor-position-1/#position-area) property.
+///
+/// A single keyword is expanded during parsing to the equivalent pair of keywords,
+/// and the shortest equivalent form is chosen when serializing.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum PositionArea {
+  /// The property has no effect.
+  None,
+  /// A region of the position-area grid.
+  Area(PositionAreaKeyword, PositionAreaKeyword),
+}
+
+impl Default for PositionArea {
+  fn default() -> Self {
+    PositionArea::None
+  }
+}
+
+impl<'i> Parse<'i> for PositionArea {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(PositionArea::None);
+    }
+
+    let location = input.current_source_location();
+    let first = PositionAreaKeyword::parse(input)?;
+    let second = match input.try_parse(PositionAreaKeyword::parse) {
+      Ok(second) => second,
+      Err(_) if first.is_single_axis() => PositionAreaKeyword::SpanAll,
+      Err(_) => first,
+    };
+
+    let valid = match (first.axis(), second.axis()) {
+      (PositionAreaAxis::Any, _) | (_, PositionAreaAxis::Any) => true,
+      (PositionAreaAxis::X, PositionAreaAxis::Y) | (PositionAreaAxis::Y, PositionAreaAxis::X) => true,
+      (PositionAreaAxis::Block, PositionAreaAxis::Inline)
+      | (PositionAreaAxis::Inline, PositionAreaAxis::Block) => true,
+      (PositionAreaAxis::SelfBlock, PositionAreaAxis::SelfInline)
+      | (PositionAreaAxis::SelfInline, PositionAreaAxis::SelfBlock) => true,
+      (a, b) => a == b && !first.is_single_axis(),
+    };
+
+    if !valid {
+      return Err(location.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(PositionArea::Area(first, second))
+  }
+}
+
+impl ToCss for PositionArea {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      PositionArea::None => dest.write_str("none"),
+      PositionArea::Area(first, second) => {
+        if *second == PositionAreaKeyword::SpanAll && first.is_single_axis() {
+          first.to_css(dest)
+        } else if *first == PositionAreaKeyword::SpanAll && second.is_single_axis() {
+          second.to_css(dest)
+        } else if first == second && !first.is_single_axis() {
+          first.to_css(dest)
+        } else {
+          first.to_css(dest)?;
+          dest.write_char(' ')?;
+          second.to_css(dest)
+        }
+      }
+    }
+  }
+}
+
+enum_property! {
+  /// A side keyword for the [anchor()](https://drafts.csswg.org/css-anchor-position-1/#anchor-pos) function.
+  pub enum AnchorSideKeyword {
+    /// The same side as the inset property.
+    "inside": Inside,
+    /// The opposite side of the inset property.
+    "outside": Outside,
+    /// The top side of the anchor.
+    "top": Top,
+    /// The left side of the anchor.
+    "left": Left,
+    /// The right side of the anchor.
+    "right": Right,
+    /// The bottom side of the anchor.
+    "bottom": Bottom,
+    /// The start side of the anchor in the containing block's writing mode.
+    "start": Start,
+    /// The end side of the anchor in the containing block's writing mode.
+    "end": End,
+    /// The start side of the anchor in the element's writing mode.
+    "self-start": SelfStart,
+    /// The end side of the anchor in the element's writing mode.
+    "self-end": SelfEnd,
+    /// The center of the anchor.
+    "center": Center,
+  }
+}
+
+/// An [`<anchor-side>`](https://drafts.csswg.org/css-anchor-position-1/#typedef-anchor-side) value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum AnchorSide {
+  /// A side keyword.
+  Keyword(AnchorSideKeyword),
+  /// A position between the start and end sides of the anchor.
+  Percentage(Percentage),
+}
+
+impl<'i> Parse<'i> for AnchorSide {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(percentage) = input.try_parse(Percentage::parse) {
+      return Ok(AnchorSide::Percentage(percentage));
+    }
+
+    Ok(AnchorSide::Keyword(AnchorSideKeyword::parse(input)?))
+  }
+}
+
+impl ToCss for AnchorSide {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      // `center` is equivalent to 50%, which is shorter.
+      AnchorSide::Keyword(AnchorSideKeyword::Center) => Percentage(0.5).to_css(dest),
+      AnchorSide::Keyword(keyword) => keyword.to_css(dest),
+      AnchorSide::Percentage(percentage) => percentage.to_css(dest),
+    }
+  }
+}
+
+/// An [anchor()](https://drafts.csswg.org/css-anchor-position-1/#anchor-pos) function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct AnchorFunction<'i> {
+  /// The name of the anchor element, or the default anchor if omitted.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: Option<DashedIdent<'i>>,
+  /// The side of the anchor to position against.
+  pub side: AnchorSide,
+  /// A fallback value in case the anchor reference is invalid.
+  pub fallback: Option<LengthPercentage>,
+}
+
+impl<'i> AnchorFunction<'i> {
+  /// Parses the arguments of an `anchor()` function.
+  pub(crate) fn parse_nested<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut name = input.try_parse(DashedIdent::parse).ok();
+    let side = AnchorSide::parse(input)?;
+    if name.is_none() {
+      name = input.try_parse(DashedIdent::parse).ok();
+    }
+
+    let fallback = if input.try_parse(|input| input.expect_comma()).is_ok() {
+      Some(LengthPercentage::parse(input)?)
+    } else {
+      None
+    };
+
+    Ok(AnchorFunction { name, side, fallback })
+  }
+}
+
+impl<'i> ToCss for AnchorFunction<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("anchor(")?;
+    if let Some(name) = &self.name {
+      name.to_css(dest)?;
+      dest.write_char(' ')?;
+    }
+    self.side.to_css(dest)?;
+    if let Some(fallback) = &self.fallback {
+      dest.delim(',', false)?;
+      fallback.to_css(dest)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+enum_property! {
+  /// A size keyword for the [anchor-size()](https://drafts.csswg.org/css-anchor-position-1/#anchor-size-fn) function.
+  pub enum AnchorSizeKeyword {
+    /// The width of the anchor.
+    "width": Width,
+    /// The height of the anchor.
+    "height": Height,
+    /// The size of the anchor in the block axis of the containing block.
+    "block": Block,
+    /// The size of the anchor in the inline axis of the containing block.
+    "inline": Inline,
+    /// The size of the anchor in the block axis of the element.
+    "self-block": SelfBlock,
+    /// The size of the anchor in the inline axis of the element.
+    "self-inline": SelfInline,
+  }
+}
+
+/// An [anchor-size()](https://drafts.csswg.org/css-anchor-position-1/#anchor-size-fn) function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct AnchorSizeFunction<'i> {
+  /// The name of the anchor element, or the default anchor if omitted.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: Option<DashedIdent<'i>>,
+  /// The dimension of the anchor to measure, or the axis of the property it is used in if omitted.
+  pub size: Option<AnchorSizeKeyword>,
+  /// A fallback value in case the anchor reference is invalid.
+  pub fallback: Option<LengthPercentage>,
+}
+
+impl<'i> AnchorSizeFunction<'i> {
+  /// Parses the arguments of an `anchor-size()` function.
+  pub(crate) fn parse_nested<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut name = input.try_parse(DashedIdent::parse).ok();
+    let size = input.try_parse(AnchorSizeKeyword::parse).ok();
+    if name.is_none() {
+      name = input.try_parse(DashedIdent::parse).ok();
+    }
+
+    let fallback = if (name.is_none() && size.is_none()) || input.try_parse(|input| input.expect_comma()).is_ok() {
+      if input.is_exhausted() {
+        None
+      } else {
+        Some(LengthPercentage::parse(input)?)
+      }
+    } else {
+      None
+    };
+
+    Ok(AnchorSizeFunction { name, size, fallback })
+  }
+}
+
+impl<'i> ToCss for AnchorSizeFunction<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("anchor-size(")?;
+    if let Some(name) = &self.name {
+      name.to_css(dest)?;
+    }
+    if let Some(size) = &self.size {
+      if self.name.is_some() {
+        dest.write_char(' ')?;
+      }
+      size.to_css(dest)?;
+    }
+    if let Some(fallback) = &self.fallback {
+      if self.name.is_some() || self.size.is_some() {
+        dest.delim(',', false)?;
+      }
+      fallback.to_css(dest)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+/// Returns whether any of the declarations use anchor positioning.
+pub(crate) fn uses_anchor_positioning(declarations: &DeclarationBlock) -> bool {
+  declarations
+    .iter()
+    .any(|(property, _)| property_uses_anchor_positioning(property))
+}
+
+/// Returns whether the property uses anchor positioning.
+pub(crate) fn property_uses_anchor_positioning(property: &Property) -> bool {
+  match property {
+    Property::AnchorName(..) | Property::PositionAnchor(..) | Property::PositionArea(..) => true,
+    Property::Unparsed(unparsed) => unparsed.value.has_anchor_functions(),
+    Property::Custom(custom) => custom.value.has_anchor_functions(),
+    _ => false,
+  }
+}
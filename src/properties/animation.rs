@@ -6,7 +6,7 @@ use crate::error::{ParserError, PrinterError};
 use crate::macros::*;
 use crate::prefixes::Feature;
 use crate::printer::Printer;
-use crate::properties::{Property, PropertyId, VendorPrefix};
+use crate::properties::{custom::CustomPropertyName, Property, PropertyId, VendorPrefix};
 use crate::targets::Browsers;
 use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss, Zero};
 use crate::values::number::CSSNumber;
@@ -332,6 +332,9 @@ pub(crate) struct AnimationHandler<'i> {
   delays: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
   fill_modes: Option<(SmallVec<[AnimationFillMode; 1]>, VendorPrefix)>,
   has_any: bool,
+  /// Whether the block contains animation longhands that are not handled here, such as
+  /// `animation-timeline`, which are matched with the animations in the list by index.
+  has_unknown_longhands: bool,
 }
 
 impl<'i> AnimationHandler<'i> {
@@ -426,6 +429,14 @@ impl<'i> PropertyHandler<'i> for AnimationHandler<'i> {
         self.flush(dest);
         dest.push(Property::Unparsed(val.get_prefixed(self.targets, Feature::Animation)));
       }
+      Custom(custom) => {
+        if let CustomPropertyName::Unknown(name) = &custom.name {
+          if name.0.contains("animation-") {
+            self.has_unknown_longhands = true;
+          }
+        }
+        return false;
+      }
       _ => return false,
     }
 
@@ -434,6 +445,7 @@ impl<'i> PropertyHandler<'i> for AnimationHandler<'i> {
 
   fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
     self.flush(dest);
+    self.has_unknown_longhands = false;
   }
 }
 
@@ -492,7 +504,7 @@ impl<'i> AnimationHandler<'i> {
         && delays.len() == len
         && fill_modes.len() == len
       {
-        let animations = izip!(
+        let mut animations: AnimationList = izip!(
           names.drain(..),
           durations.drain(..),
           timing_functions.drain(..),
@@ -517,6 +529,22 @@ impl<'i> AnimationHandler<'i> {
           },
         )
         .collect();
+
+        // An animation that is repeated later in the list is overridden by the later copy, since
+        // identical animations produce the same values and later animations take precedence.
+        // `none` entries are kept, since they only reserve a position in the list, and nothing is
+        // removed when other longhands in the block are matched with the animations by index.
+        if !self.has_unknown_longhands {
+          let mut i = 0;
+          while i < animations.len() {
+            if animations[i].name != AnimationName::None && animations[i + 1..].contains(&animations[i]) {
+              animations.remove(i);
+            } else {
+              i += 1;
+            }
+          }
+        }
+
         let mut prefix = intersection;
         if prefix.contains(VendorPrefix::None) {
           if let Some(targets) = self.targets {
@@ -8,7 +8,7 @@ use crate::prefixes::Feature;
 use crate::printer::Printer;
 use crate::properties::{Property, PropertyId, VendorPrefix};
 use crate::targets::Browsers;
-use crate::traits::{FallbackValues, Parse, PropertyHandler, Shorthand, ToCss};
+use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss};
 use crate::values::color::ColorFallbackKind;
 use crate::values::image::ImageFallback;
 use crate::values::{color::CssColor, image::Image, length::LengthPercentageOrAuto, position::*};
@@ -791,7 +791,7 @@ impl<'i> PropertyHandler<'i> for BackgroundHandler<'i> {
       ($val: ident) => {
         // If this is an image-set() and not all of our targets support it, preserve previous fallback.
         if Image::should_preserve_fallbacks(&$val, self.images.as_ref(), self.targets) {
-          self.flush(dest);
+          self.flush(dest, context);
         }
 
         // Store prefixed properties. Clear if we hit an unprefixed property and we have
@@ -825,7 +825,7 @@ impl<'i> PropertyHandler<'i> for BackgroundHandler<'i> {
         if *vendor_prefix == VendorPrefix::None {
           self.clips = Some(val.clone());
         } else {
-          self.flush(dest);
+          self.flush(dest, context);
           dest.push(property.clone())
         }
       }
@@ -843,7 +843,7 @@ impl<'i> PropertyHandler<'i> for BackgroundHandler<'i> {
         self.clips = Some(val.iter().map(|b| b.clip.clone()).collect());
       }
       Property::Unparsed(val) if is_background_property(&val.property_id) => {
-        self.flush(dest);
+        self.flush(dest, context);
         let mut unparsed = val.clone();
         context.add_unparsed_fallbacks(&mut unparsed);
         dest.push(Property::Unparsed(unparsed))
@@ -855,7 +855,7 @@ impl<'i> PropertyHandler<'i> for BackgroundHandler<'i> {
     true
   }
 
-  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
     // If the last declaration is prefixed, pop the last value
     // so it isn't duplicated when we flush.
     if self.has_prefix {
@@ -863,12 +863,12 @@ impl<'i> PropertyHandler<'i> for BackgroundHandler<'i> {
     }
 
     dest.extend(self.decls.drain(..));
-    self.flush(dest);
+    self.flush(dest, context);
   }
 }
 
 impl<'i> BackgroundHandler<'i> {
-  fn flush(&mut self, dest: &mut DeclarationList<'i>) {
+  fn flush(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
     if !self.has_any {
       return;
     }
@@ -969,7 +969,7 @@ impl<'i> BackgroundHandler<'i> {
         .collect();
 
         if let Some(targets) = self.targets {
-          for fallback in backgrounds.get_fallbacks(targets) {
+          for fallback in context.get_color_fallbacks(&mut backgrounds, targets) {
             dest.push(Property::Background(fallback));
           }
         }
@@ -987,7 +987,7 @@ impl<'i> BackgroundHandler<'i> {
 
     if let Some(mut color) = color {
       if let Some(targets) = self.targets {
-        for fallback in color.get_fallbacks(targets) {
+        for fallback in context.get_color_fallbacks(&mut color, targets) {
           dest.push(Property::BackgroundColor(fallback))
         }
       }
@@ -997,7 +997,7 @@ impl<'i> BackgroundHandler<'i> {
 
     if let Some(mut images) = images {
       if let Some(targets) = self.targets {
-        for fallback in images.get_fallbacks(targets) {
+        for fallback in context.get_color_fallbacks(&mut images, targets) {
           dest.push(Property::BackgroundImage(fallback));
         }
       }
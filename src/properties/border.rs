@@ -724,7 +724,7 @@ impl<'i> BorderHandler<'i> {
       ($prop: ident => $val: expr) => {{
         let mut val = $val;
         if let Some(targets) = self.targets {
-          let fallbacks = val.get_fallbacks(targets);
+          let fallbacks = context.get_color_fallbacks(&mut val, targets);
           for fallback in fallbacks {
             dest.push(Property::$prop(fallback))
           }
@@ -349,6 +349,11 @@ impl<'i> FallbackValues for BorderImage<'i> {
       .map(|source| BorderImage { source, ..self.clone() })
       .collect()
   }
+
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    let source = self.source.get_image_set_fallback()?;
+    Some(BorderImage { source, ..self.clone() })
+  }
 }
 
 #[derive(Default, Debug)]
@@ -384,7 +389,7 @@ impl<'i> PropertyHandler<'i> for BorderImageHandler<'i> {
     macro_rules! property {
       ($name: ident, $val: ident) => {{
         if self.vendor_prefix != VendorPrefix::None {
-          self.flush(dest);
+          self.flush(dest, context);
         }
         self.vendor_prefix = VendorPrefix::None;
         self.$name = Some($val.clone());
@@ -395,7 +400,7 @@ impl<'i> PropertyHandler<'i> for BorderImageHandler<'i> {
     match property {
       BorderImageSource(val) => {
         if val.should_preserve_fallback(&self.source, self.targets) {
-          self.flush(dest);
+          self.flush(dest, context);
         }
 
         property!(source, val);
@@ -406,7 +411,7 @@ impl<'i> PropertyHandler<'i> for BorderImageHandler<'i> {
       BorderImageRepeat(val) => property!(repeat, val),
       BorderImage(val, vp) => {
         if val.source.should_preserve_fallback(&self.source, self.targets) {
-          self.flush(dest);
+          self.flush(dest, context);
         }
 
         self.set_border_image(val);
@@ -414,7 +419,7 @@ impl<'i> PropertyHandler<'i> for BorderImageHandler<'i> {
         self.has_any = true;
       }
       Unparsed(val) if is_border_image_property(&val.property_id) => {
-        self.flush(dest);
+        self.flush(dest, context);
 
         // Even if we weren't able to parse the value (e.g. due to var() references),
         // we can still add vendor prefixes to the property itself.
@@ -433,8 +438,8 @@ impl<'i> PropertyHandler<'i> for BorderImageHandler<'i> {
     true
   }
 
-  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
-    self.flush(dest);
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
+    self.flush(dest, context);
   }
 }
 
@@ -465,7 +470,7 @@ impl<'i> BorderImageHandler<'i> {
     }
   }
 
-  fn flush(&mut self, dest: &mut DeclarationList<'i>) {
+  fn flush(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
     if !self.has_any {
       return;
     }
@@ -492,7 +497,7 @@ impl<'i> BorderImageHandler<'i> {
         if let Some(targets) = self.targets {
           prefix = Feature::BorderImage.prefixes_for(targets);
 
-          let fallbacks = border_image.get_fallbacks(targets);
+          let fallbacks = context.get_color_fallbacks(&mut border_image, targets);
           for fallback in fallbacks {
             // Match prefix of fallback. e.g. -webkit-linear-gradient
             // can only be used in -webkit-border-image, not -moz-border-image.
@@ -515,7 +520,7 @@ impl<'i> BorderImageHandler<'i> {
     } else {
       if let Some(mut source) = source {
         if let Some(targets) = self.targets {
-          let fallbacks = source.get_fallbacks(targets);
+          let fallbacks = context.get_color_fallbacks(&mut source, targets);
           for fallback in fallbacks {
             dest.push(Property::BorderImageSource(fallback));
           }
@@ -8,6 +8,7 @@ use smallvec::SmallVec;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use crate::{
+  compat,
   context::PropertyHandlerContext,
   declaration::{DeclarationBlock, DeclarationList},
   error::{ParserError, PrinterError},
@@ -17,7 +18,9 @@ use crate::{
   rules::container::ContainerName as ContainerIdent,
   targets::Browsers,
   traits::{Parse, PropertyHandler, Shorthand, ToCss},
+  values::length::Length,
 };
+use bitflags::bitflags;
 
 enum_property! {
   /// A value for the [container-type](https://drafts.csswg.org/css-contain-3/#container-type) property.
@@ -147,3 +150,506 @@ shorthand_handler!(ContainerHandler -> Container<'i> {
   name: ContainerName(ContainerNameList<'i>),
   container_type: ContainerType(ContainerType),
 });
+
+bitflags! {
+  /// A value for the [contain](https://drafts.csswg.org/css-contain-3/#contain-property) property.
+  ///
+  /// The `Size` and `InlineSize` flags may not be combined.
+  #[cfg_attr(feature = "visitor", derive(Visit))]
+  #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "Vec<ContainKeyword>", into = "Vec<ContainKeyword>"))]
+  pub struct Contain: u8 {
+    /// Size containment in both axes.
+    const Size       = 0b00001;
+    /// Size containment in the inline axis.
+    const InlineSize = 0b00010;
+    /// Layout containment.
+    const Layout     = 0b00100;
+    /// Style containment.
+    const Style      = 0b01000;
+    /// Paint containment.
+    const Paint      = 0b10000;
+    /// Size, layout, style, and paint containment.
+    const Strict = Self::Size.bits | Self::Layout.bits | Self::Style.bits | Self::Paint.bits;
+    /// Layout, style, and paint containment.
+    const Content = Self::Layout.bits | Self::Style.bits | Self::Paint.bits;
+  }
+}
+
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+enum ContainKeyword {
+  Size,
+  InlineSize,
+  Layout,
+  Style,
+  Paint,
+}
+
+impl From<Contain> for Vec<ContainKeyword> {
+  fn from(contain: Contain) -> Self {
+    let mut keywords = Vec::new();
+    macro_rules! keyword {
+      ($t: ident) => {
+        if contain.contains(Contain::$t) {
+          keywords.push(ContainKeyword::$t);
+        }
+      };
+    }
+
+    keyword!(Size);
+    keyword!(InlineSize);
+    keyword!(Layout);
+    keyword!(Style);
+    keyword!(Paint);
+    keywords
+  }
+}
+
+impl From<Vec<ContainKeyword>> for Contain {
+  fn from(keywords: Vec<ContainKeyword>) -> Self {
+    let mut contain = Contain::empty();
+    for keyword in keywords {
+      contain |= match keyword {
+        ContainKeyword::Size => Contain::Size,
+        ContainKeyword::InlineSize => Contain::InlineSize,
+        ContainKeyword::Layout => Contain::Layout,
+        ContainKeyword::Style => Contain::Style,
+        ContainKeyword::Paint => Contain::Paint,
+      }
+    }
+    contain
+  }
+}
+
+#[cfg(feature = "jsonschema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+impl<'a> schemars::JsonSchema for Contain {
+  fn is_referenceable() -> bool {
+    true
+  }
+
+  fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    Vec::<ContainKeyword>::json_schema(gen)
+  }
+
+  fn schema_name() -> String {
+    "Contain".into()
+  }
+}
+
+impl Default for Contain {
+  fn default() -> Contain {
+    Contain::empty()
+  }
+}
+
+impl<'i> Parse<'i> for Contain {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    let mut value = match_ignore_ascii_case! { ident,
+      "none" => return Ok(Contain::empty()),
+      "strict" => return Ok(Contain::Strict),
+      "content" => return Ok(Contain::Content),
+      "size" => Contain::Size,
+      "inline-size" => Contain::InlineSize,
+      "layout" => Contain::Layout,
+      "style" => Contain::Style,
+      "paint" => Contain::Paint,
+      _ => return Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(ident.clone())
+      ))
+    };
+
+    loop {
+      let flag: Result<_, ParseError<'i, ParserError<'i>>> = input.try_parse(|input| {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        let sizes = Contain::Size | Contain::InlineSize;
+        let flag = match_ignore_ascii_case! { ident,
+          "size" if !value.intersects(sizes) => Contain::Size,
+          "inline-size" if !value.intersects(sizes) => Contain::InlineSize,
+          "layout" if !value.contains(Contain::Layout) => Contain::Layout,
+          "style" if !value.contains(Contain::Style) => Contain::Style,
+          "paint" if !value.contains(Contain::Paint) => Contain::Paint,
+          _ => return Err(location.new_unexpected_token_error(
+            cssparser::Token::Ident(ident.clone())
+          ))
+        };
+        Ok(flag)
+      });
+
+      if let Ok(flag) = flag {
+        value |= flag;
+      } else {
+        break;
+      }
+    }
+
+    Ok(value)
+  }
+}
+
+impl ToCss for Contain {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.is_empty() {
+      return dest.write_str("none");
+    }
+
+    if *self == Contain::Strict {
+      return dest.write_str("strict");
+    }
+
+    if *self == Contain::Content {
+      return dest.write_str("content");
+    }
+
+    let mut needs_space = false;
+    macro_rules! val {
+      ($val: ident, $str: expr) => {
+        #[allow(unused_assignments)]
+        if self.contains(Contain::$val) {
+          if needs_space {
+            dest.write_char(' ')?;
+          }
+          dest.write_str($str)?;
+          needs_space = true;
+        }
+      };
+    }
+
+    val!(Size, "size");
+    val!(InlineSize, "inline-size");
+    val!(Layout, "layout");
+    val!(Style, "style");
+    val!(Paint, "paint");
+    Ok(())
+  }
+}
+
+enum_property! {
+  /// A value for the [content-visibility](https://drafts.csswg.org/css-contain-2/#content-visibility) property.
+  pub enum ContentVisibility {
+    /// The element's contents are rendered as normal.
+    "visible": Visible,
+    /// The element's contents are skipped when they are not relevant to the user.
+    "auto": Auto,
+    /// The element's contents are never rendered.
+    "hidden": Hidden,
+  }
+}
+
+impl Default for ContentVisibility {
+  fn default() -> Self {
+    ContentVisibility::Visible
+  }
+}
+
+impl ContentVisibility {
+  /// Returns the containment that is applied to the element by this value.
+  fn containment(&self) -> Contain {
+    match self {
+      ContentVisibility::Visible => Contain::empty(),
+      ContentVisibility::Auto => Contain::Content,
+      ContentVisibility::Hidden => Contain::Strict,
+    }
+  }
+}
+
+/// A value for the [contain-intrinsic-width](https://drafts.csswg.org/css-sizing-4/#intrinsic-size-override)
+/// and [contain-intrinsic-height](https://drafts.csswg.org/css-sizing-4/#intrinsic-size-override) properties.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ContainIntrinsicLength {
+  /// The element has no intrinsic size in the axis.
+  None,
+  /// The element has the given intrinsic size in the axis.
+  Length(Length),
+  /// The last remembered size is used if the element has one, or no intrinsic size otherwise.
+  AutoNone,
+  /// The last remembered size is used if the element has one, or the given size otherwise.
+  AutoLength(Length),
+}
+
+impl ContainIntrinsicLength {
+  fn is_auto_length(&self) -> bool {
+    matches!(self, ContainIntrinsicLength::AutoLength(..))
+  }
+
+  /// Returns the value with the `auto` keyword removed.
+  fn without_auto(&self) -> ContainIntrinsicLength {
+    match self {
+      ContainIntrinsicLength::AutoNone => ContainIntrinsicLength::None,
+      ContainIntrinsicLength::AutoLength(length) => ContainIntrinsicLength::Length(length.clone()),
+      _ => self.clone(),
+    }
+  }
+}
+
+impl Default for ContainIntrinsicLength {
+  fn default() -> Self {
+    ContainIntrinsicLength::None
+  }
+}
+
+impl<'i> Parse<'i> for ContainIntrinsicLength {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let auto = input.try_parse(|input| input.expect_ident_matching("auto")).is_ok();
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(if auto {
+        ContainIntrinsicLength::AutoNone
+      } else {
+        ContainIntrinsicLength::None
+      });
+    }
+
+    let length = Length::parse(input)?;
+    Ok(if auto {
+      ContainIntrinsicLength::AutoLength(length)
+    } else {
+      ContainIntrinsicLength::Length(length)
+    })
+  }
+}
+
+impl ToCss for ContainIntrinsicLength {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      ContainIntrinsicLength::None => dest.write_str("none"),
+      ContainIntrinsicLength::Length(length) => length.to_css(dest),
+      ContainIntrinsicLength::AutoNone => dest.write_str("auto none"),
+      ContainIntrinsicLength::AutoLength(length) => {
+        dest.write_str("auto ")?;
+        length.to_css(dest)
+      }
+    }
+  }
+}
+
+define_shorthand! {
+  /// A value for the [contain-intrinsic-size](https://drafts.csswg.org/css-sizing-4/#intrinsic-size-override) shorthand property.
+  pub struct ContainIntrinsicSize {
+    /// The intrinsic width.
+    width: ContainIntrinsicWidth(ContainIntrinsicLength),
+    /// The intrinsic height.
+    height: ContainIntrinsicHeight(ContainIntrinsicLength),
+  }
+}
+
+impl<'i> Parse<'i> for ContainIntrinsicSize {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let width = ContainIntrinsicLength::parse(input)?;
+    let height = input.try_parse(ContainIntrinsicLength::parse).unwrap_or_else(|_| width.clone());
+    Ok(ContainIntrinsicSize { width, height })
+  }
+}
+
+impl ToCss for ContainIntrinsicSize {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.width.to_css(dest)?;
+    if self.height != self.width {
+      dest.write_char(' ')?;
+      self.height.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl ContainIntrinsicSize {
+  /// Returns a fallback for browsers that don't support the `auto` keyword, if needed.
+  fn get_fallback(&self, targets: Option<Browsers>) -> Option<ContainIntrinsicSize> {
+    let targets = targets?;
+    if !self.width.is_auto_length() && !self.height.is_auto_length()
+      || compat::Feature::ContainIntrinsicSizeAuto.is_compatible(targets)
+    {
+      return None;
+    }
+
+    Some(ContainIntrinsicSize {
+      width: self.width.without_auto(),
+      height: self.height.without_auto(),
+    })
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct ContainIntrinsicSizeHandler {
+  targets: Option<Browsers>,
+  width: Option<ContainIntrinsicLength>,
+  height: Option<ContainIntrinsicLength>,
+  has_any: bool,
+}
+
+impl ContainIntrinsicSizeHandler {
+  pub fn new(targets: Option<Browsers>) -> Self {
+    ContainIntrinsicSizeHandler {
+      targets,
+      ..ContainIntrinsicSizeHandler::default()
+    }
+  }
+}
+
+impl<'i> PropertyHandler<'i> for ContainIntrinsicSizeHandler {
+  fn handle_property(
+    &mut self,
+    property: &Property<'i>,
+    dest: &mut DeclarationList<'i>,
+    context: &mut PropertyHandlerContext<'i, '_>,
+  ) -> bool {
+    match property {
+      Property::ContainIntrinsicWidth(width) => self.width = Some(width.clone()),
+      Property::ContainIntrinsicHeight(height) => self.height = Some(height.clone()),
+      Property::ContainIntrinsicSize(size) => {
+        self.width = Some(size.width.clone());
+        self.height = Some(size.height.clone());
+      }
+      Property::Unparsed(val)
+        if matches!(
+          val.property_id,
+          PropertyId::ContainIntrinsicWidth
+            | PropertyId::ContainIntrinsicHeight
+            | PropertyId::ContainIntrinsicSize
+        ) =>
+      {
+        self.finalize(dest, context);
+
+        let mut unparsed = val.clone();
+        context.add_unparsed_fallbacks(&mut unparsed);
+        dest.push(Property::Unparsed(unparsed));
+        return true;
+      }
+      _ => return false,
+    }
+
+    self.has_any = true;
+    true
+  }
+
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+    if !self.has_any {
+      return;
+    }
+
+    self.has_any = false;
+    let width = std::mem::take(&mut self.width);
+    let height = std::mem::take(&mut self.height);
+
+    if let (Some(width), Some(height)) = (&width, &height) {
+      let size = ContainIntrinsicSize {
+        width: width.clone(),
+        height: height.clone(),
+      };
+      if let Some(fallback) = size.get_fallback(self.targets) {
+        dest.push(Property::ContainIntrinsicSize(fallback));
+      }
+      dest.push(Property::ContainIntrinsicSize(size));
+      return;
+    }
+
+    macro_rules! single_property {
+      ($prop: ident, $key: ident) => {
+        if let Some(val) = $key {
+          if val.is_auto_length() {
+            if let Some(targets) = self.targets {
+              if !compat::Feature::ContainIntrinsicSizeAuto.is_compatible(targets) {
+                dest.push(Property::$prop(val.without_auto()));
+              }
+            }
+          }
+          dest.push(Property::$prop(val));
+        }
+      };
+    }
+
+    single_property!(ContainIntrinsicWidth, width);
+    single_property!(ContainIntrinsicHeight, height);
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct ContentVisibilityHandler {
+  targets: Option<Browsers>,
+  contain: Option<Contain>,
+  content_visibility: Option<ContentVisibility>,
+  has_unparsed_contain: bool,
+}
+
+impl ContentVisibilityHandler {
+  pub fn new(targets: Option<Browsers>) -> Self {
+    ContentVisibilityHandler {
+      targets,
+      ..ContentVisibilityHandler::default()
+    }
+  }
+}
+
+impl<'i> PropertyHandler<'i> for ContentVisibilityHandler {
+  fn handle_property(
+    &mut self,
+    property: &Property<'i>,
+    dest: &mut DeclarationList<'i>,
+    context: &mut PropertyHandlerContext<'i, '_>,
+  ) -> bool {
+    match property {
+      Property::Contain(contain) => self.contain = Some(*contain),
+      Property::ContentVisibility(content_visibility) => self.content_visibility = Some(*content_visibility),
+      Property::Unparsed(val)
+        if matches!(val.property_id, PropertyId::Contain | PropertyId::ContentVisibility) =>
+      {
+        self.finalize(dest, context);
+        if val.property_id == PropertyId::Contain {
+          self.has_unparsed_contain = true;
+        }
+
+        let mut unparsed = val.clone();
+        context.add_unparsed_fallbacks(&mut unparsed);
+        dest.push(Property::Unparsed(unparsed));
+      }
+      _ => return false,
+    }
+
+    true
+  }
+
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+    let contain = std::mem::take(&mut self.contain);
+    let content_visibility = std::mem::take(&mut self.content_visibility);
+    let has_contain = contain.is_some() || std::mem::take(&mut self.has_unparsed_contain);
+
+    if let Some(contain) = contain {
+      dest.push(Property::Contain(contain));
+    }
+
+    if let Some(content_visibility) = content_visibility {
+      // Browsers without content-visibility can still benefit from the containment it implies,
+      // as long as the author hasn't specified their own.
+      if let Some(targets) = self.targets {
+        let containment = content_visibility.containment();
+        if !has_contain && !containment.is_empty() && !compat::Feature::ContentVisibility.is_compatible(targets) {
+          dest.push(Property::Contain(containment));
+        }
+      }
+
+      dest.push(Property::ContentVisibility(content_visibility));
+    }
+  }
+}
@@ -5,6 +5,7 @@ use crate::error::{ParserError, PrinterError, PrinterErrorKind};
 use crate::macros::enum_property;
 use crate::prefixes::Feature;
 use crate::printer::Printer;
+use crate::properties::anchor::{AnchorFunction, AnchorSizeFunction};
 use crate::properties::PropertyId;
 use crate::rules::supports::SupportsCondition;
 use crate::stylesheet::ParserOptions;
@@ -20,6 +21,7 @@ use crate::values::number::CSSInteger;
 use crate::values::percentage::Percentage;
 use crate::values::resolution::Resolution;
 use crate::values::string::CowArcStr;
+use crate::values::syntax::SyntaxString;
 use crate::values::time::Time;
 use crate::values::url::Url;
 use crate::vendor_prefix::VendorPrefix;
@@ -234,6 +236,12 @@ pub enum TokenOrValue<'i> {
   Env(EnvironmentVariable<'i>),
   /// A custom CSS function.
   Function(Function<'i>),
+  /// An anchor positioning function.
+  Anchor(AnchorFunction<'i>),
+  /// An anchor size function.
+  AnchorSize(AnchorSizeFunction<'i>),
+  /// An attr() function.
+  Attr(AttrFunction<'i>),
   /// A length.
   Length(LengthValue),
   /// An angle.
@@ -293,6 +301,61 @@ impl<'i> TokenList<'i> {
     return Ok(TokenList(tokens));
   }
 
+  /// Tokenizes a serialized CSS value into a token list that owns all of its strings,
+  /// so that it can outlive the source string.
+  pub(crate) fn tokenize_owned(css: &str) -> TokenList<'i> {
+    fn tokenize<'i>(input: &mut Parser, tokens: &mut Vec<TokenOrValue<'i>>) {
+      // Whitespace around delimiters is omitted, as in TokenList::parse.
+      let mut last_is_delim = false;
+      while let Ok(token) = input.next_including_whitespace() {
+        let is_delim = matches!(token, cssparser::Token::Delim(_) | cssparser::Token::Comma);
+        if is_delim && matches!(tokens.last(), Some(token) if token.is_whitespace()) {
+          tokens.pop();
+        }
+        if last_is_delim && matches!(token, cssparser::Token::WhiteSpace(_)) {
+          continue;
+        }
+        last_is_delim = is_delim;
+
+        let closing_delimiter = match token {
+          cssparser::Token::Function(name) => {
+            let name = Ident(name.to_string().into());
+            let mut arguments = Vec::new();
+            let _ = input.parse_nested_block(|input| {
+              tokenize(input, &mut arguments);
+              Ok::<_, ParseError<()>>(())
+            });
+            tokens.push(TokenOrValue::Function(Function {
+              name,
+              arguments: TokenList(arguments),
+            }));
+            continue;
+          }
+          cssparser::Token::ParenthesisBlock => Token::CloseParenthesis,
+          cssparser::Token::SquareBracketBlock => Token::CloseSquareBracket,
+          cssparser::Token::CurlyBracketBlock => Token::CloseCurlyBracket,
+          token => {
+            tokens.push(owned_token(token).into());
+            continue;
+          }
+        };
+
+        tokens.push(owned_token(token).into());
+        let _ = input.parse_nested_block(|input| {
+          tokenize(input, tokens);
+          Ok::<_, ParseError<()>>(())
+        });
+        tokens.push(closing_delimiter.into());
+      }
+    }
+
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+    let mut tokens = Vec::new();
+    tokenize(&mut parser, &mut tokens);
+    TokenList(tokens)
+  }
+
   fn parse_into<'t>(
     input: &mut Parser<'i, 't>,
     tokens: &mut Vec<TokenOrValue<'i>>,
@@ -340,6 +403,40 @@ impl<'i> TokenList<'i> {
             tokens.push(var);
             last_is_delim = true;
             last_is_whitespace = false;
+          } else if f == "anchor" || f == "anchor-size" {
+            if let Ok(anchor) = input.try_parse(|input| {
+              input.parse_nested_block(|input| {
+                if f == "anchor" {
+                  Ok(TokenOrValue::Anchor(AnchorFunction::parse_nested(input)?))
+                } else {
+                  Ok(TokenOrValue::AnchorSize(AnchorSizeFunction::parse_nested(input)?))
+                }
+              })
+            }) {
+              tokens.push(anchor);
+            } else {
+              let arguments = input.parse_nested_block(|input| TokenList::parse(input, options, depth + 1))?;
+              tokens.push(TokenOrValue::Function(Function {
+                name: Ident(f),
+                arguments,
+              }));
+            }
+            last_is_delim = true;
+            last_is_whitespace = false;
+          } else if f == "attr" {
+            if let Ok(attr) = input.try_parse(|input| {
+              input.parse_nested_block(|input| AttrFunction::parse_nested(input, options, depth + 1))
+            }) {
+              tokens.push(TokenOrValue::Attr(attr));
+            } else {
+              let arguments = input.parse_nested_block(|input| TokenList::parse(input, options, depth + 1))?;
+              tokens.push(TokenOrValue::Function(Function {
+                name: Ident(f),
+                arguments,
+              }));
+            }
+            last_is_delim = true;
+            last_is_whitespace = false;
           } else if f == "env" {
             let env = input.parse_nested_block(|input| {
               let env = EnvironmentVariable::parse_nested(input, options, depth + 1)?;
@@ -440,7 +537,7 @@ fn try_parse_color_token<'i, 't>(
   input: &mut Parser<'i, 't>,
 ) -> Option<CssColor> {
   match_ignore_ascii_case! { &*f,
-    "rgb" | "rgba" | "hsl" | "hsla" | "hwb" | "lab" | "lch" | "oklab" | "oklch" | "color" | "color-mix" => {
+    "rgb" | "rgba" | "hsl" | "hsla" | "hwb" | "lab" | "lch" | "oklab" | "oklch" | "color" | "color-mix" | "light-dark" => {
       let s = input.state();
       input.reset(&state);
       if let Ok(color) = CssColor::parse(input) {
@@ -498,6 +595,18 @@ impl<'i> TokenList<'i> {
           f.to_css(dest, is_custom_property)?;
           self.write_whitespace_if_needed(i, dest)?
         }
+        TokenOrValue::Anchor(anchor) => {
+          anchor.to_css(dest)?;
+          self.write_whitespace_if_needed(i, dest)?
+        }
+        TokenOrValue::AnchorSize(anchor_size) => {
+          anchor_size.to_css(dest)?;
+          self.write_whitespace_if_needed(i, dest)?
+        }
+        TokenOrValue::Attr(attr) => {
+          attr.to_css(dest, is_custom_property)?;
+          self.write_whitespace_if_needed(i, dest)?
+        }
         TokenOrValue::Length(v) => {
           // Do not serialize unitless zero lengths in custom properties as it may break calc().
           let (value, unit) = v.to_unit_value();
@@ -568,7 +677,9 @@ impl<'i> TokenList<'i> {
       && i != self.0.len() - 1
       && !matches!(
         self.0[i + 1],
-        TokenOrValue::Token(Token::Comma) | TokenOrValue::Token(Token::CloseParenthesis)
+        TokenOrValue::Token(Token::Comma)
+          | TokenOrValue::Token(Token::CloseParenthesis)
+          | TokenOrValue::Token(Token::Delim('+' | '-'))
       )
     {
       // Whitespace is removed during parsing, so add it back if we aren't minifying.
@@ -838,6 +949,70 @@ impl<'a> From<&cssparser::Token<'a>> for Token<'a> {
   }
 }
 
+/// Converts a cssparser token to a token with owned strings.
+fn owned_token<'i>(token: &cssparser::Token) -> Token<'i> {
+  let owned = |s: &CowRcStr| CowArcStr::from(s.to_string());
+  match token {
+    cssparser::Token::Ident(x) => Token::Ident(owned(x)),
+    cssparser::Token::AtKeyword(x) => Token::AtKeyword(owned(x)),
+    cssparser::Token::Hash(x) => Token::Hash(owned(x)),
+    cssparser::Token::IDHash(x) => Token::IDHash(owned(x)),
+    cssparser::Token::QuotedString(x) => Token::String(owned(x)),
+    cssparser::Token::UnquotedUrl(x) => Token::UnquotedUrl(owned(x)),
+    cssparser::Token::Function(x) => Token::Function(owned(x)),
+    cssparser::Token::BadUrl(x) => Token::BadUrl(owned(x)),
+    cssparser::Token::BadString(x) => Token::BadString(owned(x)),
+    cssparser::Token::WhiteSpace(_) => Token::WhiteSpace(" ".into()),
+    cssparser::Token::Comment(x) => Token::Comment(x.to_string().into()),
+    cssparser::Token::Dimension {
+      has_sign,
+      value,
+      int_value,
+      unit,
+    } => Token::Dimension {
+      has_sign: *has_sign,
+      value: *value,
+      int_value: *int_value,
+      unit: owned(unit),
+    },
+    cssparser::Token::Delim(c) => Token::Delim(*c),
+    cssparser::Token::Number {
+      has_sign,
+      value,
+      int_value,
+    } => Token::Number {
+      has_sign: *has_sign,
+      value: *value,
+      int_value: *int_value,
+    },
+    cssparser::Token::Percentage {
+      has_sign,
+      unit_value,
+      int_value,
+    } => Token::Percentage {
+      has_sign: *has_sign,
+      unit_value: *unit_value,
+      int_value: *int_value,
+    },
+    cssparser::Token::Colon => Token::Colon,
+    cssparser::Token::Semicolon => Token::Semicolon,
+    cssparser::Token::Comma => Token::Comma,
+    cssparser::Token::IncludeMatch => Token::IncludeMatch,
+    cssparser::Token::DashMatch => Token::DashMatch,
+    cssparser::Token::PrefixMatch => Token::PrefixMatch,
+    cssparser::Token::SuffixMatch => Token::SuffixMatch,
+    cssparser::Token::SubstringMatch => Token::SubstringMatch,
+    cssparser::Token::CDO => Token::CDO,
+    cssparser::Token::CDC => Token::CDC,
+    cssparser::Token::ParenthesisBlock => Token::ParenthesisBlock,
+    cssparser::Token::SquareBracketBlock => Token::SquareBracketBlock,
+    cssparser::Token::CurlyBracketBlock => Token::CurlyBracketBlock,
+    cssparser::Token::CloseParenthesis => Token::CloseParenthesis,
+    cssparser::Token::CloseSquareBracket => Token::CloseSquareBracket,
+    cssparser::Token::CloseCurlyBracket => Token::CloseCurlyBracket,
+  }
+}
+
 impl<'a> ToCss for Token<'a> {
   #[inline]
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
@@ -994,6 +1169,188 @@ impl<'i> TokenList<'i> {
     res
   }
 
+  /// Returns whether the token list contains any `var()`, `env()`, or `attr()` references.
+  pub(crate) fn has_references(&self) -> bool {
+    self.0.iter().any(|token| match token {
+      TokenOrValue::Var(..) | TokenOrValue::Env(..) | TokenOrValue::Attr(..) => true,
+      TokenOrValue::Function(f) => f.arguments.has_references(),
+      TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+      | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => alpha.has_references(),
+      _ => false,
+    })
+  }
+
+  /// Calls the given function for every `var()` reference in the token list, including references
+  /// within fallbacks and function arguments.
+  pub(crate) fn for_each_var<F: FnMut(&Variable<'i>)>(&self, f: &mut F) {
+    for token in &self.0 {
+      match token {
+        TokenOrValue::Var(var) => {
+          f(var);
+          if let Some(fallback) = &var.fallback {
+            fallback.for_each_var(f);
+          }
+        }
+        TokenOrValue::Env(EnvironmentVariable {
+          fallback: Some(fallback),
+          ..
+        }) => fallback.for_each_var(f),
+        TokenOrValue::Function(function) => function.arguments.for_each_var(f),
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => alpha.for_each_var(f),
+        _ => {}
+      }
+    }
+  }
+
+  /// Returns whether the token list contains any `anchor()` or `anchor-size()` functions.
+  pub(crate) fn has_anchor_functions(&self) -> bool {
+    self.0.iter().any(|token| match token {
+      TokenOrValue::Anchor(..) | TokenOrValue::AnchorSize(..) => true,
+      TokenOrValue::Function(f) => f.arguments.has_anchor_functions(),
+      TokenOrValue::Var(Variable {
+        fallback: Some(fallback),
+        ..
+      })
+      | TokenOrValue::Env(EnvironmentVariable {
+        fallback: Some(fallback),
+        ..
+      }) => fallback.has_anchor_functions(),
+      _ => false,
+    })
+  }
+
+  /// Replaces `var()` references to the given variables with their values, which must not contain any references
+  /// themselves. Fallbacks of references to other variables are also searched. Returns whether any were replaced.
+  pub(crate) fn inline_variables(&mut self, vars: &std::collections::HashMap<String, TokenList<'i>>) -> bool {
+    let mut changed = false;
+    let mut i = 0;
+    while i < self.0.len() {
+      match &mut self.0[i] {
+        TokenOrValue::Var(var) => {
+          if let Some(value) = vars.get(var.name.ident.0.as_ref()) {
+            changed = true;
+            i += self.splice_value(i, value);
+            continue;
+          }
+          if let Some(fallback) = &mut var.fallback {
+            changed |= fallback.inline_variables(vars);
+          }
+        }
+        TokenOrValue::Env(env) => {
+          if let Some(fallback) = &mut env.fallback {
+            changed |= fallback.inline_variables(vars);
+          }
+        }
+        TokenOrValue::Function(f) => changed |= f.arguments.inline_variables(vars),
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+          changed |= alpha.inline_variables(vars)
+        }
+        _ => {}
+      }
+      i += 1;
+    }
+    changed
+  }
+
+  /// Replaces `env()` references to the given environment variables with their values. References with
+  /// indices, and to names that are not in the map, are left untouched. Returns whether any were replaced.
+  pub(crate) fn substitute_environment_variables(
+    &mut self,
+    vars: &std::collections::HashMap<String, TokenList<'i>>,
+  ) -> bool {
+    let mut changed = false;
+    let mut i = 0;
+    while i < self.0.len() {
+      match &mut self.0[i] {
+        TokenOrValue::Env(env) => {
+          if env.indices.is_empty() {
+            if let Some(value) = vars.get(env.name.name()) {
+              changed = true;
+              i += self.splice_value(i, value);
+              continue;
+            }
+          }
+          if let Some(fallback) = &mut env.fallback {
+            changed |= fallback.substitute_environment_variables(vars);
+          }
+        }
+        TokenOrValue::Var(var) => {
+          if let Some(fallback) = &mut var.fallback {
+            changed |= fallback.substitute_environment_variables(vars);
+          }
+        }
+        TokenOrValue::Function(f) => changed |= f.arguments.substitute_environment_variables(vars),
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+          changed |= alpha.substitute_environment_variables(vars)
+        }
+        _ => {}
+      }
+      i += 1;
+    }
+    changed
+  }
+
+  /// Replaces typed `attr()` functions that have a fallback with the fallback value, for browsers that only support
+  /// `attr()` in the `content` property. Returns whether any were replaced.
+  pub(crate) fn replace_typed_attr(&mut self) -> bool {
+    let mut changed = false;
+    let mut i = 0;
+    while i < self.0.len() {
+      match &mut self.0[i] {
+        TokenOrValue::Attr(AttrFunction {
+          attr_type: Some(..),
+          fallback: Some(fallback),
+          ..
+        }) => {
+          let mut fallback = fallback.clone();
+          fallback.replace_typed_attr();
+          changed = true;
+          i += self.splice_value(i, &fallback);
+          continue;
+        }
+        TokenOrValue::Attr(AttrFunction {
+          fallback: Some(fallback),
+          ..
+        })
+        | TokenOrValue::Var(Variable {
+          fallback: Some(fallback),
+          ..
+        })
+        | TokenOrValue::Env(EnvironmentVariable {
+          fallback: Some(fallback),
+          ..
+        }) => changed |= fallback.replace_typed_attr(),
+        TokenOrValue::Function(f) => changed |= f.arguments.replace_typed_attr(),
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+          changed |= alpha.replace_typed_attr()
+        }
+        _ => {}
+      }
+      i += 1;
+    }
+    changed
+  }
+
+  /// Replaces the token at the given index with the given value, and returns the number of tokens inserted.
+  fn splice_value(&mut self, i: usize, value: &TokenList<'i>) -> usize {
+    let mut len = value.0.len();
+    self.0.splice(i..i + 1, value.0.iter().cloned());
+    // Whitespace following a var() or env() is removed during parsing, so restore it to keep the value separated.
+    if matches!(
+      self.0.get(i + len),
+      Some(token) if !token.is_whitespace()
+        && !matches!(token, TokenOrValue::Token(Token::Comma | Token::CloseParenthesis | Token::Delim(..)))
+    ) {
+      self.0.insert(i + len, Token::WhiteSpace(" ".into()).into());
+      len += 1;
+    }
+    len
+  }
+
   /// Substitutes variables with the provided values.
   #[cfg(feature = "substitute_variables")]
   #[cfg_attr(docsrs, doc(cfg(feature = "substitute_variables")))]
@@ -1270,6 +1627,156 @@ impl<'i> EnvironmentVariable<'i> {
   }
 }
 
+/// An [attr()](https://drafts.csswg.org/css-values-5/#attr-notation) function, which substitutes
+/// the value of an attribute of the element, optionally parsed as the given type.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct AttrFunction<'i> {
+  /// The name of the attribute.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: Ident<'i>,
+  /// The type to parse the attribute value as. If omitted, the value is substituted as a string.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub attr_type: Option<AttrType<'i>>,
+  /// A fallback value in case the attribute is missing or cannot be parsed as the given type.
+  pub fallback: Option<TokenList<'i>>,
+}
+
+/// The type of an [attr()](AttrFunction) function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum AttrType<'i> {
+  /// A `type()` function, which parses the value according to a syntax, e.g. `type(<length>)`.
+  Type(SyntaxString),
+  /// The `raw-string` keyword, which substitutes the value as a string.
+  RawString,
+  /// The `number` keyword, which parses the value as a number.
+  Number,
+  /// A unit, e.g. `px` or `%`, which parses the value as a number with that unit.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Unit(CowArcStr<'i>),
+}
+
+impl<'i> AttrFunction<'i> {
+  pub(crate) fn parse_nested<'t>(
+    input: &mut Parser<'i, 't>,
+    options: &ParserOptions<'_, 'i>,
+    depth: usize,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let name = Ident(input.expect_ident_cloned()?.into());
+    let attr_type = input.try_parse(AttrType::parse).ok();
+    let fallback = if input.try_parse(|input| input.expect_comma()).is_ok() {
+      Some(TokenList::parse(input, options, depth + 1)?)
+    } else {
+      None
+    };
+
+    Ok(AttrFunction {
+      name,
+      attr_type,
+      fallback,
+    })
+  }
+
+  pub(crate) fn to_css<W>(&self, dest: &mut Printer<W>, is_custom_property: bool) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("attr(")?;
+    self.name.to_css(dest)?;
+
+    if let Some(attr_type) = &self.attr_type {
+      dest.write_char(' ')?;
+      attr_type.to_css(dest)?;
+    }
+
+    if let Some(fallback) = &self.fallback {
+      dest.delim(',', false)?;
+      fallback.to_css(dest, is_custom_property)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+impl<'i> Parse<'i> for AttrType<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_function_matching("type")).is_ok() {
+      return input.parse_nested_block(|input| {
+        let start = input.position();
+        while input.next().is_ok() {}
+        let syntax = SyntaxString::parse_string(input.slice_from(start))
+          .map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        Ok(AttrType::Type(syntax))
+      });
+    }
+
+    if input.try_parse(|input| input.expect_delim('%')).is_ok() {
+      return Ok(AttrType::Unit("%".into()));
+    }
+
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    Ok(match_ignore_ascii_case! { ident,
+      "raw-string" => AttrType::RawString,
+      "number" => AttrType::Number,
+      _ => {
+        if !is_known_unit(ident) {
+          return Err(location.new_unexpected_token_error(cssparser::Token::Ident(ident.clone())));
+        }
+        AttrType::Unit(ident.into())
+      }
+    })
+  }
+}
+
+/// Returns whether the given unit is a known CSS dimension unit.
+fn is_known_unit(unit: &CowRcStr) -> bool {
+  let token = cssparser::Token::Dimension {
+    has_sign: false,
+    value: 0.0,
+    int_value: Some(0),
+    unit: unit.clone(),
+  };
+  LengthValue::try_from(&token).is_ok()
+    || Angle::try_from(&token).is_ok()
+    || Time::try_from(&token).is_ok()
+    || Resolution::try_from(&token).is_ok()
+    || unit.eq_ignore_ascii_case("fr")
+    || unit.eq_ignore_ascii_case("hz")
+    || unit.eq_ignore_ascii_case("khz")
+}
+
+impl<'i> ToCss for AttrType<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      AttrType::Type(syntax) => {
+        dest.write_str("type(")?;
+        syntax.to_css_unquoted(dest)?;
+        dest.write_char(')')
+      }
+      AttrType::RawString => dest.write_str("raw-string"),
+      AttrType::Number => dest.write_str("number"),
+      AttrType::Unit(unit) => dest.write_str(unit),
+    }
+  }
+}
+
 /// A custom CSS function.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
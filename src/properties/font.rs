@@ -10,7 +10,7 @@ use crate::error::{ParserError, PrinterError};
 use crate::macros::*;
 use crate::printer::Printer;
 use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss};
-use crate::values::number::CSSNumber;
+use crate::values::number::{CSSInteger, CSSNumber};
 use crate::values::string::CowArcStr;
 use crate::values::{angle::Angle, length::LengthPercentage, percentage::Percentage};
 #[cfg(feature = "visitor")]
@@ -415,7 +415,11 @@ impl<'i> ToCss for FontFamily<'i> {
         // Generic family names such as sans-serif must be quoted if parsed as a string.
         // CSS wide keywords, as well as "default", must also be quoted.
         // https://www.w3.org/TR/css-fonts-4/#family-name-syntax
-        if !val.is_empty() && !GenericFontFamily::parse_string(val).is_ok() {
+        // Runs of whitespace are collapsed when parsing identifiers, so names containing them must stay quoted.
+        if !val.is_empty()
+          && !val.split(' ').any(|slice| slice.is_empty())
+          && !GenericFontFamily::parse_string(val).is_ok()
+        {
           let mut id = String::new();
           let mut first = true;
           for slice in val.split(' ') {
@@ -659,6 +663,220 @@ impl ToCss for VerticalAlign {
   }
 }
 
+/// A value for the [font-feature-settings](https://www.w3.org/TR/css-fonts-4/#font-feature-settings-prop) property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum FontFeatureSettings<'i> {
+  /// The default feature settings of the font are used.
+  Normal,
+  /// A list of OpenType feature settings.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Features(Vec<FontFeature<'i>>),
+}
+
+/// An OpenType feature setting, as used in the `font-feature-settings` property.
+///
+/// See [FontFeatureSettings](FontFeatureSettings).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct FontFeature<'i> {
+  /// The four character feature tag.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub tag: CowArcStr<'i>,
+  /// The feature value. `on` is represented as 1 and `off` as 0.
+  pub value: CSSInteger,
+}
+
+/// A value for the [font-variation-settings](https://www.w3.org/TR/css-fonts-4/#font-variation-settings-def) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum FontVariationSettings<'i> {
+  /// The default variation settings of the font are used.
+  Normal,
+  /// A list of variation axis values.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Axes(Vec<FontVariationAxis<'i>>),
+}
+
+/// A font variation axis value, as used in the `font-variation-settings` property.
+///
+/// See [FontVariationSettings](FontVariationSettings).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct FontVariationAxis<'i> {
+  /// The four character axis tag.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub tag: CowArcStr<'i>,
+  /// The axis value.
+  pub value: CSSNumber,
+}
+
+/// Parses an OpenType tag, which must be a string of exactly four printable ASCII characters.
+fn parse_opentype_tag<'i, 't>(
+  input: &mut Parser<'i, 't>,
+) -> Result<CowArcStr<'i>, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  let tag = input.expect_string_cloned()?;
+  if tag.len() != 4 || !tag.chars().all(|c| (' '..='~').contains(&c)) {
+    return Err(location.new_custom_error(ParserError::InvalidValue));
+  }
+  Ok(tag.into())
+}
+
+impl<'i> Parse<'i> for FontFeatureSettings<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontFeatureSettings::Normal);
+    }
+
+    Ok(FontFeatureSettings::Features(
+      input.parse_comma_separated(FontFeature::parse)?,
+    ))
+  }
+}
+
+impl<'i> Parse<'i> for FontFeature<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let tag = parse_opentype_tag(input)?;
+    let location = input.current_source_location();
+    let value = if let Ok(value) = input.try_parse(CSSInteger::parse) {
+      if value < 0 {
+        return Err(location.new_custom_error(ParserError::InvalidValue));
+      }
+      value
+    } else if input.try_parse(|input| input.expect_ident_matching("off")).is_ok() {
+      0
+    } else {
+      let _ = input.try_parse(|input| input.expect_ident_matching("on"));
+      1
+    };
+
+    Ok(FontFeature { tag, value })
+  }
+}
+
+impl<'i> ToCss for FontFeatureSettings<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      FontFeatureSettings::Normal => dest.write_str("normal"),
+      FontFeatureSettings::Features(features) => {
+        // When a tag appears more than once, only the last value is used.
+        let mut first = true;
+        for (i, feature) in features.iter().enumerate() {
+          if features[i + 1..].iter().any(|f| f.tag == feature.tag) {
+            continue;
+          }
+
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          feature.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl<'i> ToCss for FontFeature<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    serialize_string(&self.tag, dest)?;
+    // A value of 1 is the default, and may be omitted.
+    if self.value != 1 {
+      dest.whitespace()?;
+      self.value.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl<'i> Parse<'i> for FontVariationSettings<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariationSettings::Normal);
+    }
+
+    Ok(FontVariationSettings::Axes(
+      input.parse_comma_separated(FontVariationAxis::parse)?,
+    ))
+  }
+}
+
+impl<'i> Parse<'i> for FontVariationAxis<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let tag = parse_opentype_tag(input)?;
+    let value = CSSNumber::parse(input)?;
+    Ok(FontVariationAxis { tag, value })
+  }
+}
+
+impl<'i> ToCss for FontVariationSettings<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      FontVariationSettings::Normal => dest.write_str("normal"),
+      FontVariationSettings::Axes(axes) => {
+        // When an axis appears more than once, only the last value is used.
+        let mut first = true;
+        for (i, axis) in axes.iter().enumerate() {
+          if axes[i + 1..].iter().any(|a| a.tag == axis.tag) {
+            continue;
+          }
+
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          axis.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl<'i> ToCss for FontVariationAxis<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    serialize_string(&self.tag, dest)?;
+    dest.whitespace()?;
+    self.value.to_css(dest)
+  }
+}
+
 define_shorthand! {
   /// A value for the [font](https://www.w3.org/TR/css-fonts-4/#font-prop) shorthand property.
   #[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
@@ -854,6 +1072,11 @@ impl<'i> PropertyHandler<'i> for FontHandler<'i> {
         self.finalize(dest, context);
         dest.push(property.clone());
       }
+      // The `font` shorthand resets these properties, so they must stay after it.
+      FontFeatureSettings(..) | FontVariationSettings(..) => {
+        self.finalize(dest, context);
+        dest.push(property.clone());
+      }
       _ => return false,
     }
 
@@ -5,7 +5,7 @@
 use crate::context::PropertyHandlerContext;
 use crate::declaration::{DeclarationBlock, DeclarationList};
 use crate::error::{Error, ErrorLocation, ParserError, PrinterError, PrinterErrorKind};
-use crate::macros::{define_shorthand, impl_shorthand};
+use crate::macros::{define_shorthand, enum_property, impl_shorthand};
 use crate::printer::Printer;
 use crate::properties::{Property, PropertyId};
 use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss};
@@ -39,6 +39,8 @@ pub enum TrackSizing<'i> {
   /// A list of grid tracks.
   #[cfg_attr(feature = "serde", serde(borrow))]
   TrackList(TrackList<'i>),
+  /// The axis is laid out as a [masonry axis](https://drafts.csswg.org/css-grid-3/#masonry-layout).
+  Masonry,
 }
 
 /// A [`<track-list>`](https://drafts.csswg.org/css-grid-2/#typedef-track-list) value,
@@ -163,12 +165,12 @@ pub enum TrackBreadth {
 #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
 pub struct TrackRepeat<'i> {
   /// The repeat count.
-  count: RepeatCount,
+  pub count: RepeatCount,
   /// The line names to repeat.
   #[cfg_attr(feature = "serde", serde(borrow))]
-  line_names: Vec<CustomIdentList<'i>>,
+  pub line_names: Vec<CustomIdentList<'i>>,
   /// The track sizes to repeat.
-  track_sizes: Vec<TrackSize>,
+  pub track_sizes: Vec<TrackSize>,
 }
 
 /// A [`<repeat-count>`](https://drafts.csswg.org/css-grid-2/#typedef-track-repeat) value,
@@ -518,6 +520,10 @@ impl<'i> Parse<'i> for TrackSizing<'i> {
       return Ok(TrackSizing::None);
     }
 
+    if input.try_parse(|input| input.expect_ident_matching("masonry")).is_ok() {
+      return Ok(TrackSizing::Masonry);
+    }
+
     let track_list = TrackList::parse(input)?;
     Ok(TrackSizing::TrackList(track_list))
   }
@@ -531,6 +537,7 @@ impl<'i> ToCss for TrackSizing<'i> {
     match self {
       TrackSizing::None => dest.write_str("none"),
       TrackSizing::TrackList(list) => list.to_css(dest),
+      TrackSizing::Masonry => dest.write_str("masonry"),
     }
   }
 }
@@ -540,6 +547,7 @@ impl<'i> TrackSizing<'i> {
     match self {
       TrackSizing::None => true,
       TrackSizing::TrackList(list) => list.is_explicit(),
+      TrackSizing::Masonry => false,
     }
   }
 }
@@ -663,6 +671,44 @@ impl GridTemplateAreas {
   }
 }
 
+impl GridTemplateAreas {
+  /// Returns the name of the first grid area whose cells do not form a single filled-in rectangle.
+  /// Such declarations are invalid, and will be ignored by browsers.
+  pub(crate) fn non_rectangular_area(&self) -> Option<&str> {
+    let (columns, areas) = match self {
+      GridTemplateAreas::Areas { columns, areas } if *columns > 0 => (*columns as usize, areas),
+      _ => return None,
+    };
+
+    // The bounding box and number of cells of each area, in order of first appearance.
+    let mut bounds: Vec<(&str, usize, usize, usize, usize, usize)> = Vec::new();
+    for (i, area) in areas.iter().enumerate() {
+      let name = match area {
+        Some(name) => name.as_str(),
+        None => continue,
+      };
+
+      let (row, column) = (i / columns, i % columns);
+      if let Some(b) = bounds.iter_mut().find(|b| b.0 == name) {
+        b.1 = b.1.min(row);
+        b.2 = b.2.max(row);
+        b.3 = b.3.min(column);
+        b.4 = b.4.max(column);
+        b.5 += 1;
+      } else {
+        bounds.push((name, row, row, column, column, 1));
+      }
+    }
+
+    bounds
+      .into_iter()
+      .find(|(_, min_row, max_row, min_col, max_col, count)| {
+        (max_row - min_row + 1) * (max_col - min_col + 1) != *count
+      })
+      .map(|b| b.0)
+  }
+}
+
 static HTML_SPACE_CHARACTERS: &'static [char] = &['\u{0020}', '\u{0009}', '\u{000a}', '\u{000c}', '\u{000d}'];
 
 fn is_name_code_point(c: char) -> bool {
@@ -1143,6 +1189,121 @@ impl ToCss for GridAutoFlow {
   }
 }
 
+enum_property! {
+  /// A masonry item placement strategy, as used in the `masonry-auto-flow` property.
+  pub enum MasonryPlacement {
+    /// Items are placed in the track with the most remaining space.
+    "pack": Pack,
+    /// Items are placed in the next track, in order.
+    "next": Next,
+  }
+}
+
+enum_property! {
+  /// A masonry item ordering, as used in the `masonry-auto-flow` property.
+  pub enum MasonryOrder {
+    /// Items with a definite placement are placed before auto-placed items.
+    "definite-first": DefiniteFirst,
+    /// Items are placed in order-modified document order.
+    "ordered": Ordered,
+  }
+}
+
+/// A value for the [masonry-auto-flow](https://drafts.csswg.org/css-grid-3/#masonry-auto-flow) property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct MasonryAutoFlow {
+  /// How items are assigned to tracks.
+  pub placement: MasonryPlacement,
+  /// The order in which items are placed.
+  pub order: MasonryOrder,
+}
+
+impl Default for MasonryAutoFlow {
+  fn default() -> MasonryAutoFlow {
+    MasonryAutoFlow {
+      placement: MasonryPlacement::Pack,
+      order: MasonryOrder::DefiniteFirst,
+    }
+  }
+}
+
+impl<'i> Parse<'i> for MasonryAutoFlow {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut placement = None;
+    let mut order = None;
+    loop {
+      if placement.is_none() {
+        if let Ok(value) = input.try_parse(MasonryPlacement::parse) {
+          placement = Some(value);
+          continue;
+        }
+      }
+
+      if order.is_none() {
+        if let Ok(value) = input.try_parse(MasonryOrder::parse) {
+          order = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    if placement.is_none() && order.is_none() {
+      return Err(input.new_error_for_next_token());
+    }
+
+    Ok(MasonryAutoFlow {
+      placement: placement.unwrap_or(MasonryPlacement::Pack),
+      order: order.unwrap_or(MasonryOrder::DefiniteFirst),
+    })
+  }
+}
+
+impl ToCss for MasonryAutoFlow {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.order == MasonryOrder::DefiniteFirst {
+      return self.placement.to_css(dest);
+    }
+
+    if self.placement != MasonryPlacement::Pack {
+      self.placement.to_css(dest)?;
+      dest.write_char(' ')?;
+    }
+
+    self.order.to_css(dest)
+  }
+}
+
+/// Returns whether any of the declarations lay out an axis using masonry.
+pub(crate) fn uses_masonry(declarations: &DeclarationBlock) -> bool {
+  declarations.iter().any(|(property, _)| property_uses_masonry(property))
+}
+
+/// Returns whether the property lays out an axis using masonry.
+pub(crate) fn property_uses_masonry(property: &Property) -> bool {
+  match property {
+    Property::GridTemplateRows(TrackSizing::Masonry)
+    | Property::GridTemplateColumns(TrackSizing::Masonry)
+    | Property::MasonryAutoFlow(_) => true,
+    Property::GridTemplate(template) => {
+      template.rows == TrackSizing::Masonry || template.columns == TrackSizing::Masonry
+    }
+    Property::Grid(grid) => grid.rows == TrackSizing::Masonry || grid.columns == TrackSizing::Masonry,
+    _ => false,
+  }
+}
+
 /// A value for the [grid](https://drafts.csswg.org/css-grid-2/#grid-shorthand) shorthand property.
 ///
 /// Explicit and implicit values may not be combined.
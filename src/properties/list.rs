@@ -8,8 +8,9 @@ use crate::macros::{define_shorthand, enum_property, shorthand_handler, shorthan
 use crate::printer::Printer;
 use crate::targets::Browsers;
 use crate::traits::{FallbackValues, Parse, PropertyHandler, Shorthand, ToCss};
+use crate::values::ident::{CustomIdent, Ident};
+use crate::values::image::Image;
 use crate::values::string::CSSString;
-use crate::values::{ident::CustomIdent, image::Image};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
@@ -269,6 +270,8 @@ pub enum Symbol<'i> {
   String(CSSString<'i>),
   /// An image.
   Image(Image<'i>),
+  /// An identifier. Only valid within an `@counter-style` rule.
+  Ident(Ident<'i>),
 }
 
 impl<'i> Parse<'i> for Symbol<'i> {
@@ -290,6 +293,7 @@ impl<'i> ToCss for Symbol<'i> {
     match self {
       Symbol::String(s) => s.to_css(dest),
       Symbol::Image(img) => img.to_css(dest),
+      Symbol::Ident(ident) => ident.to_css(dest),
     }
   }
 }
@@ -342,6 +346,11 @@ impl<'i> FallbackValues for ListStyle<'i> {
       .map(|image| ListStyle { image, ..self.clone() })
       .collect()
   }
+
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    let image = self.image.get_image_set_fallback()?;
+    Some(ListStyle { image, ..self.clone() })
+  }
 }
 
 shorthand_handler!(ListStyleHandler -> ListStyle<'i> fallbacks: true {
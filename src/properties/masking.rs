@@ -547,6 +547,11 @@ impl<'i> FallbackValues for MaskBorder<'i> {
       .map(|source| MaskBorder { source, ..self.clone() })
       .collect()
   }
+
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    let source = self.source.get_image_set_fallback()?;
+    Some(MaskBorder { source, ..self.clone() })
+  }
 }
 
 impl<'i> Into<BorderImage<'i>> for MaskBorder<'i> {
@@ -821,7 +826,7 @@ impl<'i> MaskHandler<'i> {
         }
 
         if let Some(targets) = context.targets {
-          for fallback in masks.get_fallbacks(targets) {
+          for fallback in context.get_color_fallbacks(&mut masks, targets) {
             // Match prefix of fallback. e.g. -webkit-linear-gradient
             // can only be used in -webkit-mask-image.
             // However, if mask-image is unprefixed, gradients can still be.
@@ -885,7 +890,7 @@ impl<'i> MaskHandler<'i> {
         }
 
         if let Some(targets) = context.targets {
-          for fallback in images.get_fallbacks(targets) {
+          for fallback in context.get_color_fallbacks(&mut images, targets) {
             // Match prefix of fallback. e.g. -webkit-linear-gradient
             // can only be used in -webkit-mask-image.
             // However, if mask-image is unprefixed, gradients can still be.
@@ -1040,7 +1045,7 @@ impl<'i> MaskHandler<'i> {
 
         if let Some(targets) = context.targets {
           // Get vendor prefix and color fallbacks.
-          let fallbacks = mask_border.get_fallbacks(targets);
+          let fallbacks = context.get_color_fallbacks(&mut mask_border, targets);
           for fallback in fallbacks {
             let mut p = fallback.source.get_vendor_prefix() - VendorPrefix::None & prefix;
             if p.is_empty() {
@@ -1093,7 +1098,7 @@ impl<'i> MaskHandler<'i> {
         }
 
         // Get vendor prefix and color fallbacks.
-        let fallbacks = source.get_fallbacks(targets);
+        let fallbacks = context.get_color_fallbacks(&mut source, targets);
         for fallback in fallbacks {
           if prefix.contains(VendorPrefix::WebKit) {
             dest.push(Property::WebKitMaskBoxImageSource(
@@ -91,6 +91,7 @@
 #![deny(missing_docs)]
 
 pub mod align;
+pub mod anchor;
 pub mod animation;
 pub mod background;
 pub mod border;
@@ -111,14 +112,17 @@ pub(crate) mod margin_padding;
 pub mod masking;
 pub mod outline;
 pub mod overflow;
+pub mod page;
 pub mod position;
 pub(crate) mod prefix_handler;
+pub mod shape;
 pub mod size;
 pub mod svg;
 pub mod text;
 pub mod transform;
 pub mod transition;
 pub mod ui;
+pub mod view_transition;
 
 use crate::declaration::DeclarationBlock;
 use crate::error::{ParserError, PrinterError};
@@ -139,6 +143,7 @@ use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use align::*;
+use anchor::*;
 use animation::*;
 use background::*;
 use border::*;
@@ -160,6 +165,8 @@ use margin_padding::*;
 use masking::*;
 use outline::*;
 use overflow::*;
+use page::*;
+use shape::*;
 use size::*;
 use smallvec::{smallvec, SmallVec};
 use svg::*;
@@ -167,6 +174,7 @@ use text::*;
 use transform::*;
 use transition::*;
 use ui::*;
+use view_transition::*;
 
 macro_rules! define_properties {
   (
@@ -778,6 +786,15 @@ macro_rules! define_properties {
 
       /// Serializes the CSS property, with an optional `!important` flag.
       pub fn to_css<W>(&self, dest: &mut Printer<W>, important: bool) -> Result<(), PrinterError> where W: std::fmt::Write {
+        let start = dest.bytes;
+        self.write_declaration(dest, important)?;
+        if let Some(size_breakdown) = &mut dest.size_breakdown {
+          size_breakdown.add_property(self.property_id().name(), dest.bytes - start);
+        }
+        Ok(())
+      }
+
+      fn write_declaration<W>(&self, dest: &mut Printer<W>, important: bool) -> Result<(), PrinterError> where W: std::fmt::Write {
         use Property::*;
 
         let mut first = true;
@@ -1151,9 +1168,60 @@ macro_rules! define_properties {
         "Declaration".into()
       }
     }
+
+    /// Marker types for each CSS property, which implement [TypedProperty](TypedProperty).
+    pub mod typed {
+      $(
+        #[doc=concat!("The `", $name, "` property.")]
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $property;
+      )+
+    }
+
+    $(
+      $(#[$meta])*
+      impl<'i> TypedProperty<'i> for typed::$property {
+        type Value = $type;
+
+        fn property_id() -> PropertyId<'i> {
+          PropertyId::$property$((vp_name!($vp, VendorPrefix::None)))?
+        }
+
+        fn from_property(property: Property<'i>) -> Option<Self::Value> {
+          match property {
+            Property::$property(value, ..) => Some(value),
+            _ => None,
+          }
+        }
+
+        fn into_property(value: Self::Value) -> Property<'i> {
+          Property::$property(value, $(vp_name!($vp, VendorPrefix::None))?)
+        }
+      }
+    )+
   };
 }
 
+/// A CSS property with a typed value, implemented by the marker types in the [typed](typed) module,
+/// e.g. [typed::Color](typed::Color) with a [CssColor](crate::values::color::CssColor) value. This allows
+/// declarations to be read and written without matching on [Property](Property), using methods such as
+/// [DeclarationBlock::get_value](crate::declaration::DeclarationBlock::get_value).
+pub trait TypedProperty<'i> {
+  /// The type of the property value.
+  type Value;
+
+  /// Returns the id of the property. Properties that support vendor prefixes have no prefix.
+  fn property_id() -> PropertyId<'i>;
+
+  /// Returns the value of the given property, if it is this property.
+  fn from_property(property: Property<'i>) -> Option<Self::Value>;
+
+  /// Returns a declaration of this property with the given value. Properties that support vendor
+  /// prefixes have no prefix.
+  fn into_property(value: Self::Value) -> Property<'i>;
+}
+
 define_properties! {
   "background-color": BackgroundColor(CssColor),
   "background-image": BackgroundImage(SmallVec<[Image<'i>; 1]>),
@@ -1206,6 +1274,11 @@ define_properties! {
   "inset-inline": InsetInline(InsetInline) shorthand: true,
   "inset": Inset(Inset) shorthand: true,
 
+  // https://drafts.csswg.org/css-anchor-position-1/
+  "anchor-name": AnchorName(AnchorName<'i>),
+  "position-anchor": PositionAnchor(PositionAnchor<'i>),
+  "position-area": PositionArea(PositionArea),
+
   "border-spacing": BorderSpacing(Size2D<Length>),
 
   "border-top-color": BorderTopColor(CssColor) [logical_group: BorderColor, category: Physical],
@@ -1347,6 +1420,9 @@ define_properties! {
   "grid-template-areas": GridTemplateAreas(GridTemplateAreas),
   #[cfg(feature = "grid")]
   #[cfg_attr(docsrs, doc(cfg(feature = "grid")))]
+  "masonry-auto-flow": MasonryAutoFlow(MasonryAutoFlow),
+  #[cfg(feature = "grid")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "grid")))]
   "grid-template": GridTemplate(GridTemplate<'i>) shorthand: true,
   #[cfg(feature = "grid")]
   #[cfg_attr(docsrs, doc(cfg(feature = "grid")))]
@@ -1431,11 +1507,14 @@ define_properties! {
   "font": Font(Font<'i>) shorthand: true,
   "vertical-align": VerticalAlign(VerticalAlign),
   "font-palette": FontPalette(DashedIdentReference<'i>),
+  "font-feature-settings": FontFeatureSettings(FontFeatureSettings<'i>),
+  "font-variation-settings": FontVariationSettings(FontVariationSettings<'i>),
 
   "transition-property": TransitionProperty(SmallVec<[PropertyId<'i>; 1]>, VendorPrefix) / WebKit / Moz / Ms,
   "transition-duration": TransitionDuration(SmallVec<[Time; 1]>, VendorPrefix) / WebKit / Moz / Ms,
   "transition-delay": TransitionDelay(SmallVec<[Time; 1]>, VendorPrefix) / WebKit / Moz / Ms,
   "transition-timing-function": TransitionTimingFunction(SmallVec<[EasingFunction; 1]>, VendorPrefix) / WebKit / Moz / Ms,
+  "transition-behavior": TransitionBehavior(SmallVec<[TransitionBehavior; 1]>),
   "transition": Transition(SmallVec<[Transition<'i>; 1]>, VendorPrefix) / WebKit / Moz / Ms shorthand: true,
 
   "animation-name": AnimationName(AnimationNameList<'i>, VendorPrefix) / WebKit / Moz / O,
@@ -1460,9 +1539,24 @@ define_properties! {
   "rotate": Rotate(Rotate),
   "scale": Scale(Scale),
 
+  // https://drafts.fxtf.org/motion-1/
+  "offset-position": OffsetPosition(OffsetPosition),
+  "offset-path": OffsetPath(OffsetPath<'i>),
+  "offset-distance": OffsetDistance(LengthPercentage),
+  "offset-rotate": OffsetRotate(OffsetRotate),
+  "offset-anchor": OffsetAnchor(OffsetAnchor),
+  "offset": Offset(Offset<'i>) shorthand: true,
+  "motion-path": MotionPath(OffsetPath<'i>),
+  "motion-offset": MotionOffset(LengthPercentage),
+  "motion-rotation": MotionRotation(OffsetRotate),
+
   // https://www.w3.org/TR/2021/CRD-css-text-3-20210422
   "text-transform": TextTransform(TextTransform),
-  "white-space": WhiteSpace(WhiteSpace),
+  "white-space": WhiteSpace(WhiteSpace) shorthand: true,
+  "white-space-collapse": WhiteSpaceCollapse(WhiteSpaceCollapse),
+  "text-wrap": TextWrap(TextWrap) shorthand: true,
+  "text-wrap-mode": TextWrapMode(TextWrapMode),
+  "text-wrap-style": TextWrapStyle(TextWrapStyle),
   "tab-size": TabSize(LengthOrNumber, VendorPrefix) / Moz / O,
   "word-break": WordBreak(WordBreak),
   "line-break": LineBreak(LineBreak),
@@ -1500,7 +1594,9 @@ define_properties! {
   "caret": Caret(Caret) shorthand: true,
   "user-select": UserSelect(UserSelect, VendorPrefix) / WebKit / Moz / Ms,
   "accent-color": AccentColor(ColorOrAuto),
+  "color-scheme": ColorScheme(ColorScheme),
   "appearance": Appearance(Appearance<'i>, VendorPrefix) / WebKit / Moz / Ms,
+  "field-sizing": FieldSizing(FieldSizing),
 
   // https://www.w3.org/TR/2020/WD-css-lists-3-20201117
   "list-style-type": ListStyleType(ListStyleType<'i>),
@@ -1568,6 +1664,9 @@ define_properties! {
   "mask-box-image-outset": WebKitMaskBoxImageOutset(Rect<LengthOrNumber>, VendorPrefix) / WebKit unprefixed: false,
   "mask-box-image-repeat": WebKitMaskBoxImageRepeat(BorderImageRepeat, VendorPrefix) / WebKit unprefixed: false,
 
+  // https://www.w3.org/TR/css-shapes-1/
+  "shape-outside": ShapeOutside(ShapeOutside<'i>),
+
   // https://drafts.fxtf.org/filter-effects-1/
   "filter": Filter(FilterList<'i>, VendorPrefix) / WebKit,
   "backdrop-filter": BackdropFilter(FilterList<'i>, VendorPrefix) / WebKit,
@@ -1579,6 +1678,21 @@ define_properties! {
   "container-type": ContainerType(ContainerType),
   "container-name": ContainerName(ContainerNameList<'i>),
   "container": Container(Container<'i>) shorthand: true,
+  "contain": Contain(Contain),
+  "content-visibility": ContentVisibility(ContentVisibility),
+
+  // https://drafts.csswg.org/css-sizing-4/
+  "contain-intrinsic-width": ContainIntrinsicWidth(ContainIntrinsicLength),
+  "contain-intrinsic-height": ContainIntrinsicHeight(ContainIntrinsicLength),
+  "contain-intrinsic-size": ContainIntrinsicSize(ContainIntrinsicSize) shorthand: true,
+
+  // https://drafts.csswg.org/css-view-transitions-2/
+  "view-transition-name": ViewTransitionName(ViewTransitionName<'i>),
+  "view-transition-class": ViewTransitionClass(ViewTransitionClass<'i>),
+
+  // https://www.w3.org/TR/css-page-3/
+  "size": Size(PageSize),
+  "page": Page(PageName<'i>),
 }
 
 impl<'i, T: smallvec::Array<Item = V>, V: Parse<'i>> Parse<'i> for SmallVec<T> {
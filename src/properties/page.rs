@@ -0,0 +1,185 @@
+//! CSS properties used within `@page` rules.
+
+use crate::error::{ParserError, PrinterError};
+use crate::macros::enum_property;
+use crate::printer::Printer;
+#[cfg(feature = "serde")]
+use crate::serialization::ValueWrapper;
+use crate::traits::{Parse, ToCss, TrySign};
+use crate::values::ident::{CustomIdent, Ident};
+use crate::values::length::Length;
+use crate::values::size::Size2D;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+enum_property! {
+  /// A standard [page size](https://www.w3.org/TR/css-page-3/#typedef-page-size-page-size)
+  /// keyword, as used in the `size` property.
+  pub enum PageSizeName {
+    /// 148mm × 210mm.
+    "A5": A5,
+    /// 210mm × 297mm.
+    "A4": A4,
+    /// 297mm × 420mm.
+    "A3": A3,
+    /// 176mm × 250mm.
+    "B5": B5,
+    /// 250mm × 353mm.
+    "B4": B4,
+    /// 182mm × 257mm.
+    "JIS-B5": JisB5,
+    /// 257mm × 364mm.
+    "JIS-B4": JisB4,
+    /// 8.5in × 11in.
+    "letter": Letter,
+    /// 8.5in × 14in.
+    "legal": Legal,
+    /// 11in × 17in.
+    "ledger": Ledger,
+  }
+}
+
+enum_property! {
+  /// A page orientation keyword, as used in the `size` property.
+  pub enum PageOrientation {
+    /// The longer side of the page is vertical.
+    Portrait,
+    /// The longer side of the page is horizontal.
+    Landscape,
+  }
+}
+
+/// A value for the [size](https://www.w3.org/TR/css-page-3/#page-size-prop) property,
+/// which specifies the size and orientation of the page box.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum PageSize {
+  /// The page box matches the size and orientation of the target sheet.
+  Auto,
+  /// An explicit width and height.
+  #[cfg_attr(feature = "serde", serde(with = "ValueWrapper::<Size2D<Length>>"))]
+  Size(Size2D<Length>),
+  /// A named page size and/or orientation. At least one of the two is present.
+  Named {
+    /// The named page size.
+    size: Option<PageSizeName>,
+    /// The page orientation.
+    orientation: Option<PageOrientation>,
+  },
+}
+
+impl<'i> Parse<'i> for PageSize {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(PageSize::Auto);
+    }
+
+    if let Ok(size) = input.try_parse(Size2D::<Length>::parse) {
+      // Page dimensions may not be negative.
+      if size.0.is_sign_negative() || size.1.is_sign_negative() {
+        return Err(input.new_custom_error(ParserError::InvalidValue));
+      }
+      return Ok(PageSize::Size(size));
+    }
+
+    let mut size = None;
+    let mut orientation = None;
+    loop {
+      if size.is_none() {
+        if let Ok(value) = input.try_parse(PageSizeName::parse) {
+          size = Some(value);
+          continue;
+        }
+      }
+
+      if orientation.is_none() {
+        if let Ok(value) = input.try_parse(PageOrientation::parse) {
+          orientation = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    if size.is_none() && orientation.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(PageSize::Named { size, orientation })
+  }
+}
+
+impl ToCss for PageSize {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      PageSize::Auto => dest.write_str("auto"),
+      PageSize::Size(size) => size.to_css(dest),
+      PageSize::Named { size, orientation } => {
+        if let Some(size) = size {
+          size.to_css(dest)?;
+          if orientation.is_some() {
+            dest.write_char(' ')?;
+          }
+        }
+        if let Some(orientation) = orientation {
+          orientation.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// A value for the [page](https://www.w3.org/TR/css-page-3/#using-named-pages) property,
+/// which assigns an element to a named page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum PageName<'i> {
+  /// The element uses the page type of its parent.
+  Auto,
+  /// A named page type, as referenced by `@page` selectors.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Name(Ident<'i>),
+}
+
+impl<'i> Parse<'i> for PageName<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(PageName::Auto);
+    }
+
+    // Page names are not scoped by CSS modules, so they are stored as plain identifiers.
+    let name = CustomIdent::parse(input)?;
+    Ok(PageName::Name(Ident(name.0)))
+  }
+}
+
+impl<'i> ToCss for PageName<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      PageName::Auto => dest.write_str("auto"),
+      PageName::Name(name) => name.to_css(dest),
+    }
+  }
+}
@@ -5,7 +5,7 @@ use crate::declaration::DeclarationList;
 use crate::prefixes::Feature;
 use crate::properties::custom::CustomProperty;
 use crate::targets::Browsers;
-use crate::traits::{FallbackValues, PropertyHandler};
+use crate::traits::PropertyHandler;
 use crate::vendor_prefix::VendorPrefix;
 
 macro_rules! define_prefixes {
@@ -129,7 +129,7 @@ macro_rules! define_fallbacks {
                   }
                 )?
 
-                let fallbacks = val.get_fallbacks(targets);
+                let fallbacks = context.get_color_fallbacks(&mut val, targets);
                 #[allow(unused_variables)]
                 let has_fallbacks = !fallbacks.is_empty();
                 for fallback in fallbacks {
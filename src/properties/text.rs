@@ -213,20 +213,220 @@ impl ToCss for TextTransform {
 }
 
 enum_property! {
-  /// A value for the [white-space](https://www.w3.org/TR/2021/CRD-css-text-3-20210422/#white-space-property) property.
-  pub enum WhiteSpace {
+  /// A value for the [white-space-collapse](https://www.w3.org/TR/css-text-4/#white-space-collapsing) property.
+  pub enum WhiteSpaceCollapse {
     /// Sequences of white space are collapsed into a single character.
-    "normal": Normal,
-    /// White space is not collapsed.
-    "pre": Pre,
-    /// White space is collapsed, but no line wrapping occurs.
-    "nowrap": NoWrap,
-    /// White space is preserved, but line wrapping occurs.
-    "pre-wrap": PreWrap,
-    /// Like pre-wrap, but with different line breaking rules.
+    "collapse": Collapse,
+    /// White space is removed.
+    "discard": Discard,
+    /// White space and segment breaks are preserved.
+    "preserve": Preserve,
+    /// Segment breaks are preserved, but other white space is collapsed.
+    "preserve-breaks": PreserveBreaks,
+    /// White space is preserved, but segment breaks are collapsed.
+    "preserve-spaces": PreserveSpaces,
+    /// Like preserve, but preserved white space always takes up space and can wrap.
     "break-spaces": BreakSpaces,
-    /// White space is collapsed, but with different line breaking rules.
-    "pre-line": PreLine,
+  }
+}
+
+impl Default for WhiteSpaceCollapse {
+  fn default() -> WhiteSpaceCollapse {
+    WhiteSpaceCollapse::Collapse
+  }
+}
+
+enum_property! {
+  /// A value for the [text-wrap-mode](https://www.w3.org/TR/css-text-4/#text-wrap-mode) property.
+  pub enum TextWrapMode {
+    /// Lines may wrap at allowed soft wrap opportunities.
+    "wrap": Wrap,
+    /// Lines do not wrap.
+    "nowrap": NoWrap,
+  }
+}
+
+impl Default for TextWrapMode {
+  fn default() -> TextWrapMode {
+    TextWrapMode::Wrap
+  }
+}
+
+enum_property! {
+  /// A value for the [text-wrap-style](https://www.w3.org/TR/css-text-4/#text-wrap-style) property.
+  pub enum TextWrapStyle {
+    /// The UA chooses how lines are wrapped, optimizing for speed.
+    Auto,
+    /// Line lengths are balanced across the block.
+    Balance,
+    /// Lines before the last line being edited are not changed while the content is edited.
+    Stable,
+    /// Lines are wrapped to improve typographic layout, at the expense of speed.
+    Pretty,
+  }
+}
+
+impl Default for TextWrapStyle {
+  fn default() -> TextWrapStyle {
+    TextWrapStyle::Auto
+  }
+}
+
+define_shorthand! {
+  /// A value for the [white-space](https://www.w3.org/TR/css-text-4/#white-space-property) shorthand property.
+  pub struct WhiteSpace {
+    /// How white space is collapsed.
+    collapse: WhiteSpaceCollapse(WhiteSpaceCollapse),
+    /// Whether lines may wrap.
+    wrap_mode: TextWrapMode(TextWrapMode),
+  }
+}
+
+impl WhiteSpace {
+  /// Returns the legacy `white-space` keyword equivalent to this value, if any.
+  fn keyword(&self) -> Option<&'static str> {
+    use TextWrapMode::*;
+    use WhiteSpaceCollapse::*;
+    Some(match (self.collapse, self.wrap_mode) {
+      (Collapse, Wrap) => "normal",
+      (Collapse, NoWrap) => "nowrap",
+      (Preserve, Wrap) => "pre-wrap",
+      (Preserve, NoWrap) => "pre",
+      (PreserveBreaks, Wrap) => "pre-line",
+      (BreakSpaces, Wrap) => "break-spaces",
+      _ => return None,
+    })
+  }
+
+  /// Returns whether this value can be serialized as a legacy `white-space` keyword.
+  pub fn is_legacy_keyword(&self) -> bool {
+    self.keyword().is_some()
+  }
+}
+
+impl<'i> Parse<'i> for WhiteSpace {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(value) = input.try_parse(|input| {
+      let location = input.current_source_location();
+      let ident = input.expect_ident_cloned()?;
+      let (collapse, wrap_mode) = match_ignore_ascii_case! { &*ident,
+        "normal" => (WhiteSpaceCollapse::Collapse, TextWrapMode::Wrap),
+        "pre" => (WhiteSpaceCollapse::Preserve, TextWrapMode::NoWrap),
+        "pre-wrap" => (WhiteSpaceCollapse::Preserve, TextWrapMode::Wrap),
+        "pre-line" => (WhiteSpaceCollapse::PreserveBreaks, TextWrapMode::Wrap),
+        _ => return Err(location.new_unexpected_token_error(cssparser::Token::Ident(ident.clone())))
+      };
+      Ok::<_, ParseError<'i, ParserError<'i>>>(WhiteSpace { collapse, wrap_mode })
+    }) {
+      return Ok(value);
+    }
+
+    let mut collapse = None;
+    let mut wrap_mode = None;
+    loop {
+      if collapse.is_none() {
+        if let Ok(value) = input.try_parse(WhiteSpaceCollapse::parse) {
+          collapse = Some(value);
+          continue;
+        }
+      }
+
+      if wrap_mode.is_none() {
+        if let Ok(value) = input.try_parse(TextWrapMode::parse) {
+          wrap_mode = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    if collapse.is_none() && wrap_mode.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidDeclaration));
+    }
+
+    Ok(WhiteSpace {
+      collapse: collapse.unwrap_or_default(),
+      wrap_mode: wrap_mode.unwrap_or_default(),
+    })
+  }
+}
+
+impl ToCss for WhiteSpace {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if let Some(keyword) = self.keyword() {
+      return dest.write_str(keyword);
+    }
+
+    self.collapse.to_css(dest)?;
+    if self.wrap_mode != TextWrapMode::default() {
+      dest.write_char(' ')?;
+      self.wrap_mode.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+define_shorthand! {
+  /// A value for the [text-wrap](https://www.w3.org/TR/css-text-4/#text-wrap) shorthand property.
+  pub struct TextWrap {
+    /// Whether lines may wrap.
+    mode: TextWrapMode(TextWrapMode),
+    /// How lines are wrapped.
+    style: TextWrapStyle(TextWrapStyle),
+  }
+}
+
+impl<'i> Parse<'i> for TextWrap {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut mode = None;
+    let mut style = None;
+    loop {
+      if mode.is_none() {
+        if let Ok(value) = input.try_parse(TextWrapMode::parse) {
+          mode = Some(value);
+          continue;
+        }
+      }
+
+      if style.is_none() {
+        if let Ok(value) = input.try_parse(TextWrapStyle::parse) {
+          style = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    if mode.is_none() && style.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidDeclaration));
+    }
+
+    Ok(TextWrap {
+      mode: mode.unwrap_or_default(),
+      style: style.unwrap_or_default(),
+    })
+  }
+}
+
+impl ToCss for TextWrap {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.style == TextWrapStyle::Auto {
+      return self.mode.to_css(dest);
+    }
+
+    if self.mode != TextWrapMode::Wrap {
+      self.mode.to_css(dest)?;
+      dest.write_char(' ')?;
+    }
+    self.style.to_css(dest)
   }
 }
 
@@ -1243,7 +1443,7 @@ impl<'i> PropertyHandler<'i> for TextDecorationHandler<'i> {
     true
   }
 
-  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, context: &mut PropertyHandlerContext<'i, '_>) {
     if !self.has_any {
       return;
     }
@@ -1290,7 +1490,7 @@ impl<'i> PropertyHandler<'i> for TextDecorationHandler<'i> {
           if let Some(targets) = self.targets {
             prefix = Feature::TextDecoration.prefixes_for(targets);
 
-            let fallbacks = decoration.get_fallbacks(targets);
+            let fallbacks = context.get_color_fallbacks(&mut decoration, targets);
             for fallback in fallbacks {
               dest.push(Property::TextDecoration(fallback, prefix))
             }
@@ -1316,7 +1516,7 @@ impl<'i> PropertyHandler<'i> for TextDecorationHandler<'i> {
               if let Some(targets) = self.targets {
                 prefix = Feature::$prop.prefixes_for(targets);
 
-                let fallbacks = val.get_fallbacks(targets);
+                let fallbacks = context.get_color_fallbacks(&mut val, targets);
                 for fallback in fallbacks {
                   dest.push(Property::$prop(fallback, prefix))
                 }
@@ -1379,7 +1579,7 @@ impl<'i> PropertyHandler<'i> for TextDecorationHandler<'i> {
           if let Some(targets) = self.targets {
             prefix = Feature::TextEmphasis.prefixes_for(targets);
 
-            let fallbacks = emphasis.get_fallbacks(targets);
+            let fallbacks = context.get_color_fallbacks(&mut emphasis, targets);
             for fallback in fallbacks {
               dest.push(Property::TextEmphasis(fallback, prefix))
             }
@@ -1413,6 +1613,86 @@ impl<'i> PropertyHandler<'i> for TextDecorationHandler<'i> {
   }
 }
 
+#[derive(Default)]
+pub(crate) struct WhiteSpaceHandler {
+  collapse: Option<WhiteSpaceCollapse>,
+  wrap_mode: Option<TextWrapMode>,
+  wrap_style: Option<TextWrapStyle>,
+}
+
+impl<'i> PropertyHandler<'i> for WhiteSpaceHandler {
+  fn handle_property(
+    &mut self,
+    property: &Property<'i>,
+    dest: &mut DeclarationList<'i>,
+    context: &mut PropertyHandlerContext<'i, '_>,
+  ) -> bool {
+    match property {
+      Property::WhiteSpace(val) => {
+        self.collapse = Some(val.collapse);
+        self.wrap_mode = Some(val.wrap_mode);
+      }
+      Property::WhiteSpaceCollapse(val) => self.collapse = Some(*val),
+      Property::TextWrap(val) => {
+        self.wrap_mode = Some(val.mode);
+        self.wrap_style = Some(val.style);
+      }
+      Property::TextWrapMode(val) => self.wrap_mode = Some(*val),
+      Property::TextWrapStyle(val) => self.wrap_style = Some(*val),
+      Property::Unparsed(val)
+        if matches!(
+          val.property_id,
+          PropertyId::WhiteSpace
+            | PropertyId::WhiteSpaceCollapse
+            | PropertyId::TextWrap
+            | PropertyId::TextWrapMode
+            | PropertyId::TextWrapStyle
+        ) =>
+      {
+        self.finalize(dest, context);
+        dest.push(property.clone());
+      }
+      _ => return false,
+    }
+
+    true
+  }
+
+  fn finalize(&mut self, dest: &mut DeclarationList, _: &mut PropertyHandlerContext<'i, '_>) {
+    let collapse = std::mem::take(&mut self.collapse);
+    let wrap_mode = std::mem::take(&mut self.wrap_mode);
+    let wrap_style = std::mem::take(&mut self.wrap_style);
+
+    // Combine the longhands into the shorthands when possible. White-space values that
+    // match a legacy keyword (e.g. `pre-wrap`) are serialized as that keyword, which
+    // is also supported by browsers that don't support the longhands.
+    if let (Some(collapse), Some(wrap_mode)) = (collapse, wrap_mode) {
+      dest.push(Property::WhiteSpace(WhiteSpace { collapse, wrap_mode }));
+      if let Some(style) = wrap_style {
+        dest.push(Property::TextWrapStyle(style));
+      }
+      return;
+    }
+
+    if let Some(collapse) = collapse {
+      dest.push(Property::WhiteSpaceCollapse(collapse));
+    }
+
+    match (wrap_mode, wrap_style) {
+      (Some(mode), Some(style)) => dest.push(Property::TextWrap(TextWrap { mode, style })),
+      _ => {
+        if let Some(mode) = wrap_mode {
+          dest.push(Property::TextWrapMode(mode));
+        }
+
+        if let Some(style) = wrap_style {
+          dest.push(Property::TextWrapStyle(style));
+        }
+      }
+    }
+  }
+}
+
 /// A value for the [text-shadow](https://www.w3.org/TR/2020/WD-css-text-decor-4-20200506/#text-shadow-property) property.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -1,19 +1,23 @@
 //! CSS properties related to 2D and 3D transforms.
 
 use super::{Property, PropertyId};
+use crate::compat;
 use crate::context::PropertyHandlerContext;
-use crate::declaration::DeclarationList;
+use crate::declaration::{DeclarationBlock, DeclarationList};
 use crate::error::{ParserError, PrinterError};
-use crate::macros::enum_property;
+use crate::macros::{define_shorthand, enum_property};
 use crate::prefixes::Feature;
 use crate::printer::Printer;
 use crate::stylesheet::PrinterOptions;
 use crate::targets::Browsers;
-use crate::traits::{Parse, PropertyHandler, ToCss, Zero};
+use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss, Zero};
 use crate::values::{
   angle::Angle,
   length::{Length, LengthPercentage},
   percentage::NumberOrPercentage,
+  position::Position,
+  shape::BasicShape,
+  url::Url,
 };
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
@@ -1650,6 +1654,597 @@ impl Scale {
   }
 }
 
+/// A value for the [offset-path](https://drafts.fxtf.org/motion-1/#offset-path-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum OffsetPath<'i> {
+  /// No offset path.
+  None,
+  /// A url reference to an SVG shape element.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Url(Url<'i>),
+  /// A `ray()` function.
+  Ray(Ray),
+  /// A basic shape.
+  Shape(Box<BasicShape>),
+}
+
+impl<'i> Parse<'i> for OffsetPath<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(OffsetPath::None);
+    }
+
+    if let Ok(url) = input.try_parse(Url::parse) {
+      return Ok(OffsetPath::Url(url));
+    }
+
+    if let Ok(ray) = input.try_parse(Ray::parse) {
+      return Ok(OffsetPath::Ray(ray));
+    }
+
+    Ok(OffsetPath::Shape(Box::new(BasicShape::parse(input)?)))
+  }
+}
+
+impl<'i> ToCss for OffsetPath<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      OffsetPath::None => dest.write_str("none"),
+      OffsetPath::Url(url) => url.to_css(dest),
+      OffsetPath::Ray(ray) => ray.to_css(dest),
+      OffsetPath::Shape(shape) => shape.to_css(dest),
+    }
+  }
+}
+
+enum_property! {
+  /// A [`<ray-size>`](https://drafts.fxtf.org/motion-1/#typedef-ray-size) value,
+  /// as used in the `ray()` function.
+  pub enum RaySize {
+    /// The distance to the closest side of the containing box.
+    "closest-side": ClosestSide,
+    /// The distance to the closest corner of the containing box.
+    "closest-corner": ClosestCorner,
+    /// The distance to the farthest side of the containing box.
+    "farthest-side": FarthestSide,
+    /// The distance to the farthest corner of the containing box.
+    "farthest-corner": FarthestCorner,
+    /// The distance to the side of the containing box that the ray intersects.
+    "sides": Sides,
+  }
+}
+
+impl Default for RaySize {
+  fn default() -> RaySize {
+    RaySize::ClosestSide
+  }
+}
+
+/// A [`ray()`](https://drafts.fxtf.org/motion-1/#ray-function) function, as used in the `offset-path` property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Ray {
+  /// The angle of the ray, relative to the vertical axis.
+  pub angle: Angle,
+  /// The length of the path.
+  pub size: RaySize,
+  /// Whether the element should be kept entirely within the containing box.
+  pub contain: bool,
+  /// The starting point of the ray. Defaults to the `offset-position` when omitted.
+  pub position: Option<Position>,
+}
+
+impl<'i> Parse<'i> for Ray {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.expect_function_matching("ray")?;
+    input.parse_nested_block(|input| {
+      let mut angle = None;
+      let mut size = None;
+      let mut contain = false;
+      let mut position = None;
+
+      loop {
+        if angle.is_none() {
+          if let Ok(value) = input.try_parse(Angle::parse) {
+            angle = Some(value);
+            continue;
+          }
+        }
+
+        if size.is_none() {
+          if let Ok(value) = input.try_parse(RaySize::parse) {
+            size = Some(value);
+            continue;
+          }
+        }
+
+        if !contain && input.try_parse(|input| input.expect_ident_matching("contain")).is_ok() {
+          contain = true;
+          continue;
+        }
+
+        if position.is_none() && input.try_parse(|input| input.expect_ident_matching("at")).is_ok() {
+          position = Some(Position::parse(input)?);
+          continue;
+        }
+
+        break;
+      }
+
+      match angle {
+        Some(angle) => Ok(Ray {
+          angle,
+          size: size.unwrap_or_default(),
+          contain,
+          position,
+        }),
+        None => Err(input.new_custom_error(ParserError::InvalidValue)),
+      }
+    })
+  }
+}
+
+impl ToCss for Ray {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("ray(")?;
+    self.angle.to_css(dest)?;
+    if self.size != RaySize::default() {
+      dest.write_char(' ')?;
+      self.size.to_css(dest)?;
+    }
+    if self.contain {
+      dest.write_str(" contain")?;
+    }
+    if let Some(position) = &self.position {
+      dest.write_str(" at ")?;
+      position.to_css(dest)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+/// A value for the [offset-rotate](https://drafts.fxtf.org/motion-1/#offset-rotate-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct OffsetRotate {
+  /// Whether the element is rotated according to the direction of the path.
+  /// The `reverse` keyword is equivalent to `auto` with an additional angle of 180 degrees.
+  pub auto: bool,
+  /// A fixed angle to rotate the element by, added to the path direction if `auto` is set.
+  pub angle: Angle,
+}
+
+impl Default for OffsetRotate {
+  fn default() -> OffsetRotate {
+    OffsetRotate {
+      auto: true,
+      angle: Angle::Deg(0.0),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for OffsetRotate {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut auto = None;
+    let mut angle = None;
+
+    loop {
+      if auto.is_none() {
+        let location = input.current_source_location();
+        if let Ok(ident) = input.try_parse(|input| input.expect_ident_cloned()) {
+          match_ignore_ascii_case! { &*ident,
+            "auto" => auto = Some(Angle::Deg(0.0)),
+            "reverse" => auto = Some(Angle::Deg(180.0)),
+            _ => return Err(location.new_unexpected_token_error(Token::Ident(ident)))
+          }
+          continue;
+        }
+      }
+
+      if angle.is_none() {
+        if let Ok(value) = input.try_parse(Angle::parse_with_unitless_zero) {
+          angle = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    match (auto, angle) {
+      (Some(base), Some(angle)) if base.is_zero() => Ok(OffsetRotate { auto: true, angle }),
+      (Some(base), Some(angle)) => Ok(OffsetRotate {
+        auto: true,
+        angle: Angle::Deg(base.to_degrees() + angle.to_degrees()),
+      }),
+      (Some(base), None) => Ok(OffsetRotate {
+        auto: true,
+        angle: base,
+      }),
+      (None, Some(angle)) => Ok(OffsetRotate { auto: false, angle }),
+      (None, None) => Err(input.new_custom_error(ParserError::InvalidValue)),
+    }
+  }
+}
+
+impl ToCss for OffsetRotate {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if !self.auto {
+      return self.angle.to_css_with_unitless_zero(dest);
+    }
+
+    if self.angle.is_zero() {
+      dest.write_str("auto")
+    } else if self.angle.to_degrees() == 180.0 {
+      dest.write_str("reverse")
+    } else {
+      dest.write_str("auto ")?;
+      self.angle.to_css(dest)
+    }
+  }
+}
+
+/// A value for the [offset-anchor](https://drafts.fxtf.org/motion-1/#offset-anchor-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum OffsetAnchor {
+  /// The anchor is the same as the `transform-origin`, or the `offset-position` for `ray()` paths.
+  Auto,
+  /// An explicit anchor point.
+  Position(Position),
+}
+
+impl Default for OffsetAnchor {
+  fn default() -> OffsetAnchor {
+    OffsetAnchor::Auto
+  }
+}
+
+impl<'i> Parse<'i> for OffsetAnchor {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(OffsetAnchor::Auto);
+    }
+
+    Ok(OffsetAnchor::Position(Position::parse(input)?))
+  }
+}
+
+impl ToCss for OffsetAnchor {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      OffsetAnchor::Auto => dest.write_str("auto"),
+      OffsetAnchor::Position(position) => position.to_css(dest),
+    }
+  }
+}
+
+/// A value for the [offset-position](https://drafts.fxtf.org/motion-1/#offset-position-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum OffsetPosition {
+  /// The element has no offset starting position.
+  Normal,
+  /// The offset starting position is the element's own position.
+  Auto,
+  /// An explicit offset starting position.
+  Position(Position),
+}
+
+impl Default for OffsetPosition {
+  fn default() -> OffsetPosition {
+    OffsetPosition::Normal
+  }
+}
+
+impl<'i> Parse<'i> for OffsetPosition {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(OffsetPosition::Normal);
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(OffsetPosition::Auto);
+    }
+
+    Ok(OffsetPosition::Position(Position::parse(input)?))
+  }
+}
+
+impl ToCss for OffsetPosition {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      OffsetPosition::Normal => dest.write_str("normal"),
+      OffsetPosition::Auto => dest.write_str("auto"),
+      OffsetPosition::Position(position) => position.to_css(dest),
+    }
+  }
+}
+
+define_shorthand! {
+  /// A value for the [offset](https://drafts.fxtf.org/motion-1/#offset-shorthand) shorthand property.
+  #[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+  pub struct Offset<'i> {
+    /// The offset starting position.
+    position: OffsetPosition(OffsetPosition),
+    /// The offset path.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    path: OffsetPath(OffsetPath<'i>),
+    /// The distance along the offset path.
+    distance: OffsetDistance(LengthPercentage),
+    /// The rotation of the element along the offset path.
+    rotate: OffsetRotate(OffsetRotate),
+    /// The anchor point of the element.
+    anchor: OffsetAnchor(OffsetAnchor),
+  }
+}
+
+impl<'i> Parse<'i> for Offset<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let position = input.try_parse(OffsetPosition::parse).ok();
+    let path = input.try_parse(OffsetPath::parse).ok();
+    let mut distance = None;
+    let mut rotate = None;
+    if path.is_some() {
+      loop {
+        if distance.is_none() {
+          if let Ok(value) = input.try_parse(LengthPercentage::parse) {
+            distance = Some(value);
+            continue;
+          }
+        }
+
+        if rotate.is_none() {
+          if let Ok(value) = input.try_parse(OffsetRotate::parse) {
+            rotate = Some(value);
+            continue;
+          }
+        }
+
+        break;
+      }
+    }
+
+    let anchor = if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      Some(OffsetAnchor::parse(input)?)
+    } else {
+      None
+    };
+
+    if position.is_none() && path.is_none() && anchor.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(Offset {
+      position: position.unwrap_or_default(),
+      path: path.unwrap_or(OffsetPath::None),
+      distance: distance.unwrap_or_else(LengthPercentage::zero),
+      rotate: rotate.unwrap_or_default(),
+      anchor: anchor.unwrap_or_default(),
+    })
+  }
+}
+
+impl<'i> ToCss for Offset<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let has_position = self.position != OffsetPosition::Normal;
+    if has_position {
+      self.position.to_css(dest)?;
+    }
+
+    if self.path != OffsetPath::None || !has_position {
+      if has_position {
+        dest.write_char(' ')?;
+      }
+      self.path.to_css(dest)?;
+
+      if !self.distance.is_zero() {
+        dest.write_char(' ')?;
+        self.distance.to_css(dest)?;
+      }
+
+      if self.rotate != OffsetRotate::default() {
+        dest.write_char(' ')?;
+        self.rotate.to_css(dest)?;
+      }
+    }
+
+    if self.anchor != OffsetAnchor::Auto {
+      dest.delim('/', true)?;
+      self.anchor.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl<'i> Offset<'i> {
+  /// Returns whether the offset can be serialized as a shorthand. The distance and
+  /// rotation can only be expressed when there is an offset path.
+  fn is_representable(&self) -> bool {
+    self.path != OffsetPath::None || (self.distance.is_zero() && self.rotate == OffsetRotate::default())
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct OffsetHandler<'i> {
+  targets: Option<Browsers>,
+  position: Option<OffsetPosition>,
+  path: Option<OffsetPath<'i>>,
+  distance: Option<LengthPercentage>,
+  rotate: Option<OffsetRotate>,
+  anchor: Option<OffsetAnchor>,
+  has_any: bool,
+}
+
+impl<'i> OffsetHandler<'i> {
+  pub fn new(targets: Option<Browsers>) -> Self {
+    OffsetHandler {
+      targets,
+      ..OffsetHandler::default()
+    }
+  }
+}
+
+impl<'i> PropertyHandler<'i> for OffsetHandler<'i> {
+  fn handle_property(
+    &mut self,
+    property: &Property<'i>,
+    dest: &mut DeclarationList<'i>,
+    context: &mut PropertyHandlerContext<'i, '_>,
+  ) -> bool {
+    match property {
+      Property::OffsetPosition(val) => self.position = Some(val.clone()),
+      Property::OffsetPath(val) => self.path = Some(val.clone()),
+      Property::OffsetDistance(val) => self.distance = Some(val.clone()),
+      Property::OffsetRotate(val) => self.rotate = Some(val.clone()),
+      Property::OffsetAnchor(val) => self.anchor = Some(val.clone()),
+      Property::Offset(val) => {
+        self.position = Some(val.position.clone());
+        self.path = Some(val.path.clone());
+        self.distance = Some(val.distance.clone());
+        self.rotate = Some(val.rotate.clone());
+        self.anchor = Some(val.anchor.clone());
+      }
+      Property::Unparsed(val)
+        if matches!(
+          val.property_id,
+          PropertyId::OffsetPosition
+            | PropertyId::OffsetPath
+            | PropertyId::OffsetDistance
+            | PropertyId::OffsetRotate
+            | PropertyId::OffsetAnchor
+            | PropertyId::Offset
+        ) =>
+      {
+        self.finalize(dest, context);
+
+        let mut unparsed = val.clone();
+        context.add_unparsed_fallbacks(&mut unparsed);
+        dest.push(Property::Unparsed(unparsed));
+        return true;
+      }
+      _ => return false,
+    }
+
+    self.has_any = true;
+    true
+  }
+
+  fn finalize(&mut self, dest: &mut DeclarationList<'i>, _: &mut PropertyHandlerContext<'i, '_>) {
+    if !self.has_any {
+      return;
+    }
+
+    self.has_any = false;
+    let position = std::mem::take(&mut self.position);
+    let path = std::mem::take(&mut self.path);
+    let distance = std::mem::take(&mut self.distance);
+    let rotate = std::mem::take(&mut self.rotate);
+    let anchor = std::mem::take(&mut self.anchor);
+
+    // Chrome 46-54 only supports the motion-path, motion-offset, and motion-rotation properties.
+    if let Some(targets) = self.targets {
+      if !compat::Feature::OffsetPath.is_compatible(targets)
+        && compat::Feature::MotionPath.is_partially_compatible(targets)
+      {
+        if let Some(path) = &path {
+          let is_legacy_path = match path {
+            OffsetPath::None => true,
+            OffsetPath::Shape(shape) => matches!(**shape, BasicShape::Path(..)),
+            _ => false,
+          };
+          if is_legacy_path {
+            dest.push(Property::MotionPath(path.clone()));
+          }
+        }
+        if let Some(distance) = &distance {
+          dest.push(Property::MotionOffset(distance.clone()));
+        }
+        if let Some(rotate) = &rotate {
+          dest.push(Property::MotionRotation(rotate.clone()));
+        }
+      }
+    }
+
+    if let (Some(position), Some(path), Some(distance), Some(rotate), Some(anchor)) =
+      (&position, &path, &distance, &rotate, &anchor)
+    {
+      let offset = Offset {
+        position: position.clone(),
+        path: path.clone(),
+        distance: distance.clone(),
+        rotate: rotate.clone(),
+        anchor: anchor.clone(),
+      };
+      if offset.is_representable() {
+        dest.push(Property::Offset(offset));
+        return;
+      }
+    }
+
+    macro_rules! single_property {
+      ($prop: ident, $key: ident) => {
+        if let Some(val) = $key {
+          dest.push(Property::$prop(val));
+        }
+      };
+    }
+
+    single_property!(OffsetPosition, position);
+    single_property!(OffsetPath, path);
+    single_property!(OffsetDistance, distance);
+    single_property!(OffsetRotate, rotate);
+    single_property!(OffsetAnchor, anchor);
+  }
+}
+
 #[derive(Default)]
 pub(crate) struct TransformHandler {
   targets: Option<Browsers>,
@@ -5,7 +5,7 @@ use crate::compat;
 use crate::context::PropertyHandlerContext;
 use crate::declaration::{DeclarationBlock, DeclarationList};
 use crate::error::{ParserError, PrinterError};
-use crate::macros::define_list_shorthand;
+use crate::macros::{define_list_shorthand, enum_property};
 use crate::prefixes::Feature;
 use crate::printer::Printer;
 use crate::properties::masking::get_webkit_mask_property;
@@ -32,6 +32,24 @@ define_list_shorthand! {
     delay: TransitionDelay(Time, VendorPrefix),
     /// The easing function for the transition.
     timing_function: TransitionTimingFunction(EasingFunction, VendorPrefix),
+    /// Whether discrete properties are transitioned.
+    behavior: TransitionBehavior(TransitionBehavior),
+  }
+}
+
+enum_property! {
+  /// A value for the [transition-behavior](https://drafts.csswg.org/css-transitions-2/#transition-behavior-property) property.
+  pub enum TransitionBehavior {
+    /// Transitions are not started for discretely animated properties.
+    "normal": Normal,
+    /// Transitions are started for discretely animated properties, e.g. `display`.
+    "allow-discrete": AllowDiscrete,
+  }
+}
+
+impl Default for TransitionBehavior {
+  fn default() -> TransitionBehavior {
+    TransitionBehavior::Normal
   }
 }
 
@@ -41,6 +59,7 @@ impl<'i> Parse<'i> for Transition<'i> {
     let mut duration = None;
     let mut delay = None;
     let mut timing_function = None;
+    let mut behavior = None;
 
     loop {
       if duration.is_none() {
@@ -64,6 +83,13 @@ impl<'i> Parse<'i> for Transition<'i> {
         }
       }
 
+      if behavior.is_none() {
+        if let Ok(value) = input.try_parse(TransitionBehavior::parse) {
+          behavior = Some(value);
+          continue;
+        }
+      }
+
       if property.is_none() {
         if let Ok(value) = input.try_parse(PropertyId::parse) {
           property = Some(value);
@@ -79,6 +105,7 @@ impl<'i> Parse<'i> for Transition<'i> {
       duration: duration.unwrap_or(Time::Seconds(0.0)),
       delay: delay.unwrap_or(Time::Seconds(0.0)),
       timing_function: timing_function.unwrap_or(EasingFunction::Ease),
+      behavior: behavior.unwrap_or_default(),
     })
   }
 }
@@ -88,14 +115,28 @@ impl<'i> ToCss for Transition<'i> {
   where
     W: std::fmt::Write,
   {
-    self.property.to_css(dest)?;
-    if !self.duration.is_zero() || !self.delay.is_zero() {
-      dest.write_char(' ')?;
+    let has_duration = !self.duration.is_zero() || !self.delay.is_zero();
+    let has_timing_function = !self.timing_function.is_ease();
+
+    // `all` is the default, so it can be omitted unless it is the only value.
+    let mut needs_space = false;
+    if self.property != PropertyId::All || !(has_duration || has_timing_function) {
+      self.property.to_css(dest)?;
+      needs_space = true;
+    }
+
+    if has_duration {
+      if needs_space {
+        dest.write_char(' ')?;
+      }
       self.duration.to_css(dest)?;
+      needs_space = true;
     }
 
-    if !self.timing_function.is_ease() {
-      dest.write_char(' ')?;
+    if has_timing_function {
+      if needs_space {
+        dest.write_char(' ')?;
+      }
       self.timing_function.to_css(dest)?;
     }
 
@@ -104,6 +145,11 @@ impl<'i> ToCss for Transition<'i> {
       self.delay.to_css(dest)?;
     }
 
+    if self.behavior != TransitionBehavior::Normal {
+      dest.write_char(' ')?;
+      self.behavior.to_css(dest)?;
+    }
+
     Ok(())
   }
 }
@@ -115,6 +161,7 @@ pub(crate) struct TransitionHandler<'i> {
   durations: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
   delays: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
   timing_functions: Option<(SmallVec<[EasingFunction; 1]>, VendorPrefix)>,
+  behaviors: Option<SmallVec<[TransitionBehavior; 1]>>,
   has_any: bool,
 }
 
@@ -182,6 +229,10 @@ impl<'i> PropertyHandler<'i> for TransitionHandler<'i> {
       TransitionDuration(val, vp) => property!(TransitionDuration, durations, val, vp),
       TransitionDelay(val, vp) => property!(TransitionDelay, delays, val, vp),
       TransitionTimingFunction(val, vp) => property!(TransitionTimingFunction, timing_functions, val, vp),
+      TransitionBehavior(val) => {
+        self.behaviors = Some(val.clone());
+        self.has_any = true;
+      }
       Transition(val, vp) => {
         let properties: SmallVec<[PropertyId; 1]> = val.iter().map(|b| b.property.clone()).collect();
         maybe_flush!(properties, &properties, vp);
@@ -200,6 +251,12 @@ impl<'i> PropertyHandler<'i> for TransitionHandler<'i> {
         property!(TransitionDuration, durations, &durations, vp);
         property!(TransitionDelay, delays, &delays, vp);
         property!(TransitionTimingFunction, timing_functions, &timing_functions, vp);
+
+        // Prefixed shorthands are only output for older browsers, so only
+        // the behavior of the unprefixed shorthand is used.
+        if vp.contains(VendorPrefix::None) {
+          self.behaviors = Some(val.iter().map(|b| b.behavior).collect());
+        }
       }
       Unparsed(val) if is_transition_property(&val.property_id) => {
         self.flush(dest, context);
@@ -228,6 +285,7 @@ impl<'i> TransitionHandler<'i> {
     let mut durations = std::mem::take(&mut self.durations);
     let mut delays = std::mem::take(&mut self.delays);
     let mut timing_functions = std::mem::take(&mut self.timing_functions);
+    let mut behaviors = std::mem::take(&mut self.behaviors);
 
     let rtl_properties = if let Some((properties, _)) = &mut properties {
       expand_properties(properties, self.targets, context)
@@ -247,6 +305,20 @@ impl<'i> TransitionHandler<'i> {
       // prefixes will be handled by outputing individual properties below.
       let intersection = *property_prefixes & *duration_prefixes & *delay_prefixes & *timing_prefixes;
       if !intersection.is_empty() {
+        // Browsers that don't support transition-behavior ignore the whole shorthand if it
+        // includes allow-discrete, so it is only included when supported by all targets.
+        let include_behaviors = match &behaviors {
+          Some(behaviors) => {
+            behaviors.iter().all(|b| *b == TransitionBehavior::Normal)
+              || (intersection == VendorPrefix::None
+                && self.targets.map_or(true, |targets| {
+                  compat::Feature::TransitionBehavior.is_compatible(targets)
+                }))
+          }
+          None => true,
+        };
+        let shorthand_behaviors = if include_behaviors { behaviors.take() } else { None };
+
         macro_rules! get_transitions {
           ($properties: ident) => {{
             // transition-property determines the number of transitions. The values of other
@@ -255,15 +327,18 @@ impl<'i> TransitionHandler<'i> {
             let mut durations_iter = durations.iter().cycle().cloned();
             let mut delays_iter = delays.iter().cycle().cloned();
             let mut timing_iter = timing_functions.iter().cycle().cloned();
+            let mut behavior_iter = shorthand_behaviors.iter().flatten().cycle().cloned();
             for property_id in $properties {
               let duration = durations_iter.next().unwrap_or(Time::Seconds(0.0));
               let delay = delays_iter.next().unwrap_or(Time::Seconds(0.0));
               let timing_function = timing_iter.next().unwrap_or(EasingFunction::Ease);
+              let behavior = behavior_iter.next().unwrap_or_default();
               let transition = Transition {
                 property: property_id.clone(),
                 duration,
                 delay,
                 timing_function,
+                behavior,
               };
 
               // Expand vendor prefixes into multiple transitions.
@@ -277,10 +352,12 @@ impl<'i> TransitionHandler<'i> {
           }};
         }
 
-        let transitions: SmallVec<[Transition; 1]> = get_transitions!(properties);
+        let mut transitions: SmallVec<[Transition; 1]> = get_transitions!(properties);
+        remove_overridden_transitions(&mut transitions);
 
         if let Some(rtl_properties) = &rtl_properties {
-          let rtl_transitions = get_transitions!(rtl_properties);
+          let mut rtl_transitions = get_transitions!(rtl_properties);
+          remove_overridden_transitions(&mut rtl_transitions);
           context.add_logical_rule(
             Property::Transition(transitions, intersection),
             Property::Transition(rtl_transitions, intersection),
@@ -327,6 +404,10 @@ impl<'i> TransitionHandler<'i> {
       }
     }
 
+    if let Some(behaviors) = behaviors {
+      dest.push(Property::TransitionBehavior(behaviors));
+    }
+
     self.reset();
   }
 
@@ -335,6 +416,53 @@ impl<'i> TransitionHandler<'i> {
     self.durations = None;
     self.delays = None;
     self.timing_functions = None;
+    self.behaviors = None;
+  }
+}
+
+/// Removes transitions that are overridden by a later transition in the same list. When a property is
+/// listed more than once, either on its own, via a shorthand that contains it, or via `all`, only the
+/// last item that applies to it is used.
+fn remove_overridden_transitions(transitions: &mut SmallVec<[Transition; 1]>) {
+  fn covers(later: &PropertyId, earlier: &PropertyId) -> bool {
+    if *later == PropertyId::All || later == earlier {
+      return true;
+    }
+
+    match later.longhands() {
+      Some(longhands) => longhands.iter().any(|longhand| covers(longhand, earlier)),
+      None => false,
+    }
+  }
+
+  let mut i = 0;
+  while i < transitions.len() {
+    if transitions[i + 1..]
+      .iter()
+      .any(|later| covers(&later.property, &transitions[i].property))
+    {
+      transitions.remove(i);
+    } else {
+      i += 1;
+    }
+  }
+}
+
+/// Returns whether any of the declarations enable transitions of discretely animated properties.
+pub(crate) fn allows_discrete_transitions(declarations: &DeclarationBlock) -> bool {
+  declarations
+    .iter()
+    .any(|(property, _)| property_allows_discrete_transitions(property))
+}
+
+/// Returns whether the property enables transitions of discretely animated properties.
+pub(crate) fn property_allows_discrete_transitions(property: &Property) -> bool {
+  match property {
+    Property::TransitionBehavior(behaviors) => behaviors.contains(&TransitionBehavior::AllowDiscrete),
+    Property::Transition(transitions, _) => {
+      transitions.iter().any(|t| t.behavior == TransitionBehavior::AllowDiscrete)
+    }
+    _ => false,
   }
 }
 
@@ -345,6 +473,7 @@ fn is_transition_property(property_id: &PropertyId) -> bool {
     | PropertyId::TransitionDuration(_)
     | PropertyId::TransitionDelay(_)
     | PropertyId::TransitionTimingFunction(_)
+    | PropertyId::TransitionBehavior
     | PropertyId::Transition(_) => true,
     _ => false,
   }
@@ -290,6 +290,29 @@ enum_property! {
   }
 }
 
+enum_property! {
+  /// A value for the [field-sizing](https://drafts.csswg.org/css-ui-4/#field-sizing) property.
+  pub enum FieldSizing {
+    /// The form control has a fixed default size.
+    "fixed": Fixed,
+    /// The form control is sized to fit its contents.
+    "content": Content,
+  }
+}
+
+impl Default for FieldSizing {
+  fn default() -> Self {
+    FieldSizing::Fixed
+  }
+}
+
+/// Returns whether any of the declarations size form controls to fit their contents.
+pub(crate) fn sizes_fields_to_content(declarations: &DeclarationBlock) -> bool {
+  declarations
+    .iter()
+    .any(|(property, _)| matches!(property, Property::FieldSizing(FieldSizing::Content)))
+}
+
 /// A value for the [appearance](https://www.w3.org/TR/2021/WD-css-ui-4-20210316/#appearance-switching) property.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -415,3 +438,86 @@ impl<'a> schemars::JsonSchema for Appearance<'a> {
     "Appearance".into()
   }
 }
+
+/// A value for the [color-scheme](https://drafts.csswg.org/css-color-adjust/#color-scheme-prop) property.
+///
+/// Color scheme names other than `light` and `dark` are accepted but have no effect, and are omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct ColorScheme {
+  /// Whether the element supports a light color scheme.
+  pub light: bool,
+  /// Whether the element supports a dark color scheme.
+  pub dark: bool,
+  /// Whether the user agent is forbidden from overriding the color scheme.
+  pub only: bool,
+}
+
+impl<'i> Parse<'i> for ColorScheme {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut res = ColorScheme::default();
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(res);
+    }
+
+    let mut has_scheme = false;
+    while let Ok(ident) = input.try_parse(|input| input.expect_ident_cloned()) {
+      match_ignore_ascii_case! { &ident,
+        "only" => {
+          if res.only {
+            return Err(input.new_unexpected_token_error(Token::Ident(ident)));
+          }
+          res.only = true;
+        },
+        "light" => {
+          res.light = true;
+          has_scheme = true;
+        },
+        "dark" => {
+          res.dark = true;
+          has_scheme = true;
+        },
+        "normal" | "initial" | "inherit" | "unset" | "default" | "revert" | "revert-layer" => {
+          return Err(input.new_unexpected_token_error(Token::Ident(ident)))
+        },
+        _ => has_scheme = true
+      }
+    }
+
+    if !has_scheme {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(res)
+  }
+}
+
+impl ToCss for ColorScheme {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if !self.light && !self.dark {
+      return dest.write_str("normal");
+    }
+
+    if self.light {
+      dest.write_str("light")?;
+      if self.dark {
+        dest.write_char(' ')?;
+      }
+    }
+
+    if self.dark {
+      dest.write_str("dark")?;
+    }
+
+    if self.only {
+      dest.write_str(" only")?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,164 @@
+//! CSS properties related to view transitions.
+
+use super::Property;
+use crate::compat::Feature;
+use crate::declaration::DeclarationBlock;
+use crate::error::{ParserError, PrinterError};
+use crate::printer::Printer;
+use crate::traits::{Parse, ToCss};
+use crate::values::ident::CustomIdent;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+use smallvec::SmallVec;
+
+/// A value for the [view-transition-name](https://drafts.csswg.org/css-view-transitions-1/#view-transition-name-prop) property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ViewTransitionName<'i> {
+  /// The element will not participate independently in a view transition.
+  None,
+  /// The element is assigned a name automatically, based on its identity.
+  Auto,
+  /// The element participates in a view transition with the given name.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Custom(CustomIdent<'i>),
+}
+
+impl<'i> Default for ViewTransitionName<'i> {
+  fn default() -> Self {
+    ViewTransitionName::None
+  }
+}
+
+impl<'i> Parse<'i> for ViewTransitionName<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(ViewTransitionName::None);
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(ViewTransitionName::Auto);
+    }
+
+    Ok(ViewTransitionName::Custom(CustomIdent::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for ViewTransitionName<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      ViewTransitionName::None => dest.write_str("none"),
+      ViewTransitionName::Auto => dest.write_str("auto"),
+      ViewTransitionName::Custom(name) => name.to_css(dest),
+    }
+  }
+}
+
+/// A value for the [view-transition-class](https://drafts.csswg.org/css-view-transitions-2/#view-transition-class-prop) property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ViewTransitionClass<'i> {
+  /// No view transition classes.
+  None,
+  /// A list of view transition classes.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Classes(SmallVec<[CustomIdent<'i>; 1]>),
+}
+
+impl<'i> Default for ViewTransitionClass<'i> {
+  fn default() -> Self {
+    ViewTransitionClass::None
+  }
+}
+
+impl<'i> Parse<'i> for ViewTransitionClass<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(ViewTransitionClass::None);
+    }
+
+    let mut classes = SmallVec::new();
+    while let Ok(class) = input.try_parse(|input| {
+      let location = input.current_source_location();
+      let ident = CustomIdent::parse(input)?;
+      // `none` is only valid on its own.
+      if ident.0.eq_ignore_ascii_case("none") {
+        return Err(location.new_unexpected_token_error(Token::Ident(ident.0.as_ref().to_owned().into())));
+      }
+      Ok(ident)
+    }) {
+      classes.push(class);
+    }
+
+    if classes.is_empty() {
+      return Err(input.new_error_for_next_token());
+    }
+
+    Ok(ViewTransitionClass::Classes(classes))
+  }
+}
+
+impl<'i> ToCss for ViewTransitionClass<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      ViewTransitionClass::None => dest.write_str("none"),
+      ViewTransitionClass::Classes(classes) => {
+        let mut first = true;
+        for class in classes {
+          if first {
+            first = false;
+          } else {
+            dest.write_char(' ')?;
+          }
+          class.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Returns the compat feature required by the view transition properties in the declarations, if any.
+pub(crate) fn view_transition_feature(declarations: &DeclarationBlock) -> Option<Feature> {
+  let mut feature = None;
+  for (property, _) in declarations.iter() {
+    match property_view_transition_feature(property) {
+      Some(Feature::ViewTransitionClass) => return Some(Feature::ViewTransitionClass),
+      Some(f) => feature = Some(f),
+      None => {}
+    }
+  }
+  feature
+}
+
+/// Returns the compat feature required by a view transition property, if any.
+pub(crate) fn property_view_transition_feature(property: &Property) -> Option<Feature> {
+  match property {
+    Property::ViewTransitionClass(ViewTransitionClass::Classes(..)) => Some(Feature::ViewTransitionClass),
+    Property::ViewTransitionName(ViewTransitionName::Auto | ViewTransitionName::Custom(..)) => {
+      Some(Feature::ViewTransition)
+    }
+    _ => None,
+  }
+}
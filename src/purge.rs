@@ -0,0 +1,129 @@
+//! Removal of unused selectors based on the content of HTML, JavaScript, and other source files.
+//!
+//! A [ContentExtractor](ContentExtractor) produces the candidate class names, ids, and tag names that may be
+//! used in a source file. These are collected into a [Purge](Purge), which is passed to
+//! [MinifyOptions](crate::stylesheet::MinifyOptions) to remove the selectors that require a class name, id,
+//! or tag name that never appears in any of the content. Rules with no remaining selectors are removed entirely.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   purge::{DefaultExtractor, Purge},
+//!   stylesheet::{StyleSheet, ParserOptions, MinifyOptions, PrinterOptions},
+//! };
+//!
+//! let mut purge = Purge::new();
+//! purge.add_content(r#"<div class="foo">Hello</div>"#, &DefaultExtractor::new());
+//!
+//! let mut stylesheet = StyleSheet::parse(
+//!   ".foo, .bar { color: red } div > .baz { color: green } span { color: blue }",
+//!   ParserOptions::default()
+//! ).unwrap();
+//!
+//! stylesheet.minify(MinifyOptions {
+//!   purge: Some(purge),
+//!   ..MinifyOptions::default()
+//! }).unwrap();
+//!
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{color:red}");
+//! ```
+
+use crate::selector::{has_unused_component, Component, Selector};
+use std::collections::HashSet;
+
+/// A trait for extracting the candidate class names, ids, and tag names used by a source file.
+///
+/// Extractors may return more candidates than are actually used, e.g. every word in a file, since
+/// extra candidates only cause unused selectors to be kept.
+pub trait ContentExtractor {
+  /// Adds the candidates that appear in the given content to the set.
+  fn extract(&self, content: &str, candidates: &mut HashSet<String>);
+}
+
+impl<F: Fn(&str, &mut HashSet<String>)> ContentExtractor for F {
+  fn extract(&self, content: &str, candidates: &mut HashSet<String>) {
+    self(content, candidates)
+  }
+}
+
+/// The built-in content extractor, which produces every run of ASCII letters, digits, `_`, `-`, and
+/// any additional configured characters, along with all non-ASCII characters. This is equivalent to
+/// matching the content against the regular expression `[A-Za-z0-9_-]+`, and works for most HTML,
+/// JavaScript, and template languages.
+#[derive(Debug, Clone, Default)]
+pub struct DefaultExtractor {
+  extra_chars: Vec<char>,
+}
+
+impl DefaultExtractor {
+  /// Creates a new default extractor.
+  pub fn new() -> DefaultExtractor {
+    DefaultExtractor::default()
+  }
+
+  /// Creates an extractor that also includes the given characters in candidates, e.g. `":/"` for
+  /// utility class names such as `md:w-1/2`.
+  pub fn with_chars(chars: &str) -> DefaultExtractor {
+    DefaultExtractor {
+      extra_chars: chars.chars().collect(),
+    }
+  }
+
+  fn is_candidate_char(&self, c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || !c.is_ascii() || self.extra_chars.contains(&c)
+  }
+}
+
+impl ContentExtractor for DefaultExtractor {
+  fn extract(&self, content: &str, candidates: &mut HashSet<String>) {
+    for candidate in content.split(|c| !self.is_candidate_char(c)) {
+      if !candidate.is_empty() && !candidates.contains(candidate) {
+        candidates.insert(candidate.to_owned());
+      }
+    }
+  }
+}
+
+/// The class names, ids, and tag names used by the content of a site, for removing unused selectors
+/// with [MinifyOptions](crate::stylesheet::MinifyOptions).
+#[derive(Debug, Clone, Default)]
+pub struct Purge {
+  /// The candidates extracted from the content.
+  pub candidates: HashSet<String>,
+  /// Class names, ids, and tag names to keep even if they don't appear in the content,
+  /// e.g. those added dynamically.
+  pub safelist: HashSet<String>,
+}
+
+impl Purge {
+  /// Creates an empty purge, which removes all selectors that require a class name, id, or tag name.
+  pub fn new() -> Purge {
+    Purge::default()
+  }
+
+  /// Extracts the candidates from the given content using an extractor.
+  pub fn add_content<E: ContentExtractor + ?Sized>(&mut self, content: &str, extractor: &E) {
+    extractor.extract(content, &mut self.candidates);
+  }
+
+  fn is_used(&self, symbol: &str) -> bool {
+    self.candidates.contains(symbol) || self.safelist.contains(symbol)
+  }
+
+  /// Returns whether the selector requires a class name, id, or tag name that is not used.
+  pub(crate) fn is_unused(&self, selector: &Selector, parent_is_unused: bool) -> bool {
+    has_unused_component(
+      selector,
+      &|component| match component {
+        Component::Class(name) | Component::ID(name) => !self.is_used(name.0.as_ref()),
+        Component::LocalName(local_name) => {
+          !self.is_used(local_name.name.0.as_ref()) && !self.is_used(local_name.lower_name.0.as_ref())
+        }
+        _ => false,
+      },
+      parent_is_unused,
+    )
+  }
+}
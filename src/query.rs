@@ -0,0 +1,423 @@
+//! Queries for finding rules within a style sheet.
+//!
+//! [StyleSheet::query](crate::stylesheet::StyleSheet::query) and the related methods return mutable
+//! references to the rules matching a predicate, along with the `@media`, `@supports`, `@container`,
+//! and `@layer` rules and parent style rules that enclose them. This allows rules to be edited
+//! programmatically without implementing a [Visitor](crate::visitor::Visitor).
+//!
+//! For read-only analyses, [CssRuleList::iter_rules](crate::rules::CssRuleList::iter_rules),
+//! [iter_declarations](crate::rules::CssRuleList::iter_declarations), and
+//! [iter_selectors](crate::rules::CssRuleList::iter_selectors) walk all rules, declarations, and selectors
+//! in a rule list, including nested rules, along with the same context.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   properties::PropertyId,
+//!   stylesheet::{StyleSheet, ParserOptions, PrinterOptions},
+//!   rules::CssRule,
+//! };
+//!
+//! let mut stylesheet = StyleSheet::parse(
+//!   ".foo { color: red; top: 0 } @media print { .foo { color: black } }",
+//!   ParserOptions::default()
+//! ).unwrap();
+//!
+//! for m in stylesheet.query_selector(".foo") {
+//!   if m.context.media.is_empty() {
+//!     if let CssRule::Style(style) = m.rule {
+//!       style.declarations.remove(&PropertyId::Color);
+//!     }
+//!   }
+//! }
+//!
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{top:0}@media print{.foo{color:#000}}");
+//! ```
+
+use crate::media_query::{MediaCondition, MediaList};
+use crate::printer::PrinterOptions;
+use crate::properties::Property;
+use crate::rules::layer::LayerName;
+use crate::rules::supports::SupportsCondition;
+use crate::rules::{CssRule, CssRuleList};
+use crate::selector::{Selector, SelectorList};
+use crate::traits::ToCss;
+
+/// The rules enclosing a rule within a style sheet, each listed outermost first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleContext<'i> {
+  /// The queries of the enclosing `@media` rules.
+  pub media: Vec<MediaList<'i>>,
+  /// The conditions of the enclosing `@supports` rules.
+  pub supports: Vec<SupportsCondition<'i>>,
+  /// The conditions of the enclosing `@container` rules.
+  pub containers: Vec<MediaCondition<'i>>,
+  /// The names of the enclosing `@layer` blocks, or `None` for anonymous layers.
+  pub layers: Vec<Option<LayerName<'i>>>,
+  /// The selectors of the enclosing style rules, for nested rules.
+  pub parent_selectors: Vec<SelectorList<'i>>,
+}
+
+/// A rule matching a query.
+#[derive(Debug)]
+pub struct RuleMatch<'a, 'i, T> {
+  /// The matching rule.
+  pub rule: &'a mut CssRule<'i, T>,
+  /// The rules enclosing the matching rule.
+  pub context: RuleContext<'i>,
+}
+
+/// Adds the rules matching the predicate to the list of matches. Rules nested within a matching
+/// rule are not searched, since they are already reachable through the mutable reference to it.
+pub(crate) fn query_rules<'a, 'i, T, F>(
+  rules: &'a mut CssRuleList<'i, T>,
+  context: &mut RuleContext<'i>,
+  predicate: &mut F,
+  matches: &mut Vec<RuleMatch<'a, 'i, T>>,
+) where
+  F: FnMut(&CssRule<'i, T>, &RuleContext<'i>) -> bool,
+{
+  for rule in rules.0.iter_mut() {
+    if predicate(rule, context) {
+      matches.push(RuleMatch {
+        rule,
+        context: context.clone(),
+      });
+      continue;
+    }
+
+    match rule {
+      CssRule::Media(media) => {
+        context.media.push(media.query.clone());
+        query_rules(&mut media.rules, context, predicate, matches);
+        context.media.pop();
+      }
+      CssRule::Supports(supports) => {
+        context.supports.push(supports.condition.clone());
+        query_rules(&mut supports.rules, context, predicate, matches);
+        context.supports.pop();
+      }
+      CssRule::Container(container) => {
+        context.containers.push(container.condition.clone());
+        query_rules(&mut container.rules, context, predicate, matches);
+        context.containers.pop();
+      }
+      CssRule::LayerBlock(layer) => {
+        context.layers.push(layer.name.clone());
+        query_rules(&mut layer.rules, context, predicate, matches);
+        context.layers.pop();
+      }
+      CssRule::Style(style) => {
+        context.parent_selectors.push(style.selectors.clone());
+        query_rules(&mut style.rules, context, predicate, matches);
+        context.parent_selectors.pop();
+      }
+      CssRule::Nesting(nesting) => {
+        context.parent_selectors.push(nesting.style.selectors.clone());
+        query_rules(&mut nesting.style.rules, context, predicate, matches);
+        context.parent_selectors.pop();
+      }
+      CssRule::Scope(scope) => query_rules(&mut scope.rules, context, predicate, matches),
+      CssRule::StartingStyle(rule) => query_rules(&mut rule.rules, context, predicate, matches),
+      CssRule::MozDocument(document) => query_rules(&mut document.rules, context, predicate, matches),
+      _ => {}
+    }
+  }
+}
+
+/// Returns the selectors of a style rule, or `None` for other rules.
+pub(crate) fn style_selectors<'a, 'i, T>(rule: &'a CssRule<'i, T>) -> Option<&'a SelectorList<'i>> {
+  match rule {
+    CssRule::Style(style) => Some(&style.selectors),
+    CssRule::Nesting(nesting) => Some(&nesting.style.selectors),
+    _ => None,
+  }
+}
+
+/// Returns whether the rule is an `@media`, `@supports`, `@container`, or `@layer` block with the given
+/// name and serialized condition.
+pub(crate) fn at_rule_matches<T>(rule: &CssRule<T>, name: &str, condition: &str) -> bool {
+  let prelude = match rule {
+    CssRule::Media(media) if name.eq_ignore_ascii_case("media") => serialize(&media.query),
+    CssRule::Supports(supports) if name.eq_ignore_ascii_case("supports") => serialize(&supports.condition),
+    CssRule::Container(container) if name.eq_ignore_ascii_case("container") => match &container.name {
+      Some(container_name) => format!("{} {}", serialize(container_name), serialize(&container.condition)),
+      None => serialize(&container.condition),
+    },
+    CssRule::LayerBlock(layer) if name.eq_ignore_ascii_case("layer") => {
+      layer.name.as_ref().map(serialize).unwrap_or_default()
+    }
+    _ => return false,
+  };
+  prelude == condition
+}
+
+pub(crate) fn serialize<V: ToCss>(value: &V) -> String {
+  value.to_css_string(PrinterOptions::default()).unwrap_or_default()
+}
+
+/// A rule visited by [AllRules](AllRules).
+#[derive(Debug)]
+pub struct RuleEntry<'a, 'i, T> {
+  /// The rule.
+  pub rule: &'a CssRule<'i, T>,
+  /// The rules enclosing the rule.
+  pub context: RuleContext<'i>,
+}
+
+/// A declaration visited by [AllDeclarations](AllDeclarations).
+#[derive(Debug)]
+pub struct DeclarationEntry<'a, 'i> {
+  /// The declaration.
+  pub property: &'a Property<'i>,
+  /// Whether the declaration is `!important`.
+  pub important: bool,
+  /// The selectors of the style rule containing the declaration.
+  pub selectors: &'a SelectorList<'i>,
+  /// The rules enclosing the style rule. For nested style rules, this includes the parent selectors.
+  pub context: RuleContext<'i>,
+}
+
+/// A selector visited by [AllSelectors](AllSelectors).
+#[derive(Debug)]
+pub struct SelectorEntry<'a, 'i> {
+  /// The selector.
+  pub selector: &'a Selector<'i>,
+  /// The rules enclosing the style rule. For nested style rules, this includes the parent selectors.
+  pub context: RuleContext<'i>,
+}
+
+/// The kind of context entered by a rule, so that it can be removed when the rule's children have been visited.
+#[derive(Debug, Clone, Copy)]
+enum ContextKind {
+  Media,
+  Supports,
+  Container,
+  Layer,
+  Parent,
+  None,
+}
+
+/// An iterator over all rules in a rule list, including nested rules, in source order. Each rule is visited
+/// before its children. Returned by [CssRuleList::iter_rules](CssRuleList::iter_rules).
+#[derive(Debug)]
+pub struct AllRules<'a, 'i, T> {
+  stack: Vec<(std::slice::Iter<'a, CssRule<'i, T>>, ContextKind)>,
+  context: RuleContext<'i>,
+  parents: Vec<&'a SelectorList<'i>>,
+}
+
+impl<'a, 'i, T> AllRules<'a, 'i, T> {
+  fn new(rules: &'a CssRuleList<'i, T>) -> Self {
+    AllRules {
+      stack: vec![(rules.0.iter(), ContextKind::None)],
+      context: RuleContext::default(),
+      parents: Vec::new(),
+    }
+  }
+
+  /// Adds the context of a rule, and returns its child rules.
+  fn enter(&mut self, rule: &'a CssRule<'i, T>) -> Option<(&'a CssRuleList<'i, T>, ContextKind)> {
+    match rule {
+      CssRule::Media(media) => {
+        self.context.media.push(media.query.clone());
+        Some((&media.rules, ContextKind::Media))
+      }
+      CssRule::Supports(supports) => {
+        self.context.supports.push(supports.condition.clone());
+        Some((&supports.rules, ContextKind::Supports))
+      }
+      CssRule::Container(container) => {
+        self.context.containers.push(container.condition.clone());
+        Some((&container.rules, ContextKind::Container))
+      }
+      CssRule::LayerBlock(layer) => {
+        self.context.layers.push(layer.name.clone());
+        Some((&layer.rules, ContextKind::Layer))
+      }
+      CssRule::Style(style) => {
+        self.context.parent_selectors.push(style.selectors.clone());
+        self.parents.push(&style.selectors);
+        Some((&style.rules, ContextKind::Parent))
+      }
+      CssRule::Nesting(nesting) => {
+        self.context.parent_selectors.push(nesting.style.selectors.clone());
+        self.parents.push(&nesting.style.selectors);
+        Some((&nesting.style.rules, ContextKind::Parent))
+      }
+      _ => rule.child_rules().map(|rules| (rules, ContextKind::None)),
+    }
+  }
+
+  /// Removes the context added by [enter](AllRules::enter).
+  fn exit(&mut self, kind: ContextKind) {
+    match kind {
+      ContextKind::Media => {
+        self.context.media.pop();
+      }
+      ContextKind::Supports => {
+        self.context.supports.pop();
+      }
+      ContextKind::Container => {
+        self.context.containers.pop();
+      }
+      ContextKind::Layer => {
+        self.context.layers.pop();
+      }
+      ContextKind::Parent => {
+        self.context.parent_selectors.pop();
+        self.parents.pop();
+      }
+      ContextKind::None => {}
+    }
+  }
+}
+
+impl<'a, 'i, T> Iterator for AllRules<'a, 'i, T> {
+  type Item = RuleEntry<'a, 'i, T>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let (iter, kind) = self.stack.last_mut()?;
+      match iter.next() {
+        Some(rule) => {
+          let context = self.context.clone();
+          if let Some((rules, kind)) = self.enter(rule) {
+            self.stack.push((rules.0.iter(), kind));
+          }
+          return Some(RuleEntry { rule, context });
+        }
+        None => {
+          let kind = *kind;
+          self.stack.pop();
+          self.exit(kind);
+        }
+      }
+    }
+  }
+}
+
+/// An iterator over the declarations of all style rules in a rule list, including nested rules, along
+/// with the selectors of the style rule containing them. Returned by
+/// [CssRuleList::iter_declarations](CssRuleList::iter_declarations).
+#[derive(Debug)]
+pub struct AllDeclarations<'a, 'i, T> {
+  rules: AllRules<'a, 'i, T>,
+  current: Option<CurrentBlock<'a, 'i>>,
+}
+
+#[derive(Debug)]
+struct CurrentBlock<'a, 'i> {
+  declarations: std::slice::Iter<'a, Property<'i>>,
+  important_declarations: std::slice::Iter<'a, Property<'i>>,
+  selectors: &'a SelectorList<'i>,
+  context: RuleContext<'i>,
+}
+
+impl<'a, 'i, T> Iterator for AllDeclarations<'a, 'i, T> {
+  type Item = DeclarationEntry<'a, 'i>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(current) = &mut self.current {
+        let (property, important) = match current.declarations.next() {
+          Some(property) => (property, false),
+          None => match current.important_declarations.next() {
+            Some(property) => (property, true),
+            None => {
+              self.current = None;
+              continue;
+            }
+          },
+        };
+        return Some(DeclarationEntry {
+          property,
+          important,
+          selectors: current.selectors,
+          context: current.context.clone(),
+        });
+      }
+
+      let entry = self.rules.next()?;
+      let (declarations, selectors) = match entry.rule {
+        CssRule::Style(style) => (&style.declarations, &style.selectors),
+        CssRule::Nesting(nesting) => (&nesting.style.declarations, &nesting.style.selectors),
+        // Nested declarations apply to the selectors of the enclosing style rule, which is the
+        // innermost parent, since nested declarations have no child rules.
+        CssRule::NestedDeclarations(nested) => match self.rules.parents.last() {
+          Some(selectors) => (&nested.declarations, *selectors),
+          None => continue,
+        },
+        _ => continue,
+      };
+
+      self.current = Some(CurrentBlock {
+        declarations: declarations.declarations.iter(),
+        important_declarations: declarations.important_declarations.iter(),
+        selectors,
+        context: entry.context,
+      });
+    }
+  }
+}
+
+/// An iterator over the selectors of all style rules in a rule list, including nested rules. Returned by
+/// [CssRuleList::iter_selectors](CssRuleList::iter_selectors).
+#[derive(Debug)]
+pub struct AllSelectors<'a, 'i, T> {
+  rules: AllRules<'a, 'i, T>,
+  current: Option<(std::slice::Iter<'a, Selector<'i>>, RuleContext<'i>)>,
+}
+
+impl<'a, 'i, T> Iterator for AllSelectors<'a, 'i, T> {
+  type Item = SelectorEntry<'a, 'i>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some((selectors, context)) = &mut self.current {
+        match selectors.next() {
+          Some(selector) => {
+            return Some(SelectorEntry {
+              selector,
+              context: context.clone(),
+            })
+          }
+          None => self.current = None,
+        }
+      }
+
+      let entry = self.rules.next()?;
+      if let Some(selectors) = style_selectors(entry.rule) {
+        self.current = Some((selectors.0.iter(), entry.context));
+      }
+    }
+  }
+}
+
+impl<'i, T> CssRuleList<'i, T> {
+  /// Returns an iterator over all rules in the list, including nested rules, along with the rules
+  /// enclosing them. Each rule is visited before its children.
+  pub fn iter_rules(&self) -> AllRules<'_, 'i, T> {
+    AllRules::new(self)
+  }
+
+  /// Returns an iterator over the declarations of all style rules in the list, including nested rules,
+  /// along with the selectors and rules enclosing them.
+  pub fn iter_declarations(&self) -> AllDeclarations<'_, 'i, T> {
+    AllDeclarations {
+      rules: AllRules::new(self),
+      current: None,
+    }
+  }
+
+  /// Returns an iterator over the selectors of all style rules in the list, including nested rules,
+  /// along with the rules enclosing them.
+  pub fn iter_selectors(&self) -> AllSelectors<'_, 'i, T> {
+    AllSelectors {
+      rules: AllRules::new(self),
+      current: None,
+    }
+  }
+}
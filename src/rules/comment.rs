@@ -0,0 +1,66 @@
+//! Comments preserved in the AST.
+
+use super::Location;
+use crate::error::PrinterError;
+use crate::printer::Printer;
+use crate::traits::ToCss;
+use crate::values::string::CowArcStr;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+
+/// A comment within a rule list, which is attached to the rule that follows it.
+///
+/// Comments are only preserved when the `preserve_comments` parser option is enabled. They are
+/// written in non-minified output, and when minifying only if they start with `/*!`, which is
+/// commonly used for license comments.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_comment, COMMENTS))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct CommentRule<'i> {
+  /// The text of the comment, excluding the `/*` and `*/` delimiters.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub text: CowArcStr<'i>,
+  /// The location of the comment in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+impl<'i> CommentRule<'i> {
+  /// Returns whether the comment is written with the given `minify` option.
+  pub fn is_written(&self, minify: bool) -> bool {
+    is_comment_written(&self.text, minify)
+  }
+}
+
+impl<'i> ToCss for CommentRule<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    write_comment(&self.text, dest)
+  }
+}
+
+pub(crate) fn is_comment_written(text: &str, minify: bool) -> bool {
+  !minify || text.starts_with('!')
+}
+
+/// Writes a comment, which may span multiple lines.
+pub(crate) fn write_comment<W>(text: &str, dest: &mut Printer<W>) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  dest.write_str("/*")?;
+  for (i, line) in text.split('\n').enumerate() {
+    if i > 0 {
+      dest.write_char('\n')?;
+    }
+    dest.write_str(line)?;
+  }
+  dest.write_str("*/")
+}
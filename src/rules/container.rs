@@ -4,10 +4,13 @@ use cssparser::*;
 
 use super::Location;
 use super::{CssRuleList, MinifyContext};
+use crate::declaration::DeclarationBlock;
 use crate::error::{MinifyError, ParserError, PrinterError};
 use crate::media_query::MediaCondition;
 use crate::parser::DefaultAtRule;
-use crate::printer::Printer;
+use crate::printer::{Printer, PrinterOptions};
+use crate::properties::Property;
+use crate::selector::SelectorList;
 use crate::traits::{Parse, ToCss};
 use crate::values::ident::CustomIdent;
 #[cfg(feature = "visitor")]
@@ -58,6 +61,114 @@ impl<'i> ToCss for ContainerName<'i> {
   }
 }
 
+/// The query containers and container queries in a style sheet, produced by
+/// [StyleSheet::polyfill_container_queries](crate::stylesheet::StyleSheet::polyfill_container_queries)
+/// for use by a runtime container query polyfill.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ContainerQueryManifest {
+  /// The style rules that establish query containers.
+  pub containers: Vec<PolyfillContainer>,
+  /// The `@container` rules that were replaced with attribute selectors.
+  pub queries: Vec<PolyfillContainerQuery>,
+}
+
+/// A style rule that establishes a query container via the `container`, `container-type`,
+/// or `container-name` properties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PolyfillContainer {
+  /// The selector of the style rule, with any nesting resolved.
+  pub selector: String,
+  /// The value of `container-name`, if declared.
+  pub name: Option<String>,
+  /// The value of `container-type`, if declared.
+  pub container_type: Option<String>,
+}
+
+/// A container query that was replaced with an attribute selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct PolyfillContainerQuery {
+  /// The value of the attribute that the polyfill should add to elements while the query matches.
+  /// Multiple values are separated by spaces, e.g. `data-cq="0 1"`.
+  pub id: String,
+  /// The name of the queried container, if any.
+  pub name: Option<String>,
+  /// The container condition, e.g. `(width > 400px)`.
+  pub condition: String,
+}
+
+impl ContainerQueryManifest {
+  pub(crate) fn add_container<'i>(
+    &mut self,
+    declarations: &DeclarationBlock<'i>,
+    selectors: &[SelectorList<'i>],
+  ) -> Result<(), PrinterError> {
+    let mut name = None;
+    let mut container_type = None;
+    for (property, _) in declarations.iter() {
+      match property {
+        Property::ContainerName(names) => name = Some(names.to_css_string(PrinterOptions::default())?),
+        Property::ContainerType(t) => container_type = Some(t.as_str().to_owned()),
+        Property::Container(container) => {
+          name = Some(container.name.to_css_string(PrinterOptions::default())?);
+          container_type = Some(container.container_type.as_str().to_owned());
+        }
+        _ => {}
+      }
+    }
+
+    if name.is_none() && container_type.is_none() {
+      return Ok(());
+    }
+
+    let mut selector = String::new();
+    let mut printer = Printer::new(&mut selector, PrinterOptions::default());
+    serialize_nested_selectors(&mut printer, selectors)?;
+    self.containers.push(PolyfillContainer {
+      selector,
+      name,
+      container_type,
+    });
+    Ok(())
+  }
+
+  /// Returns the id for the given container query, reusing the id of an identical query.
+  pub(crate) fn add_query<'i, T>(&mut self, rule: &ContainerRule<'i, T>) -> Result<String, PrinterError> {
+    let name = rule.name.as_ref().map(|name| (name.0).0.to_string());
+    let condition = rule.condition.to_css_string(PrinterOptions::default())?;
+    if let Some(query) = self.queries.iter().find(|q| q.name == name && q.condition == condition) {
+      return Ok(query.id.clone());
+    }
+
+    let id = self.queries.len().to_string();
+    self.queries.push(PolyfillContainerQuery {
+      id: id.clone(),
+      name,
+      condition,
+    });
+    Ok(id)
+  }
+}
+
+/// Serializes the last selector list in the given chain of nested style rules, resolving
+/// nesting selectors against the ones before it.
+fn serialize_nested_selectors<W: std::fmt::Write>(
+  dest: &mut Printer<W>,
+  selectors: &[SelectorList],
+) -> Result<(), PrinterError> {
+  match selectors {
+    [] => Ok(()),
+    [last] => last.to_css(dest),
+    [parent, rest @ ..] => dest.with_context(parent, |dest| serialize_nested_selectors(dest, rest)),
+  }
+}
+
 impl<'i, T> ContainerRule<'i, T> {
   pub(crate) fn minify(
     &mut self,
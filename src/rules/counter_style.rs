@@ -1,13 +1,18 @@
 //! The `@counter-style` rule.
 
 use super::Location;
-use crate::declaration::DeclarationBlock;
-use crate::error::PrinterError;
+use crate::error::{ParserError, PrinterError};
+use crate::macros::enum_property;
 use crate::printer::Printer;
-use crate::traits::ToCss;
-use crate::values::ident::CustomIdent;
+use crate::properties::custom::CustomProperty;
+use crate::properties::list::{CounterStyle, Symbol};
+use crate::stylesheet::ParserOptions;
+use crate::traits::{Parse, ToCss};
+use crate::values::ident::{CustomIdent, Ident};
+use crate::values::number::CSSInteger;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
+use cssparser::*;
 
 /// A [@counter-style](https://drafts.csswg.org/css-counter-styles/#the-counter-style-rule) rule.
 #[derive(Debug, PartialEq, Clone)]
@@ -19,14 +24,536 @@ pub struct CounterStyleRule<'i> {
   /// The name of the counter style to declare.
   #[cfg_attr(feature = "serde", serde(borrow))]
   pub name: CustomIdent<'i>,
-  // TODO: eventually parse these properties
   /// Declarations in the `@counter-style` rule.
-  pub declarations: DeclarationBlock<'i>,
+  pub properties: Vec<CounterStyleProperty<'i>>,
   /// The location of the rule in the source file.
   #[cfg_attr(feature = "visitor", skip_visit)]
   pub loc: Location,
 }
 
+/// A property within an `@counter-style` rule.
+///
+/// See [CounterStyleRule](CounterStyleRule).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum CounterStyleProperty<'i> {
+  /// The `system` property.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  System(CounterSystem<'i>),
+  /// The `symbols` property.
+  Symbols(Vec<Symbol<'i>>),
+  /// The `additive-symbols` property.
+  AdditiveSymbols(Vec<AdditiveSymbol<'i>>),
+  /// The `negative` property.
+  Negative(Negative<'i>),
+  /// The `prefix` property.
+  Prefix(Symbol<'i>),
+  /// The `suffix` property.
+  Suffix(Symbol<'i>),
+  /// The `range` property.
+  Range(CounterRange),
+  /// The `pad` property.
+  Pad(Pad<'i>),
+  /// The `fallback` property.
+  Fallback(CounterStyle<'i>),
+  /// The `speak-as` property.
+  SpeakAs(SpeakAs<'i>),
+  /// An unknown or unsupported property.
+  Custom(CustomProperty<'i>),
+}
+
+/// A value for the [system](https://drafts.csswg.org/css-counter-styles/#counter-style-system)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum CounterSystem<'i> {
+  /// Cycles repeatedly through the symbols.
+  Cyclic,
+  /// Interprets the symbols as digits of a place-value numbering system.
+  Numeric,
+  /// Interprets the symbols as digits of an alphabetic numbering system.
+  Alphabetic,
+  /// Cycles repeatedly through the symbols, doubling them on each successive pass.
+  Symbolic,
+  /// Represents sign-value numbering systems using the additive symbols.
+  Additive,
+  /// Runs through the symbols once, starting at the given value.
+  Fixed(CSSInteger),
+  /// Uses the algorithm of another counter style, overriding some of its descriptors.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Extends(CounterStyle<'i>),
+}
+
+impl<'i> Parse<'i> for CounterSystem<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { ident,
+      "cyclic" => Ok(CounterSystem::Cyclic),
+      "numeric" => Ok(CounterSystem::Numeric),
+      "alphabetic" => Ok(CounterSystem::Alphabetic),
+      "symbolic" => Ok(CounterSystem::Symbolic),
+      "additive" => Ok(CounterSystem::Additive),
+      "fixed" => {
+        let first = input.try_parse(CSSInteger::parse).unwrap_or(1);
+        Ok(CounterSystem::Fixed(first))
+      },
+      "extends" => Ok(CounterSystem::Extends(parse_counter_style_name(input)?)),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+impl<'i> ToCss for CounterSystem<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      CounterSystem::Cyclic => dest.write_str("cyclic"),
+      CounterSystem::Numeric => dest.write_str("numeric"),
+      CounterSystem::Alphabetic => dest.write_str("alphabetic"),
+      CounterSystem::Symbolic => dest.write_str("symbolic"),
+      CounterSystem::Additive => dest.write_str("additive"),
+      CounterSystem::Fixed(first) => {
+        dest.write_str("fixed")?;
+        // The first symbol value defaults to 1.
+        if *first != 1 {
+          dest.write_char(' ')?;
+          first.to_css(dest)?;
+        }
+        Ok(())
+      }
+      CounterSystem::Extends(name) => {
+        dest.write_str("extends ")?;
+        name.to_css(dest)
+      }
+    }
+  }
+}
+
+/// A weighted symbol within the [additive-symbols](https://drafts.csswg.org/css-counter-styles/#counter-style-symbols)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct AdditiveSymbol<'i> {
+  /// The weight of the symbol.
+  pub weight: CSSInteger,
+  /// The symbol.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub symbol: Symbol<'i>,
+}
+
+impl<'i> Parse<'i> for AdditiveSymbol<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let (weight, symbol) = parse_integer_and_symbol(input)?;
+    Ok(AdditiveSymbol { weight, symbol })
+  }
+}
+
+impl<'i> ToCss for AdditiveSymbol<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.weight.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.symbol.to_css(dest)
+  }
+}
+
+/// A value for the [negative](https://drafts.csswg.org/css-counter-styles/#counter-style-negative)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Negative<'i> {
+  /// The symbol prepended to negative counter values.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub prefix: Symbol<'i>,
+  /// An optional symbol appended to negative counter values.
+  pub suffix: Option<Symbol<'i>>,
+}
+
+impl<'i> Parse<'i> for Negative<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let prefix = parse_symbol(input)?;
+    let suffix = input.try_parse(parse_symbol).ok();
+    Ok(Negative { prefix, suffix })
+  }
+}
+
+impl<'i> ToCss for Negative<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.prefix.to_css(dest)?;
+    if let Some(suffix) = &self.suffix {
+      dest.write_char(' ')?;
+      suffix.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// A value for the [range](https://drafts.csswg.org/css-counter-styles/#counter-style-range)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum CounterRange {
+  /// The range depends on the counter system.
+  Auto,
+  /// A list of ranges.
+  Ranges(Vec<CounterRangeItem>),
+}
+
+/// A single range within the `range` property of an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct CounterRangeItem {
+  /// The lower bound of the range, or negative infinity if `None`.
+  pub start: Option<CSSInteger>,
+  /// The upper bound of the range, or positive infinity if `None`.
+  pub end: Option<CSSInteger>,
+}
+
+impl<'i> Parse<'i> for CounterRange {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(CounterRange::Auto);
+    }
+
+    let ranges = input.parse_comma_separated(|input| {
+      let start = parse_range_bound(input)?;
+      let end = parse_range_bound(input)?;
+      if let (Some(start), Some(end)) = (start, end) {
+        if start > end {
+          return Err(input.new_custom_error(ParserError::InvalidValue));
+        }
+      }
+      Ok(CounterRangeItem { start, end })
+    })?;
+    Ok(CounterRange::Ranges(ranges))
+  }
+}
+
+fn parse_range_bound<'i, 't>(
+  input: &mut Parser<'i, 't>,
+) -> Result<Option<CSSInteger>, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_ident_matching("infinite")).is_ok() {
+    return Ok(None);
+  }
+
+  Ok(Some(CSSInteger::parse(input)?))
+}
+
+impl ToCss for CounterRange {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      CounterRange::Auto => dest.write_str("auto"),
+      CounterRange::Ranges(ranges) => {
+        let mut first = true;
+        for range in ranges {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          write_range_bound(range.start, dest)?;
+          dest.write_char(' ')?;
+          write_range_bound(range.end, dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+fn write_range_bound<W>(bound: Option<CSSInteger>, dest: &mut Printer<W>) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  match bound {
+    Some(value) => value.to_css(dest),
+    None => dest.write_str("infinite"),
+  }
+}
+
+/// A value for the [pad](https://drafts.csswg.org/css-counter-styles/#counter-style-pad)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Pad<'i> {
+  /// The minimum length of the marker representation.
+  pub width: CSSInteger,
+  /// The symbol used to pad the marker representation.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub symbol: Symbol<'i>,
+}
+
+impl<'i> Parse<'i> for Pad<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let (width, symbol) = parse_integer_and_symbol(input)?;
+    Ok(Pad { width, symbol })
+  }
+}
+
+impl<'i> ToCss for Pad<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.width.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.symbol.to_css(dest)
+  }
+}
+
+enum_property! {
+  /// A keyword for the [speak-as](https://drafts.csswg.org/css-counter-styles/#counter-style-speak-as)
+  /// property in an `@counter-style` rule.
+  pub enum SpeakAsKeyword {
+    /// Chosen based on the counter system.
+    "auto": Auto,
+    /// Reads a UA-defined phrase or audio cue for a bullet point.
+    "bullets": Bullets,
+    /// Reads the numeric value of the counter in the content language.
+    "numbers": Numbers,
+    /// Reads the generated counter representation as words.
+    "words": Words,
+    /// Spells out the generated counter representation letter by letter.
+    "spell-out": SpellOut,
+  }
+}
+
+/// A value for the [speak-as](https://drafts.csswg.org/css-counter-styles/#counter-style-speak-as)
+/// property in an `@counter-style` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum SpeakAs<'i> {
+  /// A speak-as keyword.
+  Keyword(SpeakAsKeyword),
+  /// Reads the counter as the given counter style would.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  CounterStyle(CounterStyle<'i>),
+}
+
+impl<'i> Parse<'i> for SpeakAs<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(keyword) = input.try_parse(SpeakAsKeyword::parse) {
+      return Ok(SpeakAs::Keyword(keyword));
+    }
+
+    Ok(SpeakAs::CounterStyle(parse_counter_style_name(input)?))
+  }
+}
+
+impl<'i> ToCss for SpeakAs<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      SpeakAs::Keyword(keyword) => keyword.to_css(dest),
+      SpeakAs::CounterStyle(name) => name.to_css(dest),
+    }
+  }
+}
+
+/// Parses a [`<counter-style-name>`](https://drafts.csswg.org/css-counter-styles/#typedef-counter-style-name).
+fn parse_counter_style_name<'i, 't>(
+  input: &mut Parser<'i, 't>,
+) -> Result<CounterStyle<'i>, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  match CounterStyle::parse(input)? {
+    CounterStyle::Symbols { .. } => Err(location.new_custom_error(ParserError::InvalidValue)),
+    name => Ok(name),
+  }
+}
+
+/// Parses a [`<symbol>`](https://drafts.csswg.org/css-counter-styles/#typedef-symbol),
+/// which may also be an identifier within an `@counter-style` rule.
+fn parse_symbol<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Symbol<'i>, ParseError<'i, ParserError<'i>>> {
+  if let Ok(ident) = input.try_parse(CustomIdent::parse) {
+    return Ok(Symbol::Ident(Ident(ident.0)));
+  }
+
+  Symbol::parse(input)
+}
+
+/// Parses a non-negative integer and a symbol, in either order.
+fn parse_integer_and_symbol<'i, 't>(
+  input: &mut Parser<'i, 't>,
+) -> Result<(CSSInteger, Symbol<'i>), ParseError<'i, ParserError<'i>>> {
+  let symbol = input.try_parse(parse_symbol).ok();
+  let location = input.current_source_location();
+  let integer = CSSInteger::parse(input)?;
+  if integer < 0 {
+    return Err(location.new_custom_error(ParserError::InvalidValue));
+  }
+
+  let symbol = match symbol {
+    Some(symbol) => symbol,
+    None => parse_symbol(input)?,
+  };
+
+  Ok((integer, symbol))
+}
+
+impl<'i> CounterStyleRule<'i> {
+  pub(crate) fn parse<'t, 'o>(
+    name: CustomIdent<'i>,
+    input: &mut Parser<'i, 't>,
+    loc: Location,
+    options: &ParserOptions<'o, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let parser = DeclarationListParser::new(input, CounterStyleDeclarationParser);
+    let mut properties = vec![];
+    // Invalid values for known descriptors are ignored.
+    for decl in parser {
+      match decl {
+        Ok(decl) => properties.push(decl),
+        Err((err, _)) => options.warn(err),
+      }
+    }
+
+    Ok(CounterStyleRule { name, properties, loc })
+  }
+
+  pub(crate) fn minify(&mut self) {
+    // Later descriptors override earlier ones with the same name, so only the last one is kept.
+    let mut deduped: Vec<CounterStyleProperty<'i>> = Vec::with_capacity(self.properties.len());
+    for property in self.properties.drain(..).rev() {
+      if !deduped.iter().any(|p| p.name().eq_ignore_ascii_case(property.name())) {
+        deduped.push(property);
+      }
+    }
+    deduped.reverse();
+    self.properties = deduped;
+  }
+}
+
+pub(crate) struct CounterStyleDeclarationParser;
+
+impl<'i> cssparser::DeclarationParser<'i> for CounterStyleDeclarationParser {
+  type Declaration = CounterStyleProperty<'i>;
+  type Error = ParserError<'i>;
+
+  fn parse_value<'t>(
+    &mut self,
+    name: CowRcStr<'i>,
+    input: &mut cssparser::Parser<'i, 't>,
+  ) -> Result<Self::Declaration, cssparser::ParseError<'i, Self::Error>> {
+    macro_rules! property {
+      ($property: ident, $parse: expr) => {{
+        let value = $parse(input)?;
+        input.expect_exhausted()?;
+        return Ok(CounterStyleProperty::$property(value));
+      }};
+    }
+
+    match_ignore_ascii_case! { &name,
+      "system" => property!(System, CounterSystem::parse),
+      "symbols" => property!(Symbols, |input: &mut Parser<'i, '_>| {
+        let mut symbols = vec![parse_symbol(input)?];
+        while let Ok(symbol) = input.try_parse(parse_symbol) {
+          symbols.push(symbol);
+        }
+        Ok::<_, ParseError<'i, ParserError<'i>>>(symbols)
+      }),
+      "additive-symbols" => property!(AdditiveSymbols, |input: &mut Parser<'i, '_>| {
+        let symbols = input.parse_comma_separated(AdditiveSymbol::parse)?;
+        // Weights must be in strictly descending order.
+        if symbols.windows(2).any(|w| w[0].weight <= w[1].weight) {
+          return Err(input.new_custom_error(ParserError::InvalidValue));
+        }
+        Ok(symbols)
+      }),
+      "negative" => property!(Negative, Negative::parse),
+      "prefix" => property!(Prefix, parse_symbol),
+      "suffix" => property!(Suffix, parse_symbol),
+      "range" => property!(Range, CounterRange::parse),
+      "pad" => property!(Pad, Pad::parse),
+      "fallback" => property!(Fallback, parse_counter_style_name),
+      "speak-as" => property!(SpeakAs, SpeakAs::parse),
+      _ => {}
+    }
+
+    Ok(CounterStyleProperty::Custom(CustomProperty::parse(
+      name.into(),
+      input,
+      &ParserOptions::default(),
+    )?))
+  }
+}
+
+/// Default methods reject all at rules.
+impl<'i> AtRuleParser<'i> for CounterStyleDeclarationParser {
+  type Prelude = ();
+  type AtRule = CounterStyleProperty<'i>;
+  type Error = ParserError<'i>;
+}
+
+impl<'i> CounterStyleProperty<'i> {
+  /// Returns the name of the descriptor.
+  fn name(&self) -> &str {
+    match self {
+      CounterStyleProperty::System(..) => "system",
+      CounterStyleProperty::Symbols(..) => "symbols",
+      CounterStyleProperty::AdditiveSymbols(..) => "additive-symbols",
+      CounterStyleProperty::Negative(..) => "negative",
+      CounterStyleProperty::Prefix(..) => "prefix",
+      CounterStyleProperty::Suffix(..) => "suffix",
+      CounterStyleProperty::Range(..) => "range",
+      CounterStyleProperty::Pad(..) => "pad",
+      CounterStyleProperty::Fallback(..) => "fallback",
+      CounterStyleProperty::SpeakAs(..) => "speak-as",
+      CounterStyleProperty::Custom(custom) => custom.name.as_ref(),
+    }
+  }
+}
+
 impl<'i> ToCss for CounterStyleRule<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -36,6 +563,65 @@ impl<'i> ToCss for CounterStyleRule<'i> {
     dest.add_mapping(self.loc);
     dest.write_str("@counter-style ")?;
     self.name.to_css(dest)?;
-    self.declarations.to_css_block(dest)
+    dest.whitespace()?;
+    dest.write_char('{')?;
+    dest.indent();
+    let len = self.properties.len();
+    for (i, prop) in self.properties.iter().enumerate() {
+      dest.newline()?;
+      prop.to_css(dest)?;
+      if i != len - 1 || !dest.minify {
+        dest.write_char(';')?;
+      }
+    }
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
+
+impl<'i> ToCss for CounterStyleProperty<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    macro_rules! property {
+      ($prop: literal, $value: expr) => {{
+        dest.write_str($prop)?;
+        dest.delim(':', false)?;
+        $value.to_css(dest)
+      }};
+    }
+
+    match self {
+      CounterStyleProperty::System(s) => property!("system", s),
+      CounterStyleProperty::Symbols(symbols) => {
+        dest.write_str("symbols")?;
+        dest.delim(':', false)?;
+        let mut first = true;
+        for symbol in symbols {
+          if first {
+            first = false;
+          } else {
+            dest.write_char(' ')?;
+          }
+          symbol.to_css(dest)?;
+        }
+        Ok(())
+      }
+      CounterStyleProperty::AdditiveSymbols(a) => property!("additive-symbols", a),
+      CounterStyleProperty::Negative(n) => property!("negative", n),
+      CounterStyleProperty::Prefix(p) => property!("prefix", p),
+      CounterStyleProperty::Suffix(s) => property!("suffix", s),
+      CounterStyleProperty::Range(r) => property!("range", r),
+      CounterStyleProperty::Pad(p) => property!("pad", p),
+      CounterStyleProperty::Fallback(f) => property!("fallback", f),
+      CounterStyleProperty::SpeakAs(s) => property!("speak-as", s),
+      CounterStyleProperty::Custom(custom) => {
+        dest.write_str(custom.name.as_ref())?;
+        dest.delim(':', false)?;
+        custom.value.to_css(dest, true)
+      }
+    }
   }
 }
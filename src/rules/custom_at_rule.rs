@@ -0,0 +1,227 @@
+//! At-rules registered with the `custom_at_rules` parser option.
+
+use super::{CssRuleList, Location, MinifyContext};
+use crate::declaration::DeclarationBlock;
+use crate::error::{MinifyError, ParserError, PrinterError};
+use crate::parser::{parse_nested_at_rule, DefaultAtRule};
+use crate::printer::Printer;
+use crate::properties::custom::TokenList;
+use crate::stylesheet::ParserOptions;
+use crate::traits::{AtRuleParser, ToCss};
+use crate::values::string::CowArcStr;
+use crate::values::syntax::{ParsedComponent, SyntaxString};
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+/// The grammar of an at-rule registered with the `custom_at_rules` parser option.
+///
+/// # Example
+///
+/// ```
+/// use lightningcss::{
+///   rules::custom_at_rule::{CustomAtRuleBodyType, CustomAtRuleConfig},
+///   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+///   values::syntax::SyntaxString,
+/// };
+///
+/// let mut options = ParserOptions::default();
+/// options.custom_at_rules.insert(
+///   "tailwind".into(),
+///   CustomAtRuleConfig {
+///     prelude: Some(SyntaxString::parse_string("<custom-ident>").unwrap()),
+///     body: None,
+///   },
+/// );
+///
+/// let stylesheet = StyleSheet::parse("@tailwind base;", options).unwrap();
+/// let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+/// assert_eq!(res.code, "@tailwind base;\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CustomAtRuleConfig {
+  /// The grammar of the prelude, in the syntax used by the `syntax` descriptor of `@property` rules,
+  /// or `None` if the at-rule has no prelude.
+  pub prelude: Option<SyntaxString>,
+  /// The type of the block, or `None` if the at-rule is a statement ending with a semicolon.
+  pub body: Option<CustomAtRuleBodyType>,
+}
+
+impl CustomAtRuleConfig {
+  /// Parses the prelude of an at-rule according to the registered grammar.
+  pub(crate) fn parse_prelude<'i, 't>(
+    &self,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Option<ParsedComponent<'i>>, ParseError<'i, ParserError<'i>>> {
+    let prelude = match &self.prelude {
+      Some(syntax) => Some(syntax.parse_value(input)?),
+      None => None,
+    };
+    input.expect_exhausted()?;
+    Ok(prelude)
+  }
+}
+
+/// The type of the block of a [CustomAtRule](CustomAtRule).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "kebab-case")
+)]
+pub enum CustomAtRuleBodyType {
+  /// A list of declarations, e.g. `@font-face`.
+  DeclarationList,
+  /// A list of rules, e.g. `@media`.
+  RuleList,
+  /// A list of declarations and nested rules, like the block of a style rule.
+  StyleBlock,
+  /// A list of tokens, which are not interpreted.
+  Raw,
+}
+
+/// An at-rule registered with the `custom_at_rules` parser option.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_custom_at_rule, CUSTOM_AT_RULES))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct CustomAtRule<'i, R = DefaultAtRule> {
+  /// The name of the at-rule (without the @).
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub name: CowArcStr<'i>,
+  /// The prelude of the at-rule, parsed according to its registered grammar.
+  pub prelude: Option<ParsedComponent<'i>>,
+  /// The block of the at-rule, if any.
+  pub body: Option<CustomAtRuleBody<'i, R>>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+/// The block of a [CustomAtRule](CustomAtRule).
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum CustomAtRuleBody<'i, R = DefaultAtRule> {
+  /// A list of declarations.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  DeclarationList(DeclarationBlock<'i>),
+  /// A list of rules. Style blocks are also represented as a rule list, with the declarations
+  /// before any nested rules stored in a nested declarations rule.
+  RuleList(CssRuleList<'i, R>),
+  /// A list of tokens.
+  Raw(TokenList<'i>),
+}
+
+impl<'i, T> CustomAtRule<'i, T> {
+  /// Parses the block of a registered at-rule according to its body type.
+  pub(crate) fn parse_block<'t, P: AtRuleParser<'i, AtRule = T>>(
+    name: CowArcStr<'i>,
+    prelude: Option<ParsedComponent<'i>>,
+    input: &mut Parser<'i, 't>,
+    loc: Location,
+    options: &ParserOptions<'_, 'i>,
+    at_rule_parser: &mut P,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let body = match options.custom_at_rules.get(name.as_ref()).and_then(|config| config.body) {
+      Some(CustomAtRuleBodyType::DeclarationList) => {
+        CustomAtRuleBody::DeclarationList(DeclarationBlock::parse(input, options)?)
+      }
+      Some(CustomAtRuleBodyType::RuleList) => {
+        CustomAtRuleBody::RuleList(CssRuleList::parse_with(input, options, at_rule_parser)?)
+      }
+      Some(CustomAtRuleBodyType::StyleBlock) => {
+        CustomAtRuleBody::RuleList(parse_nested_at_rule(input, options, at_rule_parser)?)
+      }
+      Some(CustomAtRuleBodyType::Raw) => CustomAtRuleBody::Raw(TokenList::parse(input, options, 0)?),
+      None => return Err(input.new_error(BasicParseErrorKind::AtRuleBodyInvalid)),
+    };
+
+    Ok(CustomAtRule {
+      name,
+      prelude,
+      body: Some(body),
+      loc,
+    })
+  }
+
+  /// Creates a registered at-rule without a block, if its grammar allows it.
+  pub(crate) fn without_block(
+    name: CowArcStr<'i>,
+    prelude: Option<ParsedComponent<'i>>,
+    loc: Location,
+    options: &ParserOptions<'_, 'i>,
+  ) -> Result<Self, ()> {
+    match options.custom_at_rules.get(name.as_ref()) {
+      Some(config) if config.body.is_none() => Ok(CustomAtRule {
+        name,
+        prelude,
+        body: None,
+        loc,
+      }),
+      _ => Err(()),
+    }
+  }
+
+  pub(crate) fn minify(
+    &mut self,
+    context: &mut MinifyContext<'_, 'i>,
+    parent_is_unused: bool,
+  ) -> Result<(), MinifyError> {
+    match &mut self.body {
+      Some(CustomAtRuleBody::DeclarationList(declarations)) => {
+        declarations.minify(context.handler, context.important_handler, context.handler_context)
+      }
+      Some(CustomAtRuleBody::RuleList(rules)) => rules.minify(context, parent_is_unused)?,
+      Some(CustomAtRuleBody::Raw(..)) | None => {}
+    }
+    Ok(())
+  }
+}
+
+impl<'i, T: ToCss> ToCss for CustomAtRule<'i, T> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    dest.write_char('@')?;
+    serialize_identifier(&self.name, dest)?;
+    if let Some(prelude) = &self.prelude {
+      dest.write_char(' ')?;
+      prelude.to_css(dest)?;
+    }
+
+    match &self.body {
+      Some(CustomAtRuleBody::DeclarationList(declarations)) => declarations.to_css_block(dest),
+      Some(CustomAtRuleBody::RuleList(rules)) => {
+        dest.whitespace()?;
+        dest.write_char('{')?;
+        dest.indent();
+        dest.newline()?;
+        rules.to_css(dest)?;
+        dest.dedent();
+        dest.newline()?;
+        dest.write_char('}')
+      }
+      Some(CustomAtRuleBody::Raw(tokens)) => {
+        dest.whitespace()?;
+        dest.write_char('{')?;
+        dest.indent();
+        dest.newline()?;
+        tokens.to_css(dest, false)?;
+        dest.dedent();
+        dest.newline()?;
+        dest.write_char('}')
+      }
+      None => dest.write_char(';'),
+    }
+  }
+}
@@ -2,30 +2,132 @@
 
 use super::Location;
 use super::{CssRuleList, MinifyContext};
-use crate::error::{MinifyError, PrinterError};
+use crate::error::{MinifyError, ParserError, PrinterError};
 use crate::parser::DefaultAtRule;
 use crate::printer::Printer;
 use crate::traits::ToCss;
+use crate::values::string::CowArcStr;
+use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
+use cssparser::*;
 
 /// A [@-moz-document](https://www.w3.org/TR/2012/WD-css3-conditional-20120911/#at-document) rule.
 ///
-/// Note that only the `url-prefix()` function with no arguments is supported, and only the `-moz` prefix
-/// is allowed since Firefox was the only browser that ever implemented this rule.
+/// Firefox was the only browser that ever implemented this rule, and it is now limited to user
+/// stylesheets there, except for an empty `url-prefix()` which is still a common Firefox-only hack.
+/// The rule is preserved as written so it keeps working in user styles.
 #[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
 #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
 pub struct MozDocumentRule<'i, R = DefaultAtRule> {
-  /// Nested rules within the `@-moz-document` rule.
+  /// The document matching functions. The rule applies if any of them match.
   #[cfg_attr(feature = "serde", serde(borrow))]
+  pub matchers: Vec<DocumentMatcher<'i>>,
+  /// Nested rules within the `@-moz-document` rule.
   pub rules: CssRuleList<'i, R>,
+  /// The vendor prefix for the rule, e.g. `@-moz-document`.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub vendor_prefix: VendorPrefix,
   /// The location of the rule in the source file.
   #[cfg_attr(feature = "visitor", skip_visit)]
   pub loc: Location,
 }
 
+/// A document matching function within an `@-moz-document` rule.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum DocumentMatcher<'i> {
+  /// Matches a document with exactly the given URL, e.g. `url(https://example.com/)`.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Url(CowArcStr<'i>),
+  /// Matches documents whose URL starts with the given string. An empty prefix matches all documents.
+  UrlPrefix(CowArcStr<'i>),
+  /// Matches documents on the given domain or one of its subdomains.
+  Domain(CowArcStr<'i>),
+  /// Matches documents whose entire URL matches the given regular expression.
+  Regexp(CowArcStr<'i>),
+}
+
+impl<'i> DocumentMatcher<'i> {
+  pub(crate) fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(url) = input.try_parse(|input| input.expect_url()) {
+      return Ok(DocumentMatcher::Url(url.into()));
+    }
+
+    let location = input.current_source_location();
+    let name = input.expect_function()?.clone();
+    input.parse_nested_block(|input| {
+      match_ignore_ascii_case! { &name,
+        "url-prefix" => Ok(DocumentMatcher::UrlPrefix(parse_quoted_or_unquoted_string(input)?)),
+        "domain" => Ok(DocumentMatcher::Domain(parse_quoted_or_unquoted_string(input)?)),
+        "regexp" => Ok(DocumentMatcher::Regexp(input.expect_string_cloned()?.into())),
+        _ => Err(location.new_unexpected_token_error(Token::Function(name.clone())))
+      }
+    })
+  }
+}
+
+/// Parses the argument of `url-prefix()` and `domain()`, which Firefox accepts either
+/// as a string or as the raw source text of the function arguments.
+fn parse_quoted_or_unquoted_string<'i, 't>(
+  input: &mut Parser<'i, 't>,
+) -> Result<CowArcStr<'i>, ParseError<'i, ParserError<'i>>> {
+  if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+    input.expect_exhausted()?;
+    return Ok(s.into());
+  }
+
+  let start = input.position();
+  while input.next().is_ok() {}
+  Ok(input.slice_from(start).trim().into())
+}
+
+impl<'i> ToCss for DocumentMatcher<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      DocumentMatcher::Url(url) => {
+        use cssparser::ToCss;
+        Token::UnquotedUrl(CowRcStr::from(url.as_ref())).to_css(dest)?;
+        Ok(())
+      }
+      DocumentMatcher::UrlPrefix(prefix) => {
+        dest.write_str("url-prefix(")?;
+        // An empty prefix is written without a string, which is the form used by the Firefox hack.
+        if !prefix.is_empty() {
+          serialize_string(prefix, dest)?;
+        }
+        dest.write_char(')')
+      }
+      DocumentMatcher::Domain(domain) => {
+        dest.write_str("domain(")?;
+        serialize_string(domain, dest)?;
+        dest.write_char(')')
+      }
+      DocumentMatcher::Regexp(regexp) => {
+        dest.write_str("regexp(")?;
+        serialize_string(regexp, dest)?;
+        dest.write_char(')')
+      }
+    }
+  }
+}
+
 impl<'i, T> MozDocumentRule<'i, T> {
   pub(crate) fn minify(&mut self, context: &mut MinifyContext<'_, 'i>) -> Result<(), MinifyError> {
     self.rules.minify(context, false)
@@ -39,7 +141,18 @@ impl<'i, T: ToCss> ToCss for MozDocumentRule<'i, T> {
   {
     #[cfg(feature = "sourcemap")]
     dest.add_mapping(self.loc);
-    dest.write_str("@-moz-document url-prefix()")?;
+    dest.write_char('@')?;
+    self.vendor_prefix.to_css(dest)?;
+    dest.write_str("document ")?;
+    let mut first = true;
+    for matcher in &self.matchers {
+      if first {
+        first = false;
+      } else {
+        dest.delim(',', false)?;
+      }
+      matcher.to_css(dest)?;
+    }
     dest.whitespace()?;
     dest.write_char('{')?;
     dest.indent();
@@ -210,29 +210,25 @@ pub enum FontFormat<'i> {
   String(CowArcStr<'i>),
 }
 
-impl<'i> Parse<'i> for FontFormat<'i> {
-  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
-    let s = input.expect_ident_or_string()?;
-    match_ignore_ascii_case! { &s,
-      "woff" => Ok(FontFormat::WOFF),
-      "woff2" => Ok(FontFormat::WOFF2),
-      "truetype" => Ok(FontFormat::TrueType),
-      "opentype" => Ok(FontFormat::OpenType),
-      "embedded-opentype" => Ok(FontFormat::EmbeddedOpenType),
-      "collection" => Ok(FontFormat::Collection),
-      "svg" => Ok(FontFormat::SVG),
-      _ => Ok(FontFormat::String(s.into()))
-    }
+impl<'i> FontFormat<'i> {
+  /// Returns the font format for the given keyword, if it is a known format.
+  pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+    Some(match_ignore_ascii_case! { s,
+      "woff" => FontFormat::WOFF,
+      "woff2" => FontFormat::WOFF2,
+      "truetype" => FontFormat::TrueType,
+      "opentype" => FontFormat::OpenType,
+      "embedded-opentype" => FontFormat::EmbeddedOpenType,
+      "collection" => FontFormat::Collection,
+      "svg" => FontFormat::SVG,
+      _ => return None
+    })
   }
-}
 
-impl<'i> ToCss for FontFormat<'i> {
-  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
-  where
-    W: std::fmt::Write,
-  {
+  /// Returns the keyword for the font format, or the string for an unknown format.
+  pub(crate) fn as_str(&self) -> &str {
     use FontFormat::*;
-    let s = match self {
+    match self {
       WOFF => "woff",
       WOFF2 => "woff2",
       TrueType => "truetype",
@@ -241,10 +237,25 @@ impl<'i> ToCss for FontFormat<'i> {
       Collection => "collection",
       SVG => "svg",
       String(s) => &s,
-    };
+    }
+  }
+}
+
+impl<'i> Parse<'i> for FontFormat<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let s = input.expect_ident_or_string()?;
+    Ok(FontFormat::from_keyword(&s).unwrap_or_else(|| FontFormat::String(s.into())))
+  }
+}
+
+impl<'i> ToCss for FontFormat<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
     // Browser support for keywords rather than strings is very limited.
     // https://developer.mozilla.org/en-US/docs/Web/CSS/@font-face/src
-    serialize_string(&s, dest)?;
+    serialize_string(self.as_str(), dest)?;
     Ok(())
   }
 }
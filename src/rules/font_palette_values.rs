@@ -163,10 +163,10 @@ impl<'i> FontPaletteValuesRule<'i> {
 impl<'i> Parse<'i> for BasePalette {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(i) = input.try_parse(CSSInteger::parse) {
-      if i.is_negative() {
-        return Err(input.new_custom_error(ParserError::InvalidValue));
-      }
-      return Ok(BasePalette::Integer(i as u16));
+      return match u16::try_from(i) {
+        Ok(i) => Ok(BasePalette::Integer(i)),
+        Err(_) => Err(input.new_custom_error(ParserError::InvalidValue)),
+      };
     }
 
     let location = input.current_source_location();
@@ -195,19 +195,14 @@ impl ToCss for BasePalette {
 impl<'i> Parse<'i> for OverrideColors {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let index = CSSInteger::parse(input)?;
-    if index.is_negative() {
-      return Err(input.new_custom_error(ParserError::InvalidValue));
-    }
+    let index = u16::try_from(index).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
 
     let color = CssColor::parse(input)?;
     if matches!(color, CssColor::CurrentColor) {
       return Err(input.new_custom_error(ParserError::InvalidValue));
     }
 
-    Ok(OverrideColors {
-      index: index as u16,
-      color,
-    })
+    Ok(OverrideColors { index, color })
   }
 }
 
@@ -233,6 +228,16 @@ impl OverrideColors {
 
 impl<'i> FontPaletteValuesRule<'i> {
   pub(crate) fn minify(&mut self, context: &mut MinifyContext<'_, 'i>, _: bool) {
+    // Later descriptors override earlier ones with the same name, so only the last one is kept.
+    let mut deduped: Vec<FontPaletteValuesProperty<'i>> = Vec::with_capacity(self.properties.len());
+    for property in self.properties.drain(..).rev() {
+      if !deduped.iter().any(|p| p.name().eq_ignore_ascii_case(property.name())) {
+        deduped.push(property);
+      }
+    }
+    deduped.reverse();
+    self.properties = deduped;
+
     let mut properties = Vec::with_capacity(self.properties.len());
     for property in &self.properties {
       match property {
@@ -257,7 +262,7 @@ impl<'i> FontPaletteValuesRule<'i> {
             }
 
             let override_colors = if fallbacks.contains(ColorFallbackKind::LAB) {
-              override_colors.iter().map(|o| o.get_fallback(ColorFallbackKind::P3)).collect()
+              override_colors.iter().map(|o| o.get_fallback(ColorFallbackKind::LAB)).collect()
             } else {
               override_colors.clone()
             };
@@ -365,6 +370,18 @@ impl<'i> ToCss for FontPaletteValuesRule<'i> {
   }
 }
 
+impl<'i> FontPaletteValuesProperty<'i> {
+  /// Returns the name of the descriptor.
+  fn name(&self) -> &str {
+    match self {
+      FontPaletteValuesProperty::FontFamily(..) => "font-family",
+      FontPaletteValuesProperty::BasePalette(..) => "base-palette",
+      FontPaletteValuesProperty::OverrideColors(..) => "override-colors",
+      FontPaletteValuesProperty::Custom(custom) => custom.name.as_ref(),
+    }
+  }
+}
+
 impl<'i> ToCss for FontPaletteValuesProperty<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
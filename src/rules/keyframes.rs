@@ -14,6 +14,7 @@ use crate::targets::Browsers;
 use crate::traits::{Parse, ToCss};
 use crate::values::color::ColorFallbackKind;
 use crate::values::ident::CustomIdent;
+use crate::values::number::CSSNumber;
 use crate::values::percentage::Percentage;
 use crate::values::string::CowArcStr;
 use crate::vendor_prefix::VendorPrefix;
@@ -125,6 +126,22 @@ impl<'i> KeyframesRule<'i> {
     context.handler_context.context = DeclarationContext::None;
   }
 
+  /// Returns whether the rule has the same vendor prefix and keyframes as another rule,
+  /// treating `from` and `to` as equal to `0%` and `100%`.
+  pub(crate) fn has_same_keyframes(&self, other: &KeyframesRule<'i>) -> bool {
+    self.vendor_prefix == other.vendor_prefix
+      && self.keyframes.len() == other.keyframes.len()
+      && self.keyframes.iter().zip(&other.keyframes).all(|(a, b)| {
+        a.declarations == b.declarations
+          && a.selectors.len() == b.selectors.len()
+          && a
+            .selectors
+            .iter()
+            .zip(&b.selectors)
+            .all(|(a, b)| a.percentage() == b.percentage())
+      })
+  }
+
   pub(crate) fn get_fallbacks<T>(&mut self, targets: Browsers) -> Vec<CssRule<'i, T>> {
     let mut fallbacks = ColorFallbackKind::empty();
     for keyframe in &self.keyframes {
@@ -193,6 +210,10 @@ impl<'i> KeyframesRule<'i> {
               _ => property.clone(),
             })
             .collect(),
+          #[cfg(feature = "comments")]
+          comments: keyframe.declarations.comments.clone(),
+          #[cfg(feature = "spans")]
+          spans: keyframe.declarations.spans.clone(),
         },
       })
       .collect();
@@ -281,6 +302,16 @@ pub enum KeyframeSelector {
   To,
 }
 
+impl KeyframeSelector {
+  fn percentage(&self) -> CSSNumber {
+    match self {
+      KeyframeSelector::Percentage(p) => p.0,
+      KeyframeSelector::From => 0.0,
+      KeyframeSelector::To => 1.0,
+    }
+  }
+}
+
 impl<'i> Parse<'i> for KeyframeSelector {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(Percentage::parse) {
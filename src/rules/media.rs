@@ -38,6 +38,18 @@ impl<'i, T> MediaRule<'i, T> {
       self.query.transform_custom_media(self.loc, custom_media)?;
     }
 
+    if let Some(targets) = context.targets {
+      if context.legacy_high_contrast
+        && (targets.ie.is_some() || matches!(targets.edge, Some(version) if version < 79 << 16))
+      {
+        self.query.add_high_contrast_fallbacks();
+      }
+
+      self.query.remove_unsupported_queries(*targets);
+    }
+
+    self.query.simplify();
+
     Ok(self.rules.0.is_empty() || self.query.never_matches())
   }
 }
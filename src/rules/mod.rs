@@ -36,8 +36,10 @@
 
 #![deny(missing_docs)]
 
+pub mod comment;
 pub mod container;
 pub mod counter_style;
+pub mod custom_at_rule;
 pub mod custom_media;
 pub mod document;
 pub mod font_face;
@@ -47,28 +49,53 @@ pub mod keyframes;
 pub mod layer;
 pub mod media;
 pub mod namespace;
+pub mod nested_declarations;
 pub mod nesting;
 pub mod page;
+pub mod position_try;
 pub mod property;
+pub mod scope;
+pub mod starting_style;
 pub mod style;
 pub mod supports;
 pub mod unknown;
 pub mod viewport;
 
-use self::font_palette_values::FontPaletteValuesRule;
-use self::layer::{LayerBlockRule, LayerStatementRule};
-use self::property::PropertyRule;
+use self::font_face::FontFaceProperty;
+use self::font_palette_values::{FontPaletteValuesProperty, FontPaletteValuesRule};
+use self::layer::{LayerBlockRule, LayerName, LayerStatementRule};
+use self::property::{PropertyRegistration, PropertyRule};
+use crate::compat;
 use crate::context::PropertyHandlerContext;
-use crate::declaration::DeclarationHandler;
+use crate::declaration::{DeclarationBlock, DeclarationHandler, DeclarationOrder};
 use crate::dependencies::{Dependency, ImportDependency};
-use crate::error::{MinifyError, ParserError, PrinterError, PrinterErrorKind};
+use crate::error::{ErrorWithLocation, MinifyError, ParserError, PrinterError, PrinterErrorKind};
+use crate::logical::PropertyCategory;
+use crate::media_query::MediaEnvironment;
 use crate::parser::{
   parse_nested_at_rule, DefaultAtRule, DefaultAtRuleParser, NestedRuleParser, TopLevelRuleParser,
 };
 use crate::prefixes::Feature;
-use crate::printer::Printer;
+use crate::printer::{Printer, PrinterOptions};
+use crate::properties::anchor::uses_anchor_positioning;
+use crate::properties::animation::AnimationName;
+use crate::properties::custom::{
+  CustomProperty, CustomPropertyName, EnvironmentVariable, Token, TokenList, TokenOrValue, UnresolvedColor,
+  Variable,
+};
+use crate::properties::font::FontFamily;
+#[cfg(feature = "grid")]
+use crate::properties::grid::uses_masonry;
+use crate::properties::transition::allows_discrete_transitions;
+use crate::properties::ui::sizes_fields_to_content;
+use crate::properties::view_transition::view_transition_feature;
+use crate::properties::{Property, PropertyId};
+use crate::purge::Purge;
 use crate::rules::keyframes::KeyframesName;
-use crate::selector::{downlevel_selectors, get_prefix, is_equivalent, SelectorList};
+use crate::selector::{
+  add_attribute_selector, adjust_specificity, downlevel_selectors, expand_is_selectors, get_necessary_prefixes,
+  get_prefix, is_equivalent, scope_selectors, Component, CustomElementUsage, SelectorList, SpecificityAdjustment,
+};
 use crate::stylesheet::ParserOptions;
 use crate::targets::Browsers;
 use crate::traits::{AtRuleParser, ToCss};
@@ -76,9 +103,11 @@ use crate::values::string::CowArcStr;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
-use container::ContainerRule;
+use comment::CommentRule;
+use container::{ContainerQueryManifest, ContainerRule};
 use counter_style::CounterStyleRule;
 use cssparser::{parse_one_rule, ParseError, Parser, ParserInput};
+use custom_at_rule::{CustomAtRule, CustomAtRuleBody};
 use custom_media::CustomMediaRule;
 use document::MozDocumentRule;
 use font_face::FontFaceRule;
@@ -86,9 +115,13 @@ use import::ImportRule;
 use keyframes::KeyframesRule;
 use media::MediaRule;
 use namespace::NamespaceRule;
+use nested_declarations::NestedDeclarationsRule;
 use nesting::NestingRule;
 use page::PageRule;
-use std::collections::{HashMap, HashSet};
+use position_try::PositionTryRule;
+use scope::ScopeRule;
+use starting_style::StartingStyleRule;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use style::StyleRule;
 use supports::SupportsRule;
 use unknown::UnknownAtRule;
@@ -113,6 +146,10 @@ pub struct Location {
   /// The column number within a line, starting at 1 for first the character of the line.
   /// Column numbers are counted in UTF-16 code units.
   pub column: u32,
+  /// The byte offsets of the rule in the source file.
+  #[cfg(feature = "spans")]
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub span: crate::span::Span,
 }
 
 /// A CSS rule.
@@ -151,6 +188,8 @@ pub enum CssRule<'i, R = DefaultAtRule> {
   MozDocument(MozDocumentRule<'i, R>),
   /// A `@nest` rule.
   Nesting(NestingRule<'i, R>),
+  /// Declarations that follow nested rules within a style rule.
+  NestedDeclarations(NestedDeclarationsRule<'i>),
   /// A `@viewport` rule.
   Viewport(ViewportRule<'i>),
   /// A `@custom-media` rule.
@@ -163,10 +202,20 @@ pub enum CssRule<'i, R = DefaultAtRule> {
   Property(PropertyRule<'i>),
   /// A `@container` rule.
   Container(ContainerRule<'i, R>),
+  /// A `@scope` rule.
+  Scope(ScopeRule<'i, R>),
+  /// A `@starting-style` rule.
+  StartingStyle(StartingStyleRule<'i, R>),
+  /// A `@position-try` rule.
+  PositionTry(PositionTryRule<'i>),
+  /// A comment, if the `preserve_comments` parser option is enabled.
+  Comment(CommentRule<'i>),
   /// A placeholder for a rule that was removed.
   Ignored,
   /// An unknown at-rule.
   Unknown(UnknownAtRule<'i>),
+  /// An at-rule registered with the `custom_at_rules` parser option.
+  CustomAtRule(CustomAtRule<'i, R>),
   /// A custom at-rule.
   Custom(R),
 }
@@ -299,11 +348,35 @@ impl<'i, 'de: 'i, R: serde::Deserialize<'de>> serde::Deserialize<'de> for CssRul
         let rule = ContainerRule::deserialize(deserializer)?;
         Ok(CssRule::Container(rule))
       }
+      "scope" => {
+        let rule = ScopeRule::deserialize(deserializer)?;
+        Ok(CssRule::Scope(rule))
+      }
+      "starting-style" => {
+        let rule = StartingStyleRule::deserialize(deserializer)?;
+        Ok(CssRule::StartingStyle(rule))
+      }
+      "position-try" => {
+        let rule = PositionTryRule::deserialize(deserializer)?;
+        Ok(CssRule::PositionTry(rule))
+      }
+      "comment" => {
+        let rule = CommentRule::deserialize(deserializer)?;
+        Ok(CssRule::Comment(rule))
+      }
+      "nested-declarations" => {
+        let rule = NestedDeclarationsRule::deserialize(deserializer)?;
+        Ok(CssRule::NestedDeclarations(rule))
+      }
       "ignored" => Ok(CssRule::Ignored),
       "unknown" => {
         let rule = UnknownAtRule::deserialize(deserializer)?;
         Ok(CssRule::Unknown(rule))
       }
+      "custom-at-rule" => {
+        let rule = CustomAtRule::deserialize(deserializer)?;
+        Ok(CssRule::CustomAtRule(rule))
+      }
       "custom" => {
         let rule = R::deserialize(deserializer)?;
         Ok(CssRule::Custom(rule))
@@ -337,7 +410,13 @@ impl<'a, 'i, T: ToCss> ToCss for CssRule<'i, T> {
       CssRule::LayerBlock(layer) => layer.to_css(dest),
       CssRule::Property(property) => property.to_css(dest),
       CssRule::Container(container) => container.to_css(dest),
+      CssRule::Scope(scope) => scope.to_css(dest),
+      CssRule::StartingStyle(rule) => rule.to_css(dest),
+      CssRule::PositionTry(rule) => rule.to_css(dest),
+      CssRule::Comment(rule) => rule.to_css(dest),
+      CssRule::NestedDeclarations(rule) => rule.to_css(dest),
       CssRule::Unknown(unknown) => unknown.to_css(dest),
+      CssRule::CustomAtRule(rule) => rule.to_css(dest),
       CssRule::Custom(rule) => rule.to_css(dest).map_err(|_| PrinterError {
         kind: PrinterErrorKind::FmtError,
         loc: None,
@@ -386,6 +465,71 @@ impl<'i, T> CssRule<'i, T> {
     let mut parser = Parser::new(&mut input);
     Self::parse_with(&mut parser, &options, at_rule_parser)
   }
+
+  /// Returns the location of the rule in the source file, if known.
+  pub(crate) fn loc(&self) -> Option<Location> {
+    Some(match self {
+      CssRule::Media(rule) => rule.loc,
+      CssRule::Import(rule) => rule.loc,
+      CssRule::Style(rule) => rule.loc,
+      CssRule::Keyframes(rule) => rule.loc,
+      CssRule::FontFace(rule) => rule.loc,
+      CssRule::FontPaletteValues(rule) => rule.loc,
+      CssRule::Page(rule) => rule.loc,
+      CssRule::Supports(rule) => rule.loc,
+      CssRule::CounterStyle(rule) => rule.loc,
+      CssRule::Namespace(rule) => rule.loc,
+      CssRule::MozDocument(rule) => rule.loc,
+      CssRule::Nesting(rule) => rule.loc,
+      CssRule::NestedDeclarations(rule) => rule.loc,
+      CssRule::Viewport(rule) => rule.loc,
+      CssRule::CustomMedia(rule) => rule.loc,
+      CssRule::LayerStatement(rule) => rule.loc,
+      CssRule::LayerBlock(rule) => rule.loc,
+      CssRule::Property(rule) => rule.loc,
+      CssRule::Container(rule) => rule.loc,
+      CssRule::Scope(rule) => rule.loc,
+      CssRule::StartingStyle(rule) => rule.loc,
+      CssRule::PositionTry(rule) => rule.loc,
+      CssRule::Comment(rule) => rule.loc,
+      CssRule::Unknown(rule) => rule.loc,
+      CssRule::CustomAtRule(rule) => rule.loc,
+      CssRule::Ignored | CssRule::Custom(..) => return None,
+    })
+  }
+
+  /// Returns a mutable reference to the location of the rule in the source file, if known.
+  #[cfg(feature = "spans")]
+  pub(crate) fn loc_mut(&mut self) -> Option<&mut Location> {
+    Some(match self {
+      CssRule::Media(rule) => &mut rule.loc,
+      CssRule::Import(rule) => &mut rule.loc,
+      CssRule::Style(rule) => &mut rule.loc,
+      CssRule::Keyframes(rule) => &mut rule.loc,
+      CssRule::FontFace(rule) => &mut rule.loc,
+      CssRule::FontPaletteValues(rule) => &mut rule.loc,
+      CssRule::Page(rule) => &mut rule.loc,
+      CssRule::Supports(rule) => &mut rule.loc,
+      CssRule::CounterStyle(rule) => &mut rule.loc,
+      CssRule::Namespace(rule) => &mut rule.loc,
+      CssRule::MozDocument(rule) => &mut rule.loc,
+      CssRule::Nesting(rule) => &mut rule.loc,
+      CssRule::NestedDeclarations(rule) => &mut rule.loc,
+      CssRule::Viewport(rule) => &mut rule.loc,
+      CssRule::CustomMedia(rule) => &mut rule.loc,
+      CssRule::LayerStatement(rule) => &mut rule.loc,
+      CssRule::LayerBlock(rule) => &mut rule.loc,
+      CssRule::Property(rule) => &mut rule.loc,
+      CssRule::Container(rule) => &mut rule.loc,
+      CssRule::Scope(rule) => &mut rule.loc,
+      CssRule::StartingStyle(rule) => &mut rule.loc,
+      CssRule::PositionTry(rule) => &mut rule.loc,
+      CssRule::Comment(rule) => &mut rule.loc,
+      CssRule::Unknown(rule) => &mut rule.loc,
+      CssRule::CustomAtRule(rule) => &mut rule.loc,
+      CssRule::Ignored | CssRule::Custom(..) => return None,
+    })
+  }
 }
 
 /// A list of CSS rules.
@@ -465,8 +609,22 @@ pub(crate) struct MinifyContext<'a, 'i> {
   pub important_handler: &'a mut DeclarationHandler<'i>,
   pub handler_context: &'a mut PropertyHandlerContext<'i, 'a>,
   pub unused_symbols: &'a HashSet<String>,
+  pub purge: Option<&'a Purge>,
   pub custom_media: Option<HashMap<CowArcStr<'i>, CustomMediaRule<'i>>>,
   pub css_modules: bool,
+  pub remove_unsupported_has: bool,
+  pub remove_shadowed_declarations: bool,
+  pub merge_media_rules: bool,
+  pub merge_non_adjacent_rules: bool,
+  pub unsafe_merge_non_adjacent_rules: bool,
+  pub merge_same_selector_rules: bool,
+  pub normalize_attribute_selectors: bool,
+  pub expand_is_selectors: bool,
+  pub media_environment: Option<&'a MediaEnvironment>,
+  pub legacy_high_contrast: bool,
+  pub preserve_empty_rules: bool,
+  pub namespaces: Option<HashSet<CowArcStr<'i>>>,
+  pub warnings: Vec<ErrorWithLocation<ParserError<'i>>>,
 }
 
 impl<'i, T> CssRuleList<'i, T> {
@@ -477,6 +635,7 @@ impl<'i, T> CssRuleList<'i, T> {
   ) -> Result<(), MinifyError> {
     let mut keyframe_rules = HashMap::new();
     let mut layer_rules = HashMap::new();
+    let mut declared_layers = HashSet::new();
     let mut rules = Vec::new();
     for mut rule in self.0.drain(..) {
       match &mut rule {
@@ -527,6 +686,18 @@ impl<'i, T> CssRuleList<'i, T> {
           }
         }
         CssRule::Media(media) => {
+          if let Some(environment) = context.media_environment {
+            match media.query.evaluate(environment) {
+              Some(false) => continue,
+              Some(true) => {
+                media.rules.minify(context, parent_is_unused)?;
+                rules.append(&mut media.rules.0);
+                continue;
+              }
+              None => {}
+            }
+          }
+
           if let Some(CssRule::Media(last_rule)) = rules.last_mut() {
             if last_rule.query == media.query {
               last_rule.rules.0.extend(media.rules.0.drain(..));
@@ -540,6 +711,20 @@ impl<'i, T> CssRuleList<'i, T> {
           }
         }
         CssRule::Supports(supports) => {
+          if let Some(targets) = context.targets {
+            match supports.condition.evaluate(*targets) {
+              // The condition never matches in any of the targets, so the rule is dead code.
+              Some(false) => continue,
+              // The condition always matches, so the rule can be replaced with its contents.
+              Some(true) => {
+                supports.rules.minify(context, parent_is_unused)?;
+                rules.append(&mut supports.rules.0);
+                continue;
+              }
+              None => {}
+            }
+          }
+
           if let Some(CssRule::Supports(last_rule)) = rules.last_mut() {
             if last_rule.condition == supports.condition {
               last_rule.rules.0.extend(supports.rules.0.drain(..));
@@ -566,6 +751,54 @@ impl<'i, T> CssRuleList<'i, T> {
             continue;
           }
         }
+        CssRule::Scope(scope) => {
+          if let Some(targets) = context.targets {
+            // Hoist the rules out of the @scope rule, using descendant selectors to scope them instead.
+            if !compat::Feature::AtScope.is_compatible(*targets) && scope.downlevel() {
+              if scope.scope_end.is_some() {
+                context.warnings.push(ErrorWithLocation {
+                  kind: ParserError::UnsupportedScopeLimit,
+                  loc: scope.loc,
+                });
+              }
+              scope.rules.minify(context, parent_is_unused)?;
+              rules.append(&mut scope.rules.0);
+              continue;
+            }
+          }
+
+          if scope.minify(context, parent_is_unused)? {
+            continue;
+          }
+        }
+        CssRule::StartingStyle(starting_style) => {
+          // Browsers that don't support @starting-style ignore it, so elements will appear
+          // without transitioning from the starting styles.
+          if let Some(targets) = context.targets {
+            if !compat::Feature::AtStartingStyle.is_compatible(*targets) {
+              context.warnings.push(ErrorWithLocation {
+                kind: ParserError::UnsupportedStartingStyle,
+                loc: starting_style.loc,
+              });
+            }
+          }
+
+          if starting_style.minify(context, parent_is_unused)? {
+            continue;
+          }
+        }
+        CssRule::LayerStatement(layer) => {
+          // Layers are ordered by where they are first declared, so declaring a layer again has no effect.
+          layer.names.retain(|name| declare_layer(&mut declared_layers, name));
+          if layer.names.is_empty() {
+            continue;
+          }
+
+          if let Some(CssRule::LayerStatement(last_rule)) = rules.last_mut() {
+            last_rule.names.append(&mut layer.names);
+            continue;
+          }
+        }
         CssRule::LayerBlock(layer) => {
           // Merging non-adjacent layer rules is safe because they are applied
           // in the order they are first defined.
@@ -578,19 +811,97 @@ impl<'i, T> CssRuleList<'i, T> {
               }
             }
 
+            let name = name.clone();
+            let is_new = declare_layer(&mut declared_layers, &name);
             layer_rules.insert(name.clone(), rules.len());
+            if layer.minify(context, parent_is_unused)? {
+              layer_rules.remove(&name);
+              // An empty layer block still establishes the order of the layer, so keep it as a
+              // statement unless the layer was already declared.
+              if is_new {
+                if let Some(CssRule::LayerStatement(last_rule)) = rules.last_mut() {
+                  last_rule.names.push(name);
+                } else {
+                  rules.push(CssRule::LayerStatement(LayerStatementRule {
+                    names: vec![name],
+                    loc: layer.loc,
+                  }));
+                }
+              }
+              continue;
+            }
+          } else if layer.minify(context, parent_is_unused)? {
+            continue;
+          }
+        }
+        CssRule::FontFace(font_face) => {
+          if font_face.properties.is_empty() {
+            continue;
           }
-          if layer.minify(context, parent_is_unused)? {
+        }
+        CssRule::Page(page) => {
+          page.minify(context);
+          if page.is_empty() {
             continue;
           }
         }
         CssRule::MozDocument(document) => document.minify(context)?,
         CssRule::Style(style) => {
+          if let Some(targets) = context.targets {
+            if !compat::Feature::TransitionBehavior.is_compatible(*targets)
+              && allows_discrete_transitions(&style.declarations)
+            {
+              context.warnings.push(ErrorWithLocation {
+                kind: ParserError::UnsupportedAllowDiscrete,
+                loc: style.loc,
+              });
+            }
+
+            if !compat::Feature::AnchorPositioning.is_compatible(*targets)
+              && uses_anchor_positioning(&style.declarations)
+            {
+              context.warnings.push(ErrorWithLocation {
+                kind: ParserError::UnsupportedAnchorPositioning,
+                loc: style.loc,
+              });
+            }
+
+            if let Some(feature) = view_transition_feature(&style.declarations) {
+              if !feature.is_compatible(*targets) {
+                context.warnings.push(ErrorWithLocation {
+                  kind: ParserError::UnsupportedViewTransition,
+                  loc: style.loc,
+                });
+              }
+            }
+
+            if !compat::Feature::FieldSizing.is_compatible(*targets)
+              && sizes_fields_to_content(&style.declarations)
+            {
+              context.warnings.push(ErrorWithLocation {
+                kind: ParserError::UnsupportedFieldSizing,
+                loc: style.loc,
+              });
+            }
+
+            #[cfg(feature = "grid")]
+            if !compat::Feature::Masonry.is_compatible(*targets) && uses_masonry(&style.declarations) {
+              context.warnings.push(ErrorWithLocation {
+                kind: ParserError::UnsupportedMasonry,
+                loc: style.loc,
+              });
+            }
+          }
+
           if parent_is_unused || style.minify(context, parent_is_unused)? {
             continue;
           }
 
           if let Some(targets) = context.targets {
+            if context.expand_is_selectors && !compat::Feature::CssMatchesPseudo.is_compatible(*targets) {
+              expand_is_selectors(&mut style.selectors);
+            }
+
             style.vendor_prefix = get_prefix(&style.selectors);
             if style.vendor_prefix.contains(VendorPrefix::None) {
               style.vendor_prefix = downlevel_selectors(style.selectors.0.as_mut_slice(), *targets);
@@ -623,7 +934,8 @@ impl<'i, T> CssRuleList<'i, T> {
 
           let supports = context.handler_context.get_supports_rules(&style);
           let logical = context.handler_context.get_logical_rules(&style);
-          if !merged && !style.is_empty() {
+          let color_fallbacks = context.handler_context.get_color_fallback_rules(style);
+          if !merged && (!style.is_empty() || context.preserve_empty_rules) {
             rules.push(rule);
           }
 
@@ -633,6 +945,7 @@ impl<'i, T> CssRuleList<'i, T> {
             rules.extend(logical.0)
           }
 
+          rules.extend(color_fallbacks);
           rules.extend(supports);
           continue;
         }
@@ -640,6 +953,7 @@ impl<'i, T> CssRuleList<'i, T> {
           if context.unused_symbols.contains(counter_style.name.0.as_ref()) {
             continue;
           }
+          counter_style.minify();
         }
         CssRule::Nesting(nesting) => {
           if nesting.minify(context, parent_is_unused)? {
@@ -665,122 +979,1686 @@ impl<'i, T> CssRuleList<'i, T> {
             continue;
           }
         }
+        CssRule::PositionTry(position_try) => {
+          if context.unused_symbols.contains(position_try.name.0.as_ref()) {
+            continue;
+          }
+
+          position_try.minify(context);
+        }
+        CssRule::CustomAtRule(rule) => rule.minify(context, parent_is_unused)?,
+        CssRule::NestedDeclarations(nested) => {
+          if nested.minify(context, parent_is_unused) {
+            continue;
+          }
+        }
         _ => {}
       }
 
       rules.push(rule)
     }
 
+    if context.merge_media_rules {
+      merge_media_rules(&mut rules, context, parent_is_unused)?;
+    }
+
+    if context.merge_same_selector_rules {
+      merge_same_selector_rules(&mut rules, context);
+    }
+
+    if context.merge_non_adjacent_rules {
+      merge_non_adjacent_style_rules(&mut rules, context);
+    }
+
+    if context.remove_shadowed_declarations {
+      remove_shadowed_declarations(&mut rules, context);
+    }
+
     self.0 = rules;
     Ok(())
   }
 }
 
-fn merge_style_rules<'i, T>(
-  style: &mut StyleRule<'i, T>,
-  last_style_rule: &mut StyleRule<'i, T>,
+/// Adds a layer name and its parent layers to the set of declared layers.
+/// Returns whether the layer itself was not already declared.
+fn declare_layer<'i>(declared: &mut HashSet<LayerName<'i>>, name: &LayerName<'i>) -> bool {
+  for len in 1..name.0.len() {
+    declared.insert(LayerName(name.0[..len].into()));
+  }
+  declared.insert(name.clone())
+}
+
+/// Merges `@media` rules into an earlier `@media` rule with an identical query, when this
+/// does not change the cascade. Moving the contents of a rule earlier only matters when a rule in
+/// between could set the same property, so merging is skipped whenever any property overlaps.
+fn merge_media_rules<'i, T>(
+  rules: &mut Vec<CssRule<'i, T>>,
   context: &mut MinifyContext<'_, 'i>,
-) -> bool {
-  // Merge declarations if the selectors are equivalent, and both are compatible with all targets.
-  if style.selectors == last_style_rule.selectors
-    && style.is_compatible(*context.targets)
-    && last_style_rule.is_compatible(*context.targets)
-    && style.rules.0.is_empty()
-    && last_style_rule.rules.0.is_empty()
-    && (!context.css_modules || style.loc.source_index == last_style_rule.loc.source_index)
-  {
-    last_style_rule
-      .declarations
-      .declarations
-      .extend(style.declarations.declarations.drain(..));
-    last_style_rule
-      .declarations
-      .important_declarations
-      .extend(style.declarations.important_declarations.drain(..));
-    last_style_rule
-      .declarations
-      .minify(context.handler, context.important_handler, context.handler_context);
-    return true;
-  } else if style.declarations == last_style_rule.declarations
-    && style.rules.0.is_empty()
-    && last_style_rule.rules.0.is_empty()
-  {
-    // Append the selectors to the last rule if the declarations are the same, and all selectors are compatible.
-    if style.is_compatible(*context.targets) && last_style_rule.is_compatible(*context.targets) {
-      last_style_rule.selectors.0.extend(style.selectors.0.drain(..));
-      return true;
-    }
+  parent_is_unused: bool,
+) -> Result<(), MinifyError> {
+  let mut i = 0;
+  while i < rules.len() {
+    let target = match &rules[i] {
+      CssRule::Media(media) => (0..i)
+        .rev()
+        .find(|j| matches!(&rules[*j], CssRule::Media(other) if other.query == media.query))
+        .filter(|j| can_move_media_rule(media, &rules[j + 1..i])),
+      _ => None,
+    };
 
-    // If both selectors are potentially vendor prefixable, and they are
-    // equivalent minus prefixes, add the prefix to the last rule.
-    if !style.vendor_prefix.is_empty()
-      && !last_style_rule.vendor_prefix.is_empty()
-      && !last_style_rule.vendor_prefix.contains(style.vendor_prefix)
-      && is_equivalent(&style.selectors, &last_style_rule.selectors)
-    {
-      // If the new rule is unprefixed, replace the prefixes of the last rule.
-      // Otherwise, add the new prefix.
-      if style.vendor_prefix.contains(VendorPrefix::None) {
-        last_style_rule.vendor_prefix = style.vendor_prefix;
-      } else {
-        last_style_rule.vendor_prefix |= style.vendor_prefix;
+    if let Some(target) = target {
+      if let (CssRule::Media(media), CssRule::Media(target)) = (rules.remove(i), &mut rules[target]) {
+        target.rules.0.extend(media.rules.0);
+        target.minify(context, parent_is_unused)?;
       }
-      return true;
+    } else {
+      i += 1;
     }
   }
-  false
+
+  Ok(())
 }
 
-impl<'a, 'i, T: ToCss> ToCss for CssRuleList<'i, T> {
-  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
-  where
-    W: std::fmt::Write,
-  {
-    let mut first = true;
-    let mut last_without_block = false;
+/// Merges style rules with an identical selector list that are not adjacent, and minifies the combined
+/// declarations so that complementary longhands are collapsed into shorthands. The declarations of one rule
+/// are moved to the position of the other, so rules are only merged when none of the rules in between set any
+/// of the moved properties.
+fn merge_same_selector_rules<'i, T>(rules: &mut Vec<CssRule<'i, T>>, context: &mut MinifyContext<'_, 'i>) {
+  let can_merge = |style: &StyleRule<'i, T>| style.rules.0.is_empty() && style.is_compatible(*context.targets);
+  let can_move = |rule: &CssRule<'i, T>, between: &[CssRule<'i, T>]| {
+    let mut moved = Vec::new();
+    let mut properties = Vec::new();
+    collect_properties(rule, &mut moved, true)
+      && between.iter().all(|rule| collect_properties(rule, &mut properties, false))
+      && !moved.iter().any(|a| properties.iter().any(|b| properties_overlap(a, b)))
+  };
 
-    for rule in &self.0 {
-      if let CssRule::Ignored = &rule {
+  let mut i = 0;
+  while i < rules.len() {
+    let target = match &rules[i] {
+      CssRule::Style(style) if can_merge(style) => (0..i).rev().find(|j| {
+        matches!(&rules[*j], CssRule::Style(other) if other.selectors == style.selectors
+          && other.vendor_prefix == style.vendor_prefix
+          && (!context.css_modules || other.loc.source_index == style.loc.source_index)
+          && can_merge(other))
+      }),
+      _ => None,
+    };
+
+    // Move the later declarations up to the earlier rule if possible, otherwise move the earlier
+    // declarations down to the later rule.
+    let (j, move_up) = match target {
+      Some(j) if can_move(&rules[i], &rules[j + 1..i]) => (j, true),
+      Some(j) if can_move(&rules[j], &rules[j + 1..i]) => (j, false),
+      _ => {
+        i += 1;
         continue;
       }
+    };
 
-      // Skip @import rules if collecting dependencies.
-      if let CssRule::Import(rule) = &rule {
-        if dest.remove_imports {
-          let dep = if dest.dependencies.is_some() {
-            Some(Dependency::Import(ImportDependency::new(&rule, dest.filename())))
-          } else {
-            None
-          };
+    let (earlier, later) = rules.split_at_mut(i);
+    if let (CssRule::Style(first), CssRule::Style(second)) = (&mut earlier[j], &mut later[0]) {
+      let mut declarations = std::mem::take(&mut first.declarations);
+      declarations.append(&mut second.declarations);
+      declarations.minify(context.handler, context.important_handler, context.handler_context);
+      if move_up {
+        first.declarations = declarations;
+      } else {
+        second.declarations = declarations;
+      }
+    }
 
-          if let Some(dependencies) = &mut dest.dependencies {
-            dependencies.push(dep.unwrap());
-            continue;
+    // The next rule to visit is now at index i either way.
+    rules.remove(if move_up { i } else { j });
+  }
+}
+
+/// Merges style rules into an earlier style rule with identical declarations by combining their selectors.
+/// This moves the declarations earlier, so unless the `unsafe_merge_non_adjacent_rules` option is set,
+/// rules are only merged when none of the rules in between set any of the same properties.
+fn merge_non_adjacent_style_rules<'i, T>(rules: &mut Vec<CssRule<'i, T>>, context: &MinifyContext<'_, 'i>) {
+  let can_merge = |style: &StyleRule<'i, T>| style.rules.0.is_empty() && style.is_compatible(*context.targets);
+
+  let mut i = 0;
+  while i < rules.len() {
+    let target = match &rules[i] {
+      CssRule::Style(style) if can_merge(style) => (0..i)
+        .rev()
+        .find(|j| {
+          matches!(&rules[*j], CssRule::Style(other) if other.declarations == style.declarations
+            && other.vendor_prefix == style.vendor_prefix
+            && (!context.css_modules || other.loc.source_index == style.loc.source_index)
+            && can_merge(other))
+        })
+        .filter(|j| {
+          if context.unsafe_merge_non_adjacent_rules {
+            return true;
+          }
+
+          let mut moved = Vec::new();
+          let mut properties = Vec::new();
+          collect_properties(&rules[i], &mut moved, true)
+            && rules[j + 1..i]
+              .iter()
+              .all(|rule| collect_properties(rule, &mut properties, false))
+            && !moved.iter().any(|a| properties.iter().any(|b| properties_overlap(a, b)))
+        }),
+      _ => None,
+    };
+
+    if let Some(target) = target {
+      if let (CssRule::Style(style), CssRule::Style(target)) = (rules.remove(i), &mut rules[target]) {
+        for selector in style.selectors.0 {
+          if !target.selectors.0.contains(&selector) {
+            target.selectors.0.push(selector);
           }
         }
       }
+    } else {
+      i += 1;
+    }
+  }
+}
 
-      if first {
-        first = false;
-      } else {
-        if !dest.minify
-          && !(last_without_block
-            && matches!(
-              rule,
-              CssRule::Import(..) | CssRule::Namespace(..) | CssRule::LayerStatement(..)
-            ))
-        {
-          dest.write_char('\n')?;
+fn can_move_media_rule<'i, T>(media: &MediaRule<'i, T>, between: &[CssRule<'i, T>]) -> bool {
+  let mut moved = Vec::new();
+  if !media.rules.0.iter().all(|rule| collect_properties(rule, &mut moved, true)) {
+    return false;
+  }
+
+  let mut properties = Vec::new();
+  if !between.iter().all(|rule| collect_properties(rule, &mut properties, false)) {
+    return false;
+  }
+
+  !moved.iter().any(|a| properties.iter().any(|b| properties_overlap(a, b)))
+}
+
+/// Collects the properties declared by style rules, including those within nested and conditional rules.
+/// Returns false if a rule's effect on the cascade is unknown. When `strict` is set, only rules that
+/// match elements are allowed, since the order of other rules such as `@keyframes` or `@layer` is significant.
+fn collect_properties<'i, T>(rule: &CssRule<'i, T>, properties: &mut Vec<PropertyId<'i>>, strict: bool) -> bool {
+  let rules = match rule {
+    CssRule::Style(style) => {
+      properties.extend(style.declarations.iter().map(|(property, _)| property.property_id()));
+      &style.rules
+    }
+    CssRule::Nesting(nesting) => {
+      properties.extend(nesting.style.declarations.iter().map(|(property, _)| property.property_id()));
+      &nesting.style.rules
+    }
+    CssRule::NestedDeclarations(nested) => {
+      properties.extend(nested.declarations.iter().map(|(property, _)| property.property_id()));
+      return true;
+    }
+    CssRule::Media(media) => &media.rules,
+    CssRule::Supports(supports) => &supports.rules,
+    CssRule::Container(container) => &container.rules,
+    CssRule::Scope(scope) => &scope.rules,
+    CssRule::StartingStyle(rule) => &rule.rules,
+    CssRule::MozDocument(document) => &document.rules,
+    CssRule::LayerBlock(layer) if !strict => &layer.rules,
+    CssRule::LayerStatement(..) | CssRule::Unknown(..) | CssRule::CustomAtRule(..) | CssRule::Custom(..) => {
+      return false
+    }
+    CssRule::Ignored | CssRule::CustomMedia(..) | CssRule::Comment(..) => return true,
+    _ => return !strict,
+  };
+
+  rules.0.iter().all(|rule| collect_properties(rule, properties, strict))
+}
+
+/// Returns whether two properties may affect the same value, e.g. a shorthand and one of its longhands,
+/// or a logical property and its physical equivalent. Vendor prefixes are ignored.
+fn properties_overlap(a: &PropertyId, b: &PropertyId) -> bool {
+  fn for_each_longhand<F: FnMut(&PropertyId)>(id: &PropertyId, f: &mut F) {
+    match id.longhands() {
+      Some(longhands) => longhands.iter().for_each(|longhand| for_each_longhand(longhand, f)),
+      None => f(id),
+    }
+  }
+
+  if matches!(a, PropertyId::All) || matches!(b, PropertyId::All) {
+    return true;
+  }
+
+  let mut overlap = false;
+  for_each_longhand(a, &mut |a| {
+    for_each_longhand(b, &mut |b| {
+      // Logical properties may map to any physical property in the same group.
+      overlap |= a.name() == b.name()
+        || (a.logical_group().is_some()
+          && a.logical_group() == b.logical_group()
+          && (a.category() == Some(PropertyCategory::Logical) || b.category() == Some(PropertyCategory::Logical)));
+    })
+  });
+  overlap
+}
+
+/// Like [properties_overlap](properties_overlap), but also treats unknown properties as overlapping with any
+/// property other than a custom property, since they may be shorthands that are not supported yet.
+pub(crate) fn declarations_overlap(a: &PropertyId, b: &PropertyId) -> bool {
+  match (a, b) {
+    (PropertyId::Custom(CustomPropertyName::Unknown(..)), PropertyId::Custom(CustomPropertyName::Custom(..)))
+    | (PropertyId::Custom(CustomPropertyName::Custom(..)), PropertyId::Custom(CustomPropertyName::Unknown(..))) => {
+      false
+    }
+    (PropertyId::Custom(CustomPropertyName::Unknown(..)), _)
+    | (_, PropertyId::Custom(CustomPropertyName::Unknown(..))) => true,
+    _ => properties_overlap(a, b),
+  }
+}
+
+/// Returns whether a declaration of the given property is the only one among the declared properties that
+/// sets an overlapping property, so that removing its `!important` cannot change the cascade.
+pub(crate) fn is_uncontested(id: &PropertyId, properties: &[PropertyId]) -> bool {
+  properties.iter().filter(|other| declarations_overlap(id, other)).count() == 1
+}
+
+/// Removes declarations that are overridden by a later style rule in the same list with an identical
+/// selector list, regardless of any rules in between. Since both rules have the same specificity, the
+/// later declaration always wins the cascade unless the earlier one is `!important` and the later one is not.
+/// Rules with selectors that are not compatible with all targets are skipped, because browsers that drop
+/// the later rule would otherwise lose the earlier declarations as well. Likewise, an earlier declaration is
+/// only removed if a later declaration of the same property is compatible with all targets, since otherwise
+/// it may be a fallback, e.g. `width: 100px` before `width: fit-content`.
+fn remove_shadowed_declarations<'i, T>(rules: &mut Vec<CssRule<'i, T>>, context: &MinifyContext<'_, 'i>) {
+  struct Seen<'i> {
+    selectors: SelectorList<'i>,
+    vendor_prefix: VendorPrefix,
+    source_index: u32,
+    properties: Vec<PropertyId<'i>>,
+    important_properties: Vec<PropertyId<'i>>,
+  }
+
+  let targets = *context.targets;
+  let mut seen: Vec<Seen<'i>> = Vec::new();
+  let mut removed = false;
+  for rule in rules.iter_mut().rev() {
+    let style = match rule {
+      CssRule::Style(style) if style.is_compatible(targets) => style,
+      _ => continue,
+    };
+
+    let source_index = if context.css_modules { style.loc.source_index } else { 0 };
+    let entry = seen.iter_mut().find(|s| {
+      s.selectors == style.selectors && s.vendor_prefix == style.vendor_prefix && s.source_index == source_index
+    });
+
+    let declarations = &mut style.declarations;
+    if let Some(entry) = entry {
+      let len = declarations.len();
+      // A normal declaration is overridden by any later declaration of the same property,
+      // but an !important declaration only by a later !important one.
+      declarations.declarations.retain(|property| {
+        let id = property.property_id();
+        !entry.properties.contains(&id) && !entry.important_properties.contains(&id)
+      });
+      declarations
+        .important_declarations
+        .retain(|property| !entry.important_properties.contains(&property.property_id()));
+      removed |= declarations.len() != len;
+
+      entry
+        .properties
+        .extend(shadowing_properties(&declarations.declarations, targets));
+      entry
+        .important_properties
+        .extend(shadowing_properties(&declarations.important_declarations, targets));
+    } else {
+      seen.push(Seen {
+        selectors: style.selectors.clone(),
+        vendor_prefix: style.vendor_prefix,
+        source_index,
+        properties: shadowing_properties(&declarations.declarations, targets).collect(),
+        important_properties: shadowing_properties(&declarations.important_declarations, targets).collect(),
+      });
+    }
+  }
+
+  if removed {
+    rules.retain(|rule| !matches!(rule, CssRule::Style(style) if style.is_empty()));
+  }
+}
+
+/// Returns the ids of the given declarations that can override an earlier declaration of the same property.
+fn shadowing_properties<'a, 'i>(
+  declarations: &'a [Property<'i>],
+  targets: Option<Browsers>,
+) -> impl Iterator<Item = PropertyId<'i>> + 'a {
+  declarations
+    .iter()
+    .filter(move |property| is_supported_declaration(property, targets))
+    .map(|property| property.property_id())
+}
+
+/// Returns whether a declaration is known to be supported by all of the targets. There is no compatibility
+/// data for unparsed values or intrinsic size keywords, so these are assumed to be unsupported by some targets.
+fn is_supported_declaration(property: &Property, targets: Option<Browsers>) -> bool {
+  use crate::properties::size::{MaxSize, Size};
+
+  let targets = match targets {
+    Some(targets) => targets,
+    None => return true,
+  };
+
+  let known = match property {
+    Property::Unparsed(..)
+    | Property::Custom(CustomProperty {
+      name: CustomPropertyName::Unknown(..),
+      ..
+    }) => false,
+    Property::Width(size)
+    | Property::Height(size)
+    | Property::MinWidth(size)
+    | Property::MinHeight(size)
+    | Property::BlockSize(size)
+    | Property::InlineSize(size)
+    | Property::MinBlockSize(size)
+    | Property::MinInlineSize(size) => matches!(size, Size::Auto | Size::LengthPercentage(..)),
+    Property::MaxWidth(size)
+    | Property::MaxHeight(size)
+    | Property::MaxBlockSize(size)
+    | Property::MaxInlineSize(size) => matches!(size, MaxSize::None | MaxSize::LengthPercentage(..)),
+    _ => true,
+  };
+
+  known && style::untranspilable_feature(property).map_or(true, |feature| feature.is_compatible(targets))
+}
+
+impl<'i, T> CssRuleList<'i, T> {
+  /// Scopes all style rules in the list, including those within conditional
+  /// group rules such as `@media`, under the given root selector.
+  pub(crate) fn scope(&mut self, scope: &SelectorList<'i>) {
+    for rule in self.0.iter_mut() {
+      match rule {
+        CssRule::Style(style) => scope_selectors(&mut style.selectors, scope),
+        CssRule::Media(media) => media.rules.scope(scope),
+        CssRule::Supports(supports) => supports.rules.scope(scope),
+        CssRule::Container(container) => container.rules.scope(scope),
+        CssRule::Scope(rule) => match &mut rule.scope_start {
+          Some(start) => scope_selectors(start, scope),
+          None => rule.rules.scope(scope),
+        },
+        CssRule::StartingStyle(rule) => rule.rules.scope(scope),
+        CssRule::LayerBlock(layer) => layer.rules.scope(scope),
+        CssRule::MozDocument(document) => document.rules.scope(scope),
+        // Other rules, e.g. @keyframes and @font-face, do not match elements.
+        _ => {}
+      }
+    }
+  }
+
+  /// Returns the registrations of the `@property` rules in the list, including those in nested rules,
+  /// and removes the rules if `remove` is true.
+  pub(crate) fn extract_property_rules(
+    &mut self,
+    remove: bool,
+  ) -> Result<Vec<PropertyRegistration>, PrinterError> {
+    let mut registrations = Vec::new();
+    let mut res = Ok(());
+    self.for_each_rule(&mut |rule| {
+      if let CssRule::Property(property) = rule {
+        match property.registration() {
+          Ok(registration) => registrations.push(registration),
+          Err(err) => res = Err(err),
         }
-        dest.newline()?;
       }
-      rule.to_css(dest)?;
+    });
+    res?;
+
+    if remove {
+      self.retain_rules(&mut |rule| !matches!(rule, CssRule::Property(..)));
+    }
+
+    Ok(registrations)
+  }
+
+  /// Replaces `@container` rules with their contents, adding an attribute selector identifying the
+  /// container query to each style rule within them. The query containers and container queries
+  /// are recorded in the manifest.
+  pub(crate) fn polyfill_container_queries(
+    &mut self,
+    attr: &str,
+    parents: &[SelectorList<'i>],
+    manifest: &mut ContainerQueryManifest,
+  ) -> Result<(), PrinterError> {
+    let mut rules = Vec::with_capacity(self.0.len());
+    for mut rule in self.0.drain(..) {
+      match &mut rule {
+        CssRule::Container(container) => {
+          let id = manifest.add_query(container)?;
+          container.rules.polyfill_container_queries(attr, parents, manifest)?;
+          container.rules.add_attribute_selector(attr, &id);
+          rules.append(&mut container.rules.0);
+          continue;
+        }
+        CssRule::Style(style) => {
+          let mut parents = parents.to_vec();
+          parents.push(style.selectors.clone());
+          manifest.add_container(&style.declarations, &parents)?;
+          style.rules.polyfill_container_queries(attr, &parents, manifest)?;
+        }
+        CssRule::Media(media) => media.rules.polyfill_container_queries(attr, parents, manifest)?,
+        CssRule::Supports(supports) => supports.rules.polyfill_container_queries(attr, parents, manifest)?,
+        CssRule::LayerBlock(layer) => layer.rules.polyfill_container_queries(attr, parents, manifest)?,
+        CssRule::MozDocument(document) => document.rules.polyfill_container_queries(attr, parents, manifest)?,
+        _ => {}
+      }
+      rules.push(rule);
+    }
+    self.0 = rules;
+    Ok(())
+  }
+
+  fn add_attribute_selector(&mut self, attr: &str, value: &str) {
+    for rule in self.0.iter_mut() {
+      match rule {
+        CssRule::Style(style) => {
+          add_attribute_selector(&mut style.selectors, attr, value);
+          style.rules.add_attribute_selector(attr, value);
+        }
+        CssRule::Media(media) => media.rules.add_attribute_selector(attr, value),
+        CssRule::Supports(supports) => supports.rules.add_attribute_selector(attr, value),
+        CssRule::LayerBlock(layer) => layer.rules.add_attribute_selector(attr, value),
+        CssRule::MozDocument(document) => document.rules.add_attribute_selector(attr, value),
+        _ => {}
+      }
+    }
+  }
+
+  /// Adjusts the specificity of all style rules in the list.
+  pub(crate) fn adjust_specificity(&mut self, adjustment: SpecificityAdjustment, nested: bool) {
+    for rule in self.0.iter_mut() {
+      match rule {
+        CssRule::Style(style) => {
+          // Nested rules inherit a raised specificity from their parent via the nesting selector,
+          // but must be zeroed themselves, otherwise their own components still count.
+          if !nested || adjustment == SpecificityAdjustment::Zero {
+            adjust_specificity(&mut style.selectors, adjustment);
+          }
+          style.rules.adjust_specificity(adjustment, true);
+        }
+        CssRule::Media(media) => media.rules.adjust_specificity(adjustment, nested),
+        CssRule::Supports(supports) => supports.rules.adjust_specificity(adjustment, nested),
+        CssRule::Container(container) => container.rules.adjust_specificity(adjustment, nested),
+        CssRule::Scope(scope) => scope.rules.adjust_specificity(adjustment, nested),
+        CssRule::StartingStyle(rule) => rule.rules.adjust_specificity(adjustment, nested),
+        CssRule::LayerBlock(layer) => layer.rules.adjust_specificity(adjustment, nested),
+        CssRule::MozDocument(document) => document.rules.adjust_specificity(adjustment, nested),
+        CssRule::Nesting(nesting) => {
+          if adjustment == SpecificityAdjustment::Zero {
+            adjust_specificity(&mut nesting.style.selectors, adjustment);
+          }
+          nesting.style.rules.adjust_specificity(adjustment, true);
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /// Calls the given function for every rule in the list, including rules nested within other rules.
+  pub(crate) fn for_each_rule<'a, F: FnMut(&'a CssRule<'i, T>)>(&'a self, f: &mut F) {
+    for rule in &self.0 {
+      f(rule);
+      match rule {
+        CssRule::Style(style) => style.rules.for_each_rule(f),
+        CssRule::Media(media) => media.rules.for_each_rule(f),
+        CssRule::Supports(supports) => supports.rules.for_each_rule(f),
+        CssRule::Container(container) => container.rules.for_each_rule(f),
+        CssRule::Scope(scope) => scope.rules.for_each_rule(f),
+        CssRule::StartingStyle(rule) => rule.rules.for_each_rule(f),
+        CssRule::LayerBlock(layer) => layer.rules.for_each_rule(f),
+        CssRule::MozDocument(document) => document.rules.for_each_rule(f),
+        CssRule::Nesting(nesting) => nesting.style.rules.for_each_rule(f),
+        CssRule::CustomAtRule(CustomAtRule {
+          body: Some(CustomAtRuleBody::RuleList(rules)),
+          ..
+        }) => rules.for_each_rule(f),
+        _ => {}
+      }
+    }
+  }
+
+  /// Calls the given function for every declaration block in the list, including those in nested rules.
+  /// Returns `false` if the list contains unknown or custom at-rules, whose contents cannot be analyzed.
+  pub(crate) fn for_each_declaration_block<F: FnMut(&DeclarationBlock<'i>)>(&self, f: &mut F) -> bool {
+    let mut known = true;
+    self.for_each_rule(&mut |rule| match rule {
+      CssRule::Style(style) => f(&style.declarations),
+      CssRule::Nesting(nesting) => f(&nesting.style.declarations),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          f(&keyframe.declarations);
+        }
+      }
+      CssRule::Page(page) => {
+        f(&page.declarations);
+        for margin_rule in &page.rules {
+          f(&margin_rule.declarations);
+        }
+      }
+      CssRule::Viewport(viewport) => f(&viewport.declarations),
+      CssRule::PositionTry(position_try) => f(&position_try.declarations),
+      CssRule::NestedDeclarations(nested) => f(&nested.declarations),
+      CssRule::Unknown(..) | CssRule::CustomAtRule(..) | CssRule::Custom(..) => known = false,
+      _ => {}
+    });
+    known
+  }
+
+  /// Removes rules for which the given function returns `false`, including nested rules
+  /// within conditional group rules, layers, and style rules.
+  fn retain_rules<F: FnMut(&mut CssRule<'i, T>) -> bool>(&mut self, f: &mut F) {
+    self.0.retain_mut(|rule| {
+      match rule {
+        CssRule::Style(style) => style.rules.retain_rules(f),
+        CssRule::Nesting(nesting) => nesting.style.rules.retain_rules(f),
+        // Conditional group rules left empty are removed, as `minify` would have done.
+        CssRule::Media(media) => {
+          media.rules.retain_rules(f);
+          return !media.rules.0.is_empty();
+        }
+        CssRule::Supports(supports) => {
+          supports.rules.retain_rules(f);
+          return !supports.rules.0.is_empty();
+        }
+        CssRule::Container(container) => {
+          container.rules.retain_rules(f);
+          return !container.rules.0.is_empty();
+        }
+        CssRule::Scope(scope) => {
+          scope.rules.retain_rules(f);
+          return !scope.rules.0.is_empty();
+        }
+        CssRule::StartingStyle(rule) => {
+          rule.rules.retain_rules(f);
+          return !rule.rules.0.is_empty();
+        }
+        CssRule::LayerBlock(layer) => layer.rules.retain_rules(f),
+        CssRule::MozDocument(document) => document.rules.retain_rules(f),
+        _ => {}
+      }
+      f(rule)
+    });
+  }
+
+  /// Removes declarations from style rules within top-level `@media` rules that repeat a declaration of the
+  /// nearest earlier top-level style rule with the same selector, e.g. `.a{color:red} @media print{.a{color:red}}`.
+  /// The earlier declaration applies regardless of the media query, so this is safe as long as none of the
+  /// rules in between set an overlapping property and the earlier rule sets no other overlapping property.
+  pub(crate) fn remove_redundant_media_declarations(&mut self) {
+    let mut removals = Vec::new();
+    for (i, rule) in self.0.iter().enumerate() {
+      let media = match rule {
+        CssRule::Media(media) => media,
+        _ => continue,
+      };
+
+      for (k, rule) in media.rules.0.iter().enumerate() {
+        let style = match rule {
+          CssRule::Style(style) => style,
+          _ => continue,
+        };
+
+        let found = self.0[..i].iter().enumerate().rev().find_map(|(j, rule)| match rule {
+          CssRule::Style(other)
+            if other.selectors == style.selectors && other.vendor_prefix == style.vendor_prefix =>
+          {
+            Some((j, other))
+          }
+          _ => None,
+        });
+        let (j, earlier) = match found {
+          Some(found) => found,
+          None => continue,
+        };
+
+        let mut between = Vec::new();
+        if !self.0[j + 1..i]
+          .iter()
+          .chain(media.rules.0[..k].iter())
+          .all(|rule| collect_properties(rule, &mut between, true))
+        {
+          continue;
+        }
+
+        let redundant: Vec<(Property<'i>, bool)> = style
+          .declarations
+          .iter()
+          .filter(|(property, important)| {
+            let id = property.property_id();
+            earlier.declarations.iter().any(|(p, i)| i == *important && p == *property)
+              && earlier
+                .declarations
+                .iter()
+                .filter(|(p, _)| declarations_overlap(&id, &p.property_id()))
+                .count()
+                == 1
+              && !between.iter().any(|other| declarations_overlap(&id, other))
+          })
+          .map(|(property, important)| (property.clone(), important))
+          .collect();
+        if !redundant.is_empty() {
+          removals.push((i, k, redundant));
+        }
+      }
+    }
+
+    for (i, k, redundant) in removals.into_iter().rev() {
+      if let CssRule::Media(media) = &mut self.0[i] {
+        if let CssRule::Style(style) = &mut media.rules.0[k] {
+          let declarations = &mut style.declarations;
+          declarations
+            .declarations
+            .retain(|property| !redundant.contains(&(property.clone(), false)));
+          declarations
+            .important_declarations
+            .retain(|property| !redundant.contains(&(property.clone(), true)));
+          if style.is_empty() {
+            media.rules.0.remove(k);
+          }
+        }
+        if media.rules.0.is_empty() {
+          self.0.remove(i);
+        }
+      }
+    }
+  }
+
+  /// Returns the ids of the properties of every declaration in the list, including those in nested rules
+  /// and `@keyframes`, or `None` if the list contains rules whose contents cannot be analyzed.
+  pub(crate) fn declared_properties(&self) -> Option<Vec<PropertyId<'i>>> {
+    let mut properties = Vec::new();
+    if self.for_each_declaration_block(&mut |block| {
+      properties.extend(block.iter().map(|(property, _)| property.property_id()))
+    }) {
+      Some(properties)
+    } else {
+      None
+    }
+  }
+
+  /// Converts `!important` declarations in style rules to normal declarations when no other declaration in
+  /// the list sets an overlapping property, including within `@keyframes`, since important declarations also
+  /// override animations.
+  pub(crate) fn remove_unneeded_important(&mut self) {
+    let properties = match self.declared_properties() {
+      Some(properties) => properties,
+      None => return,
+    };
+
+    let is_unique = |property: &Property<'i>| is_uncontested(&property.property_id(), &properties);
+
+    self.retain_rules(&mut |rule| {
+      let declarations = match rule {
+        CssRule::Style(style) => &mut style.declarations,
+        CssRule::Nesting(nesting) => &mut nesting.style.declarations,
+        CssRule::NestedDeclarations(nested) => &mut nested.declarations,
+        _ => return true,
+      };
+
+      let (mut unique, important) = std::mem::take(&mut declarations.important_declarations)
+        .into_iter()
+        .partition(|property| is_unique(property));
+      declarations.important_declarations = important;
+      declarations.declarations.append(&mut unique);
+      true
+    });
+  }
+
+  /// Sorts the declarations of every style rule in the list, including nested rules.
+  pub(crate) fn sort_declarations(&mut self, order: DeclarationOrder) {
+    self.retain_rules(&mut |rule| {
+      match rule {
+        CssRule::Style(style) => style.declarations.sort(order),
+        CssRule::Nesting(nesting) => nesting.style.declarations.sort(order),
+        CssRule::NestedDeclarations(nested) => nested.declarations.sort(order),
+        _ => {}
+      }
+      true
+    });
+  }
+
+  /// Removes vendor prefixed declarations, `@keyframes` rules, and style rules with prefixed selectors
+  /// that are followed by an unprefixed equivalent, when none of the targets need the prefix anymore.
+  pub(crate) fn remove_obsolete_prefixes(&mut self, targets: Browsers) {
+    for rule in &mut self.0 {
+      match rule {
+        CssRule::Style(style) => {
+          style.declarations.remove_obsolete_prefixes(targets);
+          style.rules.remove_obsolete_prefixes(targets);
+        }
+        CssRule::Nesting(nesting) => {
+          nesting.style.declarations.remove_obsolete_prefixes(targets);
+          nesting.style.rules.remove_obsolete_prefixes(targets);
+        }
+        CssRule::NestedDeclarations(nested) => nested.declarations.remove_obsolete_prefixes(targets),
+        CssRule::Keyframes(keyframes) => {
+          for keyframe in &mut keyframes.keyframes {
+            keyframe.declarations.remove_obsolete_prefixes(targets);
+          }
+        }
+        CssRule::Media(media) => media.rules.remove_obsolete_prefixes(targets),
+        CssRule::Supports(supports) => supports.rules.remove_obsolete_prefixes(targets),
+        CssRule::Container(container) => container.rules.remove_obsolete_prefixes(targets),
+        CssRule::Scope(scope) => scope.rules.remove_obsolete_prefixes(targets),
+        CssRule::StartingStyle(rule) => rule.rules.remove_obsolete_prefixes(targets),
+        CssRule::LayerBlock(layer) => layer.rules.remove_obsolete_prefixes(targets),
+        CssRule::MozDocument(document) => document.rules.remove_obsolete_prefixes(targets),
+        _ => {}
+      }
+    }
+
+    let mut i = 0;
+    while i < self.0.len() {
+      if is_obsolete_prefixed_rule(&self.0[i], &self.0[i + 1..], targets) {
+        self.0.remove(i);
+      } else {
+        i += 1;
+      }
+    }
+  }
+
+  /// Replaces top-level anonymous `@layer` blocks with their contents when none of the properties they set
+  /// are set anywhere else in the list. Unlayered rules win over layered rules, so this only preserves the
+  /// cascade when no other style sheet in the document sets the same properties.
+  pub(crate) fn flatten_anonymous_layers(&mut self) {
+    let mut i = 0;
+    while i < self.0.len() {
+      let can_flatten = match &self.0[i] {
+        CssRule::LayerBlock(LayerBlockRule { name: None, rules, .. }) => {
+          let mut moved = Vec::new();
+          let mut properties = Vec::new();
+          rules.0.iter().all(|rule| collect_properties(rule, &mut moved, true))
+            && self.0.iter().enumerate().filter(|(j, _)| *j != i).all(|(_, rule)| match rule {
+              CssRule::LayerStatement(..) => true,
+              CssRule::LayerBlock(layer) => layer
+                .rules
+                .0
+                .iter()
+                .all(|rule| collect_properties(rule, &mut properties, false)),
+              rule => collect_properties(rule, &mut properties, false),
+            })
+            && !moved.iter().any(|a| properties.iter().any(|b| properties_overlap(a, b)))
+        }
+        _ => false,
+      };
+
+      if can_flatten {
+        if let CssRule::LayerBlock(layer) = self.0.remove(i) {
+          let len = layer.rules.0.len();
+          self.0.splice(i..i, layer.rules.0);
+          i += len;
+        }
+      } else {
+        i += 1;
+      }
+    }
+  }
+
+  /// Removes `@font-face` rules whose font family is never referenced by a declaration, and
+  /// returns the removed font family names.
+  pub(crate) fn remove_unused_font_faces(&mut self) -> Vec<String> {
+    let mut families = HashSet::new();
+    let mut tokens = HashSet::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        match property {
+          Property::FontFamily(list) => collect_family_names(list, &mut families),
+          Property::Font(font) => collect_family_names(&font.family, &mut families),
+          Property::Unparsed(unparsed) => collect_token_values(&unparsed.value, &mut tokens),
+          Property::Custom(custom) => collect_token_values(&custom.value, &mut tokens),
+          _ => {}
+        }
+      }
+    });
+
+    let mut removed = Vec::new();
+    if !known {
+      return removed;
+    }
+
+    self.for_each_rule(&mut |rule| {
+      if let CssRule::FontPaletteValues(palette) = rule {
+        for property in &palette.properties {
+          if let FontPaletteValuesProperty::FontFamily(FontFamily::FamilyName(name)) = property {
+            families.insert(name.to_ascii_lowercase());
+          }
+        }
+      }
+    });
+
+    // Fonts referenced from custom properties and unparsed values are only known as a list of tokens,
+    // so a multi-word family name is considered used if all of its words appear.
+    let words: HashSet<String> = tokens
+      .iter()
+      .flat_map(|token| token.split_whitespace())
+      .map(|word| word.to_ascii_lowercase())
+      .collect();
+    let is_used = |name: &str| {
+      let name = name.to_ascii_lowercase();
+      families.contains(&name) || name.split_whitespace().all(|word| words.contains(word))
+    };
+
+    self.retain_rules(&mut |rule| {
+      if let CssRule::FontFace(font_face) = rule {
+        for property in &font_face.properties {
+          if let FontFaceProperty::FontFamily(FontFamily::FamilyName(name)) = property {
+            if !is_used(name) {
+              if !removed.iter().any(|r| r == name.as_ref()) {
+                removed.push(name.to_string());
+              }
+              return false;
+            }
+          }
+        }
+      }
+      true
+    });
+    removed
+  }
+
+  /// Removes items from `transition` declarations for properties that are never set by a declaration in
+  /// the list, since their value can never change. Declarations where no items would remain are left as is.
+  pub(crate) fn remove_unused_transitions(&mut self) {
+    let mut declared: Vec<PropertyId<'i>> = Vec::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        let id = property.property_id();
+        let is_transition = matches!(
+          id,
+          PropertyId::Transition(..)
+            | PropertyId::TransitionProperty(..)
+            | PropertyId::TransitionDuration(..)
+            | PropertyId::TransitionDelay(..)
+            | PropertyId::TransitionTimingFunction(..)
+        );
+        if !is_transition && !declared.contains(&id) {
+          declared.push(id);
+        }
+      }
+    });
+
+    if !known {
+      return;
+    }
+
+    let is_used = |id: &PropertyId| declared.iter().any(|other| declarations_overlap(id, other));
+    let update = |declarations: &mut DeclarationBlock<'i>| {
+      for property in declarations
+        .declarations
+        .iter_mut()
+        .chain(declarations.important_declarations.iter_mut())
+      {
+        if let Property::Transition(transitions, _) = property {
+          if transitions.iter().any(|t| is_used(&t.property)) {
+            transitions.retain(|t| is_used(&t.property));
+          }
+        }
+      }
+    };
+
+    self.retain_rules(&mut |rule| {
+      match rule {
+        CssRule::Style(style) => update(&mut style.declarations),
+        CssRule::Nesting(nesting) => update(&mut nesting.style.declarations),
+        CssRule::NestedDeclarations(nested) => update(&mut nested.declarations),
+        _ => {}
+      }
+      true
+    });
+  }
+
+  /// Removes custom property declarations and `@property` rules for properties that are never
+  /// read by a `var()`, except those in `preserved`.
+  pub(crate) fn remove_unused_custom_properties(&mut self, preserved: &HashSet<String>) {
+    // Custom properties may reference each other, so track which properties each one reads,
+    // and only follow those references once the property itself is known to be read.
+    let mut reads = HashSet::new();
+    let mut dependencies: HashMap<String, HashSet<String>> = HashMap::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        match property {
+          Property::Custom(CustomProperty {
+            name: CustomPropertyName::Custom(name),
+            value,
+          }) => collect_variable_references(value, dependencies.entry(name.0.to_string()).or_default()),
+          Property::Custom(custom) => collect_variable_references(&custom.value, &mut reads),
+          Property::Unparsed(unparsed) => collect_variable_references(&unparsed.value, &mut reads),
+          // Registered custom properties may be referenced by name in transitions.
+          Property::TransitionProperty(properties, _) => {
+            for property in properties {
+              if let PropertyId::Custom(CustomPropertyName::Custom(name)) = property {
+                reads.insert(name.0.to_string());
+              }
+            }
+          }
+          Property::Transition(transitions, _) => {
+            for transition in transitions {
+              if let PropertyId::Custom(CustomPropertyName::Custom(name)) = &transition.property {
+                reads.insert(name.0.to_string());
+              }
+            }
+          }
+          _ => {}
+        }
+      }
+    });
+
+    if !known {
+      return;
+    }
+
+    let mut stack: Vec<String> = reads.iter().chain(preserved.iter()).cloned().collect();
+    while let Some(name) = stack.pop() {
+      reads.insert(name.clone());
+      if let Some(deps) = dependencies.remove(&name) {
+        stack.extend(deps.into_iter().filter(|dep| !reads.contains(dep)));
+      }
+    }
+
+    let is_used = |property: &Property| match property {
+      Property::Custom(CustomProperty {
+        name: CustomPropertyName::Custom(name),
+        ..
+      }) => reads.contains(name.0.as_ref()),
+      _ => true,
+    };
+    let retain = |declarations: &mut DeclarationBlock| {
+      declarations.declarations.retain(is_used);
+      declarations.important_declarations.retain(is_used);
+    };
+
+    self.retain_rules(&mut |rule| match rule {
+      CssRule::Style(style) => {
+        retain(&mut style.declarations);
+        !style.is_empty()
+      }
+      CssRule::Nesting(nesting) => {
+        retain(&mut nesting.style.declarations);
+        !nesting.style.is_empty()
+      }
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &mut keyframes.keyframes {
+          retain(&mut keyframe.declarations);
+        }
+        true
+      }
+      CssRule::Page(page) => {
+        retain(&mut page.declarations);
+        for margin_rule in &mut page.rules {
+          retain(&mut margin_rule.declarations);
+        }
+        true
+      }
+      CssRule::Viewport(viewport) => {
+        retain(&mut viewport.declarations);
+        true
+      }
+      CssRule::PositionTry(position_try) => {
+        retain(&mut position_try.declarations);
+        true
+      }
+      CssRule::NestedDeclarations(nested) => {
+        retain(&mut nested.declarations);
+        !nested.declarations.is_empty()
+      }
+      CssRule::Property(property) => reads.contains(property.name.0.as_ref()),
+      _ => true,
+    });
+  }
+
+  /// Replaces `var()` references to custom properties that are defined exactly once in the style sheet, in a
+  /// top-level `:root` rule, with a value that does not reference other variables. Custom properties registered
+  /// with `@property` or listed in `dynamic` are never inlined. Definitions that are no longer referenced are
+  /// removed unless they are listed in `preserved`.
+  pub(crate) fn inline_custom_properties(&mut self, dynamic: &HashSet<String>, preserved: &HashSet<String>) {
+    let mut definitions: HashMap<String, usize> = HashMap::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        if let Property::Custom(CustomProperty {
+          name: CustomPropertyName::Custom(name),
+          ..
+        }) = property
+        {
+          *definitions.entry(name.0.to_string()).or_default() += 1;
+        }
+      }
+    });
+
+    if !known {
+      return;
+    }
+
+    let mut registered = HashSet::new();
+    self.for_each_rule(&mut |rule| {
+      if let CssRule::Property(property) = rule {
+        registered.insert(property.name.0.to_string());
+      }
+    });
+
+    let mut vars = HashMap::new();
+    for rule in &self.0 {
+      let style = match rule {
+        CssRule::Style(style) if is_root_selector(&style.selectors) => style,
+        _ => continue,
+      };
+
+      for property in &style.declarations.declarations {
+        if let Property::Custom(CustomProperty {
+          name: CustomPropertyName::Custom(name),
+          value,
+        }) = property
+        {
+          let name = name.0.as_ref();
+          if definitions.get(name) == Some(&1)
+            && !registered.contains(name)
+            && !dynamic.contains(name)
+            && value.0.iter().any(|token| !token.is_whitespace())
+            && !value.has_references()
+          {
+            vars.insert(name.to_owned(), value.clone());
+          }
+        }
+      }
+    }
+
+    if vars.is_empty() {
+      return;
+    }
+
+    let mut references = HashSet::new();
+    self.retain_rules(&mut |rule| {
+      let mut inline = |declarations: &mut DeclarationBlock<'i>| {
+        for property in declarations
+          .declarations
+          .iter_mut()
+          .chain(declarations.important_declarations.iter_mut())
+        {
+          let value = match property {
+            Property::Unparsed(unparsed) => {
+              // Only inline if the substituted value is still valid for the property.
+              let mut value = unparsed.value.clone();
+              if value.inline_variables(&vars)
+                && (value.has_references() || reparses_as(&unparsed.property_id, &value))
+              {
+                unparsed.value = value;
+              }
+              &mut unparsed.value
+            }
+            Property::Custom(custom) => {
+              custom.value.inline_variables(&vars);
+              &mut custom.value
+            }
+            _ => continue,
+          };
+          collect_variable_references(value, &mut references);
+        }
+      };
+
+      match rule {
+        CssRule::Style(style) => inline(&mut style.declarations),
+        CssRule::Nesting(nesting) => inline(&mut nesting.style.declarations),
+        CssRule::Keyframes(keyframes) => keyframes
+          .keyframes
+          .iter_mut()
+          .for_each(|keyframe| inline(&mut keyframe.declarations)),
+        CssRule::Page(page) => {
+          inline(&mut page.declarations);
+          page
+            .rules
+            .iter_mut()
+            .for_each(|margin_rule| inline(&mut margin_rule.declarations));
+        }
+        CssRule::Viewport(viewport) => inline(&mut viewport.declarations),
+        CssRule::PositionTry(position_try) => inline(&mut position_try.declarations),
+        CssRule::NestedDeclarations(nested) => inline(&mut nested.declarations),
+        _ => {}
+      }
+      true
+    });
+
+    self.0.retain_mut(|rule| match rule {
+      CssRule::Style(style) if is_root_selector(&style.selectors) => {
+        style.declarations.declarations.retain(|property| match property {
+          Property::Custom(CustomProperty {
+            name: CustomPropertyName::Custom(name),
+            ..
+          }) => {
+            let name = name.0.as_ref();
+            !vars.contains_key(name) || references.contains(name) || preserved.contains(name)
+          }
+          _ => true,
+        });
+        !style.is_empty()
+      }
+      _ => true,
+    });
+  }
+
+  /// Replaces `env()` references to the given environment variables with their values.
+  pub(crate) fn substitute_environment_variables(&mut self, vars: &HashMap<String, TokenList<'i>>) {
+    self.for_each_token_list_mut(&mut |tokens| {
+      tokens.substitute_environment_variables(vars);
+    });
+  }
+
+  /// Replaces typed `attr()` functions with their fallback values, for targets that do not support them.
+  pub(crate) fn replace_typed_attr(&mut self) {
+    self.for_each_token_list_mut(&mut |tokens| {
+      tokens.replace_typed_attr();
+    });
+  }
+
+  /// Calls the given function for the value of every unparsed and custom property declaration in the list,
+  /// including those in nested rules.
+  fn for_each_token_list_mut<F: FnMut(&mut TokenList<'i>)>(&mut self, f: &mut F) {
+    self.retain_rules(&mut |rule| {
+      let mut visit = |declarations: &mut DeclarationBlock<'i>| {
+        for property in declarations
+          .declarations
+          .iter_mut()
+          .chain(declarations.important_declarations.iter_mut())
+        {
+          match property {
+            Property::Unparsed(unparsed) => f(&mut unparsed.value),
+            Property::Custom(custom) => f(&mut custom.value),
+            _ => {}
+          }
+        }
+      };
+
+      match rule {
+        CssRule::Style(style) => visit(&mut style.declarations),
+        CssRule::Nesting(nesting) => visit(&mut nesting.style.declarations),
+        CssRule::Keyframes(keyframes) => keyframes
+          .keyframes
+          .iter_mut()
+          .for_each(|keyframe| visit(&mut keyframe.declarations)),
+        CssRule::Page(page) => {
+          visit(&mut page.declarations);
+          page
+            .rules
+            .iter_mut()
+            .for_each(|margin_rule| visit(&mut margin_rule.declarations));
+        }
+        CssRule::Viewport(viewport) => visit(&mut viewport.declarations),
+        CssRule::PositionTry(position_try) => visit(&mut position_try.declarations),
+        CssRule::NestedDeclarations(nested) => visit(&mut nested.declarations),
+        _ => {}
+      }
+      true
+    });
+  }
+
+  /// Removes `@keyframes` rules whose name is never referenced by an `animation` or `animation-name`
+  /// declaration, except those in `preserved`. Returns the removed names.
+  pub(crate) fn remove_unused_keyframes(&mut self, preserved: &HashSet<String>) -> Vec<String> {
+    let mut names = HashSet::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        match property {
+          Property::AnimationName(list, _) => {
+            for name in list {
+              collect_animation_name(name, &mut names);
+            }
+          }
+          Property::Animation(list, _) => {
+            for animation in list {
+              collect_animation_name(&animation.name, &mut names);
+            }
+          }
+          // Animation names may also be referenced via variables.
+          Property::Unparsed(unparsed) => collect_token_values(&unparsed.value, &mut names),
+          Property::Custom(custom) => collect_token_values(&custom.value, &mut names),
+          _ => {}
+        }
+      }
+    });
+
+    let mut removed = Vec::new();
+    if !known {
+      return removed;
+    }
+
+    self.retain_rules(&mut |rule| {
+      if let CssRule::Keyframes(keyframes) = rule {
+        let name = keyframes_name(&keyframes.name);
+        if !names.contains(name) && !preserved.contains(name) {
+          if !removed.iter().any(|r| r == name) {
+            removed.push(name.to_string());
+          }
+          return false;
+        }
+      }
+      true
+    });
+    removed
+  }
+
+  /// Removes top-level `@keyframes` rules with the same keyframes as an earlier rule, and updates
+  /// `animation` and `animation-name` declarations to reference the earlier rule instead. Names that
+  /// are defined more than once, referenced by unparsed values, or in `preserved` are left as is.
+  /// Returns a mapping from each removed name to the name that replaced it.
+  pub(crate) fn merge_duplicate_keyframes(&mut self, preserved: &HashSet<String>) -> BTreeMap<String, String> {
+    let mut merged = BTreeMap::new();
+    let mut tokens = HashSet::new();
+    let known = self.for_each_declaration_block(&mut |declarations| {
+      for (property, _) in declarations.iter() {
+        match property {
+          Property::Unparsed(unparsed) => collect_token_values(&unparsed.value, &mut tokens),
+          Property::Custom(custom) => collect_token_values(&custom.value, &mut tokens),
+          _ => {}
+        }
+      }
+    });
+    if !known {
+      return merged;
+    }
+
+    let mut definitions: HashMap<&str, usize> = HashMap::new();
+    self.for_each_rule(&mut |rule| {
+      if let CssRule::Keyframes(keyframes) = rule {
+        *definitions.entry(keyframes_name(&keyframes.name)).or_default() += 1;
+      }
+    });
+
+    let mut survivors: Vec<&KeyframesRule<'i>> = Vec::new();
+    let mut replacements: HashMap<String, KeyframesName<'i>> = HashMap::new();
+    for rule in &self.0 {
+      let keyframes = match rule {
+        CssRule::Keyframes(keyframes) if definitions[keyframes_name(&keyframes.name)] == 1 => keyframes,
+        _ => continue,
+      };
+
+      let name = keyframes_name(&keyframes.name);
+      let survivor = survivors.iter().find(|s| s.has_same_keyframes(keyframes));
+      match survivor {
+        Some(survivor) if !tokens.contains(name) && !preserved.contains(name) => {
+          merged.insert(name.to_string(), keyframes_name(&survivor.name).to_string());
+          replacements.insert(name.to_string(), survivor.name.clone());
+        }
+        _ => survivors.push(keyframes),
+      }
+    }
+
+    if replacements.is_empty() {
+      return merged;
+    }
+
+    let replace = |name: &mut AnimationName<'i>| {
+      let replacement = match name {
+        AnimationName::Ident(ident) => replacements.get(ident.0.as_ref()),
+        AnimationName::String(s) => replacements.get(s.as_ref()),
+        AnimationName::None => None,
+      };
+      match replacement {
+        Some(KeyframesName::Ident(ident)) => *name = AnimationName::Ident(ident.clone()),
+        Some(KeyframesName::Custom(s)) => *name = AnimationName::String(s.clone()),
+        None => {}
+      }
+    };
+
+    self.retain_rules(&mut |rule| {
+      let declarations = match rule {
+        CssRule::Keyframes(keyframes) => return !replacements.contains_key(keyframes_name(&keyframes.name)),
+        CssRule::Style(style) => &mut style.declarations,
+        CssRule::Nesting(nesting) => &mut nesting.style.declarations,
+        CssRule::NestedDeclarations(nested) => &mut nested.declarations,
+        _ => return true,
+      };
+      for property in declarations
+        .declarations
+        .iter_mut()
+        .chain(declarations.important_declarations.iter_mut())
+      {
+        match property {
+          Property::AnimationName(names, _) => names.iter_mut().for_each(replace),
+          Property::Animation(animations, _) => {
+            animations.iter_mut().for_each(|animation| replace(&mut animation.name))
+          }
+          _ => {}
+        }
+      }
+      true
+    });
+    merged
+  }
+
+  /// Collects the custom element names referenced by all style rules in the list, including nested rules.
+  pub(crate) fn custom_element_usage(&self, usage: &mut CustomElementUsage) {
+    for rule in &self.0 {
+      match rule {
+        CssRule::Style(style) => {
+          usage.add_selectors(&style.selectors);
+          style.rules.custom_element_usage(usage);
+        }
+        CssRule::Media(media) => media.rules.custom_element_usage(usage),
+        CssRule::Supports(supports) => supports.rules.custom_element_usage(usage),
+        CssRule::Container(container) => container.rules.custom_element_usage(usage),
+        CssRule::Scope(scope) => scope.rules.custom_element_usage(usage),
+        CssRule::StartingStyle(rule) => rule.rules.custom_element_usage(usage),
+        CssRule::LayerBlock(layer) => layer.rules.custom_element_usage(usage),
+        CssRule::MozDocument(document) => document.rules.custom_element_usage(usage),
+        CssRule::Nesting(nesting) => {
+          usage.add_selectors(&nesting.style.selectors);
+          nesting.style.rules.custom_element_usage(usage);
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /// Collects the features used by the rules that are not supported by all of the targets and
+  /// that cannot be transpiled, along with the offending value and the location of its rule.
+  pub(crate) fn compat_issues(&self, targets: Browsers, issues: &mut Vec<(compat::Feature, String, Location)>) {
+    let at_rule = |issues: &mut Vec<_>, feature: compat::Feature, prelude: String, loc: Location| {
+      if !feature.is_compatible(targets) {
+        issues.push((feature, prelude, loc));
+      }
+    };
+
+    for rule in &self.0 {
+      match rule {
+        CssRule::Style(style) => style.compat_issues(targets, issues),
+        CssRule::Nesting(nesting) => nesting.style.compat_issues(targets, issues),
+        CssRule::Media(media) => {
+          let query = media.query.to_css_string(PrinterOptions::default()).unwrap_or_default();
+          for feature in media.query.unsupported_features(targets) {
+            issues.push((feature, format!("@media {}", query), media.loc));
+          }
+          media.rules.compat_issues(targets, issues);
+        }
+        CssRule::Scope(scope) => {
+          at_rule(issues, compat::Feature::AtScope, "@scope".into(), scope.loc);
+          scope.rules.compat_issues(targets, issues);
+        }
+        CssRule::StartingStyle(rule) => {
+          at_rule(
+            issues,
+            compat::Feature::AtStartingStyle,
+            "@starting-style".into(),
+            rule.loc,
+          );
+          rule.rules.compat_issues(targets, issues);
+        }
+        CssRule::Property(rule) => at_rule(
+          issues,
+          compat::Feature::AtProperty,
+          format!("@property {}", rule.name.0),
+          rule.loc,
+        ),
+        CssRule::PositionTry(rule) => at_rule(
+          issues,
+          compat::Feature::AnchorPositioning,
+          format!("@position-try {}", rule.name.0),
+          rule.loc,
+        ),
+        CssRule::Supports(supports) => supports.rules.compat_issues(targets, issues),
+        CssRule::Container(container) => container.rules.compat_issues(targets, issues),
+        CssRule::LayerBlock(layer) => layer.rules.compat_issues(targets, issues),
+        CssRule::MozDocument(document) => document.rules.compat_issues(targets, issues),
+        _ => {}
+      }
+    }
+  }
+}
+
+fn collect_family_names(list: &[FontFamily], families: &mut HashSet<String>) {
+  for family in list {
+    if let FontFamily::FamilyName(name) = family {
+      families.insert(name.to_ascii_lowercase());
+    }
+  }
+}
+
+/// Returns whether a rule is a vendor prefixed `@keyframes` or style rule that is followed by an unprefixed
+/// equivalent, and none of the targets need the prefix.
+fn is_obsolete_prefixed_rule<'i, T>(
+  rule: &CssRule<'i, T>,
+  following: &[CssRule<'i, T>],
+  targets: Browsers,
+) -> bool {
+  let is_single_prefix =
+    |prefix: VendorPrefix| !prefix.contains(VendorPrefix::None) && prefix.bits().count_ones() == 1;
+  match rule {
+    CssRule::Keyframes(keyframes) if is_single_prefix(keyframes.vendor_prefix) => {
+      !Feature::AtKeyframes.prefixes_for(targets).contains(keyframes.vendor_prefix)
+        && following.iter().any(|other| {
+          matches!(other, CssRule::Keyframes(other) if other.name == keyframes.name && other.vendor_prefix.contains(VendorPrefix::None))
+        })
+    }
+    CssRule::Style(style) if style.rules.0.is_empty() && is_single_prefix(get_prefix(&style.selectors)) => {
+      let prefix = get_prefix(&style.selectors);
+      following.iter().any(|other| match other {
+        CssRule::Style(other) => {
+          get_prefix(&other.selectors) == VendorPrefix::None
+            && is_equivalent(&style.selectors, &other.selectors)
+            && !get_necessary_prefixes(&other.selectors, targets).contains(prefix)
+            && style.declarations.iter().all(|(property, _)| {
+              let id = property.property_id();
+              other.declarations.iter().any(|(other, _)| other.property_id() == id)
+            })
+        }
+        _ => false,
+      })
+    }
+    _ => false,
+  }
+}
+
+fn keyframes_name<'a>(name: &'a KeyframesName) -> &'a str {
+  match name {
+    KeyframesName::Ident(ident) => ident.0.as_ref(),
+    KeyframesName::Custom(name) => name.as_ref(),
+  }
+}
+
+fn collect_animation_name(name: &AnimationName, names: &mut HashSet<String>) {
+  match name {
+    AnimationName::Ident(ident) => {
+      names.insert(ident.0.to_string());
+    }
+    AnimationName::String(s) => {
+      names.insert(s.to_string());
+    }
+    AnimationName::None => {}
+  }
+}
+
+fn is_root_selector(selectors: &SelectorList) -> bool {
+  selectors.0.len() == 1 && {
+    let mut iter = selectors.0[0].iter_raw_match_order();
+    matches!((iter.next(), iter.next()), (Some(Component::Root), None))
+  }
+}
+
+/// Returns whether a fully substituted token list parses as a valid value for the property.
+fn reparses_as(property_id: &PropertyId, tokens: &TokenList) -> bool {
+  if tokens.0.iter().all(|token| token.is_whitespace()) {
+    return false;
+  }
+
+  let mut css = String::new();
+  let mut dest = Printer::new(&mut css, PrinterOptions::default());
+  if tokens.to_css(&mut dest, false).is_err() {
+    return false;
+  }
+
+  let result = Property::parse_string(property_id.clone(), &css, ParserOptions::default());
+  matches!(result, Ok(property) if !matches!(property, Property::Unparsed(..)))
+}
+
+/// Collects the names of all custom properties referenced by `var()` in a token list.
+fn collect_variable_references(tokens: &TokenList, names: &mut HashSet<String>) {
+  for token in &tokens.0 {
+    match token {
+      TokenOrValue::Var(variable) => {
+        names.insert(variable.name.ident.0.to_string());
+        if let Some(fallback) = &variable.fallback {
+          collect_variable_references(fallback, names);
+        }
+      }
+      TokenOrValue::Env(EnvironmentVariable {
+        fallback: Some(fallback),
+        ..
+      }) => collect_variable_references(fallback, names),
+      TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+      | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+        collect_variable_references(alpha, names)
+      }
+      TokenOrValue::Function(function) => collect_variable_references(&function.arguments, names),
+      _ => {}
+    }
+  }
+}
+
+/// Collects the values of all identifier and string tokens in a token list, including
+/// within functions and `var()` fallbacks.
+pub(crate) fn collect_token_values(tokens: &TokenList, values: &mut HashSet<String>) {
+  for token in &tokens.0 {
+    match token {
+      TokenOrValue::Token(Token::Ident(s)) | TokenOrValue::Token(Token::String(s)) => {
+        values.insert(s.to_string());
+      }
+      TokenOrValue::Var(Variable {
+        fallback: Some(fallback),
+        ..
+      }) => collect_token_values(fallback, values),
+      TokenOrValue::Function(function) => collect_token_values(&function.arguments, values),
+      _ => {}
+    }
+  }
+}
+
+fn merge_style_rules<'i, T>(
+  style: &mut StyleRule<'i, T>,
+  last_style_rule: &mut StyleRule<'i, T>,
+  context: &mut MinifyContext<'_, 'i>,
+) -> bool {
+  // Merge declarations if the selectors are equivalent, and both are compatible with all targets.
+  if style.selectors == last_style_rule.selectors
+    && style.is_compatible(*context.targets)
+    && last_style_rule.is_compatible(*context.targets)
+    && style.rules.0.is_empty()
+    && last_style_rule.rules.0.is_empty()
+    && (!context.css_modules || style.loc.source_index == last_style_rule.loc.source_index)
+  {
+    last_style_rule
+      .declarations
+      .declarations
+      .extend(style.declarations.declarations.drain(..));
+    last_style_rule
+      .declarations
+      .important_declarations
+      .extend(style.declarations.important_declarations.drain(..));
+    last_style_rule
+      .declarations
+      .minify(context.handler, context.important_handler, context.handler_context);
+    return true;
+  } else if style.declarations == last_style_rule.declarations
+    && style.rules.0.is_empty()
+    && last_style_rule.rules.0.is_empty()
+  {
+    // Append the selectors to the last rule if the declarations are the same, and all selectors are compatible.
+    if style.is_compatible(*context.targets) && last_style_rule.is_compatible(*context.targets) {
+      last_style_rule.selectors.0.extend(style.selectors.0.drain(..));
+      return true;
+    }
+
+    // If both selectors are potentially vendor prefixable, and they are
+    // equivalent minus prefixes, add the prefix to the last rule.
+    if !style.vendor_prefix.is_empty()
+      && !last_style_rule.vendor_prefix.is_empty()
+      && !last_style_rule.vendor_prefix.contains(style.vendor_prefix)
+      && is_equivalent(&style.selectors, &last_style_rule.selectors)
+    {
+      // If the new rule is unprefixed, replace the prefixes of the last rule.
+      // Otherwise, add the new prefix.
+      if style.vendor_prefix.contains(VendorPrefix::None) {
+        last_style_rule.vendor_prefix = style.vendor_prefix;
+      } else {
+        last_style_rule.vendor_prefix |= style.vendor_prefix;
+      }
+      return true;
+    }
+  }
+  false
+}
+
+impl<'a, 'i, T: ToCss> ToCss for CssRuleList<'i, T> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let mut first = true;
+    let mut last_without_block = false;
+    let mut last_comment = false;
+    // Only the outermost rule list records sizes.
+    let mut rule_ranges = dest.rule_ranges.take();
+
+    for rule in &self.0 {
+      if let CssRule::Ignored = &rule {
+        continue;
+      }
+
+      // Comments are omitted when minifying, except for `/*!` comments.
+      if let CssRule::Comment(comment) = &rule {
+        if !comment.is_written(dest.minify) {
+          continue;
+        }
+      }
+
+      // Skip @import rules if collecting dependencies.
+      if let CssRule::Import(rule) = &rule {
+        if dest.remove_imports {
+          let dep = if dest.dependencies.is_some() {
+            Some(Dependency::Import(ImportDependency::new(&rule, dest.filename())))
+          } else {
+            None
+          };
+
+          if let Some(dependencies) = &mut dest.dependencies {
+            dependencies.push(dep.unwrap());
+            continue;
+          }
+        }
+      }
+
+      if first {
+        first = false;
+      } else {
+        // A comment is written directly above the rule it is attached to.
+        if !dest.minify
+          && !last_comment
+          && !(last_without_block
+            && matches!(
+              rule,
+              CssRule::Import(..) | CssRule::Namespace(..) | CssRule::LayerStatement(..)
+            ))
+        {
+          dest.write_char('\n')?;
+        }
+        dest.newline()?;
+      }
+      let start = dest.bytes;
+      let preceding_bytes = dest.size_breakdown.as_mut().map(|breakdown| breakdown.enter_rule(rule));
+      rule.to_css(dest)?;
+      if let Some(rule_ranges) = &mut rule_ranges {
+        rule_ranges.push(start..dest.bytes);
+      }
+      if let (Some(breakdown), Some(preceding_bytes)) = (&mut dest.size_breakdown, preceding_bytes) {
+        breakdown.exit_rule(rule, dest.bytes - start, preceding_bytes);
+      }
       last_without_block = matches!(
         rule,
         CssRule::Import(..) | CssRule::Namespace(..) | CssRule::LayerStatement(..)
       );
+      last_comment = matches!(rule, CssRule::Comment(..));
     }
 
+    dest.rule_ranges = rule_ranges;
     Ok(())
   }
 }
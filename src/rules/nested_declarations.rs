@@ -0,0 +1,108 @@
+//! Nested declarations that follow nested rules.
+
+use super::Location;
+use super::MinifyContext;
+use crate::context::DeclarationContext;
+use crate::declaration::DeclarationBlock;
+use crate::error::PrinterError;
+use crate::parser::DefaultAtRule;
+use crate::printer::Printer;
+use crate::rules::comment::write_comment;
+use crate::rules::style::StyleRule;
+use crate::rules::CssRuleList;
+use crate::selector::{Component, SelectorList};
+use crate::traits::ToCss;
+use crate::vendor_prefix::VendorPrefix;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+
+/// A [nested declarations](https://drafts.csswg.org/css-nesting/#nested-declarations-rule) rule.
+///
+/// Holds declarations that appear after a nested rule within a style rule, or directly within
+/// a nested group rule such as `@media`. These apply with the same specificity as the parent
+/// style rule, but in their original position relative to the nested rules.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct NestedDeclarationsRule<'i> {
+  /// The declarations.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub declarations: DeclarationBlock<'i>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+impl<'i> NestedDeclarationsRule<'i> {
+  pub(crate) fn minify(&mut self, context: &mut MinifyContext<'_, 'i>, parent_is_unused: bool) -> bool {
+    if parent_is_unused {
+      return true;
+    }
+
+    context.handler_context.context = DeclarationContext::StyleRule;
+    self
+      .declarations
+      .minify(context.handler, context.important_handler, context.handler_context);
+    context.handler_context.context = DeclarationContext::None;
+    self.declarations.is_empty()
+  }
+}
+
+impl<'i> ToCss for NestedDeclarationsRule<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+
+    // When nesting is being compiled, the parent selectors are available in the printer context,
+    // so the declarations are written as a separate `&` rule, which resolves to the parent.
+    if dest.context().is_some() {
+      let style = StyleRule::<DefaultAtRule> {
+        selectors: SelectorList::new(smallvec::smallvec![Component::Nesting.into()]),
+        vendor_prefix: VendorPrefix::empty(),
+        declarations: self.declarations.clone(),
+        rules: CssRuleList(vec![]),
+        loc: self.loc,
+        #[cfg(feature = "spans")]
+        selector_spans: vec![],
+      };
+      return style.to_css(dest);
+    }
+
+    // Otherwise, write the declarations directly into the parent block. A semicolon is always
+    // written, since a rule may follow.
+    let mut first = true;
+    macro_rules! newline {
+      () => {
+        if first {
+          first = false;
+        } else {
+          dest.newline()?;
+        }
+      };
+    }
+
+    for (decls, important) in [
+      (&self.declarations.declarations, false),
+      (&self.declarations.important_declarations, true),
+    ] {
+      for index in 0..=decls.len() {
+        for comment in self.declarations.comments_at(important, index, dest.minify) {
+          newline!();
+          write_comment(comment, dest)?;
+        }
+
+        if let Some(decl) = decls.get(index) {
+          newline!();
+          decl.to_css(dest, important)?;
+          dest.write_char(';')?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
@@ -1,6 +1,7 @@
 //! The `@page` rule.
 
-use super::Location;
+use super::{Location, MinifyContext};
+use crate::context::DeclarationContext;
 use crate::declaration::{parse_declaration, DeclarationBlock};
 use crate::error::{ParserError, PrinterError};
 use crate::macros::enum_property;
@@ -208,6 +209,27 @@ impl<'i> PageRule<'i> {
       loc,
     })
   }
+
+  pub(crate) fn minify(&mut self, context: &mut MinifyContext<'_, 'i>) {
+    context.handler_context.context = DeclarationContext::Page;
+    self
+      .declarations
+      .minify(context.handler, context.important_handler, context.handler_context);
+
+    for rule in &mut self.rules {
+      rule
+        .declarations
+        .minify(context.handler, context.important_handler, context.handler_context);
+    }
+
+    // Margin boxes are only generated when they have content, so empty margin rules have no effect.
+    self.rules.retain(|rule| !rule.declarations.is_empty());
+    context.handler_context.context = DeclarationContext::None;
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.declarations.is_empty() && self.rules.is_empty()
+  }
 }
 
 impl<'i> ToCss for PageRule<'i> {
@@ -243,7 +265,8 @@ impl<'i> ToCss for PageRule<'i> {
 
     macro_rules! write {
       ($decls: expr, $important: literal) => {
-        for decl in &$decls {
+        for (index, decl) in $decls.iter().enumerate() {
+          self.declarations.write_comments($important, index, dest)?;
           dest.newline()?;
           decl.to_css(dest, $important)?;
           if i != len - 1 || !dest.minify {
@@ -251,6 +274,7 @@ impl<'i> ToCss for PageRule<'i> {
           }
           i += 1;
         }
+        self.declarations.write_comments($important, $decls.len(), dest)?;
       };
     }
 
@@ -356,6 +380,8 @@ impl<'a, 'o, 'i> AtRuleParser<'i> for PageRuleParser<'a, 'o, 'i> {
         source_index: self.options.source_index,
         line: loc.line,
         column: loc.column,
+        #[cfg(feature = "spans")]
+        span: crate::span::Span::default(),
       },
     });
     Ok(())
@@ -0,0 +1,176 @@
+//! The `@position-try` rule.
+
+use super::{Location, MinifyContext};
+use crate::context::DeclarationContext;
+use crate::declaration::DeclarationBlock;
+use crate::error::{ParserError, PrinterError};
+use crate::printer::Printer;
+use crate::properties::{Property, PropertyId};
+use crate::stylesheet::ParserOptions;
+use crate::traits::ToCss;
+use crate::values::ident::DashedIdent;
+use crate::values::string::CowArcStr;
+use crate::vendor_prefix::VendorPrefix;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+/// A [@position-try](https://drafts.csswg.org/css-anchor-position-1/#fallback-rule) rule.
+///
+/// Defines a fallback position for an anchor positioned element, which is used when
+/// its initial position would overflow the containing block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct PositionTryRule<'i> {
+  /// The name of the position option.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: DashedIdent<'i>,
+  /// Declarations in the `@position-try` rule. Only inset, margin, sizing,
+  /// self-alignment, `position-anchor`, and `position-area` properties are accepted.
+  pub declarations: DeclarationBlock<'i>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+impl<'i> PositionTryRule<'i> {
+  pub(crate) fn parse<'t, 'o>(
+    name: DashedIdent<'i>,
+    input: &mut Parser<'i, 't>,
+    loc: Location,
+    options: &ParserOptions<'o, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut declarations = DeclarationBlock::new();
+    let mut parser = DeclarationListParser::new(
+      input,
+      PositionTryDeclarationParser {
+        declarations: &mut declarations,
+        options,
+      },
+    );
+
+    // Declarations that are not accepted within `@position-try` are ignored.
+    while let Some(decl) = parser.next() {
+      if let Err((err, _)) = decl {
+        parser.parser.options.warn(err);
+      }
+    }
+
+    Ok(PositionTryRule {
+      name,
+      declarations,
+      loc,
+    })
+  }
+
+  pub(crate) fn minify(&mut self, context: &mut MinifyContext<'_, 'i>) {
+    context.handler_context.context = DeclarationContext::PositionTry;
+    self
+      .declarations
+      .minify(context.handler, context.important_handler, context.handler_context);
+    context.handler_context.context = DeclarationContext::None;
+  }
+}
+
+impl<'i> ToCss for PositionTryRule<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    dest.write_str("@position-try ")?;
+    self.name.to_css(dest)?;
+    self.declarations.to_css_block(dest)
+  }
+}
+
+struct PositionTryDeclarationParser<'a, 'o, 'i> {
+  declarations: &'a mut DeclarationBlock<'i>,
+  options: &'a ParserOptions<'o, 'i>,
+}
+
+impl<'a, 'o, 'i> cssparser::DeclarationParser<'i> for PositionTryDeclarationParser<'a, 'o, 'i> {
+  type Declaration = ();
+  type Error = ParserError<'i>;
+
+  fn parse_value<'t>(
+    &mut self,
+    name: CowRcStr<'i>,
+    input: &mut cssparser::Parser<'i, 't>,
+  ) -> Result<Self::Declaration, cssparser::ParseError<'i, Self::Error>> {
+    // https://drafts.csswg.org/css-anchor-position-1/#accepted-position-try-properties
+    let property_id = PropertyId::from(CowArcStr::from(name));
+    if !is_accepted_property(&property_id) {
+      return Err(input.new_custom_error(ParserError::InvalidDeclaration));
+    }
+
+    let property = input.parse_until_before(Delimiter::Bang, |input| {
+      Property::parse(property_id, input, self.options)
+    })?;
+    // `!important` is not allowed in `@position-try` rules.
+    input.expect_exhausted()?;
+    self.declarations.declarations.push(property);
+    Ok(())
+  }
+}
+
+/// Default methods reject all at rules.
+impl<'a, 'o, 'i> AtRuleParser<'i> for PositionTryDeclarationParser<'a, 'o, 'i> {
+  type Prelude = ();
+  type AtRule = ();
+  type Error = ParserError<'i>;
+}
+
+fn is_accepted_property(property_id: &PropertyId) -> bool {
+  match property_id {
+    // Inset properties.
+    PropertyId::Top
+    | PropertyId::Bottom
+    | PropertyId::Left
+    | PropertyId::Right
+    | PropertyId::InsetBlockStart
+    | PropertyId::InsetBlockEnd
+    | PropertyId::InsetInlineStart
+    | PropertyId::InsetInlineEnd
+    | PropertyId::InsetBlock
+    | PropertyId::InsetInline
+    | PropertyId::Inset
+    // Margin properties.
+    | PropertyId::MarginTop
+    | PropertyId::MarginBottom
+    | PropertyId::MarginLeft
+    | PropertyId::MarginRight
+    | PropertyId::MarginBlockStart
+    | PropertyId::MarginBlockEnd
+    | PropertyId::MarginInlineStart
+    | PropertyId::MarginInlineEnd
+    | PropertyId::MarginBlock
+    | PropertyId::MarginInline
+    | PropertyId::Margin
+    // Sizing properties.
+    | PropertyId::Width
+    | PropertyId::Height
+    | PropertyId::MinWidth
+    | PropertyId::MinHeight
+    | PropertyId::MaxWidth
+    | PropertyId::MaxHeight
+    | PropertyId::BlockSize
+    | PropertyId::InlineSize
+    | PropertyId::MinBlockSize
+    | PropertyId::MinInlineSize
+    | PropertyId::MaxBlockSize
+    | PropertyId::MaxInlineSize
+    // Self-alignment properties.
+    | PropertyId::JustifySelf
+    | PropertyId::PlaceSelf
+    // Anchor positioning properties.
+    | PropertyId::PositionAnchor
+    | PropertyId::PositionArea => true,
+    PropertyId::AlignSelf(prefix) => *prefix == VendorPrefix::None,
+    _ => false,
+  }
+}
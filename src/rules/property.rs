@@ -5,7 +5,7 @@ use super::Location;
 use crate::visitor::Visit;
 use crate::{
   error::{ParserError, PrinterError},
-  printer::Printer,
+  printer::{Printer, PrinterOptions},
   traits::{Parse, ToCss},
   values::{
     ident::DashedIdent,
@@ -97,6 +97,87 @@ impl<'i> PropertyRule<'i> {
   }
 }
 
+impl<'i> PropertyRule<'i> {
+  /// Returns the descriptors of the rule as an equivalent `CSS.registerProperty()` registration.
+  pub fn registration(&self) -> Result<PropertyRegistration, PrinterError> {
+    let mut syntax = String::new();
+    self
+      .syntax
+      .to_css_unquoted(&mut Printer::new(&mut syntax, PrinterOptions::default()))?;
+    Ok(PropertyRegistration {
+      name: self.name.0.to_string(),
+      syntax,
+      inherits: self.inherits,
+      initial_value: match &self.initial_value {
+        Some(initial_value) => Some(initial_value.to_css_string(PrinterOptions::default())?),
+        None => None,
+      },
+    })
+  }
+}
+
+/// The descriptors of an `@property` rule, in the form accepted by
+/// [CSS.registerProperty()](https://drafts.css-houdini.org/css-properties-values-api/#the-registerproperty-function).
+/// These can be used to register custom properties from JavaScript in browsers that do not support `@property`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct PropertyRegistration {
+  /// The name of the custom property.
+  pub name: String,
+  /// The syntax of the custom property, e.g. `<length>`.
+  pub syntax: String,
+  /// Whether the custom property is inherited.
+  pub inherits: bool,
+  /// The initial value of the custom property, if any.
+  #[cfg_attr(
+    any(feature = "serde", feature = "nodejs"),
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub initial_value: Option<String>,
+}
+
+impl PropertyRegistration {
+  /// Returns a JavaScript statement that registers the custom property via `CSS.registerProperty()`.
+  pub fn to_js(&self) -> String {
+    let mut js = format!(
+      "CSS.registerProperty({{name: {}, syntax: {}, inherits: {}",
+      js_string(&self.name),
+      js_string(&self.syntax),
+      self.inherits
+    );
+    if let Some(initial_value) = &self.initial_value {
+      js.push_str(", initialValue: ");
+      js.push_str(&js_string(initial_value));
+    }
+    js.push_str("});");
+    js
+  }
+}
+
+/// Serializes a string as a double quoted JavaScript string literal.
+fn js_string(s: &str) -> String {
+  let mut res = String::with_capacity(s.len() + 2);
+  res.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => res.push_str("\\\""),
+      '\\' => res.push_str("\\\\"),
+      '\n' => res.push_str("\\n"),
+      '\r' => res.push_str("\\r"),
+      c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+        res.push_str(&format!("\\u{:04x}", c as u32));
+      }
+      c => res.push(c),
+    }
+  }
+  res.push('"');
+  res
+}
+
 impl<'i> ToCss for PropertyRule<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
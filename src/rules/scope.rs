@@ -0,0 +1,113 @@
+//! The `@scope` rule.
+
+use super::Location;
+use super::{CssRule, CssRuleList, MinifyContext};
+use crate::error::{MinifyError, PrinterError};
+use crate::parser::DefaultAtRule;
+use crate::printer::Printer;
+use crate::selector::{scope_root_selectors, SelectorList};
+use crate::traits::ToCss;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+
+/// A [@scope](https://drafts.csswg.org/css-cascade-6/#scope-atrule) rule.
+///
+/// @scope (<scope-start>) [to (<scope-end>)]? {
+///  <stylesheet>
+/// }
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct ScopeRule<'i, R = DefaultAtRule> {
+  /// A selector list used to identify the scoping root(s).
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub scope_start: Option<SelectorList<'i>>,
+  /// A selector list used to identify any scoping limits.
+  pub scope_end: Option<SelectorList<'i>>,
+  /// Nested rules within the `@scope` rule.
+  pub rules: CssRuleList<'i, R>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+impl<'i, T> ScopeRule<'i, T> {
+  pub(crate) fn minify(
+    &mut self,
+    context: &mut MinifyContext<'_, 'i>,
+    parent_is_unused: bool,
+  ) -> Result<bool, MinifyError> {
+    self.rules.minify(context, parent_is_unused)?;
+    Ok(self.rules.0.is_empty())
+  }
+
+  /// Rewrites the rules within the `@scope` rule as descendants of the scoping root, so that they
+  /// can be hoisted out of the rule for targets that don't support it. Scoping limits cannot be
+  /// represented with plain selectors, so they are ignored. Returns `false` if there is no explicit
+  /// scoping root, in which case the rule is left unchanged.
+  pub(crate) fn downlevel(&mut self) -> bool {
+    match &self.scope_start {
+      Some(root) => {
+        scope_root_rules(&mut self.rules, root);
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+fn scope_root_rules<'i, T>(rules: &mut CssRuleList<'i, T>, root: &SelectorList<'i>) {
+  for rule in rules.0.iter_mut() {
+    match rule {
+      // Nested style rules are relative to their parent, so only the top-level selectors change.
+      CssRule::Style(style) => scope_root_selectors(&mut style.selectors, root),
+      CssRule::Scope(scope) => {
+        if let Some(start) = &mut scope.scope_start {
+          scope_root_selectors(start, root);
+        }
+      }
+      CssRule::Media(media) => scope_root_rules(&mut media.rules, root),
+      CssRule::Supports(supports) => scope_root_rules(&mut supports.rules, root),
+      CssRule::Container(container) => scope_root_rules(&mut container.rules, root),
+      CssRule::StartingStyle(rule) => scope_root_rules(&mut rule.rules, root),
+      CssRule::LayerBlock(layer) => scope_root_rules(&mut layer.rules, root),
+      _ => {}
+    }
+  }
+}
+
+impl<'i, T: ToCss> ToCss for ScopeRule<'i, T> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    dest.write_str("@scope")?;
+    dest.whitespace()?;
+    if let Some(scope_start) = &self.scope_start {
+      dest.write_char('(')?;
+      scope_start.to_css(dest)?;
+      dest.write_char(')')?;
+      dest.whitespace()?;
+    }
+    if let Some(scope_end) = &self.scope_end {
+      if dest.minify {
+        dest.write_char(' ')?;
+      }
+      dest.write_str("to (")?;
+      scope_end.to_css(dest)?;
+      dest.write_char(')')?;
+      dest.whitespace()?;
+    }
+    dest.write_char('{')?;
+    dest.indent();
+    dest.newline()?;
+    self.rules.to_css(dest)?;
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
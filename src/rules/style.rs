@@ -7,12 +7,26 @@ use super::MinifyContext;
 use crate::compat::Feature;
 use crate::context::DeclarationContext;
 use crate::declaration::DeclarationBlock;
-use crate::error::ParserError;
+use crate::error::{ErrorWithLocation, ParserError, SelectorError};
 use crate::error::{MinifyError, PrinterError, PrinterErrorKind};
 use crate::parser::DefaultAtRule;
-use crate::printer::Printer;
+use crate::printer::{LightDarkMode, NestingMode, Printer, PrinterOptions};
+use crate::properties::anchor::property_uses_anchor_positioning;
+use crate::properties::custom::{CustomProperty, CustomPropertyName};
+use crate::properties::display::{Display, DisplayInside, DisplayPair};
+#[cfg(feature = "grid")]
+use crate::properties::grid::property_uses_masonry;
+use crate::properties::position::Position;
+use crate::properties::transition::property_allows_discrete_transitions;
+use crate::properties::ui::FieldSizing;
+use crate::properties::view_transition::property_view_transition_feature;
+use crate::properties::Property;
 use crate::rules::CssRuleList;
-use crate::selector::{is_compatible, is_unused, SelectorList};
+use crate::selector::{
+  expand_nesting, find_undeclared_namespace, has_has_selector, is_compatible, is_unused,
+  normalize_attribute_selectors, unsupported_features, SelectorList,
+};
+use crate::stylesheet::ColorFallbackStrategy;
 use crate::targets::Browsers;
 use crate::traits::ToCss;
 use crate::vendor_prefix::VendorPrefix;
@@ -42,6 +56,11 @@ pub struct StyleRule<'i, R = DefaultAtRule> {
   /// The location of the rule in the source file.
   #[cfg_attr(feature = "visitor", skip_visit)]
   pub loc: Location,
+  /// The source spans of the selectors in the selector list.
+  #[cfg(feature = "spans")]
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub selector_spans: Vec<crate::span::Span>,
 }
 
 #[cfg(feature = "serde")]
@@ -55,27 +74,130 @@ impl<'i, T> StyleRule<'i, T> {
     context: &mut MinifyContext<'_, 'i>,
     parent_is_unused: bool,
   ) -> Result<bool, MinifyError> {
-    let mut unused = false;
-    if !context.unused_symbols.is_empty() {
-      if is_unused(&mut self.selectors.0.iter(), &context.unused_symbols, parent_is_unused) {
-        if self.rules.0.is_empty() {
-          return Ok(true);
+    // Selectors may be removed, merged, or rewritten below.
+    #[cfg(feature = "spans")]
+    self.selector_spans.clear();
+
+    if let Some(targets) = context.targets {
+      if !Feature::CssHas.is_compatible(*targets) && self.selectors.0.iter().any(has_has_selector) {
+        context.warnings.push(ErrorWithLocation {
+          kind: ParserError::SelectorError(SelectorError::UnsupportedHasSelector),
+          loc: self.loc,
+        });
+
+        if context.remove_unsupported_has {
+          self.selectors.0.retain(|selector| !has_has_selector(selector));
+          if self.selectors.0.is_empty() {
+            return Ok(true);
+          }
+        }
+      }
+    }
+
+    if let Some(namespaces) = &context.namespaces {
+      if let Some(prefix) = self
+        .selectors
+        .0
+        .iter()
+        .find_map(|selector| find_undeclared_namespace(selector, namespaces))
+      {
+        context.warnings.push(ErrorWithLocation {
+          kind: ParserError::SelectorError(SelectorError::ExpectedNamespace(prefix)),
+          loc: self.loc,
+        });
+      }
+    }
+
+    #[cfg(feature = "grid")]
+    for property in self
+      .declarations
+      .declarations
+      .iter()
+      .chain(self.declarations.important_declarations.iter())
+    {
+      let areas = match property {
+        Property::GridTemplateAreas(areas) => areas,
+        Property::GridTemplate(template) => &template.areas,
+        Property::Grid(grid) => &grid.areas,
+        _ => continue,
+      };
+
+      if let Some(name) = areas.non_rectangular_area() {
+        context.warnings.push(ErrorWithLocation {
+          kind: ParserError::NonRectangularGridArea(name.to_owned().into()),
+          loc: self.loc,
+        });
+      }
+    }
+
+    if context.normalize_attribute_selectors {
+      for selector in self.selectors.0.iter_mut() {
+        normalize_attribute_selectors(selector);
+      }
+
+      let mut i = 1;
+      while i < self.selectors.0.len() {
+        if self.selectors.0[..i].contains(&self.selectors.0[i]) {
+          self.selectors.0.remove(i);
+        } else {
+          i += 1;
         }
+      }
+    }
 
-        self.declarations.declarations.clear();
-        self.declarations.important_declarations.clear();
-        unused = true;
+    // Selectors that require unused symbols are removed individually. If none remain, the
+    // selectors are kept so that nested rules can still be minified against them.
+    let purged = context.purge.map_or(false, |purge| {
+      if self
+        .selectors
+        .0
+        .iter()
+        .all(|selector| purge.is_unused(selector, parent_is_unused))
+      {
+        return true;
       }
+      self.selectors.0.retain(|selector| !purge.is_unused(selector, parent_is_unused));
+      false
+    });
+
+    let mut unused = false;
+    if purged
+      || (!context.unused_symbols.is_empty()
+        && is_unused(&mut self.selectors.0.iter(), &context.unused_symbols, parent_is_unused))
+    {
+      if self.rules.0.is_empty() {
+        return Ok(true);
+      }
+
+      self.declarations.declarations.clear();
+      self.declarations.important_declarations.clear();
+      unused = true;
     }
 
     context.handler_context.context = DeclarationContext::StyleRule;
+    let original = if context.handler_context.color_fallback_strategy != ColorFallbackStrategy::Inline {
+      Some(self.declarations.clone())
+    } else {
+      None
+    };
     self
       .declarations
       .minify(context.handler, context.important_handler, context.handler_context);
+    if let Some(original) = original {
+      context.handler_context.add_wide_gamut_declarations(
+        original,
+        &self.declarations,
+        context.handler,
+        context.important_handler,
+      );
+    }
     context.handler_context.context = DeclarationContext::None;
 
     if !self.rules.0.is_empty() {
+      // Nested rules add their own wide-gamut declarations, so keep ours until the rule is added.
+      let wide_gamut = context.handler_context.wide_gamut.take();
       self.rules.minify(context, unused)?;
+      context.handler_context.wide_gamut = wide_gamut;
       if unused && self.rules.0.is_empty() {
         return Ok(true);
       }
@@ -95,6 +217,28 @@ impl<'i, T> StyleRule<'i, T> {
     is_compatible(&self.selectors, targets)
   }
 
+  /// Collects the features used by the selectors and declarations of the rule and its nested rules
+  /// that are not supported by all of the targets and cannot be transpiled.
+  pub(crate) fn compat_issues(&self, targets: Browsers, issues: &mut Vec<(Feature, String, Location)>) {
+    for selector in &self.selectors.0 {
+      for feature in unsupported_features(selector, targets) {
+        let value = selector.to_css_string(PrinterOptions::default()).unwrap_or_default();
+        issues.push((feature, value, self.loc));
+      }
+    }
+
+    for (property, important) in self.declarations.iter() {
+      if let Some(feature) = untranspilable_feature(property) {
+        if !feature.is_compatible(targets) {
+          let value = property.to_css_string(important, PrinterOptions::default()).unwrap_or_default();
+          issues.push((feature, value, self.loc));
+        }
+      }
+    }
+
+    self.rules.compat_issues(targets, issues);
+  }
+
   /// Returns the line and column range of the property key and value at the given index in this style rule.
   ///
   /// For performance and memory efficiency in non-error cases, source locations are not stored during parsing.
@@ -208,10 +352,31 @@ impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
     let len = self.declarations.declarations.len() + self.declarations.important_declarations.len();
     let has_declarations = supports_nesting || len > 0 || self.rules.0.is_empty();
 
+    // When `light-dark()` is not supported, declarations need to be repeated for the dark color scheme.
+    let light_dark_mode = if dest.targets.map_or(false, |targets| !Feature::LightDark.is_compatible(targets)) {
+      Some(dest.light_dark_mode)
+    } else {
+      None
+    };
+    let mut dark_declarations = Vec::new();
+    let mut dark_color_scheme = false;
+
+    // When compiling nesting with the expand mode, the nesting selectors are replaced with each of the
+    // parent selectors. The expanded selectors are also used as the parents of nested rules.
+    let expanded;
+    let selectors = match dest.context() {
+      Some(ctx) if dest.nesting_mode == NestingMode::Expand => {
+        // The parent selectors outlive this call, but their lifetime parameter is tied to the printer rather than the rule.
+        expanded = expand_nesting(&self.selectors, unsafe { std::mem::transmute(ctx.selectors) });
+        &expanded
+      }
+      _ => &self.selectors,
+    };
+
     if has_declarations {
       #[cfg(feature = "sourcemap")]
       dest.add_mapping(self.loc);
-      self.selectors.to_css(dest)?;
+      selectors.to_css(dest)?;
       dest.whitespace()?;
       dest.write_char('{')?;
       dest.indent();
@@ -219,7 +384,9 @@ impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
       let mut i = 0;
       macro_rules! write {
         ($decls: ident, $important: literal) => {
-          for decl in &self.declarations.$decls {
+          for (index, decl) in self.declarations.$decls.iter().enumerate() {
+            self.declarations.write_comments($important, index, dest)?;
+
             // The CSS modules `composes` property is handled specially, and omitted during printing.
             // We need to add the classes it references to the list for the selectors in this rule.
             if let crate::properties::Property::Composes(composes) = &decl {
@@ -235,14 +402,31 @@ impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
               }
             }
 
+            if light_dark_mode == Some(LightDarkMode::CustomProperties) {
+              if let Property::ColorScheme(color_scheme) = &decl {
+                let dark = color_scheme.dark && !color_scheme.light;
+                write_light_dark_properties(dest, dark, false)?;
+                dark_color_scheme |= color_scheme.light && color_scheme.dark;
+              }
+            }
+
             dest.newline()?;
+            dest.has_light_dark = false;
             decl.to_css(dest, $important)?;
-            if i != len - 1 || !dest.minify {
+            // A semicolon is also required when nested rules follow the declarations.
+            if i != len - 1 || !dest.minify || (supports_nesting && !self.rules.0.is_empty()) {
               dest.write_char(';')?;
             }
 
+            if dest.has_light_dark {
+              dark_declarations.push((decl, $important));
+            }
+
             i += 1;
           }
+          self
+            .declarations
+            .write_comments($important, self.declarations.$decls.len(), dest)?;
         };
       }
 
@@ -267,6 +451,42 @@ impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
           dest.dedent();
           dest.newline()?;
           dest.write_char('}')?;
+
+          if !dark_declarations.is_empty() || dark_color_scheme {
+            if !dest.minify {
+              dest.write_char('\n')?; // no indent
+            }
+            dest.newline()?;
+            dest.write_str("@media (prefers-color-scheme")?;
+            dest.delim(':', false)?;
+            dest.write_str("dark)")?;
+            dest.whitespace()?;
+            dest.write_char('{')?;
+            dest.indent();
+            dest.newline()?;
+            selectors.to_css(dest)?;
+            dest.whitespace()?;
+            dest.write_char('{')?;
+            dest.indent();
+            if dark_color_scheme {
+              write_light_dark_properties(dest, true, dark_declarations.is_empty())?;
+            }
+            dest.dark_color_scheme = true;
+            for (i, (decl, important)) in dark_declarations.iter().enumerate() {
+              dest.newline()?;
+              decl.to_css(dest, *important)?;
+              if i != dark_declarations.len() - 1 || !dest.minify {
+                dest.write_char(';')?;
+              }
+            }
+            dest.dark_color_scheme = false;
+            dest.dedent();
+            dest.newline()?;
+            dest.write_char('}')?;
+            dest.dedent();
+            dest.newline()?;
+            dest.write_char('}')?;
+          }
         }
       };
     }
@@ -279,9 +499,57 @@ impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
     } else {
       end!();
       newline!();
-      dest.with_context(&self.selectors, |dest| self.rules.to_css(dest))?;
+      dest.with_context(selectors, |dest| self.rules.to_css(dest))?;
     }
 
     Ok(())
   }
 }
+
+/// Writes the custom properties that select the light or dark color of `light-dark()` colors
+/// compiled with [LightDarkMode::CustomProperties](LightDarkMode::CustomProperties). The unused
+/// color substitutes to whitespace, which older browsers require instead of an empty value.
+fn write_light_dark_properties<W>(dest: &mut Printer<W>, dark: bool, last: bool) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  let properties = [("--lightningcss-light", !dark), ("--lightningcss-dark", dark)];
+  for (i, (name, active)) in properties.into_iter().enumerate() {
+    dest.newline()?;
+    dest.write_str(name)?;
+    dest.write_char(':')?;
+    if active {
+      dest.whitespace()?;
+      dest.write_str("initial")?;
+    } else {
+      dest.write_char(' ')?;
+    }
+
+    if i == 0 || !last || !dest.minify {
+      dest.write_char(';')?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Returns the compatibility feature required by a property that cannot be transpiled, if any.
+pub(crate) fn untranspilable_feature(property: &Property) -> Option<Feature> {
+  Some(match property {
+    Property::Display(Display::Pair(DisplayPair {
+      inside: DisplayInside::Grid,
+      ..
+    })) => Feature::CssGrid,
+    Property::Position(Position::Sticky(prefix)) if *prefix == VendorPrefix::None => Feature::CssSticky,
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Custom(..),
+      ..
+    }) => Feature::CssVariables,
+    Property::FieldSizing(FieldSizing::Content) => Feature::FieldSizing,
+    #[cfg(feature = "grid")]
+    property if property_uses_masonry(property) => Feature::Masonry,
+    property if property_uses_anchor_positioning(property) => Feature::AnchorPositioning,
+    property if property_allows_discrete_transitions(property) => Feature::TransitionBehavior,
+    property => return property_view_transition_feature(property),
+  })
+}
@@ -2,12 +2,20 @@
 
 use super::Location;
 use super::{CssRuleList, MinifyContext};
+use crate::compat::Feature;
 use crate::error::{MinifyError, ParserError, PrinterError};
 use crate::parser::DefaultAtRule;
+use crate::parser::ParserOptions;
 use crate::printer::Printer;
-use crate::properties::PropertyId;
+use crate::properties::custom::{CustomProperty, CustomPropertyName};
+use crate::properties::display::{Display, DisplayInside, DisplayPair};
+use crate::properties::position::Position;
+use crate::properties::{Property, PropertyId};
+use crate::rules::font_face::{FontFormat, FontTechnology};
+use crate::selector::{is_compatible, is_unsupported, SelectorList};
 use crate::targets::Browsers;
-use crate::traits::{Parse, ToCss};
+use crate::traits::{Parse, ParseWithOptions, ToCss};
+use crate::values::color::{CssColor, LABColor, PredefinedColor};
 use crate::values::string::CowArcStr;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
@@ -103,7 +111,12 @@ pub enum SupportsCondition<'i> {
   /// A selector to evaluate.
   #[cfg_attr(feature = "serde", serde(with = "ValueWrapper::<CowArcStr>"))]
   Selector(CowArcStr<'i>),
-  // FontTechnology()
+  /// A font technology to evaluate.
+  #[cfg_attr(feature = "serde", serde(with = "ValueWrapper::<FontTechnology>"))]
+  FontTechnology(FontTechnology),
+  /// A font format to evaluate.
+  #[cfg_attr(feature = "serde", serde(borrow, with = "ValueWrapper::<FontFormat>"))]
+  FontFormat(FontFormat<'i>),
   /// An unknown condition.
   #[cfg_attr(feature = "serde", serde(with = "ValueWrapper::<CowArcStr>"))]
   Unknown(CowArcStr<'i>),
@@ -132,6 +145,75 @@ impl<'i> SupportsCondition<'i> {
     }
   }
 
+  /// Evaluates the condition against the compatibility data for the given browser targets. Returns
+  /// `Some(true)` if the condition matches in all of the targets, `Some(false)` if it matches in none
+  /// of them, and `None` if this cannot be determined, e.g. for properties without compatibility data.
+  pub(crate) fn evaluate(&self, targets: Browsers) -> Option<bool> {
+    match self {
+      SupportsCondition::Not(cond) => cond.evaluate(targets).map(|supported| !supported),
+      SupportsCondition::And(items) => {
+        let mut result = Some(true);
+        for item in items {
+          match item.evaluate(targets) {
+            Some(false) => return Some(false),
+            Some(true) => {}
+            None => result = None,
+          }
+        }
+        result
+      }
+      SupportsCondition::Or(items) => {
+        let mut result = Some(false);
+        for item in items {
+          match item.evaluate(targets) {
+            Some(true) => return Some(true),
+            Some(false) => {}
+            None => result = None,
+          }
+        }
+        result
+      }
+      SupportsCondition::Declaration { property_id, value } => {
+        if property_id.prefix() != VendorPrefix::None && !property_id.prefix().is_empty() {
+          return None;
+        }
+
+        let property =
+          Property::parse_string(property_id.clone(), value.as_ref(), ParserOptions::default()).ok()?;
+        let feature = declaration_feature(&property)?;
+        if feature.is_compatible(targets) {
+          Some(true)
+        } else if !feature.is_partially_compatible(targets) {
+          Some(false)
+        } else {
+          None
+        }
+      }
+      SupportsCondition::Selector(selector) => {
+        if !Feature::SupportsSelector.is_partially_compatible(targets) {
+          return Some(false);
+        }
+
+        let selectors =
+          SelectorList::parse_string_with_options(selector.as_ref(), ParserOptions::default()).ok()?;
+        if is_unsupported(&selectors, targets) {
+          Some(false)
+        } else if Feature::SupportsSelector.is_compatible(targets) && is_compatible(&selectors, Some(targets)) {
+          Some(true)
+        } else {
+          None
+        }
+      }
+      SupportsCondition::FontTechnology(technology) => {
+        evaluate_function(Feature::SupportsFontTech, font_technology_support(technology), targets)
+      }
+      SupportsCondition::FontFormat(format) => {
+        evaluate_function(Feature::SupportsFontFormat, font_format_support(format), targets)
+      }
+      SupportsCondition::Unknown(..) => None,
+    }
+  }
+
   fn set_prefixes_for_targets(&mut self, targets: &Browsers) {
     match self {
       SupportsCondition::Not(cond) => cond.set_prefixes_for_targets(targets),
@@ -151,6 +233,94 @@ impl<'i> SupportsCondition<'i> {
   }
 }
 
+/// Evaluates a `font-tech()` or `font-format()` test, given whether the argument is supported
+/// by all browsers that implement the function itself (`Some(true)`), by none of them (`Some(false)`),
+/// or only some of them (`None`).
+fn evaluate_function(function: Feature, argument: Option<bool>, targets: Browsers) -> Option<bool> {
+  if argument == Some(false) || !function.is_partially_compatible(targets) {
+    Some(false)
+  } else if argument == Some(true) && function.is_compatible(targets) {
+    Some(true)
+  } else {
+    None
+  }
+}
+
+fn font_technology_support(technology: &FontTechnology) -> Option<bool> {
+  match technology {
+    // Supported by every browser that shipped `font-tech()`.
+    FontTechnology::FeaturesOpentype
+    | FontTechnology::ColorCOLRv0
+    | FontTechnology::Variations
+    | FontTechnology::Palettes => Some(true),
+    // Not implemented by any browser.
+    FontTechnology::Incremental => Some(false),
+    _ => None,
+  }
+}
+
+fn font_format_support(format: &FontFormat) -> Option<bool> {
+  match format {
+    // Supported by every browser that shipped `font-format()`.
+    FontFormat::WOFF | FontFormat::WOFF2 | FontFormat::TrueType | FontFormat::OpenType => Some(true),
+    // Only supported by Internet Explorer, which doesn't support `@supports`.
+    FontFormat::EmbeddedOpenType => Some(false),
+    _ => None,
+  }
+}
+
+/// Returns the compatibility feature that determines whether a parsed `@supports` declaration is supported.
+fn declaration_feature(property: &Property) -> Option<Feature> {
+  Some(match property {
+    Property::Display(Display::Pair(DisplayPair {
+      inside: DisplayInside::Grid,
+      ..
+    })) => Feature::CssGrid,
+    Property::Position(Position::Sticky(prefix)) if *prefix == VendorPrefix::None => Feature::CssSticky,
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Custom(..),
+      ..
+    }) => Feature::CssVariables,
+    Property::PlaceContent(..) => Feature::PlaceContent,
+    Property::PlaceItems(..) => Feature::PlaceItems,
+    Property::PlaceSelf(..) => Feature::PlaceSelf,
+    Property::MarginBlockStart(..)
+    | Property::MarginBlockEnd(..)
+    | Property::MarginInlineStart(..)
+    | Property::MarginInlineEnd(..) => Feature::LogicalMargin,
+    Property::MarginBlock(..) | Property::MarginInline(..) => Feature::LogicalMarginShorthand,
+    Property::PaddingBlockStart(..)
+    | Property::PaddingBlockEnd(..)
+    | Property::PaddingInlineStart(..)
+    | Property::PaddingInlineEnd(..) => Feature::LogicalPadding,
+    Property::PaddingBlock(..) | Property::PaddingInline(..) => Feature::LogicalPaddingShorthand,
+    Property::InsetBlockStart(..)
+    | Property::InsetBlockEnd(..)
+    | Property::InsetInlineStart(..)
+    | Property::InsetInlineEnd(..) => Feature::LogicalInset,
+    Property::BlockSize(..)
+    | Property::InlineSize(..)
+    | Property::MinBlockSize(..)
+    | Property::MinInlineSize(..)
+    | Property::MaxBlockSize(..)
+    | Property::MaxInlineSize(..) => Feature::LogicalSize,
+    Property::Color(color) | Property::BackgroundColor(color) => match color {
+      CssColor::LAB(lab) => match &**lab {
+        LABColor::LAB(..) | LABColor::LCH(..) => Feature::LabColors,
+        // The compatibility data for oklab colors is intentionally empty so that fallbacks are
+        // always generated, which would make every condition appear unsupported.
+        LABColor::OKLAB(..) | LABColor::OKLCH(..) => return None,
+      },
+      CssColor::Predefined(predefined) => match &**predefined {
+        PredefinedColor::DisplayP3(..) => Feature::P3Colors,
+        _ => Feature::ColorFunction,
+      },
+      _ => return None,
+    },
+    _ => return None,
+  })
+}
+
 impl<'i> Parse<'i> for SupportsCondition<'i> {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("not")).is_ok() {
@@ -223,6 +393,35 @@ impl<'i> SupportsCondition<'i> {
               return res
             }
           },
+          "font-tech" => {
+            let res = input.try_parse(|input| {
+              input.parse_nested_block(|input| {
+                let technology = FontTechnology::parse(input)?;
+                input.expect_exhausted()?;
+                Ok(SupportsCondition::FontTechnology(technology))
+              })
+            });
+            if res.is_ok() {
+              return res
+            }
+          },
+          "font-format" => {
+            let res = input.try_parse(|input| {
+              input.parse_nested_block(|input| {
+                let location = input.current_source_location();
+                let ident = input.expect_ident()?;
+                let format = match FontFormat::from_keyword(ident) {
+                  Some(format) => format,
+                  None => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone()))),
+                };
+                input.expect_exhausted()?;
+                Ok(SupportsCondition::FontFormat(format))
+              })
+            });
+            if res.is_ok() {
+              return res
+            }
+          },
           _ => {}
         }
       }
@@ -353,6 +552,16 @@ impl<'i> ToCss for SupportsCondition<'i> {
         dest.write_str(sel)?;
         dest.write_char(')')
       }
+      SupportsCondition::FontTechnology(technology) => {
+        dest.write_str("font-tech(")?;
+        technology.to_css(dest)?;
+        dest.write_char(')')
+      }
+      SupportsCondition::FontFormat(format) => {
+        dest.write_str("font-format(")?;
+        dest.write_str(format.as_str())?;
+        dest.write_char(')')
+      }
       SupportsCondition::Unknown(unknown) => dest.write_str(&unknown),
     }
   }
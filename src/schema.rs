@@ -0,0 +1,92 @@
+//! Versioning of the serialized AST.
+//!
+//! With the `serde` feature, a [StyleSheet](crate::stylesheet::StyleSheet) can be serialized to and from JSON,
+//! e.g. for processing by external tools. The shape of the JSON is described by a JSON schema, which is generated
+//! from the Rust types with the `jsonschema` feature by the `json_schema` function. Serialized style sheets include
+//! a `version` field containing the [AST_VERSION](AST_VERSION), so tools can check that they understand the format.
+//!
+//! # Compatibility
+//!
+//! The version is incremented whenever the format changes in a way that may break existing consumers or producers,
+//! such as removing or renaming a field, rule type, property, or value variant, or changing the type of a field.
+//! Additive changes, such as support for new properties, rules, and values, or new optional fields, do not change
+//! the version, so consumers should be prepared to encounter variants they do not know about.
+//!
+//! Deserialization accepts style sheets with any version from [MIN_AST_VERSION](MIN_AST_VERSION) to
+//! [AST_VERSION](AST_VERSION), and style sheets without a `version` field, which are assumed to use the current
+//! version. Style sheets with a newer version are rejected with an error rather than misinterpreted. Serialization
+//! always produces the current version.
+//!
+//! # Deprecation policy
+//!
+//! A breaking change to the format is preceded by at least one minor release in which the affected part of the format
+//! is documented as deprecated, and the new form is accepted when deserializing alongside the old one. The version is
+//! then incremented with the breaking change, and [MIN_AST_VERSION](MIN_AST_VERSION) is only raised to drop support
+//! for older versions in a major release.
+
+/// The version of the serialized AST produced by this release.
+pub const AST_VERSION: u32 = 1;
+
+/// The oldest version of the serialized AST that this release can deserialize.
+pub const MIN_AST_VERSION: u32 = 1;
+
+/// The `version` field of a serialized style sheet, which is always serialized as the current
+/// [AST_VERSION](AST_VERSION), and validated when deserializing.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AstVersion;
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AstVersion {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    serializer.serialize_u32(AST_VERSION)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AstVersion {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    let version = u32::deserialize(deserializer)?;
+    if !(MIN_AST_VERSION..=AST_VERSION).contains(&version) {
+      return Err(serde::de::Error::custom(format!(
+        "unsupported AST version {}, expected a version from {} to {}",
+        version, MIN_AST_VERSION, AST_VERSION
+      )));
+    }
+    Ok(AstVersion)
+  }
+}
+
+#[cfg(feature = "jsonschema")]
+impl schemars::JsonSchema for AstVersion {
+  fn is_referenceable() -> bool {
+    true
+  }
+
+  fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    schemars::schema::SchemaObject {
+      instance_type: Some(schemars::schema::InstanceType::Integer.into()),
+      const_value: Some(AST_VERSION.into()),
+      ..Default::default()
+    }
+    .into()
+  }
+
+  fn schema_name() -> String {
+    "AstVersion".into()
+  }
+}
+
+/// Returns the JSON schema for a serialized [StyleSheet](crate::stylesheet::StyleSheet) with the default
+/// at-rule type, which requires the `version` field to be the current [AST_VERSION](AST_VERSION).
+#[cfg(feature = "jsonschema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+pub fn json_schema() -> schemars::schema::RootSchema {
+  schemars::schema_for!(crate::stylesheet::StyleSheet)
+}
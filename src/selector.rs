@@ -1,14 +1,15 @@
 //! CSS selectors.
 
 use crate::compat::Feature;
+use crate::css_modules::LocalGlobalMode;
 use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
 use crate::properties::custom::TokenList;
-use crate::rules::StyleContext;
+use crate::rules::{Location, StyleContext};
 use crate::stylesheet::{ParserOptions, PrinterOptions};
 use crate::targets::Browsers;
 use crate::traits::{Parse, ParseWithOptions, ToCss};
-use crate::values::ident::Ident;
+use crate::values::ident::{CustomIdent, Ident};
 use crate::values::string::CSSString;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
@@ -17,10 +18,10 @@ use crate::{macros::enum_property, values::string::CowArcStr};
 use cssparser::*;
 use parcel_selectors::parser::SelectorParseErrorKind;
 use parcel_selectors::{
-  attr::{AttrSelectorOperator, ParsedAttrSelectorOperation, ParsedCaseSensitivity},
+  attr::{AttrSelectorOperator, NamespaceConstraint, ParsedAttrSelectorOperation, ParsedCaseSensitivity},
   parser::SelectorImpl,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt;
 
 #[cfg(feature = "serde")]
@@ -148,6 +149,11 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
       "-webkit-autofill" => Autofill(VendorPrefix::WebKit),
       "-o-autofill" => Autofill(VendorPrefix::O),
 
+      // https://html.spec.whatwg.org/multipage/semantics-other.html#selector-popover-open
+      "popover-open" => PopoverOpen,
+      // https://html.spec.whatwg.org/multipage/semantics-other.html#selector-modal
+      "modal" => Modal,
+
       // https://webkit.org/blog/363/styling-scrollbars/
       "horizontal" => WebKitScrollbar(WebKitScrollbarPseudoClass::Horizontal),
       "vertical" => WebKitScrollbar(WebKitScrollbarPseudoClass::Vertical),
@@ -162,6 +168,11 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
       "window-inactive" => WebKitScrollbar(WebKitScrollbarPseudoClass::WindowInactive),
 
       _ => {
+        // Legacy custom state syntax, e.g. :--checked.
+        if name.len() > 2 && name.starts_with("--") {
+          return Ok(State { state: Ident(name[2..].to_owned().into()), legacy: true });
+        }
+
         if !name.starts_with('-') {
           self.options.warn(loc.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())));
         }
@@ -188,8 +199,16 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
         Lang { languages }
       },
       "dir" => Dir { direction: Direction::parse(parser)? },
-      "local" if self.options.css_modules.is_some() => Local { selector: Box::new(Selector::parse(self, parser)?) },
-      "global" if self.options.css_modules.is_some() => Global { selector: Box::new(Selector::parse(self, parser)?) },
+      "state" => State { state: Ident::parse(parser)?, legacy: false },
+      "local" if self.options.css_modules.is_some() || self.options.local_global == LocalGlobalMode::Strip => Local { selector: Box::new(Selector::parse(self, parser)?) },
+      "global" if self.options.css_modules.is_some() || self.options.local_global == LocalGlobalMode::Strip => Global { selector: Box::new(Selector::parse(self, parser)?) },
+      "local" | "global" if self.options.local_global == LocalGlobalMode::Error => {
+        return Err(parser.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())))
+      },
+      "local" | "global" if self.options.local_global == LocalGlobalMode::Preserve => CustomFunction {
+        name: name.into(),
+        arguments: TokenList::parse(parser, self.options, 0)?
+      },
       _ => {
         if !name.starts_with('-') {
           self.options.warn(parser.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())));
@@ -246,6 +265,9 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
       "-webkit-scrollbar-corner" => WebKitScrollbar(WebKitScrollbarPseudoElement::Corner),
       "-webkit-resizer" => WebKitScrollbar(WebKitScrollbarPseudoElement::Resizer),
 
+      "view-transition" => ViewTransition,
+      "details-content" => DetailsContent,
+
       _ => {
         if !name.starts_with('-') {
           self.options.warn(loc.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())));
@@ -266,6 +288,10 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
     let pseudo_element = match_ignore_ascii_case! { &name,
       "cue" => CueFunction { selector: Box::new(Selector::parse(self, arguments)?) },
       "cue-region" => CueRegionFunction { selector: Box::new(Selector::parse(self, arguments)?) },
+      "view-transition-group" => ViewTransitionGroup { part: ViewTransitionPartSelector::parse(arguments)? },
+      "view-transition-image-pair" => ViewTransitionImagePair { part: ViewTransitionPartSelector::parse(arguments)? },
+      "view-transition-old" => ViewTransitionOld { part: ViewTransitionPartSelector::parse(arguments)? },
+      "view-transition-new" => ViewTransitionNew { part: ViewTransitionPartSelector::parse(arguments)? },
       _ => {
         if !name.starts_with('-') {
           self.options.warn(arguments.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())));
@@ -445,6 +471,20 @@ pub enum PseudoClass<'i> {
   #[cfg_attr(feature = "serde", serde(with = "PrefixWrapper"))]
   Autofill(VendorPrefix),
 
+  /// The [:popover-open](https://html.spec.whatwg.org/multipage/semantics-other.html#selector-popover-open) pseudo class.
+  PopoverOpen,
+  /// The [:modal](https://html.spec.whatwg.org/multipage/semantics-other.html#selector-modal) pseudo class.
+  Modal,
+
+  /// The [:state()](https://html.spec.whatwg.org/multipage/semantics-other.html#selector-custom) pseudo class,
+  /// which matches a custom state of a custom element.
+  State {
+    /// The name of the custom state.
+    state: Ident<'i>,
+    /// Whether the state was written using the legacy dashed syntax, e.g. `:--checked`.
+    legacy: bool,
+  },
+
   // CSS modules
   /// The CSS modules :local() pseudo class.
   Local {
@@ -583,6 +623,25 @@ where
       dir.to_css(dest)?;
       return dest.write_str(")");
     }
+    State { state, legacy } => {
+      // Use the legacy dashed syntax if it was authored that way, or if
+      // the targets do not support :state(). If the targets support :state(),
+      // upgrade legacy selectors to the standard syntax.
+      let use_legacy = match dest.targets {
+        Some(targets) => !Feature::StatePseudoClass.is_compatible(targets),
+        None => *legacy,
+      };
+
+      if use_legacy {
+        dest.write_str(":--")?;
+        serialize_name(&state.0, dest)?;
+        return Ok(());
+      }
+
+      dest.write_str(":state(")?;
+      state.to_css(dest)?;
+      return dest.write_str(")");
+    }
     _ => {}
   }
 
@@ -688,6 +747,9 @@ where
     // https://html.spec.whatwg.org/multipage/semantics-other.html#selector-autofill
     Autofill(prefix) => write_prefixed!(prefix, "autofill"),
 
+    PopoverOpen => dest.write_str(":popover-open"),
+    Modal => dest.write_str(":modal"),
+
     Local { selector } => serialize_selector(selector, dest, context, false),
     Global { selector } => {
       let css_module = std::mem::take(&mut dest.css_module);
@@ -714,7 +776,7 @@ where
       })
     }
 
-    Lang { languages: _ } | Dir { direction: _ } => unreachable!(),
+    Lang { languages: _ } | Dir { direction: _ } | State { .. } => unreachable!(),
     Custom { name } => {
       dest.write_char(':')?;
       return dest.write_str(&name);
@@ -819,6 +881,30 @@ pub enum PseudoElement<'i> {
     /// The selector argument.
     selector: Box<Selector<'i>>,
   },
+  /// The [::details-content](https://drafts.csswg.org/css-pseudo-4/#details-content-pseudo) pseudo element.
+  DetailsContent,
+  /// The [::view-transition](https://drafts.csswg.org/css-view-transitions-1/#selectordef-view-transition) pseudo element.
+  ViewTransition,
+  /// The [::view-transition-group()](https://drafts.csswg.org/css-view-transitions-1/#selectordef-view-transition-group) functional pseudo element.
+  ViewTransitionGroup {
+    /// The view transition part selector.
+    part: ViewTransitionPartSelector<'i>,
+  },
+  /// The [::view-transition-image-pair()](https://drafts.csswg.org/css-view-transitions-1/#selectordef-view-transition-image-pair) functional pseudo element.
+  ViewTransitionImagePair {
+    /// The view transition part selector.
+    part: ViewTransitionPartSelector<'i>,
+  },
+  /// The [::view-transition-old()](https://drafts.csswg.org/css-view-transitions-1/#selectordef-view-transition-old) functional pseudo element.
+  ViewTransitionOld {
+    /// The view transition part selector.
+    part: ViewTransitionPartSelector<'i>,
+  },
+  /// The [::view-transition-new()](https://drafts.csswg.org/css-view-transitions-1/#selectordef-view-transition-new) functional pseudo element.
+  ViewTransitionNew {
+    /// The view transition part selector.
+    part: ViewTransitionPartSelector<'i>,
+  },
   /// An unknown pseudo element.
   Custom {
     /// The name of the pseudo element.
@@ -859,6 +945,110 @@ pub enum WebKitScrollbarPseudoElement {
   Resizer,
 }
 
+/// A view transition part name, as used in the argument of a view transition pseudo element.
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ViewTransitionPartName<'i> {
+  /// `*`, which matches all view transition names.
+  All,
+  /// A view transition name.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Name(CustomIdent<'i>),
+}
+
+impl<'i> Parse<'i> for ViewTransitionPartName<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_delim('*')).is_ok() {
+      return Ok(ViewTransitionPartName::All);
+    }
+
+    Ok(ViewTransitionPartName::Name(CustomIdent::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for ViewTransitionPartName<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: fmt::Write,
+  {
+    match self {
+      ViewTransitionPartName::All => dest.write_char('*'),
+      ViewTransitionPartName::Name(name) => name.to_css(dest),
+    }
+  }
+}
+
+/// A [view transition part selector](https://drafts.csswg.org/css-view-transitions-2/#typedef-pt-name-and-class-selector),
+/// i.e. the argument of a view transition pseudo element such as `::view-transition-group(*.card)`.
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct ViewTransitionPartSelector<'i> {
+  /// The view transition name, or `*`.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: Option<ViewTransitionPartName<'i>>,
+  /// A list of view transition classes.
+  pub classes: Vec<CustomIdent<'i>>,
+}
+
+impl<'i> Parse<'i> for ViewTransitionPartSelector<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.skip_whitespace();
+    let name = input.try_parse(ViewTransitionPartName::parse).ok();
+
+    // Classes must immediately follow the name, with no whitespace in between.
+    let mut classes = Vec::new();
+    while let Ok(class) = input.try_parse(|input| -> Result<_, ParseError<'i, ParserError<'i>>> {
+      let location = input.current_source_location();
+      match input.next_including_whitespace()? {
+        Token::Delim('.') => {}
+        t => return Err(location.new_unexpected_token_error(t.clone())),
+      }
+
+      let location = input.current_source_location();
+      match input.next_including_whitespace()? {
+        Token::Ident(class) => Ok(CustomIdent(class.into())),
+        t => Err(location.new_unexpected_token_error(t.clone())),
+      }
+    }) {
+      classes.push(class);
+    }
+
+    if name.is_none() && classes.is_empty() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(ViewTransitionPartSelector { name, classes })
+  }
+}
+
+impl<'i> ToCss for ViewTransitionPartSelector<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: fmt::Write,
+  {
+    if let Some(name) = &self.name {
+      name.to_css(dest)?;
+    }
+
+    for class in &self.classes {
+      dest.write_char('.')?;
+      class.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
 impl<'i> cssparser::ToCss for PseudoElement<'i> {
   fn to_css<W>(&self, _: &mut W) -> std::fmt::Result
   where
@@ -952,6 +1142,28 @@ where
         Resizer => "::-webkit-resizer",
       })
     }
+    DetailsContent => dest.write_str("::details-content"),
+    ViewTransition => dest.write_str("::view-transition"),
+    ViewTransitionGroup { part } => {
+      dest.write_str("::view-transition-group(")?;
+      part.to_css(dest)?;
+      dest.write_char(')')
+    }
+    ViewTransitionImagePair { part } => {
+      dest.write_str("::view-transition-image-pair(")?;
+      part.to_css(dest)?;
+      dest.write_char(')')
+    }
+    ViewTransitionOld { part } => {
+      dest.write_str("::view-transition-old(")?;
+      part.to_css(dest)?;
+      dest.write_char(')')
+    }
+    ViewTransitionNew { part } => {
+      dest.write_str("::view-transition-new(")?;
+      part.to_css(dest)?;
+      dest.write_char(')')
+    }
     Custom { name: val } => {
       dest.write_str("::")?;
       return dest.write_str(val);
@@ -991,6 +1203,16 @@ impl<'i> parcel_selectors::parser::PseudoElement<'i> for PseudoElement<'i> {
   fn is_webkit_scrollbar(&self) -> bool {
     matches!(*self, PseudoElement::WebKitScrollbar(..))
   }
+
+  fn is_view_transition(&self) -> bool {
+    matches!(
+      *self,
+      PseudoElement::ViewTransitionGroup { .. }
+        | PseudoElement::ViewTransitionImagePair { .. }
+        | PseudoElement::ViewTransitionNew { .. }
+        | PseudoElement::ViewTransitionOld { .. }
+    )
+  }
 }
 
 impl<'i> PseudoElement<'i> {
@@ -1312,6 +1534,12 @@ where
       dest.write_str(")")
     }
     Component::Has(ref list) => {
+      if let Some(attr) = dest.has_polyfill {
+        if dest.targets.map_or(true, |targets| !Feature::CssHas.is_compatible(targets)) {
+          return serialize_has_polyfill(list, attr, dest);
+        }
+      }
+
       dest.write_str(":has(")?;
       serialize_selector_list(list.iter(), dest, context, true)?;
       dest.write_str(")")
@@ -1348,6 +1576,39 @@ where
   }
 }
 
+fn serialize_has_polyfill<W>(list: &[Selector], attr: &str, dest: &mut Printer<W>) -> Result<(), PrinterError>
+where
+  W: fmt::Write,
+{
+  let mut selector = String::new();
+  let mut printer = Printer::new(
+    &mut selector,
+    PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    },
+  );
+  serialize_selector_list(list.iter(), &mut printer, None, true)?;
+
+  let polyfills = dest.has_polyfills.get_or_insert_with(Vec::new);
+  let id = if let Some(polyfill) = polyfills.iter().find(|p| p.selector == selector) {
+    polyfill.id.clone()
+  } else {
+    let id = polyfills.len().to_string();
+    polyfills.push(HasPolyfill {
+      id: id.clone(),
+      selector,
+    });
+    id
+  };
+
+  dest.write_char('[')?;
+  dest.write_str(attr)?;
+  dest.write_str("~=\"")?;
+  dest.write_str(&id)?;
+  dest.write_str("\"]")
+}
+
 fn serialize_nesting<W>(
   dest: &mut Printer<W>,
   context: Option<&StyleContext>,
@@ -1473,170 +1734,234 @@ pub(crate) fn is_compatible(selectors: &SelectorList, targets: Option<Browsers>)
   for selector in &selectors.0 {
     let iter = selector.iter();
     for component in iter {
-      let feature = match component {
-        Component::ID(_) | Component::Class(_) | Component::LocalName(_) => continue,
-
-        Component::ExplicitAnyNamespace
-        | Component::ExplicitNoNamespace
-        | Component::DefaultNamespace(_)
-        | Component::Namespace(_, _) => Feature::CssNamespaces,
-
-        Component::ExplicitUniversalType => Feature::CssSel2,
+      let feature = match component_support(component) {
+        ComponentSupport::Always => continue,
+        ComponentSupport::Feature(feature) => feature,
+        ComponentSupport::Unknown => return false,
+      };
 
-        Component::AttributeInNoNamespaceExists { .. } => Feature::CssSel2,
-        Component::AttributeInNoNamespace {
-          operator,
-          case_sensitivity,
-          ..
-        } => {
-          if *case_sensitivity != ParsedCaseSensitivity::CaseSensitive {
-            Feature::CssCaseInsensitive
-          } else {
-            match operator {
-              AttrSelectorOperator::Equal | AttrSelectorOperator::Includes | AttrSelectorOperator::DashMatch => {
-                Feature::CssSel2
-              }
-              AttrSelectorOperator::Prefix | AttrSelectorOperator::Substring | AttrSelectorOperator::Suffix => {
-                Feature::CssSel3
-              }
-            }
-          }
+      if let Some(targets) = targets {
+        if !feature.is_compatible(targets) {
+          return false;
         }
-        Component::AttributeOther(attr) => match attr.operation {
-          ParsedAttrSelectorOperation::Exists => Feature::CssSel2,
-          ParsedAttrSelectorOperation::WithValue {
-            operator,
-            case_sensitivity,
-            ..
-          } => {
-            if case_sensitivity != ParsedCaseSensitivity::CaseSensitive {
-              Feature::CssCaseInsensitive
-            } else {
-              match operator {
-                AttrSelectorOperator::Equal | AttrSelectorOperator::Includes | AttrSelectorOperator::DashMatch => {
-                  Feature::CssSel2
-                }
-                AttrSelectorOperator::Prefix | AttrSelectorOperator::Substring | AttrSelectorOperator::Suffix => {
-                  Feature::CssSel3
-                }
-              }
-            }
-          }
-        },
+      } else {
+        return false;
+      }
+    }
+  }
 
-        Component::FirstChild => Feature::CssSel2,
+  true
+}
 
-        Component::Empty
-        | Component::FirstOfType
-        | Component::LastChild
-        | Component::LastOfType
-        | Component::Negation(_)
-        | Component::NthChild(_, _)
-        | Component::NthLastChild(_, _)
-        | Component::NthCol(_, _)
-        | Component::NthLastCol(_, _)
-        | Component::NthLastOfType(_, _)
-        | Component::NthOfType(_, _)
-        | Component::OnlyChild
-        | Component::OnlyOfType
-        | Component::Root => Feature::CssSel3,
+/// Returns whether none of the selectors are supported by any of the targets,
+/// according to the compatibility data for their components.
+pub(crate) fn is_unsupported(selectors: &SelectorList, targets: Browsers) -> bool {
+  selectors.0.iter().all(|selector| {
+    selector
+      .iter_raw_match_order()
+      .any(|component| match component_support(component) {
+        ComponentSupport::Feature(feature) => !feature.is_partially_compatible(targets),
+        _ => false,
+      })
+  })
+}
 
-        Component::Is(_) | Component::Nesting => Feature::CssMatchesPseudo,
-        Component::Any(..) => Feature::AnyPseudo,
-        Component::Has(_) => Feature::CssHas,
+/// Returns the compatibility features used by the components of a selector that are not supported
+/// by all of the targets.
+pub(crate) fn unsupported_features(selector: &Selector, targets: Browsers) -> Vec<Feature> {
+  let mut features = Vec::new();
+  for component in selector.iter_raw_match_order() {
+    if let ComponentSupport::Feature(feature) = component_support(component) {
+      if !feature.is_compatible(targets) && !features.contains(&feature) {
+        features.push(feature);
+      }
+    }
+  }
+  features
+}
 
-        Component::Scope | Component::Host(_) | Component::Slotted(_) => Feature::Shadowdomv1,
+enum ComponentSupport {
+  /// The component is supported everywhere.
+  Always,
+  /// The component is supported where the given feature is.
+  Feature(Feature),
+  /// There is no compatibility data for the component.
+  Unknown,
+}
+
+fn component_support(component: &Component) -> ComponentSupport {
+  let feature = match component {
+    Component::ID(_) | Component::Class(_) | Component::LocalName(_) => return ComponentSupport::Always,
 
-        Component::Part(_) | Component::Where(_) => return false, // TODO: find this data in caniuse-lite
+    Component::ExplicitAnyNamespace
+    | Component::ExplicitNoNamespace
+    | Component::DefaultNamespace(_)
+    | Component::Namespace(_, _) => Feature::CssNamespaces,
 
-        Component::NonTSPseudoClass(pseudo) => {
-          match pseudo {
-            PseudoClass::Link
-            | PseudoClass::Visited
-            | PseudoClass::Active
-            | PseudoClass::Hover
-            | PseudoClass::Focus
-            | PseudoClass::Lang { languages: _ } => Feature::CssSel2,
+    Component::ExplicitUniversalType => Feature::CssSel2,
 
-            PseudoClass::Checked | PseudoClass::Disabled | PseudoClass::Enabled | PseudoClass::Target => {
+    Component::AttributeInNoNamespaceExists { .. } => Feature::CssSel2,
+    Component::AttributeInNoNamespace {
+      operator,
+      case_sensitivity,
+      ..
+    } => {
+      if *case_sensitivity != ParsedCaseSensitivity::CaseSensitive {
+        Feature::CssCaseInsensitive
+      } else {
+        match operator {
+          AttrSelectorOperator::Equal | AttrSelectorOperator::Includes | AttrSelectorOperator::DashMatch => {
+            Feature::CssSel2
+          }
+          AttrSelectorOperator::Prefix | AttrSelectorOperator::Substring | AttrSelectorOperator::Suffix => {
+            Feature::CssSel3
+          }
+        }
+      }
+    }
+    Component::AttributeOther(attr) => match attr.operation {
+      ParsedAttrSelectorOperation::Exists => Feature::CssSel2,
+      ParsedAttrSelectorOperation::WithValue {
+        operator,
+        case_sensitivity,
+        ..
+      } => {
+        if case_sensitivity != ParsedCaseSensitivity::CaseSensitive {
+          Feature::CssCaseInsensitive
+        } else {
+          match operator {
+            AttrSelectorOperator::Equal | AttrSelectorOperator::Includes | AttrSelectorOperator::DashMatch => {
+              Feature::CssSel2
+            }
+            AttrSelectorOperator::Prefix | AttrSelectorOperator::Substring | AttrSelectorOperator::Suffix => {
               Feature::CssSel3
             }
+          }
+        }
+      }
+    },
+
+    Component::FirstChild => Feature::CssSel2,
+
+    Component::Empty
+    | Component::FirstOfType
+    | Component::LastChild
+    | Component::LastOfType
+    | Component::Negation(_)
+    | Component::NthChild(_, _)
+    | Component::NthLastChild(_, _)
+    | Component::NthCol(_, _)
+    | Component::NthLastCol(_, _)
+    | Component::NthLastOfType(_, _)
+    | Component::NthOfType(_, _)
+    | Component::OnlyChild
+    | Component::OnlyOfType
+    | Component::Root => Feature::CssSel3,
+
+    Component::Is(_) | Component::Nesting => Feature::CssMatchesPseudo,
+    Component::Any(..) => Feature::AnyPseudo,
+    Component::Has(_) => Feature::CssHas,
+
+    Component::Scope | Component::Host(_) | Component::Slotted(_) => Feature::Shadowdomv1,
+
+    Component::Part(_) => Feature::PartPseudo,
+
+    Component::Where(_) => return ComponentSupport::Unknown, // TODO: find this data in caniuse-lite
+
+    Component::NonTSPseudoClass(pseudo) => {
+      match pseudo {
+        PseudoClass::Link
+        | PseudoClass::Visited
+        | PseudoClass::Active
+        | PseudoClass::Hover
+        | PseudoClass::Focus
+        | PseudoClass::Lang { languages: _ } => Feature::CssSel2,
 
-            PseudoClass::AnyLink(prefix) if *prefix == VendorPrefix::None => Feature::CssAnyLink,
-            PseudoClass::Indeterminate => Feature::CssIndeterminatePseudo,
+        PseudoClass::Checked | PseudoClass::Disabled | PseudoClass::Enabled | PseudoClass::Target => {
+          Feature::CssSel3
+        }
 
-            PseudoClass::Fullscreen(prefix) if *prefix == VendorPrefix::None => Feature::Fullscreen,
+        PseudoClass::AnyLink(prefix) if *prefix == VendorPrefix::None => Feature::CssAnyLink,
+        PseudoClass::Indeterminate => Feature::CssIndeterminatePseudo,
 
-            PseudoClass::FocusVisible => Feature::CssFocusVisible,
-            PseudoClass::FocusWithin => Feature::CssFocusWithin,
-            PseudoClass::Default => Feature::CssDefaultPseudo,
-            PseudoClass::Dir { direction: _ } => Feature::CssDirPseudo,
-            PseudoClass::Optional => Feature::CssOptionalPseudo,
-            PseudoClass::PlaceholderShown(prefix) if *prefix == VendorPrefix::None => Feature::CssPlaceholderShown,
+        PseudoClass::Fullscreen(prefix) if *prefix == VendorPrefix::None => Feature::Fullscreen,
 
-            PseudoClass::ReadOnly(prefix) | PseudoClass::ReadWrite(prefix) if *prefix == VendorPrefix::None => {
-              Feature::CssReadOnlyWrite
-            }
+        PseudoClass::FocusVisible => Feature::CssFocusVisible,
+        PseudoClass::FocusWithin => Feature::CssFocusWithin,
+        PseudoClass::Default => Feature::CssDefaultPseudo,
+        PseudoClass::Dir { direction: _ } => Feature::CssDirPseudo,
+        PseudoClass::Optional => Feature::CssOptionalPseudo,
+        PseudoClass::PlaceholderShown(prefix) if *prefix == VendorPrefix::None => Feature::CssPlaceholderShown,
 
-            PseudoClass::Valid | PseudoClass::Invalid | PseudoClass::Required => Feature::FormValidation,
-
-            PseudoClass::InRange | PseudoClass::OutOfRange => Feature::CssInOutOfRange,
-
-            PseudoClass::Autofill(prefix) if *prefix == VendorPrefix::None => Feature::CssAutofill,
-
-            // Experimental, no browser support.
-            PseudoClass::Current
-            | PseudoClass::Past
-            | PseudoClass::Future
-            | PseudoClass::Playing
-            | PseudoClass::Paused
-            | PseudoClass::Seeking
-            | PseudoClass::Stalled
-            | PseudoClass::Buffering
-            | PseudoClass::Muted
-            | PseudoClass::VolumeLocked
-            | PseudoClass::TargetWithin
-            | PseudoClass::LocalLink
-            | PseudoClass::Blank
-            | PseudoClass::UserInvalid
-            | PseudoClass::UserValid
-            | PseudoClass::Defined => return false,
-
-            PseudoClass::Custom { .. } | _ => return false,
-          }
+        PseudoClass::ReadOnly(prefix) | PseudoClass::ReadWrite(prefix) if *prefix == VendorPrefix::None => {
+          Feature::CssReadOnlyWrite
         }
 
-        Component::PseudoElement(pseudo) => match pseudo {
-          PseudoElement::After | PseudoElement::Before => Feature::CssGencontent,
-          PseudoElement::FirstLine => Feature::CssFirstLine,
-          PseudoElement::FirstLetter => Feature::CssFirstLetter,
-          PseudoElement::Selection(prefix) if *prefix == VendorPrefix::None => Feature::CssSelection,
-          PseudoElement::Placeholder(prefix) if *prefix == VendorPrefix::None => Feature::CssPlaceholder,
-          PseudoElement::Marker => Feature::CssMarkerPseudo,
-          PseudoElement::Backdrop(prefix) if *prefix == VendorPrefix::None => Feature::Dialog,
-          PseudoElement::Cue => Feature::Cue,
-          PseudoElement::CueFunction { selector: _ } => Feature::CueFunction,
-          PseudoElement::Custom { name: _ } | _ => return false,
-        },
-
-        Component::Combinator(combinator) => match combinator {
-          Combinator::Child | Combinator::NextSibling => Feature::CssSel2,
-          Combinator::LaterSibling => Feature::CssSel3,
-          _ => continue,
-        },
-      };
+        PseudoClass::Valid | PseudoClass::Invalid | PseudoClass::Required => Feature::FormValidation,
+
+        PseudoClass::InRange | PseudoClass::OutOfRange => Feature::CssInOutOfRange,
+
+        PseudoClass::Autofill(prefix) if *prefix == VendorPrefix::None => Feature::CssAutofill,
+
+        PseudoClass::PopoverOpen => Feature::PopoverOpenPseudoClass,
+        PseudoClass::Modal => Feature::ModalPseudoClass,
+        PseudoClass::State { .. } => Feature::StatePseudoClass,
+
+        // Experimental, no browser support.
+        PseudoClass::Current
+        | PseudoClass::Past
+        | PseudoClass::Future
+        | PseudoClass::Playing
+        | PseudoClass::Paused
+        | PseudoClass::Seeking
+        | PseudoClass::Stalled
+        | PseudoClass::Buffering
+        | PseudoClass::Muted
+        | PseudoClass::VolumeLocked
+        | PseudoClass::TargetWithin
+        | PseudoClass::LocalLink
+        | PseudoClass::Blank
+        | PseudoClass::UserInvalid
+        | PseudoClass::UserValid
+        | PseudoClass::Defined => return ComponentSupport::Unknown,
+
+        PseudoClass::Custom { .. } | _ => return ComponentSupport::Unknown,
+      }
+    }
 
-      if let Some(targets) = targets {
-        if !feature.is_compatible(targets) {
-          return false;
+    Component::PseudoElement(pseudo) => match pseudo {
+      PseudoElement::After | PseudoElement::Before => Feature::CssGencontent,
+      PseudoElement::FirstLine => Feature::CssFirstLine,
+      PseudoElement::FirstLetter => Feature::CssFirstLetter,
+      PseudoElement::Selection(prefix) if *prefix == VendorPrefix::None => Feature::CssSelection,
+      PseudoElement::Placeholder(prefix) if *prefix == VendorPrefix::None => Feature::CssPlaceholder,
+      PseudoElement::Marker => Feature::CssMarkerPseudo,
+      PseudoElement::Backdrop(prefix) if *prefix == VendorPrefix::None => Feature::Dialog,
+      PseudoElement::Cue => Feature::Cue,
+      PseudoElement::CueFunction { selector: _ } => Feature::CueFunction,
+      PseudoElement::DetailsContent => Feature::DetailsContent,
+      PseudoElement::ViewTransition => Feature::ViewTransition,
+      PseudoElement::ViewTransitionGroup { part }
+      | PseudoElement::ViewTransitionImagePair { part }
+      | PseudoElement::ViewTransitionOld { part }
+      | PseudoElement::ViewTransitionNew { part } => {
+        if part.classes.is_empty() {
+          Feature::ViewTransition
+        } else {
+          Feature::ViewTransitionClass
         }
-      } else {
-        return false;
       }
-    }
-  }
+      PseudoElement::Custom { name: _ } | _ => return ComponentSupport::Unknown,
+    },
+
+    Component::Combinator(combinator) => match combinator {
+      Combinator::Child | Combinator::NextSibling => Feature::CssSel2,
+      Combinator::LaterSibling => Feature::CssSel3,
+      _ => return ComponentSupport::Always,
+    },
+  };
 
-  true
+  ComponentSupport::Feature(feature)
 }
 
 /// Returns whether two selector lists are equivalent, i.e. the same minus any vendor prefix differences.
@@ -1700,6 +2025,23 @@ pub(crate) fn get_prefix(selectors: &SelectorList) -> VendorPrefix {
   prefix
 }
 
+/// Returns the vendor prefixes necessary for the unprefixed pseudo classes and pseudo elements
+/// in the given selector list to be supported by the targets.
+pub(crate) fn get_necessary_prefixes(selectors: &SelectorList, targets: Browsers) -> VendorPrefix {
+  let mut prefixes = VendorPrefix::empty();
+  for selector in &selectors.0 {
+    for component in selector.iter_raw_match_order() {
+      prefixes |= match component {
+        Component::NonTSPseudoClass(pc) => pc.get_necessary_prefixes(targets),
+        Component::PseudoElement(pe) => pe.get_necessary_prefixes(targets),
+        _ => VendorPrefix::empty(),
+      };
+    }
+  }
+
+  prefixes
+}
+
 const RTL_LANGS: &[&str] = &[
   "ae", "ar", "arc", "bcc", "bqi", "ckb", "dv", "fa", "glk", "he", "ku", "mzn", "nqo", "pnb", "ps", "sd", "ug",
   "ur", "yi",
@@ -1799,6 +2141,831 @@ fn downlevel_dir<'i>(dir: Direction, targets: Browsers) -> Component<'i> {
   }
 }
 
+/// Scopes each selector in the list under the given root selector, e.g. `.foo` becomes `.widget .foo`.
+/// Compound selectors targeting the document root (`html`, `body`, and `:root`) are replaced
+/// by the scope, so `:root` becomes `.widget` and `body.dark .foo` becomes `.widget.dark .foo`.
+pub(crate) fn scope_selectors<'i>(selectors: &mut SelectorList<'i>, scope: &SelectorList<'i>) {
+  // A scope made up of multiple selectors is wrapped in :is() so it acts as a single compound selector.
+  let scope = if scope.0.len() == 1 {
+    parse_order_components(&scope.0[0])
+  } else {
+    vec![Component::Is(scope.0.to_vec().into_boxed_slice())]
+  };
+
+  for selector in selectors.0.iter_mut() {
+    // Compound selectors in parse order, and the combinators between them.
+    let compounds: Vec<_> = selector
+      .iter_raw_match_order()
+      .as_slice()
+      .split(|c| c.is_combinator())
+      .rev()
+      .collect();
+    let combinators: Vec<_> = selector
+      .iter_raw_match_order()
+      .rev()
+      .filter_map(|c| c.as_combinator())
+      .collect();
+
+    let mut components = scope.clone();
+    let mut index = 0;
+    while index < compounds.len() && compounds[index].iter().any(is_root_component) {
+      components.extend(compounds[index].iter().filter(|c| !is_root_component(c)).cloned());
+      index += 1;
+    }
+
+    if index < compounds.len() {
+      // Keep the combinator following the last root compound, e.g. `:root > .foo` or `:root::before`.
+      let combinator = if index > 0 {
+        combinators[index - 1]
+      } else {
+        Combinator::Descendant
+      };
+      components.push(Component::Combinator(combinator));
+      for (i, compound) in compounds[index..].iter().enumerate() {
+        if i > 0 {
+          components.push(Component::Combinator(combinators[index + i - 1]));
+        }
+        components.extend(compound.iter().cloned());
+      }
+    }
+
+    *selector = Selector::from(components);
+  }
+
+  // Scoping may produce duplicate selectors, e.g. for `html, body`.
+  let mut i = 1;
+  while i < selectors.0.len() {
+    if selectors.0[..i].contains(&selectors.0[i]) {
+      selectors.0.remove(i);
+    } else {
+      i += 1;
+    }
+  }
+}
+
+/// Rewrites the selectors of a style rule within an `@scope` rule so that they only match descendants
+/// of the scoping root, e.g. `.title` becomes `:where(.card) .title`. References to the scoping root
+/// via `:scope` or `&` are replaced with `:where(.card)` rather than adding a descendant combinator.
+pub(crate) fn scope_root_selectors<'i>(selectors: &mut SelectorList<'i>, root: &SelectorList<'i>) {
+  let root = Component::Where(root.0.to_vec().into_boxed_slice());
+  for selector in selectors.0.iter_mut() {
+    let mut components = parse_order_components(selector);
+    if components.iter().any(|c| matches!(c, Component::Scope | Component::Nesting)) {
+      for component in components.iter_mut() {
+        if matches!(component, Component::Scope | Component::Nesting) {
+          *component = root.clone();
+        }
+      }
+    } else {
+      components.insert(0, Component::Combinator(Combinator::Descendant));
+      components.insert(0, root.clone());
+    }
+
+    *selector = Selector::from(components);
+  }
+}
+
+/// Adds an `[attr~="value"]` selector to the subject of each selector in the list, before any pseudo element.
+pub(crate) fn add_attribute_selector<'i>(selectors: &mut SelectorList<'i>, attr: &str, value: &str) {
+  let attribute = Component::AttributeInNoNamespace {
+    local_name: Ident(attr.to_owned().into()),
+    operator: AttrSelectorOperator::Includes,
+    value: CSSString(value.to_owned().into()),
+    case_sensitivity: ParsedCaseSensitivity::CaseSensitive,
+    never_matches: false,
+  };
+
+  for selector in selectors.0.iter_mut() {
+    let mut components = parse_order_components(selector);
+    let subject = components
+      .iter()
+      .rposition(|c| {
+        matches!(
+          c,
+          Component::Combinator(
+            Combinator::Descendant | Combinator::Child | Combinator::NextSibling | Combinator::LaterSibling
+          )
+        )
+      })
+      .map_or(0, |i| i + 1);
+    let index = components[subject..]
+      .iter()
+      .position(|c| matches!(c, Component::Combinator(_)))
+      .map_or(components.len(), |i| subject + i);
+    components.insert(index, attribute.clone());
+    *selector = Selector::from(components);
+  }
+}
+
+/// A uniform adjustment to the specificity of every selector in a style sheet.
+/// See [StyleSheet::adjust_specificity](crate::stylesheet::StyleSheet::adjust_specificity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecificityAdjustment {
+  /// Wraps each selector in `:where()`, reducing its specificity to zero.
+  Zero,
+  /// Raises the specificity of each selector by the given number of ids and classes.
+  /// Ids are added via `:not(#\#)`, and classes via `:not(.\#)`, which match any element.
+  Raise {
+    /// The number of ids to add.
+    ids: u32,
+    /// The number of classes to add.
+    classes: u32,
+  },
+}
+
+pub(crate) fn adjust_specificity(selectors: &mut SelectorList, adjustment: SpecificityAdjustment) {
+  for selector in selectors.0.iter_mut() {
+    let mut components = parse_order_components(selector);
+
+    // Pseudo elements, ::part(), and ::slotted() are not allowed within :where() or :not(),
+    // so the adjustment applies to the part of the selector before them.
+    let index = components
+      .iter()
+      .position(|c| {
+        matches!(
+          c,
+          Component::Combinator(Combinator::PseudoElement | Combinator::Part | Combinator::SlotAssignment)
+        )
+      })
+      .unwrap_or(components.len());
+
+    match adjustment {
+      SpecificityAdjustment::Zero => {
+        if index == 0 || selector.specificity() == 0 {
+          continue;
+        }
+
+        let rest = components.split_off(index);
+        if components.iter().any(|c| matches!(c, Component::Nesting)) {
+          // Nested selectors must keep the nesting selector at the top level, so wrap
+          // the rest of each compound selector individually, e.g. `&.foo .bar` becomes
+          // `&:where(.foo) :where(.bar)`.
+          let mut wrapped = Vec::new();
+          for (i, compound) in components.split(|c| c.is_combinator()).enumerate() {
+            if i > 0 {
+              wrapped.push(Component::Combinator(combinator_before_compound(&components, i)));
+            }
+            let (nesting, other): (Vec<_>, Vec<_>) =
+              compound.iter().cloned().partition(|c| matches!(c, Component::Nesting));
+            wrapped.extend(nesting);
+            if !other.is_empty() {
+              wrapped.push(Component::Where(vec![Selector::from(other)].into_boxed_slice()));
+            }
+          }
+          components = wrapped;
+        } else {
+          components = vec![Component::Where(vec![Selector::from(components)].into_boxed_slice())];
+        }
+        components.extend(rest);
+      }
+      SpecificityAdjustment::Raise { ids, classes } => {
+        let never = || Ident("#".into());
+        let ids = (0..ids).map(|_| Component::ID(never()));
+        let classes = (0..classes).map(|_| Component::Class(never()));
+        let negations: Vec<_> = ids
+          .chain(classes)
+          .map(|c| Component::Negation(vec![Selector::from(c)].into_boxed_slice()))
+          .collect();
+        components.splice(index..index, negations);
+      }
+    }
+
+    *selector = Selector::from(components);
+  }
+}
+
+/// Expands each selector in the list into one selector for every combination of parent selectors
+/// substituted for its nesting selectors, e.g. `& .c` within `.a, .b` becomes `.a .c, .b .c`.
+/// Only nesting selectors at the top level are expanded. Those within pseudo classes such as `:not()`
+/// are left in place. See [NestingMode::Expand](crate::printer::NestingMode::Expand).
+pub(crate) fn expand_nesting<'i>(selectors: &SelectorList<'i>, parents: &SelectorList<'i>) -> SelectorList<'i> {
+  SelectorList::new(
+    expand_selectors(&selectors.0, |component| match component {
+      Component::Nesting => Some(parents.0.to_vec()),
+      _ => None,
+    })
+    .into(),
+  )
+}
+
+/// Expands `:is()` selectors containing combinators into a separate selector for each of their arguments,
+/// e.g. `:is(.a .b, .c) .d` becomes `.a .b .d, .c .d`. These cannot be downleveled to `:-webkit-any()`,
+/// which only accepts compound selectors. Only `:is()` selectors in the leading compound selector are
+/// expanded, since e.g. `.x :is(.a > .b)` also matches when `.a` is outside `.x`, unlike `.x .a > .b`.
+/// If any selector in the list has such an `:is()` elsewhere, the list is left unchanged.
+pub(crate) fn expand_is_selectors(selectors: &mut SelectorList) {
+  if !selectors
+    .0
+    .iter()
+    .any(|selector| selector.iter_raw_match_order().any(is_complex_is_selector))
+  {
+    return;
+  }
+
+  if selectors.0.iter().any(|selector| {
+    // In match order, the leading compound selector comes after the last combinator.
+    let components = selector.iter_raw_match_order().as_slice();
+    let leading = components.iter().rposition(|c| c.is_combinator()).map_or(0, |i| i + 1);
+    components[..leading].iter().any(is_complex_is_selector)
+  }) {
+    return;
+  }
+
+  let expanded = expand_selectors(&selectors.0, |component| match component {
+    Component::Is(list) if is_complex_is_selector(component) => Some(list.to_vec()),
+    _ => None,
+  });
+  selectors.0 = expanded.into();
+}
+
+fn is_complex_is_selector(component: &Component) -> bool {
+  match component {
+    Component::Is(list) => list.iter().any(|selector| selector.has_combinator()),
+    _ => false,
+  }
+}
+
+/// Replaces each top level component for which `replacement` returns a list of selectors with each of those
+/// selectors in turn, producing a selector for every combination. Duplicate selectors are removed.
+fn expand_selectors<'i, F>(selectors: &[Selector<'i>], replacement: F) -> Vec<Selector<'i>>
+where
+  F: Fn(&Component<'i>) -> Option<Vec<Selector<'i>>>,
+{
+  let mut result: Vec<Selector<'i>> = Vec::new();
+  for selector in selectors {
+    let mut expanded = vec![Vec::new()];
+    for component in parse_order_components(selector) {
+      if let Some(replacements) = replacement(&component) {
+        expanded = expanded
+          .iter()
+          .flat_map(|components| {
+            replacements
+              .iter()
+              .map(|replacement| substitute_selector(components, replacement))
+          })
+          .collect();
+      } else {
+        for components in expanded.iter_mut() {
+          components.push(component.clone());
+        }
+      }
+    }
+
+    for components in expanded {
+      let selector = Selector::from(components);
+      if !result.contains(&selector) {
+        result.push(selector);
+      }
+    }
+  }
+
+  result
+}
+
+/// Appends the selector to the given components in place of a nesting selector or `:is()` selector.
+fn substitute_selector<'i>(components: &[Component<'i>], selector: &Selector<'i>) -> Vec<Component<'i>> {
+  let mut result = components.to_vec();
+  let selector_components = parse_order_components(selector);
+  let compound_start = result.iter().rposition(|c| c.is_combinator()).map_or(0, |i| i + 1);
+  let current = result.split_off(compound_start);
+  if current.is_empty() {
+    // The replaced component starts a compound selector, so the selector can be written in its place.
+    result.extend(selector_components);
+    return result;
+  }
+
+  // Otherwise, the last compound selector of the selector is merged into the current compound selector,
+  // and the rest of the selector is written before it. This is not possible if both compound selectors
+  // have a type selector, or the selector has a pseudo element, so fall back to :is() in that case.
+  let selector_start = selector_components.iter().rposition(|c| c.is_combinator()).map_or(0, |i| i + 1);
+  let (selector_type, selector_rest) = split_type_selector(&selector_components[selector_start..]);
+  let (current_type, current_rest) = split_type_selector(&current);
+  let has_pseudo_element = selector_components.iter().any(|c| {
+    matches!(
+      c,
+      Component::Combinator(Combinator::PseudoElement | Combinator::Part | Combinator::SlotAssignment)
+    )
+  });
+  if (!selector_type.is_empty() && !current_type.is_empty()) || has_pseudo_element {
+    result.extend(current);
+    result.push(Component::Is(vec![selector.clone()].into_boxed_slice()));
+    return result;
+  }
+
+  let mut compound = Vec::new();
+  compound.extend_from_slice(selector_type);
+  compound.extend_from_slice(current_type);
+  compound.extend_from_slice(current_rest);
+  compound.extend_from_slice(selector_rest);
+  result.extend_from_slice(&selector_components[..selector_start]);
+  result.extend(compound);
+  result
+}
+
+/// Splits a compound selector into its leading type selector (including any namespace) and the rest.
+fn split_type_selector<'a, 'i>(compound: &'a [Component<'i>]) -> (&'a [Component<'i>], &'a [Component<'i>]) {
+  let index = usize::from(is_namespace(compound.first()));
+  if is_type_selector(compound.get(index)) {
+    compound.split_at(index + 1)
+  } else {
+    compound.split_at(0)
+  }
+}
+
+fn combinator_before_compound(components: &[Component], index: usize) -> Combinator {
+  components.iter().filter_map(|c| c.as_combinator()).nth(index - 1).unwrap()
+}
+
+fn parse_order_components<'i>(selector: &Selector<'i>) -> Vec<Component<'i>> {
+  let mut combinators = selector.iter_raw_match_order().rev().filter_map(|c| c.as_combinator());
+  let mut components = Vec::new();
+  for (i, compound) in selector
+    .iter_raw_match_order()
+    .as_slice()
+    .split(|c| c.is_combinator())
+    .rev()
+    .enumerate()
+  {
+    if i > 0 {
+      components.push(Component::Combinator(combinators.next().unwrap()));
+    }
+    components.extend(compound.iter().cloned());
+  }
+  components
+}
+
+fn is_root_component(component: &Component) -> bool {
+  match component {
+    Component::Root => true,
+    Component::LocalName(local) => matches!(local.lower_name.0.as_ref(), "html" | "body"),
+    _ => false,
+  }
+}
+
+/// Returns whether the selector contains a `:has()` selector, including within
+/// other selectors such as `:is()` or `:not()`.
+pub(crate) fn has_has_selector(selector: &Selector) -> bool {
+  selector.iter_raw_match_order().any(|component| match component {
+    Component::Has(..) => true,
+    Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Negation(selectors)
+    | Component::Any(_, selectors) => selectors.iter().any(has_has_selector),
+    _ => false,
+  })
+}
+
+/// Normalizes the attribute selectors within a selector so that logically equal selectors compare
+/// as equal. Values that are matched ASCII case-insensitively are lowercased, and duplicate attribute
+/// selectors within the same compound selector are removed.
+pub(crate) fn normalize_attribute_selectors(selector: &mut Selector) {
+  fn fold_case(value: &mut CSSString) {
+    if value.0.bytes().any(|b| b.is_ascii_uppercase()) {
+      value.0 = value.0.to_ascii_lowercase().into();
+    }
+  }
+
+  for component in selector.iter_mut_raw_match_order() {
+    match component {
+      Component::AttributeInNoNamespace {
+        value,
+        case_sensitivity: ParsedCaseSensitivity::AsciiCaseInsensitive,
+        ..
+      } => fold_case(value),
+      Component::AttributeOther(attr) => {
+        if let ParsedAttrSelectorOperation::WithValue {
+          case_sensitivity: ParsedCaseSensitivity::AsciiCaseInsensitive,
+          expected_value,
+          ..
+        } = &mut attr.operation
+        {
+          fold_case(expected_value)
+        }
+      }
+      Component::Negation(selectors)
+      | Component::Is(selectors)
+      | Component::Where(selectors)
+      | Component::Has(selectors)
+      | Component::Any(_, selectors) => selectors.iter_mut().for_each(normalize_attribute_selectors),
+      Component::Slotted(selector) | Component::Host(Some(selector)) => normalize_attribute_selectors(selector),
+      _ => {}
+    }
+  }
+
+  let components = parse_order_components(selector);
+  let len = components.len();
+  let mut deduped = Vec::with_capacity(len);
+  let mut compound_start = 0;
+  for component in components {
+    if component.is_combinator() {
+      compound_start = deduped.len() + 1;
+    } else if matches!(
+      component,
+      Component::AttributeInNoNamespaceExists { .. }
+        | Component::AttributeInNoNamespace { .. }
+        | Component::AttributeOther(..)
+    ) && deduped[compound_start..].contains(&component)
+    {
+      continue;
+    }
+    deduped.push(component);
+  }
+
+  if deduped.len() != len {
+    *selector = Selector::from(deduped);
+  }
+}
+
+/// Returns the first namespace prefix referenced by a selector that is not in the given set
+/// of prefixes declared by `@namespace` rules.
+pub(crate) fn find_undeclared_namespace<'i>(
+  selector: &Selector<'i>,
+  namespaces: &HashSet<CowArcStr<'i>>,
+) -> Option<CowArcStr<'i>> {
+  selector.iter_raw_match_order().find_map(|component| match component {
+    Component::Namespace(prefix, _) if !namespaces.contains(&prefix.0) => Some(prefix.0.clone()),
+    Component::AttributeOther(attr) => match &attr.namespace {
+      Some(NamespaceConstraint::Specific((prefix, _)))
+        if !prefix.0.is_empty() && !namespaces.contains(&prefix.0) =>
+      {
+        Some(prefix.0.clone())
+      }
+      _ => None,
+    },
+    Component::Negation(selectors)
+    | Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Has(selectors)
+    | Component::Any(_, selectors) => selectors
+      .iter()
+      .find_map(|selector| find_undeclared_namespace(selector, namespaces)),
+    Component::Slotted(selector) | Component::Host(Some(selector)) => {
+      find_undeclared_namespace(selector, namespaces)
+    }
+    _ => None,
+  })
+}
+
+/// The custom element related names referenced by the selectors in a style sheet.
+///
+/// See [StyleSheet::custom_element_usage](crate::stylesheet::StyleSheet::custom_element_usage).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct CustomElementUsage {
+  /// Custom element tag names, e.g. `my-button`.
+  pub tag_names: BTreeSet<String>,
+  /// Shadow part names referenced via `::part()`.
+  pub parts: BTreeSet<String>,
+  /// Custom state names referenced via `:state()`.
+  pub states: BTreeSet<String>,
+}
+
+impl CustomElementUsage {
+  pub(crate) fn add_selectors(&mut self, selectors: &SelectorList) {
+    for selector in &selectors.0 {
+      self.add_selector(selector);
+    }
+  }
+
+  fn add_selector(&mut self, selector: &Selector) {
+    for component in selector.iter_raw_match_order() {
+      match component {
+        Component::LocalName(local) if is_custom_element_name(&local.lower_name.0) => {
+          self.tag_names.insert(local.lower_name.0.to_string());
+        }
+        Component::Part(names) => {
+          self.parts.extend(names.iter().map(|name| name.0.to_string()));
+        }
+        Component::NonTSPseudoClass(PseudoClass::State { state, .. }) => {
+          self.states.insert(state.0.to_string());
+        }
+        Component::NonTSPseudoClass(PseudoClass::Local { selector })
+        | Component::NonTSPseudoClass(PseudoClass::Global { selector }) => self.add_selector(selector),
+        Component::Slotted(selector) | Component::Host(Some(selector)) => self.add_selector(selector),
+        Component::Negation(selectors)
+        | Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Has(selectors)
+        | Component::Any(_, selectors) => {
+          for selector in selectors.iter() {
+            self.add_selector(selector);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+/// Returns whether the given lowercase tag name is a valid custom element name.
+/// https://html.spec.whatwg.org/multipage/custom-elements.html#valid-custom-element-name
+fn is_custom_element_name(name: &str) -> bool {
+  name.contains('-')
+    && name.starts_with(|c: char| c.is_ascii_lowercase())
+    && !matches!(
+      name,
+      "annotation-xml"
+        | "color-profile"
+        | "font-face"
+        | "font-face-src"
+        | "font-face-uri"
+        | "font-face-format"
+        | "font-face-name"
+        | "missing-glyph"
+    )
+}
+
+/// The specificity of a selector, compared by ID selectors first, then class-like selectors,
+/// then type selectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct Specificity {
+  /// The number of ID selectors.
+  pub ids: u32,
+  /// The number of class, attribute, and pseudo class selectors.
+  pub classes: u32,
+  /// The number of type and pseudo element selectors.
+  pub types: u32,
+}
+
+impl Specificity {
+  /// Creates a specificity from the number of ID, class-like, and type selectors.
+  pub fn new(ids: u32, classes: u32, types: u32) -> Specificity {
+    Specificity { ids, classes, types }
+  }
+}
+
+impl From<u32> for Specificity {
+  fn from(value: u32) -> Specificity {
+    Specificity {
+      ids: value >> 20,
+      classes: (value >> 10) & 0x3ff,
+      types: value & 0x3ff,
+    }
+  }
+}
+
+impl fmt::Display for Specificity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{},{},{}", self.ids, self.classes, self.types)
+  }
+}
+
+/// The specificity of a selector in a style sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SelectorSpecificity {
+  /// The selector.
+  pub selector: String,
+  /// The specificity of the selector. Nested selectors count `&` as having no specificity.
+  pub specificity: Specificity,
+  /// The location of the style rule containing the selector.
+  pub loc: Location,
+}
+
+/// The specificity of every selector in a style sheet.
+///
+/// See [StyleSheet::specificity_report](crate::stylesheet::StyleSheet::specificity_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SpecificityReport {
+  /// The selectors of all style rules, in source order.
+  pub selectors: Vec<SelectorSpecificity>,
+}
+
+impl SpecificityReport {
+  pub(crate) fn add_selectors(&mut self, selectors: &SelectorList, loc: Location) {
+    for selector in &selectors.0 {
+      self.selectors.push(SelectorSpecificity {
+        selector: selector.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+        specificity: selector.specificity().into(),
+        loc,
+      });
+    }
+  }
+
+  /// Returns the number of selectors with each specificity.
+  pub fn histogram(&self) -> BTreeMap<Specificity, usize> {
+    let mut histogram = BTreeMap::new();
+    for selector in &self.selectors {
+      *histogram.entry(selector.specificity).or_insert(0) += 1;
+    }
+    histogram
+  }
+
+  /// Returns the `n` most specific selectors, most specific first. Selectors with the same
+  /// specificity are kept in source order.
+  pub fn most_specific(&self, n: usize) -> Vec<&SelectorSpecificity> {
+    let mut selectors: Vec<_> = self.selectors.iter().collect();
+    selectors.sort_by(|a, b| b.specificity.cmp(&a.specificity));
+    selectors.truncate(n);
+    selectors
+  }
+
+  /// Returns the selectors with a higher specificity than the given maximum, in source order.
+  pub fn exceeding(&self, max: Specificity) -> Vec<&SelectorSpecificity> {
+    self.selectors.iter().filter(|selector| selector.specificity > max).collect()
+  }
+}
+
+/// Metrics describing how expensive a selector is for browsers to match.
+///
+/// Pseudo classes, pseudo elements, universal selectors, and attribute selectors are counted
+/// within selector arguments such as `:is()`, `:not()`, and `:has()` as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct SelectorComplexity {
+  /// The number of compound selectors, e.g. 3 for `.a > .b .c`.
+  pub compounds: u32,
+  /// The number of combinators between compound selectors, e.g. 2 for `.a > .b .c`.
+  pub combinator_depth: u32,
+  /// The number of descendant combinators, which may require browsers to test every ancestor of an element.
+  pub descendant_combinators: u32,
+  /// The number of pseudo classes, including `:is()`, `:not()`, `:where()`, and `:has()`.
+  pub pseudo_classes: u32,
+  /// The number of pseudo elements.
+  pub pseudo_elements: u32,
+  /// The number of universal selectors, i.e. `*`.
+  pub universal: u32,
+  /// The number of attribute selectors.
+  pub attributes: u32,
+  /// Whether the rightmost compound selector is not narrowed by a type, class, ID, or attribute
+  /// selector, e.g. `.a *` or `.a > :hover`, so that browsers must test the selector against every element.
+  pub universal_key: bool,
+}
+
+impl SelectorComplexity {
+  /// Computes the complexity metrics for a selector.
+  pub fn new(selector: &Selector) -> SelectorComplexity {
+    let mut complexity = SelectorComplexity {
+      compounds: 1,
+      universal_key: true,
+      ..SelectorComplexity::default()
+    };
+
+    let mut iter = selector.iter();
+    let mut is_key = true;
+    loop {
+      for component in &mut iter {
+        if is_key
+          && matches!(
+            component,
+            Component::LocalName(..)
+              | Component::ID(..)
+              | Component::Class(..)
+              | Component::AttributeInNoNamespaceExists { .. }
+              | Component::AttributeInNoNamespace { .. }
+              | Component::AttributeOther(..)
+              | Component::Is(..)
+              | Component::Where(..)
+              | Component::Any(..)
+              | Component::Nesting
+          )
+        {
+          complexity.universal_key = false;
+        }
+      }
+
+      match iter.next_sequence() {
+        Some(Combinator::PseudoElement | Combinator::SlotAssignment | Combinator::Part) => {}
+        Some(combinator) => {
+          is_key = false;
+          complexity.compounds += 1;
+          complexity.combinator_depth += 1;
+          if combinator == Combinator::Descendant {
+            complexity.descendant_combinators += 1;
+          }
+        }
+        None => break,
+      }
+    }
+
+    complexity.count_components(selector, false);
+    complexity
+  }
+
+  fn count_components(&mut self, selector: &Selector, is_relative: bool) {
+    let mut components = selector.iter_raw_match_order().as_slice();
+    // Relative selectors starting with a combinator, e.g. `:has(> img)`, are anchored with an implicit
+    // `:scope` by the parser, which is not written by the author.
+    if is_relative {
+      if let [rest @ .., Component::Combinator(..), Component::Scope] = components {
+        components = rest;
+      }
+    }
+
+    for component in components {
+      match component {
+        Component::ExplicitUniversalType => self.universal += 1,
+        Component::AttributeInNoNamespaceExists { .. }
+        | Component::AttributeInNoNamespace { .. }
+        | Component::AttributeOther(..) => self.attributes += 1,
+        Component::Negation(selectors)
+        | Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Any(_, selectors) => {
+          self.pseudo_classes += 1;
+          for selector in selectors.iter() {
+            self.count_components(selector, false);
+          }
+        }
+        Component::Has(selectors) => {
+          self.pseudo_classes += 1;
+          for selector in selectors.iter() {
+            self.count_components(selector, true);
+          }
+        }
+        Component::Host(selector) => {
+          self.pseudo_classes += 1;
+          if let Some(selector) = selector {
+            self.count_components(selector, false);
+          }
+        }
+        Component::FirstChild
+        | Component::LastChild
+        | Component::OnlyChild
+        | Component::Root
+        | Component::Empty
+        | Component::Scope
+        | Component::NthChild(..)
+        | Component::NthLastChild(..)
+        | Component::NthCol(..)
+        | Component::NthLastCol(..)
+        | Component::NthOfType(..)
+        | Component::NthLastOfType(..)
+        | Component::FirstOfType
+        | Component::LastOfType
+        | Component::OnlyOfType
+        | Component::NonTSPseudoClass(..) => self.pseudo_classes += 1,
+        Component::Slotted(selector) => {
+          self.pseudo_elements += 1;
+          self.count_components(selector, false);
+        }
+        Component::Part(..) | Component::PseudoElement(..) => self.pseudo_elements += 1,
+        _ => {}
+      }
+    }
+  }
+}
+
+/// The complexity of a selector in a style sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SelectorComplexityEntry {
+  /// The selector.
+  pub selector: String,
+  /// The complexity metrics of the selector.
+  pub complexity: SelectorComplexity,
+  /// The location of the style rule containing the selector.
+  pub loc: Location,
+}
+
+/// The complexity of every selector in a style sheet.
+///
+/// See [StyleSheet::selector_complexity](crate::stylesheet::StyleSheet::selector_complexity).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SelectorComplexityReport {
+  /// The selectors of all style rules, in source order.
+  pub selectors: Vec<SelectorComplexityEntry>,
+}
+
+impl SelectorComplexityReport {
+  pub(crate) fn add_selectors(&mut self, selectors: &SelectorList, loc: Location) {
+    for selector in &selectors.0 {
+      self.selectors.push(SelectorComplexityEntry {
+        selector: selector.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+        complexity: SelectorComplexity::new(selector),
+        loc,
+      });
+    }
+  }
+
+  /// Returns the selectors whose complexity matches the given predicate, in source order,
+  /// e.g. to flag selectors with more than a certain number of compound selectors.
+  pub fn filter<F: Fn(&SelectorComplexity) -> bool>(&self, predicate: F) -> Vec<&SelectorComplexityEntry> {
+    self.selectors.iter().filter(|entry| predicate(&entry.complexity)).collect()
+  }
+}
+
+/// A `:has()` selector that was replaced with an attribute selector by the
+/// `has_polyfill` printer option, so that it can be matched at runtime.
+///
+/// See [PrinterOptions](crate::printer::PrinterOptions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct HasPolyfill {
+  /// The value of the attribute that the `:has()` selector was replaced with.
+  /// Multiple values are separated by spaces, e.g. `data-has-polyfill="0 1"`.
+  pub id: String,
+  /// The relative selector list within `:has()`, e.g. `> img`.
+  pub selector: String,
+}
+
 /// Determines whether a selector list contains only unused selectors.
 /// A selector is considered unused if it contains a class or id component that exists in the set of unused symbols.
 pub(crate) fn is_unused(
@@ -1811,35 +2978,58 @@ pub(crate) fn is_unused(
   }
 
   selectors.all(|selector| {
-    for component in selector.iter_raw_match_order() {
-      match component {
-        Component::Class(name) | Component::ID(name) => {
-          if unused_symbols.contains(&name.0.to_string()) {
-            return true;
-          }
+    has_unused_component(
+      selector,
+      &|component| match component {
+        Component::Class(name) | Component::ID(name) => unused_symbols.contains(name.0.as_ref()),
+        _ => false,
+      },
+      parent_is_unused,
+    )
+  })
+}
+
+/// Returns whether the selector can never match, because one of its components is unused
+/// according to the given predicate, or all of the arguments of an `:is()`, `:where()`, or
+/// `:has()` selector within it are unused.
+pub(crate) fn has_unused_component(
+  selector: &Selector,
+  is_unused_component: &dyn Fn(&Component) -> bool,
+  parent_is_unused: bool,
+) -> bool {
+  for component in selector.iter_raw_match_order() {
+    match component {
+      Component::Is(selectors)
+      | Component::Where(selectors)
+      | Component::Any(_, selectors)
+      | Component::Has(selectors) => {
+        if selectors
+          .iter()
+          .all(|selector| has_unused_component(selector, is_unused_component, parent_is_unused))
+        {
+          return true;
         }
-        Component::Is(is) | Component::Where(is) | Component::Any(_, is) => {
-          if is_unused(&mut is.iter(), unused_symbols, parent_is_unused) {
-            return true;
-          }
+      }
+      Component::Nesting => {
+        if parent_is_unused {
+          return true;
         }
-        Component::Nesting => {
-          if parent_is_unused {
-            return true;
-          }
+      }
+      component => {
+        if is_unused_component(component) {
+          return true;
         }
-        _ => {}
       }
     }
+  }
 
-    false
-  })
+  false
 }
 
 #[cfg(feature = "visitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
 impl<'i, T: Visit<'i, T, V>, V: Visitor<'i, T>> Visit<'i, T, V> for SelectorList<'i> {
-  const CHILD_TYPES: VisitTypes = VisitTypes::SELECTORS;
+  const CHILD_TYPES: VisitTypes = crate::visit_types!(SELECTORS | TOKENS);
 
   fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
     if visitor.visit_types().contains(VisitTypes::SELECTORS) {
@@ -1857,13 +3047,43 @@ impl<'i, T: Visit<'i, T, V>, V: Visitor<'i, T>> Visit<'i, T, V> for SelectorList
 #[cfg(feature = "visitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
 impl<'i, T: Visit<'i, T, V>, V: Visitor<'i, T>> Visit<'i, T, V> for Selector<'i> {
-  const CHILD_TYPES: VisitTypes = VisitTypes::SELECTORS;
+  const CHILD_TYPES: VisitTypes = crate::visit_types!(SELECTORS | TOKENS);
 
   fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
-    visitor.visit_selector(self)
+    if visitor.visit_types().contains(VisitTypes::SELECTORS) {
+      visitor.visit_selector(self)
+    } else {
+      self.visit_children(visitor)
+    }
   }
 
-  fn visit_children(&mut self, _visitor: &mut V) -> Result<(), V::Error> {
+  /// Visits the arguments of unknown functional pseudo classes and pseudo elements, including those
+  /// within nested selectors.
+  fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if !visitor.visit_types().contains(VisitTypes::TOKENS) {
+      return Ok(());
+    }
+
+    for component in self.iter_mut_raw_match_order() {
+      match component {
+        Component::NonTSPseudoClass(PseudoClass::CustomFunction { arguments, .. })
+        | Component::PseudoElement(PseudoElement::CustomFunction { arguments, .. }) => arguments.visit(visitor)?,
+        Component::Negation(selectors)
+        | Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Has(selectors)
+        | Component::Any(_, selectors) => {
+          for selector in selectors.iter_mut() {
+            selector.visit_children(visitor)?;
+          }
+        }
+        Component::Slotted(selector) | Component::Host(Some(selector)) => selector.visit_children(visitor)?,
+        Component::NonTSPseudoClass(PseudoClass::Local { selector })
+        | Component::NonTSPseudoClass(PseudoClass::Global { selector }) => selector.visit_children(visitor)?,
+        _ => {}
+      }
+    }
+
     Ok(())
   }
 }
@@ -0,0 +1,251 @@
+//! Source spans of AST nodes.
+//!
+//! With the `spans` feature, the parser records the start and end byte offsets of rules, selectors,
+//! and declarations, along with the names, values, and component values of declarations, in addition
+//! to the line and column of each rule's [Location](crate::rules::Location). These allow linters and
+//! editors to highlight or replace the exact source text of a node.
+//!
+//! Spans refer to the source text that the style sheet was parsed from, and are not updated when the
+//! AST is transformed. In particular, minifying a style sheet removes the spans of selectors and
+//! declarations, since they may be merged or reordered. Nodes created from scratch have empty spans.
+//!
+//! Like the line and column of a [Location](crate::rules::Location), spans are compared when checking
+//! AST nodes for equality, so the same CSS parsed from different positions is not equal. Compare the
+//! printed output instead when only the contents matter.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   rules::CssRule,
+//!   span::Spanned,
+//!   stylesheet::{ParserOptions, StyleSheet},
+//! };
+//!
+//! let source = ".foo, .bar { color: red }";
+//! let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+//! let rule = &stylesheet.rules.0[0];
+//! assert_eq!(rule.span().slice(source), ".foo, .bar { color: red }");
+//!
+//! if let CssRule::Style(style) = rule {
+//!   assert_eq!(style.selector_spans[1].slice(source), ".bar");
+//!   let declaration = style.declarations.span_of(false, 0).unwrap();
+//!   assert_eq!(declaration.name.slice(source), "color");
+//!   assert_eq!(declaration.value.slice(source), "red");
+//!   assert_eq!(declaration.values[0].slice(source), "red");
+//! }
+//! ```
+
+use crate::rules::{CssRule, Location};
+use cssparser::{ParseError, Parser, ParserState, Token};
+
+/// A range of byte offsets within the source text, from `start` up to but excluding `end`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Span {
+  /// The byte offset of the start of the span.
+  pub start: u32,
+  /// The byte offset of the end of the span.
+  pub end: u32,
+}
+
+impl Span {
+  /// Returns the number of bytes within the span.
+  pub fn len(&self) -> u32 {
+    self.end.saturating_sub(self.start)
+  }
+
+  /// Returns whether the span is empty, e.g. because the node was not parsed from source.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the source text within the span.
+  pub fn slice<'a>(&self, source: &'a str) -> &'a str {
+    &source[self.start as usize..self.end as usize]
+  }
+
+  pub(crate) fn between(start: &ParserState, end: &ParserState) -> Span {
+    Span {
+      start: start.position().byte_index() as u32,
+      end: end.position().byte_index() as u32,
+    }
+  }
+}
+
+/// The span of a declaration within a [DeclarationBlock](crate::declaration::DeclarationBlock).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct DeclarationSpan {
+  /// Whether the declaration is within the `!important` declarations.
+  pub important: bool,
+  /// The index of the declaration within the normal or `!important` declarations.
+  pub index: usize,
+  /// The span of the whole declaration, excluding the trailing semicolon.
+  pub span: Span,
+  /// The span of the property name.
+  pub name: Span,
+  /// The span of the value, excluding `!important`.
+  pub value: Span,
+  /// The spans of the top-level component values within the value, e.g. `1px`, `solid`, and `red`
+  /// in `border: 1px solid red`. Functions and blocks are a single component value, and commas are
+  /// not included.
+  pub values: Vec<Span>,
+}
+
+/// A trait for AST nodes with a source span.
+pub trait Spanned {
+  /// Returns the span of the node in the source text.
+  fn span(&self) -> Span;
+}
+
+impl Spanned for Span {
+  fn span(&self) -> Span {
+    *self
+  }
+}
+
+impl Spanned for Location {
+  fn span(&self) -> Span {
+    self.span
+  }
+}
+
+impl Spanned for DeclarationSpan {
+  fn span(&self) -> Span {
+    self.span
+  }
+}
+
+impl<'i, T> Spanned for CssRule<'i, T> {
+  fn span(&self) -> Span {
+    self.loc().map_or(Span::default(), |loc| loc.span)
+  }
+}
+
+/// Records the span of a rule that was parsed from the given start state up to the current position,
+/// along with the spans of the selectors of a style rule.
+pub(crate) fn set_rule_span<'i, T>(rule: &mut CssRule<'i, T>, input: &mut Parser<'i, '_>, start: &ParserState) {
+  let end = input.state();
+  if let Some(loc) = rule.loc_mut() {
+    loc.span = Span::between(start, &end);
+  }
+
+  if let CssRule::Style(style) = rule {
+    input.reset(start);
+    style.selector_spans = selector_spans(input);
+    input.reset(&end);
+  }
+}
+
+/// Returns the spans of the comma separated selectors before a `{}` block.
+fn selector_spans(input: &mut Parser<'_, '_>) -> Vec<Span> {
+  let mut spans = Vec::new();
+  let mut selector: Option<(ParserState, ParserState)> = None;
+  loop {
+    input.skip_whitespace();
+    let token_start = input.state();
+    let done = match input.next() {
+      Ok(Token::Comma) => false,
+      Ok(Token::CurlyBracketBlock) | Err(_) => true,
+      Ok(token) => {
+        let token = token.clone();
+        let token_end = end_of_token(input, &token);
+        selector = Some(match selector.take() {
+          Some((start, _)) => (start, token_end),
+          None => (token_start, token_end),
+        });
+        continue;
+      }
+    };
+
+    if let Some((start, end)) = selector.take() {
+      spans.push(Span::between(&start, &end));
+    }
+    if done {
+      return spans;
+    }
+  }
+}
+
+/// Returns the state after the given token, which was just returned by the parser. For functions
+/// and blocks, this is after the closing bracket rather than the opening one.
+fn end_of_token(input: &mut Parser<'_, '_>, token: &Token) -> ParserState {
+  if matches!(
+    token,
+    Token::Function(..) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock
+  ) {
+    let _ = input.parse_nested_block(|input| -> Result<(), ParseError<()>> {
+      while input.next().is_ok() {}
+      Ok(())
+    });
+  }
+  input.state()
+}
+
+/// Returns the span of a declaration that was parsed from the given start state up to the current
+/// position, which may include a trailing semicolon.
+pub(crate) fn declaration_span<'i>(
+  input: &mut Parser<'i, '_>,
+  start: &ParserState,
+  important: bool,
+  index: usize,
+) -> DeclarationSpan {
+  let end = input.state();
+  input.reset(start);
+
+  let mut name = None;
+  let mut colon = false;
+  let mut value: Option<(ParserState, ParserState)> = None;
+  let mut values = Vec::new();
+  let mut last = start.clone();
+  while input.position().byte_index() < end.position().byte_index() {
+    input.skip_whitespace();
+    let token_start = input.state();
+    let token = match input.next() {
+      Ok(token) => token.clone(),
+      Err(_) => break,
+    };
+
+    match token {
+      Token::Semicolon => break,
+      Token::Ident(..) if name.is_none() => name = Some(Span::between(&token_start, &input.state())),
+      Token::Colon if !colon => colon = true,
+      Token::Delim('!')
+        if value.is_some() && input.try_parse(|input| input.expect_ident_matching("important")).is_ok() =>
+      {
+        // `!important` is not part of the value.
+        last = input.state();
+        break;
+      }
+      _ => {
+        let token_end = end_of_token(input, &token);
+        if colon && token != Token::Comma {
+          values.push(Span::between(&token_start, &token_end));
+        }
+        value = Some(match value.take() {
+          Some((value_start, _)) => (value_start, token_end),
+          None => (token_start, token_end),
+        });
+      }
+    }
+    last = input.state();
+  }
+
+  input.reset(&end);
+  DeclarationSpan {
+    important,
+    index,
+    span: Span::between(start, &last),
+    name: name.unwrap_or_default(),
+    value: match value {
+      Some((value_start, value_end)) => Span::between(&value_start, &value_end),
+      None => Span::between(&last, &last),
+    },
+    values,
+  }
+}
@@ -0,0 +1,153 @@
+//! Usage statistics for style sheets.
+//!
+//! [StyleSheet::usage_stats](crate::stylesheet::StyleSheet::usage_stats) counts the properties, value functions,
+//! at-rules, and vendor prefixes used by a style sheet, e.g. to build dashboards that track how a code base
+//! evolves over time.
+
+use crate::printer::PrinterOptions;
+use crate::rules::{CssRule, CssRuleList};
+use crate::traits::ToCss;
+use crate::vendor_prefix::VendorPrefix;
+use cssparser::{ParseError, Parser, ParserInput, Token};
+use std::collections::BTreeMap;
+
+/// Counts of the features used by a style sheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct UsageStats {
+  /// The number of style rules, including nested rules.
+  pub style_rules: usize,
+  /// The number of selectors in all style rules.
+  pub selectors: usize,
+  /// The number of declarations, including those in `@keyframes`, `@page`, and other at-rules.
+  pub declarations: usize,
+  /// The number of `!important` declarations.
+  pub important_declarations: usize,
+  /// The number of declarations of each property, by name without a vendor prefix.
+  pub properties: BTreeMap<String, usize>,
+  /// The number of times each function is used in property values, e.g. `var`, `calc`, or `oklch`.
+  /// Functions are counted as they are serialized, so e.g. `rgb()` colors that are written as hex
+  /// colors are not counted, and `calc()` expressions that can be simplified are counted as their result.
+  pub functions: BTreeMap<String, usize>,
+  /// The number of each at-rule, by name without a vendor prefix, e.g. `media` or `keyframes`.
+  pub at_rules: BTreeMap<String, usize>,
+  /// The number of properties, at-rules, and functions using each vendor prefix, e.g. `-webkit-`.
+  pub vendor_prefixes: BTreeMap<String, usize>,
+}
+
+impl UsageStats {
+  pub(crate) fn build<T>(rules: &CssRuleList<T>) -> UsageStats {
+    let mut stats = UsageStats::default();
+    rules.for_each_rule(&mut |rule| stats.add_rule(rule));
+    rules.for_each_declaration_block(&mut |declarations| {
+      for (property, important) in declarations.iter() {
+        stats.declarations += 1;
+        if important {
+          stats.important_declarations += 1;
+        }
+
+        let property_id = property.property_id();
+        *stats.properties.entry(property_id.name().to_owned()).or_default() += 1;
+        stats.add_prefix(property_id.prefix());
+
+        if let Ok(value) = property.value_to_css_string(PrinterOptions::default()) {
+          let mut input = ParserInput::new(&value);
+          stats.add_functions(&mut Parser::new(&mut input));
+        }
+      }
+    });
+    stats
+  }
+
+  fn add_rule<T>(&mut self, rule: &CssRule<T>) {
+    let name = match rule {
+      CssRule::Style(style) => {
+        self.style_rules += 1;
+        self.selectors += style.selectors.0.len();
+        return;
+      }
+      CssRule::Nesting(nesting) => {
+        self.style_rules += 1;
+        self.selectors += nesting.style.selectors.0.len();
+        "nest"
+      }
+      CssRule::Media(..) => "media",
+      CssRule::Import(..) => "import",
+      CssRule::Keyframes(keyframes) => {
+        self.add_prefix(keyframes.vendor_prefix);
+        "keyframes"
+      }
+      CssRule::FontFace(..) => "font-face",
+      CssRule::FontPaletteValues(..) => "font-palette-values",
+      CssRule::Page(..) => "page",
+      CssRule::Supports(..) => "supports",
+      CssRule::CounterStyle(..) => "counter-style",
+      CssRule::Namespace(..) => "namespace",
+      CssRule::MozDocument(..) => {
+        self.add_prefix(VendorPrefix::Moz);
+        "document"
+      }
+      CssRule::Viewport(viewport) => {
+        self.add_prefix(viewport.vendor_prefix);
+        "viewport"
+      }
+      CssRule::CustomMedia(..) => "custom-media",
+      CssRule::LayerStatement(..) | CssRule::LayerBlock(..) => "layer",
+      CssRule::Property(..) => "property",
+      CssRule::Container(..) => "container",
+      CssRule::Scope(..) => "scope",
+      CssRule::StartingStyle(..) => "starting-style",
+      CssRule::PositionTry(..) => "position-try",
+      CssRule::Unknown(unknown) => unknown.name.as_ref(),
+      CssRule::CustomAtRule(rule) => rule.name.as_ref(),
+      CssRule::NestedDeclarations(..) | CssRule::Comment(..) | CssRule::Ignored | CssRule::Custom(..) => return,
+    };
+    *self.at_rules.entry(name.to_owned()).or_default() += 1;
+  }
+
+  fn add_prefix(&mut self, prefix: VendorPrefix) {
+    for prefix in prefix {
+      if prefix != VendorPrefix::None {
+        let name = prefix.to_css_string(PrinterOptions::default()).unwrap_or_default();
+        *self.vendor_prefixes.entry(name).or_default() += 1;
+      }
+    }
+  }
+
+  fn add_functions(&mut self, input: &mut Parser) {
+    while let Ok(token) = input.next() {
+      let name = match token {
+        Token::Function(name) => name.to_ascii_lowercase(),
+        Token::UnquotedUrl(..) => {
+          *self.functions.entry("url".into()).or_default() += 1;
+          continue;
+        }
+        Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+          let _ = input.parse_nested_block(|input| {
+            self.add_functions(input);
+            Ok::<_, ParseError<()>>(())
+          });
+          continue;
+        }
+        _ => continue,
+      };
+
+      let mut unprefixed = name.as_str();
+      for prefix in ["-webkit-", "-moz-", "-ms-", "-o-"] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+          *self.vendor_prefixes.entry(prefix.into()).or_default() += 1;
+          unprefixed = rest;
+        }
+      }
+      *self.functions.entry(unprefixed.to_owned()).or_default() += 1;
+      let _ = input.parse_nested_block(|input| {
+        self.add_functions(input);
+        Ok::<_, ParseError<()>>(())
+      });
+    }
+  }
+}
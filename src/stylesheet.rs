@@ -5,21 +5,50 @@
 
 use crate::compat::Feature;
 use crate::context::{DeclarationContext, PropertyHandlerContext};
+use crate::coverage::{Coverage, UnusedRule};
 use crate::css_modules::{CssModule, CssModuleExports, CssModuleReferences};
-use crate::declaration::{DeclarationBlock, DeclarationHandler};
+use crate::cssom::RuleListError;
+use crate::declaration::{DeclarationBlock, DeclarationHandler, DeclarationOrder};
 use crate::dependencies::Dependency;
+use crate::design_tokens::{DesignTokenOptions, DesignTokens};
+use crate::diff::{diff_rules, StyleSheetDiff};
+use crate::duplicates::DuplicateReport;
 use crate::error::{Error, ErrorLocation, MinifyErrorKind, ParserError, PrinterError, PrinterErrorKind};
-use crate::parser::{DefaultAtRule, DefaultAtRuleParser, TopLevelRuleParser};
+use crate::hacks::{find_legacy_hacks, remove_legacy_hacks, LegacyHack};
+use crate::important::ImportantAudit;
+#[cfg(feature = "json_patch")]
+use crate::json_patch::{JsonPatch, JsonPatchError};
+use crate::lossless::LosslessSource;
+use crate::mangle::mangle_idents;
+use crate::media_query::MediaEnvironment;
+use crate::parser::{parse_comments, DefaultAtRule, DefaultAtRuleParser, TopLevelRuleParser};
 use crate::printer::Printer;
-use crate::rules::{CssRule, CssRuleList, MinifyContext};
+use crate::properties::custom::TokenList;
+use crate::properties::PropertyId;
+use crate::purge::Purge;
+use crate::query::{
+  at_rule_matches, query_rules, serialize, style_selectors, AllDeclarations, AllRules, AllSelectors, RuleContext,
+  RuleMatch,
+};
+use crate::rules::container::ContainerQueryManifest;
+use crate::rules::property::PropertyRegistration;
+use crate::rules::{namespace::NamespaceRule, CssRule, CssRuleList, Location, MinifyContext};
+#[cfg(feature = "serde")]
+use crate::schema::AstVersion;
+use crate::selector::{
+  CustomElementUsage, HasPolyfill, Selector, SelectorComplexityReport, SelectorList, SpecificityAdjustment,
+  SpecificityReport,
+};
+use crate::stats::UsageStats;
 use crate::targets::Browsers;
-use crate::traits::{AtRuleParser, ToCss};
+use crate::traits::{AtRuleParser, ParseWithOptions, ToCss};
+use crate::var_graph::VarGraph;
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
 use cssparser::{Parser, ParserInput, RuleListParser};
 #[cfg(feature = "sourcemap")]
 use parcel_sourcemap::SourceMap;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub use crate::parser::ParserOptions;
 pub use crate::printer::PrinterOptions;
@@ -71,6 +100,10 @@ pub use crate::printer::PseudoClasses;
   schemars(rename = "StyleSheet", bound = "T: schemars::JsonSchema")
 )]
 pub struct StyleSheet<'i, 'o, T = DefaultAtRule> {
+  /// The version of the serialized AST. See the [schema](crate::schema) module.
+  #[cfg(feature = "serde")]
+  #[serde(default)]
+  version: AstVersion,
   /// A list of top-level rules within the style sheet.
   #[cfg_attr(feature = "serde", serde(borrow))]
   pub rules: CssRuleList<'i, T>,
@@ -82,6 +115,57 @@ pub struct StyleSheet<'i, 'o, T = DefaultAtRule> {
   #[cfg_attr(feature = "serde", serde(skip))]
   /// The options the style sheet was originally parsed with.
   options: ParserOptions<'o, 'i>,
+  /// The original source text, if the `lossless` parser option is enabled.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  lossless: Option<LosslessSource<'i>>,
+}
+
+/// An optimization level for the `minify` function of a [StyleSheet](StyleSheet).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum OptimizationLevel {
+  /// Only perform transforms that are safe regardless of any other CSS in the document.
+  #[default]
+  O1,
+  /// Additionally perform transforms that assume no CSS outside of the style sheet, including
+  /// inline styles, sets any of the same properties on the same elements. This enables merging
+  /// rules across the style sheet, flattening anonymous layers, removing `!important` from
+  /// declarations that nothing else could override, and removing declarations in `@media` rules
+  /// that repeat an earlier rule with the same selector.
+  O2,
+}
+
+/// How color fallbacks for wide-gamut colors, e.g. `display-p3` or `oklch()`, are added
+/// for targets that may not support them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFallbackStrategy {
+  /// Fallback declarations are added before the original declaration in the same rule.
+  #[default]
+  Inline,
+  /// The rule only includes the lowest fallback, and is duplicated with the wide-gamut colors
+  /// inside a `@supports (color: lab(0% 0 0))` rule.
+  Supports,
+  /// The rule only includes the lowest fallback, and is duplicated with the wide-gamut colors
+  /// inside a `@media (color-gamut: p3)` rule, so they only apply on wide-gamut displays.
+  MediaQuery,
+}
+
+/// How logical properties and values, e.g. `margin-inline-start` or `text-align: start`, are
+/// compiled to physical equivalents for targets that do not support them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogicalPropertyStrategy {
+  /// Both physical variants are added in extra rules with the `:dir(ltr)` and `:dir(rtl)` pseudo classes,
+  /// which are compiled to `:lang()` when `:dir()` is not supported either.
+  #[default]
+  Dir,
+  /// The physical properties for left-to-right text are added inline, assuming the whole document is `ltr`.
+  Ltr,
+  /// The physical properties for right-to-left text are added inline, assuming the whole document is `rtl`.
+  Rtl,
+  /// The physical properties are added inline with both values selected by the `--ltr` and `--rtl`
+  /// custom properties, e.g. `margin-left: var(--ltr, 10px) var(--rtl, 0)`. These must be defined by
+  /// the page, e.g. `[dir=ltr] { --ltr: initial; --rtl: ; }` and `[dir=rtl] { --ltr: ; --rtl: initial; }`.
+  /// Physical sides that are only set in one direction fall back to their initial value in the other.
+  CustomProperties,
 }
 
 /// Options for the `minify` function of a [StyleSheet](StyleSheet)
@@ -90,9 +174,173 @@ pub struct StyleSheet<'i, 'o, T = DefaultAtRule> {
 pub struct MinifyOptions {
   /// Browser targets to compile the CSS for.
   pub targets: Option<Browsers>,
+  /// The optimization level. [OptimizationLevel::O2](OptimizationLevel::O2) enables all of the
+  /// transforms that assume no other CSS interacts with the style sheet, in addition to the
+  /// options below.
+  pub optimization_level: OptimizationLevel,
   /// A list of known unused symbols, including CSS class names,
   /// ids, and `@keyframe` names. The declarations of these will be removed.
   pub unused_symbols: HashSet<String>,
+  /// The class names, ids, and tag names used by the content of a site. When given, selectors that require
+  /// any other class name, id, or tag name are removed, and rules with no remaining selectors are removed
+  /// entirely. See the [purge](crate::purge) module for details.
+  pub purge: Option<Purge>,
+  /// Whether to remove selectors containing `:has()` when it is not supported by the targets.
+  /// Rules with no remaining selectors are removed entirely. Either way, a warning is emitted
+  /// if the style sheet was parsed with the `warnings` option.
+  pub remove_unsupported_has: bool,
+  /// Whether to normalize attribute selectors so that logically equal selectors can be deduplicated.
+  /// Values matched case-insensitively (e.g. `[type="Text" i]`) are lowercased, and duplicate
+  /// attribute selectors within a compound selector are removed. Quotes are already omitted
+  /// where possible when printing with the `minify` option.
+  pub normalize_attribute_selectors: bool,
+  /// Whether to expand `:is()` selectors that contain combinators into separate selectors when `:is()` is
+  /// not supported by the targets, e.g. `:is(.a .b, .c) .d` becomes `.a .b .d, .c .d`. Otherwise, these are
+  /// left as is, since only `:is()` selectors with compound selectors can use `:-webkit-any()` instead.
+  /// The expanded selectors have their own specificity. Selectors with such an `:is()` after a combinator,
+  /// e.g. `.x :is(.a > .b)`, are left as is, since writing the argument in its place would not be equivalent.
+  pub expand_is_selectors: bool,
+  /// Whether to keep style rules with no declarations, e.g. rules that only exist so that a class
+  /// is exported by CSS modules. Other empty rules, such as empty `@media` blocks, are always removed.
+  pub preserve_empty_rules: bool,
+  /// Whether to emit a warning for namespace prefixes in selectors (e.g. `svg|a` or `[xlink|href]`)
+  /// that are not declared by an `@namespace` rule.
+  pub strict_namespaces: bool,
+  /// A static environment to evaluate `@media` rules against, e.g. for device-class-specific builds.
+  /// Rules whose query can never match in the environment are removed, and rules whose query always
+  /// matches are replaced with their contents. Queries that depend on anything the environment does
+  /// not specify are kept.
+  pub media_environment: Option<MediaEnvironment>,
+  /// Whether to add an equivalent `-ms-high-contrast` query to `@media` rules that use the `forced-colors`
+  /// media feature, when the targets include Internet Explorer or legacy Edge.
+  pub legacy_high_contrast: bool,
+  /// Whether to add a fallback declaration using a plain `url()` before values containing `image-set()`,
+  /// for targets that do not support `image-set()` at all. The 1x image is used when available.
+  pub image_set_fallback: bool,
+  /// Values to substitute for `env()` references at build time by variable name, e.g. `safe-area-inset-top`
+  /// as `0px` for platforms that predefine them. Other names, and references with indices, are left as is.
+  pub environment_variables: HashMap<String, String>,
+  /// Whether to replace typed `attr()` functions, e.g. `attr(data-size px, 1em)`, with their fallback value
+  /// when the targets only support `attr()` in the `content` property. Those without a fallback are kept.
+  pub replace_typed_attr: bool,
+  /// Whether to remove declarations that are overridden by a later style rule with an identical
+  /// selector list, e.g. in style sheets produced by repeated utility generation. Declarations are kept when
+  /// the later value may not be supported by the browser targets, since they may be fallbacks for it.
+  pub remove_shadowed_declarations: bool,
+  /// Whether to merge `@media` rules into an earlier `@media` rule with the same query, when none
+  /// of the rules in between set any of the same properties. Adjacent rules are always merged.
+  pub merge_media_rules: bool,
+  /// Whether to merge style rules with identical declarations into an earlier rule even when they are
+  /// not adjacent, e.g. `.a{color:red} .b{width:0} .c{color:red}` becomes `.a,.c{color:red} .b{width:0}`.
+  /// Rules are only merged when none of the rules in between set any of the same properties.
+  pub merge_non_adjacent_rules: bool,
+  /// Skips the cascade analysis of `merge_non_adjacent_rules`, merging all rules with identical declarations.
+  /// This is only safe when the order of rules is known not to matter.
+  pub unsafe_merge_non_adjacent_rules: bool,
+  /// Whether to merge style rules with the same selector even when they are not adjacent, collapsing
+  /// complementary longhands into shorthands, e.g. `.a{margin-top:0} .b{color:red} .a{margin-bottom:0}`.
+  /// Rules are only merged when none of the rules in between set any of the same properties.
+  pub merge_same_selector_rules: bool,
+  /// Whether to replace anonymous `@layer` blocks with their contents when no other rule in the style sheet sets
+  /// any of the same properties. This assumes that no other style sheet in the document sets these properties,
+  /// since unlayered rules take precedence over layered rules.
+  pub flatten_anonymous_layers: bool,
+  /// Whether to rename `@keyframes`, `@counter-style`, container, and grid line and area names to short
+  /// identifiers. The mapping from original to new names is reported in the [MinifyResult](MinifyResult)
+  /// so that references from JavaScript can be updated. This option is ignored for CSS modules.
+  pub mangle_idents: bool,
+  /// An order in which to sort the declarations within each style rule, producing deterministic
+  /// output for generated CSS that also tends to compress better. Declarations are only reordered
+  /// when they cannot affect the same value, so the result is equivalent in the cascade.
+  pub sort_declarations: Option<DeclarationOrder>,
+  /// Whether to remove vendor prefixed declarations and rules that are followed by their unprefixed
+  /// equivalent, e.g. `-webkit-border-radius` before `border-radius`, when none of the targets need the
+  /// prefix anymore. This goes beyond the prefixes removed by default, covering unknown properties and
+  /// properties that are otherwise passed through as is. Ignored when no targets are given.
+  pub remove_obsolete_prefixes: bool,
+  /// Whether to remove legacy hacks that none of the targets need, such as `_zoom: 1` and `color: red\9`
+  /// declarations for old versions of Internet Explorer, and `@-moz-document` rules when Firefox is not
+  /// targeted. Obsolete prefixes are also removed, as with `remove_obsolete_prefixes`. See the
+  /// [hacks](crate::hacks) module. Ignored when no targets are given.
+  pub remove_legacy_hacks: bool,
+  /// Whether to remove `!important` from declarations in style rules when no other declaration in the style
+  /// sheet sets an overlapping property, as with [OptimizationLevel::O2](OptimizationLevel::O2), without
+  /// enabling its other transforms. See the [important](crate::important) module.
+  pub remove_unneeded_important: bool,
+  /// Whether to remove `@font-face` rules whose font family is not referenced by any declaration.
+  /// The removed font families are reported in the [MinifyResult](MinifyResult).
+  pub remove_unused_font_faces: bool,
+  /// Whether to remove `@keyframes` rules whose name is not referenced by any `animation` or
+  /// `animation-name` declaration. The removed names are reported in the [MinifyResult](MinifyResult).
+  pub remove_unused_keyframes: bool,
+  /// Names of `@keyframes` rules to keep when `remove_unused_keyframes` is enabled, e.g. animations
+  /// that are applied dynamically from JavaScript.
+  pub preserved_keyframes: HashSet<String>,
+  /// Whether to remove `@keyframes` rules with the same keyframes as an earlier rule, and update
+  /// `animation` and `animation-name` declarations to reference the earlier rule instead. Names listed in
+  /// `preserved_keyframes` are never removed. The merged names are reported in the [MinifyResult](MinifyResult).
+  /// This option is ignored for CSS modules.
+  pub merge_duplicate_keyframes: bool,
+  /// Whether to remove items from `transition` declarations for properties that are not set by any
+  /// declaration in the style sheet, and so never change. This assumes that the properties are not
+  /// set by other style sheets or inline styles, e.g. from JavaScript.
+  pub remove_unused_transitions: bool,
+  /// Whether to remove custom property declarations and `@property` rules for custom properties
+  /// that are never read by a `var()` in the style sheet.
+  pub remove_unused_custom_properties: bool,
+  /// Names of custom properties to keep when `remove_unused_custom_properties` is enabled,
+  /// e.g. variables read from JavaScript via `getPropertyValue`.
+  pub preserved_custom_properties: HashSet<String>,
+  /// Whether to replace `var()` references with the value of the custom property when it is defined exactly
+  /// once, in a top-level `:root` rule, with a value that does not reference other variables. Definitions
+  /// that are no longer referenced are removed, unless listed in `preserved_custom_properties`.
+  pub inline_custom_properties: bool,
+  /// Names of custom properties that may be changed at runtime, e.g. with `style.setProperty()` from
+  /// JavaScript. These are never inlined by the `inline_custom_properties` option.
+  pub dynamic_custom_properties: HashSet<String>,
+  /// How wide-gamut colors are downleveled for targets that may not support them. Fallbacks in
+  /// style attributes, `@keyframes`, and other rules without selectors are always added inline.
+  pub color_fallback_strategy: ColorFallbackStrategy,
+  /// How logical properties are compiled for targets that do not support them. Only the strategies that
+  /// add declarations inline apply to style attributes; with the default, logical properties are kept there.
+  pub logical_property_strategy: LogicalPropertyStrategy,
+}
+
+/// A result returned from `minify`, including metadata depending on the input options.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MinifyResult {
+  /// The font families whose `@font-face` rules were removed by the `remove_unused_font_faces` option.
+  pub removed_font_faces: Vec<String>,
+  /// The names of the `@keyframes` rules removed by the `remove_unused_keyframes` option.
+  pub removed_keyframes: Vec<String>,
+  /// The names of the `@keyframes` rules merged by the `merge_duplicate_keyframes` option,
+  /// mapped to the name of the rule that replaced them.
+  pub merged_keyframes: BTreeMap<String, String>,
+  /// The identifiers renamed by the `mangle_idents` option.
+  pub mangled_idents: MangledIdents,
+  /// The `@custom-media` rules in the style sheet, mapped to their media queries with any references
+  /// to other custom media resolved. Only populated when the `custom_media` parser option is enabled.
+  pub custom_media: BTreeMap<String, String>,
+}
+
+/// A mapping from original to mangled names for each kind of identifier, produced
+/// by the `mangle_idents` minify option.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct MangledIdents {
+  /// Renamed `@keyframes` names.
+  pub keyframes: BTreeMap<String, String>,
+  /// Renamed `@counter-style` names.
+  pub counter_styles: BTreeMap<String, String>,
+  /// Renamed container names.
+  pub container_names: BTreeMap<String, String>,
+  /// Renamed grid area and line names. Line names ending in `-start` or `-end` are renamed
+  /// along with the area of the same name, e.g. `header-start` becomes `a-start` if `header` becomes `a`.
+  pub grid_names: BTreeMap<String, String>,
 }
 
 /// A result returned from `to_css`, including the serialize CSS
@@ -110,6 +358,162 @@ pub struct ToCssResult {
   /// A list of dependencies (e.g. `@import` or `url()`) found in
   /// the style sheet, if the `analyze_dependencies` option is enabled.
   pub dependencies: Option<Vec<Dependency>>,
+  /// A list of `:has()` selectors that were replaced with attribute selectors,
+  /// if the `has_polyfill` option is enabled and any were found.
+  pub has_polyfills: Option<Vec<HasPolyfill>>,
+  /// The number of bytes each top-level rule contributes to the output, in source order,
+  /// if the `size_report` option is enabled.
+  pub size_report: Option<Vec<RuleSize>>,
+  /// The size of the output grouped by media query, layer, source file, and property,
+  /// if the `size_report` option is enabled.
+  pub size_breakdown: Option<SizeBreakdown>,
+}
+
+/// The number of bytes a top-level rule contributes to the output of [to_css](StyleSheet::to_css).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct RuleSize {
+  /// The prelude of the rule as printed, e.g. a selector list or `@media print`.
+  pub prelude: String,
+  /// The number of bytes the rule occupies in the output, excluding the whitespace between rules.
+  pub bytes: usize,
+}
+
+impl RuleSize {
+  fn from_range(code: &str, range: std::ops::Range<usize>) -> RuleSize {
+    let text = &code[range.clone()];
+    let end = text.find(|c| c == '{' || c == ';').unwrap_or(text.len());
+    RuleSize {
+      prelude: text[..end].trim().to_owned(),
+      bytes: range.len(),
+    }
+  }
+}
+
+/// The size of the output of [to_css](StyleSheet::to_css) grouped in several ways, to find which
+/// breakpoint, layer, or import contributes most to it. Each list is sorted from largest to smallest.
+///
+/// Bytes written by a rule are attributed to the innermost rule, e.g. the selector and declarations of a
+/// style rule within `@media print` are counted for the style rule, and the `@media print {}` wrapper is
+/// counted for the `@media` rule. The whitespace between rules is not included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct SizeBreakdown {
+  /// The number of bytes written for rules within each `@media` rule, by query. The queries of nested
+  /// `@media` rules are joined with `and`. Rules outside of `@media` rules are not included.
+  pub media_queries: Vec<SizeGroup>,
+  /// The number of bytes written for rules within each `@layer` block, by layer name. Anonymous layers
+  /// are named `<anonymous>`. Unlayered rules are not included.
+  pub layers: Vec<SizeGroup>,
+  /// The number of bytes written for rules from each source file, e.g. the files imported by a bundle.
+  pub sources: Vec<SizeGroup>,
+  /// The number of bytes written for declarations of each property, excluding the separators between them.
+  pub properties: Vec<SizeGroup>,
+}
+
+/// A number of bytes written for a media query, layer, source file, or property in a [SizeBreakdown](SizeBreakdown).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SizeGroup {
+  /// The media query, layer name, file name, or property name.
+  pub name: String,
+  /// The number of bytes.
+  pub bytes: usize,
+}
+
+/// A feature used by a style sheet that is not supported by all of the browser targets and cannot be
+/// transpiled for them. Returned by [compat_report](StyleSheet::compat_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct CompatIssue {
+  /// The name of the feature, e.g. `css-has` or `anchor-positioning`.
+  pub feature: String,
+  /// The selector, declaration, or at-rule prelude that uses the feature.
+  pub value: String,
+  /// The file containing the rule that uses the feature.
+  pub filename: String,
+  /// The location of the rule that uses the feature.
+  pub loc: Location,
+  /// The targets that don't support the feature, e.g. `safari 15.4`.
+  pub browsers: Vec<String>,
+}
+
+impl std::fmt::Display for CompatIssue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{}:{}:{}: {} is not supported by {}: {}",
+      self.filename,
+      self.loc.line + 1,
+      self.loc.column,
+      self.feature,
+      self.browsers.join(", "),
+      self.value
+    )
+  }
+}
+
+impl CompatIssue {
+  fn new(feature: Feature, value: String, loc: Location, sources: &[String], targets: Browsers) -> CompatIssue {
+    // Feature names are derived from their enum variants, e.g. `CssHas` becomes `css-has`.
+    let mut name = String::new();
+    for (i, c) in format!("{:?}", feature).chars().enumerate() {
+      if c.is_ascii_uppercase() && i > 0 {
+        name.push('-');
+      }
+      name.push(c.to_ascii_lowercase());
+    }
+
+    let mut browsers = Vec::new();
+    macro_rules! browser {
+      ($name: ident) => {
+        if let Some(version) = targets.$name {
+          let target = Browsers {
+            $name: Some(version),
+            ..Browsers::default()
+          };
+          if !feature.is_compatible(target) {
+            let (major, minor) = (version >> 16, (version >> 8) & 0xff);
+            browsers.push(if minor > 0 {
+              format!("{} {}.{}", stringify!($name), major, minor)
+            } else {
+              format!("{} {}", stringify!($name), major)
+            });
+          }
+        }
+      };
+    }
+
+    browser!(android);
+    browser!(chrome);
+    browser!(edge);
+    browser!(firefox);
+    browser!(ie);
+    browser!(ios_saf);
+    browser!(opera);
+    browser!(safari);
+    browser!(samsung);
+
+    CompatIssue {
+      feature: name,
+      value,
+      filename: sources.get(loc.source_index as usize).cloned().unwrap_or_default(),
+      loc,
+      browsers,
+    }
+  }
 }
 
 impl<'i, 'o> StyleSheet<'i, 'o, DefaultAtRule> {
@@ -117,6 +521,52 @@ impl<'i, 'o> StyleSheet<'i, 'o, DefaultAtRule> {
   pub fn parse(code: &'i str, options: ParserOptions<'o, 'i>) -> Result<Self, Error<ParserError<'i>>> {
     Self::parse_with(code, options, &mut DefaultAtRuleParser)
   }
+
+  /// Visits the values in the style sheet with an [AsyncVisitor](crate::async_visitor::AsyncVisitor), awaiting
+  /// the future returned for each value in traversal order. See the [async_visitor](crate::async_visitor) module.
+  ///
+  /// Fails to compile if the visitor's `TYPES` are not a subset of
+  /// [SUPPORTED_TYPES](crate::async_visitor::SUPPORTED_TYPES).
+  #[cfg(feature = "async_visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "async_visitor")))]
+  pub fn visit_async<'a, V: crate::async_visitor::AsyncVisitor<'i>>(
+    &'a mut self,
+    visitor: &'a mut V,
+  ) -> impl std::future::Future<Output = Result<(), V::Error>> + crate::async_visitor::Captures<'i> + 'a {
+    crate::async_visitor::visit_rules(&mut self.rules, visitor)
+  }
+
+  /// Visits the top-level rules of the style sheet in parallel with a [ParallelVisitor](crate::visitor::ParallelVisitor).
+  /// The [visit_rule_list](Visitor::visit_rule_list) method of the visitor is not called for the top-level rule list.
+  /// If visiting a rule fails, one of the errors is returned, and the remaining rules may or may not have been visited.
+  #[cfg(feature = "parallel")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+  pub fn visit_parallel<V>(&mut self, visitor: &mut V) -> Result<(), V::Error>
+  where
+    V: crate::visitor::ParallelVisitor<'i>,
+    V::Error: Send,
+  {
+    use rayon::prelude::*;
+
+    let shared = &*visitor;
+    let forked = self
+      .rules
+      .0
+      .par_iter_mut()
+      .try_fold(
+        || shared.fork(),
+        |mut forked, rule| {
+          rule.visit(&mut forked)?;
+          Ok(forked)
+        },
+      )
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for forked in forked {
+      visitor.merge(forked);
+    }
+    Ok(())
+  }
 }
 
 impl<'i, 'o, T> StyleSheet<'i, 'o, T>
@@ -130,10 +580,13 @@ where
     options: ParserOptions<'o, 'i>,
   ) -> StyleSheet<'i, 'o, T> {
     StyleSheet {
+      #[cfg(feature = "serde")]
+      version: AstVersion,
       sources,
       source_map_urls: Vec::new(),
       rules,
       options,
+      lossless: None,
     }
   }
 
@@ -145,12 +598,34 @@ where
   ) -> Result<Self, Error<ParserError<'i>>> {
     let mut input = ParserInput::new(&code);
     let mut parser = Parser::new(&mut input);
+    let mut lossless = if options.lossless {
+      Some(LosslessSource::default())
+    } else {
+      None
+    };
     let mut rule_list_parser =
       RuleListParser::new_for_stylesheet(&mut parser, TopLevelRuleParser::new(&mut options, at_rule_parser));
 
     let mut rules = vec![];
-    while let Some(rule) = rule_list_parser.next() {
-      let rule = match rule {
+    loop {
+      let comments_start = rule_list_parser.input.position();
+      let comments = parse_comments(rule_list_parser.input, rule_list_parser.parser.options);
+      if let Some(lossless) = &mut lossless {
+        lossless.push_comments(code, comments_start, &comments);
+      }
+      rules.extend(comments.into_iter().map(CssRule::Comment));
+
+      let start = {
+        rule_list_parser.input.skip_whitespace();
+        rule_list_parser.input.state()
+      };
+      let rule = match rule_list_parser.next() {
+        Some(rule) => rule,
+        None => break,
+      };
+
+      #[allow(unused_mut)]
+      let mut rule = match rule {
         Ok((_, CssRule::Ignored)) => continue,
         Ok((_, rule)) => rule,
         Err((e, _)) => {
@@ -164,14 +639,26 @@ where
         }
       };
 
+      if let Some(lossless) = &mut lossless {
+        lossless.push_rule(code, start.position(), rule_list_parser.input.position(), &rule);
+      }
+      #[cfg(feature = "spans")]
+      crate::span::set_rule_span(&mut rule, rule_list_parser.input, &start);
       rules.push(rule)
     }
 
+    if let Some(lossless) = &mut lossless {
+      lossless.finish(code);
+    }
+
     Ok(StyleSheet {
+      #[cfg(feature = "serde")]
+      version: AstVersion,
       sources: vec![options.filename.clone()],
       source_map_urls: vec![parser.current_source_map_url().map(|s| s.to_owned())],
       rules: CssRuleList(rules),
       options,
+      lossless,
     })
   }
 
@@ -188,17 +675,17 @@ where
   }
 
   /// Minify and transform the style sheet for the provided browser targets.
-  pub fn minify(&mut self, options: MinifyOptions) -> Result<(), Error<MinifyErrorKind>> {
+  pub fn minify(&mut self, options: MinifyOptions) -> Result<MinifyResult, Error<MinifyErrorKind>> {
     let mut context = PropertyHandlerContext::new(options.targets, &options.unused_symbols);
+    context.color_fallback_strategy = options.color_fallback_strategy;
+    context.logical_property_strategy = options.logical_property_strategy;
+    context.image_set_fallback = options.image_set_fallback;
     let mut handler = DeclarationHandler::new(options.targets);
     let mut important_handler = DeclarationHandler::new(options.targets);
 
     // @custom-media rules may be defined after they are referenced, but may only be defined at the top level
     // of a stylesheet. Do a pre-scan here and create a lookup table by name.
-    let custom_media = if self.options.custom_media
-      && options.targets.is_some()
-      && !Feature::CustomMediaQueries.is_compatible(options.targets.unwrap())
-    {
+    let custom_media = if self.options.custom_media {
       let mut custom_media = HashMap::new();
       for rule in &self.rules.0 {
         if let CssRule::CustomMedia(rule) = rule {
@@ -210,14 +697,55 @@ where
       None
     };
 
+    let lower_custom_media = options
+      .targets
+      .map_or(false, |targets| !Feature::CustomMediaQueries.is_compatible(targets));
+
+    let namespaces = if options.strict_namespaces {
+      let mut namespaces = HashSet::new();
+      for rule in &self.rules.0 {
+        if let CssRule::Namespace(NamespaceRule {
+          prefix: Some(prefix), ..
+        }) = rule
+        {
+          namespaces.insert(prefix.0.clone());
+        }
+      }
+      Some(namespaces)
+    } else {
+      None
+    };
+
+    // Remove hacks before minifying, so that they are not merged with the declarations they override.
+    if options.remove_legacy_hacks {
+      if let Some(targets) = options.targets {
+        remove_legacy_hacks(&mut self.rules, targets);
+      }
+    }
+
+    let aggressive = options.optimization_level >= OptimizationLevel::O2;
     let mut ctx = MinifyContext {
       targets: &options.targets,
       handler: &mut handler,
       important_handler: &mut important_handler,
       handler_context: &mut context,
       unused_symbols: &options.unused_symbols,
-      custom_media,
+      purge: options.purge.as_ref(),
+      custom_media: if lower_custom_media { custom_media.clone() } else { None },
       css_modules: self.options.css_modules.is_some(),
+      remove_unsupported_has: options.remove_unsupported_has,
+      remove_shadowed_declarations: options.remove_shadowed_declarations || aggressive,
+      merge_media_rules: options.merge_media_rules || aggressive,
+      merge_non_adjacent_rules: options.merge_non_adjacent_rules || aggressive,
+      unsafe_merge_non_adjacent_rules: options.unsafe_merge_non_adjacent_rules,
+      merge_same_selector_rules: options.merge_same_selector_rules || aggressive,
+      normalize_attribute_selectors: options.normalize_attribute_selectors,
+      expand_is_selectors: options.expand_is_selectors,
+      media_environment: options.media_environment.as_ref(),
+      legacy_high_contrast: options.legacy_high_contrast,
+      preserve_empty_rules: options.preserve_empty_rules,
+      namespaces,
+      warnings: Vec::new(),
     };
 
     self.rules.minify(&mut ctx, false).map_err(|e| Error {
@@ -228,7 +756,389 @@ where
       )),
     })?;
 
-    Ok(())
+    // Resolve each definition, including references to other custom media, so they can be exported.
+    let mut resolved_custom_media = BTreeMap::new();
+    if let Some(custom_media) = &custom_media {
+      // Visit the rules in source order so errors are reported deterministically.
+      let mut rules: Vec<_> = custom_media.values().collect();
+      rules.sort_by_key(|rule| (rule.loc.source_index, rule.loc.line, rule.loc.column));
+      for rule in rules {
+        let mut query = rule.query.clone();
+        query.transform_custom_media(rule.loc, custom_media).map_err(|e| Error {
+          kind: e.kind,
+          loc: Some(ErrorLocation::new(
+            e.loc,
+            self.sources[e.loc.source_index as usize].clone(),
+          )),
+        })?;
+        query.simplify();
+        if let Ok(query) = query.to_css_string(PrinterOptions::default()) {
+          resolved_custom_media.insert(rule.name.to_string(), query);
+        }
+      }
+    }
+
+    if let Some(warnings) = &self.options.warnings {
+      if let Ok(mut warnings) = warnings.write() {
+        for warning in ctx.warnings {
+          warnings.push(Error {
+            kind: warning.kind,
+            loc: Some(ErrorLocation::new(
+              warning.loc,
+              self.sources[warning.loc.source_index as usize].clone(),
+            )),
+          });
+        }
+      }
+    }
+
+    if options.flatten_anonymous_layers || aggressive {
+      self.rules.flatten_anonymous_layers();
+    }
+
+    if aggressive {
+      self.rules.remove_redundant_media_declarations();
+    }
+
+    if aggressive || options.remove_unneeded_important {
+      self.rules.remove_unneeded_important();
+    }
+
+    if options.remove_obsolete_prefixes || options.remove_legacy_hacks {
+      if let Some(targets) = options.targets {
+        self.rules.remove_obsolete_prefixes(targets);
+      }
+    }
+
+    if !options.environment_variables.is_empty() {
+      let vars = options
+        .environment_variables
+        .iter()
+        .map(|(name, value)| (name.clone(), TokenList::tokenize_owned(value)))
+        .collect();
+      self.rules.substitute_environment_variables(&vars);
+    }
+
+    if options.replace_typed_attr {
+      if let Some(targets) = options.targets {
+        if !Feature::TypedAttr.is_compatible(targets) {
+          self.rules.replace_typed_attr();
+        }
+      }
+    }
+
+    if options.inline_custom_properties {
+      self
+        .rules
+        .inline_custom_properties(&options.dynamic_custom_properties, &options.preserved_custom_properties);
+    }
+
+    // Removing custom properties may remove the last reference to a font or animation, so do it first.
+    if options.remove_unused_custom_properties {
+      self.rules.remove_unused_custom_properties(&options.preserved_custom_properties);
+    }
+
+    let mut result = MinifyResult {
+      custom_media: resolved_custom_media,
+      ..MinifyResult::default()
+    };
+    if options.remove_unused_font_faces {
+      result.removed_font_faces = self.rules.remove_unused_font_faces();
+    }
+    if options.remove_unused_keyframes {
+      result.removed_keyframes = self.rules.remove_unused_keyframes(&options.preserved_keyframes);
+    }
+    if options.merge_duplicate_keyframes && self.options.css_modules.is_none() {
+      result.merged_keyframes = self.rules.merge_duplicate_keyframes(&options.preserved_keyframes);
+    }
+    if options.remove_unused_transitions {
+      self.rules.remove_unused_transitions();
+    }
+    if let Some(order) = options.sort_declarations {
+      self.rules.sort_declarations(order);
+    }
+    if options.mangle_idents && self.options.css_modules.is_none() {
+      result.mangled_idents = mangle_idents(&mut self.rules);
+    }
+
+    Ok(result)
+  }
+
+  /// Scopes every style rule in the style sheet under the given root selector, e.g. to embed
+  /// the styles for a widget into a host page without affecting the rest of the page.
+  ///
+  /// Selectors that target the document root (`html`, `body`, and `:root`) are rewritten to
+  /// target the scope instead. Rules that do not contain selectors, such as `@keyframes`, are unaffected.
+  pub fn scope(&mut self, selector: &SelectorList<'i>) {
+    self.rules.scope(selector)
+  }
+
+  /// Uniformly raises or lowers the specificity of every selector in the style sheet, e.g. to
+  /// zero out the specificity of a utility layer, or to override third-party CSS.
+  ///
+  /// Note that [SpecificityAdjustment::Zero](SpecificityAdjustment::Zero) relies on `:where()`,
+  /// which is not supported by older browsers.
+  pub fn adjust_specificity(&mut self, adjustment: SpecificityAdjustment) {
+    self.rules.adjust_specificity(adjustment, false)
+  }
+
+  /// Replaces `@container` rules with their contents for use with a runtime container query polyfill,
+  /// e.g. to support browsers without container queries from a single style sheet.
+  ///
+  /// Each unique container query is assigned an id, and an `[attr~="id"]` selector is added to the
+  /// rules within it, which the polyfill can match by adding the id to the given attribute of an
+  /// element while the query matches. The returned manifest lists the ids along with their conditions,
+  /// and the selectors of the rules that establish query containers.
+  pub fn polyfill_container_queries(&mut self, attr: &str) -> Result<ContainerQueryManifest, PrinterError> {
+    let mut manifest = ContainerQueryManifest::default();
+    self.rules.polyfill_container_queries(attr, &[], &mut manifest)?;
+    Ok(manifest)
+  }
+
+  /// Returns the `@property` rules in the style sheet as `CSS.registerProperty()` registrations, e.g. to
+  /// register custom properties from JavaScript in browsers that do not support `@property`. If `remove`
+  /// is true, the rules are also removed from the style sheet.
+  ///
+  /// If targets are given and all of them support `@property`, nothing is returned and the rules are kept.
+  pub fn extract_property_rules(
+    &mut self,
+    targets: Option<Browsers>,
+    remove: bool,
+  ) -> Result<Vec<PropertyRegistration>, PrinterError> {
+    if let Some(targets) = targets {
+      if Feature::AtProperty.is_compatible(targets) {
+        return Ok(Vec::new());
+      }
+    }
+
+    self.rules.extract_property_rules(remove)
+  }
+
+  /// Returns the features used by the style sheet that are not supported by all of the given targets,
+  /// with the location of the rule that uses each of them.
+  ///
+  /// This should be called after [minify](StyleSheet::minify) with the same targets, so that features
+  /// which are transpiled or given fallbacks are not reported.
+  pub fn compat_report(&self, targets: Browsers) -> Vec<CompatIssue> {
+    let mut issues = Vec::new();
+    self.rules.compat_issues(targets, &mut issues);
+    issues
+      .into_iter()
+      .map(|(feature, value, loc)| CompatIssue::new(feature, value, loc, &self.sources, targets))
+      .collect()
+  }
+
+  /// Returns the colors, font stacks, spacing values, radii, and shadows used by the style sheet,
+  /// e.g. to export them as a design tokens document. See the [design_tokens](crate::design_tokens) module.
+  pub fn design_tokens(&self, options: DesignTokenOptions) -> DesignTokens {
+    DesignTokens::extract(&self.rules, &options)
+  }
+
+  /// Returns a graph of the custom properties defined and referenced by the style sheet.
+  /// See the [var_graph](crate::var_graph) module.
+  pub fn var_graph(&self) -> VarGraph {
+    VarGraph::build(&self.rules)
+  }
+
+  /// Returns the style rules with identical declaration blocks, the selectors repeated across the style sheet,
+  /// and the declarations repeated within the same scope, along with their sizes in bytes.
+  pub fn duplicate_report(&self) -> DuplicateReport {
+    DuplicateReport::build(&self.rules)
+  }
+
+  /// Returns the specificity of the selectors of every style rule in the style sheet, including nested rules.
+  pub fn specificity_report(&self) -> SpecificityReport {
+    let mut report = SpecificityReport::default();
+    self.rules.for_each_rule(&mut |rule| match rule {
+      CssRule::Style(style) => report.add_selectors(&style.selectors, style.loc),
+      CssRule::Nesting(nesting) => report.add_selectors(&nesting.style.selectors, nesting.style.loc),
+      _ => {}
+    });
+    report
+  }
+
+  /// Returns complexity metrics for the selectors of every style rule in the style sheet, including nested rules,
+  /// e.g. to flag selectors that are expensive for browsers to match.
+  pub fn selector_complexity(&self) -> SelectorComplexityReport {
+    let mut report = SelectorComplexityReport::default();
+    self.rules.for_each_rule(&mut |rule| match rule {
+      CssRule::Style(style) => report.add_selectors(&style.selectors, style.loc),
+      CssRule::Nesting(nesting) => report.add_selectors(&nesting.style.selectors, nesting.style.loc),
+      _ => {}
+    });
+    report
+  }
+
+  /// Returns the number of times each property, value function, at-rule, and vendor prefix is used by the
+  /// style sheet. See the [stats](crate::stats) module.
+  pub fn usage_stats(&self) -> UsageStats {
+    UsageStats::build(&self.rules)
+  }
+
+  /// Returns the legacy hacks used by the style sheet, such as `_zoom: 1` or `@-moz-document` rules, in source
+  /// order. Vendor prefixes that none of the targets need are also reported if targets are given. See the
+  /// [hacks](crate::hacks) module.
+  pub fn legacy_hacks(&self, targets: Option<Browsers>) -> Vec<LegacyHack> {
+    let mut hacks = Vec::new();
+    find_legacy_hacks(&self.rules, targets, &mut hacks);
+    hacks
+  }
+
+  /// Returns the `!important` declarations in the style rules of the style sheet, in source order, and whether
+  /// each of them is needed. See the [important](crate::important) module.
+  pub fn important_audit(&self) -> ImportantAudit {
+    ImportantAudit::build(&self.rules)
+  }
+
+  /// Serializes the style sheet to JSON, and applies the operations of the given JSON patch to it. The patched
+  /// JSON can be deserialized into a new style sheet, which borrows from it. See the
+  /// [json_patch](crate::json_patch) module.
+  #[cfg(feature = "json_patch")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "json_patch")))]
+  pub fn to_patched_json(&self, patch: &JsonPatch) -> Result<serde_json::Value, JsonPatchError>
+  where
+    T: serde::Serialize,
+  {
+    let mut json = serde_json::to_value(self).map_err(JsonPatchError::Serialize)?;
+    patch.apply(&mut json)?;
+    Ok(json)
+  }
+
+  /// Returns the style rules that were never used according to the given coverage, e.g. as exported from
+  /// Chrome DevTools, in source order. See the [coverage](crate::coverage) module.
+  pub fn unused_rules(&self, coverage: &Coverage) -> Vec<UnusedRule> {
+    let mut unused = Vec::new();
+    coverage.resolve(&self.sources).unused_rules(&self.rules, &mut unused);
+    unused
+  }
+
+  /// Removes the style rules that were never used according to the given coverage, along with any at-rules
+  /// left empty, and returns the removed rules. This should be called before [minify](StyleSheet::minify),
+  /// which may merge rules from different source locations.
+  pub fn remove_unused_rules(&mut self, coverage: &Coverage) -> Vec<UnusedRule> {
+    let mut unused = Vec::new();
+    coverage
+      .resolve(&self.sources)
+      .remove_unused_rules(&mut self.rules, &mut unused);
+    unused
+  }
+
+  /// Returns mutable references to the rules matching the predicate, along with the at-rules and style rules
+  /// enclosing them. Rules nested within a matching rule are not searched. See the [query](crate::query) module.
+  pub fn query<F>(&mut self, mut predicate: F) -> Vec<RuleMatch<'_, 'i, T>>
+  where
+    F: FnMut(&CssRule<'i, T>, &RuleContext<'i>) -> bool,
+  {
+    let mut matches = Vec::new();
+    query_rules(
+      &mut self.rules,
+      &mut RuleContext::default(),
+      &mut predicate,
+      &mut matches,
+    );
+    matches
+  }
+
+  /// Returns the style rules with at least one selector matching the predicate.
+  pub fn query_selectors<F>(&mut self, mut predicate: F) -> Vec<RuleMatch<'_, 'i, T>>
+  where
+    F: FnMut(&Selector<'i>) -> bool,
+  {
+    self.query(|rule, _| style_selectors(rule).map_or(false, |selectors| selectors.0.iter().any(&mut predicate)))
+  }
+
+  /// Returns the style rules that include any of the given selectors, e.g. `.foo` matches a rule with the
+  /// selectors `.foo, .bar`. Selectors are compared after serialization, so `.a>.b` matches `.a > .b`.
+  /// If the selector cannot be parsed, no rules are returned.
+  pub fn query_selector(&mut self, selector: &str) -> Vec<RuleMatch<'_, 'i, T>> {
+    let expected: Vec<String> = match SelectorList::parse_string_with_options(selector, ParserOptions::default()) {
+      Ok(selectors) => selectors.0.iter().map(serialize).collect(),
+      Err(_) => return Vec::new(),
+    };
+    self.query_selectors(|selector| expected.contains(&serialize(selector)))
+  }
+
+  /// Returns the style rules that declare the given property.
+  pub fn query_property(&mut self, property_id: &PropertyId) -> Vec<RuleMatch<'_, 'i, T>> {
+    self.query(|rule, _| {
+      let declarations = match rule {
+        CssRule::Style(style) => &style.declarations,
+        CssRule::Nesting(nesting) => &nesting.style.declarations,
+        _ => return false,
+      };
+      declarations.iter().any(|(property, _)| property.property_id() == *property_id)
+    })
+  }
+
+  /// Returns the at-rules with the given name and condition, e.g. `media` and `(min-width: 500px)`.
+  /// The `media`, `supports`, `container`, and `layer` rules are supported, with the layer name as the
+  /// condition of `@layer` blocks. Conditions are compared against their serialized form, as printed
+  /// without minification.
+  pub fn query_at_rule(&mut self, name: &str, condition: &str) -> Vec<RuleMatch<'_, 'i, T>> {
+    self.query(|rule, _| at_rule_matches(rule, name, condition.trim()))
+  }
+
+  /// Returns an iterator over all rules in the style sheet, including nested rules, along with the
+  /// rules enclosing them.
+  pub fn iter_rules(&self) -> AllRules<'_, 'i, T> {
+    self.rules.iter_rules()
+  }
+
+  /// Returns an iterator over the declarations of all style rules in the style sheet, including nested
+  /// rules, along with their selectors and the rules enclosing them.
+  pub fn iter_declarations(&self) -> AllDeclarations<'_, 'i, T> {
+    self.rules.iter_declarations()
+  }
+
+  /// Returns an iterator over the selectors of all style rules in the style sheet, including nested rules,
+  /// along with the rules enclosing them.
+  pub fn iter_selectors(&self) -> AllSelectors<'_, 'i, T> {
+    self.rules.iter_selectors()
+  }
+
+  /// Returns the rule at the given path of indices, e.g. `&[1, 0]` for the first rule within the second
+  /// top-level rule. See the [cssom](crate::cssom) module.
+  pub fn rule(&self, path: &[usize]) -> Option<&CssRule<'i, T>> {
+    self.rules.rule_at(path)
+  }
+
+  /// Returns a mutable reference to the rule at the given path of indices.
+  pub fn rule_mut(&mut self, path: &[usize]) -> Option<&mut CssRule<'i, T>> {
+    self.rules.rule_at_mut(path)
+  }
+
+  /// Inserts a top-level rule at the given index, and returns the index.
+  pub fn insert_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<usize, RuleListError> {
+    self.rules.insert_rule(index, rule)
+  }
+
+  /// Removes the top-level rule at the given index, and returns it.
+  pub fn delete_rule(&mut self, index: usize) -> Result<CssRule<'i, T>, RuleListError> {
+    self.rules.delete_rule(index)
+  }
+
+  /// Replaces the top-level rule at the given index, and returns the previous rule.
+  pub fn replace_rule(&mut self, index: usize, rule: CssRule<'i, T>) -> Result<CssRule<'i, T>, RuleListError> {
+    self.rules.replace_rule(index, rule)
+  }
+
+  /// Returns the changes between this style sheet and another version of it, e.g. to send a minimal
+  /// update to a client when the source changes. See the [diff](crate::diff) module.
+  pub fn diff(&self, other: &StyleSheet<'i, '_, T>) -> StyleSheetDiff<'i, T>
+  where
+    T: Clone,
+  {
+    let mut changes = Vec::new();
+    diff_rules(&self.rules, &other.rules, &mut Vec::new(), &mut changes);
+    StyleSheetDiff { changes }
+  }
+
+  /// Returns the custom element tag names, `::part()` names, and `:state()` names referenced
+  /// by the selectors in the style sheet, e.g. to cross-check them against a component registry.
+  pub fn custom_element_usage(&self) -> CustomElementUsage {
+    let mut usage = CustomElementUsage::default();
+    self.rules.custom_element_usage(&mut usage);
+    usage
   }
 
   /// Serialize the style sheet to a CSS string.
@@ -255,20 +1165,37 @@ where
       self.rules.to_css(&mut printer)?;
       printer.newline()?;
 
+      let rule_ranges = printer.rule_ranges.take();
+      let size_breakdown = printer.size_breakdown.take().map(|breakdown| breakdown.finish(&self.sources));
       Ok(ToCssResult {
         dependencies: printer.dependencies,
+        has_polyfills: printer.has_polyfills,
         exports: Some(std::mem::take(
           &mut printer.css_module.unwrap().exports_by_source_index[0],
         )),
+        size_report: rule_ranges
+          .map(|ranges| ranges.into_iter().map(|r| RuleSize::from_range(&dest, r)).collect()),
+        size_breakdown,
         code: dest,
         references: Some(references),
       })
     } else {
-      self.rules.to_css(&mut printer)?;
-      printer.newline()?;
+      match &self.lossless {
+        Some(lossless) if lossless.can_write(&printer) => lossless.write(&self.rules, &mut printer)?,
+        _ => {
+          self.rules.to_css(&mut printer)?;
+          printer.newline()?;
+        }
+      }
 
+      let rule_ranges = printer.rule_ranges.take();
+      let size_breakdown = printer.size_breakdown.take().map(|breakdown| breakdown.finish(&self.sources));
       Ok(ToCssResult {
         dependencies: printer.dependencies,
+        has_polyfills: printer.has_polyfills,
+        size_report: rule_ranges
+          .map(|ranges| ranges.into_iter().map(|r| RuleSize::from_range(&dest, r)).collect()),
+        size_breakdown,
         code: dest,
         exports: None,
         references: None,
@@ -345,6 +1272,8 @@ impl<'i> StyleAttribute<'i> {
     let mut handler = DeclarationHandler::new(options.targets);
     let mut important_handler = DeclarationHandler::new(options.targets);
     context.context = DeclarationContext::StyleAttribute;
+    context.logical_property_strategy = options.logical_property_strategy;
+    context.image_set_fallback = options.image_set_fallback;
     self.declarations.minify(&mut handler, &mut important_handler, &mut context);
   }
 
@@ -368,6 +1297,9 @@ impl<'i> StyleAttribute<'i> {
       code: dest,
       exports: None,
       references: None,
+      has_polyfills: None,
+      size_report: None,
+      size_breakdown: None,
     })
   }
 }
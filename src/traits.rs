@@ -113,6 +113,12 @@ pub(crate) trait FromStandard<T>: Sized {
 
 pub(crate) trait FallbackValues: Sized {
   fn get_fallbacks(&mut self, targets: Browsers) -> Vec<Self>;
+
+  /// Returns a copy of the value with each `image-set()` replaced by a single image,
+  /// for browsers that do not support `image-set()` at all.
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    None
+  }
 }
 
 /// Trait for shorthand properties.
@@ -73,19 +73,72 @@ impl Default for RoundingStrategy {
 }
 
 fn round(value: f32, to: f32, strategy: RoundingStrategy) -> f32 {
+  // https://www.w3.org/TR/css-values-4/#round-infinities
+  if to == 0.0 || (value.is_infinite() && to.is_infinite()) {
+    return f32::NAN;
+  }
+
+  if value.is_infinite() {
+    return value;
+  }
+
+  if to.is_infinite() {
+    return match strategy {
+      RoundingStrategy::Up if value > 0.0 => f32::INFINITY,
+      RoundingStrategy::Down if value < 0.0 => -f32::INFINITY,
+      _ => 0.0f32.copysign(value),
+    };
+  }
+
+  // The multiples of B are the same regardless of its sign.
+  let to = to.abs();
   let v = value / to;
   match strategy {
     RoundingStrategy::Down => v.floor() * to,
     RoundingStrategy::Up => v.ceil() * to,
-    RoundingStrategy::Nearest => v.round() * to,
+    // Ties are rounded toward positive infinity.
+    RoundingStrategy::Nearest => (v + 0.5).floor() * to,
     RoundingStrategy::ToZero => v.trunc() * to,
   }
 }
 
 fn modulo(a: f32, b: f32) -> f32 {
+  // https://www.w3.org/TR/css-values-4/#round-infinities
+  if b.is_infinite() && a.is_finite() {
+    return if a.is_sign_positive() == b.is_sign_positive() {
+      a
+    } else {
+      f32::NAN
+    };
+  }
+
   ((a % b) + b) % b
 }
 
+fn tan(angle: Angle) -> f32 {
+  // Angles that can exactly represent an asymptote map to infinity.
+  // Radians cannot, and always produce a very large value instead.
+  if !matches!(angle, Angle::Rad(_)) {
+    match angle.to_degrees().rem_euclid(360.0) {
+      deg if deg == 90.0 => return f32::INFINITY,
+      deg if deg == 270.0 => return -f32::INFINITY,
+      _ => {}
+    }
+  }
+
+  angle.to_radians().tan()
+}
+
+/// Censors a top-level calculation result that is NaN or infinite.
+/// https://www.w3.org/TR/css-values-4/#top-level-calculation
+fn censor(value: f32) -> f32 {
+  if value.is_nan() {
+    0.0
+  } else {
+    value.clamp(f32::MIN, f32::MAX)
+  }
+}
+
 impl<V: ToCss + std::ops::Mul<f32, Output = V> + TrySign + Clone + std::fmt::Debug> ToCss for MathFunction<V> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -297,6 +350,18 @@ impl<
   pub(crate) fn parse_with<'t, Parse: Copy + Fn(&str) -> Option<Calc<V>>>(
     input: &mut Parser<'i, 't>,
     parse_ident: Parse,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let calc = Self::parse_math_function(input, parse_ident)?;
+    Ok(calc.censor())
+  }
+
+  fn parse_nested<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    Self::parse_math_function(input, |_| None)
+  }
+
+  fn parse_math_function<'t, Parse: Copy + Fn(&str) -> Option<Calc<V>>>(
+    input: &mut Parser<'i, 't>,
+    parse_ident: Parse,
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let f = input.expect_function()?;
@@ -309,7 +374,15 @@ impl<
         }
       },
       "min" => {
-        let mut args = input.parse_nested_block(|input| input.parse_comma_separated(|input| Calc::parse_sum(input, parse_ident)))?;
+        let args = input.parse_nested_block(|input| input.parse_comma_separated(|input| Calc::parse_sum(input, parse_ident)))?;
+        // min(a, min(b, c)) is equivalent to min(a, b, c).
+        let mut args = args.into_iter().flat_map(|arg| match arg {
+          Calc::Function(f) => match *f {
+            MathFunction::Min(args) => args,
+            f => vec![Calc::Function(Box::new(f))],
+          },
+          arg => vec![arg],
+        }).collect();
         let mut reduced = Calc::reduce_args(&mut args, std::cmp::Ordering::Less);
         if reduced.len() == 1 {
           return Ok(reduced.remove(0))
@@ -317,7 +390,15 @@ impl<
         Ok(Calc::Function(Box::new(MathFunction::Min(reduced))))
       },
       "max" => {
-        let mut args = input.parse_nested_block(|input| input.parse_comma_separated(|input| Calc::parse_sum(input, parse_ident)))?;
+        let args = input.parse_nested_block(|input| input.parse_comma_separated(|input| Calc::parse_sum(input, parse_ident)))?;
+        // max(a, max(b, c)) is equivalent to max(a, b, c).
+        let mut args = args.into_iter().flat_map(|arg| match arg {
+          Calc::Function(f) => match *f {
+            MathFunction::Max(args) => args,
+            f => vec![Calc::Function(Box::new(f))],
+          },
+          arg => vec![arg],
+        }).collect();
         let mut reduced = Calc::reduce_args(&mut args, std::cmp::Ordering::Greater);
         if reduced.len() == 1 {
           return Ok(reduced.remove(0))
@@ -335,10 +416,15 @@ impl<
         })?;
 
         // According to the spec, the minimum should "win" over the maximum if they are in the wrong order.
-        let cmp = if let (Some(Calc::Value(max_val)), Calc::Value(center_val)) = (&max, &center) {
-          center_val.partial_cmp(&max_val)
-        } else {
-          None
+        if let (Some(min_val), Some(max_val)) = (&min, &max) {
+          if matches!(min_val.compare(max_val), Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)) {
+            return Ok(min.unwrap());
+          }
+        }
+
+        let cmp = match (&max, &center) {
+          (Some(max_val), center_val) => center_val.compare(max_val),
+          _ => None,
         };
 
         // If center is known to be greater than the maximum, replace it with maximum and remove the max argument.
@@ -353,10 +439,9 @@ impl<
           None => {}
         }
 
-        let cmp = if let (Some(Calc::Value(min_val)), Calc::Value(center_val)) = (&min, &center) {
-          center_val.partial_cmp(&min_val)
-        } else {
-          None
+        let cmp = match (&min, &center) {
+          (Some(min_val), center_val) => center_val.compare(min_val),
+          _ => None,
         };
 
         // If center is known to be less than the minimum, replace it with minimum and remove the min argument.
@@ -406,12 +491,12 @@ impl<
           Self::parse_math_fn(input, modulo, MathFunction::Mod, parse_ident)
         })
       },
-      "sin" => Self::parse_trig(input, f32::sin, false, parse_ident),
-      "cos" => Self::parse_trig(input, f32::cos, false, parse_ident),
-      "tan" => Self::parse_trig(input, f32::tan, false, parse_ident),
-      "asin" => Self::parse_trig(input, f32::asin, true, parse_ident),
-      "acos" => Self::parse_trig(input, f32::acos, true, parse_ident),
-      "atan" => Self::parse_trig(input, f32::atan, true, parse_ident),
+      "sin" => Self::parse_trig(input, |v| v.to_radians().sin(), false, parse_ident),
+      "cos" => Self::parse_trig(input, |v| v.to_radians().cos(), false, parse_ident),
+      "tan" => Self::parse_trig(input, tan, false, parse_ident),
+      "asin" => Self::parse_trig(input, |v| v.to_radians().asin(), true, parse_ident),
+      "acos" => Self::parse_trig(input, |v| v.to_radians().acos(), true, parse_ident),
+      "atan" => Self::parse_trig(input, |v| v.to_radians().atan(), true, parse_ident),
       "atan2" => {
         input.parse_nested_block(|input| {
           let res = Self::parse_atan2(input, parse_ident)?;
@@ -566,8 +651,9 @@ impl<
     input: &mut Parser<'i, 't>,
     parse_ident: Parse,
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
-    // Parse nested calc() and other math functions.
-    if let Ok(calc) = input.try_parse(Self::parse) {
+    // Parse nested calc() and other math functions. These are not censored,
+    // so NaN and infinity propagate to the top-level calculation.
+    if let Ok(calc) = input.try_parse(Self::parse_nested) {
       match calc {
         Calc::Function(f) => {
           return Ok(match *f {
@@ -604,31 +690,44 @@ impl<
     Ok(Calc::Value(Box::new(value)))
   }
 
+  fn censor(self) -> Self {
+    match self {
+      Calc::Number(n) => Calc::Number(censor(n)),
+      Calc::Value(v) => match v.try_map(censor) {
+        Some(v) => Calc::Value(Box::new(v)),
+        None => Calc::Value(v),
+      },
+      calc => calc,
+    }
+  }
+
+  /// Compares two values if both are constants of compatible types.
+  fn compare(&self, other: &Calc<V>) -> Option<std::cmp::Ordering> {
+    match (self, other) {
+      (Calc::Value(a), Calc::Value(b)) => a.partial_cmp(b),
+      (Calc::Number(a), Calc::Number(b)) => a.partial_cmp(b),
+      _ => None,
+    }
+  }
+
   fn reduce_args(args: &mut Vec<Calc<V>>, cmp: std::cmp::Ordering) -> Vec<Calc<V>> {
     // Reduces the arguments of a min() or max() expression, combining compatible values.
     // e.g. min(1px, 1em, 2px, 3in) => min(1px, 1em)
     let mut reduced: Vec<Calc<V>> = vec![];
     for arg in args.drain(..) {
       let mut found = None;
-      match &arg {
-        Calc::Value(val) => {
-          for b in reduced.iter_mut() {
-            if let Calc::Value(v) = b {
-              match val.partial_cmp(v) {
-                Some(ord) if ord == cmp => {
-                  found = Some(Some(b));
-                  break;
-                }
-                Some(_) => {
-                  found = Some(None);
-                  break;
-                }
-                None => {}
-              }
-            }
+      for b in reduced.iter_mut() {
+        match arg.compare(b) {
+          Some(ord) if ord == cmp => {
+            found = Some(Some(b));
+            break;
           }
+          Some(_) => {
+            found = Some(None);
+            break;
+          }
+          None => {}
         }
-        _ => {}
       }
       if let Some(r) = found {
         if let Some(r) = r {
@@ -687,7 +786,7 @@ impl<
     None
   }
 
-  fn parse_trig<'t, F: FnOnce(f32) -> f32, Parse: Copy + Fn(&str) -> Option<Calc<V>>>(
+  fn parse_trig<'t, F: FnOnce(Angle) -> f32, Parse: Copy + Fn(&str) -> Option<Calc<V>>>(
     input: &mut Parser<'i, 't>,
     f: F,
     to_angle: bool,
@@ -701,8 +800,9 @@ impl<
         })
       })?;
       let rad = match v {
-        Calc::Value(angle) if !to_angle => f(angle.to_radians()),
-        Calc::Number(v) => f(v),
+        Calc::Value(angle) if !to_angle => f(*angle),
+        // Numbers are passed through as radians.
+        Calc::Number(v) => f(Angle::Rad(v)),
         _ => return Err(input.new_custom_error(ParserError::InvalidValue)),
       };
 
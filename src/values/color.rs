@@ -9,7 +9,7 @@ use super::percentage::Percentage;
 use crate::compat::Feature;
 use crate::error::{ParserError, PrinterError};
 use crate::macros::enum_property;
-use crate::printer::Printer;
+use crate::printer::{ColorFormat, GamutMapping, LightDarkMode, Printer};
 use crate::properties::PropertyId;
 use crate::rules::supports::SupportsCondition;
 use crate::targets::Browsers;
@@ -57,6 +57,15 @@ pub enum CssColor {
   Predefined(Box<PredefinedColor>),
   /// A floating point representation of an RGB, HSL, or HWB color when it contains `none` components.
   Float(Box<FloatColor>),
+  /// The [`light-dark()`](https://drafts.csswg.org/css-color-5/#light-dark) function, which
+  /// chooses between two colors based on the used color scheme.
+  #[cfg_attr(feature = "visitor", skip_type)]
+  LightDark {
+    /// The color used in a light color scheme.
+    light: Box<CssColor>,
+    /// The color used in a dark color scheme.
+    dark: Box<CssColor>,
+  },
 }
 
 #[cfg(feature = "serde")]
@@ -285,19 +294,55 @@ impl CssColor {
     CssColor::RGBA(RGBA::transparent())
   }
 
-  /// Converts the color to RGBA.
+  /// Converts the color to RGBA. Colors outside the sRGB gamut are kept at full precision,
+  /// and are mapped into the gamut when printed according to the
+  /// [GamutMapping](crate::printer::GamutMapping) printer option.
+  ///
+  /// The `currentColor` keyword is returned unchanged, and both sides of a `light-dark()` color are converted.
   pub fn to_rgb(&self) -> CssColor {
-    RGBA::from(self).into()
+    match self {
+      CssColor::CurrentColor | CssColor::LightDark { .. } => return self.map_components(CssColor::to_rgb),
+      _ => {}
+    }
+
+    let srgb = SRGB::from(self).resolve_missing();
+    if srgb.in_gamut() {
+      RGBA::from(srgb).into()
+    } else {
+      CssColor::Float(Box::new(FloatColor::RGB(srgb)))
+    }
   }
 
   /// Converts the color to the LAB color space.
+  ///
+  /// The `currentColor` keyword is returned unchanged, and both sides of a `light-dark()` color are converted.
   pub fn to_lab(&self) -> CssColor {
-    LAB::from(self).into()
+    match self {
+      CssColor::CurrentColor | CssColor::LightDark { .. } => self.map_components(CssColor::to_lab),
+      _ => LAB::from(self).into(),
+    }
   }
 
   /// Converts the color to the P3 color space.
+  ///
+  /// The `currentColor` keyword is returned unchanged, and both sides of a `light-dark()` color are converted.
   pub fn to_p3(&self) -> CssColor {
-    P3::from(self).into()
+    match self {
+      CssColor::CurrentColor | CssColor::LightDark { .. } => self.map_components(CssColor::to_p3),
+      _ => P3::from(self).into(),
+    }
+  }
+
+  /// Applies a conversion to each side of a `light-dark()` color. Other colors that
+  /// cannot be converted, i.e. `currentColor`, are returned unchanged.
+  fn map_components(&self, f: fn(&CssColor) -> CssColor) -> CssColor {
+    match self {
+      CssColor::LightDark { light, dark } => CssColor::LightDark {
+        light: Box::new(f(light)),
+        dark: Box::new(f(dark)),
+      },
+      _ => self.clone(),
+    }
   }
 
   pub(crate) fn get_possible_fallbacks(&self, targets: Browsers) -> ColorFallbackKind {
@@ -306,6 +351,9 @@ impl CssColor {
     // compatible with our browser targets.
     let mut fallbacks = match self {
       CssColor::CurrentColor | CssColor::RGBA(_) | CssColor::Float(..) => return ColorFallbackKind::empty(),
+      CssColor::LightDark { light, dark } => {
+        return light.get_possible_fallbacks(targets) | dark.get_possible_fallbacks(targets)
+      }
       CssColor::LAB(lab) => match &**lab {
         LABColor::LAB(..) | LABColor::LCH(..) => ColorFallbackKind::LAB.and_below(),
         LABColor::OKLAB(..) | LABColor::OKLCH(..) => ColorFallbackKind::OKLAB.and_below(),
@@ -362,8 +410,15 @@ impl CssColor {
 
   /// Returns a fallback color for the given fallback type.
   pub fn get_fallback(&self, kind: ColorFallbackKind) -> CssColor {
-    if matches!(self, CssColor::RGBA(_)) {
-      return self.clone();
+    match self {
+      CssColor::RGBA(_) | CssColor::CurrentColor => return self.clone(),
+      CssColor::LightDark { light, dark } => {
+        return CssColor::LightDark {
+          light: Box::new(light.get_fallback(kind)),
+          dark: Box::new(dark.get_fallback(kind)),
+        }
+      }
+      _ => {}
     }
 
     match kind {
@@ -381,15 +436,15 @@ impl FallbackValues for CssColor {
 
     let mut res = Vec::new();
     if fallbacks.contains(ColorFallbackKind::RGB) {
-      res.push(self.to_rgb());
+      res.push(self.get_fallback(ColorFallbackKind::RGB));
     }
 
     if fallbacks.contains(ColorFallbackKind::P3) {
-      res.push(self.to_p3());
+      res.push(self.get_fallback(ColorFallbackKind::P3));
     }
 
     if fallbacks.contains(ColorFallbackKind::LAB) {
-      *self = self.to_lab();
+      *self = self.get_fallback(ColorFallbackKind::LAB);
     }
 
     res
@@ -430,8 +485,26 @@ impl ToCss for CssColor {
     match self {
       CssColor::CurrentColor => dest.write_str("currentColor"),
       CssColor::RGBA(color) => {
+        match dest.color_format {
+          ColorFormat::Rgb => return write_rgb(color, dest),
+          ColorFormat::Modern => {
+            if dest.targets.map_or(true, |targets| Feature::OklabColors.is_compatible(targets)) {
+              let lch = OKLCH::from(*color);
+              return write_components("oklch", lch.l, lch.c, lch.h, lch.alpha, dest);
+            }
+          }
+          _ => {}
+        }
+
+        // Hex and preserved colors are always written in full so the output is stable.
+        let full = matches!(dest.color_format, ColorFormat::Hex | ColorFormat::Preserve);
         if color.alpha == 255 {
           let hex: u32 = ((color.red as u32) << 16) | ((color.green as u32) << 8) | (color.blue as u32);
+          if full {
+            write!(dest, "#{:06x}", hex)?;
+            return Ok(());
+          }
+
           if let Some(name) = short_color_name(hex) {
             return dest.write_str(name);
           }
@@ -448,27 +521,11 @@ impl ToCss for CssColor {
             if !Feature::CssRrggbbaa.is_compatible(targets) {
               // If the browser doesn't support `#rrggbbaa` color syntax, it is converted to `transparent` when compressed(minify = true).
               // https://www.w3.org/TR/css-color-4/#transparent-black
-              if dest.minify && color.red == 0 && color.green == 0 && color.blue == 0 && color.alpha == 0 {
+              if dest.minify && !full && color.red == 0 && color.green == 0 && color.blue == 0 && color.alpha == 0
+              {
                 return dest.write_str("transparent");
               } else {
-                dest.write_str("rgba(")?;
-                write!(dest, "{}", color.red)?;
-                dest.delim(',', false)?;
-                write!(dest, "{}", color.green)?;
-                dest.delim(',', false)?;
-                write!(dest, "{}", color.blue)?;
-                dest.delim(',', false)?;
-
-                // Try first with two decimal places, then with three.
-                let mut rounded_alpha = (color.alpha_f32() * 100.0).round() / 100.0;
-                let clamped = (rounded_alpha * 255.0).round().max(0.).min(255.0) as u8;
-                if clamped != color.alpha {
-                  rounded_alpha = (color.alpha_f32() * 1000.).round() / 1000.;
-                }
-
-                rounded_alpha.to_css(dest)?;
-                dest.write_char(')')?;
-                return Ok(());
+                return write_rgb(color, dest);
               }
             }
           }
@@ -478,7 +535,7 @@ impl ToCss for CssColor {
             | ((color.blue as u32) << 8)
             | (color.alpha as u32);
           let compact = compact_hex(hex);
-          if hex == expand_hex(compact) {
+          if !full && hex == expand_hex(compact) {
             write!(dest, "#{:04x}", compact)?;
           } else {
             write!(dest, "#{:08x}", hex)?;
@@ -493,9 +550,52 @@ impl ToCss for CssColor {
         LABColor::OKLCH(lch) => write_components("oklch", lch.l, lch.c, lch.h, lch.alpha, dest),
       },
       CssColor::Predefined(predefined) => write_predefined(predefined, dest),
+      CssColor::LightDark { light, dark } => {
+        if dest.targets.map_or(true, |targets| Feature::LightDark.is_compatible(targets)) {
+          dest.write_str("light-dark(")?;
+          light.to_css(dest)?;
+          dest.delim(',', false)?;
+          dark.to_css(dest)?;
+          return dest.write_char(')');
+        }
+
+        match dest.light_dark_mode {
+          // Both colors are written, and custom properties defined alongside `color-scheme`
+          // substitute to nothing for the unused one.
+          LightDarkMode::CustomProperties => {
+            dest.write_str("var(--lightningcss-light")?;
+            dest.delim(',', false)?;
+            light.to_css(dest)?;
+            dest.write_str(") var(--lightningcss-dark")?;
+            dest.delim(',', false)?;
+            dark.to_css(dest)?;
+            dest.write_char(')')
+          }
+          // The dark color is written in a separate `prefers-color-scheme` media query by the style rule.
+          LightDarkMode::MediaQuery => {
+            dest.has_light_dark = true;
+            if dest.dark_color_scheme {
+              dark.to_css(dest)
+            } else {
+              light.to_css(dest)
+            }
+          }
+        }
+      }
       CssColor::Float(float) => {
+        if dest.color_format == ColorFormat::Preserve {
+          match &**float {
+            FloatColor::HSL(hsl) => return write_hue_components("hsl", hsl.h, hsl.s, hsl.l, hsl.alpha, dest),
+            FloatColor::HWB(hwb) => return write_hue_components("hwb", hwb.h, hwb.w, hwb.b, hwb.alpha, dest),
+            FloatColor::RGB(..) => {}
+          }
+        }
+
         // Serialize as hex.
-        let srgb = SRGB::from(**float);
+        let mut srgb = SRGB::from(**float);
+        if dest.gamut_mapping == GamutMapping::Clip {
+          srgb = srgb.resolve_missing().clip();
+        }
         CssColor::from(srgb).to_css(dest)
       }
     }
@@ -726,7 +826,7 @@ impl ComponentParser {
     input: &mut Parser<'i, 't>,
   ) -> Result<(), ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("from")).is_ok() {
-      let from = T::from(CssColor::parse(input)?).resolve();
+      let from = T::from(parse_origin_color(input)?).resolve();
       self.from = Some(RelativeComponentParser::new(&from));
     }
 
@@ -864,12 +964,31 @@ fn parse_color_function<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CssColor,
     "color-mix" => {
       input.parse_nested_block(parse_color_mix)
     },
+    "light-dark" => {
+      input.parse_nested_block(|input| {
+        let light = CssColor::parse(input)?;
+        input.expect_comma()?;
+        let dark = CssColor::parse(input)?;
+        Ok(CssColor::LightDark { light: Box::new(light), dark: Box::new(dark) })
+      })
+    },
     _ => Err(location.new_unexpected_token_error(
       cssparser::Token::Ident(function.clone())
     ))
   }
 }
 
+/// Parses a color that is converted to another color space, e.g. the origin color of a relative color
+/// or an argument to `color-mix()`. Colors chosen by the color scheme cannot be converted during parsing.
+fn parse_origin_color<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CssColor, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  let color = CssColor::parse(input)?;
+  if matches!(color, CssColor::LightDark { .. }) {
+    return Err(location.new_custom_error(ParserError::InvalidValue));
+  }
+  Ok(color)
+}
+
 /// Parses the lab() and oklab() functions.
 #[inline]
 fn parse_lab<'i, 't, T: From<CssColor> + ColorSpace>(
@@ -929,7 +1048,7 @@ fn parse_predefined<'i, 't>(
   // https://www.w3.org/TR/css-color-4/#color-function
   let res = input.parse_nested_block(|input| {
     let from = if input.try_parse(|input| input.expect_ident_matching("from")).is_ok() {
-      Some(CssColor::parse(input)?)
+      Some(parse_origin_color(input)?)
     } else {
       None
     };
@@ -1152,6 +1271,61 @@ where
   Ok(())
 }
 
+fn write_rgb<W>(color: &RGBA, dest: &mut Printer<W>) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  dest.write_str(if color.alpha == 255 { "rgb(" } else { "rgba(" })?;
+  write!(dest, "{}", color.red)?;
+  dest.delim(',', false)?;
+  write!(dest, "{}", color.green)?;
+  dest.delim(',', false)?;
+  write!(dest, "{}", color.blue)?;
+  if color.alpha != 255 {
+    dest.delim(',', false)?;
+
+    // Try first with two decimal places, then with three.
+    let mut rounded_alpha = (color.alpha_f32() * 100.0).round() / 100.0;
+    let clamped = (rounded_alpha * 255.0).round().max(0.).min(255.0) as u8;
+    if clamped != color.alpha {
+      rounded_alpha = (color.alpha_f32() * 1000.).round() / 1000.;
+    }
+
+    rounded_alpha.to_css(dest)?;
+  }
+  dest.write_char(')')
+}
+
+fn write_hue_components<W>(
+  name: &str,
+  h: f32,
+  a: f32,
+  b: f32,
+  alpha: f32,
+  dest: &mut Printer<W>,
+) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  dest.write_str(name)?;
+  dest.write_char('(')?;
+  write_component(h, dest)?;
+  for c in [a, b] {
+    dest.write_char(' ')?;
+    if c.is_nan() {
+      dest.write_str("none")?;
+    } else {
+      Percentage(c).to_css(dest)?;
+    }
+  }
+  if alpha.is_nan() || (alpha - 1.0).abs() > f32::EPSILON {
+    dest.delim('/', true)?;
+    write_component(alpha, dest)?;
+  }
+
+  dest.write_char(')')
+}
+
 #[inline]
 fn write_predefined<W>(predefined: &PredefinedColor, dest: &mut Printer<W>) -> Result<(), PrinterError>
 where
@@ -2558,7 +2732,7 @@ macro_rules! color_space {
           CssColor::LAB(lab) => (**lab).into(),
           CssColor::Predefined(predefined) => (**predefined).into(),
           CssColor::Float(float) => (**float).into(),
-          CssColor::CurrentColor => unreachable!(),
+          CssColor::CurrentColor | CssColor::LightDark { .. } => unreachable!(),
         }
       }
     }
@@ -2570,7 +2744,7 @@ macro_rules! color_space {
           CssColor::LAB(lab) => (*lab).into(),
           CssColor::Predefined(predefined) => (*predefined).into(),
           CssColor::Float(float) => (*float).into(),
-          CssColor::CurrentColor => unreachable!(),
+          CssColor::CurrentColor | CssColor::LightDark { .. } => unreachable!(),
         }
       }
     }
@@ -2830,14 +3004,14 @@ fn parse_color_mix<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CssColor, Parse
   input.expect_comma()?;
 
   let first_percent = input.try_parse(|input| input.expect_percentage());
-  let first_color = CssColor::parse(input)?;
+  let first_color = parse_origin_color(input)?;
   let first_percent = first_percent
     .or_else(|_| input.try_parse(|input| input.expect_percentage()))
     .ok();
   input.expect_comma()?;
 
   let second_percent = input.try_parse(|input| input.expect_percentage());
-  let second_color = CssColor::parse(input)?;
+  let second_color = parse_origin_color(input)?;
   let second_percent = second_percent
     .or_else(|_| input.try_parse(|input| input.expect_percentage()))
     .ok();
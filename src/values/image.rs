@@ -1,13 +1,17 @@
 //! CSS image values.
 
-use super::color::ColorFallbackKind;
+use super::color::{ColorFallbackKind, CssColor};
 use super::gradient::*;
+use super::ident::{CustomIdent, Ident};
+use super::percentage::Percentage;
 use super::resolution::Resolution;
 use crate::compat;
 use crate::dependencies::{Dependency, UrlDependency};
 use crate::error::{ParserError, PrinterError};
 use crate::prefixes::{is_webkit_gradient, Feature};
 use crate::printer::Printer;
+use crate::properties::custom::TokenList;
+use crate::stylesheet::ParserOptions;
 use crate::targets::Browsers;
 use crate::traits::{FallbackValues, Parse, ToCss};
 use crate::values::string::CowArcStr;
@@ -39,6 +43,12 @@ pub enum Image<'i> {
   Gradient(Box<Gradient>),
   /// An `image-set()`.
   ImageSet(ImageSet<'i>),
+  /// A `paint()` function.
+  Paint(Box<PaintImage<'i>>),
+  /// A `cross-fade()` function.
+  CrossFade(CrossFade<'i>),
+  /// An `element()` function, referencing an element by id.
+  Element(CustomIdent<'i>),
 }
 
 impl<'i> Default for Image<'i> {
@@ -95,6 +105,7 @@ impl<'i> Image<'i> {
   pub fn get_necessary_fallbacks(&self, targets: Browsers) -> ColorFallbackKind {
     match self {
       Image::Gradient(grad) => grad.get_necessary_fallbacks(targets),
+      Image::CrossFade(cross_fade) => cross_fade.get_necessary_fallbacks(targets),
       _ => ColorFallbackKind::empty(),
     }
   }
@@ -103,10 +114,19 @@ impl<'i> Image<'i> {
   pub fn get_fallback(&self, kind: ColorFallbackKind) -> Image<'i> {
     match self {
       Image::Gradient(grad) => Image::Gradient(Box::new(grad.get_fallback(kind))),
+      Image::CrossFade(cross_fade) => Image::CrossFade(cross_fade.get_fallback(kind)),
       _ => self.clone(),
     }
   }
 
+  /// Returns the image to use in place of an `image-set()` in browsers that do not support it.
+  pub(crate) fn get_image_set_fallback(&self) -> Option<Image<'i>> {
+    match self {
+      Image::ImageSet(image_set) => image_set.get_fallback_image().cloned(),
+      _ => None,
+    }
+  }
+
   pub(crate) fn should_preserve_fallback(&self, fallback: &Option<Image>, targets: Option<Browsers>) -> bool {
     if let (Some(fallback), Some(targets)) = (&fallback, targets) {
       return !compat::Feature::ImageSet.is_compatible(targets)
@@ -160,6 +180,10 @@ impl<'i> ImageFallback<'i> for Image<'i> {
 }
 
 impl<'i> FallbackValues for Image<'i> {
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    Image::get_image_set_fallback(self)
+  }
+
   fn get_fallbacks(&mut self, targets: Browsers) -> Vec<Self> {
     // Determine which prefixes and color fallbacks are needed.
     let prefixes = self.get_necessary_prefixes(targets);
@@ -226,6 +250,20 @@ impl<'i> FallbackValues for Image<'i> {
 }
 
 impl<'i, T: ImageFallback<'i>> FallbackValues for SmallVec<[T; 1]> {
+  fn get_image_set_fallback(&self) -> Option<Self> {
+    if !self.iter().any(|item| matches!(item.get_image(), Image::ImageSet(..))) {
+      return None;
+    }
+
+    self
+      .iter()
+      .map(|item| match item.get_image() {
+        Image::ImageSet(..) => item.get_image().get_image_set_fallback().map(|image| item.with_image(image)),
+        _ => Some(item.with_image(item.get_image().clone())),
+      })
+      .collect()
+  }
+
   fn get_fallbacks(&mut self, targets: Browsers) -> Vec<Self> {
     // Determine what vendor prefixes and color fallbacks are needed.
     let mut prefixes = VendorPrefix::empty();
@@ -323,6 +361,18 @@ impl<'i> Parse<'i> for Image<'i> {
       return Ok(Image::ImageSet(image_set));
     }
 
+    if let Ok(paint) = input.try_parse(PaintImage::parse) {
+      return Ok(Image::Paint(Box::new(paint)));
+    }
+
+    if let Ok(cross_fade) = input.try_parse(CrossFade::parse) {
+      return Ok(Image::CrossFade(cross_fade));
+    }
+
+    if let Ok(id) = input.try_parse(parse_element) {
+      return Ok(Image::Element(id));
+    }
+
     Err(input.new_error_for_next_token())
   }
 }
@@ -337,6 +387,13 @@ impl<'i> ToCss for Image<'i> {
       Image::Url(url) => url.to_css(dest),
       Image::Gradient(grad) => grad.to_css(dest),
       Image::ImageSet(image_set) => image_set.to_css(dest),
+      Image::Paint(paint) => paint.to_css(dest),
+      Image::CrossFade(cross_fade) => cross_fade.to_css(dest),
+      Image::Element(id) => {
+        dest.write_str("element(#")?;
+        id.to_css(dest)?;
+        dest.write_char(')')
+      }
     }
   }
 }
@@ -371,19 +428,47 @@ impl<'i> ImageSet<'i> {
   /// Returns the vendor prefixes needed for the given browser targets.
   pub fn get_necessary_prefixes(&self, targets: Browsers) -> VendorPrefix {
     if self.vendor_prefix.contains(VendorPrefix::None) {
-      Feature::ImageSet.prefixes_for(targets)
+      let mut prefixes = Feature::ImageSet.prefixes_for(targets);
+      // -webkit-image-set() does not support type(), so it is only useful if some options have no type.
+      if self.options.iter().all(|option| option.file_type.is_some()) {
+        prefixes.remove(VendorPrefix::WebKit);
+        prefixes.insert(VendorPrefix::None);
+      }
+      prefixes
     } else {
       self.vendor_prefix
     }
   }
 
   /// Returns the `image-set()` value with the given vendor prefix.
+  ///
+  /// Options with a `type()` are omitted from prefixed values, which do not support it.
   pub fn get_prefixed(&self, prefix: VendorPrefix) -> ImageSet<'i> {
+    let options = if prefix == VendorPrefix::None {
+      self.options.clone()
+    } else {
+      self
+        .options
+        .iter()
+        .filter(|option| option.file_type.is_none())
+        .cloned()
+        .collect()
+    };
+
     ImageSet {
-      options: self.options.clone(),
+      options,
       vendor_prefix: prefix,
     }
   }
+
+  /// Returns the image to use for browsers that do not support `image-set()`. This is the
+  /// 1x option if there is one, or otherwise the first option, ignoring options with a `type()`.
+  pub fn get_fallback_image(&self) -> Option<&Image<'i>> {
+    let mut options = self.options.iter().filter(|option| option.file_type.is_none());
+    let first = options.clone().next()?;
+    let option = options.find(|option| option.resolution.to_dppx() == 1.0).unwrap_or(first);
+    Some(&option.image)
+  }
 }
 
 impl<'i> Parse<'i> for ImageSet<'i> {
@@ -501,7 +586,7 @@ impl<'i> ImageSetOption<'i> {
 
     // TODO: Throwing an error when `self.resolution = Resolution::Dppx(0.0)`
     // TODO: -webkit-image-set() does not support `<image()> | <image-set()> |
-    // <cross-fade()> | <element()> | <gradient>` and `type(<string>)`.
+    // <cross-fade()> | <element()> | <gradient>`.
     dest.write_char(' ')?;
 
     // Safari only supports the x resolution unit in image-set().
@@ -525,3 +610,213 @@ fn parse_file_type<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CowRcStr<'i>, P
   input.expect_function_matching("type")?;
   input.parse_nested_block(|input| Ok(input.expect_string_cloned()?))
 }
+
+fn parse_element<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CustomIdent<'i>, ParseError<'i, ParserError<'i>>> {
+  input.expect_function_matching("element")?;
+  input.parse_nested_block(|input| {
+    let location = input.current_source_location();
+    match input.next()? {
+      Token::IDHash(id) => Ok(CustomIdent(id.into())),
+      t => Err(location.new_unexpected_token_error(t.clone())),
+    }
+  })
+}
+
+/// A CSS [`paint()`](https://drafts.css-houdini.org/css-paint-api/#paint-notation) function,
+/// which generates an image using a registered paint worklet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct PaintImage<'i> {
+  /// The name of the paint worklet.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub name: Ident<'i>,
+  /// The arguments passed to the paint worklet.
+  pub arguments: Option<TokenList<'i>>,
+}
+
+impl<'i> Parse<'i> for PaintImage<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.expect_function_matching("paint")?;
+    input.parse_nested_block(|input| {
+      let name = Ident::parse(input)?;
+      let arguments = if input.try_parse(|input| input.expect_comma()).is_ok() {
+        Some(TokenList::parse(input, &ParserOptions::default(), 0)?)
+      } else {
+        None
+      };
+      Ok(PaintImage { name, arguments })
+    })
+  }
+}
+
+impl<'i> ToCss for PaintImage<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("paint(")?;
+    // Worklet names are global, so they are only scoped if enabled in the CSS modules config.
+    if matches!(&dest.css_module, Some(css_module) if css_module.config.paint_worklets) {
+      dest.write_ident(&self.name.0)?;
+    } else {
+      serialize_identifier(&self.name.0, dest)?;
+    }
+    if let Some(arguments) = &self.arguments {
+      dest.delim(',', false)?;
+      arguments.to_css(dest, false)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+/// A CSS [`cross-fade()`](https://drafts.csswg.org/css-images-4/#cross-fade-function) function,
+/// which blends multiple images together.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct CrossFade<'i> {
+  /// The images to blend.
+  #[cfg_attr(feature = "visitor", skip_type)]
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub images: Vec<CrossFadeImage<'i>>,
+}
+
+impl<'i> CrossFade<'i> {
+  fn get_necessary_fallbacks(&self, targets: Browsers) -> ColorFallbackKind {
+    let mut fallbacks = ColorFallbackKind::empty();
+    for item in &self.images {
+      fallbacks |= match &item.image {
+        CrossFadeSource::Image(image) => image.get_necessary_fallbacks(targets),
+        CrossFadeSource::Color(color) => color.get_necessary_fallbacks(targets),
+      };
+    }
+    fallbacks
+  }
+
+  fn get_fallback(&self, kind: ColorFallbackKind) -> CrossFade<'i> {
+    CrossFade {
+      images: self
+        .images
+        .iter()
+        .map(|item| CrossFadeImage {
+          percentage: item.percentage.clone(),
+          image: match &item.image {
+            CrossFadeSource::Image(image) => CrossFadeSource::Image(image.get_fallback(kind)),
+            CrossFadeSource::Color(color) => CrossFadeSource::Color(color.get_fallback(kind)),
+          },
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for CrossFade<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.expect_function_matching("cross-fade")?;
+    let images = input.parse_nested_block(|input| input.parse_comma_separated(CrossFadeImage::parse))?;
+    Ok(CrossFade { images })
+  }
+}
+
+impl<'i> ToCss for CrossFade<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_str("cross-fade(")?;
+    let mut first = true;
+    for image in &self.images {
+      if first {
+        first = false;
+      } else {
+        dest.delim(',', false)?;
+      }
+      image.to_css(dest)?;
+    }
+    dest.write_char(')')
+  }
+}
+
+/// An image within the `cross-fade()` function. See [CrossFade](CrossFade).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct CrossFadeImage<'i> {
+  /// How much of the image is used in the blend.
+  pub percentage: Option<Percentage>,
+  /// The image or color to blend.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub image: CrossFadeSource<'i>,
+}
+
+impl<'i> Parse<'i> for CrossFadeImage<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut percentage = input.try_parse(Percentage::parse).ok();
+    let image = CrossFadeSource::parse(input)?;
+    if percentage.is_none() {
+      percentage = input.try_parse(Percentage::parse).ok();
+    }
+    Ok(CrossFadeImage { percentage, image })
+  }
+}
+
+impl<'i> ToCss for CrossFadeImage<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.image.to_css(dest)?;
+    if let Some(percentage) = &self.percentage {
+      dest.write_char(' ')?;
+      percentage.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// An image or color within the `cross-fade()` function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(lightningcss_derive::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum CrossFadeSource<'i> {
+  /// An image.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Image(Image<'i>),
+  /// A solid color.
+  Color(CssColor),
+}
+
+impl<'i> Parse<'i> for CrossFadeSource<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(image) = input.try_parse(Image::parse) {
+      return Ok(CrossFadeSource::Image(image));
+    }
+
+    Ok(CrossFadeSource::Color(CssColor::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for CrossFadeSource<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      CrossFadeSource::Image(image) => image.to_css(dest),
+      CrossFadeSource::Color(color) => color.to_css(dest),
+    }
+  }
+}
@@ -444,6 +444,7 @@ impl ToCss for LengthValue {
     W: std::fmt::Write,
   {
     let (value, unit) = self.to_unit_value();
+    let value = dest.round_shape_value(value);
 
     // The unit can be omitted if the value is zero, except inside calc()
     // expressions, where unitless numbers won't be parsed as dimensions.
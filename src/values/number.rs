@@ -34,10 +34,10 @@ impl ToCss for CSSNumber {
   where
     W: std::fmt::Write,
   {
-    let number = *self;
+    let number = dest.round_shape_value(*self);
     if number != 0.0 && number.abs() < 1.0 {
       let mut s = String::new();
-      cssparser::ToCss::to_css(self, &mut s)?;
+      cssparser::ToCss::to_css(&number, &mut s)?;
       if number < 0.0 {
         dest.write_char('-')?;
         dest.write_str(s.trim_start_matches("-0"))
@@ -45,12 +45,18 @@ impl ToCss for CSSNumber {
         dest.write_str(s.trim_start_matches('0'))
       }
     } else {
-      cssparser::ToCss::to_css(self, dest)?;
+      cssparser::ToCss::to_css(&number, dest)?;
       Ok(())
     }
   }
 }
 
+/// Rounds a number to the given number of decimal places.
+pub(crate) fn round_to_precision(value: f32, precision: u8) -> f32 {
+  let factor = 10f64.powi(precision as i32);
+  ((value as f64 * factor).round() / factor) as f32
+}
+
 impl std::convert::Into<Calc<CSSNumber>> for CSSNumber {
   fn into(self) -> Calc<CSSNumber> {
     Calc::Value(Box::new(self))
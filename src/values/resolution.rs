@@ -63,6 +63,17 @@ impl<'i> TryFrom<&Token<'i>> for Resolution {
   }
 }
 
+impl Resolution {
+  /// Returns the resolution in dots per px.
+  pub fn to_dppx(&self) -> CSSNumber {
+    match self {
+      Resolution::Dpi(dpi) => dpi / 96.0,
+      Resolution::Dpcm(dpcm) => dpcm * 2.54 / 96.0,
+      Resolution::Dppx(dppx) => *dppx,
+    }
+  }
+}
+
 impl ToCss for Resolution {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -11,6 +11,7 @@ use crate::traits::{Parse, ToCss};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
+use smallvec::{smallvec, SmallVec};
 
 /// A CSS [`<basic-shape>`](https://www.w3.org/TR/css-shapes-1/#basic-shape-functions) value.
 #[derive(Debug, Clone, PartialEq)]
@@ -30,6 +31,8 @@ pub enum BasicShape {
   Ellipse(Ellipse),
   /// A polygon.
   Polygon(Polygon),
+  /// An SVG path.
+  Path(Path),
 }
 
 /// An [`inset()`](https://www.w3.org/TR/css-shapes-1/#funcdef-inset) rectangle shape.
@@ -123,6 +126,144 @@ pub struct Point {
   y: LengthPercentage,
 }
 
+/// A [`path()`](https://www.w3.org/TR/css-shapes-1/#funcdef-basic-shape-path) shape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Path {
+  /// The fill rule used to determine the interior of the path.
+  pub fill_rule: FillRule,
+  /// The commands that make up the path.
+  pub commands: Vec<PathCommand>,
+}
+
+/// A command within SVG [path data](https://www.w3.org/TR/SVG/paths.html#PathData).
+///
+/// Relative commands are interpreted relative to the current point. Coordinates are stored as
+/// `f64` rather than [CSSNumber](super::number::CSSNumber), so that path data keeps its full precision.
+/// See [Path](Path).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum PathCommand {
+  /// Starts a new subpath at the given point.
+  MoveTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate.
+    x: f64,
+    /// The y coordinate.
+    y: f64,
+  },
+  /// Draws a straight line to the given point.
+  LineTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate.
+    x: f64,
+    /// The y coordinate.
+    y: f64,
+  },
+  /// Draws a horizontal line.
+  HorizontalLineTo {
+    /// Whether the coordinate is relative.
+    relative: bool,
+    /// The x coordinate.
+    x: f64,
+  },
+  /// Draws a vertical line.
+  VerticalLineTo {
+    /// Whether the coordinate is relative.
+    relative: bool,
+    /// The y coordinate.
+    y: f64,
+  },
+  /// Draws a cubic Bézier curve.
+  CurveTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate of the first control point.
+    x1: f64,
+    /// The y coordinate of the first control point.
+    y1: f64,
+    /// The x coordinate of the second control point.
+    x2: f64,
+    /// The y coordinate of the second control point.
+    y2: f64,
+    /// The x coordinate of the end point.
+    x: f64,
+    /// The y coordinate of the end point.
+    y: f64,
+  },
+  /// Draws a cubic Bézier curve, reflecting the previous control point.
+  SmoothCurveTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate of the second control point.
+    x2: f64,
+    /// The y coordinate of the second control point.
+    y2: f64,
+    /// The x coordinate of the end point.
+    x: f64,
+    /// The y coordinate of the end point.
+    y: f64,
+  },
+  /// Draws a quadratic Bézier curve.
+  QuadraticCurveTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate of the control point.
+    x1: f64,
+    /// The y coordinate of the control point.
+    y1: f64,
+    /// The x coordinate of the end point.
+    x: f64,
+    /// The y coordinate of the end point.
+    y: f64,
+  },
+  /// Draws a quadratic Bézier curve, reflecting the previous control point.
+  SmoothQuadraticCurveTo {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x coordinate of the end point.
+    x: f64,
+    /// The y coordinate of the end point.
+    y: f64,
+  },
+  /// Draws an elliptical arc.
+  Arc {
+    /// Whether the coordinates are relative.
+    relative: bool,
+    /// The x-radius of the ellipse.
+    rx: f64,
+    /// The y-radius of the ellipse.
+    ry: f64,
+    /// The rotation of the ellipse's x-axis, in degrees.
+    angle: f64,
+    /// Whether to draw the larger of the two possible arcs.
+    #[cfg_attr(feature = "serde", serde(rename = "largeArc"))]
+    large_arc: bool,
+    /// Whether to draw the arc in the positive-angle direction.
+    sweep: bool,
+    /// The x coordinate of the end point.
+    x: f64,
+    /// The y coordinate of the end point.
+    y: f64,
+  },
+  /// Closes the current subpath.
+  ClosePath,
+}
+
 enum_property! {
   /// A [`<fill-rule>`](https://www.w3.org/TR/css-shapes-1/#typedef-fill-rule) used to
   /// determine the interior of a `polygon()` shape.
@@ -151,6 +292,7 @@ impl<'i> Parse<'i> for BasicShape {
       "circle" => Ok(BasicShape::Circle(input.parse_nested_block(Circle::parse)?)),
       "ellipse" => Ok(BasicShape::Ellipse(input.parse_nested_block(Ellipse::parse)?)),
       "polygon" => Ok(BasicShape::Polygon(input.parse_nested_block(Polygon::parse)?)),
+      "path" => Ok(BasicShape::Path(input.parse_nested_block(Path::parse)?)),
       _ => Err(location.new_unexpected_token_error(Token::Ident(f.clone()))),
     }
   }
@@ -239,6 +381,291 @@ impl<'i> Parse<'i> for Polygon {
   }
 }
 
+impl<'i> Parse<'i> for Path {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let fill_rule = input.try_parse(FillRule::parse);
+    if fill_rule.is_ok() {
+      input.expect_comma()?;
+    }
+
+    let location = input.current_source_location();
+    let data = input.expect_string()?;
+    let commands = match PathDataParser::new(data).parse() {
+      Some(commands) => commands,
+      None => return Err(location.new_custom_error(ParserError::InvalidValue)),
+    };
+
+    Ok(Path {
+      fill_rule: fill_rule.unwrap_or_default(),
+      commands,
+    })
+  }
+}
+
+/// A parser for the [SVG path data](https://www.w3.org/TR/SVG/paths.html#PathDataBNF) grammar.
+struct PathDataParser<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+  fn new(data: &'a str) -> Self {
+    PathDataParser {
+      bytes: data.as_bytes(),
+      pos: 0,
+    }
+  }
+
+  fn parse(&mut self) -> Option<Vec<PathCommand>> {
+    let mut commands = Vec::new();
+    self.skip_whitespace();
+    while self.pos < self.bytes.len() {
+      let letter = self.bytes[self.pos];
+      if commands.is_empty() && !matches!(letter, b'M' | b'm') {
+        return None;
+      }
+      self.pos += 1;
+
+      let relative = letter.is_ascii_lowercase();
+      let command = letter.to_ascii_uppercase();
+      if command == b'Z' {
+        commands.push(PathCommand::ClosePath);
+        self.skip_whitespace();
+        continue;
+      }
+
+      let kinds = path_argument_kinds(command)?;
+      let mut command = command;
+      loop {
+        self.skip_whitespace();
+        let mut args = [0.0; 7];
+        for (i, kind) in kinds.iter().enumerate() {
+          if i > 0 {
+            self.skip_separator();
+          }
+          args[i] = match kind {
+            PathArgumentKind::Flag => self.parse_flag()? as u8 as f64,
+            _ => self.parse_number()?,
+          };
+        }
+        commands.push(PathCommand::from_parts(command, relative, &args));
+
+        // Additional argument sets repeat the command. Pairs following a move become lines.
+        self.skip_separator();
+        if !self.at_number() {
+          break;
+        }
+        if command == b'M' {
+          command = b'L';
+        }
+      }
+    }
+
+    if commands.is_empty() {
+      return None;
+    }
+
+    Some(commands)
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b' ' | b'\t' | b'\n' | b'\r' | b'\x0C') {
+      self.pos += 1;
+    }
+  }
+
+  fn skip_separator(&mut self) {
+    self.skip_whitespace();
+    if self.pos < self.bytes.len() && self.bytes[self.pos] == b',' {
+      self.pos += 1;
+      self.skip_whitespace();
+    }
+  }
+
+  fn at_number(&self) -> bool {
+    self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'0'..=b'9' | b'.' | b'-' | b'+')
+  }
+
+  fn consume_digits(&mut self) -> usize {
+    let start = self.pos;
+    while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+      self.pos += 1;
+    }
+    self.pos - start
+  }
+
+  fn parse_number(&mut self) -> Option<f64> {
+    let start = self.pos;
+    if self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'-' | b'+') {
+      self.pos += 1;
+    }
+
+    let mut digits = self.consume_digits();
+    if self.pos < self.bytes.len() && self.bytes[self.pos] == b'.' {
+      self.pos += 1;
+      digits += self.consume_digits();
+    }
+
+    if digits == 0 {
+      return None;
+    }
+
+    // Only consume an exponent if it is followed by digits, e.g. `1e5`, but not `1em`.
+    if self.pos < self.bytes.len() && matches!(self.bytes[self.pos], b'e' | b'E') {
+      let mut end = self.pos + 1;
+      if end < self.bytes.len() && matches!(self.bytes[end], b'-' | b'+') {
+        end += 1;
+      }
+      if end < self.bytes.len() && self.bytes[end].is_ascii_digit() {
+        self.pos = end;
+        self.consume_digits();
+      }
+    }
+
+    let s = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+    let value: f64 = s.parse().ok()?;
+    if !value.is_finite() {
+      return None;
+    }
+    Some(value)
+  }
+
+  fn parse_flag(&mut self) -> Option<bool> {
+    let flag = match self.bytes.get(self.pos)? {
+      b'0' => false,
+      b'1' => true,
+      _ => return None,
+    };
+    self.pos += 1;
+    Some(flag)
+  }
+}
+
+/// How an argument of a path command is interpreted.
+#[derive(Clone, Copy, PartialEq)]
+enum PathArgumentKind {
+  /// A horizontal coordinate, which is offset by the current point in relative commands.
+  X,
+  /// A vertical coordinate, which is offset by the current point in relative commands.
+  Y,
+  /// A number that is unaffected by the current point.
+  Number,
+  /// An arc flag.
+  Flag,
+}
+
+fn path_argument_kinds(command: u8) -> Option<&'static [PathArgumentKind]> {
+  use PathArgumentKind::*;
+  Some(match command {
+    b'M' | b'L' | b'T' => &[X, Y],
+    b'H' => &[X],
+    b'V' => &[Y],
+    b'C' => &[X, Y, X, Y, X, Y],
+    b'S' | b'Q' => &[X, Y, X, Y],
+    b'A' => &[Number, Number, Number, Flag, Flag, X, Y],
+    b'Z' => &[],
+    _ => return None,
+  })
+}
+
+impl PathCommand {
+  fn from_parts(command: u8, relative: bool, args: &[f64; 7]) -> PathCommand {
+    let a = |i: usize| args[i];
+    match command {
+      b'M' => PathCommand::MoveTo {
+        relative,
+        x: a(0),
+        y: a(1),
+      },
+      b'L' => PathCommand::LineTo {
+        relative,
+        x: a(0),
+        y: a(1),
+      },
+      b'H' => PathCommand::HorizontalLineTo { relative, x: a(0) },
+      b'V' => PathCommand::VerticalLineTo { relative, y: a(0) },
+      b'C' => PathCommand::CurveTo {
+        relative,
+        x1: a(0),
+        y1: a(1),
+        x2: a(2),
+        y2: a(3),
+        x: a(4),
+        y: a(5),
+      },
+      b'S' => PathCommand::SmoothCurveTo {
+        relative,
+        x2: a(0),
+        y2: a(1),
+        x: a(2),
+        y: a(3),
+      },
+      b'Q' => PathCommand::QuadraticCurveTo {
+        relative,
+        x1: a(0),
+        y1: a(1),
+        x: a(2),
+        y: a(3),
+      },
+      b'T' => PathCommand::SmoothQuadraticCurveTo {
+        relative,
+        x: a(0),
+        y: a(1),
+      },
+      b'A' => PathCommand::Arc {
+        relative,
+        rx: a(0),
+        ry: a(1),
+        angle: a(2),
+        large_arc: args[3] != 0.0,
+        sweep: args[4] != 0.0,
+        x: a(5),
+        y: a(6),
+      },
+      _ => PathCommand::ClosePath,
+    }
+  }
+
+  /// Returns the uppercase command letter, whether the command is relative, and its arguments.
+  fn to_parts(&self) -> (u8, bool, SmallVec<[f64; 7]>) {
+    use PathCommand::*;
+    let flag = |f: bool| if f { 1.0 } else { 0.0 };
+    match *self {
+      MoveTo { relative, x, y } => (b'M', relative, smallvec![x, y]),
+      LineTo { relative, x, y } => (b'L', relative, smallvec![x, y]),
+      HorizontalLineTo { relative, x } => (b'H', relative, smallvec![x]),
+      VerticalLineTo { relative, y } => (b'V', relative, smallvec![y]),
+      CurveTo {
+        relative,
+        x1,
+        y1,
+        x2,
+        y2,
+        x,
+        y,
+      } => (b'C', relative, smallvec![x1, y1, x2, y2, x, y]),
+      SmoothCurveTo { relative, x2, y2, x, y } => (b'S', relative, smallvec![x2, y2, x, y]),
+      QuadraticCurveTo { relative, x1, y1, x, y } => (b'Q', relative, smallvec![x1, y1, x, y]),
+      SmoothQuadraticCurveTo { relative, x, y } => (b'T', relative, smallvec![x, y]),
+      Arc {
+        relative,
+        rx,
+        ry,
+        angle,
+        large_arc,
+        sweep,
+        x,
+        y,
+      } => (
+        b'A',
+        relative,
+        smallvec![rx, ry, angle, flag(large_arc), flag(sweep), x, y],
+      ),
+      ClosePath => (b'Z', false, SmallVec::new()),
+    }
+  }
+}
+
 impl<'i> Parse<'i> for Point {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let x = LengthPercentage::parse(input)?;
@@ -252,7 +679,10 @@ impl ToCss for BasicShape {
   where
     W: std::fmt::Write,
   {
-    match self {
+    let was_in_shape = dest.in_shape;
+    dest.in_shape = true;
+
+    let res = match self {
       BasicShape::Inset(rect) => {
         dest.write_str("inset(")?;
         rect.to_css(dest)?;
@@ -273,7 +703,15 @@ impl ToCss for BasicShape {
         poly.to_css(dest)?;
         dest.write_char(')')
       }
-    }
+      BasicShape::Path(path) => {
+        dest.write_str("path(")?;
+        path.to_css(dest)?;
+        dest.write_char(')')
+      }
+    };
+
+    dest.in_shape = was_in_shape;
+    res
   }
 }
 
@@ -386,3 +824,208 @@ impl ToCss for Point {
     self.y.to_css(dest)
   }
 }
+
+impl ToCss for Path {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.fill_rule != FillRule::default() {
+      self.fill_rule.to_css(dest)?;
+      dest.delim(',', false)?;
+    }
+
+    let data = self.serialize_data(dest);
+    serialize_string(&data, dest)?;
+    Ok(())
+  }
+}
+
+impl Path {
+  /// Serializes the path data. When minifying, each command is written in whichever of its
+  /// absolute or relative forms is shorter, and redundant command letters and separators are omitted.
+  fn serialize_data<W>(&self, dest: &Printer<W>) -> String
+  where
+    W: std::fmt::Write,
+  {
+    let mut out = String::new();
+    let mut writer = PathDataWriter {
+      minify: dest.minify,
+      empty: true,
+      implicit_command: None,
+      last_number: None,
+    };
+
+    // The current point and subpath start, as seen by a consumer of the output.
+    // These are tracked separately from the input so that rounding errors don't accumulate.
+    let mut current = (0.0, 0.0);
+    let mut input_current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    let mut input_start = (0.0, 0.0);
+
+    for command in &self.commands {
+      let (letter, relative, args) = command.to_parts();
+      if letter == b'Z' {
+        let mut buf = String::new();
+        writer.write_command(&mut buf, if relative { 'z' } else { 'Z' }, &[]);
+        out.push_str(&buf);
+        current = start;
+        input_current = input_start;
+        continue;
+      }
+
+      let kinds = path_argument_kinds(letter).unwrap_or(&[]);
+      let offset = |point: (f64, f64), kind: &PathArgumentKind| match kind {
+        PathArgumentKind::X => point.0,
+        PathArgumentKind::Y => point.1,
+        _ => 0.0,
+      };
+
+      let absolute: SmallVec<[f64; 7]> = args
+        .iter()
+        .zip(kinds)
+        .map(|(arg, kind)| {
+          if relative {
+            arg + offset(input_current, kind)
+          } else {
+            *arg
+          }
+        })
+        .collect();
+
+      let mut candidates: SmallVec<[bool; 2]> = SmallVec::new();
+      if dest.minify {
+        candidates.push(false);
+        candidates.push(true);
+      } else {
+        candidates.push(relative);
+      }
+
+      let mut best: Option<(String, PathDataWriter, SmallVec<[f64; 7]>, bool)> = None;
+      for as_relative in candidates {
+        let values: SmallVec<[f64; 7]> = absolute
+          .iter()
+          .zip(kinds)
+          .map(|(arg, kind)| {
+            let value = if as_relative { arg - offset(current, kind) } else { *arg };
+            match dest.shape_precision() {
+              Some(precision) => {
+                let factor = 10f64.powi(precision as i32);
+                (value * factor).round() / factor
+              }
+              None => value,
+            }
+          })
+          .collect();
+
+        let c = letter as char;
+        let c = if as_relative { c.to_ascii_lowercase() } else { c };
+        let mut candidate_writer = writer;
+        let mut buf = String::new();
+        candidate_writer.write_command(&mut buf, c, &values);
+        if best.as_ref().map_or(true, |(b, ..)| buf.len() < b.len()) {
+          best = Some((buf, candidate_writer, values, as_relative));
+        }
+      }
+
+      let (buf, next_writer, values, as_relative) = best.unwrap();
+      out.push_str(&buf);
+      writer = next_writer;
+
+      // Update the current point from the end point of the command.
+      let end = |point: (f64, f64), args: &[f64], rel: bool| {
+        let mut point_x = if rel { point.0 } else { 0.0 };
+        let mut point_y = if rel { point.1 } else { 0.0 };
+        let mut has_x = false;
+        let mut has_y = false;
+        for (arg, kind) in args.iter().zip(kinds).rev() {
+          match kind {
+            PathArgumentKind::X if !has_x => {
+              point_x += arg;
+              has_x = true;
+            }
+            PathArgumentKind::Y if !has_y => {
+              point_y += arg;
+              has_y = true;
+            }
+            _ => {}
+          }
+        }
+        (
+          if has_x { point_x } else { point.0 },
+          if has_y { point_y } else { point.1 },
+        )
+      };
+
+      current = end(current, &values, as_relative);
+      input_current = end(input_current, &args, relative);
+      if letter == b'M' {
+        start = current;
+        input_start = input_current;
+      }
+    }
+
+    out
+  }
+}
+
+/// Tracks state while writing path data, so that separators and repeated command letters can be omitted.
+#[derive(Clone, Copy)]
+struct PathDataWriter {
+  minify: bool,
+  /// Whether nothing has been written yet.
+  empty: bool,
+  /// The command that will be implied if a command letter is omitted.
+  implicit_command: Option<char>,
+  /// Whether the last thing written was a number, and if so, whether
+  /// a following number starting with `.` can be written without a separator.
+  last_number: Option<bool>,
+}
+
+impl PathDataWriter {
+  fn write_command(&mut self, out: &mut String, command: char, args: &[f64]) {
+    if !self.minify || self.implicit_command != Some(command) {
+      if !self.minify && !self.empty {
+        out.push(' ');
+      }
+      out.push(command);
+      self.empty = false;
+      self.last_number = None;
+    }
+
+    self.implicit_command = match command {
+      'M' => Some('L'),
+      'm' => Some('l'),
+      'Z' | 'z' => None,
+      c => Some(c),
+    };
+
+    for arg in args {
+      self.write_number(out, *arg);
+    }
+  }
+
+  fn write_number(&mut self, out: &mut String, value: f64) {
+    // Avoid writing negative zero.
+    let value = if value == 0.0 { 0.0 } else { value };
+    let mut s = String::new();
+    let _ = cssparser::ToCss::to_css(&value, &mut s);
+    if value.abs() < 1.0 && value != 0.0 {
+      s = s.replacen("0.", ".", 1);
+    }
+
+    if self.minify {
+      if let Some(can_omit_before_dot) = self.last_number {
+        if !(s.starts_with('-') || (s.starts_with('.') && can_omit_before_dot)) {
+          out.push(' ');
+        }
+      }
+    } else if !self.empty {
+      out.push(' ');
+    }
+
+    out.push_str(&s);
+    self.empty = false;
+    self.last_number = Some(s.contains(|c| matches!(c, '.' | 'e' | 'E')));
+  }
+}
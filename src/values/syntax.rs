@@ -387,8 +387,19 @@ impl ToCss for SyntaxString {
     W: std::fmt::Write,
   {
     dest.write_char('"')?;
+    self.to_css_unquoted(dest)?;
+    dest.write_char('"')
+  }
+}
+
+impl SyntaxString {
+  /// Serializes the syntax without surrounding quotes, as in the `type()` function of `attr()`.
+  pub(crate) fn to_css_unquoted<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
     match self {
-      SyntaxString::Universal => dest.write_char('*')?,
+      SyntaxString::Universal => dest.write_char('*'),
       SyntaxString::Components(components) => {
         let mut first = true;
         for component in components {
@@ -400,10 +411,9 @@ impl ToCss for SyntaxString {
 
           component.to_css(dest)?;
         }
+        Ok(())
       }
     }
-
-    dest.write_char('"')
   }
 }
 
@@ -0,0 +1,277 @@
+//! Custom property dependency graphs.
+//!
+//! [StyleSheet::var_graph](crate::stylesheet::StyleSheet::var_graph) builds a [VarGraph](VarGraph) of the
+//! custom properties in a style sheet: where each of them is defined, which custom properties each
+//! definition depends on, and which declarations reference them via `var()`. The graph can be queried
+//! for dependencies and dependents, for references to custom properties that are never defined, and for
+//! cycles, which make all of the custom properties involved invalid at computed-value time.
+//!
+//! [VarGraph::undefined_references](VarGraph::undefined_references) returns the `var()` references to custom
+//! properties that are never defined and have no fallback value, which are also reported by the
+//! [NoUndefinedVariables](crate::lint::NoUndefinedVariables) lint rule. Run these on a bundled style sheet so
+//! that custom properties defined in imported files are taken into account.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::custom::{CustomProperty, CustomPropertyName, TokenList};
+use crate::properties::Property;
+use crate::rules::{CssRule, CssRuleList, Location};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A graph of the custom properties defined and referenced by a style sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct VarGraph {
+  /// The custom properties defined or referenced by the style sheet, by name including the leading `--`.
+  pub variables: BTreeMap<String, VarNode>,
+}
+
+/// A custom property within a [VarGraph](VarGraph).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct VarNode {
+  /// The declarations that define the custom property.
+  pub definitions: Vec<VarDefinition>,
+  /// The declarations that reference the custom property via `var()`.
+  pub references: Vec<VarReference>,
+}
+
+/// A declaration of a custom property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct VarDefinition {
+  /// The location of the rule containing the declaration.
+  pub loc: Location,
+  /// The custom properties referenced by the value, including within fallbacks.
+  pub dependencies: Vec<String>,
+}
+
+/// A `var()` reference to a custom property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "camelCase")
+)]
+pub struct VarReference {
+  /// The name of the property whose value contains the reference, e.g. `color` or `--theme`.
+  pub property: String,
+  /// The location of the rule containing the declaration.
+  pub loc: Location,
+  /// Whether the reference has a fallback value.
+  pub has_fallback: bool,
+}
+
+impl VarGraph {
+  pub(crate) fn build<T>(rules: &CssRuleList<T>) -> VarGraph {
+    let mut graph = VarGraph::default();
+    rules.for_each_rule(&mut |rule| match rule {
+      CssRule::Style(style) => graph.add_declarations(&style.declarations, style.loc),
+      CssRule::Nesting(nesting) => graph.add_declarations(&nesting.style.declarations, nesting.style.loc),
+      CssRule::NestedDeclarations(nested) => graph.add_declarations(&nested.declarations, nested.loc),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          graph.add_declarations(&keyframe.declarations, keyframes.loc);
+        }
+      }
+      CssRule::Page(page) => {
+        graph.add_declarations(&page.declarations, page.loc);
+        for margin_rule in &page.rules {
+          graph.add_declarations(&margin_rule.declarations, margin_rule.loc);
+        }
+      }
+      CssRule::Viewport(viewport) => graph.add_declarations(&viewport.declarations, viewport.loc),
+      CssRule::PositionTry(position_try) => graph.add_declarations(&position_try.declarations, position_try.loc),
+      _ => {}
+    });
+    graph
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock, loc: Location) {
+    for (property, _) in declarations.iter() {
+      match property {
+        Property::Custom(CustomProperty {
+          name: CustomPropertyName::Custom(name),
+          value,
+        }) => {
+          let dependencies = self.add_references(value, name.0.as_ref(), loc);
+          self
+            .variables
+            .entry(name.0.to_string())
+            .or_default()
+            .definitions
+            .push(VarDefinition { loc, dependencies });
+        }
+        Property::Custom(CustomProperty {
+          name: CustomPropertyName::Unknown(name),
+          value,
+        }) => {
+          self.add_references(value, name.0.as_ref(), loc);
+        }
+        Property::Unparsed(unparsed) => {
+          self.add_references(&unparsed.value, unparsed.property_id.name(), loc);
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /// Adds the references within a property value, and returns the names of the referenced custom properties.
+  fn add_references(&mut self, value: &TokenList, property: &str, loc: Location) -> Vec<String> {
+    let mut names = Vec::new();
+    value.for_each_var(&mut |var| {
+      // References to custom properties defined in other files by CSS modules are not part of this graph.
+      if var.name.from.is_some() {
+        return;
+      }
+
+      let name = var.name.ident.0.to_string();
+      self.variables.entry(name.clone()).or_default().references.push(VarReference {
+        property: property.to_owned(),
+        loc,
+        has_fallback: var.fallback.is_some(),
+      });
+      if !names.contains(&name) {
+        names.push(name);
+      }
+    });
+    names
+  }
+
+  /// Returns whether the custom property is defined anywhere in the style sheet.
+  pub fn is_defined(&self, name: &str) -> bool {
+    self.variables.get(name).map_or(false, |node| !node.definitions.is_empty())
+  }
+
+  /// Returns the custom properties referenced by any definition of the given custom property.
+  pub fn dependencies(&self, name: &str) -> BTreeSet<&str> {
+    let mut dependencies = BTreeSet::new();
+    if let Some(node) = self.variables.get(name) {
+      for definition in &node.definitions {
+        dependencies.extend(definition.dependencies.iter().map(|name| name.as_str()));
+      }
+    }
+    dependencies
+  }
+
+  /// Returns the custom properties with a definition that references the given custom property.
+  pub fn dependents(&self, name: &str) -> BTreeSet<&str> {
+    self
+      .variables
+      .iter()
+      .filter(|(_, node)| {
+        node
+          .definitions
+          .iter()
+          .any(|definition| definition.dependencies.iter().any(|dependency| dependency == name))
+      })
+      .map(|(name, _)| name.as_str())
+      .collect()
+  }
+
+  /// Returns the custom properties that are referenced but never defined in the style sheet.
+  pub fn undefined(&self) -> Vec<&str> {
+    self
+      .variables
+      .iter()
+      .filter(|(_, node)| node.definitions.is_empty())
+      .map(|(name, _)| name.as_str())
+      .collect()
+  }
+
+  /// Returns the references to custom properties that are never defined in the style sheet and have no
+  /// fallback value, along with the name of the referenced custom property. Such references make the
+  /// declaration invalid at computed-value time.
+  ///
+  /// Custom properties matching the allowlist, e.g. those injected at runtime, are not included. Entries
+  /// ending in `*` match all custom properties starting with the rest of the entry, e.g. `--theme-*`.
+  pub fn undefined_references<S: AsRef<str>>(&self, allowlist: &[S]) -> Vec<(&str, &VarReference)> {
+    self
+      .variables
+      .iter()
+      .filter(|(name, node)| node.definitions.is_empty() && !is_allowed(name, allowlist))
+      .flat_map(|(name, node)| {
+        node
+          .references
+          .iter()
+          .filter(|reference| !reference.has_fallback)
+          .map(move |reference| (name.as_str(), reference))
+      })
+      .collect()
+  }
+
+  /// Returns the groups of custom properties that depend on each other in a cycle, each sorted by name.
+  pub fn cycles(&self) -> Vec<Vec<&str>> {
+    // Tarjan's strongly connected components algorithm.
+    struct State<'a> {
+      graph: &'a VarGraph,
+      index: usize,
+      indices: HashMap<&'a str, usize>,
+      lowlinks: HashMap<&'a str, usize>,
+      stack: Vec<&'a str>,
+      cycles: Vec<Vec<&'a str>>,
+    }
+
+    fn connect<'a>(state: &mut State<'a>, name: &'a str) {
+      state.indices.insert(name, state.index);
+      state.lowlinks.insert(name, state.index);
+      state.index += 1;
+      state.stack.push(name);
+
+      let graph = state.graph;
+      for dependency in graph.dependencies(name) {
+        if !graph.is_defined(dependency) {
+          continue;
+        }
+
+        if !state.indices.contains_key(dependency) {
+          connect(state, dependency);
+          let lowlink = state.lowlinks[name].min(state.lowlinks[dependency]);
+          state.lowlinks.insert(name, lowlink);
+        } else if state.stack.contains(&dependency) {
+          let lowlink = state.lowlinks[name].min(state.indices[dependency]);
+          state.lowlinks.insert(name, lowlink);
+        }
+      }
+
+      if state.lowlinks[name] == state.indices[name] {
+        let mut component = Vec::new();
+        while let Some(member) = state.stack.pop() {
+          component.push(member);
+          if member == name {
+            break;
+          }
+        }
+
+        if component.len() > 1 || graph.dependencies(name).contains(name) {
+          component.sort();
+          state.cycles.push(component);
+        }
+      }
+    }
+
+    let mut state = State {
+      graph: self,
+      index: 0,
+      indices: HashMap::new(),
+      lowlinks: HashMap::new(),
+      stack: Vec::new(),
+      cycles: Vec::new(),
+    };
+
+    for (name, node) in &self.variables {
+      if !node.definitions.is_empty() && !state.indices.contains_key(name.as_str()) {
+        connect(&mut state, name);
+      }
+    }
+
+    state.cycles.sort();
+    state.cycles
+  }
+}
+
+fn is_allowed<S: AsRef<str>>(name: &str, allowlist: &[S]) -> bool {
+  allowlist.iter().any(|entry| match entry.as_ref().strip_suffix('*') {
+    Some(prefix) => name.starts_with(prefix),
+    None => name == entry.as_ref(),
+  })
+}
@@ -64,8 +64,10 @@ use crate::{
     custom::{EnvironmentVariable, Function, TokenList, TokenOrValue, Variable},
     Property,
   },
-  rules::{supports::SupportsCondition, CssRule, CssRuleList},
-  selector::{Selector, SelectorList},
+  rules::{comment::CommentRule, custom_at_rule::CustomAtRule, supports::SupportsCondition, CssRule, CssRuleList},
+  selector::{Component, PseudoClass, Selector, SelectorList},
+  stylesheet::{ParserOptions, PrinterOptions},
+  traits::{ParseWithOptions, ToCss},
   values::{
     angle::Angle,
     color::CssColor,
@@ -80,6 +82,7 @@ use crate::{
 };
 use bitflags::bitflags;
 use smallvec::SmallVec;
+use std::collections::HashMap;
 
 pub(crate) use lightningcss_derive::Visit;
 
@@ -127,6 +130,10 @@ bitflags! {
     const FUNCTIONS = 1 << 17;
     /// Visit a token.
     const TOKENS = 1 << 18;
+    /// Visit comments.
+    const COMMENTS = 1 << 19;
+    /// Visit at-rules registered with the `custom_at_rules` parser option.
+    const CUSTOM_AT_RULES = 1 << 20;
   }
 }
 
@@ -155,6 +162,15 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule>: Sized {
     Self::TYPES
   }
 
+  /// Combines this visitor with another one, so that both run in a single traversal. See [Chain](Chain).
+  fn chain<V>(self, other: V) -> Chain<Self, V>
+  where
+    T: Visit<'i, T, V>,
+    V: Visitor<'i, T, Error = Self::Error>,
+  {
+    Chain::new(self, other)
+  }
+
   /// Visits a rule list.
   #[inline]
   fn visit_rule_list(&mut self, rules: &mut CssRuleList<'i, T>) -> Result<(), Self::Error> {
@@ -167,6 +183,18 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule>: Sized {
     rule.visit_children(self)
   }
 
+  /// Visits a comment within a rule list, if the `preserve_comments` parser option is enabled.
+  #[allow(unused_variables)]
+  fn visit_comment(&mut self, comment: &mut CommentRule<'i>) -> Result<(), Self::Error> {
+    Ok(())
+  }
+
+  /// Visits an at-rule registered with the `custom_at_rules` parser option.
+  #[inline]
+  fn visit_custom_at_rule(&mut self, rule: &mut CustomAtRule<'i, T>) -> Result<(), Self::Error> {
+    rule.visit_children(self)
+  }
+
   /// Visits a declaration block.
   #[inline]
   fn visit_declaration_block(&mut self, decls: &mut DeclarationBlock<'i>) -> Result<(), Self::Error> {
@@ -287,9 +315,9 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule>: Sized {
   }
 
   /// Visits a selector.
-  #[allow(unused_variables)]
+  #[inline]
   fn visit_selector(&mut self, selector: &mut Selector<'i>) -> Result<(), Self::Error> {
-    Ok(())
+    selector.visit_children(self)
   }
 
   /// Visits a custom function.
@@ -304,7 +332,8 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule>: Sized {
     tokens.visit_children(self)
   }
 
-  /// Visits a token or value in an unparsed property.
+  /// Visits a token or value in an unparsed property, the arguments of an unknown pseudo class or
+  /// pseudo element, or the prelude or block of an unknown at-rule.
   #[inline]
   fn visit_token(&mut self, token: &mut TokenOrValue<'i>) -> Result<(), Self::Error> {
     token.visit_children(self)
@@ -404,8 +433,355 @@ impl_visit!(u16);
 impl_visit!(u32);
 impl_visit!(i32);
 impl_visit!(f32);
+impl_visit!(f64);
 impl_visit!(bool);
 impl_visit!(char);
 impl_visit!(str);
 impl_visit!(String);
 impl_visit!((f32, f32));
+
+/// A [Visitor](Visitor) that runs two visitors in a single traversal, created with [Visitor::chain](Visitor::chain).
+///
+/// The chain visits the union of the [VisitTypes](VisitTypes) of both visitors. At each value, the hook of the first
+/// visitor is called if it visits that type, followed by the hook of the second visitor, so the second visitor sees
+/// the changes made to the value by the first one. A visitor whose hook is called is responsible for visiting the
+/// children of the value, as usual, and is skipped while the chain visits them for the other visitor. This ensures
+/// that each visitor sees every value exactly once. Visitors that only visit values without children, such as urls, colors, and
+/// lengths, therefore share a single traversal, while visitors that also visit rules or properties traverse those
+/// branches themselves.
+///
+/// More than two visitors may be combined by chaining repeatedly.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use lightningcss::{
+///   stylesheet::{StyleSheet, ParserOptions, PrinterOptions},
+///   visitor::{Visitor, Visit, VisitTypes},
+///   visit_types,
+///   values::length::LengthValue,
+///   values::url::Url
+/// };
+///
+/// struct UrlPrefixer;
+///
+/// impl<'i> Visitor<'i> for UrlPrefixer {
+///   type Error = Infallible;
+///   const TYPES: VisitTypes = visit_types!(URLS);
+///
+///   fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+///     url.url = format!("https://mywebsite.com/{}", url.url).into();
+///     Ok(())
+///   }
+/// }
+///
+/// struct PxToRem;
+///
+/// impl<'i> Visitor<'i> for PxToRem {
+///   type Error = Infallible;
+///   const TYPES: VisitTypes = visit_types!(LENGTHS);
+///
+///   fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
+///     if let LengthValue::Px(px) = length {
+///       *length = LengthValue::Rem(*px / 16.0);
+///     }
+///     Ok(())
+///   }
+/// }
+///
+/// let mut stylesheet = StyleSheet::parse(
+///   ".foo { background: url(bg.png); width: 32px }",
+///   ParserOptions::default()
+/// ).unwrap();
+///
+/// stylesheet.visit(&mut UrlPrefixer.chain(PxToRem)).unwrap();
+///
+/// let res = stylesheet.to_css(PrinterOptions { minify: true, ..Default::default() }).unwrap();
+/// assert_eq!(res.code, ".foo{background:url(https://mywebsite.com/bg.png);width:2rem}");
+/// ```
+pub struct Chain<A, B> {
+  first: A,
+  second: B,
+  first_active: bool,
+  second_active: bool,
+}
+
+impl<A, B> Chain<A, B> {
+  /// Creates a chain of two visitors.
+  pub fn new(first: A, second: B) -> Self {
+    Chain {
+      first,
+      second,
+      first_active: true,
+      second_active: true,
+    }
+  }
+
+  /// Returns the chained visitors, e.g. to read the results they collected.
+  pub fn into_inner(self) -> (A, B) {
+    (self.first, self.second)
+  }
+}
+
+macro_rules! chain_children {
+  ($value: ident, $visitor: ident) => {
+    $value.visit_children($visitor)
+  };
+  // Identifiers have no children, and are not tied to the `'i` lifetime in the hook signatures.
+  ($value: ident, $visitor: ident, leaf) => {
+    Ok(())
+  };
+}
+
+macro_rules! chain_hooks {
+  ($($method: ident($ty: ty, $flag: ident $(, $leaf: ident)?);)*) => {
+    $(
+      fn $method(&mut self, value: &mut $ty) -> Result<(), Self::Error> {
+        let (first_active, second_active) = (self.first_active, self.second_active);
+        let mut res = Ok(());
+        if first_active && self.first.visit_types().contains(VisitTypes::$flag) {
+          res = self.first.$method(value);
+          self.first_active = false;
+        }
+        if res.is_ok() && second_active && self.second.visit_types().contains(VisitTypes::$flag) {
+          res = self.second.$method(value);
+          self.second_active = false;
+        }
+        if res.is_ok() {
+          res = chain_children!(value, self $(, $leaf)?);
+        }
+        self.first_active = first_active;
+        self.second_active = second_active;
+        res
+      }
+    )*
+  };
+}
+
+impl<'i, A, B> Visitor<'i> for Chain<A, B>
+where
+  A: Visitor<'i>,
+  B: Visitor<'i, Error = A::Error>,
+{
+  type Error = A::Error;
+
+  const TYPES: VisitTypes = VisitTypes::from_bits_truncate(A::TYPES.bits() | B::TYPES.bits());
+
+  fn visit_types(&self) -> VisitTypes {
+    let mut types = VisitTypes::empty();
+    if self.first_active {
+      types |= self.first.visit_types();
+    }
+    if self.second_active {
+      types |= self.second.visit_types();
+    }
+    types
+  }
+
+  chain_hooks! {
+    visit_rule_list(CssRuleList<'i>, RULES);
+    visit_rule(CssRule<'i>, RULES);
+    visit_comment(CommentRule<'i>, COMMENTS);
+    visit_custom_at_rule(CustomAtRule<'i>, CUSTOM_AT_RULES);
+    visit_declaration_block(DeclarationBlock<'i>, PROPERTIES);
+    visit_property(Property<'i>, PROPERTIES);
+    visit_url(Url<'i>, URLS);
+    visit_color(CssColor, COLORS);
+    visit_image(Image<'i>, IMAGES);
+    visit_length(LengthValue, LENGTHS);
+    visit_angle(Angle, ANGLES);
+    visit_ratio(Ratio, RATIOS);
+    visit_resolution(Resolution, RESOLUTIONS);
+    visit_time(Time, TIMES);
+    visit_custom_ident(CustomIdent, CUSTOM_IDENTS, leaf);
+    visit_dashed_ident(DashedIdent, DASHED_IDENTS, leaf);
+    visit_variable(Variable<'i>, VARIABLES);
+    visit_environment_variable(EnvironmentVariable<'i>, ENVIRONMENT_VARIABLES);
+    visit_media_list(MediaList<'i>, MEDIA_QUERIES);
+    visit_media_query(MediaQuery<'i>, MEDIA_QUERIES);
+    visit_media_feature(MediaFeature<'i>, MEDIA_QUERIES);
+    visit_media_feature_value(MediaFeatureValue<'i>, MEDIA_QUERIES);
+    visit_supports_condition(SupportsCondition<'i>, SUPPORTS_CONDITIONS);
+    visit_selector_list(SelectorList<'i>, SELECTORS);
+    visit_selector(Selector<'i>, SELECTORS);
+    visit_function(Function<'i>, FUNCTIONS);
+    visit_token_list(TokenList<'i>, TOKENS);
+    visit_token(TokenOrValue<'i>, TOKENS);
+  }
+}
+
+/// A [Visitor](Visitor) that can visit the top-level rules of a style sheet in parallel, using
+/// [StyleSheet::visit_parallel](crate::stylesheet::StyleSheet::visit_parallel).
+///
+/// The rules are split between threads, each of which visits its rules with a copy of the visitor created
+/// by [fork](ParallelVisitor::fork). Rules are visited in order within each thread, but not across threads,
+/// so parallel visitors must not depend on the order of rules, e.g. by renaming identifiers based on those
+/// seen previously. Once all rules are visited, the copies are passed back to the original visitor with
+/// [merge](ParallelVisitor::merge), in the order of the rules they visited.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use lightningcss::{
+///   stylesheet::{StyleSheet, ParserOptions},
+///   visitor::{ParallelVisitor, Visitor, VisitTypes},
+///   visit_types,
+///   values::url::Url
+/// };
+///
+/// struct UrlCollector(Vec<String>);
+///
+/// impl<'i> Visitor<'i> for UrlCollector {
+///   type Error = Infallible;
+///   const TYPES: VisitTypes = visit_types!(URLS);
+///
+///   fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+///     self.0.push(url.url.to_string());
+///     Ok(())
+///   }
+/// }
+///
+/// impl<'i> ParallelVisitor<'i> for UrlCollector {
+///   fn fork(&self) -> Self {
+///     UrlCollector(Vec::new())
+///   }
+///
+///   fn merge(&mut self, other: Self) {
+///     self.0.extend(other.0);
+///   }
+/// }
+///
+/// let mut stylesheet = StyleSheet::parse(
+///   ".foo { background: url(a.png) } .bar { background: url(b.png) }",
+///   ParserOptions::default()
+/// ).unwrap();
+///
+/// let mut collector = UrlCollector(Vec::new());
+/// stylesheet.visit_parallel(&mut collector).unwrap();
+/// assert_eq!(collector.0, vec!["a.png", "b.png"]);
+/// ```
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub trait ParallelVisitor<'i>: Visitor<'i> + Send + Sync {
+  /// Creates a visitor to visit a subset of the rules on another thread.
+  fn fork(&self) -> Self;
+
+  /// Merges the results collected by a forked visitor into this visitor. By default, the forked visitor
+  /// is dropped, which is suitable for visitors that only transform values.
+  #[allow(unused_variables)]
+  fn merge(&mut self, other: Self) {}
+}
+
+/// A mapping of class names and ids to rename, used by [SelectorRenamer](SelectorRenamer).
+///
+/// When deserialized (e.g. from the JSON file passed to the CLI's `--rename-map` flag),
+/// both fields are optional objects mapping the original name to the new name:
+///
+/// ```json
+/// { "classes": { "button": "btn" }, "ids": { "main": "m" } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(default))]
+pub struct RenameMap {
+  /// A map of class names to rename, without the leading `.`.
+  pub classes: HashMap<String, String>,
+  /// A map of ids to rename, without the leading `#`.
+  pub ids: HashMap<String, String>,
+}
+
+/// A [Visitor](Visitor) that renames classes and ids throughout a style sheet.
+///
+/// Selectors within `:is()`, `:not()`, `:where()`, `:has()`, nested rules, and
+/// `@supports selector()` conditions are all updated consistently.
+///
+/// # Example
+///
+/// ```
+/// use lightningcss::{
+///   stylesheet::{StyleSheet, ParserOptions, PrinterOptions},
+///   visitor::{RenameMap, SelectorRenamer, Visit},
+/// };
+///
+/// let mut stylesheet = StyleSheet::parse(".foo:not(#bar) {color: red}", ParserOptions::default()).unwrap();
+///
+/// let mut map = RenameMap::default();
+/// map.classes.insert("foo".into(), "a".into());
+/// map.ids.insert("bar".into(), "b".into());
+/// stylesheet.visit(&mut SelectorRenamer::new(&map)).unwrap();
+///
+/// let res = stylesheet.to_css(PrinterOptions { minify: true, ..Default::default() }).unwrap();
+/// assert_eq!(res.code, ".a:not(#b){color:red}");
+/// ```
+pub struct SelectorRenamer<'a> {
+  map: &'a RenameMap,
+}
+
+impl<'a> SelectorRenamer<'a> {
+  /// Creates a new renamer for the given mapping.
+  pub fn new(map: &'a RenameMap) -> Self {
+    SelectorRenamer { map }
+  }
+
+  fn rename_selector(&self, selector: &mut Selector) {
+    for component in selector.iter_mut_raw_match_order() {
+      match component {
+        Component::Class(name) => {
+          if let Some(renamed) = self.map.classes.get(name.0.as_ref()) {
+            name.0 = renamed.clone().into();
+          }
+        }
+        Component::ID(name) => {
+          if let Some(renamed) = self.map.ids.get(name.0.as_ref()) {
+            name.0 = renamed.clone().into();
+          }
+        }
+        Component::Negation(selectors)
+        | Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Has(selectors)
+        | Component::Any(_, selectors) => {
+          for selector in selectors.iter_mut() {
+            self.rename_selector(selector)
+          }
+        }
+        Component::Slotted(selector) | Component::Host(Some(selector)) => self.rename_selector(selector),
+        Component::NonTSPseudoClass(PseudoClass::Local { selector })
+        | Component::NonTSPseudoClass(PseudoClass::Global { selector }) => self.rename_selector(selector),
+        _ => {}
+      }
+    }
+  }
+}
+
+impl<'a, 'i> Visitor<'i> for SelectorRenamer<'a> {
+  type Error = std::convert::Infallible;
+
+  const TYPES: VisitTypes = visit_types!(SELECTORS | SUPPORTS_CONDITIONS);
+
+  fn visit_selector(&mut self, selector: &mut Selector<'i>) -> Result<(), Self::Error> {
+    self.rename_selector(selector);
+    Ok(())
+  }
+
+  fn visit_supports_condition(&mut self, condition: &mut SupportsCondition<'i>) -> Result<(), Self::Error> {
+    if let SupportsCondition::Selector(sel) = condition {
+      // The selector is stored unparsed, so round trip it in order to rename it.
+      // Invalid selectors are left untouched.
+      let source = sel.to_string();
+      if let Ok(mut selectors) = SelectorList::parse_string_with_options(&source, ParserOptions::default()) {
+        for selector in selectors.0.iter_mut() {
+          self.rename_selector(selector);
+        }
+        if let Ok(renamed) = selectors.to_css_string(PrinterOptions::default()) {
+          *sel = renamed.into();
+        }
+      }
+      return Ok(());
+    }
+
+    condition.visit_children(self)
+  }
+}
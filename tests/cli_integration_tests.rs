@@ -248,6 +248,495 @@ fn nesting_option() -> Result<(), Box<dyn std::error::Error>> {
   Ok(())
 }
 
+#[test]
+fn scope_under_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      :root { --accent: red; }
+      .foo { color: var(--accent); }
+      @media (min-width: 500px) {
+        body .bar { color: blue; }
+      }
+      @keyframes fade { from { opacity: 0 } }
+    "#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--scope-under").arg(".my-widget");
+  cmd.assert().success().stdout(predicate::str::contains(
+    ".my-widget{--accent:red}.my-widget .foo{color:var(--accent)}@media (min-width:500px){.my-widget .bar{color:#00f}}@keyframes fade{0%{opacity:0}}",
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn rename_map_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .button:not(.disabled) { color: red; }
+      #main .button { color: blue; }
+      @supports selector(.button:has(.icon)) {
+        .icon { color: green; }
+      }
+    "#,
+  )?;
+  let mapfile = assert_fs::NamedTempFile::new("map.json")?;
+  mapfile.write_str(r#"{"classes": {"button": "a", "disabled": "b", "icon": "c"}, "ids": {"main": "d"}}"#)?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--rename-map").arg(mapfile.path());
+  cmd.assert().success().stdout(predicate::str::contains(
+    ".a:not(.b){color:red}#d .a{color:#00f}@supports selector(.a:has(.c)){.c{color:green}}",
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn mangle_idents_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      @keyframes fade-in { from { opacity: 0 } }
+      .foo { animation: fade-in 1s; }
+    "#,
+  )?;
+  let mapfile = assert_fs::NamedTempFile::new("map.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--mangle-idents").arg(mapfile.path());
+  cmd.assert().success().stdout(predicate::str::contains(
+    "@keyframes a{0%{opacity:0}}.foo{animation:1s a}",
+  ));
+
+  let expected = serde_json::json!({
+    "keyframes": { "fade-in": "a" },
+    "counterStyles": {},
+    "containerNames": {},
+    "gridNames": {}
+  });
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(mapfile.path())?)?;
+  assert_eq!(expected, actual);
+
+  Ok(())
+}
+
+#[test]
+fn optimization_level_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { color: red !important; }
+      .bar { width: 0; }
+      .foo { width: 0; }
+    "#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("-O").arg("2");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}.bar,.foo{width:0}"));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red!important}.bar,.foo{width:0}"));
+
+  Ok(())
+}
+
+#[test]
+fn remove_obsolete_prefixes_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { -webkit-filter: blur(1px); filter: blur(1px); }
+    "#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--targets").arg("chrome 100");
+  cmd.arg("--remove-obsolete-prefixes");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{filter:blur(1px)}"));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--remove-obsolete-prefixes");
+  cmd.assert().failure();
+
+  Ok(())
+}
+
+#[test]
+fn remove_legacy_hacks_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { _zoom: 1; color: red; }
+      @-moz-document url-prefix() { .bar { color: green; } }
+    "#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--targets").arg("chrome 100");
+  cmd.arg("--remove-legacy-hacks");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}").and(predicate::str::contains(".bar").not()));
+
+  Ok(())
+}
+
+#[test]
+fn remove_unneeded_important_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { color: red !important; }
+      .bar { margin: 1px; margin-top: 0 !important; }
+    "#,
+  )?;
+  let report = assert_fs::NamedTempFile::new("report.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--remove-unneeded-important");
+  cmd.arg("--important-report").arg(report.path());
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}").and(predicate::str::contains("margin-top:0!important")));
+
+  let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(report.path())?)?;
+  assert_eq!(report["declarations"][0]["property"], "color");
+  assert_eq!(report["declarations"][0]["removable"], true);
+  assert_eq!(report["declarations"][1]["selectors"], ".bar");
+  assert_eq!(report["declarations"][1]["removable"], false);
+
+  Ok(())
+}
+
+#[test]
+fn ast_patch_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { color: red; }
+      .bar { color: green; }
+    "#,
+  )?;
+  let patch = assert_fs::NamedTempFile::new("patch.json")?;
+  patch.write_str(
+    r#"[
+      { "op": "remove", "path": "/rules/1" },
+      { "op": "replace", "path": "/rules/0/value/selectors/0/0/name", "value": "baz" }
+    ]"#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--ast-patch").arg(patch.path());
+  cmd.assert().success().stdout(predicate::str::contains(".baz{color:red}"));
+
+  patch.write_str(r#"[{ "op": "remove", "path": "/rules/2" }]"#)?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--ast-patch").arg(patch.path());
+  cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("path \"/rules/2\" does not exist"));
+
+  Ok(())
+}
+
+#[test]
+fn preserve_comments_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      /*! License */
+      .foo {
+        /* Comment */
+        color: red;
+      }
+    "#,
+  )?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--preserve-comments");
+  cmd.assert().success().stdout(predicate::str::contains(indoc! {r#"
+    /*! License */
+    .foo {
+      /* Comment */
+      color: red;
+    }
+  "#}));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--preserve-comments");
+  cmd.arg("--minify");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("/*! License */.foo{color:red}"));
+
+  Ok(())
+}
+
+#[test]
+fn purge_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo, .bar { color: red; }
+      .baz { color: green; }
+      .dynamic { color: blue; }
+    "#,
+  )?;
+  let content = assert_fs::NamedTempFile::new("index.html")?;
+  content.write_str(r#"<div class="foo"></div>"#)?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--purge").arg(content.path());
+  cmd.arg("--purge-safelist").arg("dynamic");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}.dynamic{color:#00f}"));
+
+  Ok(())
+}
+
+#[test]
+fn coverage_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  let source = ".foo { color: red; }\n.bar { color: green; }\n";
+  infile.write_str(source)?;
+  let coverage = assert_fs::NamedTempFile::new("coverage.json")?;
+  coverage.write_str(&serde_json::to_string(&serde_json::json!([{
+    "url": "http://localhost:8080/test.css",
+    "ranges": [{ "start": 0, "end": 20 }],
+    "text": source,
+  }]))?)?;
+  let report = assert_fs::NamedTempFile::new("report.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--coverage").arg(coverage.path());
+  cmd.arg("--coverage-report").arg(report.path());
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}").and(predicate::str::contains(".bar").not()));
+
+  let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(report.path())?)?;
+  assert_eq!(report[0]["selectors"], ".bar");
+  assert_eq!(report[0]["loc"]["line"], 1);
+
+  Ok(())
+}
+
+#[test]
+fn design_tokens_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      :root { --brand: #ff0000; }
+      .foo { color: red; padding: 4px; }
+    "#,
+  )?;
+  let tokensfile = assert_fs::NamedTempFile::new("tokens.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--design-tokens").arg(tokensfile.path());
+  cmd.assert().success();
+
+  let expected = serde_json::json!({
+    "color": {
+      "brand": {
+        "$type": "color",
+        "$value": "#ff0000",
+        "$extensions": { "dev.lightningcss": { "usages": 1, "customProperty": true } }
+      }
+    },
+    "spacing": {
+      "spacing-1": {
+        "$type": "dimension",
+        "$value": "4px",
+        "$extensions": { "dev.lightningcss": { "usages": 4, "customProperty": false } }
+      }
+    }
+  });
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(tokensfile.path())?)?;
+  assert_eq!(expected, actual);
+
+  Ok(())
+}
+
+#[test]
+fn emit_var_graph_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      :root { --a: var(--b); --b: var(--a); }
+      .foo { color: var(--missing); }
+    "#,
+  )?;
+  let graphfile = assert_fs::NamedTempFile::new("vars.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--emit-var-graph").arg(graphfile.path());
+  cmd.assert().success();
+
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(graphfile.path())?)?;
+  assert_eq!(actual["cycles"], serde_json::json!([["--a", "--b"]]));
+  assert_eq!(actual["undefined"], serde_json::json!(["--missing"]));
+  assert_eq!(
+    actual["variables"]["--a"]["definitions"][0]["dependencies"],
+    serde_json::json!(["--b"])
+  );
+  assert_eq!(actual["variables"]["--missing"]["references"][0]["property"], "color");
+
+  Ok(())
+}
+
+#[test]
+fn size_report_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { color: red; }
+      @media print { .bar { width: 0; } }
+    "#,
+  )?;
+  let reportfile = assert_fs::NamedTempFile::new("report.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--size-report").arg(reportfile.path());
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}@media print{.bar{width:0}}"))
+    .stderr(predicate::str::contains("@media print"));
+
+  let expected = serde_json::json!([
+    { "prelude": ".foo", "bytes": 15 },
+    { "prelude": "@media print", "bytes": 27 }
+  ]);
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(reportfile.path())?)?;
+  assert_eq!(expected, actual);
+
+  Ok(())
+}
+
+#[test]
+fn size_breakdown_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(
+    r#"
+      .foo { color: red; }
+      @media print { .bar { width: 0; } }
+    "#,
+  )?;
+  let reportfile = assert_fs::NamedTempFile::new("breakdown.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--minify");
+  cmd.arg("--size-breakdown").arg(reportfile.path());
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{color:red}@media print{.bar{width:0}}"))
+    .stderr(predicate::str::contains("Media query"))
+    .stderr(predicate::str::contains("Property"));
+
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(reportfile.path())?)?;
+  assert_eq!(
+    actual["mediaQueries"],
+    serde_json::json!([{ "name": "print", "bytes": 27 }])
+  );
+  assert_eq!(actual["layers"], serde_json::json!([]));
+  assert_eq!(actual["sources"][0]["bytes"], 42);
+  assert_eq!(
+    actual["properties"],
+    serde_json::json!([{ "name": "color", "bytes": 9 }, { "name": "width", "bytes": 7 }])
+  );
+
+  Ok(())
+}
+
+#[test]
+fn compat_report_option() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(".foo:has(.bar) { color: red; }\n.baz { display: flex; field-sizing: content; }\n")?;
+  let reportfile = assert_fs::NamedTempFile::new("report.json")?;
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--targets").arg("chrome 100");
+  cmd.arg("--compat-report").arg(reportfile.path());
+  cmd
+    .assert()
+    .success()
+    .stderr(predicate::str::contains(
+      ":1:1: css-has is not supported by chrome 100: .foo:has(.bar)",
+    ))
+    .stderr(predicate::str::contains(
+      ":2:1: field-sizing is not supported by chrome 100: field-sizing: content",
+    ));
+
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(reportfile.path())?)?;
+  assert_eq!(actual.as_array().unwrap().len(), 2);
+  assert_eq!(actual[0]["feature"], "css-has");
+  assert_eq!(actual[1]["loc"]["line"], 1);
+  assert_eq!(actual[1]["browsers"], serde_json::json!(["chrome 100"]));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--compat-report");
+  cmd.assert().failure();
+
+  Ok(())
+}
+
 #[test]
 fn css_modules_infer_output_file() -> Result<(), Box<dyn std::error::Error>> {
   let (input, _, exports) = css_module_test_vals();
@@ -1,5 +1,7 @@
 #[cfg(feature = "serde")]
-use lightningcss::stylesheet::{ParserOptions, StyleSheet};
+use lightningcss::schema::AST_VERSION;
+#[cfg(feature = "serde")]
+use lightningcss::stylesheet::{ParserOptions, PrinterOptions, StyleSheet};
 
 #[cfg(feature = "serde")]
 #[test]
@@ -18,3 +20,112 @@ fn test_serde() {
   let deserialized: StyleSheet = serde_json::from_str(&json).unwrap();
   assert_eq!(&deserialized.rules, &stylesheet.rules);
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_ast_version() {
+  let stylesheet = StyleSheet::parse(".foo { color: red }", ParserOptions::default()).unwrap();
+  let json: serde_json::Value = serde_json::to_value(&stylesheet).unwrap();
+  assert_eq!(json["version"], AST_VERSION);
+
+  let mut json = json;
+  json.as_object_mut().unwrap().remove("version");
+  let json = serde_json::to_string(&json).unwrap();
+  let deserialized: StyleSheet = serde_json::from_str(&json).unwrap();
+  assert_eq!(&deserialized.rules, &stylesheet.rules);
+
+  let json = json.replacen('{', &format!("{{\"version\":{},", AST_VERSION + 1), 1);
+  let err = serde_json::from_str::<StyleSheet>(&json).unwrap_err();
+  assert!(err.to_string().contains("unsupported AST version"));
+}
+
+/// Serialized style sheets from each supported AST version must keep deserializing to the same CSS.
+#[cfg(feature = "serde")]
+#[test]
+fn test_ast_compatibility() {
+  fn compat_test(json: &str, expected: &str) {
+    let stylesheet: StyleSheet = serde_json::from_str(json).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+  }
+
+  compat_test(
+    include_str!("testdata/ast/v1.json"),
+    ".foo{color:red;width:10px!important}#bar .baz{color:#00f}",
+  );
+}
+
+#[cfg(feature = "json_patch")]
+#[test]
+fn test_json_patch() {
+  use lightningcss::json_patch::{JsonPatch, JsonPatchError};
+  use serde::Deserialize;
+
+  fn patch_test(source: &str, patch: &str, expected: &str) {
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let patch: JsonPatch = serde_json::from_str(patch).unwrap();
+    let json = stylesheet.to_patched_json(&patch).unwrap();
+    let patched: StyleSheet = StyleSheet::deserialize(&json).unwrap();
+    let res = patched
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+  }
+
+  patch_test(
+    ".foo { color: red } .bar { color: green }",
+    r#"[
+      { "op": "test", "path": "/rules/0/value/selectors/0/0/name", "value": "foo" },
+      { "op": "copy", "from": "/rules/0", "path": "/rules/-" },
+      { "op": "remove", "path": "/rules/1" },
+      { "op": "replace", "path": "/rules/1/value/selectors/0/0/name", "value": "baz" }
+    ]"#,
+    ".foo{color:red}.baz{color:red}",
+  );
+  patch_test(
+    ".foo { color: red; width: 0 }",
+    r#"[
+      { "op": "move", "from": "/rules/0/value/declarations/declarations/0", "path": "/rules/0/value/declarations/importantDeclarations/0" },
+      { "op": "add", "path": "/rules/0/value/declarations/declarations/0", "value": { "property": "height", "raw": "1px" } }
+    ]"#,
+    ".foo{height:1px;width:0;color:red!important}",
+  );
+
+  let mut value = serde_json::json!({ "a": [1, 2], "b~c": { "d/e": true } });
+  let patch: JsonPatch = serde_json::from_str(
+    r#"[
+      { "op": "add", "path": "/a/1", "value": 3 },
+      { "op": "test", "path": "/b~0c/d~1e", "value": true },
+      { "op": "move", "from": "/a", "path": "/f" }
+    ]"#,
+  )
+  .unwrap();
+  patch.apply(&mut value).unwrap();
+  assert_eq!(value, serde_json::json!({ "f": [1, 3, 2], "b~c": { "d/e": true } }));
+
+  let patch: JsonPatch = serde_json::from_str(
+    r#"[
+      { "op": "remove", "path": "/f/0" },
+      { "op": "test", "path": "/f/0", "value": 2 }
+    ]"#,
+  )
+  .unwrap();
+  assert!(matches!(patch.apply(&mut value), Err(JsonPatchError::TestFailed(..))));
+  assert_eq!(value, serde_json::json!({ "f": [1, 3, 2], "b~c": { "d/e": true } }));
+
+  let patch: JsonPatch = serde_json::from_str(r#"[{ "op": "remove", "path": "/f/01" }]"#).unwrap();
+  assert!(matches!(patch.apply(&mut value), Err(JsonPatchError::PathNotFound(..))));
+  let patch: JsonPatch = serde_json::from_str(r#"[{ "op": "move", "from": "/f", "path": "/f/0" }]"#).unwrap();
+  assert!(matches!(
+    patch.apply(&mut value),
+    Err(JsonPatchError::MoveIntoChild(..))
+  ));
+}